@@ -1,27 +1,66 @@
 //! Etherscan API client.
 //! 
-//! Currently only covers the [`getabi`](https://docs.etherscan.io/api-endpoints/contracts#get-contract-abi-for-verified-contract-source-codes)
-//! endpoint because the [`getsourcecode`](https://docs.etherscan.io/api-endpoints/contracts#get-contract-abi-for-verified-contract-source-codes) 
+//! Signatures are only extracted from the [`getabi`](https://docs.etherscan.io/api-endpoints/contracts#get-contract-abi-for-verified-contract-source-codes)
+//! endpoint because the source code returned by the [`getsourcecode`](https://docs.etherscan.io/api-endpoints/contracts#get-contract-source-code-for-verified-contract-source-codes) 
 //! endpoints is a fucking mess which I really don't want to implemente even though it would yield signatures
-//!  with a `private` / `internal` visibility which the scraper can find.
+//!  with a `private` / `internal` visibility which the scraper can find. The latter endpoint is however used
+//! to retrieve the raw source code and compiler settings of a contract, see
+//! [`EtherscanClient::get_source_code`].
 
+use crate::api::ratelimit::TokenBucket;
 use crate::config::Config;
 use crate::error::Error;
+use crate::model::EtherscanCompilerSettings;
 use crate::model::EtherscanContract;
+use crate::model::EtherscanContractCreation;
+use crate::model::EtherscanSourceCode;
+use crate::parser::PARSER_VERSION;
 use chrono::Utc;
+use lazy_static::lazy_static;
 use select::document::Document;
 use select::predicate::Name;
 use select::predicate::Class;
 use select::predicate::Predicate;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
 
 use super::EtherscanResponseHandler;
 use super::GenericResponseHandler;
 use super::RequestHandler;
+use super::RequestPolicy;
+
+/// Base URL of the Etherscan API, see [`Config::etherscan_base_url`].
+pub const ETHERSCAN_BASE_URL: &str = "https://api.etherscan.io/api";
+
+/// Chain id of Ethereum mainnet, i.e. the chain of all contracts listed on <https://etherscan.io>.
+pub const ETHEREUM_MAINNET_CHAIN_ID: i32 = 1;
+
+/// Number of API calls Etherscan allows per second and API key.
+const ETHERSCAN_REQUESTS_PER_SECOND: f64 = 5.0;
+
+lazy_static! {
+    // Etherscan enforces its ratelimit per API key rather than per client, as such all clients sharing the
+    // same key (e.g. multiple scraper workers) also have to share the same token bucket. Keys of different
+    // Etherscan-family explorers are independent of each other, hence the bucket is keyed by base URL too.
+    static ref RATELIMITERS: Mutex<HashMap<(String, String), Arc<TokenBucket>>> = Mutex::new(HashMap::new());
+}
 
 pub struct EtherscanClient {
     request_handler: RequestHandler,
+    base_url: String,
     token: String,
+    ratelimiter: Arc<TokenBucket>,
+}
+
+/// Builder for [`EtherscanClient`]s not relying on `.env`, e.g. when embedding this crate into other
+/// programs.
+pub struct EtherscanClientBuilder {
+    base_url: String,
+    token: String,
+    policy: RequestPolicy,
+    proxy: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -29,26 +68,148 @@ struct Page {
     result: String,
 }
 
+#[derive(Deserialize)]
+struct PageProxy {
+    result: String,
+}
+
+#[derive(Deserialize)]
+struct PageSourceCode {
+    result: Vec<SourceCode>,
+}
+
+// Etherscan returns all values as strings, e.g. `"OptimizationUsed": "1"`
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SourceCode {
+    source_code: String,
+    compiler_version: String,
+    optimization_used: String,
+    runs: String,
+
+    #[serde(rename = "EVMVersion")]
+    evm_version: String,
+    license_type: String,
+}
+
+#[derive(Deserialize)]
+struct PageContractCreation {
+    result: Vec<ContractCreation>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContractCreation {
+    contract_creator: String,
+    tx_hash: String,
+    block_number: Option<String>,
+}
+
 impl EtherscanClient {
     /// Returns a new Etherscan API client.
     pub fn new() -> Result<Self, Error> {
-        Ok(EtherscanClient {
-            request_handler: RequestHandler::new(),
-            token: Config::new()?.token_etherscan,
-        })
+        let config = Config::new()?;
+        let request_handler = RequestHandler::new()?;
+        let (base_url, token) = (config.etherscan_base_url, config.token_etherscan);
+
+        Ok(EtherscanClient::with_request_handler(request_handler, base_url, token))
+    }
+
+    /// Returns a builder for a client of the Etherscan (or Etherscan-family explorer, e.g.
+    /// `https://api.bscscan.com/api`) API at `base_url` using `token` instead of the ones configured in
+    /// `.env`.
+    pub fn builder(base_url: &str, token: &str) -> EtherscanClientBuilder {
+        EtherscanClientBuilder {
+            base_url: base_url.to_string(),
+            token: token.to_string(),
+            policy: RequestPolicy::default(),
+            proxy: None,
+        }
+    }
+
+    fn with_request_handler(request_handler: RequestHandler, base_url: String, token: String) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let ratelimiter = RATELIMITERS
+            .lock()
+            .unwrap()
+            .entry((base_url.clone(), token.clone()))
+            .or_insert_with(|| Arc::new(TokenBucket::new(1, ETHERSCAN_REQUESTS_PER_SECOND)))
+            .clone();
+
+        EtherscanClient {
+            request_handler,
+            base_url,
+            token,
+            ratelimiter,
+        }
+    }
+
+    /// Replaces the timeouts and retry policy of this client, which default to the configured ones.
+    pub fn set_request_policy(&mut self, policy: RequestPolicy) -> Result<(), Error> {
+        self.request_handler.set_policy(policy)
     }
 
     /// Returns the JSON response returned by the [`getabi`](https://docs.etherscan.io/api-endpoints/contracts#get-contract-abi-for-verified-contract-source-codes)
     /// endpoint.
     pub fn get_abi(&self, address: &str) -> Result<String, Error> {
         let url = format!(
-            "https://api.etherscan.io/api?module=contract&action=getabi&address={}&apikey={}",
-            address, self.token
+            "{}?module=contract&action=getabi&address={}&apikey={}",
+            self.base_url, address, self.token
         );
 
+        self.ratelimiter.acquire();
         Ok(self.request_handler.execute_deser::<EtherscanResponseHandler, Page>(&url)?.result)
     }
 
+    /// Returns the contract creation metadata returned by the [`getcontractcreation`](https://docs.etherscan.io/api-endpoints/contracts#get-contract-creator-and-creation-tx-hash)
+    /// endpoint, `None` if Etherscan has no data for the address.
+    pub fn get_contract_creation(&self, address: &str) -> Result<Option<EtherscanContractCreation>, Error> {
+        let url = format!(
+            "{}?module=contract&action=getcontractcreation&contractaddresses={}&apikey={}",
+            self.base_url, address, self.token
+        );
+
+        self.ratelimiter.acquire();
+        let page = self.request_handler.execute_deser::<EtherscanResponseHandler, PageContractCreation>(&url);
+        let page = match page {
+            Ok(page) => page,
+            Err(Error::EtherscanNoData(_)) => return Ok(None),
+            Err(why) => return Err(why),
+        };
+
+        Ok(page.result.into_iter().next().map(|creation| EtherscanContractCreation {
+            creator_address: creation.contract_creator,
+            tx_hash: creation.tx_hash,
+            block: creation.block_number.and_then(|block| block.parse().ok()),
+        }))
+    }
+
+    /// Returns the source code and compiler settings returned by the [`getsourcecode`](https://docs.etherscan.io/api-endpoints/contracts#get-contract-source-code-for-verified-contract-source-codes)
+    /// endpoint, `None` if the contracts source code isn't verified.
+    pub fn get_source_code(&self, address: &str) -> Result<Option<EtherscanSourceCode>, Error> {
+        let url = format!(
+            "{}?module=contract&action=getsourcecode&address={}&apikey={}",
+            self.base_url, address, self.token
+        );
+
+        self.ratelimiter.acquire();
+        let page = self.request_handler.execute_deser::<EtherscanResponseHandler, PageSourceCode>(&url)?;
+
+        Ok(page.result.into_iter().next().and_then(SourceCode::into_source_code))
+    }
+
+    /// Returns the hex encoded code of the given address as returned by the [`eth_getCode`](https://docs.etherscan.io/api-endpoints/geth-parity-proxy#eth_getcode)
+    /// proxy endpoint, i.e. `0x` if the address has no code (anymore).
+    pub fn get_code(&self, address: &str) -> Result<String, Error> {
+        let url = format!(
+            "{}?module=proxy&action=eth_getCode&address={}&tag=latest&apikey={}",
+            self.base_url, address, self.token
+        );
+
+        self.ratelimiter.acquire();
+        Ok(self.request_handler.execute_deser::<EtherscanResponseHandler, PageProxy>(&url)?.result)
+    }
+
     /// Returns a list of [`EtherscanContract`] scraped from the <https://etherscan.io/contractsVerified> 
     /// page. <br/><b>Note</b>: Not part of the official Etherscan API. 
     pub fn get_verified_contracts(&self) -> Result<Vec<EtherscanContract>, Error> {
@@ -73,6 +234,21 @@ impl EtherscanClient {
                     url: format!("https://etherscan.io/address/{}", row_column[0].trim()).to_string(),
                     scraped_at: None,
                     added_at: Utc::now(),
+                    retry_count: 0,
+                    next_retry_at: None,
+                    last_error: None,
+                    is_unverified: false,
+                    creator_address: None,
+                    creation_tx_hash: None,
+                    creation_block: None,
+                    is_destroyed: false,
+                    code_checked_at: None,
+                    parser_version: PARSER_VERSION,
+                    chain_id: ETHEREUM_MAINNET_CHAIN_ID,
+                    optimization_used: None,
+                    optimization_runs: None,
+                    evm_version: None,
+                    license: None,
                 });
             }
         }
@@ -81,9 +257,79 @@ impl EtherscanClient {
     }
 }
 
+impl SourceCode {
+    fn into_source_code(self) -> Option<EtherscanSourceCode> {
+        // Unverified contracts are returned with empty values rather than an error
+        if self.compiler_version.is_empty() {
+            return None;
+        }
+
+        Some(EtherscanSourceCode {
+            content: self.source_code,
+            settings: EtherscanCompilerSettings {
+                optimization_used: self.optimization_used == "1",
+                optimization_runs: self.runs.parse().unwrap_or_default(),
+                evm_version: self.evm_version,
+                license: self.license_type,
+            },
+        })
+    }
+}
+
+impl EtherscanClientBuilder {
+    /// Uses the given timeouts and retry policy, defaults to [`RequestPolicy::default`].
+    pub fn request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Routes all requests through `proxy`, e.g. `http://proxy.internal:3128`.
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_string());
+        self
+    }
+
+    /// Returns the Etherscan API client.
+    pub fn build(self) -> Result<EtherscanClient, Error> {
+        let request_handler = RequestHandler::with_options(self.policy, self.proxy)?;
+        Ok(EtherscanClient::with_request_handler(request_handler, self.base_url, self.token))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::api::etherscan::EtherscanClient;
+    use crate::api::RequestPolicy;
+    use crate::error::Error;
+    use httpmock::prelude::*;
+    use serde_json::json;
+
+    /// Returns a client of the mock server failing fast, i.e. without any retries.
+    fn client(server: &MockServer) -> EtherscanClient {
+        let policy = RequestPolicy {
+            max_retries: 1,
+            ..RequestPolicy::default()
+        };
+
+        EtherscanClient::builder(&server.url("/api"), "token").request_policy(policy).build().unwrap()
+    }
+
+    #[test]
+    fn builder_uses_base_url() {
+        let policy = RequestPolicy {
+            max_retries: 1,
+            ..RequestPolicy::default()
+        };
+
+        // Nothing listens on port 1, hence the request fails right away without any retries
+        let esc = EtherscanClient::builder("http://127.0.0.1:1/api/", "token").request_policy(policy).build();
+        match esc.unwrap().get_abi("0x4a25e19e0765ef63d7196728ac3c3f3119199555") {
+            Err(Error::HttpRequest(url, _)) => {
+                assert!(url.starts_with("http://127.0.0.1:1/api?module=contract&action=getabi"))
+            }
+            _ => panic!("Expected a failed HTTP request"),
+        }
+    }
 
     #[test]
     fn get_abi() {
@@ -93,6 +339,94 @@ mod test {
         );
     }
 
+    #[test]
+    fn get_contract_creation() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api").query_param("action", "getcontractcreation").query_param(
+                "contractaddresses",
+                "0x4a25e19e0765ef63d7196728ac3c3f3119199555",
+            );
+            then.status(200).json_body(json!({
+                "status": "1",
+                "message": "OK",
+                "result": [{
+                    "contractAddress": "0x4a25e19e0765ef63d7196728ac3c3f3119199555",
+                    "contractCreator": "0x9c8a0a1d2ae9ba6a5e1fbc2b5a4c4a0c6d8e1f2a",
+                    "txHash": "0x1b2c3d4e5f60718293a4b5c6d7e8f90112233445566778899aabbccddeeff001",
+                    "blockNumber": "14000000"
+                }]
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/api").query_param("action", "getcontractcreation").query_param(
+                "contractaddresses",
+                "0x0000000000000000000000000000000000000000",
+            );
+            then.status(200).json_body(json!({ "status": "0", "message": "No data found", "result": [] }));
+        });
+
+        let esc = client(&server);
+        let creation = esc.get_contract_creation("0x4a25e19e0765ef63d7196728ac3c3f3119199555").unwrap();
+        let creation = creation.unwrap();
+        assert_eq!(creation.creator_address, "0x9c8a0a1d2ae9ba6a5e1fbc2b5a4c4a0c6d8e1f2a");
+        assert_eq!(creation.tx_hash, "0x1b2c3d4e5f60718293a4b5c6d7e8f90112233445566778899aabbccddeeff001");
+        assert_eq!(creation.block, Some(14000000));
+
+        assert!(esc.get_contract_creation("0x0000000000000000000000000000000000000000").unwrap().is_none());
+    }
+
+    #[test]
+    fn get_source_code() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api").query_param("action", "getsourcecode");
+            then.status(200).json_body(json!({
+                "status": "1",
+                "message": "OK",
+                "result": [{
+                    "SourceCode": "pragma solidity ^0.8.0; contract Token {}",
+                    "CompilerVersion": "v0.8.4+commit.c7e474f2",
+                    "OptimizationUsed": "1",
+                    "Runs": "200",
+                    "EVMVersion": "Default",
+                    "LicenseType": "MIT"
+                }]
+            }));
+        });
+
+        let source = client(&server).get_source_code("0x4a25e19e0765ef63d7196728ac3c3f3119199555").unwrap();
+        let source = source.unwrap();
+        assert!(source.content.contains("pragma solidity"));
+        assert!(source.settings.optimization_used);
+        assert_eq!(source.settings.optimization_runs, 200);
+        assert_eq!(source.settings.evm_version, "Default");
+        assert_eq!(source.settings.license, "MIT");
+    }
+
+    #[test]
+    fn get_code() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api").query_param("action", "eth_getCode").query_param(
+                "address",
+                "0x4a25e19e0765ef63d7196728ac3c3f3119199555",
+            );
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x6080604052" }));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/api").query_param("action", "eth_getCode").query_param(
+                "address",
+                "0x0000000000000000000000000000000000000000",
+            );
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": "0x" }));
+        });
+
+        let esc = client(&server);
+        assert_eq!(esc.get_code("0x4a25e19e0765ef63d7196728ac3c3f3119199555").unwrap(), "0x6080604052");
+        assert_eq!(esc.get_code("0x0000000000000000000000000000000000000000").unwrap(), "0x");
+    }
+
     #[test]
     #[rustfmt::skip]
     fn get_verified_contracts() {
@@ -10,6 +10,10 @@ use serde::Deserialize;
 
 use super::GenericResponseHandler;
 use super::RequestHandler;
+use super::RequestPolicy;
+
+/// Base URL of the 4Byte API, see [`FourbyteClient::with_options`].
+pub const FOURBYTE_BASE_URL: &str = "https://www.4byte.directory";
 
 pub struct FourbyteClient {
     request_handler: RequestHandler,
@@ -34,15 +38,33 @@ struct FourbyteSignature {
 
 impl FourbyteClient {
     /// Returns a new 4Byte API client.
-    pub fn new() -> Self {
+    pub fn new() -> Result<Self, Error> {
+        Ok(FourbyteClient::with_request_handler(RequestHandler::new()?, FOURBYTE_BASE_URL))
+    }
+
+    /// Returns a new client of the 4Byte API at `base_url` (e.g. a mock server) with the given timeouts and
+    /// retry policy, routing all requests through `proxy` if present. Unlike [`FourbyteClient::new`] this
+    /// doesn't rely on `.env`.
+    pub fn with_options(base_url: &str, policy: RequestPolicy, proxy: Option<String>) -> Result<Self, Error> {
+        Ok(FourbyteClient::with_request_handler(RequestHandler::with_options(policy, proxy)?, base_url))
+    }
+
+    fn with_request_handler(request_handler: RequestHandler, base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+
         FourbyteClient {
-            request_handler: RequestHandler::new(),
+            request_handler,
 
-            page_next_function: Some("https://www.4byte.directory/api/v1/signatures/?page=1".to_string()),
-            page_next_event: Some("https://www.4byte.directory/api/v1/event-signatures/?page=1".to_string()),
+            page_next_function: Some(format!("{base_url}/api/v1/signatures/?page=1")),
+            page_next_event: Some(format!("{base_url}/api/v1/event-signatures/?page=1")),
         }
     }
 
+    /// Replaces the timeouts and retry policy of this client, which default to the configured ones.
+    pub fn set_request_policy(&mut self, policy: RequestPolicy) -> Result<(), Error> {
+        self.request_handler.set_policy(policy)
+    }
+
     /// Returns the next function signature page, where the page index auto-increments internally with each
     /// function call.
     pub fn page_function_signature(&mut self) -> Result<Option<Vec<SignatureWithMetadata>>, Error> {
@@ -93,6 +115,8 @@ mod tests {
     use crate::api::fourbyte::FourbyteClient;
     use crate::api::fourbyte::Page;
     use crate::api::GenericResponseHandler;
+    use crate::api::RequestPolicy;
+    use httpmock::prelude::*;
 
     fn page_signature_test(functions_endpoint: bool) {
         let url_page_01 = match functions_endpoint {
@@ -109,7 +133,7 @@ mod tests {
         let html_content_page01 = http_client.get(url_page_01).send().unwrap().text().unwrap();
         let html_content_page02 = http_client.get(url_page_02).send().unwrap().text().unwrap();
 
-        let mut fbc = FourbyteClient::new();
+        let mut fbc = FourbyteClient::new().unwrap();
         let fbc_signatures_page01 = match functions_endpoint {
             true => fbc.page_function_signature().unwrap().unwrap(),
             false => fbc.page_event_signature().unwrap().unwrap(),
@@ -139,6 +163,32 @@ mod tests {
         page_signature_test(false);
     }
 
+    #[test]
+    fn page_function_signatures_mocked() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/api/v1/signatures/").query_param("page", "1");
+            then.status(200).json_body(serde_json::json!({
+                "count": 2,
+                "next": server.url("/api/v1/signatures/?page=2"),
+                "results": [{ "text_signature": "transfer(address,uint256)" }]
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/api/v1/signatures/").query_param("page", "2");
+            then.status(200).json_body(serde_json::json!({
+                "count": 2,
+                "next": null,
+                "results": [{ "text_signature": "approve(address,uint256)" }]
+            }));
+        });
+
+        let mut fbc = FourbyteClient::with_options(&server.base_url(), RequestPolicy::default(), None).unwrap();
+        assert_eq!(fbc.page_function_signature().unwrap().unwrap()[0].text, "transfer(address,uint256)");
+        assert_eq!(fbc.page_function_signature().unwrap().unwrap()[0].text, "approve(address,uint256)");
+        assert!(fbc.page_function_signature().unwrap().is_none());
+    }
+
     fn page_count(signature_count: usize) -> usize {
         // We can calculate the total number of pages by checking if count / 100 has a remainder, if so
         // we have to round up otherwise it's OK. For example https://www.4byte.directory/api/v1/event-signatures/?page=1
@@ -153,7 +203,7 @@ mod tests {
 
     #[test]
     fn page_event_signatures_none() {
-        let mut fbc = FourbyteClient::new();
+        let mut fbc = FourbyteClient::new().unwrap();
         let page = fbc
             .request_handler
             .execute_deser::<GenericResponseHandler, Page>(fbc.page_next_event.as_ref().unwrap().as_ref())
@@ -170,7 +220,7 @@ mod tests {
 
     #[test]
     fn page_function_signatures_none() {
-        let mut fbc = FourbyteClient::new();
+        let mut fbc = FourbyteClient::new().unwrap();
         let page = fbc
             .request_handler
             .execute_deser::<GenericResponseHandler, Page>(fbc.page_next_function.as_ref().unwrap().as_ref())
@@ -3,44 +3,202 @@
 //! Currently covers only the necessary `/user`, `/repositories` and `/search` (sub-)endpoints needed for
 //! crawling and finding Solidity repositories.
 
+pub(crate) mod budget;
+mod cache;
 pub mod handler;
 mod page;
 pub(crate) mod token;
 
+use super::GithubGraphqlResponseHandler;
 use super::GithubResponseHandler;
 use super::RequestHandler;
+use super::RequestPolicy;
 use crate::api::github::handler::repositories::RepoHandler;
 use crate::api::github::handler::search::SearchHandler;
+use crate::api::github::cache::EtagCache;
+use crate::api::github::handler::graphql::GraphqlHandler;
 use crate::api::github::handler::user::UserHandler;
+use crate::api::github::token::TokenManager;
+use crate::config::Config;
+use crate::config::DEFAULT_GITHUB_API_VERSION;
+use crate::config::DEFAULT_GITHUB_BUDGET_RESERVED_CRAWLER;
+use crate::config::DEFAULT_GITHUB_BUDGET_RESERVED_SCRAPER;
+use crate::config::DEFAULT_GITHUB_MEDIA_TYPE;
 use crate::error::Error;
 use reqwest::blocking::Response;
 use reqwest::header;
 use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
 use reqwest::Url;
+use serde::de::DeserializeOwned;
+use std::cell::RefCell;
 
-const GITHUB_BASE_URL: &str = "https://api.github.com";
-const GITHUB_RATELIMIT_URL: &str = "https://api.github.com/rate_limit";
+/// Base URL of the GitHub API, see [`GithubClientBuilder::base_url`].
+pub const GITHUB_BASE_URL: &str = "https://api.github.com";
 
-/// See https://docs.github.com/en/rest/overview/resources-in-the-rest-api#current-version
-const HEADER_API_VERSION: &str = "application/vnd.github.v3+json";
+/// Maximum number of items per page GitHub allows for listings, see [`Pagination::per_page`].
+pub const GITHUB_MAX_PER_PAGE: usize = 100;
 
-/// See https://docs.github.com/en/rest/overview/resources-in-the-rest-api#user-agent-required
-const HEADER_USER_AGENT: &str = "Etherface";
+/// Maximum number of responses held by the ETag cache.
+const ETAG_CACHE_CAPACITY: usize = 1000;
+
+pub use budget::GithubConsumer;
+pub use page::Pages;
+pub use page::Pagination;
+pub use token::TokenQuota;
 
 pub struct GithubClient {
     request_handler: RequestHandler,
+    etag_cache: RefCell<EtagCache>,
+    base_url: String,
+    pagination: Pagination,
+}
+
+/// Builder for [`GithubClient`]s not relying on `.env`, e.g. when embedding this crate into other programs.
+pub struct GithubClientBuilder {
+    base_url: String,
+    tokens: Vec<String>,
+    consumer: GithubConsumer,
+    reserved: [u64; 2],
+    policy: RequestPolicy,
+    proxy: Option<String>,
+    pagination: Pagination,
+    api_version: String,
+    media_type: String,
+}
+
+/// Headers and body of a GitHub response, either freshly received or served from the ETag cache.
+#[derive(Clone)]
+pub(crate) struct GithubResponse {
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+impl GithubResponse {
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, Error> {
+        Ok(serde_json::from_str(&self.body)?)
+    }
 }
 
 impl GithubClient {
-    /// Returns a new GitHub API client.
+    /// Returns a new GitHub API client, accounting its requests to the [`GithubConsumer::Crawler`] budget.
     pub fn new() -> Result<Self, Error> {
-        let mut headers = HeaderMap::new();
-        headers.insert(header::ACCEPT, HEADER_API_VERSION.parse().unwrap());
-        headers.insert(header::USER_AGENT, HEADER_USER_AGENT.parse().unwrap());
+        GithubClient::with_consumer(GithubConsumer::Crawler)
+    }
+
+    /// Returns a new GitHub API client, accounting its requests to the budget of `consumer`.
+    pub fn with_consumer(consumer: GithubConsumer) -> Result<Self, Error> {
+        let request_handler = RequestHandler::new_github(consumer)?;
+        let mut ghc = GithubClient::with_request_handler(request_handler, GITHUB_BASE_URL);
+        ghc.pagination = Pagination::from_config(&Config::new()?);
+
+        Ok(ghc)
+    }
+
+    /// Returns a builder for a GitHub API client using `tokens` instead of the ones configured in `.env`.
+    pub fn builder(tokens: Vec<String>) -> GithubClientBuilder {
+        GithubClientBuilder {
+            base_url: GITHUB_BASE_URL.to_string(),
+            tokens,
+            consumer: GithubConsumer::Crawler,
+            reserved: [DEFAULT_GITHUB_BUDGET_RESERVED_CRAWLER, DEFAULT_GITHUB_BUDGET_RESERVED_SCRAPER],
+            policy: RequestPolicy::default(),
+            proxy: None,
+            pagination: Pagination::default(),
+            api_version: DEFAULT_GITHUB_API_VERSION.to_string(),
+            media_type: DEFAULT_GITHUB_MEDIA_TYPE.to_string(),
+        }
+    }
+
+    fn with_request_handler(request_handler: RequestHandler, base_url: &str) -> Self {
+        GithubClient {
+            request_handler,
+            etag_cache: RefCell::new(EtagCache::new(ETAG_CACHE_CAPACITY)),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            pagination: Pagination::default(),
+        }
+    }
+
+    /// Replaces the timeouts and retry policy of this client, which default to the configured ones.
+    pub fn set_request_policy(&mut self, policy: RequestPolicy) -> Result<(), Error> {
+        self.request_handler.set_policy(policy)
+    }
+
+    /// Replaces the pagination settings of this client, which default to the configured ones.
+    pub fn set_pagination(&mut self, pagination: Pagination) {
+        self.pagination = pagination;
+    }
+}
+
+impl GithubClientBuilder {
+    /// Sends all requests to the API at `base_url` (e.g. a GitHub Enterprise instance or a mock server),
+    /// defaults to [`GITHUB_BASE_URL`].
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+
+    /// Accounts the clients requests to the budget of `consumer`, defaults to [`GithubConsumer::Crawler`].
+    pub fn consumer(mut self, consumer: GithubConsumer) -> Self {
+        self.consumer = consumer;
+        self
+    }
+
+    /// Reserves the given percentages of the hourly GitHub API budget for the crawler and scraper, see
+    /// [`Config::github_budget_reserved_crawler`](crate::config::Config::github_budget_reserved_crawler).
+    pub fn budget_reserved(mut self, crawler: u64, scraper: u64) -> Self {
+        self.reserved = [crawler, scraper];
+        self
+    }
+
+    /// Uses the given timeouts and retry policy, defaults to [`RequestPolicy::default`].
+    pub fn request_policy(mut self, policy: RequestPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Uses the given pagination settings, defaults to [`Pagination::default`].
+    pub fn pagination(mut self, pagination: Pagination) -> Self {
+        self.pagination = pagination;
+        self
+    }
+
+    /// Pins all requests to the dated `api_version`, defaults to [`DEFAULT_GITHUB_API_VERSION`].
+    pub fn api_version(mut self, api_version: &str) -> Self {
+        self.api_version = api_version.to_string();
+        self
+    }
 
-        Ok(GithubClient {
-            request_handler: RequestHandler::new_github()?,
-        })
+    /// Accepts responses of the given `media_type`, defaults to [`DEFAULT_GITHUB_MEDIA_TYPE`].
+    pub fn media_type(mut self, media_type: &str) -> Self {
+        self.media_type = media_type.to_string();
+        self
+    }
+
+    /// Routes all requests through `proxy`, e.g. `http://proxy.internal:3128`.
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_string());
+        self
+    }
+
+    /// Returns the GitHub API client, failing if none of the tokens are valid.
+    pub fn build(self) -> Result<GithubClient, Error> {
+        let token_manager = TokenManager::with_tokens(
+            self.tokens,
+            self.consumer,
+            self.reserved,
+            RequestHandler::with_options(self.policy, self.proxy.clone())?
+                .with_github_api(&self.api_version, &self.media_type)?,
+            &self.base_url,
+        )?;
+
+        let request_handler = RequestHandler::with_options(self.policy, self.proxy)?
+            .with_github_api(&self.api_version, &self.media_type)?;
+        let request_handler = request_handler.with_token_manager(token_manager);
+
+        let mut ghc = GithubClient::with_request_handler(request_handler, &self.base_url);
+        ghc.pagination = self.pagination;
+        Ok(ghc)
     }
 }
 
@@ -60,24 +218,152 @@ impl GithubClient {
     pub fn search(&self) -> SearchHandler {
         SearchHandler::new(self)
     }
+
+    /// Returns the remaining quota of all valid tokens within the token pool.
+    pub fn token_quotas(&self) -> Vec<TokenQuota> {
+        self.request_handler.github_tokenmanager.as_ref().unwrap().lock().unwrap().quotas()
+    }
+
+    /// Returns a handler for the `/graphql` endpoint.
+    pub fn graphql(&self) -> GraphqlHandler {
+        GraphqlHandler::new(self)
+    }
 }
 
 /// HTTP methods
 impl GithubClient {
-    fn execute(&self, path: &str) -> Result<Response, Error> {
-        self.request_handler.execute_resp::<GithubResponseHandler>(&to_absolute_url(path))
+    /// Executes a conditional request if a response for `path` has been cached before, serving the cached
+    /// response if GitHub reports the resource as unmodified.
+    fn execute(&self, path: &str) -> Result<GithubResponse, Error> {
+        let url = self.to_absolute_url(path);
+        let etag = self.etag_cache.borrow().get(&url).map(|(etag, _)| etag.clone());
+
+        let response = match &etag {
+            Some(etag) => self.execute_with_header(&url, (header::IF_NONE_MATCH.as_str(), etag))?,
+            None => self.request_handler.execute_resp::<GithubResponseHandler>(&url)?,
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some((_, cached)) = self.etag_cache.borrow().get(&url) {
+                return Ok(cached.clone());
+            }
+        }
+
+        let headers = response.headers().clone();
+        let etag = headers.get(header::ETAG).and_then(|x| x.to_str().ok()).map(str::to_string);
+        let response = GithubResponse {
+            headers,
+            body: response.text()?,
+        };
+
+        if let Some(etag) = etag {
+            self.etag_cache.borrow_mut().insert(url, etag, response.clone());
+        }
+
+        Ok(response)
+    }
+
+    fn execute_graphql<T: DeserializeOwned>(&self, body: &serde_json::Value) -> Result<T, Error> {
+        let url = format!("{}/graphql", self.base_url);
+        self.request_handler.execute_deser_body::<GithubGraphqlResponseHandler, T>(&url, body)
     }
 
     fn execute_with_header(&self, path: &str, header: (&str, &str)) -> Result<Response, Error> {
-        self.request_handler.execute_resp_header::<GithubResponseHandler>(&to_absolute_url(path), header)
+        let url = self.to_absolute_url(path);
+        let response = self.request_handler.execute_resp_header::<GithubResponseHandler>(&url, header)?;
+
+        // Conditional requests answered with a 304 don't count against the ratelimit
+        if response.status() == StatusCode::NOT_MODIFIED {
+            self.request_handler.github_tokenmanager.as_ref().unwrap().lock().unwrap().refund();
+        }
+
+        Ok(response)
+    }
+
+    #[inline]
+    fn to_absolute_url(&self, path: &str) -> String {
+        if let Err(url::ParseError::RelativeUrlWithoutBase) = Url::parse(path) {
+            return format!("{}/{}", self.base_url, path);
+        }
+
+        path.to_string() // Already an absolute URL, return as is
     }
 }
 
-#[inline]
-fn to_absolute_url(path: &str) -> String {
-    if let Err(url::ParseError::RelativeUrlWithoutBase) = Url::parse(path) {
-        return format!("{}/{}", GITHUB_BASE_URL, path);
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::api::github::GithubClient;
+    use crate::config::DEFAULT_GITHUB_API_VERSION;
+    use crate::error::Error;
+    use httpmock::prelude::*;
+
+    pub(crate) const MOCK_TOKEN: &str = "ghp_MOCKuMzJHt21404WDydRCjB7PMOCK";
+
+    /// Returns a GitHub client for the API mocked by `server`, mocking its `/rate_limit` endpoint such that
+    /// [`MOCK_TOKEN`] is a valid token with its whole ratelimit remaining.
+    pub(crate) fn mocked_client(server: &MockServer) -> GithubClient {
+        let reset = chrono::Utc::now().timestamp() + 3600;
+        server.mock(|when, then| {
+            when.method(GET).path("/rate_limit").header("authorization", format!("Bearer {MOCK_TOKEN}"));
+            then.status(200).json_body(serde_json::json!({
+                "resources": {
+                    "core": { "limit": 5000, "remaining": 5000, "reset": reset },
+                    "search": { "limit": 30, "remaining": 30, "reset": reset }
+                }
+            }));
+        });
+
+        GithubClient::builder(vec![MOCK_TOKEN.to_string()]).base_url(&server.base_url()).build().unwrap()
     }
 
-    path.to_string() // Already an absolute URL, return as is
+    #[test]
+    fn mocked_etag_cache() {
+        let server = MockServer::start();
+        let ghc = mocked_client(&server);
+
+        let mut fresh = server.mock(|when, then| {
+            when.method(GET).path("/user/1");
+            then.status(200).header("etag", "\"abc\"").body(r#"{"login": "octocat"}"#);
+        });
+        assert!(ghc.execute("user/1").unwrap().body.contains("octocat"));
+        fresh.delete();
+
+        // The second request is conditional and served from the cache
+        let not_modified = server.mock(|when, then| {
+            when.method(GET).path("/user/1").header("if-none-match", "\"abc\"");
+            then.status(304);
+        });
+        assert!(ghc.execute("user/1").unwrap().body.contains("octocat"));
+        not_modified.assert();
+    }
+
+    #[test]
+    fn mocked_api_version() {
+        let server = MockServer::start();
+        let ghc = mocked_client(&server);
+
+        let pinned = server.mock(|when, then| {
+            when.method(GET)
+                .path("/user/1")
+                .header("x-github-api-version", DEFAULT_GITHUB_API_VERSION)
+                .header("accept", "application/vnd.github+json");
+            then.status(200).body(r#"{"login": "octocat"}"#);
+        });
+        assert!(ghc.execute("user/1").unwrap().body.contains("octocat"));
+        pinned.assert();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/user/2").header("x-github-api-version", "1970-01-01");
+            then.status(400).json_body(serde_json::json!({
+                "message": "API version 1970-01-01 is not supported."
+            }));
+        });
+
+        let ghc = GithubClient::builder(vec![MOCK_TOKEN.to_string()])
+            .base_url(&server.base_url())
+            .api_version("1970-01-01")
+            .build()
+            .unwrap();
+        assert!(matches!(ghc.execute("user/2"), Err(Error::GithubApiVersionUnsupported(_))));
+    }
 }
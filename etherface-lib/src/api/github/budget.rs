@@ -0,0 +1,187 @@
+//! Shared GitHub API budget allocator.
+//!
+//! All GitHub API clients of a process (e.g. the crawler and the scraper) draw from the same token pool and
+//! therefore from the same hourly budget of `5000 * number of tokens` requests. Without any coordination a
+//! busy consumer can drain the whole budget, starving the others. The allocator therefore reserves a
+//! configurable percentage of the budget for each [`GithubConsumer`]; a consumer may use more than its
+//! reservation as long as the unused reservations of all other consumers remain available. A consumer
+//! exceeding this is blocked until budget is available again, which is reported as contention.
+
+use crate::metrics::GITHUB_BUDGET_CONTENTION;
+use crate::metrics::GITHUB_BUDGET_USED;
+use lazy_static::lazy_static;
+use log::warn;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Number of API calls GitHub allows per hour and token.
+const REQUESTS_PER_TOKEN: u64 = 5000;
+
+/// Duration of GitHub's ratelimit window.
+const BUDGET_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Maximum duration a blocked consumer sleeps before checking the budget again.
+const CONTENTION_RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+lazy_static! {
+    /// Budget allocator shared by all GitHub API clients of this process.
+    pub(crate) static ref BUDGET: BudgetAllocator = BudgetAllocator::new(0, [0, 0]);
+}
+
+/// Consumers of the GitHub API budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GithubConsumer {
+    /// The GitHub fetcher, i.e. the crawler and its events.
+    Crawler,
+
+    /// The GitHub scraper.
+    Scraper,
+}
+
+impl GithubConsumer {
+    const ALL: [GithubConsumer; 2] = [GithubConsumer::Crawler, GithubConsumer::Scraper];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            GithubConsumer::Crawler => "crawler",
+            GithubConsumer::Scraper => "scraper",
+        }
+    }
+}
+
+pub(crate) struct BudgetAllocator {
+    state: Mutex<BudgetState>,
+}
+
+struct BudgetState {
+    /// Number of requests available per window summed over all tokens.
+    capacity: u64,
+
+    /// Reserved percentage of `capacity` per consumer, indexed by [`GithubConsumer::index`].
+    reserved: [u64; 2],
+
+    /// Requests sent within the current window per consumer, indexed by [`GithubConsumer::index`].
+    used: [u64; 2],
+
+    window_start: Instant,
+}
+
+impl BudgetAllocator {
+    /// Returns a new allocator for `tokens` GitHub tokens with the given reserved percentages.
+    pub fn new(tokens: usize, reserved: [u64; 2]) -> Self {
+        BudgetAllocator {
+            state: Mutex::new(BudgetState {
+                capacity: tokens as u64 * REQUESTS_PER_TOKEN,
+                reserved,
+                used: [0, 0],
+                window_start: Instant::now(),
+            }),
+        }
+    }
+
+    /// Updates the number of available tokens and the reserved percentage of each consumer.
+    pub fn configure(&self, tokens: usize, reserved: [u64; 2]) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = tokens as u64 * REQUESTS_PER_TOKEN;
+        state.reserved = reserved;
+    }
+
+    /// Accounts one request to `consumer` if the budget allows it, otherwise returns the duration after which
+    /// the budget should be checked again.
+    pub fn try_acquire(&self, consumer: GithubConsumer) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.window_start.elapsed() >= BUDGET_WINDOW {
+            state.used = [0, 0];
+            state.window_start = Instant::now();
+        }
+
+        let used: u64 = state.used.iter().sum();
+        let unused_reserved_by_others: u64 = GithubConsumer::ALL
+            .iter()
+            .filter(|other| **other != consumer)
+            .map(|other| {
+                let reserved = state.capacity * state.reserved[other.index()] / 100;
+                reserved.saturating_sub(state.used[other.index()])
+            })
+            .sum();
+
+        if used + unused_reserved_by_others < state.capacity {
+            state.used[consumer.index()] += 1;
+            GITHUB_BUDGET_USED
+                .with_label_values(&[consumer.as_str()])
+                .set(state.used[consumer.index()] as i64);
+            return Ok(());
+        }
+
+        Err(BUDGET_WINDOW.saturating_sub(state.window_start.elapsed()).min(CONTENTION_RECHECK_INTERVAL))
+    }
+
+    /// Returns one previously accounted request to the budget of `consumer`.
+    pub fn refund(&self, consumer: GithubConsumer) {
+        let mut state = self.state.lock().unwrap();
+        state.used[consumer.index()] = state.used[consumer.index()].saturating_sub(1);
+    }
+
+    /// Accounts one request to `consumer`, blocking the current thread until the budget allows it.
+    pub fn acquire(&self, consumer: GithubConsumer) {
+        while let Err(wait) = self.try_acquire(consumer) {
+            warn!("GitHub budget contention, {} waiting {}s", consumer.as_str(), wait.as_secs());
+            GITHUB_BUDGET_CONTENTION.with_label_values(&[consumer.as_str()]).inc();
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::github::budget::BudgetAllocator;
+    use crate::api::github::budget::GithubConsumer;
+
+    #[test]
+    fn reservation_is_kept_for_other_consumers() {
+        // One token, i.e. 5000 requests of which 20% (1000) are reserved for the scraper
+        let budget = BudgetAllocator::new(1, [0, 20]);
+
+        for _ in 0..4000 {
+            assert!(budget.try_acquire(GithubConsumer::Crawler).is_ok());
+        }
+        assert!(budget.try_acquire(GithubConsumer::Crawler).is_err());
+
+        // The scraper is still able to use its whole reservation...
+        for _ in 0..1000 {
+            assert!(budget.try_acquire(GithubConsumer::Scraper).is_ok());
+        }
+
+        // ...but not more than the whole budget
+        assert!(budget.try_acquire(GithubConsumer::Scraper).is_err());
+    }
+
+    #[test]
+    fn unreserved_budget_is_shared() {
+        let budget = BudgetAllocator::new(1, [0, 20]);
+
+        // The crawler reserves nothing, as such the scraper may use the whole budget
+        for _ in 0..5000 {
+            assert!(budget.try_acquire(GithubConsumer::Scraper).is_ok());
+        }
+        assert!(budget.try_acquire(GithubConsumer::Crawler).is_err());
+    }
+
+    #[test]
+    fn configure_updates_capacity() {
+        let budget = BudgetAllocator::new(0, [0, 0]);
+        assert!(budget.try_acquire(GithubConsumer::Crawler).is_err());
+
+        budget.configure(1, [50, 50]);
+        for _ in 0..2500 {
+            assert!(budget.try_acquire(GithubConsumer::Crawler).is_ok());
+        }
+        assert!(budget.try_acquire(GithubConsumer::Crawler).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+//! ETag based conditional request cache of the GitHub client.
+//!
+//! GitHub returns an `ETag` header for most responses, which if sent back within an `If-None-Match` header
+//! results in a `304 Not Modified` response if the resource didn't change. Such responses don't count
+//! against the ratelimit but also have no body, as such the cache stores the ETag alongside the headers and
+//! body of the most recent responses per URL. Once full the oldest entries are evicted first.
+//! <br/>See <https://docs.github.com/en/rest/overview/resources-in-the-rest-api#conditional-requests>.
+
+use crate::api::github::GithubResponse;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+pub(crate) struct EtagCache {
+    capacity: usize,
+    entries: HashMap<String, (String, GithubResponse)>,
+
+    /// URLs in insertion order, used to evict the oldest entry once `capacity` is reached.
+    order: VecDeque<String>,
+}
+
+impl EtagCache {
+    /// Returns a new cache holding at most `capacity` responses.
+    pub fn new(capacity: usize) -> Self {
+        EtagCache {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Returns the ETag and response cached for `url`, if any.
+    pub fn get(&self, url: &str) -> Option<&(String, GithubResponse)> {
+        self.entries.get(url)
+    }
+
+    /// Caches `response` with its `etag` for `url`, evicting the oldest entry if the cache is full.
+    pub fn insert(&mut self, url: String, etag: String, response: GithubResponse) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.insert(url.clone(), (etag, response)).is_some() {
+            return; // Updated an existing entry, hence the order and size remain unchanged
+        }
+
+        self.order.push_back(url);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::github::cache::EtagCache;
+    use crate::api::github::GithubResponse;
+    use reqwest::header::HeaderMap;
+
+    fn response(body: &str) -> GithubResponse {
+        GithubResponse {
+            headers: HeaderMap::new(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn insert_and_update() {
+        let mut cache = EtagCache::new(2);
+        cache.insert("a".to_string(), "etag-a".to_string(), response("body-a"));
+        assert_eq!(cache.get("a").unwrap().0, "etag-a");
+        assert!(cache.get("b").is_none());
+
+        cache.insert("a".to_string(), "etag-a2".to_string(), response("body-a2"));
+        assert_eq!(cache.get("a").unwrap().0, "etag-a2");
+        assert_eq!(cache.get("a").unwrap().1.body, "body-a2");
+    }
+
+    #[test]
+    fn evicts_oldest() {
+        let mut cache = EtagCache::new(2);
+        cache.insert("a".to_string(), "etag-a".to_string(), response("body-a"));
+        cache.insert("b".to_string(), "etag-b".to_string(), response("body-b"));
+        cache.insert("a".to_string(), "etag-a2".to_string(), response("body-a2"));
+        cache.insert("c".to_string(), "etag-c".to_string(), response("body-c"));
+
+        assert!(cache.get("a").is_none());
+        assert!(cache.get("b").is_some());
+        assert!(cache.get("c").is_some());
+    }
+
+    #[test]
+    fn zero_capacity_disables_cache() {
+        let mut cache = EtagCache::new(0);
+        cache.insert("a".to_string(), "etag-a".to_string(), response("body-a"));
+        assert!(cache.get("a").is_none());
+    }
+}
@@ -0,0 +1,227 @@
+//! `/graphql` endpoint handler.
+//!
+//! Compared to the REST API, which needs (at least) one request per repository, the GraphQL API allows
+//! batching up to [`GRAPHQL_BATCH_SIZE`] repositories into a single query. This is used by the crawlers
+//! `CheckRepositories` event, which checks thousands of repositories for updates.
+//! <br/>See <https://docs.github.com/en/graphql>.
+
+use crate::api::github::GithubClient;
+use crate::error::Error;
+use crate::model::GithubLicense;
+use crate::model::GithubRepository;
+use crate::model::GithubUser;
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Maximum number of repositories queried at once, see
+/// <https://docs.github.com/en/graphql/overview/resource-limitations#node-limit>.
+pub const GRAPHQL_BATCH_SIZE: usize = 100;
+
+const QUERY_REPOSITORIES: &str = "query($ids: [ID!]!) {
+    nodes(ids: $ids) {
+        ... on Repository {
+            databaseId name url isFork stargazerCount diskUsage createdAt pushedAt updatedAt description
+            primaryLanguage { name }
+            licenseInfo { spdxId }
+            repositoryTopics(first: 100) { nodes { topic { name } } }
+            languages(first: 100) { totalSize edges { size node { name } } }
+            owner { login url ... on User { databaseId } ... on Organization { databaseId } }
+        }
+    }
+}";
+
+pub struct GraphqlHandler<'a> {
+    ghc: &'a GithubClient,
+}
+
+/// Repository metadata as returned by [`GraphqlHandler::repositories`].
+pub struct RepositoryMetadata {
+    pub repository: GithubRepository,
+
+    /// Solidity ratio of the repository, i.e. Solidity Ratio / Summed Ratio of All Languages.
+    pub solidity_ratio: f32,
+}
+
+#[derive(Deserialize)]
+struct Root<T> {
+    // Resources which can't be resolved (e.g. deleted ones) are returned as `null` alongside an entry
+    // within the `errors` array, as such errors only have to be handled if no data is present at all.
+    data: Option<T>,
+
+    #[serde(default)]
+    errors: Vec<GraphqlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphqlError {
+    message: String,
+}
+
+impl<T> Root<T> {
+    fn into_data(self) -> Result<T, Error> {
+        match self.data {
+            Some(data) => Ok(data),
+            None => {
+                let messages = self.errors.into_iter().map(|x| x.message).collect::<Vec<String>>();
+                Err(Error::GithubGraphql(messages.join("; ")))
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Nodes {
+    nodes: Vec<Option<RepositoryNode>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepositoryNode {
+    database_id: i32,
+    name: String,
+    url: String,
+    is_fork: bool,
+    stargazer_count: i32,
+    disk_usage: Option<i32>,
+    created_at: DateTime<Utc>,
+    pushed_at: Option<DateTime<Utc>>, // Not present for empty repositories
+    updated_at: DateTime<Utc>,
+    description: Option<String>,
+    primary_language: Option<Language>,
+    license_info: Option<LicenseNode>,
+    repository_topics: RepositoryTopics,
+    languages: Languages,
+    owner: OwnerNode,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LicenseNode {
+    spdx_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RepositoryTopics {
+    nodes: Vec<RepositoryTopic>,
+}
+
+#[derive(Deserialize)]
+struct RepositoryTopic {
+    topic: Topic,
+}
+
+#[derive(Deserialize)]
+struct Topic {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Language {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Languages {
+    total_size: usize,
+    edges: Vec<LanguageEdge>,
+}
+
+#[derive(Deserialize)]
+struct LanguageEdge {
+    size: usize,
+    node: Language,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnerNode {
+    database_id: Option<i32>,
+    login: String,
+    url: String,
+}
+
+impl<'a> GraphqlHandler<'a> {
+    pub(crate) fn new(ghc: &'a GithubClient) -> Self {
+        GraphqlHandler { ghc }
+    }
+
+    /// Returns the metadata of at most [`GRAPHQL_BATCH_SIZE`] repositories in the same order as `ids`, where
+    /// `None` indicates a repository which could not be resolved, e.g. because it was deleted or made
+    /// private.
+    pub fn repositories(&self, ids: &[i32]) -> Result<Vec<Option<RepositoryMetadata>>, Error> {
+        let node_ids = ids.iter().map(|id| legacy_node_id("010:Repository", *id)).collect::<Vec<String>>();
+        let body = serde_json::json!({ "query": QUERY_REPOSITORIES, "variables": { "ids": node_ids } });
+
+        let nodes = self.ghc.execute_graphql::<Root<Nodes>>(&body)?.into_data()?.nodes;
+        let mut nodes = nodes.into_iter().flatten().map(|x| (x.database_id, x)).collect::<HashMap<_, _>>();
+        Ok(ids.iter().map(|id| nodes.remove(id).and_then(RepositoryNode::into_metadata)).collect())
+    }
+}
+
+impl RepositoryNode {
+    fn into_metadata(self) -> Option<RepositoryMetadata> {
+        let solidity = self.languages.edges.iter().find(|x| x.node.name == "Solidity").map(|x| x.size);
+        let solidity_ratio = match (solidity, self.languages.total_size) {
+            (Some(solidity), total) if total > 0 => solidity as f32 / total as f32,
+            _ => 0.0,
+        };
+
+        Some(RepositoryMetadata {
+            repository: GithubRepository {
+                id: self.database_id,
+                name: self.name,
+                html_url: self.url,
+                language: self.primary_language.map(|x| x.name),
+                stargazers_count: self.stargazer_count,
+                size: self.disk_usage.unwrap_or_default(),
+                fork: self.is_fork,
+                fork_parent: None,
+                created_at: self.created_at,
+                pushed_at: self.pushed_at.unwrap_or(self.created_at),
+                updated_at: self.updated_at,
+                description: self.description,
+                topics: self.repository_topics.nodes.into_iter().map(|x| x.topic.name).collect(),
+                license: self.license_info.map(|x| GithubLicense { spdx_id: x.spdx_id }),
+                owner: GithubUser {
+                    id: self.owner.database_id?,
+                    login: self.owner.login,
+                    html_url: self.owner.url,
+                    public_repos: None,
+                },
+            },
+            solidity_ratio,
+        })
+    }
+}
+
+/// Returns the legacy global node ID of a resource, i.e. the base64 encoded `{prefix}{id}` string, which
+/// GitHub still resolves and which (unlike the new format) can be derived from the numeric ID alone.
+fn legacy_node_id(prefix: &str, id: i32) -> String {
+    base64::encode(format!("{prefix}{id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::github::handler::graphql::legacy_node_id;
+    use crate::api::github::GithubClient;
+
+    #[test]
+    fn legacy_node_id_of_repository() {
+        // See https://api.github.com/repositories/44971752 (`node_id` field)
+        assert_eq!(legacy_node_id("010:Repository", 44971752), "MDEwOlJlcG9zaXRvcnk0NDk3MTc1Mg==");
+    }
+
+    #[test]
+    fn repositories() {
+        let ghc = GithubClient::new().unwrap();
+
+        // 44971752 = ethereum/EIPs, 0 = non-existent repository
+        let metadata = ghc.graphql().repositories(&[44971752, 0]).unwrap();
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata[0].as_ref().unwrap().repository.html_url, "https://github.com/ethereum/EIPs");
+        assert!(metadata[1].is_none());
+    }
+}
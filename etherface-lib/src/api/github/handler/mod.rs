@@ -1,5 +1,6 @@
 //! GitHub API endpoint handlers.
 
+pub mod graphql;
 pub mod repositories;
 pub mod search;
 pub mod user;
@@ -1,12 +1,17 @@
 //! `/repositories` endpoint handler.
 
 use crate::api::github::page::Page;
+use crate::api::github::page::Pages;
 use crate::api::github::GithubClient;
 use crate::error::Error;
+use crate::model::GithubCommit;
+use crate::model::GithubComparison;
 use crate::model::GithubRepository;
+use crate::model::GithubTree;
 use crate::model::GithubUser;
 use chrono::DateTime;
 use chrono::Utc;
+use reqwest::Url;
 use std::collections::HashMap;
 
 pub struct RepoHandler<'a> {
@@ -33,6 +38,12 @@ impl<'a> RepoHandler<'a> {
         Page::all_pages(self.ghc, path)
     }
 
+    /// Returns an iterator over the pages of the `/repositories/{id}/stargazers` response, see
+    /// [`RepoHandler::stargazers`].
+    pub fn stargazers_pages(&self) -> Pages<'a, GithubUser> {
+        Pages::new(self.ghc, format!("repositories/{id}/stargazers", id = self.id))
+    }
+
     /// Returns the deserialized JSON `/repositories/{id}/languages` response.
     pub fn languages(&self) -> Result<HashMap<String, usize>, Error> {
         let path = format!("repositories/{id}/languages", id = self.id);
@@ -47,6 +58,42 @@ impl<'a> RepoHandler<'a> {
         Page::all_pages(self.ghc, path)
     }
 
+    /// Returns the deserialized JSON `/repositories/{id}/git/trees/HEAD?recursive=1` response, i.e. the
+    /// recursive file tree of the repositories default branch.
+    pub fn tree(&self) -> Result<GithubTree, Error> {
+        let path = format!("repositories/{id}/git/trees/HEAD?recursive=1", id = self.id);
+
+        self.ghc.execute(&path)?.json()
+    }
+
+    /// Returns the raw `/repositories/{id}/contents/{path}` response, i.e. the content of the file at `path`
+    /// within the repositories default branch, without having to clone the repository.
+    pub fn contents(&self, path: &str) -> Result<String, Error> {
+        let url = format!("{}/repositories/{}/contents", self.ghc.base_url, self.id);
+        let mut url = Url::parse(&url).unwrap();
+        url.path_segments_mut().unwrap().extend(path.split('/')); // Takes care of percent-encoding
+
+        let response = self.ghc.execute_with_header(url.as_str(), ("Accept", "application/vnd.github.raw"))?;
+        Ok(response.text()?)
+    }
+
+    /// Returns the deserialized JSON `/repositories/{id}/commits?since={date}` response, i.e. all commits of
+    /// the repositories default branch committed after the given date.
+    pub fn commits_since(&self, date: DateTime<Utc>) -> Result<Vec<GithubCommit>, Error> {
+        let date = date.format("%Y-%m-%dT%H:%M:%SZ");
+        let path = format!("repositories/{id}/commits?since={date}", id = self.id);
+
+        Page::all_pages(self.ghc, path)
+    }
+
+    /// Returns the deserialized JSON `/repositories/{id}/compare/{base}...{head}` response, where `base` and
+    /// `head` are either commit SHAs or branch names.
+    pub fn compare(&self, base: &str, head: &str) -> Result<GithubComparison, Error> {
+        let path = format!("repositories/{id}/compare/{base}...{head}", id = self.id);
+
+        self.ghc.execute(&path)?.json()
+    }
+
     /// Returns the absolute Solidity ratio of a repositories,
     /// i.e. Solidity Ratio / Summed Ratio of All Languages.
     pub fn solidity_ratio(&self) -> Result<f32, Error> {
@@ -79,9 +126,12 @@ impl<'a> RepoHandler<'a> {
 
 #[cfg(test)]
 mod tests {
+    use crate::api::github::tests::mocked_client;
     use crate::api::github::GithubClient;
+    use crate::api::github::Pagination;
     use chrono::TimeZone;
     use chrono::Utc;
+    use httpmock::prelude::*;
 
     #[test]
     fn get() {
@@ -109,6 +159,62 @@ mod tests {
         assert!(stargazer_names.contains(&"volsa".to_string()));
     }
 
+    #[test]
+    fn stargazers_pages_mocked() {
+        let server = MockServer::start();
+        let ghc = mocked_client(&server);
+
+        let user = |id: i32| {
+            serde_json::json!({ "id": id, "login": format!("user{id}"), "html_url": "https://github.com" })
+        };
+
+        // Mocks are matched in the order of their creation, hence the more specific second page goes first
+        let second = server.mock(|when, then| {
+            when.method(GET).path("/repositories/1/stargazers").query_param("page", "2");
+            then.status(200).json_body(serde_json::json!([user(3)]));
+        });
+        let next = server.url("/repositories/1/stargazers?page=2");
+        let first = server.mock(|when, then| {
+            when.method(GET).path("/repositories/1/stargazers");
+            then.status(200)
+                .header("link", format!("<{next}>; rel=\"next\""))
+                .json_body(serde_json::json!([user(1), user(2)]));
+        });
+
+        // Stopping early doesn't request any further pages...
+        let mut pages = ghc.repos(1).stargazers_pages();
+        assert_eq!(pages.next().unwrap().unwrap().len(), 2);
+        first.assert_hits(1);
+        second.assert_hits(0);
+
+        // ...whereas exhausting the iterator requests all of them
+        let ids: Vec<i32> = pages.flat_map(Result::unwrap).map(|x| x.id).collect();
+        assert_eq!(ids, vec![3]);
+        second.assert_hits(1);
+    }
+
+    #[test]
+    fn stargazers_max_pages_mocked() {
+        let server = MockServer::start();
+        let mut ghc = mocked_client(&server);
+        ghc.set_pagination(Pagination {
+            per_page: 1,
+            max_pages: Some(1),
+        });
+
+        let next = server.url("/repositories/1/stargazers?per_page=1&page=2");
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/repositories/1/stargazers").query_param("per_page", "1");
+            then.status(200).header("link", format!("<{next}>; rel=\"next\"")).json_body(serde_json::json!([{
+                "id": 1, "login": "user1", "html_url": "https://github.com/user1"
+            }]));
+        });
+
+        // The listing has more pages, however only the first one may be requested
+        assert_eq!(ghc.repos(1).stargazers().unwrap().len(), 1);
+        mock.assert_hits(1);
+    }
+
     #[test]
     fn languages() {
         let ghc = GithubClient::new().unwrap();
@@ -161,6 +267,51 @@ mod tests {
         assert!(ratio >= 0.6 && ratio <= 0.65);
     }
 
+    #[test]
+    fn tree() {
+        let ghc = GithubClient::new().unwrap();
+        let tree = ghc.repos(44971752).tree().unwrap();
+
+        assert!(!tree.truncated);
+        assert!(tree.tree.iter().any(|x| x.path == "README.md" && x.kind == "blob"));
+        assert!(tree.tree.iter().any(|x| x.path == "EIPS" && x.kind == "tree"));
+    }
+
+    #[test]
+    fn contents() {
+        let ghc = GithubClient::new().unwrap();
+        let content = ghc.repos(44971752).contents("EIPS/eip-20.md").unwrap();
+
+        assert!(content.contains("function transfer(address _to, uint256 _value)"));
+    }
+
+    #[test]
+    fn commits_since() {
+        let ghc = GithubClient::new().unwrap();
+        let since = Utc::now() - chrono::Duration::days(90);
+        let commits = ghc.repos(44971752).commits_since(since).unwrap();
+
+        assert!(!commits.is_empty());
+        assert!(commits.iter().all(|x| x.commit.committer.as_ref().unwrap().date >= since));
+        assert!(ghc.repos(44971752).commits_since(Utc::now()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn compare() {
+        let ghc = GithubClient::new().unwrap();
+        let commits = ghc.repos(44971752).commits_since(Utc::now() - chrono::Duration::days(90)).unwrap();
+        assert!(commits.len() >= 2, "Need at least 2 commits within the last 90 days");
+
+        // Commits are ordered from newest to oldest
+        let comparison = ghc.repos(44971752).compare(&commits[1].sha, &commits[0].sha).unwrap();
+        assert_eq!(comparison.status, "ahead");
+        assert!(comparison.ahead_by >= 1);
+        assert_eq!(comparison.commits.last().unwrap().sha, commits[0].sha);
+
+        let comparison = ghc.repos(44971752).compare(&commits[0].sha, &commits[0].sha).unwrap();
+        assert_eq!(comparison.status, "identical");
+    }
+
     #[test]
     fn where_modified_since() {
         let ghc = GithubClient::new().unwrap();
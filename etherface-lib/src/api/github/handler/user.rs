@@ -1,10 +1,14 @@
 //! `/user` endpoint handler.
 
 use crate::api::github::page::Page;
+use crate::api::github::page::Pages;
 use crate::api::github::GithubClient;
 use crate::error::Error;
 use crate::model::GithubRepository;
 use crate::model::GithubUser;
+use crate::model::GithubUserEvent;
+use chrono::DateTime;
+use chrono::Utc;
 
 pub struct UserHandler<'a> {
     ghc: &'a GithubClient,
@@ -28,16 +32,56 @@ impl<'a> UserHandler<'a> {
         Page::all_pages(self.ghc, path)
     }
 
+    /// Returns an iterator over the pages of the `/user/{id}/starred` response, see [`UserHandler::starred`].
+    pub fn starred_pages(&self) -> Pages<'a, GithubRepository> {
+        Pages::new(self.ghc, format!("user/{id}/starred", id = self.id))
+    }
+
     /// Returns the deserialized JSON `/user/{id}/repos` response.
     pub fn repos(&self) -> Result<Vec<GithubRepository>, Error> {
         let path = format!("user/{id}/repos", id = self.id,);
         Page::all_pages(self.ghc, path)
     }
+
+    /// Returns an iterator over the pages of the `/user/{id}/repos` response, see [`UserHandler::repos`].
+    pub fn repos_pages(&self) -> Pages<'a, GithubRepository> {
+        Pages::new(self.ghc, format!("user/{id}/repos", id = self.id))
+    }
+
+    /// Returns an iterator over the pages of the `/user/{id}/events/public` response, most recent events
+    /// first. Note that GitHub only returns events of the last 90 days.
+    pub fn events_pages(&self) -> Pages<'a, GithubUserEvent> {
+        Pages::new(self.ghc, format!("user/{id}/events/public", id = self.id))
+    }
+
+    /// Returns whether the user pushed to or created a repository since `since`. Compared to listing all
+    /// repositories of a user this usually takes a single request, which (thanks to the ETag cache) doesn't
+    /// count against the ratelimit if no new events occurred since the last call.
+    pub fn has_repository_activity_since(&self, since: DateTime<Utc>) -> Result<bool, Error> {
+        for page in self.events_pages() {
+            for event in page? {
+                if event.created_at < since {
+                    return Ok(false); // Events are ordered, hence all further events are older too
+                }
+
+                if event.is_repository_activity() {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::api::github::tests::mocked_client;
     use crate::api::github::GithubClient;
+    use crate::error::Error;
+    use chrono::DateTime;
+    use chrono::Utc;
+    use httpmock::prelude::*;
 
     #[test]
     fn get() {
@@ -48,6 +92,32 @@ mod tests {
         assert_eq!(user.public_repos, Some(4));
     }
 
+    #[test]
+    fn get_mocked() {
+        let server = MockServer::start();
+        let ghc = mocked_client(&server);
+
+        server.mock(|when, then| {
+            when.method(GET).path("/user/29666622");
+            then.status(200).json_body(serde_json::json!({
+                "id": 29666622,
+                "login": "volsa",
+                "html_url": "https://github.com/volsa",
+                "public_repos": 4
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/user/0");
+            then.status(404).json_body(serde_json::json!({ "message": "Not Found" }));
+        });
+
+        let user = ghc.user(29666622).get().unwrap();
+        assert_eq!(user.login, "volsa");
+        assert_eq!(user.public_repos, Some(4));
+
+        assert!(matches!(ghc.user(0).get(), Err(Error::GithubResourceUnavailable(_))));
+    }
+
     #[test]
     fn starred() {
         let ghc = GithubClient::new().unwrap();
@@ -60,6 +130,41 @@ mod tests {
         assert!(starred_names.contains(&"hashbrown".to_string()));
     }
 
+    #[test]
+    fn starred_pages() {
+        let ghc = GithubClient::new().unwrap();
+
+        let pages = ghc.user(29666622).starred_pages().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(pages.len(), 1); // 6 starred repositories fit into a single page
+        assert_eq!(pages[0].len(), 6);
+    }
+
+    #[test]
+    fn has_repository_activity_since_mocked() {
+        let server = MockServer::start();
+        let ghc = mocked_client(&server);
+
+        server.mock(|when, then| {
+            when.method(GET).path("/user/1/events/public");
+            then.status(200).json_body(serde_json::json!([
+                { "type": "WatchEvent", "created_at": "2022-06-03T00:00:00Z" },
+                { "type": "PushEvent", "created_at": "2022-06-02T00:00:00Z" },
+            ]));
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/user/0/events/public");
+            then.status(404).json_body(serde_json::json!({ "message": "Not Found" }));
+        });
+
+        let since = |date: &str| date.parse::<DateTime<Utc>>().unwrap();
+        assert!(ghc.user(1).has_repository_activity_since(since("2022-06-01T00:00:00Z")).unwrap());
+        assert!(!ghc.user(1).has_repository_activity_since(since("2022-06-02T12:00:00Z")).unwrap());
+        assert!(matches!(
+            ghc.user(0).has_repository_activity_since(since("2022-06-01T00:00:00Z")),
+            Err(Error::GithubResourceUnavailable(_))
+        ));
+    }
+
     #[test]
     fn repos() {
         let ghc = GithubClient::new().unwrap();
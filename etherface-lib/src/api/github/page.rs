@@ -1,12 +1,51 @@
 //! Handles all GitHub API pagination logic. For a better understanding of this module make sure to read
 //! <https://docs.github.com/en/rest/overview/resources-in-the-rest-api#pagination>,
+//!
+//! Listings can either be retrieved as a whole (see [`Page::all_pages`]) or page by page using the
+//! [`Pages`] iterator, which allows processing and dropping each page before the next one is requested as
+//! well as stopping early, e.g. for stargazer lists of huge repositories. Both honor the clients
+//! [`Pagination`] settings, capping the number of pages requested per listing such that pathological
+//! listings (e.g. repositories with hundreds of thousands of stargazers) can't drain a tokens budget.
 
 use crate::api::github::GithubClient;
+use crate::api::github::GITHUB_MAX_PER_PAGE;
+use crate::config::Config;
+use crate::config::DEFAULT_GITHUB_PER_PAGE;
 use crate::error::Error;
 use hyperx::header::TypedHeaders;
 use log::warn;
 use reqwest::header::HeaderMap;
 use serde::de::DeserializeOwned;
+use std::marker::PhantomData;
+
+/// Pagination settings of a [`GithubClient`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pagination {
+    /// Number of items requested per page, at most [`GITHUB_MAX_PER_PAGE`].
+    pub per_page: usize,
+
+    /// Maximum number of pages requested per listing, unlimited if `None`.
+    pub max_pages: Option<usize>,
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Pagination {
+            per_page: DEFAULT_GITHUB_PER_PAGE,
+            max_pages: None,
+        }
+    }
+}
+
+impl Pagination {
+    /// Returns the pagination settings configured in `config`.
+    pub fn from_config(config: &Config) -> Self {
+        Pagination {
+            per_page: config.github_per_page,
+            max_pages: config.github_max_pages,
+        }
+    }
+}
 
 pub(crate) struct Page<T> {
     items: Vec<T>,
@@ -19,26 +58,78 @@ where
 {
     pub fn all_pages(ghc: &GithubClient, path: String) -> Result<Vec<T>, Error> {
         let mut items = Vec::new();
-        let mut page = get_page(ghc, &path)?;
 
-        items.append(&mut page.items); // append items from first page before iterating
-        while let Some(rel_next) = page.rel_next {
-            page = get_page(ghc, &rel_next)?;
-            items.append(&mut page.items);
+        for page in Pages::new(ghc, path) {
+            items.append(&mut page?);
         }
 
         Ok(items)
     }
 }
 
+/// Iterator over the pages of a GitHub listing, lazily requesting the next page with each iteration. Stops
+/// after the last page or the first error.
+pub struct Pages<'a, T> {
+    ghc: &'a GithubClient,
+    next: Option<String>,
+
+    /// Number of pages requested so far, compared against [`Pagination::max_pages`].
+    requested: usize,
+    _item: PhantomData<T>,
+}
+
+impl<'a, T> Pages<'a, T> {
+    pub(crate) fn new(ghc: &'a GithubClient, path: String) -> Self {
+        // Only the first page needs the `per_page` parameter, the `rel="next"` links returned by GitHub
+        // already include it
+        let separator = if path.contains('?') { '&' } else { '?' };
+        let per_page = ghc.pagination.per_page.min(GITHUB_MAX_PER_PAGE);
+
+        Pages {
+            ghc,
+            next: Some(format!("{path}{separator}per_page={per_page}")),
+            requested: 0,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Pages<'a, T>
+where
+    T: DeserializeOwned,
+{
+    type Item = Result<Vec<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let url = self.next.take()?;
+
+        if let Some(max_pages) = self.ghc.pagination.max_pages {
+            if self.requested >= max_pages {
+                warn!("Stopping pagination after {max_pages} pages, skipping {url} and all further pages");
+                return None;
+            }
+        }
+
+        self.requested += 1;
+        match get_page(self.ghc, &url) {
+            Ok(page) => {
+                self.next = page.rel_next;
+                Some(Ok(page.items))
+            }
+
+            Err(why) => Some(Err(why)),
+        }
+    }
+}
+
 fn get_page<T>(ghc: &GithubClient, url: &str) -> Result<Page<T>, Error>
 where
     T: DeserializeOwned,
 {
     let response = ghc.execute(url)?;
-    let rel_next = get_rel_next(response.headers());
+    let rel_next = get_rel_next(&response.headers);
 
-    let json_response = match response.json() {
+    let json_response = match response.json::<serde_json::Value>() {
         Ok(val) => val,
         Err(why) => {
             warn!("Failed to parse JSON on page {url}; {why}");
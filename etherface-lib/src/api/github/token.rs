@@ -7,18 +7,41 @@
 //! requests. If, however, the active token is drained, i.e. all 5000 requests / hour have been reached, the 
 //! token manager will automatically find a new token in the pool to temporarily replace the old active token
 //! (see the [`refresh`] function). As such the GitHub API client doesn't have to worry about token managment.
-
-use crate::api::github::GITHUB_RATELIMIT_URL;
+//! The token pool is periodically re-read from `.env` (see the [`reload_if_due`] function), such that new
+//! tokens can be added and revoked ones removed during operation without restarting Etherface.
+//! Furthermore each request is accounted to the token managers consumer within the shared budget allocator
+//! (see [`crate::api::github::budget`]), such that e.g. the crawler can't starve the scraper.
+
+use crate::api::github::budget::GithubConsumer;
+use crate::api::github::budget::BUDGET;
+use crate::api::github::GITHUB_BASE_URL;
 use crate::api::RequestHandler;
 use crate::api::TokenManagerResponseHandler;
 use crate::config::Config;
 use crate::error::Error;
+use crate::metrics::GITHUB_TOKEN_BUDGET;
+use crate::metrics::GITHUB_TOKEN_REMAINING;
+use chrono::DateTime;
+use chrono::TimeZone;
+use chrono::Utc;
 use log::info;
 use log::warn;
 use serde::Deserialize;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Interval in which the token pool is re-read from `.env`.
+const TOKEN_RELOAD_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Interval in which the remaining quota of all tokens is reported, see
+/// [`TokenManager::report_quotas_if_due`].
+const QUOTA_REPORT_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
-/// Sleep duration if all API tokens are drained.
-const SLEEP_DURATION_TOKENS_DRAINED: u64 = 5 * 60;
+/// Percentage of the summed core ratelimit below which a warning to add further tokens is logged.
+const QUOTA_WARNING_THRESHOLD: usize = 10;
+
+/// Additional seconds slept past a ratelimits reset, accounting for clock drift between us and GitHub.
+const RESET_SLEEP_MARGIN: u64 = 5;
 
 #[derive(Debug, Deserialize)]
 struct RatelimitRoot {
@@ -33,24 +56,88 @@ struct RatelimitObject {
 
 #[derive(Debug, Deserialize)]
 struct Ratelimit {
+    pub limit: usize,
     pub remaining: usize,
+
+    /// Time at which the ratelimit resets in UTC epoch seconds.
+    pub reset: i64,
+}
+
+/// Remaining quota of a single GitHub token.
+#[derive(Debug)]
+pub struct TokenQuota {
+    /// Token with all but its last four characters masked.
+    pub token: String,
+
+    pub core_limit: usize,
+    pub core_remaining: usize,
+    pub core_reset: DateTime<Utc>,
+    pub search_limit: usize,
+    pub search_remaining: usize,
 }
 
 pub(crate) struct TokenManager {
     pub active: String,
     pool: Vec<String>,
+
+    /// Tokens as last read from the config, i.e. including invalid ones which were removed from `pool`.
+    configured: Vec<String>,
+    last_reload: Instant,
+
+    /// Whether `configured` is periodically re-read from `.env`, see [`TokenManager::reload_if_due`].
+    reload_from_env: bool,
+
+    last_quota_report: Option<Instant>,
+
+    consumer: GithubConsumer,
+    reserved: [u64; 2],
     request_handler: Box<RequestHandler>,
+
+    /// URL of the `/rate_limit` endpoint used to check the tokens.
+    ratelimit_url: String,
 }
 
 impl TokenManager {
-    /// Returns a new token manager.
-    pub fn new() -> Result<Self, Error> {
-        let tokens = Config::new()?.tokens_github;
+    /// Returns a new token manager for the tokens configured in `.env`, accounting its requests to the budget
+    /// of `consumer`.
+    pub fn new(consumer: GithubConsumer) -> Result<Self, Error> {
+        let config = Config::new()?;
+        let reserved = [config.github_budget_reserved_crawler, config.github_budget_reserved_scraper];
+
+        let request_handler = RequestHandler::new()?;
+        let tokens = config.tokens_github;
+        let mut manager =
+            TokenManager::with_tokens(tokens, consumer, reserved, request_handler, GITHUB_BASE_URL)?;
+        manager.reload_from_env = true;
+
+        Ok(manager)
+    }
+
+    /// Returns a new token manager for `tokens` of the GitHub API at `base_url`, accounting its requests to
+    /// the budget of `consumer` with the given reserved percentages (see [`super::budget`]). Unlike
+    /// [`TokenManager::new`] the tokens are never reloaded from `.env`.
+    pub fn with_tokens(
+        tokens: Vec<String>,
+        consumer: GithubConsumer,
+        reserved: [u64; 2],
+        request_handler: RequestHandler,
+        base_url: &str,
+    ) -> Result<Self, Error> {
+        if tokens.is_empty() {
+            return Err(Error::GithubTokenPoolEmpty);
+        }
 
         let mut manager = TokenManager {
             active: tokens[0].clone(),
-            pool: tokens,
-            request_handler: Box::new(RequestHandler::new()),
+            pool: tokens.clone(),
+            configured: tokens,
+            last_reload: Instant::now(),
+            reload_from_env: false,
+            last_quota_report: None,
+            consumer,
+            reserved,
+            request_handler: Box::new(request_handler),
+            ratelimit_url: format!("{}/rate_limit", base_url.trim_end_matches('/')),
         };
         manager.cleanup()?; // Make sure we have only valid tokens before returning the TokenManager
 
@@ -58,27 +145,28 @@ impl TokenManager {
     }
 
     /// Finds and replaces the active GitHub token with one that has more remaining API calls.
-    /// If none can be found, that is all tokens are drained, this method will sleep for
-    /// [`SLEEP_DURATION_TOKENS_DRAINED`] minutes.
+    /// If none can be found, that is all tokens are drained, this method will sleep until the earliest
+    /// ratelimit reset of all tokens.
     pub fn refresh(&mut self) -> Result<(), Error> {
         if let Ok(ratelimit) = self.execute(&self.active) {
             if ratelimit.search.remaining == 0 {
-                // The search ratelimit resets every minute, as such we can sleep for one minute
+                // The search ratelimit resets every minute, as such we can sleep until its reset
                 // instead of hotswapping the active token. This makes the method much more readable
                 // and has less of an overhead.
                 // See the docs for the differences between the core and search ratelimit:
                 // https://docs.github.com/en/rest/overview/resources-in-the-rest-api#rate-limiting
                 // https://docs.github.com/en/rest/reference/search#rate-limit
-                info!("Github search ratelimit drained, sleeping one minute to reset");
-                std::thread::sleep(std::time::Duration::from_secs(60));
+                let duration = seconds_until_reset(ratelimit.search.reset, Utc::now().timestamp());
+                info!("Github search ratelimit drained, sleeping {duration} seconds to reset");
+                std::thread::sleep(std::time::Duration::from_secs(duration));
                 return Ok(());
             }
         }
 
-        let mut valid_tokens: Vec<(&str, usize)> = Vec::new();
+        let mut valid_tokens: Vec<(&str, usize, i64)> = Vec::new();
         for token in &self.pool {
             if let Ok(ratelimit) = self.execute(token) {
-                valid_tokens.push((token, ratelimit.core.remaining));
+                valid_tokens.push((token, ratelimit.core.remaining, ratelimit.core.reset));
             }
         }
 
@@ -86,6 +174,8 @@ impl TokenManager {
             return Err(Error::GithubTokenPoolEmpty);
         }
 
+        GITHUB_TOKEN_BUDGET.set(valid_tokens.iter().map(|token| token.1 as i64).sum());
+
         let mut best = &valid_tokens[0];
         for token in &valid_tokens {
             if token.1 > best.1 {
@@ -95,8 +185,11 @@ impl TokenManager {
 
         match best.1 {
             0 => {
-                info!("All github tokens drained, sleeping {SLEEP_DURATION_TOKENS_DRAINED} seconds");
-                std::thread::sleep(std::time::Duration::from_secs(SLEEP_DURATION_TOKENS_DRAINED));
+                let earliest_reset = valid_tokens.iter().map(|token| token.2).min().unwrap();
+                let duration = seconds_until_reset(earliest_reset, Utc::now().timestamp());
+
+                info!("All github tokens drained, sleeping {duration} seconds until the earliest reset");
+                std::thread::sleep(std::time::Duration::from_secs(duration));
             }
             _ => {
                 info!("Replacing activen github token {} with {}", self.active, best.0);
@@ -129,20 +222,130 @@ impl TokenManager {
         // Replace the activen token in case it _might_ have been removed from the pool
         info!("Replacing active github token {} with {}", self.active, self.pool[0]);
         self.active = self.pool[0].to_string();
+        BUDGET.configure(self.pool.len(), self.reserved);
+
         Ok(())
     }
 
+    /// Re-reads the token pool from `.env` if [`TOKEN_RELOAD_INTERVAL`] elapsed since the last reload. If
+    /// the configured tokens changed the pool is replaced and cleaned up, keeping the previous pool if none
+    /// of the new tokens are valid.
+    pub fn reload_if_due(&mut self) {
+        if !self.reload_from_env || self.last_reload.elapsed() < TOKEN_RELOAD_INTERVAL {
+            return;
+        }
+        self.last_reload = Instant::now();
+
+        let tokens = match Config::read_github_tokens() {
+            Ok(tokens) => tokens,
+            Err(why) => {
+                warn!("Failed to re-read github tokens, keeping the current token pool; {why}");
+                return;
+            }
+        };
+
+        if tokens == self.configured {
+            return;
+        }
+
+        info!("Github tokens changed ({} -> {}), reloading token pool", self.configured.len(), tokens.len());
+        let prev_pool = std::mem::replace(&mut self.pool, tokens.clone());
+        match self.cleanup() {
+            Ok(()) => self.configured = tokens,
+            Err(why) => {
+                warn!("Keeping the previous github token pool as the reloaded one is unusable; {why}");
+                self.pool = prev_pool;
+            }
+        }
+    }
+
+    /// Returns the remaining quota of all valid tokens within the pool, updating the per-token metrics.
+    pub fn quotas(&self) -> Vec<TokenQuota> {
+        let mut quotas = Vec::new();
+
+        for token in &self.pool {
+            if let Ok(ratelimit) = self.execute(token) {
+                let quota = TokenQuota {
+                    token: mask_token(token),
+                    core_limit: ratelimit.core.limit,
+                    core_remaining: ratelimit.core.remaining,
+                    core_reset: Utc.timestamp(ratelimit.core.reset, 0),
+                    search_limit: ratelimit.search.limit,
+                    search_remaining: ratelimit.search.remaining,
+                };
+
+                GITHUB_TOKEN_REMAINING
+                    .with_label_values(&[&quota.token, "core"])
+                    .set(quota.core_remaining as i64);
+                GITHUB_TOKEN_REMAINING
+                    .with_label_values(&[&quota.token, "search"])
+                    .set(quota.search_remaining as i64);
+
+                quotas.push(quota);
+            }
+        }
+
+        quotas
+    }
+
+    /// Reports the remaining quota of all tokens (see [`TokenManager::quotas`]) if [`QUOTA_REPORT_INTERVAL`]
+    /// elapsed since the last report, warning if the summed remaining quota is running low.
+    pub fn report_quotas_if_due(&mut self) {
+        if self.last_quota_report.map_or(false, |x| x.elapsed() < QUOTA_REPORT_INTERVAL) {
+            return;
+        }
+        self.last_quota_report = Some(Instant::now());
+
+        let quotas = self.quotas();
+        let limit: usize = quotas.iter().map(|x| x.core_limit).sum();
+        let remaining: usize = quotas.iter().map(|x| x.core_remaining).sum();
+        GITHUB_TOKEN_BUDGET.set(remaining as i64);
+
+        if remaining * 100 < limit * QUOTA_WARNING_THRESHOLD {
+            warn!("Github token budget low ({remaining} / {limit} calls remaining), consider adding tokens");
+        }
+    }
+
+    /// Accounts one request to this token managers consumer, blocking until the shared budget allows it.
+    pub fn acquire(&self) {
+        BUDGET.acquire(self.consumer);
+    }
+
+    /// Returns one previously acquired request to the shared budget, e.g. for `304 Not Modified` responses
+    /// which don't count against GitHub's ratelimit.
+    pub fn refund(&self) {
+        BUDGET.refund(self.consumer);
+    }
+
     fn execute(&self, token: &str) -> Result<RatelimitObject, Error> {
         Ok(self
             .request_handler
-            .execute_deser_token::<TokenManagerResponseHandler, RatelimitRoot>(GITHUB_RATELIMIT_URL, token)?
+            .execute_deser_token::<TokenManagerResponseHandler, RatelimitRoot>(&self.ratelimit_url, token)?
             .resources)
     }
 }
 
+/// Returns `token` with all but its last four characters masked, such that it can be logged / exported.
+fn mask_token(token: &str) -> String {
+    let visible = token.chars().rev().take(4).collect::<Vec<char>>();
+    format!("***{}", visible.into_iter().rev().collect::<String>())
+}
+
+/// Returns the seconds from `now` until `reset` (both UTC epoch seconds) plus [`RESET_SLEEP_MARGIN`].
+fn seconds_until_reset(reset: i64, now: i64) -> u64 {
+    (reset - now).max(0) as u64 + RESET_SLEEP_MARGIN
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::api::github::budget::GithubConsumer;
+    use crate::api::github::token::mask_token;
+    use crate::api::github::token::seconds_until_reset;
     use crate::api::github::token::TokenManager;
+    use crate::api::github::GITHUB_BASE_URL;
+    use crate::api::github::token::RESET_SLEEP_MARGIN;
+    use crate::api::RequestHandler;
+    use crate::api::RequestPolicy;
     use crate::error::Error;
     use reqwest::blocking::Client;
     use reqwest::StatusCode;
@@ -153,7 +356,7 @@ mod tests {
 
     #[test]
     fn refresh() {
-        let mut token_manager = TokenManager::new().unwrap();
+        let mut token_manager = TokenManager::new(GithubConsumer::Crawler).unwrap();
         assert!(token_manager.pool.len() >= 3, "Need at least 3 valid github tokens");
         token_manager.pool.truncate(3); // 3 tokens are more than plenty for this test
 
@@ -195,7 +398,7 @@ mod tests {
 
     #[test]
     fn cleanup_every_token_valid() {
-        let mut token_manager = TokenManager::new().unwrap();
+        let mut token_manager = TokenManager::new(GithubConsumer::Crawler).unwrap();
         assert!(token_manager.pool.len() >= 3, "Need at least 3 valid github tokens");
 
         // Check if all tokens are valid
@@ -211,7 +414,7 @@ mod tests {
 
     #[test]
     fn cleanup_every_token_valid_but_one() {
-        let mut token_manager = TokenManager::new().unwrap();
+        let mut token_manager = TokenManager::new(GithubConsumer::Crawler).unwrap();
         assert!(token_manager.pool.len() >= 3, "Need at least 3 valid github tokens");
 
         // Check if all tokens are valid
@@ -229,7 +432,7 @@ mod tests {
 
     #[test]
     fn cleanup_every_token_invalid() {
-        let mut token_manager = TokenManager::new().unwrap();
+        let mut token_manager = TokenManager::new(GithubConsumer::Crawler).unwrap();
 
         token_manager.pool.clear();
         token_manager.pool.push(INVALID_TOKEN_0.to_string());
@@ -246,7 +449,7 @@ mod tests {
 
     #[test]
     fn cleanup_every_token_invalid_but_one() {
-        let mut token_manager = TokenManager::new().unwrap();
+        let mut token_manager = TokenManager::new(GithubConsumer::Crawler).unwrap();
         assert!(token_manager.pool.len() >= 3, "Need at least 3 valid github tokens");
 
         // Check if all tokens are valid
@@ -264,6 +467,54 @@ mod tests {
         assert_eq!(token_manager.pool.len(), 1);
     }
 
+    #[test]
+    fn with_tokens() {
+        let with_tokens = |tokens: Vec<String>| {
+            let request_handler = RequestHandler::with_options(RequestPolicy::default(), None).unwrap();
+            let consumer = GithubConsumer::Crawler;
+            TokenManager::with_tokens(tokens, consumer, [0, 0], request_handler, GITHUB_BASE_URL)
+        };
+
+        let result = with_tokens(vec![]);
+        assert_eq!(result.err().unwrap().to_string(), Error::GithubTokenPoolEmpty.to_string());
+
+        let result = with_tokens(vec![INVALID_TOKEN_0.to_string(), INVALID_TOKEN_1.to_string()]);
+        assert_eq!(result.err().unwrap().to_string(), Error::GithubTokenPoolEmpty.to_string());
+
+        // Tokens passed directly are never reloaded from `.env`
+        let tokens = TokenManager::new(GithubConsumer::Crawler).unwrap().pool;
+        let token_manager = with_tokens(tokens.clone()).unwrap();
+        assert_eq!(token_manager.pool, tokens);
+        assert!(!token_manager.reload_from_env);
+    }
+
+    #[test]
+    fn quotas() {
+        let token_manager = TokenManager::new(GithubConsumer::Crawler).unwrap();
+        let quotas = token_manager.quotas();
+
+        assert_eq!(quotas.len(), token_manager.pool.len());
+        for quota in quotas {
+            assert!(quota.token.starts_with("***"));
+            assert!(quota.core_remaining <= quota.core_limit);
+            assert!(quota.search_remaining <= quota.search_limit);
+        }
+    }
+
+    #[test]
+    fn mask() {
+        assert_eq!(mask_token(INVALID_TOKEN_0), "***LID0");
+        assert_eq!(mask_token("abc"), "***abc");
+    }
+
+    #[test]
+    fn seconds_until_reset_with_margin() {
+        assert_eq!(seconds_until_reset(1_000_060, 1_000_000), 60 + RESET_SLEEP_MARGIN);
+
+        // Reset already passed, e.g. because of clock drift
+        assert_eq!(seconds_until_reset(1_000_000, 1_000_060), RESET_SLEEP_MARGIN);
+    }
+
     fn get_status_code_on_ratelimit_endpoint(token: &str) -> StatusCode {
         let http_client = Client::builder().user_agent("Etherface").build().unwrap();
         http_client.get("https://api.github.com/rate_limit").bearer_auth(token).send().unwrap().status()
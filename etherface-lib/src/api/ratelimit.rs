@@ -0,0 +1,93 @@
+//! Token-bucket rate limiter.
+//!
+//! Used by API clients whose upstream enforces a fixed number of requests per second (e.g. Etherscan with 5
+//! requests / second per API key). The bucket holds up to `capacity` tokens which are refilled at a constant
+//! rate; every request consumes one token and blocks until one is available. Because the bucket is guarded by
+//! a mutex it can be shared between multiple worker threads, keeping their combined request rate within the
+//! upstream limit.
+
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+pub(crate) struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Returns a new, initially full, token bucket.
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        TokenBucket {
+            capacity: capacity as f64,
+            refill_per_sec,
+            state: Mutex::new(BucketState {
+                tokens: capacity as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Consumes one token if available, otherwise returns the duration until the next token is available.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(Duration::from_secs_f64((1.0 - state.tokens) / self.refill_per_sec))
+    }
+
+    /// Consumes one token, blocking the current thread until one is available.
+    pub fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::ratelimit::TokenBucket;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[test]
+    fn burst_up_to_capacity() {
+        let bucket = TokenBucket::new(3, 1.0);
+
+        assert!(bucket.try_acquire().is_ok());
+        assert!(bucket.try_acquire().is_ok());
+        assert!(bucket.try_acquire().is_ok());
+
+        // Bucket drained, the next token is available in roughly one second
+        let wait = bucket.try_acquire().unwrap_err();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn acquire_blocks_until_refilled() {
+        let bucket = TokenBucket::new(1, 10.0);
+
+        let start = Instant::now();
+        for _ in 0..4 {
+            bucket.acquire();
+        }
+
+        // One token initially available, the remaining three are refilled every 100ms
+        assert!(start.elapsed() >= Duration::from_millis(290));
+    }
+}
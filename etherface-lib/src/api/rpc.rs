@@ -0,0 +1,306 @@
+//! Ethereum JSON-RPC client.
+//!
+//! Currently only covers `eth_getTransactionByHash`, `eth_getTransactionReceipt` and `eth_call` (all we
+//! really need to decode a transaction), see <https://ethereum.org/en/developers/docs/apis/json-rpc/>.
+use crate::error::Error;
+use ethabi::ethereum_types::U256;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use serde_json::Value;
+use std::time::Duration;
+
+use super::RequestHandler;
+use super::RequestPolicy;
+use super::RpcResponseHandler;
+
+pub struct RpcClient {
+    request_handler: RequestHandler,
+    url: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransaction {
+    pub hash: String,
+    pub from: String,
+
+    /// `None` for contract creations.
+    pub to: Option<String>,
+
+    /// Hex encoded quantity of wei.
+    pub value: String,
+    pub gas: String,
+    pub input: String,
+
+    /// `None` for pending transactions.
+    pub block_number: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcReceipt {
+    /// `0x1` if the transaction succeeded, `0x0` if it reverted; `None` for pre-Byzantium transactions.
+    pub status: Option<String>,
+    pub logs: Vec<RpcLog>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RpcLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+
+    /// Revert data of failed `eth_call`s, a hex string for most nodes.
+    #[serde(default)]
+    data: Value,
+}
+
+impl RpcReceipt {
+    pub fn is_reverted(&self) -> bool {
+        self.status.as_deref() == Some("0x0")
+    }
+}
+
+impl RpcClient {
+    /// Returns a new client of the JSON-RPC endpoint at `url`.
+    pub fn new(url: &str) -> Result<Self, Error> {
+        Ok(RpcClient {
+            request_handler: RequestHandler::new()?,
+            url: url.to_string(),
+        })
+    }
+
+    /// Returns a new client of the JSON-RPC endpoint at `url` with the given timeouts and retry policy,
+    /// routing all requests through `proxy` if present. Unlike [`RpcClient::new`] this doesn't rely on
+    /// `.env`.
+    pub fn with_options(url: &str, policy: RequestPolicy, proxy: Option<String>) -> Result<Self, Error> {
+        Ok(RpcClient {
+            request_handler: RequestHandler::with_options(policy, proxy)?,
+            url: url.to_string(),
+        })
+    }
+
+    /// Returns a new client of the JSON-RPC endpoint at `url` which fails after `timeout` rather than
+    /// retrying failed requests or error responses, e.g. for calls made while answering REST API requests.
+    pub fn without_retries(url: &str, timeout: Duration, proxy: Option<String>) -> Result<Self, Error> {
+        let policy = RequestPolicy {
+            connect_timeout: timeout,
+            timeout,
+            max_retries: 1,
+            backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        };
+
+        Ok(RpcClient {
+            request_handler: RequestHandler::with_options(policy, proxy)?.without_response_retries(),
+            url: url.to_string(),
+        })
+    }
+
+    /// Returns the transaction with the given `0x` prefixed hash, `None` if the node doesn't know it.
+    pub fn transaction(&self, hash: &str) -> Result<Option<RpcTransaction>, Error> {
+        self.call("eth_getTransactionByHash", json!([hash]))
+    }
+
+    /// Returns the receipt of the transaction with the given `0x` prefixed hash, `None` if it's pending.
+    pub fn receipt(&self, hash: &str) -> Result<Option<RpcReceipt>, Error> {
+        self.call("eth_getTransactionReceipt", json!([hash]))
+    }
+
+    /// Returns the data a mined `transaction` reverted with by replaying it on top of its parent block.
+    /// Transactions preceding it within its block are not replayed, as such the revert data may differ if
+    /// they affected its execution. Returns `None` if the replay doesn't revert or reverts without data.
+    pub fn revert_data(&self, transaction: &RpcTransaction) -> Result<Option<Vec<u8>>, Error> {
+        let block_number = transaction.block_number.as_deref().and_then(parse_quantity);
+        let parent = match block_number {
+            Some(block_number) if block_number > 0 => format!("{:#x}", block_number - 1),
+            _ => return Ok(None),
+        };
+
+        let call = json!({
+            "from": transaction.from,
+            "to": transaction.to,
+            "gas": transaction.gas,
+            "value": transaction.value,
+            "data": transaction.input,
+        });
+
+        match self.request::<Value>("eth_call", json!([call, parent]))? {
+            Ok(_) => Ok(None),
+            Err(error) => Ok(error.data.as_str().and_then(parse_hex).filter(|x| !x.is_empty())),
+        }
+    }
+
+    /// Calls `method`, returning an [`Error::Rpc`] if the node responded with an error.
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<Option<T>, Error> {
+        match self.request(method, params)? {
+            Ok(result) => Ok(result),
+            Err(error) => Err(Error::Rpc(method.to_string(), error.code, error.message)),
+        }
+    }
+
+    fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Result<Option<T>, RpcError>, Error> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: RpcResponse<T> = self
+            .request_handler
+            .execute_deser_body::<RpcResponseHandler, _>(&self.url, &body)
+            .map_err(|why| self.without_endpoint_path(why))?;
+
+        match response.error {
+            Some(error) => Ok(Err(error)),
+            None => Ok(Ok(response.result)),
+        }
+    }
+
+    /// Reduces the endpoint URL within `error` to its origin, as providers commonly embed API keys within the
+    /// path (e.g. `https://mainnet.infura.io/v3/<key>`) which [`super::redact_url`] can't tell apart.
+    fn without_endpoint_path(&self, error: Error) -> Error {
+        let origin = match url::Url::parse(&self.url) {
+            Ok(url) => url.origin().ascii_serialization(),
+            Err(_) => "<invalid url>".to_string(),
+        };
+
+        match error {
+            Error::HttpRequest(_, why) => Error::HttpRequest(origin, why),
+            Error::HttpStatus(_, status, why) => Error::HttpStatus(origin, status, why),
+            Error::HttpResponseNotRetried(_, why) => Error::HttpResponseNotRetried(origin, why),
+            error => error,
+        }
+    }
+}
+
+/// Decodes `0x` prefixed hex data, returning `None` if it isn't valid hex.
+pub fn parse_hex(data: &str) -> Option<Vec<u8>> {
+    crate::model::decode_hash(data.strip_prefix("0x")?)
+}
+
+/// Parses a `0x` prefixed hex encoded quantity, returning `None` if it isn't valid or exceeds 64 bits.
+pub fn parse_quantity(quantity: &str) -> Option<u64> {
+    u64::from_str_radix(quantity.strip_prefix("0x")?, 16).ok()
+}
+
+/// Parses a `0x` prefixed hex encoded quantity of wei into a decimal string, as it may exceed 64 bits.
+pub fn parse_wei(quantity: &str) -> Option<String> {
+    U256::from_str_radix(quantity.strip_prefix("0x")?, 16).ok().map(|x| x.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::rpc::RpcClient;
+    use crate::api::RequestPolicy;
+    use crate::error::Error;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn client(server: &MockServer) -> RpcClient {
+        let policy = RequestPolicy {
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(60),
+            max_retries: 1,
+            backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(1),
+        };
+
+        RpcClient::with_options(&server.url("/"), policy, None).unwrap()
+    }
+
+    #[test]
+    fn transaction_receipt_and_revert_data_mocked() {
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(POST).path("/").json_body_partial(r#"{"method": "eth_getTransactionByHash"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": {
+                    "hash": "0x01",
+                    "from": "0x02",
+                    "to": null,
+                    "value": "0x0",
+                    "gas": "0x5208",
+                    "input": "0x",
+                    "blockNumber": "0x10",
+                },
+            }));
+        });
+
+        server.mock(|when, then| {
+            when.method(POST).path("/").json_body_partial(r#"{"method": "eth_getTransactionReceipt"}"#);
+            then.status(200).json_body(json!({ "jsonrpc": "2.0", "id": 1, "result": null }));
+        });
+
+        let call = server.mock(|when, then| {
+            // Replayed on top of the parent block
+            let when = when.method(POST).path("/").json_body_partial(r#"{"method": "eth_call"}"#);
+            when.body_contains(r#""0xf""#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": 3, "message": "execution reverted", "data": "0x4e487b71" },
+            }));
+        });
+
+        server.mock(|when, then| {
+            when.method(POST).path("/").json_body_partial(r#"{"method": "eth_chainId"}"#);
+            then.status(200).json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": -32601, "message": "method not found" },
+            }));
+        });
+
+        let rpc = client(&server);
+        let transaction = rpc.transaction("0x01").unwrap().unwrap();
+        assert_eq!(transaction.to, None);
+        assert_eq!(transaction.block_number.as_deref(), Some("0x10"));
+        assert!(rpc.receipt("0x01").unwrap().is_none());
+
+        assert_eq!(rpc.revert_data(&transaction).unwrap(), Some(vec![0x4e, 0x48, 0x7b, 0x71]));
+        call.assert();
+
+        match rpc.call::<String>("eth_chainId", json!([])) {
+            Err(Error::Rpc(method, code, _)) => {
+                assert_eq!(method, "eth_chainId");
+                assert_eq!(code, -32601);
+            }
+
+            _ => panic!("Expected an Error::Rpc"),
+        }
+    }
+
+    #[test]
+    fn without_retries_fails_fast_hiding_the_endpoint_path() {
+        let server = MockServer::start();
+        let unavailable = server.mock(|when, then| {
+            when.method(POST).path("/v3/secret");
+            then.status(503);
+        });
+
+        let url = server.url("/v3/secret");
+        let rpc = RpcClient::without_retries(&url, Duration::from_secs(5), None).unwrap();
+        match rpc.transaction("0x01") {
+            Err(why @ Error::HttpResponseNotRetried(..)) => assert!(!why.to_string().contains("secret")),
+            _ => panic!("Expected an Error::HttpResponseNotRetried"),
+        }
+
+        unavailable.assert_hits(1);
+    }
+}
@@ -0,0 +1,362 @@
+//! Webhook client, POSTing JSON payloads to URLs registered by REST API consumers (see
+//! `etherface/src/notifier.rs` and `etherface/src/dispatcher.rs`).
+//!
+//! Each call is attempted once (retrying connection errors only), as failed calls are retried with a backoff
+//! by the caller rather than blocking it for unreachable webhooks.
+//!
+//! Calls to webhooks registered with a secret are signed within the [`SIGNATURE_HEADER`] in the form of
+//! `t=<unix timestamp>,v1=<signature>`, where the signature is the hex encoded HMAC-SHA256 of
+//! `<unix timestamp>.<body>` keyed with the secret. Consumers should verify it and reject old timestamps.
+//!
+//! As webhooks are registered by any API consumer but called from within the deployments network, only hosts
+//! resolving to public addresses are called (see [`is_valid_url`]). This is checked both when registering a
+//! webhook and before each call, as the hosts DNS records may change in between. Each call connects to the
+//! addresses checked right before rather than resolving the host again, such that its DNS records can't be
+//! changed in between either (DNS rebinding). Redirects aren't followed.
+use crate::config::Config;
+use crate::error::Error;
+use chrono::Utc;
+use hmac::Hmac;
+use hmac::Mac;
+use rand::Rng;
+use serde_json::Value;
+use sha2::Sha256;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use url::Url;
+
+use super::RequestHandler;
+use super::RequestPolicy;
+use super::WebhookResponseHandler;
+
+/// Header holding the signature of signed calls, see [`sign`].
+pub const SIGNATURE_HEADER: &str = "X-Etherface-Signature";
+
+/// Prefix of all webhook secrets, making them recognizable e.g. for secret scanners.
+pub const SECRET_PREFIX: &str = "whsec_";
+
+pub struct WebhookClient {
+    request_handler: RequestHandler,
+
+    /// Whether or not hosts resolving to non-public addresses are called, see
+    /// [`WebhookClient::allow_private_hosts`].
+    allow_private_hosts: bool,
+}
+
+impl WebhookClient {
+    pub fn new() -> Result<Self, Error> {
+        let policy = RequestPolicy {
+            max_retries: 1,
+            ..RequestPolicy::from_config(&Config::new()?)
+        };
+
+        Self::with_options(policy, None)
+    }
+
+    /// Returns a new client with the given timeouts and retry policy, routing all requests through `proxy` if
+    /// present. Unlike [`WebhookClient::new`] this doesn't rely on `.env`.
+    pub fn with_options(policy: RequestPolicy, proxy: Option<String>) -> Result<Self, Error> {
+        Ok(WebhookClient {
+            request_handler: RequestHandler::with_options(policy, proxy)?.without_redirects()?,
+            allow_private_hosts: false,
+        })
+    }
+
+    /// Also calls hosts resolving to loopback, private or link-local addresses, e.g. a local test server.
+    pub fn allow_private_hosts(self) -> Self {
+        WebhookClient {
+            allow_private_hosts: true,
+            ..self
+        }
+    }
+
+    /// POSTs `body` to `url`, returning an [`Error::HttpStatus`] if it didn't respond with a 2xx status or an
+    /// [`Error::WebhookHostForbidden`] if its host doesn't resolve to public addresses only.
+    pub fn post(&self, url: &str, body: &Value) -> Result<(), Error> {
+        self.pinned(url)?.execute_resp_body::<WebhookResponseHandler>(url, None, body)?;
+        Ok(())
+    }
+
+    /// Same as [`WebhookClient::post`], signing `body` with `secret`.
+    pub fn post_signed(&self, url: &str, body: &Value, secret: &str) -> Result<(), Error> {
+        let request_handler = self.pinned(url)?;
+
+        // The request body is serialized by `serde_json::to_vec` just like here, as such it's what's signed
+        let signature = sign(secret, Utc::now().timestamp(), &serde_json::to_string(body)?);
+
+        let header = Some((SIGNATURE_HEADER, signature.as_str()));
+        request_handler.execute_resp_body::<WebhookResponseHandler>(url, header, body)?;
+        Ok(())
+    }
+
+    /// Resolves the host of `url` and returns a request handler connecting to its addresses only, if they're
+    /// all public.
+    fn pinned(&self, url: &str) -> Result<RequestHandler, Error> {
+        let forbidden = || Error::WebhookHostForbidden(url.to_string());
+
+        let (url, addresses) = match self.allow_private_hosts {
+            true => resolve(url).ok_or_else(forbidden)?,
+            false => resolve_public(url).ok_or_else(forbidden)?,
+        };
+
+        self.request_handler.pinned_to(url.host_str().ok_or_else(forbidden)?, &addresses)
+    }
+}
+
+/// Returns a new random secret deliveries to a webhook are signed with.
+pub fn generate_secret() -> String {
+    let random: [u8; 32] = rand::thread_rng().gen();
+    let random: String = random.iter().map(|x| format!("{x:02x}")).collect();
+
+    format!("{SECRET_PREFIX}{random}")
+}
+
+/// Returns the value of the [`SIGNATURE_HEADER`] of a call with the given `body` at `timestamp`.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+
+    let signature: String = mac.finalize().into_bytes().iter().map(|x| format!("{x:02x}")).collect();
+    format!("t={timestamp},v1={signature}")
+}
+
+/// Returns whether `url` may be registered as a webhook, i.e. is an absolute `https` URL whose host resolves to
+/// public addresses only (see [`is_public_address`]). Note that this blocks while resolving the host.
+pub fn is_valid_url(url: &str) -> bool {
+    resolve_public(url).is_some()
+}
+
+/// Returns the parsed `url` alongside the addresses its host resolves to, if it's valid (see
+/// [`is_valid_url`]).
+fn resolve_public(url: &str) -> Option<(Url, Vec<SocketAddr>)> {
+    let (url, addresses) = resolve(url)?;
+    match url.scheme() == "https" && addresses.iter().all(|x| is_public_address(x.ip())) {
+        true => Some((url, addresses)),
+        false => None,
+    }
+}
+
+/// Returns the parsed `url` alongside the addresses its host resolves to, `None` if it doesn't resolve.
+fn resolve(url: &str) -> Option<(Url, Vec<SocketAddr>)> {
+    let url = Url::parse(url).ok().filter(|x| x.host_str().is_some())?;
+    let addresses = url.socket_addrs(|| None).ok().filter(|x| !x.is_empty())?;
+
+    Some((url, addresses))
+}
+
+/// Returns whether `address` is publicly routable, i.e. not a loopback, private, link-local, unique-local,
+/// shared, multicast or otherwise reserved address. IPv4-mapped IPv6 addresses are checked as IPv4 addresses,
+/// whereas IPv6 addresses embedding IPv4 addresses otherwise (e.g. NAT64 or 6to4) aren't public, as they
+/// might be translated to any IPv4 address.
+pub fn is_public_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let [a, b, ..] = address.octets();
+
+            !(address.is_unspecified()
+                || address.is_loopback()
+                || address.is_private()
+                || address.is_link_local()
+                || address.is_broadcast()
+                || address.is_documentation()
+                || address.is_multicast()
+                || a == 0 // "This network", 0.0.0.0/8
+                || (a == 100 && (64..128).contains(&b)) // Shared address space, 100.64.0.0/10
+                || a >= 240) // Reserved, 240.0.0.0/4
+        }
+
+        IpAddr::V6(address) => {
+            if let Some(mapped) = address.to_ipv4_mapped() {
+                return is_public_address(IpAddr::V4(mapped));
+            }
+
+            let [segment, second, ..] = address.segments();
+            !(address.is_unspecified()
+                || address.is_loopback()
+                || address.is_multicast()
+                || address.to_ipv4().is_some() // IPv4-compatible, ::a.b.c.d
+                || (segment & 0xfe00) == 0xfc00 // Unique-local, fc00::/7
+                || (segment & 0xffc0) == 0xfe80 // Link-local, fe80::/10
+                || (segment == 0x0064 && second == 0xff9b) // NAT64, 64:ff9b::/96 and 64:ff9b:1::/48
+                || (segment == 0x2001 && second == 0x0000) // Teredo, 2001::/32
+                || segment == 0x2002 // 6to4, 2002::/16
+                || (segment == 0x2001 && second == 0x0db8)) // Documentation, 2001:db8::/32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::webhook::is_public_address;
+    use crate::api::webhook::is_valid_url;
+    use crate::api::webhook::sign;
+    use crate::api::webhook::WebhookClient;
+    use crate::api::webhook::SIGNATURE_HEADER;
+    use crate::api::RequestPolicy;
+    use crate::api::WebhookResponseHandler;
+    use crate::error::Error;
+    use httpmock::prelude::*;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn client_policy() -> RequestPolicy {
+        RequestPolicy {
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(60),
+            max_retries: 1,
+            backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+
+    /// Returns a client calling the local mock server.
+    fn client() -> WebhookClient {
+        WebhookClient::with_options(client_policy(), None).unwrap().allow_private_hosts()
+    }
+
+    #[test]
+    fn post_accepts_any_success_status() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/hook").json_body(json!({ "hash": "a9059cbb" }));
+            then.status(204);
+        });
+
+        client().post(&server.url("/hook"), &json!({ "hash": "a9059cbb" })).unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn post_does_not_retry_error_statuses() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(503).body("maintenance");
+        });
+
+        match client().post(&server.url("/hook"), &json!({})) {
+            Err(Error::HttpStatus(_, status, body)) => {
+                assert_eq!(status, 503);
+                assert_eq!(body, "maintenance");
+            }
+            _ => panic!("Expected a HTTP status error"),
+        }
+        mock.assert_hits(1);
+    }
+
+    #[test]
+    fn post_refuses_private_hosts() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(200);
+        });
+
+        let client = WebhookClient::with_options(client_policy(), None).unwrap();
+        match client.post(&server.url("/hook"), &json!({})) {
+            Err(Error::WebhookHostForbidden(_)) => (),
+            _ => panic!("Expected a forbidden host error"),
+        }
+        mock.assert_hits(0);
+    }
+
+    #[test]
+    fn post_does_not_follow_redirects() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(302).header("Location", "http://169.254.169.254/latest/meta-data/");
+        });
+
+        match client().post(&server.url("/hook"), &json!({})) {
+            Err(Error::HttpStatus(_, status, _)) => assert_eq!(status, 302),
+            _ => panic!("Expected a HTTP status error"),
+        }
+        mock.assert_hits(1);
+    }
+
+    #[test]
+    fn pinned_to_connects_without_resolving() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(200);
+        });
+
+        // `.invalid` never resolves, as such the request can only reach the pinned address
+        let url = format!("http://webhook.invalid:{}/hook", server.port());
+        let client = client();
+        let request_handler = client.request_handler.pinned_to("webhook.invalid", &[*server.address()]);
+        request_handler.unwrap().execute_resp_body::<WebhookResponseHandler>(&url, None, &json!({})).unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn post_signed_sends_signature_header() {
+        let server = MockServer::start();
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/hook").header_exists(SIGNATURE_HEADER);
+            then.status(200);
+        });
+
+        client().post_signed(&server.url("/hook"), &json!({ "hash": "a9059cbb" }), "secret").unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn sign_hmac_sha256() {
+        // echo -n '1667000000.{}' | openssl dgst -sha256 -hmac secret
+        assert_eq!(
+            sign("secret", 1667000000, "{}"),
+            "t=1667000000,v1=19bc07c6e5d4c45107ce14aa35197a229b29c550b45acdb5cefcd4a7af17b68a"
+        );
+    }
+
+    #[test]
+    fn valid_urls() {
+        assert!(is_valid_url("https://1.1.1.1/hooks/etherface?token=abc"));
+        assert!(!is_valid_url("http://1.1.1.1/hook"));
+        assert!(!is_valid_url("example.com/hook"));
+        assert!(!is_valid_url("file:///etc/passwd"));
+
+        // Hosts within the deployments network
+        assert!(!is_valid_url("https://localhost/hook"));
+        assert!(!is_valid_url("https://127.0.0.1:8080/hook"));
+        assert!(!is_valid_url("https://10.0.0.1/hook"));
+        assert!(!is_valid_url("https://169.254.169.254/latest/meta-data/"));
+        assert!(!is_valid_url("https://[::1]/hook"));
+        assert!(!is_valid_url("https://[fd00::1]/hook"));
+    }
+
+    #[test]
+    fn public_addresses() {
+        assert!(is_public_address("1.1.1.1".parse().unwrap()));
+        assert!(is_public_address("2606:4700:4700::1111".parse().unwrap()));
+
+        for address in [
+            "0.0.0.0",
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "fc00::1",
+            "fd12:3456::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1",
+            "::127.0.0.1",
+            "::a00:1",
+            "64:ff9b::a9fe:a9fe",
+            "64:ff9b:1::1",
+            "2002:a9fe:a9fe::1",
+            "2001:0:4136:e378::1",
+        ] {
+            assert!(!is_public_address(address.parse().unwrap()), "{address}");
+        }
+    }
+}
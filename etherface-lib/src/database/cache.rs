@@ -0,0 +1,276 @@
+//! Least recently used cache of already inserted signatures.
+//!
+//! A handful of signatures (e.g. `transfer(address,uint256)`) occur in nearly every scraped repository and
+//! contract, as such inserting them would otherwise cost a `SELECT` followed by an `INSERT ... ON CONFLICT`
+//! of their kind mapping each and every time. The cache remembers signatures which are known to be present in
+//! the database alongside their kind mapping, keyed by their hash and kind, such that these round-trips are
+//! only made once per process. It's shared by all database clients of a process (see [`SIGNATURE_CACHE`]),
+//! with the exception of test clients which bring their own, and evicts the least recently used entries once
+//! full.
+//!
+//! Signatures inserted within a transaction are only cached once the transaction is committed (see
+//! [`PendingSignatures`]), as otherwise other clients could reference rows which were rolled back.
+
+use crate::config::DEFAULT_SIGNATURE_CACHE_CAPACITY;
+use crate::model::Signature;
+use crate::model::SignatureKind;
+use lazy_static::lazy_static;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+lazy_static! {
+    /// Signature cache shared by all database clients of this process.
+    pub(crate) static ref SIGNATURE_CACHE: Arc<SignatureCache> =
+        Arc::new(SignatureCache::new(DEFAULT_SIGNATURE_CACHE_CAPACITY));
+}
+
+type Key = (String, SignatureKind);
+
+pub(crate) struct SignatureCache {
+    state: Mutex<SignatureCacheState>,
+}
+
+struct SignatureCacheState {
+    capacity: usize,
+
+    /// Cached signatures alongside the tick they were last used at.
+    entries: HashMap<Key, (Signature, u64)>,
+
+    /// Keys ordered by the tick they were last used at, used to evict the least recently used entry.
+    recency: BTreeMap<u64, Key>,
+
+    tick: u64,
+}
+
+impl SignatureCache {
+    /// Returns a new cache holding at most `capacity` signatures.
+    pub fn new(capacity: usize) -> Self {
+        SignatureCache {
+            state: Mutex::new(SignatureCacheState {
+                capacity,
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    /// Updates the maximum number of cached signatures, evicting the least recently used ones if necessary.
+    pub fn configure(&self, capacity: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = capacity;
+        state.evict();
+    }
+
+    /// Returns the cached signature with the given hash and kind, if any.
+    pub fn get(&self, hash: &str, kind: SignatureKind) -> Option<Signature> {
+        let mut state = self.state.lock().unwrap();
+        let key = (hash.to_string(), kind);
+
+        let tick = state.next_tick();
+        let (signature, last_used) = state.entries.get_mut(&key)?;
+        let (signature, last_used) = (signature.clone(), std::mem::replace(last_used, tick));
+
+        state.recency.remove(&last_used);
+        state.recency.insert(tick, key);
+        Some(signature)
+    }
+
+    /// Caches `signature` as being present in the database with the given kind.
+    pub fn insert(&self, signature: &Signature, kind: SignatureKind) {
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+
+        let key = (signature.hash.clone(), kind);
+        let tick = state.next_tick();
+        if let Some((_, last_used)) = state.entries.insert(key.clone(), (signature.clone(), tick)) {
+            state.recency.remove(&last_used);
+        }
+
+        state.recency.insert(tick, key);
+        state.evict();
+    }
+}
+
+/// Signatures inserted within the not yet committed transaction of a database client, which are added to the
+/// clients cache (usually [`SIGNATURE_CACHE`]) once the outermost transaction is committed and discarded if
+/// it's rolled back.
+pub(crate) struct PendingSignatures {
+    cache: Arc<SignatureCache>,
+
+    /// `None` outside of transactions.
+    entries: RefCell<Option<Vec<(Signature, SignatureKind)>>>,
+}
+
+/// State of [`PendingSignatures`] at the start of a (possibly nested) transaction.
+pub(crate) struct PendingSavepoint {
+    outermost: bool,
+    len: usize,
+}
+
+impl PendingSignatures {
+    /// Returns pending signatures which are added to `cache` once committed.
+    pub fn new(cache: Arc<SignatureCache>) -> Self {
+        PendingSignatures {
+            cache,
+            entries: RefCell::new(None),
+        }
+    }
+
+    /// Returns the cache signatures are added to once committed, which is also the one to look them up in.
+    pub fn cache(&self) -> &SignatureCache {
+        &self.cache
+    }
+
+    /// Caches `signature`, deferring it until the current transaction is committed if there is one.
+    pub fn insert(&self, signature: &Signature, kind: SignatureKind) {
+        match self.entries.borrow_mut().as_mut() {
+            Some(entries) => entries.push((signature.clone(), kind)),
+            None => self.cache.insert(signature, kind),
+        }
+    }
+
+    /// Marks the start of a transaction.
+    pub fn begin(&self) -> PendingSavepoint {
+        let mut entries = self.entries.borrow_mut();
+        let outermost = entries.is_none();
+        let entries = entries.get_or_insert_with(Vec::new);
+
+        PendingSavepoint {
+            outermost,
+            len: entries.len(),
+        }
+    }
+
+    /// Marks the commit of the transaction started at `savepoint`, caching all pending signatures if it was
+    /// the outermost one.
+    pub fn commit(&self, savepoint: PendingSavepoint) {
+        if !savepoint.outermost {
+            return;
+        }
+
+        for (signature, kind) in self.entries.borrow_mut().take().unwrap_or_default() {
+            self.cache.insert(&signature, kind);
+        }
+    }
+
+    /// Marks the rollback of the transaction started at `savepoint`, discarding all signatures inserted
+    /// since.
+    pub fn rollback(&self, savepoint: PendingSavepoint) {
+        let mut entries = self.entries.borrow_mut();
+        match savepoint.outermost {
+            true => *entries = None,
+            false => entries.iter_mut().for_each(|x| x.truncate(savepoint.len)),
+        }
+    }
+}
+
+impl SignatureCacheState {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let oldest = match self.recency.keys().next() {
+                Some(tick) => *tick,
+                None => return,
+            };
+
+            if let Some(key) = self.recency.remove(&oldest) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::cache::PendingSignatures;
+    use crate::database::cache::SignatureCache;
+    use crate::model::Signature;
+    use crate::model::SignatureKind;
+    use chrono::Utc;
+    use std::sync::Arc;
+
+    fn signature(id: i32, hash: &str) -> Signature {
+        Signature {
+            id,
+            text: format!("f{id}()"),
+            hash: hash.to_string(),
+            is_valid: true,
+            added_at: Utc::now(),
+            selector: Vec::new(),
+            topic0: Vec::new(),
+            usage_count: 0,
+            last_called_at: None,
+        }
+    }
+
+    #[test]
+    fn keyed_by_hash_and_kind() {
+        let cache = SignatureCache::new(2);
+        cache.insert(&signature(1, "a"), SignatureKind::Function);
+
+        assert_eq!(cache.get("a", SignatureKind::Function).unwrap().id, 1);
+        assert!(cache.get("a", SignatureKind::Event).is_none());
+        assert!(cache.get("b", SignatureKind::Function).is_none());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = SignatureCache::new(2);
+        cache.insert(&signature(1, "a"), SignatureKind::Function);
+        cache.insert(&signature(2, "b"), SignatureKind::Function);
+
+        // Using `a` makes `b` the least recently used entry
+        assert!(cache.get("a", SignatureKind::Function).is_some());
+        cache.insert(&signature(3, "c"), SignatureKind::Function);
+
+        assert!(cache.get("a", SignatureKind::Function).is_some());
+        assert!(cache.get("b", SignatureKind::Function).is_none());
+        assert!(cache.get("c", SignatureKind::Function).is_some());
+    }
+
+    #[test]
+    fn pending_signatures_are_cached_on_commit_only() {
+        let cache = Arc::new(SignatureCache::new(10));
+        let pending = PendingSignatures::new(cache.clone());
+
+        let outer = pending.begin();
+        pending.insert(&signature(-1, "pending-a"), SignatureKind::Function);
+
+        let inner = pending.begin();
+        pending.insert(&signature(-2, "pending-b"), SignatureKind::Function);
+        pending.rollback(inner);
+
+        assert!(cache.get("pending-a", SignatureKind::Function).is_none());
+        pending.commit(outer);
+        assert!(cache.get("pending-a", SignatureKind::Function).is_some());
+        assert!(cache.get("pending-b", SignatureKind::Function).is_none());
+
+        let outer = pending.begin();
+        pending.insert(&signature(-3, "pending-c"), SignatureKind::Function);
+        pending.rollback(outer);
+        assert!(cache.get("pending-c", SignatureKind::Function).is_none());
+    }
+
+    #[test]
+    fn configure_shrinks_cache() {
+        let cache = SignatureCache::new(3);
+        cache.insert(&signature(1, "a"), SignatureKind::Function);
+        cache.insert(&signature(2, "b"), SignatureKind::Function);
+        cache.insert(&signature(3, "c"), SignatureKind::Function);
+
+        cache.configure(1);
+        assert!(cache.get("a", SignatureKind::Function).is_none());
+        assert!(cache.get("b", SignatureKind::Function).is_none());
+        assert!(cache.get("c", SignatureKind::Function).is_some());
+    }
+}
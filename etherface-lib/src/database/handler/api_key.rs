@@ -0,0 +1,98 @@
+//! `api_key` table handler.
+//!
+//! Keys are random 32 byte hex strings prefixed by [`API_KEY_PREFIX`], of which only the hash (see
+//! [`hash_api_key`]) is stored such that a leaked database doesn't leak usable keys.
+
+use crate::database::schema::api_key;
+use crate::database::schema::api_key::dsl::*;
+use crate::error::Error;
+use crate::model::ApiKey;
+use crate::model::ApiKeyInsert;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use rand::Rng;
+use sha3::Digest;
+use sha3::Sha3_256;
+use std::cell::RefCell;
+
+/// Prefix of all API keys, making them recognizable e.g. for secret scanners.
+pub const API_KEY_PREFIX: &str = "ef_";
+
+pub struct ApiKeyHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> ApiKeyHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        ApiKeyHandler { connection }
+    }
+
+    /// Issues a new key to the consumer `entity_name` with the given quota, returning the stored key as well
+    /// as the key itself, which can't be recovered afterwards.
+    pub fn issue(
+        &self,
+        entity_name: &str,
+        entity_rate_limit_per_minute: i32,
+        entity_rate_limit_burst: i32,
+    ) -> Result<(ApiKey, String), Error> {
+        let random: [u8; 32] = rand::thread_rng().gen();
+        let random: String = random.iter().map(|x| format!("{x:02x}")).collect();
+        let key = format!("{API_KEY_PREFIX}{random}");
+
+        let entity = ApiKeyInsert {
+            key_hash: &hash_api_key(&key),
+            name: entity_name,
+            rate_limit_per_minute: entity_rate_limit_per_minute,
+            rate_limit_burst: entity_rate_limit_burst,
+            created_at: Utc::now(),
+        };
+
+        let entity = diesel::insert_into(api_key::table)
+            .values(&entity)
+            .get_result(&mut *self.connection.borrow_mut())?;
+
+        Ok((entity, key))
+    }
+
+    /// Revokes the key, returning whether it existed and wasn't revoked already.
+    pub fn revoke(&self, entity_id: i32) -> Result<bool, Error> {
+        let updated = diesel::update(api_key.filter(id.eq(entity_id).and(revoked_at.is_null())))
+            .set(revoked_at.eq(Utc::now()))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(updated == 1)
+    }
+
+    /// Returns all keys including revoked ones, ordered by their id.
+    pub fn get_all(&self) -> Result<Vec<ApiKey>, Error> {
+        Ok(api_key.order_by(id.asc()).get_results(&mut *self.connection.borrow_mut())?)
+    }
+}
+
+/// Returns the hex encoded SHA3-256 hash of `key`, i.e. the value stored within `api_key.key_hash`.
+pub fn hash_api_key(key: &str) -> String {
+    format!("{:x}", Sha3_256::digest(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::handler::api_key::hash_api_key;
+    use crate::database::handler::api_key::API_KEY_PREFIX;
+    use crate::database::handler::testing;
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn issue_and_revoke() {
+        let dbc = testing::client();
+
+        let (entity, key) = dbc.api_key().issue("dune", 600, 100).unwrap();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(entity.key_hash, hash_api_key(&key));
+        assert!(entity.revoked_at.is_none());
+
+        assert!(dbc.api_key().revoke(entity.id).unwrap());
+        assert!(!dbc.api_key().revoke(entity.id).unwrap());
+        assert!(dbc.api_key().get_all().unwrap()[0].revoked_at.is_some());
+    }
+}
@@ -0,0 +1,59 @@
+//! `audit_log` table handler.
+
+use crate::database::schema::audit_log;
+use crate::database::schema::audit_log::dsl::*;
+use crate::error::Error;
+use crate::model::AuditAction;
+use crate::model::AuditLog;
+use crate::model::AuditLogInsert;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct AuditLogHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> AuditLogHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        AuditLogHandler { connection }
+    }
+
+    /// Records an administrative mutation performed by `entity_actor`. Should be called within the same
+    /// transaction as the mutation itself (see `DatabaseClient::transaction`), such that no mutation goes
+    /// unrecorded.
+    pub fn insert(
+        &self,
+        entity_actor: &str,
+        entity_action: AuditAction,
+        entity_target: Option<&str>,
+        entity_details: Option<&str>,
+    ) -> Result<AuditLog, Error> {
+        let entity = AuditLogInsert {
+            actor: entity_actor,
+            action: entity_action,
+            target: entity_target,
+            details: entity_details,
+            created_at: Utc::now(),
+        };
+
+        Ok(diesel::insert_into(audit_log::table)
+            .values(&entity)
+            .get_result(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Returns the `limit` most recent entries, newest first.
+    pub fn get_latest(&self, limit: i64) -> Result<Vec<AuditLog>, Error> {
+        Ok(audit_log.order_by(id.desc()).limit(limit).get_results(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Returns the `limit` most recent entries concerning `entity_target`, newest first.
+    pub fn get_by_target(&self, entity_target: &str, limit: i64) -> Result<Vec<AuditLog>, Error> {
+        Ok(audit_log
+            .filter(target.eq(entity_target))
+            .order_by(id.desc())
+            .limit(limit)
+            .get_results(&mut *self.connection.borrow_mut())?)
+    }
+}
@@ -0,0 +1,54 @@
+//! `etherscan_bytecode_hash` table handler.
+
+use crate::database::handler::INSERT_BATCH_SIZE;
+use crate::database::schema::etherscan_bytecode_hash;
+use crate::database::schema::etherscan_bytecode_hash::dsl::*;
+use crate::error::Error;
+use crate::model::EtherscanBytecodeHash;
+use crate::model::SignatureHash;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct EtherscanBytecodeHashHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> EtherscanBytecodeHashHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        EtherscanBytecodeHashHandler { connection }
+    }
+
+    /// Stores the hashes extracted from the bytecode of the given contract, returning the number of newly
+    /// inserted hashes.
+    pub fn insert_many(&self, entity_contract_id: i32, hashes: &[SignatureHash]) -> Result<usize, Error> {
+        let entities = hashes
+            .iter()
+            .map(|x| EtherscanBytecodeHash {
+                contract_id: entity_contract_id,
+                hash: x.hash.clone(),
+                kind: x.kind,
+                added_at: Utc::now(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut inserted = 0;
+        for chunk in entities.chunks(INSERT_BATCH_SIZE) {
+            inserted += diesel::insert_into(etherscan_bytecode_hash::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        Ok(inserted)
+    }
+
+    /// Returns all hashes extracted from the bytecode of the given contract.
+    pub fn get_by_contract(&self, entity_contract_id: i32) -> Result<Vec<EtherscanBytecodeHash>, Error> {
+        Ok(etherscan_bytecode_hash
+            .filter(contract_id.eq(entity_contract_id))
+            .order_by((kind.asc(), hash.asc()))
+            .load(&mut *self.connection.borrow_mut())?)
+    }
+}
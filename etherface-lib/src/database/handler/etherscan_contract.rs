@@ -2,43 +2,226 @@
 
 use crate::database::schema::etherscan_contract;
 use crate::database::schema::etherscan_contract::dsl::*;
+use crate::error::Error;
+use crate::model::EtherscanCompilerSettings;
 use crate::model::EtherscanContract;
+use crate::model::EtherscanContractCreation;
+use chrono::DateTime;
 use chrono::Utc;
 use diesel::prelude::*;
+use diesel::upsert::excluded;
 use diesel::PgConnection;
+use std::cell::RefCell;
 
 pub struct EtherscanContractHandler<'a> {
-    connection: &'a PgConnection,
+    connection: &'a RefCell<PgConnection>,
 }
 
 impl<'a> EtherscanContractHandler<'a> {
-    pub fn new(connection: &'a PgConnection) -> Self {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
         EtherscanContractHandler { connection }
     }
 
-    pub fn insert(&self, entity: &EtherscanContract) -> EtherscanContract {
-        if let Some(row) = self.get(entity) {
-            return row;
-        }
-
-        diesel::insert_into(etherscan_contract::table)
+    /// Inserts the contract unless a contract with the same chain id and address already exists, returning
+    /// either the inserted or the existing row. Done within a single upsert such that concurrent inserts of
+    /// the same contract don't conflict.
+    pub fn insert(&self, entity: &EtherscanContract) -> Result<EtherscanContract, Error> {
+        // A no-op update rather than `DO NOTHING`, which wouldn't return the existing row
+        Ok(diesel::insert_into(etherscan_contract::table)
             .values(&entity.to_insertable())
-            .get_result(self.connection)
-            .unwrap()
+            .on_conflict((chain_id, address))
+            .do_update()
+            .set(address.eq(excluded(address)))
+            .get_result(&mut *self.connection.borrow_mut())?)
     }
 
-    fn get(&self, entity: &EtherscanContract) -> Option<EtherscanContract> {
-        etherscan_contract.filter(address.eq(&entity.address)).first(self.connection).optional().unwrap()
+    /// Returns all unscraped contracts which are due, i.e. contracts that are neither unverified nor failed
+    /// more than `max_retries` times and whose retry date, if any, has passed.
+    pub fn get_unvisited(&self, max_retries: i32) -> Result<Vec<EtherscanContract>, Error> {
+        Ok(etherscan_contract
+            .filter(
+                scraped_at
+                    .is_null()
+                    .and(is_unverified.eq(false))
+                    .and(retry_count.lt(max_retries))
+                    .and(next_retry_at.is_null().or(next_retry_at.le(Utc::now()))),
+            )
+            .order_by(is_destroyed.asc()) // Live contracts first
+            .get_results(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_unvisited(&self) -> Vec<EtherscanContract> {
-        etherscan_contract.filter(scraped_at.is_null()).get_results(self.connection).unwrap()
+    /// Returns the number of unscraped contracts which haven't been flagged as unverified, including those
+    /// whose retry date hasn't passed yet.
+    pub fn get_unscraped_count(&self) -> Result<i64, Error> {
+        Ok(etherscan_contract
+            .filter(scraped_at.is_null().and(is_unverified.eq(false)))
+            .count()
+            .get_result(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn set_visited(&self, entity: &EtherscanContract) {
-        diesel::update(etherscan_contract.filter(address.eq(&entity.address)))
+    pub fn set_visited(&self, entity: &EtherscanContract) -> Result<(), Error> {
+        diesel::update(etherscan_contract.find(entity.id))
             .set(scraped_at.eq(Utc::now()))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn set_creation(
+        &self,
+        entity: &EtherscanContract,
+        creation: &EtherscanContractCreation,
+    ) -> Result<(), Error> {
+        diesel::update(etherscan_contract.find(entity.id))
+            .set((
+                creator_address.eq(&creation.creator_address),
+                creation_tx_hash.eq(&creation.tx_hash),
+                creation_block.eq(creation.block),
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn set_compiler_settings(
+        &self,
+        entity: &EtherscanContract,
+        settings: &EtherscanCompilerSettings,
+    ) -> Result<(), Error> {
+        diesel::update(etherscan_contract.find(entity.id))
+            .set((
+                optimization_used.eq(settings.optimization_used),
+                optimization_runs.eq(settings.optimization_runs),
+                evm_version.eq(&settings.evm_version),
+                license.eq(&settings.license),
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Returns at most `limit` non-destroyed contracts whose code hasn't been checked in the last `days` days.
+    pub fn get_code_check_due(&self, days: i64, limit: i64) -> Result<Vec<EtherscanContract>, Error> {
+        Ok(etherscan_contract
+            .filter(
+                is_destroyed
+                    .eq(false)
+                    .and(code_checked_at.is_null().or(code_checked_at.lt(Utc::now() - chrono::Duration::days(days)))),
+            )
+            .order_by(id.asc())
+            .limit(limit)
+            .get_results(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn set_code_checked(&self, entity: &EtherscanContract, destroyed: bool) -> Result<(), Error> {
+        diesel::update(etherscan_contract.find(entity.id))
+            .set((is_destroyed.eq(destroyed), code_checked_at.eq(Utc::now())))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Returns all scraped contracts which were scraped with a parser version older than `version`.
+    pub fn get_scraped_with_parser_version_below(
+        &self,
+        version: i32,
+    ) -> Result<Vec<EtherscanContract>, Error> {
+        Ok(etherscan_contract
+            .filter(scraped_at.is_not_null().and(parser_version.lt(version)))
+            .get_results(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn set_parser_version(&self, entity: &EtherscanContract, version: i32) -> Result<(), Error> {
+        diesel::update(etherscan_contract.find(entity.id))
+            .set(parser_version.eq(version))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Sets the `etherscan_contract::scraped_at` field to NULL in order to re-trigger the scraping process.
+    pub fn set_scraped_to_null(&self, entity: &EtherscanContract) -> Result<(), Error> {
+        diesel::update(etherscan_contract.find(entity.id))
+            .set(scraped_at.eq::<Option<DateTime<Utc>>>(None))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Marks the contract as unverified, a terminal state in which the contract is never scraped again.
+    pub fn set_unverified(&self, entity: &EtherscanContract) -> Result<(), Error> {
+        diesel::update(etherscan_contract.find(entity.id))
+            .set(is_unverified.eq(true))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Records a failed scraping attempt, scheduling the next attempt at `retry_at`.
+    pub fn set_failed(
+        &self,
+        entity: &EtherscanContract,
+        error: &str,
+        retry_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        diesel::update(etherscan_contract.find(entity.id))
+            .set((retry_count.eq(retry_count + 1), last_error.eq(error), next_retry_at.eq(retry_at)))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::etherscan::ETHEREUM_MAINNET_CHAIN_ID;
+    use crate::database::handler::testing;
+    use crate::model::EtherscanContract;
+    use crate::parser::PARSER_VERSION;
+    use chrono::Utc;
+
+    fn contract(chain_id: i32, address: &str) -> EtherscanContract {
+        EtherscanContract {
+            id: 0,
+            address: address.to_string(),
+            name: "Token".to_string(),
+            compiler: "Solidity".to_string(),
+            compiler_version: "0.8.17".to_string(),
+            url: format!("https://etherscan.io/address/{address}"),
+            scraped_at: None,
+            added_at: Utc::now(),
+            retry_count: 0,
+            next_retry_at: None,
+            last_error: None,
+            is_unverified: false,
+            creator_address: None,
+            creation_tx_hash: None,
+            creation_block: None,
+            is_destroyed: false,
+            code_checked_at: None,
+            parser_version: PARSER_VERSION,
+            chain_id,
+            optimization_used: None,
+            optimization_runs: None,
+            evm_version: None,
+            license: None,
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn insert_deduplicates_by_chain_and_address() {
+        let dbc = testing::client();
+
+        let mainnet = contract(ETHEREUM_MAINNET_CHAIN_ID, "0xdac17f958d2ee523a2206206994597c13d831ec7");
+        let polygon = contract(137, "0xdac17f958d2ee523a2206206994597c13d831ec7");
+
+        let inserted = dbc.etherscan_contract().insert(&mainnet).unwrap();
+        let existing = dbc.etherscan_contract().insert(&mainnet).unwrap();
+        let other_chain = dbc.etherscan_contract().insert(&polygon).unwrap();
+
+        assert_eq!(inserted.id, existing.id);
+        assert_ne!(inserted.id, other_chain.id);
+        assert_eq!(dbc.etherscan_contract().get_unscraped_count().unwrap(), 2);
     }
 }
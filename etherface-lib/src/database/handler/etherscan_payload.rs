@@ -0,0 +1,109 @@
+//! `etherscan_payload` table handler.
+
+use crate::database::schema::etherscan_payload;
+use crate::database::schema::etherscan_payload::dsl::*;
+use crate::database::schema::mapping_payload_etherscan;
+use crate::error::Error;
+use crate::model::EtherscanPayload;
+use crate::model::EtherscanPayloadInsert;
+use crate::model::MappingPayloadEtherscan;
+use crate::model::PayloadKind;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use sha3::Digest;
+use sha3::Keccak256;
+use std::cell::RefCell;
+use std::io::Read;
+use std::io::Write;
+
+pub struct EtherscanPayloadHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> EtherscanPayloadHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        EtherscanPayloadHandler { connection }
+    }
+
+    /// Stores the given payload (if not already present) and maps it to the given contract. Identical payloads
+    /// (e.g. ABIs of clones and proxies) may be inserted concurrently by several scraper workers, hence
+    /// conflicts on the payloads hash are ignored in favour of the already stored payload.
+    pub fn insert(
+        &self,
+        entity_contract_id: i32,
+        entity_kind: PayloadKind,
+        entity_content: &str,
+    ) -> Result<EtherscanPayload, Error> {
+        let entity_hash = format!("{:x}", Keccak256::digest(entity_content));
+
+        let res = match self.get_by_hash(&entity_hash)? {
+            Some(val) => val,
+            None => {
+                diesel::insert_into(etherscan_payload::table)
+                    .values(&EtherscanPayloadInsert {
+                        hash: &entity_hash,
+                        kind: entity_kind,
+                        content: &compress(entity_content)?,
+                        added_at: Utc::now(),
+                    })
+                    .on_conflict(hash)
+                    .do_nothing()
+                    .execute(&mut *self.connection.borrow_mut())?;
+
+                etherscan_payload
+                    .filter(hash.eq(&entity_hash))
+                    .first(&mut *self.connection.borrow_mut())?
+            }
+        };
+
+        diesel::insert_into(mapping_payload_etherscan::table)
+            .values(&MappingPayloadEtherscan {
+                payload_id: res.id,
+                contract_id: entity_contract_id,
+            })
+            .on_conflict_do_nothing()
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(res)
+    }
+
+    /// Returns all decompressed payloads of the given kind mapped to the given contract, failing if any of them
+    /// can't be decompressed.
+    pub fn get_by_contract(
+        &self,
+        entity_contract_id: i32,
+        entity_kind: PayloadKind,
+    ) -> Result<Vec<String>, Error> {
+        Ok(etherscan_payload
+            .inner_join(mapping_payload_etherscan::table)
+            .filter(mapping_payload_etherscan::contract_id.eq(entity_contract_id).and(kind.eq(entity_kind)))
+            .select(etherscan_payload::all_columns)
+            .load::<EtherscanPayload>(&mut *self.connection.borrow_mut())?
+            .iter()
+            .map(|payload| decompress(&payload.content))
+            .collect::<Result<_, _>>()?)
+    }
+
+    fn get_by_hash(&self, entity_hash: &str) -> Result<Option<EtherscanPayload>, Error> {
+        Ok(etherscan_payload
+            .filter(hash.eq(entity_hash))
+            .first(&mut *self.connection.borrow_mut())
+            .optional()?)
+    }
+}
+
+fn compress(content: &str) -> Result<Vec<u8>, Error> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(content.as_bytes()).map_err(Error::PayloadCompression)?;
+    encoder.finish().map_err(Error::PayloadCompression)
+}
+
+fn decompress(content: &[u8]) -> Result<String, Error> {
+    let mut decompressed = String::new();
+    ZlibDecoder::new(content).read_to_string(&mut decompressed).map_err(Error::PayloadCompression)?;
+    Ok(decompressed)
+}
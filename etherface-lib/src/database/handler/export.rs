@@ -0,0 +1,278 @@
+//! Handler for exporting the public dataset as CSV and importing such exports, see
+//! `etherface/src/exporter.rs` and `etherface/src/maintenance/import.rs`.
+//!
+//! Each [`ExportSource`] is read in chunks of consecutive key ranges rather than by `OFFSET` such that
+//! exporting tables with millions of rows doesn't get progressively slower. The CSV lines themselves are
+//! formatted by the database, every value being quoted and `NULL` values being empty. Imports on the other
+//! hand are inserted as JSON arrays of objects (column name to value), converted by the database as well.
+
+use crate::database::handler::partition::PARTITIONED_TABLES;
+use crate::database::schema::signature;
+use crate::error::Error;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::BigInt;
+use diesel::sql_types::Nullable;
+use diesel::sql_types::Text;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+/// A table (or join of tables) exported into its own file.
+pub struct ExportSource {
+    /// File name of the export, without extension.
+    pub name: &'static str,
+
+    /// CSV header, i.e. the names of `columns`.
+    pub header: &'static str,
+
+    /// Exported column expressions.
+    columns: &'static str,
+
+    /// `FROM` clause, i.e. the table or tables being exported.
+    from: &'static str,
+
+    /// `WHERE` condition restricting the exported rows.
+    filter: &'static str,
+
+    /// Integer column expression the rows are chunked and ordered by.
+    key: &'static str,
+
+    /// Table the export is imported into, `None` for exports derived from other tables.
+    pub table: Option<&'static str>,
+
+    /// Columns of `table` which are not exported but have to be set when importing (prefixed by a comma),
+    /// alongside their values which may reference the exported `columns`.
+    import_columns: &'static str,
+    import_values: &'static str,
+}
+
+/// All exported tables as well as a flat `selector,text,kind` file, omitting internal bookkeeping columns
+/// such as scraping dates and errors. Ordered such that referenced tables come first, i.e. the order in
+/// which they have to be imported.
+pub const EXPORT_SOURCES: [ExportSource; 9] = [
+    ExportSource {
+        name: "github_user",
+        header: "id,login,html_url,added_at",
+        columns: "id, login, html_url, added_at",
+        from: "github_user",
+        filter: "TRUE",
+        key: "id",
+        table: Some("github_user"),
+        import_columns: ", is_deleted",
+        import_values: ", FALSE",
+    },
+    ExportSource {
+        name: "github_repository",
+        header: "id,owner_id,name,html_url,language,stargazers_count,fork,created_at,added_at",
+        columns: "id, owner_id, name, html_url, language, stargazers_count, fork, created_at, added_at",
+        from: "github_repository",
+        filter: "TRUE",
+        key: "id",
+        table: Some("github_repository"),
+        // Imported repositories are marked as scraped (as their signatures are imported as well) but not as
+        // visited, such that the crawler continues from them
+        import_columns: ", size, pushed_at, updated_at, scraped_at, is_deleted, found_by_crawling",
+        import_values: ", 0, created_at, created_at, NOW(), FALSE, FALSE",
+    },
+    ExportSource {
+        name: "etherscan_contract",
+        header: "id,chain_id,address,name,compiler,compiler_version,url,added_at",
+        columns: "id, chain_id, address, name, compiler, compiler_version, url, added_at",
+        from: "etherscan_contract",
+        filter: "TRUE",
+        key: "id",
+        table: Some("etherscan_contract"),
+        import_columns: ", scraped_at",
+        import_values: ", NOW()",
+    },
+    ExportSource {
+        name: "signature",
+        header: "id,text,hash,is_valid,added_at",
+        columns: "id, text, hash, is_valid, added_at",
+        from: "signature",
+        filter: "TRUE",
+        key: "id",
+        table: Some("signature"),
+        import_columns: ", selector, topic0",
+        import_values: ", decode(substr(hash, 1, 8), 'hex'), decode(hash, 'hex')",
+    },
+    ExportSource {
+        name: "mapping_signature_kind",
+        header: "signature_id,kind",
+        columns: "signature_id, kind",
+        from: "mapping_signature_kind",
+        filter: "TRUE",
+        key: "signature_id",
+        table: Some("mapping_signature_kind"),
+        import_columns: "",
+        import_values: "",
+    },
+    ExportSource {
+        name: "mapping_signature_github",
+        header: "signature_id,repository_id,kind,added_at,last_seen_at",
+        columns: "signature_id, repository_id, kind, added_at, last_seen_at",
+        from: "mapping_signature_github",
+        filter: "TRUE",
+        key: "signature_id",
+        table: Some("mapping_signature_github"),
+        import_columns: "",
+        import_values: "",
+    },
+    ExportSource {
+        name: "mapping_signature_etherscan",
+        header: "signature_id,contract_id,kind,added_at,last_seen_at",
+        columns: "signature_id, contract_id, kind, added_at, last_seen_at",
+        from: "mapping_signature_etherscan",
+        filter: "TRUE",
+        key: "signature_id",
+        table: Some("mapping_signature_etherscan"),
+        import_columns: "",
+        import_values: "",
+    },
+    ExportSource {
+        name: "mapping_signature_fourbyte",
+        header: "signature_id,kind,added_at,last_seen_at",
+        columns: "signature_id, kind, added_at, last_seen_at",
+        from: "mapping_signature_fourbyte",
+        filter: "TRUE",
+        key: "signature_id",
+        table: Some("mapping_signature_fourbyte"),
+        import_columns: "",
+        import_values: "",
+    },
+    ExportSource {
+        name: "selectors",
+        header: "selector,text,kind",
+        // Events are identified by their whole hash (`topic0`) rather than their first four bytes
+        columns: concat!(
+            "'0x' || encode(CASE WHEN k.kind = 'event' THEN s.topic0 ELSE s.selector END, 'hex'), ",
+            "s.text, k.kind",
+        ),
+        from: "signature s JOIN mapping_signature_kind k ON s.id = k.signature_id",
+        filter: "s.is_valid",
+        key: "s.id",
+        table: None,
+        import_columns: "",
+        import_values: "",
+    },
+];
+
+/// Tables whose `id` column is backed by a sequence, which has to be advanced past the imported ids.
+const SERIAL_TABLES: [&str; 2] = ["etherscan_contract", "signature"];
+
+#[derive(QueryableByName)]
+struct KeyRange {
+    #[diesel(sql_type = Nullable<BigInt>)]
+    min: Option<i64>,
+
+    #[diesel(sql_type = Nullable<BigInt>)]
+    max: Option<i64>,
+}
+
+#[derive(QueryableByName)]
+struct Line {
+    #[diesel(sql_type = Text)]
+    line: String,
+}
+
+pub struct ExportHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> ExportHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        ExportHandler { connection }
+    }
+
+    /// Returns the smallest and largest key of `source`, `None` if it has no rows.
+    pub fn key_range(&self, source: &ExportSource) -> Result<Option<(i64, i64)>, Error> {
+        let query = format!(
+            "SELECT MIN({key})::BIGINT AS min, MAX({key})::BIGINT AS max FROM {from} WHERE {filter}",
+            key = source.key,
+            from = source.from,
+            filter = source.filter,
+        );
+
+        let range = sql_query(query).get_result::<KeyRange>(&mut *self.connection.borrow_mut())?;
+        Ok(range.min.zip(range.max))
+    }
+
+    /// Returns the CSV lines of all rows of `source` whose key is within `start..end`, ordered by their key.
+    pub fn lines(&self, source: &ExportSource, start: i64, end: i64) -> Result<Vec<String>, Error> {
+        let query = format!(
+            "SELECT array_to_string(ARRAY(
+                SELECT COALESCE('\"' || replace(value, '\"', '\"\"') || '\"', '')
+                FROM json_array_elements_text(json_build_array({columns})) AS value
+            ), ',') AS line
+            FROM {from} WHERE {filter} AND {key} >= $1 AND {key} < $2 ORDER BY {key}",
+            columns = source.columns,
+            from = source.from,
+            filter = source.filter,
+            key = source.key,
+        );
+
+        Ok(sql_query(query)
+            .bind::<BigInt, _>(start)
+            .bind::<BigInt, _>(end)
+            .load::<Line>(&mut *self.connection.borrow_mut())?
+            .into_iter()
+            .map(|x| x.line)
+            .collect())
+    }
+
+    /// Returns whether the database contains no signatures, i.e. whether it's a fresh database.
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        Ok(signature::table
+            .select(signature::id)
+            .first::<i32>(&mut *self.connection.borrow_mut())
+            .optional()?
+            .is_none())
+    }
+
+    /// Inserts `rows` (JSON objects of the exported columns) into the table of `source`, skipping already
+    /// present rows and returning the number of inserted rows.
+    pub fn import(&self, source: &ExportSource, rows: &[serde_json::Value]) -> Result<usize, Error> {
+        let table = match source.table {
+            Some(table) => table,
+            None => return Ok(0),
+        };
+
+        let query = format!(
+            "INSERT INTO {table} ({columns}{import_columns})
+            SELECT {columns}{import_values} FROM json_populate_recordset(NULL::{table}, $1::JSON)
+            ON CONFLICT DO NOTHING",
+            columns = source.columns,
+            import_columns = source.import_columns,
+            import_values = source.import_values,
+        );
+
+        Ok(sql_query(query)
+            .bind::<Text, _>(serde_json::to_string(rows)?)
+            .execute(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Finishes an import by advancing the id sequences past the imported ids and moving the imported
+    /// mappings from the default partitions into their monthly partitions.
+    pub fn finish_import(&self) -> Result<(), Error> {
+        for table in SERIAL_TABLES {
+            sql_query(format!(
+                "SELECT setval(pg_get_serial_sequence('{table}', 'id'), MAX(id))
+                FROM {table} HAVING COUNT(*) > 0"
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        // See `create_monthly_partition`, which moves the rows of the month out of the default partition
+        for table in PARTITIONED_TABLES {
+            sql_query(format!(
+                "SELECT create_monthly_partition('{table}', month)
+                FROM (
+                    SELECT DISTINCT date_trunc('month', added_at)::DATE AS month FROM {table}_default
+                ) AS months"
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        Ok(())
+    }
+}
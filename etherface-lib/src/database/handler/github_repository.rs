@@ -1,38 +1,48 @@
 //! `github_repository` table handler.
 
+use crate::database::handler::mapping_stargazer::solidity_stargazer_ids_query;
 use crate::database::schema::github_repository;
 use crate::database::schema::github_repository::dsl::*;
+use crate::error::Error;
 use crate::model::GithubRepository;
 use crate::model::GithubRepositoryDatabase;
 use chrono::DateTime;
 use chrono::Utc;
 use diesel::prelude::*;
 use diesel::sql_query;
+use diesel::sql_types::BigInt;
 use diesel::PgConnection;
 use diesel::RunQueryDsl;
 use log::debug;
+use std::cell::RefCell;
 
 pub struct GithubRepositoryHandler<'a> {
-    connection: &'a PgConnection,
+    connection: &'a RefCell<PgConnection>,
 }
 
 impl<'a> GithubRepositoryHandler<'a> {
-    pub fn new(connection: &'a PgConnection) -> Self {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
         GithubRepositoryHandler { connection }
     }
 
-    pub fn get_total_count(&self) -> i64 {
-        github_repository.count().get_result(self.connection).unwrap()
+    pub fn get_total_count(&self) -> Result<i64, Error> {
+        Ok(github_repository.count().get_result(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn insert(&self, entity: &GithubRepository, entity_solidity_ratio: f32, by_crawling: bool) {
+    pub fn insert(
+        &self,
+        entity: &GithubRepository,
+        entity_solidity_ratio: f32,
+        by_crawling: bool,
+    ) -> Result<(), Error> {
         diesel::insert_into(github_repository::table)
             .values(&entity.to_insertable(Some(entity_solidity_ratio), by_crawling))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
-    pub fn update(&self, entity: &GithubRepository, entity_ratio: f32) {
+    pub fn update(&self, entity: &GithubRepository, entity_ratio: f32) -> Result<(), Error> {
         diesel::update(github_repository.filter(id.eq(entity.id)))
             .set((
                 name.eq(&entity.name),
@@ -43,12 +53,20 @@ impl<'a> GithubRepositoryHandler<'a> {
                 pushed_at.eq(entity.pushed_at),
                 updated_at.eq(entity.updated_at),
                 solidity_ratio.eq(Some(entity_ratio)),
+                description.eq(&entity.description),
+                topics.eq(&entity.topics),
+                license.eq(entity.license_spdx_id()),
             ))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
-    pub fn update_and_set_scraped_to_null(&self, entity: &GithubRepository, entity_solidity_ratio: f32) {
+    pub fn update_and_set_scraped_to_null(
+        &self,
+        entity: &GithubRepository,
+        entity_solidity_ratio: f32,
+    ) -> Result<(), Error> {
         diesel::update(github_repository.filter(id.eq(entity.id)))
             .set((
                 name.eq(&entity.name),
@@ -57,15 +75,19 @@ impl<'a> GithubRepositoryHandler<'a> {
                 pushed_at.eq(&entity.pushed_at),
                 updated_at.eq(&entity.updated_at),
                 solidity_ratio.eq(&entity_solidity_ratio),
+                description.eq(&entity.description),
+                topics.eq(&entity.topics),
+                license.eq(entity.license_spdx_id()),
                 visited_at.eq(Some(Utc::now())),
                 scraped_at.eq::<Option<DateTime<Utc>>>(None), // Set to NULL to trigger re-scraping
             ))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
-    pub fn get_unvisited_ordered_by_added_at(&self) -> Vec<GithubRepositoryDatabase> {
-        sql_query(
+    pub fn get_unvisited_ordered_by_added_at(&self) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        Ok(sql_query(
             "SELECT github_repository.* FROM github_repository 
             JOIN mapping_signature_github ON github_repository.id = mapping_signature_github.repository_id
             WHERE 
@@ -76,12 +98,11 @@ impl<'a> GithubRepositoryHandler<'a> {
             GROUP BY github_repository.id 
             ORDER BY github_repository.added_at DESC",
         )
-        .load(self.connection)
-        .unwrap()
+        .load(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_unvisited_ordered_by_signature_count(&self) -> Vec<GithubRepositoryDatabase> {
-        sql_query(
+    pub fn get_unvisited_ordered_by_signature_count(&self) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        Ok(sql_query(
             "SELECT github_repository.* FROM github_repository 
             JOIN mapping_signature_github ON github_repository.id = mapping_signature_github.repository_id
             WHERE 
@@ -92,64 +113,146 @@ impl<'a> GithubRepositoryHandler<'a> {
             GROUP BY github_repository.id 
             ORDER BY COUNT(*) DESC",
         )
-        .load(self.connection)
-        .unwrap()
+        .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Returns at most `limit` unvisited repositories, randomly sampled where the probability of a repository
+    /// being picked is weighted by its stargazer count.
+    pub fn get_unvisited_weighted_by_stargazers(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        // Weighted random sampling (Efraimidis-Spirakis), ordering by `-ln(u) / weight` with `u` in (0, 1]
+        self.get_unvisited_ordered_by("-LN(1.0 - RANDOM()) / (github_repository.stargazers_count + 1)", limit)
+    }
+
+    /// Returns at most `limit` unvisited repositories, ordered by the number of Solidity stargazers (see
+    /// [`crate::database::handler::mapping_stargazer::MIN_STARRED_SOLIDITY_REPOSITORIES`]) having starred
+    /// them.
+    pub fn get_unvisited_ordered_by_solidity_stargazers(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        self.get_unvisited_ordered_by(
+            &format!(
+                "(SELECT COUNT(*) FROM mapping_stargazer
+                    WHERE mapping_stargazer.repository_id = github_repository.id
+                        AND mapping_stargazer.user_id IN ({})) DESC,
+                github_repository.added_at DESC",
+                solidity_stargazer_ids_query()
+            ),
+            limit,
+        )
     }
 
-    pub fn set_ratio(&self, entity_id: i32, entity_ratio: f32) {
+    /// Returns at most `limit` randomly sampled unvisited repositories.
+    pub fn get_unvisited_random(&self, limit: i64) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        self.get_unvisited_ordered_by("RANDOM()", limit)
+    }
+
+    fn get_unvisited_ordered_by(
+        &self,
+        order: &str,
+        limit: i64,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        Ok(sql_query(format!(
+            "SELECT github_repository.* FROM github_repository
+            JOIN mapping_signature_github ON github_repository.id = mapping_signature_github.repository_id
+            WHERE
+                (github_repository.solidity_ratio > 0.0 OR github_repository.language LIKE 'Solidity')
+                AND github_repository.visited_at IS NULL
+                AND github_repository.is_deleted IS FALSE
+                AND github_repository.fork IS FALSE
+            GROUP BY github_repository.id
+            ORDER BY {order}
+            LIMIT $1"
+        ))
+        .bind::<BigInt, _>(limit)
+        .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn set_ratio(&self, entity_id: i32, entity_ratio: f32) -> Result<(), Error> {
         diesel::update(github_repository.filter(id.eq(entity_id)))
             .set(solidity_ratio.eq(entity_ratio))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
     /// Sets the `github_repository::scraped_at` field to NULL in order to re-trigger the scraping process.
-    pub fn set_scraped_to_null(&self, entity_id: i32) {
+    pub fn set_scraped_to_null(&self, entity_id: i32) -> Result<(), Error> {
         diesel::update(github_repository.filter(id.eq(entity_id)))
             .set(scraped_at.eq::<Option<DateTime<Utc>>>(None))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Sets the `github_repository::scraped_at` field to NULL for all repositories scraped with a parser
+    /// version older than `version`, returning the number of affected repositories.
+    pub fn set_scraped_to_null_where_parser_version_below(&self, version: i32) -> Result<usize, Error> {
+        Ok(diesel::update(github_repository.filter(scraped_at.is_not_null().and(parser_version.lt(version))))
+            .set(scraped_at.eq::<Option<DateTime<Utc>>>(None))
+            .execute(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn set_parser_version(&self, entity_id: i32, version: i32) -> Result<(), Error> {
+        diesel::update(github_repository.filter(id.eq(entity_id)))
+            .set(parser_version.eq(version))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
-    pub fn get_total_repo_count_of_user(&self, entity_id: i32) -> i64 {
-        github_repository.filter(id.eq(entity_id)).count().get_result(self.connection).unwrap()
+    pub fn get_total_repo_count_of_user(&self, entity_id: i32) -> Result<i64, Error> {
+        Ok(github_repository
+            .filter(id.eq(entity_id))
+            .count()
+            .get_result(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_solidity_repo_count_of_user(&self, entity_id: i32) -> i64 {
-        github_repository
+    pub fn get_solidity_repo_count_of_user(&self, entity_id: i32) -> Result<i64, Error> {
+        Ok(github_repository
             .filter(id.eq(entity_id).and(solidity_ratio.gt(0.0)))
             .count()
-            .get_result(self.connection)
-            .unwrap()
+            .get_result(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_solidity_repos_active_in_last_n_days(&self, days: i64) -> Vec<GithubRepositoryDatabase> {
-        github_repository
+    pub fn get_solidity_repos_active_in_last_n_days(
+        &self,
+        days: i64,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        Ok(github_repository
             .filter(
                 updated_at
                     .gt(Utc::now() - chrono::Duration::days(days))
                     .and(solidity_ratio.gt(0.0).or(language.eq("Solidity"))),
             )
-            .get_results(self.connection)
-            .unwrap()
+            .get_results(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_unvisited(&self) -> Vec<GithubRepositoryDatabase> {
-        github_repository
+    pub fn get_unvisited(&self) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        Ok(github_repository
             .filter(visited_at.is_null().and(solidity_ratio.gt(0.0)))
-            .get_results(self.connection)
-            .unwrap()
+            .get_results(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_unscraped_with_forks(&self) -> Vec<GithubRepositoryDatabase> {
-        github_repository
+    pub fn get_unscraped_with_forks(&self) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        Ok(github_repository
             .filter(scraped_at.is_null().and(is_deleted.eq(false)).and(solidity_ratio.gt(0.0)))
-            .get_results(self.connection)
-            .unwrap()
+            .get_results(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_unscraped_without_forks(&self) -> Vec<GithubRepositoryDatabase> {
-        github_repository
+    /// Returns the number of repositories [`Self::get_unscraped_with_forks`] would return.
+    pub fn get_unscraped_count(&self) -> Result<i64, Error> {
+        Ok(github_repository
+            .filter(scraped_at.is_null().and(is_deleted.eq(false)).and(solidity_ratio.gt(0.0)))
+            .count()
+            .get_result(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn get_unscraped_without_forks(&self) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        Ok(github_repository
             .filter(
                 scraped_at
                     .is_null()
@@ -157,52 +260,61 @@ impl<'a> GithubRepositoryHandler<'a> {
                     .and(solidity_ratio.gt(0.0))
                     .and(fork.eq(false)),
             )
-            .get_results(self.connection)
-            .unwrap()
+            .get_results(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn set_visited(&self, entity_id: i32) {
+    pub fn set_visited(&self, entity_id: i32) -> Result<(), Error> {
         diesel::update(github_repository.filter(id.eq(entity_id)))
             .set(visited_at.eq(Utc::now()))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
-    pub fn set_scraped(&self, entity_id: i32) {
+    pub fn set_scraped(&self, entity_id: i32) -> Result<(), Error> {
         diesel::update(github_repository.filter(id.eq(entity_id)))
             .set(scraped_at.eq(Utc::now()))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
     // pub fn set_solidity_ratio(&self, entity_id: i32, entity_solidity_ratio: f32) {
     //     diesel::update(github_repository.filter(id.eq(entity_id)))
     //         .set(solidity_ratio.eq(entity_solidity_ratio))
-    //         .execute(self.connection)
+    //         .execute(&mut *self.connection.borrow_mut())
     //         .unwrap();
     // }
 
-    pub fn set_deleted(&self, entity_id: i32) {
+    pub fn set_deleted(&self, entity_id: i32) -> Result<(), Error> {
         diesel::update(github_repository.filter(id.eq(entity_id)))
             .set(is_deleted.eq(true))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
         debug!("Setting repository with id '{entity_id}' as deleted");
+
+        Ok(())
     }
 
-    pub fn set_undeleted(&self, entity_id: i32) {
+    pub fn set_undeleted(&self, entity_id: i32) -> Result<(), Error> {
         diesel::update(github_repository.filter(id.eq(entity_id)))
             .set(is_deleted.eq(false))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
-    pub fn get_by_id(&self, entity_id: i32) -> Option<GithubRepositoryDatabase> {
-        github_repository.filter(id.eq(entity_id)).get_result(self.connection).optional().unwrap()
+    pub fn get_by_id(&self, entity_id: i32) -> Result<Option<GithubRepositoryDatabase>, Error> {
+        Ok(github_repository
+            .filter(id.eq(entity_id))
+            .get_result(&mut *self.connection.borrow_mut())
+            .optional()?)
     }
 
-    pub fn get_unvisited_repos_with_ratio_greater_than(&self, ratio: f32) -> Vec<GithubRepositoryDatabase> {
-        github_repository
+    pub fn get_unvisited_repos_with_ratio_greater_than(
+        &self,
+        ratio: f32,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        Ok(github_repository
             .filter(
                 github_repository::visited_at
                     .is_null()
@@ -211,7 +323,6 @@ impl<'a> GithubRepositoryHandler<'a> {
             )
             .distinct_on(github_repository::id)
             .select(github_repository::all_columns)
-            .load(self.connection)
-            .unwrap()
+            .load(&mut *self.connection.borrow_mut())?)
     }
 }
@@ -1,53 +1,58 @@
 //! `github_user` table handler.
 
+use crate::database::handler::mapping_stargazer::solidity_stargazer_ids_query;
 use crate::database::schema::github_user;
 use crate::database::schema::github_user::dsl::*;
+use crate::error::Error;
 use crate::model::GithubUser;
 use crate::model::GithubUserDatabase;
 use chrono::Utc;
 use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::BigInt;
 use diesel::PgConnection;
 use diesel::RunQueryDsl;
+use std::cell::RefCell;
 
 pub struct GithubUserHandler<'a> {
-    connection: &'a PgConnection,
+    connection: &'a RefCell<PgConnection>,
 }
 
 impl<'a> GithubUserHandler<'a> {
-    pub fn new(connection: &'a PgConnection) -> Self {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
         GithubUserHandler { connection }
     }
 
-    pub fn insert_if_not_exists(&self, entity: &GithubUser) -> GithubUserDatabase {
-        if let Some(user) = self.get_by_id(entity.id) {
-            return user;
+    pub fn insert_if_not_exists(&self, entity: &GithubUser) -> Result<GithubUserDatabase, Error> {
+        if let Some(user) = self.get_by_id(entity.id)? {
+            return Ok(user);
         }
 
-        diesel::insert_into(github_user::table)
+        Ok(diesel::insert_into(github_user::table)
             .values(entity.to_insertable())
-            .get_result(self.connection)
-            .unwrap()
+            .get_result(&mut *self.connection.borrow_mut())?)
     }
 
-    fn get_by_id(&self, entity_id: i32) -> Option<GithubUserDatabase> {
-        github_user.filter(id.eq(entity_id)).first(self.connection).optional().unwrap()
+    fn get_by_id(&self, entity_id: i32) -> Result<Option<GithubUserDatabase>, Error> {
+        Ok(github_user.filter(id.eq(entity_id)).first(&mut *self.connection.borrow_mut()).optional()?)
     }
 
-    pub fn repo_count(&self, entity_id: i32) -> i64 {
+    pub fn repo_count(&self, entity_id: i32) -> Result<i64, Error> {
         use crate::database::schema::github_repository;
 
-        github_user
+        Ok(github_user
             .inner_join(github_repository::table)
             .filter(github_user::id.eq(entity_id).and(github_repository::is_deleted.eq(false)))
             .count()
-            .get_result(self.connection)
-            .unwrap()
+            .get_result(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_unvisited_solidity_repository_owners_orderd_by_added_at(&self) -> Vec<GithubUserDatabase> {
+    pub fn get_unvisited_solidity_repository_owners_orderd_by_added_at(
+        &self,
+    ) -> Result<Vec<GithubUserDatabase>, Error> {
         use crate::database::schema::github_repository;
 
-        github_user
+        Ok(github_user
             .inner_join(github_repository::table)
             .filter(
                 (github_repository::solidity_ratio.gt(0.0).or(github_repository::language.eq("Solidity")))
@@ -55,21 +60,98 @@ impl<'a> GithubUserHandler<'a> {
             )
             .select(github_user::all_columns)
             .order_by(github_user::added_at.desc())
-            .load(self.connection)
-            .unwrap()
+            .load(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn set_deleted(&self, entity_id: i32) {
+    /// Returns at most `limit` unvisited Solidity repository owners, ordered by the number of signatures
+    /// found within their repositories.
+    pub fn get_unvisited_solidity_repository_owners_ordered_by_signature_count(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<GithubUserDatabase>, Error> {
+        self.get_unvisited_solidity_repository_owners_ordered_by(
+            "(SELECT COUNT(*) FROM mapping_signature_github
+                JOIN github_repository AS owned ON owned.id = mapping_signature_github.repository_id
+                WHERE owned.owner_id = github_user.id) DESC",
+            limit,
+        )
+    }
+
+    /// Returns at most `limit` unvisited Solidity repository owners, randomly sampled where the probability
+    /// of an owner being picked is weighted by the total stargazer count of their Solidity repositories.
+    pub fn get_unvisited_solidity_repository_owners_weighted_by_stargazers(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<GithubUserDatabase>, Error> {
+        // Weighted random sampling (Efraimidis-Spirakis), ordering by `-ln(u) / weight` with `u` in (0, 1]
+        self.get_unvisited_solidity_repository_owners_ordered_by(
+            "-LN(1.0 - RANDOM()) / (SUM(github_repository.stargazers_count) + 1)",
+            limit,
+        )
+    }
+
+    /// Returns at most `limit` unvisited Solidity repository owners, ordered by the number of Solidity
+    /// stargazers (see [`crate::database::handler::mapping_stargazer::MIN_STARRED_SOLIDITY_REPOSITORIES`])
+    /// having starred their repositories.
+    pub fn get_unvisited_solidity_repository_owners_ordered_by_solidity_stargazers(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<GithubUserDatabase>, Error> {
+        self.get_unvisited_solidity_repository_owners_ordered_by(
+            &format!(
+                "(SELECT COUNT(DISTINCT mapping_stargazer.user_id) FROM mapping_stargazer
+                    JOIN github_repository AS owned ON owned.id = mapping_stargazer.repository_id
+                    WHERE owned.owner_id = github_user.id
+                        AND mapping_stargazer.user_id IN ({})) DESC,
+                github_user.added_at DESC",
+                solidity_stargazer_ids_query()
+            ),
+            limit,
+        )
+    }
+
+    /// Returns at most `limit` randomly sampled unvisited Solidity repository owners.
+    pub fn get_unvisited_solidity_repository_owners_random(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<GithubUserDatabase>, Error> {
+        self.get_unvisited_solidity_repository_owners_ordered_by("RANDOM()", limit)
+    }
+
+    fn get_unvisited_solidity_repository_owners_ordered_by(
+        &self,
+        order: &str,
+        limit: i64,
+    ) -> Result<Vec<GithubUserDatabase>, Error> {
+        Ok(sql_query(format!(
+            "SELECT github_user.* FROM github_user
+            JOIN github_repository ON github_user.id = github_repository.owner_id
+            WHERE
+                (github_repository.solidity_ratio > 0.0 OR github_repository.language LIKE 'Solidity')
+                AND github_user.visited_at IS NULL
+            GROUP BY github_user.id
+            ORDER BY {order}
+            LIMIT $1"
+        ))
+        .bind::<BigInt, _>(limit)
+        .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn set_deleted(&self, entity_id: i32) -> Result<(), Error> {
         diesel::update(github_user.filter(id.eq(entity_id)))
             .set(is_deleted.eq(true))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 
-    pub fn get_solidity_repository_owners_active_in_last_n_days(&self, days: i64) -> Vec<GithubUserDatabase> {
+    pub fn get_solidity_repository_owners_active_in_last_n_days(
+        &self,
+        days: i64,
+    ) -> Result<Vec<GithubUserDatabase>, Error> {
         use crate::database::schema::github_repository;
 
-        github_user
+        Ok(github_user
             .inner_join(github_repository::table)
             .filter(
                 (github_repository::solidity_ratio.gt(0.0).or(github_repository::language.eq("Solidity")))
@@ -81,15 +163,15 @@ impl<'a> GithubUserHandler<'a> {
             )
             .select(github_user::all_columns)
             .distinct()
-            .load(self.connection)
-            .unwrap()
+            .load(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn set_visited(&self, entity_id: i32) {
+    pub fn set_visited(&self, entity_id: i32) -> Result<(), Error> {
         diesel::update(github_user::table)
             .filter(id.eq(entity_id))
             .set(visited_at.eq(Utc::now()))
-            .execute(self.connection)
-            .unwrap();
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 }
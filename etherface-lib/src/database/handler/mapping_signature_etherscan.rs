@@ -1,26 +1,89 @@
 //! `mapping_signature_etherscan` table handler.
+//!
+//! Same as the `mapping_signature_github` table the table is partitioned by month of `added_at`, as such
+//! existing mappings are filtered out before inserting, updating their `last_seen_at` instead (see
+//! [`super::mapping_signature_github`]).
 
+use crate::database::handler::INSERT_BATCH_SIZE;
 use crate::database::schema::mapping_signature_etherscan;
+use crate::database::schema::mapping_signature_etherscan::dsl::*;
+use crate::error::Error;
+use crate::metrics::SIGNATURES_INSERTED;
 use crate::model::MappingSignatureEtherscan;
-// use crate::database::schema::mapping_signature_etherscan::dsl::*;
-
+use crate::model::SignatureKind;
+use crate::model::Signature_kind;
 use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Array;
+use diesel::sql_types::Int4;
+use diesel::sql_types::Timestamptz;
 use diesel::PgConnection;
+use std::cell::RefCell;
+use std::collections::HashSet;
 
 pub struct MappingSignatureEtherscanHandler<'a> {
-    connection: &'a PgConnection,
+    connection: &'a RefCell<PgConnection>,
 }
 
 impl<'a> MappingSignatureEtherscanHandler<'a> {
-    pub fn new(connection: &'a PgConnection) -> Self {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
         MappingSignatureEtherscanHandler { connection }
     }
 
-    pub fn insert(&self, entity: &MappingSignatureEtherscan) -> usize {
-        diesel::insert_into(mapping_signature_etherscan::table)
-            .values(entity)
-            .on_conflict_do_nothing()
-            .execute(self.connection)
-            .unwrap()
+    pub fn insert(&self, entity: &MappingSignatureEtherscan) -> Result<usize, Error> {
+        self.insert_many(std::slice::from_ref(entity))
+    }
+
+    /// Same as [`MappingSignatureEtherscanHandler::insert`] but for many mappings at once, returning the
+    /// number of newly inserted mappings.
+    pub fn insert_many(&self, entities: &[MappingSignatureEtherscan]) -> Result<usize, Error> {
+        let mut inserted = 0;
+        for chunk in entities.chunks(INSERT_BATCH_SIZE) {
+            let existing: HashSet<(i32, i32, SignatureKind)> = mapping_signature_etherscan
+                .filter(signature_id.eq_any(chunk.iter().map(|x| x.signature_id)))
+                .filter(contract_id.eq_any(chunk.iter().map(|x| x.contract_id)))
+                .select((signature_id, contract_id, kind))
+                .load(&mut *self.connection.borrow_mut())?
+                .into_iter()
+                .collect();
+
+            let (seen, chunk): (Vec<&MappingSignatureEtherscan>, Vec<&MappingSignatureEtherscan>) =
+                chunk.iter().partition(|x| existing.contains(&(x.signature_id, x.contract_id, x.kind)));
+            self.update_last_seen(&seen)?;
+
+            if chunk.is_empty() {
+                continue;
+            }
+
+            inserted += diesel::insert_into(mapping_signature_etherscan::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        SIGNATURES_INSERTED.with_label_values(&["etherscan"]).inc_by(inserted as u64);
+        Ok(inserted)
+    }
+
+    /// Sets the `last_seen_at` of the already existing mappings `entities` to theirs, unless it's older.
+    fn update_last_seen(&self, entities: &[&MappingSignatureEtherscan]) -> Result<(), Error> {
+        if entities.is_empty() {
+            return Ok(());
+        }
+
+        sql_query(
+            "UPDATE mapping_signature_etherscan AS m
+            SET last_seen_at = GREATEST(m.last_seen_at, seen.last_seen_at)
+            FROM unnest($1, $2, $3, $4) AS seen (signature_id, contract_id, kind, last_seen_at)
+            WHERE m.signature_id = seen.signature_id AND m.contract_id = seen.contract_id
+            AND m.kind = seen.kind",
+        )
+        .bind::<Array<Int4>, _>(entities.iter().map(|x| x.signature_id).collect::<Vec<_>>())
+        .bind::<Array<Int4>, _>(entities.iter().map(|x| x.contract_id).collect::<Vec<_>>())
+        .bind::<Array<Signature_kind>, _>(entities.iter().map(|x| x.kind).collect::<Vec<_>>())
+        .bind::<Array<Timestamptz>, _>(entities.iter().map(|x| x.last_seen_at).collect::<Vec<_>>())
+        .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 }
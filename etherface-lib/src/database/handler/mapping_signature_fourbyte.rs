@@ -1,42 +1,144 @@
 //! `mapping_signature_fourbyte` table handler.
+//!
+//! Same as the `mapping_signature_github` table the table is partitioned by month of `added_at`, as such
+//! existing mappings are filtered out before inserting, updating their `last_seen_at` instead (see
+//! [`super::mapping_signature_github`]).
 
+use crate::database::handler::INSERT_BATCH_SIZE;
 use crate::database::schema::mapping_signature_fourbyte;
 use crate::database::schema::mapping_signature_fourbyte::dsl::*;
+use crate::error::Error;
+use crate::metrics::SIGNATURES_INSERTED;
 use crate::model::MappingSignatureFourbyte;
 use crate::model::SignatureKind;
+use crate::model::Signature_kind;
 use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Array;
+use diesel::sql_types::Int4;
+use diesel::sql_types::Timestamptz;
 use diesel::PgConnection;
+use std::cell::RefCell;
+use std::collections::HashSet;
 
 pub struct MappingSignatureFourbyteHandler<'a> {
-    connection: &'a PgConnection,
+    connection: &'a RefCell<PgConnection>,
 }
 
 impl<'a> MappingSignatureFourbyteHandler<'a> {
-    pub fn new(connection: &'a PgConnection) -> Self {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
         MappingSignatureFourbyteHandler { connection }
     }
 
-    pub fn get(&self, entity: &MappingSignatureFourbyte) -> Option<MappingSignatureFourbyte> {
-        mapping_signature_fourbyte
+    pub fn get(&self, entity: &MappingSignatureFourbyte) -> Result<Option<MappingSignatureFourbyte>, Error> {
+        Ok(mapping_signature_fourbyte
             .filter(signature_id.eq(&entity.signature_id).and(kind.eq(&entity.kind)))
-            .first(self.connection)
-            .optional()
-            .unwrap()
+            .first(&mut *self.connection.borrow_mut())
+            .optional()?)
     }
 
-    pub fn get_functions_count(&self) -> usize {
-        mapping_signature_fourbyte.filter(kind.eq(SignatureKind::Function)).execute(self.connection).unwrap()
+    pub fn get_functions_count(&self) -> Result<usize, Error> {
+        Ok(mapping_signature_fourbyte
+            .filter(kind.eq(SignatureKind::Function))
+            .execute(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn get_events_count(&self) -> usize {
-        mapping_signature_fourbyte.filter(kind.eq(SignatureKind::Event)).execute(self.connection).unwrap()
+    pub fn get_events_count(&self) -> Result<usize, Error> {
+        Ok(mapping_signature_fourbyte
+            .filter(kind.eq(SignatureKind::Event))
+            .execute(&mut *self.connection.borrow_mut())?)
     }
 
-    pub fn insert(&self, entity: &MappingSignatureFourbyte) {
-        diesel::insert_into(mapping_signature_fourbyte::table)
-            .values(entity)
-            .on_conflict_do_nothing()
-            .execute(self.connection)
-            .unwrap();
+    pub fn insert(&self, entity: &MappingSignatureFourbyte) -> Result<(), Error> {
+        self.insert_many(std::slice::from_ref(entity))?;
+        Ok(())
+    }
+
+    /// Same as [`MappingSignatureFourbyteHandler::insert`] but for many mappings at once, returning the
+    /// number of newly inserted mappings.
+    pub fn insert_many(&self, entities: &[MappingSignatureFourbyte]) -> Result<usize, Error> {
+        let mut inserted = 0;
+        for chunk in entities.chunks(INSERT_BATCH_SIZE) {
+            let existing: HashSet<(i32, SignatureKind)> = mapping_signature_fourbyte
+                .filter(signature_id.eq_any(chunk.iter().map(|x| x.signature_id)))
+                .select((signature_id, kind))
+                .load(&mut *self.connection.borrow_mut())?
+                .into_iter()
+                .collect();
+
+            let (seen, chunk): (Vec<&MappingSignatureFourbyte>, Vec<&MappingSignatureFourbyte>) =
+                chunk.iter().partition(|x| existing.contains(&(x.signature_id, x.kind)));
+            self.update_last_seen(&seen)?;
+
+            if chunk.is_empty() {
+                continue;
+            }
+
+            inserted += diesel::insert_into(mapping_signature_fourbyte::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        SIGNATURES_INSERTED.with_label_values(&["fourbyte"]).inc_by(inserted as u64);
+        Ok(inserted)
+    }
+
+    /// Sets the `last_seen_at` of the already existing mappings `entities` to theirs, unless it's older.
+    fn update_last_seen(&self, entities: &[&MappingSignatureFourbyte]) -> Result<(), Error> {
+        if entities.is_empty() {
+            return Ok(());
+        }
+
+        sql_query(
+            "UPDATE mapping_signature_fourbyte AS m
+            SET last_seen_at = GREATEST(m.last_seen_at, seen.last_seen_at)
+            FROM unnest($1, $2, $3) AS seen (signature_id, kind, last_seen_at)
+            WHERE m.signature_id = seen.signature_id AND m.kind = seen.kind",
+        )
+        .bind::<Array<Int4>, _>(entities.iter().map(|x| x.signature_id).collect::<Vec<_>>())
+        .bind::<Array<Signature_kind>, _>(entities.iter().map(|x| x.kind).collect::<Vec<_>>())
+        .bind::<Array<Timestamptz>, _>(entities.iter().map(|x| x.last_seen_at).collect::<Vec<_>>())
+        .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::handler::testing;
+    use crate::model::MappingSignatureFourbyte;
+    use crate::model::SignatureKind;
+    use crate::model::SignatureWithMetadata;
+    use chrono::DateTime;
+    use chrono::Duration;
+    use chrono::Utc;
+
+    fn mapping(signature_id: i32, seen_at: DateTime<Utc>) -> MappingSignatureFourbyte {
+        MappingSignatureFourbyte {
+            signature_id,
+            kind: SignatureKind::Function,
+            added_at: seen_at,
+            last_seen_at: seen_at,
+        }
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn insert_many_updates_last_seen_at() {
+        let dbc = testing::client();
+
+        let signature = SignatureWithMetadata::new("f()".to_string(), SignatureKind::Function, true);
+        let signature = dbc.signature().insert(&signature).unwrap();
+
+        let first_seen = Utc::now() - Duration::days(30);
+        let handler = dbc.mapping_signature_fourbyte();
+        assert_eq!(handler.insert_many(&[mapping(signature.id, first_seen)]).unwrap(), 1);
+        assert_eq!(handler.insert_many(&[mapping(signature.id, Utc::now())]).unwrap(), 0);
+
+        let row = handler.get(&mapping(signature.id, Utc::now())).unwrap().unwrap();
+        assert_eq!(row.added_at.timestamp(), first_seen.timestamp());
+        assert!(row.last_seen_at > first_seen);
     }
 }
@@ -1,26 +1,90 @@
 //! `mapping_signature_github` table handler.
+//!
+//! The table is partitioned by month of `added_at`, which as the partition key is part of the primary key.
+//! Re-inserting an existing mapping at a later date would therefore not conflict, as such existing mappings
+//! are filtered out before inserting, updating their `last_seen_at` instead.
 
+use crate::database::handler::INSERT_BATCH_SIZE;
 use crate::database::schema::mapping_signature_github;
+use crate::database::schema::mapping_signature_github::dsl::*;
+use crate::error::Error;
+use crate::metrics::SIGNATURES_INSERTED;
 use crate::model::MappingSignatureGithub;
-// use crate::database::schema::mapping_signature_github::dsl::*;
-
+use crate::model::SignatureKind;
+use crate::model::Signature_kind;
 use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Array;
+use diesel::sql_types::Int4;
+use diesel::sql_types::Timestamptz;
 use diesel::PgConnection;
+use std::cell::RefCell;
+use std::collections::HashSet;
 
 pub struct MappingSignatureGithubHandler<'a> {
-    connection: &'a PgConnection,
+    connection: &'a RefCell<PgConnection>,
 }
 
 impl<'a> MappingSignatureGithubHandler<'a> {
-    pub fn new(connection: &'a PgConnection) -> Self {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
         MappingSignatureGithubHandler { connection }
     }
 
-    pub fn insert(&self, entity: &MappingSignatureGithub) {
-        diesel::insert_into(mapping_signature_github::table)
-            .values(entity)
-            .on_conflict_do_nothing()
-            .execute(self.connection)
-            .unwrap();
+    pub fn insert(&self, entity: &MappingSignatureGithub) -> Result<(), Error> {
+        self.insert_many(std::slice::from_ref(entity))?;
+        Ok(())
+    }
+
+    /// Same as [`MappingSignatureGithubHandler::insert`] but for many mappings at once, returning the number
+    /// of newly inserted mappings.
+    pub fn insert_many(&self, entities: &[MappingSignatureGithub]) -> Result<usize, Error> {
+        let mut inserted = 0;
+        for chunk in entities.chunks(INSERT_BATCH_SIZE) {
+            let existing: HashSet<(i32, i32, SignatureKind)> = mapping_signature_github
+                .filter(signature_id.eq_any(chunk.iter().map(|x| x.signature_id)))
+                .filter(repository_id.eq_any(chunk.iter().map(|x| x.repository_id)))
+                .select((signature_id, repository_id, kind))
+                .load(&mut *self.connection.borrow_mut())?
+                .into_iter()
+                .collect();
+
+            let (seen, chunk): (Vec<&MappingSignatureGithub>, Vec<&MappingSignatureGithub>) =
+                chunk.iter().partition(|x| existing.contains(&(x.signature_id, x.repository_id, x.kind)));
+            self.update_last_seen(&seen)?;
+
+            if chunk.is_empty() {
+                continue;
+            }
+
+            inserted += diesel::insert_into(mapping_signature_github::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        SIGNATURES_INSERTED.with_label_values(&["github"]).inc_by(inserted as u64);
+        Ok(inserted)
+    }
+
+    /// Sets the `last_seen_at` of the already existing mappings `entities` to theirs, unless it's older.
+    fn update_last_seen(&self, entities: &[&MappingSignatureGithub]) -> Result<(), Error> {
+        if entities.is_empty() {
+            return Ok(());
+        }
+
+        sql_query(
+            "UPDATE mapping_signature_github AS m
+            SET last_seen_at = GREATEST(m.last_seen_at, seen.last_seen_at)
+            FROM unnest($1, $2, $3, $4) AS seen (signature_id, repository_id, kind, last_seen_at)
+            WHERE m.signature_id = seen.signature_id AND m.repository_id = seen.repository_id
+            AND m.kind = seen.kind",
+        )
+        .bind::<Array<Int4>, _>(entities.iter().map(|x| x.signature_id).collect::<Vec<_>>())
+        .bind::<Array<Int4>, _>(entities.iter().map(|x| x.repository_id).collect::<Vec<_>>())
+        .bind::<Array<Signature_kind>, _>(entities.iter().map(|x| x.kind).collect::<Vec<_>>())
+        .bind::<Array<Timestamptz>, _>(entities.iter().map(|x| x.last_seen_at).collect::<Vec<_>>())
+        .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
     }
 }
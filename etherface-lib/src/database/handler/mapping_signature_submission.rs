@@ -0,0 +1,29 @@
+//! `mapping_signature_submission` table handler.
+
+use crate::database::schema::mapping_signature_submission;
+use crate::error::Error;
+use crate::metrics::SIGNATURES_INSERTED;
+use crate::model::MappingSignatureSubmission;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct MappingSignatureSubmissionHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> MappingSignatureSubmissionHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        MappingSignatureSubmissionHandler { connection }
+    }
+
+    pub fn insert(&self, entity: &MappingSignatureSubmission) -> Result<(), Error> {
+        let inserted = diesel::insert_into(mapping_signature_submission::table)
+            .values(entity)
+            .on_conflict_do_nothing()
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        SIGNATURES_INSERTED.with_label_values(&["submission"]).inc_by(inserted as u64);
+        Ok(())
+    }
+}
@@ -0,0 +1,151 @@
+//! `mapping_stargazer` table handler.
+//!
+//! The table holds the stargazer graph recorded while crawling GitHub, i.e. which users starred which
+//! repositories, allowing to find users interested in Solidity and repositories commonly starred together.
+
+use crate::database::handler::INSERT_BATCH_SIZE;
+use crate::database::schema::mapping_stargazer;
+use crate::error::Error;
+use crate::model::CoStarredRepository;
+use crate::model::GithubUserDatabase;
+use crate::model::MappingStargazer;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::BigInt;
+use diesel::sql_types::Int4;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+/// Minimum number of starred Solidity repositories for a user to be considered a Solidity stargazer, i.e.
+/// someone whose starred repositories are worth exploring.
+pub const MIN_STARRED_SOLIDITY_REPOSITORIES: i64 = 3;
+
+pub struct MappingStargazerHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> MappingStargazerHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        MappingStargazerHandler { connection }
+    }
+
+    /// Inserts the given mappings, skipping already existing ones, and returns the number of newly inserted
+    /// mappings.
+    pub fn insert_many(&self, entities: &[MappingStargazer]) -> Result<usize, Error> {
+        let mut inserted = 0;
+        for chunk in entities.chunks(INSERT_BATCH_SIZE) {
+            inserted += diesel::insert_into(mapping_stargazer::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        Ok(inserted)
+    }
+
+    /// Returns at most `limit` users having starred at least `min_starred` Solidity repositories, ordered by
+    /// their number of starred Solidity repositories.
+    pub fn get_solidity_stargazers(
+        &self,
+        min_starred: i64,
+        limit: i64,
+    ) -> Result<Vec<GithubUserDatabase>, Error> {
+        Ok(sql_query(
+            "SELECT github_user.* FROM github_user
+            JOIN mapping_stargazer ON github_user.id = mapping_stargazer.user_id
+            JOIN github_repository ON github_repository.id = mapping_stargazer.repository_id
+            WHERE github_repository.solidity_ratio > 0.0 OR github_repository.language LIKE 'Solidity'
+            GROUP BY github_user.id
+            HAVING COUNT(*) >= $1
+            ORDER BY COUNT(*) DESC, github_user.id
+            LIMIT $2",
+        )
+        .bind::<BigInt, _>(min_starred)
+        .bind::<BigInt, _>(limit)
+        .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Returns at most `limit` repositories sharing stargazers with the given repository, ordered by the
+    /// number of shared stargazers.
+    pub fn get_co_starred_repositories(
+        &self,
+        entity_repository_id: i32,
+        limit: i64,
+    ) -> Result<Vec<CoStarredRepository>, Error> {
+        Ok(sql_query(
+            "SELECT other.repository_id, COUNT(*) AS shared_stargazers FROM mapping_stargazer
+            JOIN mapping_stargazer AS other ON other.user_id = mapping_stargazer.user_id
+            WHERE mapping_stargazer.repository_id = $1 AND other.repository_id <> $1
+            GROUP BY other.repository_id
+            ORDER BY shared_stargazers DESC, other.repository_id
+            LIMIT $2",
+        )
+        .bind::<Int4, _>(entity_repository_id)
+        .bind::<BigInt, _>(limit)
+        .load(&mut *self.connection.borrow_mut())?)
+    }
+}
+
+/// Returns a sub-query selecting the IDs of all users having starred at least
+/// [`MIN_STARRED_SOLIDITY_REPOSITORIES`] Solidity repositories, used by the frontier queries of the
+/// `github_user` and `github_repository` handlers.
+pub(crate) fn solidity_stargazer_ids_query() -> String {
+    format!(
+        "SELECT starred.user_id FROM mapping_stargazer AS starred
+        JOIN github_repository AS starred_repository ON starred_repository.id = starred.repository_id
+        WHERE starred_repository.solidity_ratio > 0.0 OR starred_repository.language LIKE 'Solidity'
+        GROUP BY starred.user_id
+        HAVING COUNT(*) >= {MIN_STARRED_SOLIDITY_REPOSITORIES}"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::handler::testing;
+    use crate::model::MappingStargazer;
+    use chrono::Utc;
+
+    const SEED: &str = "
+        INSERT INTO github_user (id, login, html_url, is_deleted, added_at) VALUES
+            (1, 'alice', 'https://github.com/alice', FALSE, NOW()),
+            (2, 'bob', 'https://github.com/bob', FALSE, NOW()),
+            (3, 'carol', 'https://github.com/carol', FALSE, NOW());
+
+        INSERT INTO github_repository (id, owner_id, name, html_url, language, stargazers_count, size, fork,
+            created_at, pushed_at, updated_at, is_deleted, solidity_ratio, found_by_crawling, added_at)
+        VALUES
+            (10, 1, 'a', 'https://github.com/alice/a', 'Solidity', 0, 0, FALSE, NOW(), NOW(), NOW(), FALSE,
+                1.0, TRUE, NOW()),
+            (11, 1, 'b', 'https://github.com/alice/b', 'Solidity', 0, 0, FALSE, NOW(), NOW(), NOW(), FALSE,
+                1.0, TRUE, NOW()),
+            (12, 2, 'c', 'https://github.com/bob/c', 'Rust', 0, 0, FALSE, NOW(), NOW(), NOW(), FALSE,
+                NULL, TRUE, NOW());
+    ";
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn solidity_stargazers_and_co_starred_repositories() {
+        let dbc = testing::client();
+        testing::seed(&dbc, SEED);
+
+        let star = |user_id, repository_id| MappingStargazer {
+            user_id,
+            repository_id,
+            added_at: Utc::now(),
+        };
+        let stars = [star(1, 10), star(1, 11), star(1, 12), star(2, 10), star(2, 12), star(3, 12)];
+        assert_eq!(dbc.mapping_stargazer().insert_many(&stars).unwrap(), 6);
+        assert_eq!(dbc.mapping_stargazer().insert_many(&stars[..2]).unwrap(), 0);
+
+        let users = dbc.mapping_stargazer().get_solidity_stargazers(2, 10).unwrap();
+        assert_eq!(users.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1]);
+
+        let users = dbc.mapping_stargazer().get_solidity_stargazers(1, 10).unwrap();
+        assert_eq!(users.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let co_starred = dbc.mapping_stargazer().get_co_starred_repositories(12, 10).unwrap();
+        let co_starred: Vec<(i32, i64)> =
+            co_starred.iter().map(|x| (x.repository_id, x.shared_stargazers)).collect();
+        assert_eq!(co_starred, vec![(10, 2), (11, 1)]);
+    }
+}
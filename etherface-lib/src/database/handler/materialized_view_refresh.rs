@@ -0,0 +1,69 @@
+//! `materialized_view_refresh` table handler.
+
+use crate::database::schema::materialized_view_refresh;
+use crate::database::schema::materialized_view_refresh::dsl::*;
+use crate::error::Error;
+use crate::model::MaterializedViewRefresh;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::PgConnection;
+use std::cell::RefCell;
+use std::time::Instant;
+
+/// Materialized views read by the REST APIs statistics and collisions endpoints, see
+/// `migrations/2022-08-01-201536_create_materialized_views/up.sql`,
+/// `migrations/2022-10-20-093115_analytics_views/up.sql`,
+/// `migrations/2022-10-26-140513_extended_analytics_views/up.sql` and
+/// `migrations/2022-11-01-094215_selector_collision_view/up.sql`.
+pub const MATERIALIZED_VIEWS: [&str; 10] = [
+    "view_signature_insert_rate",
+    "view_signatures_popular_on_github",
+    "view_signature_kind_distribution",
+    "view_signature_count_statistics",
+    "view_signature_insert_rate_by_source",
+    "view_repositories_top_unique_signatures",
+    "view_signatures_per_contract_distribution",
+    "view_etherscan_compiler_version_distribution",
+    "view_etherscan_chain_distribution",
+    "view_selector_collision",
+];
+
+pub struct MaterializedViewRefreshHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> MaterializedViewRefreshHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        MaterializedViewRefreshHandler { connection }
+    }
+
+    /// Refreshes all [`MATERIALIZED_VIEWS`] one after another, recording the date and duration of each
+    /// refresh. The views are refreshed concurrently, i.e. they can still be read while being refreshed.
+    pub fn refresh_all(&self) -> Result<(), Error> {
+        for view in MATERIALIZED_VIEWS {
+            let started_at = Instant::now();
+            sql_query(format!("REFRESH MATERIALIZED VIEW CONCURRENTLY {view}"))
+                .execute(&mut *self.connection.borrow_mut())?;
+
+            let entity = MaterializedViewRefresh {
+                name: view.to_string(),
+                refreshed_at: Utc::now(),
+                duration_ms: started_at.elapsed().as_millis() as i64,
+            };
+
+            diesel::insert_into(materialized_view_refresh::table)
+                .values(&entity)
+                .on_conflict(name)
+                .do_update()
+                .set((refreshed_at.eq(entity.refreshed_at), duration_ms.eq(entity.duration_ms)))
+                .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_all(&self) -> Result<Vec<MaterializedViewRefresh>, Error> {
+        Ok(materialized_view_refresh.order_by(name.asc()).get_results(&mut *self.connection.borrow_mut())?)
+    }
+}
@@ -3,35 +3,86 @@
 //! All tables can be further inspected in the `migrations/2022-03-06-133006_etherface_database/up.sql` or
 //! `schema.rs` file.
 
+pub mod api_key;
+pub mod audit_log;
+pub mod etherscan_bytecode_hash;
 pub mod etherscan_contract;
+pub mod etherscan_payload;
+pub mod export;
 pub mod github_crawler_metadata;
 pub mod github_repository;
 pub mod github_user;
 pub mod mapping_signature_etherscan;
 pub mod mapping_signature_fourbyte;
 pub mod mapping_signature_github;
+pub mod mapping_signature_submission;
+pub mod mapping_stargazer;
+pub mod materialized_view_refresh;
+pub mod partition;
 pub mod rest;
 pub mod signature;
+pub mod signature_flag;
+pub mod signature_reader;
+pub mod signature_submission;
+#[cfg(test)]
+pub(crate) mod testing;
+pub mod watchlist_entry;
+pub mod webhook;
+pub mod worker_status;
 
 use crate::config::Config;
+use crate::config::DEFAULT_DATABASE_POOL_CONNECTION_TIMEOUT;
+use crate::config::DEFAULT_DATABASE_POOL_MAX_SIZE;
+use crate::database::cache::PendingSignatures;
+use crate::database::cache::SIGNATURE_CACHE;
+use crate::database::pagination::COUNT_CACHE;
+use crate::database::run_migrations;
+use crate::database::handler::api_key::ApiKeyHandler;
+use crate::database::handler::audit_log::AuditLogHandler;
+use crate::database::handler::etherscan_bytecode_hash::EtherscanBytecodeHashHandler;
 use crate::database::handler::etherscan_contract::EtherscanContractHandler;
+use crate::database::handler::etherscan_payload::EtherscanPayloadHandler;
+use crate::database::handler::export::ExportHandler;
 use crate::database::handler::github_crawler_metadata::GithubCrawlerMetadataHandler;
 use crate::database::handler::github_repository::GithubRepositoryHandler;
 use crate::database::handler::github_user::GithubUserHandler;
 use crate::database::handler::mapping_signature_etherscan::MappingSignatureEtherscanHandler;
 use crate::database::handler::mapping_signature_fourbyte::MappingSignatureFourbyteHandler;
 use crate::database::handler::mapping_signature_github::MappingSignatureGithubHandler;
+use crate::database::handler::mapping_signature_submission::MappingSignatureSubmissionHandler;
+use crate::database::handler::mapping_stargazer::MappingStargazerHandler;
+use crate::database::handler::materialized_view_refresh::MaterializedViewRefreshHandler;
+use crate::database::handler::partition::PartitionHandler;
 use crate::database::handler::rest::RestHandler;
 use crate::database::handler::signature::SignatureHandler;
+use crate::database::handler::signature_flag::SignatureFlagHandler;
+use crate::database::handler::signature_reader::SignatureReaderHandler;
+use crate::database::handler::signature_submission::SignatureSubmissionHandler;
+use crate::database::handler::watchlist_entry::WatchlistEntryHandler;
+use crate::database::handler::webhook::WebhookHandler;
+use crate::database::handler::worker_status::WorkerStatusHandler;
 use crate::error::Error;
+use diesel::connection::AnsiTransactionManager;
+use diesel::connection::TransactionManager;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use diesel::Connection;
 use diesel::PgConnection;
+use std::cell::RefCell;
+use std::time::Duration;
+
+/// Maximum number of rows written by a single batched `INSERT`, keeping the number of bind parameters well
+/// below PostgreSQL's limit of 65535 per statement.
+pub(crate) const INSERT_BATCH_SIZE: usize = 1000;
 
 /// Database client, providing all table handlers.
 pub struct DatabaseClient {
-    connection: PgConnection,
+    // Diesel requires mutable access to the connection for every query, whereas the table handlers (and their
+    // callers) only ever hold a shared reference to the client
+    connection: RefCell<PgConnection>,
+
+    /// Signatures inserted within the current transaction, cached once it's committed.
+    pending_signatures: PendingSignatures,
 }
 
 /// Same as [`DatabaseClient`] but threaded for the REST API.
@@ -39,32 +90,139 @@ pub struct DatabaseClientPooled {
     connection: Pool<ConnectionManager<PgConnection>>,
 }
 
+/// Connection pool settings of a [`DatabaseClientPooled`], defaulting to the values configured in [`Config`]
+/// (see [`PoolOptions::from_config`]) if constructed from `.env`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolOptions {
+    /// Maximum number of connections, i.e. the number of queries which can be executed concurrently.
+    pub max_size: u32,
+
+    /// Timeout for checking out a connection if all of them are in use.
+    pub connection_timeout: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        PoolOptions {
+            max_size: DEFAULT_DATABASE_POOL_MAX_SIZE,
+            connection_timeout: Duration::from_secs(DEFAULT_DATABASE_POOL_CONNECTION_TIMEOUT),
+        }
+    }
+}
+
+impl PoolOptions {
+    /// Returns the pool settings configured in `config`.
+    pub fn from_config(config: &Config) -> Self {
+        PoolOptions {
+            max_size: config.database_pool_max_size,
+            connection_timeout: Duration::from_secs(config.database_pool_connection_timeout),
+        }
+    }
+}
+
 impl DatabaseClientPooled {
     /// Returns a new threaded database client.
     pub fn new() -> Result<Self, Error> {
         let config = Config::new()?;
-        let manager = diesel::r2d2::ConnectionManager::<PgConnection>::new(&config.database_url);
-        let pool = diesel::r2d2::Pool::builder().build(manager).unwrap();
+        COUNT_CACHE.configure(Duration::from_secs(config.database_count_cache_ttl));
+
+        DatabaseClientPooled::with_options(&config.database_url, PoolOptions::from_config(&config))
+    }
+
+    /// Returns a new threaded database client connecting to `database_url` instead of the one configured in
+    /// `.env`.
+    pub fn with_url(database_url: &str) -> Result<Self, Error> {
+        DatabaseClientPooled::with_options(database_url, PoolOptions::default())
+    }
+
+    /// Returns a new threaded database client connecting to `database_url` with the given pool settings.
+    pub fn with_options(database_url: &str, options: PoolOptions) -> Result<Self, Error> {
+        let manager = ConnectionManager::<PgConnection>::new(database_url);
+        let pool = Pool::builder()
+            .max_size(options.max_size)
+            .connection_timeout(options.connection_timeout)
+            .build(manager)?;
 
         Ok(DatabaseClientPooled { connection: pool })
     }
 
+    /// Runs all pending embedded migrations.
+    pub fn run_pending_migrations(&self) -> Result<(), Error> {
+        run_migrations(&mut self.connection.get()?)
+    }
+
     /// Returns a handler for REST specific purposes.
     pub fn rest(&self) -> RestHandler {
         RestHandler::new(&self.connection)
     }
+
+    /// Returns the number of connections currently in use, idle and at most opened by the pool.
+    pub fn pool_state(&self) -> (u32, u32, u32) {
+        let state = self.connection.state();
+        (state.connections - state.idle_connections, state.idle_connections, self.connection.max_size())
+    }
 }
 
 impl DatabaseClient {
     /// Returns a new database client.
     pub fn new() -> Result<Self, Error> {
         let config = Config::new()?;
+        SIGNATURE_CACHE.configure(config.signature_cache_capacity);
 
+        DatabaseClient::with_url(&config.database_url)
+    }
+
+    /// Returns a new database client connecting to `database_url` instead of the one configured in `.env`.
+    pub fn with_url(database_url: &str) -> Result<Self, Error> {
         Ok(DatabaseClient {
-            connection: PgConnection::establish(&config.database_url)?,
+            connection: RefCell::new(PgConnection::establish(database_url)?),
+            pending_signatures: PendingSignatures::new(SIGNATURE_CACHE.clone()),
         })
     }
 
+    /// Runs `f` within a database transaction, committing it if `f` succeeds and rolling it back otherwise,
+    /// such that either all or none of the queries executed by `f` are persisted. Transactions may be nested,
+    /// in which case inner transactions are rolled back to a savepoint.
+    pub fn transaction<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+        E: From<Error>,
+    {
+        // Diesel's `Connection::transaction` borrows the connection for the whole closure, whereas the table
+        // handlers used within `f` borrow it for each query, hence the transaction is managed manually
+        AnsiTransactionManager::begin_transaction(&mut *self.connection.borrow_mut()).map_err(Error::from)?;
+        let savepoint = self.pending_signatures.begin();
+
+        let res = f();
+        let mut connection = self.connection.borrow_mut();
+
+        match res {
+            Ok(value) => match AnsiTransactionManager::commit_transaction(&mut *connection) {
+                Ok(()) => {
+                    self.pending_signatures.commit(savepoint);
+                    Ok(value)
+                }
+
+                Err(why) => {
+                    self.pending_signatures.rollback(savepoint);
+                    Err(Error::from(why).into())
+                }
+            },
+
+            Err(why) => {
+                self.pending_signatures.rollback(savepoint);
+                AnsiTransactionManager::rollback_transaction(&mut *connection).map_err(Error::from)?;
+
+                Err(why)
+            }
+        }
+    }
+
+    /// Runs all pending embedded migrations.
+    pub fn run_pending_migrations(&self) -> Result<(), Error> {
+        run_migrations(&mut self.connection.borrow_mut())
+    }
+
     /// Returns a handler for the `github_user` table.
     pub fn github_user(&self) -> GithubUserHandler {
         GithubUserHandler::new(&self.connection)
@@ -80,9 +238,24 @@ impl DatabaseClient {
         EtherscanContractHandler::new(&self.connection)
     }
 
+    /// Returns a handler for the `etherscan_bytecode_hash` table.
+    pub fn etherscan_bytecode_hash(&self) -> EtherscanBytecodeHashHandler {
+        EtherscanBytecodeHashHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `etherscan_payload` table.
+    pub fn etherscan_payload(&self) -> EtherscanPayloadHandler {
+        EtherscanPayloadHandler::new(&self.connection)
+    }
+
     /// Returns a handler for the `signature` table.
     pub fn signature(&self) -> SignatureHandler {
-        SignatureHandler::new(&self.connection)
+        SignatureHandler::new(&self.connection, &self.pending_signatures)
+    }
+
+    /// Returns a handler for looking up signatures and their mappings, see [`crate::database::reader`].
+    pub fn signature_reader(&self) -> SignatureReaderHandler {
+        SignatureReaderHandler::new(&self.connection)
     }
 
     /// Returns a handler for the `mapping_signature_etherscan` table.
@@ -100,8 +273,68 @@ impl DatabaseClient {
         MappingSignatureGithubHandler::new(&self.connection)
     }
 
+    /// Returns a handler for the `mapping_signature_submission` table.
+    pub fn mapping_signature_submission(&self) -> MappingSignatureSubmissionHandler {
+        MappingSignatureSubmissionHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `mapping_stargazer` table.
+    pub fn mapping_stargazer(&self) -> MappingStargazerHandler {
+        MappingStargazerHandler::new(&self.connection)
+    }
+
     /// Returns a handler for the `github_crawler_metadata` table.
     pub fn github_crawler_metadata(&self) -> GithubCrawlerMetadataHandler {
         GithubCrawlerMetadataHandler::new(&self.connection)
     }
+
+    /// Returns a handler for the `worker_status` table.
+    pub fn worker_status(&self) -> WorkerStatusHandler {
+        WorkerStatusHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `materialized_view_refresh` table.
+    pub fn materialized_view_refresh(&self) -> MaterializedViewRefreshHandler {
+        MaterializedViewRefreshHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the monthly partitions of the `mapping_signature_*` tables.
+    pub fn partition(&self) -> PartitionHandler {
+        PartitionHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `audit_log` table.
+    pub fn audit_log(&self) -> AuditLogHandler {
+        AuditLogHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `api_key` table.
+    pub fn api_key(&self) -> ApiKeyHandler {
+        ApiKeyHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `signature_submission` table.
+    pub fn signature_submission(&self) -> SignatureSubmissionHandler {
+        SignatureSubmissionHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `signature_flag` table.
+    pub fn signature_flag(&self) -> SignatureFlagHandler {
+        SignatureFlagHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `watchlist_entry` table.
+    pub fn watchlist_entry(&self) -> WatchlistEntryHandler {
+        WatchlistEntryHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for the `webhook` and `webhook_delivery` tables.
+    pub fn webhook(&self) -> WebhookHandler {
+        WebhookHandler::new(&self.connection)
+    }
+
+    /// Returns a handler for exporting the public dataset.
+    pub fn export(&self) -> ExportHandler {
+        ExportHandler::new(&self.connection)
+    }
 }
@@ -0,0 +1,41 @@
+//! Monthly partitions of the `mapping_signature_*` tables.
+//!
+//! Each table has a default partition catching rows whose monthly partition doesn't exist yet, however
+//! partitions should be created ahead of time (see [`PartitionHandler::create_monthly`]) such that the
+//! default partitions remain empty. See `migrations/2022-10-06-184211_partition_signature_mappings/up.sql`.
+
+use crate::error::Error;
+use chrono::NaiveDate;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Date;
+use diesel::sql_types::Text;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+/// Tables partitioned by month of their `added_at` column.
+pub const PARTITIONED_TABLES: [&str; 3] =
+    ["mapping_signature_github", "mapping_signature_etherscan", "mapping_signature_fourbyte"];
+
+pub struct PartitionHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> PartitionHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        PartitionHandler { connection }
+    }
+
+    /// Creates the partition of each [`PARTITIONED_TABLES`] table for the month of `month` if it doesn't
+    /// exist yet.
+    pub fn create_monthly(&self, month: NaiveDate) -> Result<(), Error> {
+        for table in PARTITIONED_TABLES {
+            sql_query("SELECT create_monthly_partition($1, $2)")
+                .bind::<Text, _>(table)
+                .bind::<Date, _>(month)
+                .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        Ok(())
+    }
+}
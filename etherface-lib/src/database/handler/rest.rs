@@ -1,20 +1,73 @@
 //! `/v1/` REST API handler.
 
+use crate::api::rpc::parse_hex;
+use crate::api::rpc::parse_quantity;
+use crate::api::rpc::parse_wei;
+use crate::api::rpc::RpcReceipt;
+use crate::api::rpc::RpcTransaction;
+use crate::database::pagination::Cursor;
+use crate::database::pagination::CursorOrder;
 use crate::database::pagination::Paginate;
+use crate::decoder::decode_log;
+use crate::decoder::decode_with_selector;
+use crate::decoder::encode_hex;
+use crate::decoder::Decoded;
+use crate::decoder::DecodedTransaction;
+use crate::decoder::BUILTIN_ERRORS;
+use crate::error::Error;
+use crate::model::decode_hash;
+use crate::model::views::ViewEtherscanChainDistribution;
+use crate::model::views::ViewEtherscanCompilerVersionDistribution;
+use crate::model::views::ViewRepositoriesTopUniqueSignatures;
 use crate::model::views::ViewSignatureCountStatistics;
 use crate::model::views::ViewSignatureInsertRate;
+use crate::model::views::ViewSignatureInsertRateBySource;
 use crate::model::views::ViewSignatureKindDistribution;
+use crate::model::views::ViewSignaturesPerContractDistribution;
 use crate::model::views::ViewSignaturesPopularOnGithub;
+use crate::model::ApiKey;
+use crate::model::ContractBytecodeHash;
+use crate::model::ContractDetail;
+use crate::model::ContractSignature;
 use crate::model::EtherscanContract;
+use crate::model::FlagStatus;
 use crate::model::GithubRepositoryDatabase;
+use crate::model::MaterializedViewRefresh;
+use crate::model::SelectorCollision;
 use crate::model::Signature;
+use crate::model::SignatureDetail;
+use crate::model::SignatureFlag;
+use crate::model::SignatureFlagInsert;
 use crate::model::SignatureKind;
+use crate::model::SignatureSource;
+use crate::model::SignatureSourceCounts;
+use crate::model::SignatureSubmission;
+use crate::model::SignatureSubmissionInsert;
+use crate::model::SignatureWithMetadata;
+use crate::model::WatchlistEntry;
+use crate::model::WatchlistEntryInsert;
+use crate::model::Webhook;
+use crate::model::WebhookInsert;
+use crate::model::WorkerStatus;
+use crate::standard::standards_of;
+use chrono::DateTime;
+use chrono::Utc;
+use diesel::define_sql_function;
+use diesel::infix_operator;
+use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::Pool;
 use diesel::sql_query;
+use diesel::sql_types::BigInt;
+use diesel::sql_types::Float4;
+use diesel::sql_types::Int4;
+use diesel::sql_types::Nullable;
+use diesel::sql_types::Text;
+use diesel::sql_types::Timestamptz;
 use diesel::PgConnection;
 use serde::Serialize;
+use std::collections::HashMap;
 
 #[derive(Serialize)]
 pub struct RestResponse<T> {
@@ -27,242 +80,2001 @@ pub struct RestHandler<'a> {
     connection: &'a Pool<ConnectionManager<PgConnection>>,
 }
 
+#[derive(Serialize)]
+pub struct RestCursorResponse<T> {
+    /// Cursor of the next page, `None` if this is the last page.
+    pub next_cursor: Option<Cursor>,
+    pub items: T,
+}
+
+/// Line format of [`RestHandler::signatures_export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line.
+    Ndjson,
+
+    /// Comma separated values, every value being quoted and kinds being separated by `;`; see
+    /// [`EXPORT_CSV_HEADER`].
+    Csv,
+}
+
+/// Header of [`ExportFormat::Csv`] exports.
+pub const EXPORT_CSV_HEADER: &str = "id,text,hash,is_valid,added_at,kinds";
+
+#[derive(QueryableByName, Debug)]
+pub struct ExportLine {
+    /// Id of the exported signature, i.e. the cursor to continue the export from.
+    #[diesel(sql_type = Int4)]
+    pub id: i32,
+
+    #[diesel(sql_type = Text)]
+    pub line: String,
+}
+
+/// Direction of sorted results, see [`SignatureSort`] and [`SourceSort`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Sort key of signature results, sorted in the direction documented per key unless a [`SortOrder`] is
+/// given. Ties are ordered by `id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignatureSort {
+    /// Oldest signatures first, i.e. by `id` ascending.
+    #[default]
+    Id,
+
+    /// Most used signatures on chain first.
+    Usage,
+
+    /// Most recently added signatures first.
+    Added,
+
+    /// Signatures in alphabetical order of their text.
+    Text,
+}
+
+impl SignatureSort {
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SignatureSort::Id | SignatureSort::Text => SortOrder::Asc,
+            SignatureSort::Usage | SignatureSort::Added => SortOrder::Desc,
+        }
+    }
+}
+
+/// Sort key of [`RestHandler::sources_github`] results, sorted in the direction documented per key unless a
+/// [`SortOrder`] is given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SourceSort {
+    /// Most starred repositories first.
+    #[default]
+    Stars,
+
+    /// Most recently added repositories first.
+    Added,
+}
+
+impl SourceSort {
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SourceSort::Stars | SourceSort::Added => SortOrder::Desc,
+        }
+    }
+}
+
+/// Filters of [`RestHandler::signatures_search`], all of which are optional.
+#[derive(Debug, Clone, Default)]
+pub struct SignatureSearch {
+    /// Text prefix the signatures start with, or their hash (8 or 64 hex characters) if prefixed by `0x`.
+    pub query: Option<String>,
+    pub kind: Option<SignatureKind>,
+    pub source: Option<SignatureSource>,
+    pub sort: SignatureSort,
+
+    /// Direction of `sort`, its default direction if `None`.
+    pub order: Option<SortOrder>,
+}
+
+/// Flag inserted or merged into, see [`RestHandler::signature_flags_insert`].
+#[derive(QueryableByName)]
+struct FlagId {
+    #[diesel(sql_type = Int4)]
+    id: i32,
+}
+
+/// Source mapping summary of a signature, see [`RestHandler::signature_detail`].
+#[derive(QueryableByName)]
+struct SourceSummary {
+    #[diesel(sql_type = BigInt)]
+    github: i64,
+
+    #[diesel(sql_type = BigInt)]
+    etherscan: i64,
+
+    #[diesel(sql_type = BigInt)]
+    fourbyte: i64,
+
+    #[diesel(sql_type = Nullable<Timestamptz>)]
+    first_seen_at: Option<DateTime<Utc>>,
+
+    #[diesel(sql_type = Nullable<Timestamptz>)]
+    last_seen_at: Option<DateTime<Utc>>,
+}
+
+/// Minimum word similarity (see `pg_trgm`s `<%` operator) of signatures found by
+/// [`RestHandler::signatures_search_fuzzy`]. Lower than `pg_trgm`s default of 0.6, which misses typos within
+/// camel-case identifiers, e.g. `tranfer` scoring 0.5 against `transferFrom(address,address,uint256)`.
+const FUZZY_SEARCH_THRESHOLD: f32 = 0.4;
+
+/// Maximum number of signatures containing the query respectively similar to it which are ranked by
+/// [`RestHandler::signatures_search_fuzzy`], bounding the sort for frequent fragments such as `address`.
+const MAX_FUZZY_SEARCH_CANDIDATES: i64 = 1000;
+
+/// Maximum number of signatures sharing a selector data is decoded with, see [`RestHandler::decode_error`].
+pub const MAX_DECODE_CANDIDATES: i64 = 100;
+
 type Response<T> = Option<RestResponse<Vec<T>>>;
+type CursorResponse<T> = Option<RestCursorResponse<Vec<T>>>;
+
+// `pg_trgm` operators, backed by the `signature.text` trigram indexes
+infix_operator!(TrigramSimilar, " % ", backend: Pg);
+infix_operator!(TrigramDistance, " <-> ", Float4, backend: Pg);
+infix_operator!(TrigramWordSimilar, " <% ", backend: Pg);
+infix_operator!(TrigramWordDistance, " <<-> ", Float4, backend: Pg);
+define_sql_function!(fn lower(x: Text) -> Text);
 
 impl<'a> RestHandler<'a> {
     pub fn new(connection: &'a Pool<ConnectionManager<PgConnection>>) -> Self {
         RestHandler { connection }
     }
 
+    /// Returns the signatures whose text starts with `entity_str`, ordered by `sort` (their id if `None`).
     pub fn signatures_where_text_starts_with(
         &self,
         entity_str: &str,
         entity_kind: Option<SignatureKind>,
+        source: Option<SignatureSource>,
+        sort: Option<SignatureSort>,
+        order: Option<SortOrder>,
         page: i64,
-    ) -> Response<Signature> {
-        use crate::database::schema::mapping_signature_kind;
-        use crate::database::schema::signature;
+    ) -> Result<Response<Signature>, Error> {
+        use crate::database::handler::signature::filter_by_kind_and_source;
         use crate::database::schema::signature::dsl::*;
-        // use crate::database::schema::mapping_signature_kind::dsl::*;
 
-        let (items, total_items, total_pages) = match entity_kind {
-            Some(entity_kind) => {
-                let query = signature
-                    .inner_join(mapping_signature_kind::table)
-                    .filter(
-                        signature::text
-                            .like(format!("{entity_str}%"))
-                            .and(signature::is_valid.eq(true))
-                            .and(mapping_signature_kind::kind.eq(entity_kind)),
-                    )
-                    .order_by(signature::id.asc())
-                    .select(signature::all_columns)
-                    .paginate(page);
+        let query = signature.filter(text.like(format!("{entity_str}%")).and(is_valid.eq(true))).into_boxed();
+        let query = filter_by_kind_and_source(query, entity_kind, source);
+        let query = order_signatures(query, sort.unwrap_or_default(), order);
 
-                query.load_and_count_pages::<Signature>(&mut self.connection.get().unwrap()).unwrap()
-            }
+        let (items, total_items, total_pages) =
+            query.paginate(page).load_and_count_pages::<Signature>(&mut self.connection.get()?)?;
 
-            None => {
-                let query = signature
-                    .filter(signature::text.like(format!("{entity_str}%")).and(signature::is_valid.eq(true)))
-                    .order_by(signature::id.asc())
-                    .select(signature::all_columns)
-                    .paginate(page);
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestResponse {
+                items,
+                total_items,
+                total_pages,
+            }),
+        })
+    }
 
-                query.load_and_count_pages::<Signature>(&mut self.connection.get().unwrap()).unwrap()
-            }
+    /// Returns the signatures whose text contains `entity_str` anywhere (case-insensitive), e.g. `Swap`
+    /// matching `swapExactTokensForTokens(...)` as well as `Swap(...)`, ordered by `sort` (their id if
+    /// `None`).
+    pub fn signatures_where_text_contains(
+        &self,
+        entity_str: &str,
+        entity_kind: Option<SignatureKind>,
+        source: Option<SignatureSource>,
+        sort: Option<SignatureSort>,
+        order: Option<SortOrder>,
+        page: i64,
+    ) -> Result<Response<Signature>, Error> {
+        use crate::database::handler::signature::filter_by_kind_and_source;
+        use crate::database::schema::signature::dsl::*;
+
+        let pattern = format!("%{}%", escape_like(entity_str));
+        let query = signature.filter(text.ilike(pattern).and(is_valid.eq(true))).into_boxed();
+        let query = filter_by_kind_and_source(query, entity_kind, source);
+        let query = order_signatures(query, sort.unwrap_or_default(), order);
+
+        let (items, total_items, total_pages) =
+            query.paginate(page).load_and_count_pages::<Signature>(&mut self.connection.get()?)?;
+
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestResponse {
+                items,
+                total_items,
+                total_pages,
+            }),
+        })
+    }
+
+    /// Returns the signatures whose text is similar to `entity_str` (see `pg_trgm`s `%` operator), ordered by
+    /// their similarity such that typos or differently cased queries still find the closest signatures. The
+    /// similarity ranking is replaced by `sort` if given.
+    pub fn signatures_where_text_similar_to(
+        &self,
+        entity_str: &str,
+        entity_kind: Option<SignatureKind>,
+        source: Option<SignatureSource>,
+        sort: Option<SignatureSort>,
+        order: Option<SortOrder>,
+        page: i64,
+    ) -> Result<Response<Signature>, Error> {
+        use crate::database::handler::signature::filter_by_kind_and_source;
+        use crate::database::schema::signature::dsl::*;
+
+        let similar = TrigramSimilar::new(text, entity_str.to_string().into_sql::<Text>());
+        let distance = TrigramDistance::new(text, entity_str.to_string().into_sql::<Text>());
+
+        let query = signature.filter(similar.and(is_valid.eq(true))).into_boxed();
+        let query = filter_by_kind_and_source(query, entity_kind, source);
+        let query = match sort {
+            Some(sort) => order_signatures(query, sort, order),
+            None => query.order_by((distance, id.asc())),
         };
 
-        match items.len() {
+        let (items, total_items, total_pages) =
+            query.paginate(page).load_and_count_pages::<Signature>(&mut self.connection.get()?)?;
+
+        Ok(match items.len() {
             0 => None,
             _ => Some(RestResponse {
                 items,
                 total_items,
                 total_pages,
             }),
-        }
+        })
+    }
+
+    /// Returns the signatures either containing `entity_str` (case-insensitive) or a word similar to it (see
+    /// `pg_trgm`s `<%` operator), such that typos as well as camel-case fragments find the intended
+    /// signature. Signatures containing `entity_str` come first, followed by ones ranked by their similarity.
+    /// Only the [`MAX_FUZZY_SEARCH_CANDIDATES`] most similar signatures of each are ranked.
+    pub fn signatures_search_fuzzy(
+        &self,
+        entity_str: &str,
+        entity_kind: Option<SignatureKind>,
+        source: Option<SignatureSource>,
+        page: i64,
+    ) -> Result<Response<Signature>, Error> {
+        use crate::database::handler::signature::filter_by_kind_and_source;
+        use crate::database::schema::signature::dsl::*;
+
+        let pattern = format!("%{}%", escape_like(entity_str));
+        let similar = TrigramWordSimilar::new(entity_str.to_string().into_sql::<Text>(), text);
+        let distance = || TrigramWordDistance::new(entity_str.to_string().into_sql::<Text>(), text);
+
+        // Both branches are bounded on their own, each being backed by the trigram index. Bounding them by
+        // the same order as the final one ensures the best matches are never cut off.
+        let containing = signature.filter(text.ilike(pattern.clone()).and(is_valid.eq(true))).into_boxed();
+        let containing = filter_by_kind_and_source(containing, entity_kind, source)
+            .select(id)
+            .order_by((distance(), id.asc()))
+            .limit(MAX_FUZZY_SEARCH_CANDIDATES);
+        let similar = signature.filter(similar.and(is_valid.eq(true))).into_boxed();
+        let similar = filter_by_kind_and_source(similar, entity_kind, source)
+            .select(id)
+            .order_by((distance(), id.asc()))
+            .limit(MAX_FUZZY_SEARCH_CANDIDATES);
+
+        let query = signature
+            .filter(id.eq_any(containing).or(id.eq_any(similar)))
+            .order_by((text.ilike(pattern).desc(), distance(), id.asc()));
+
+        // The threshold only applies to this transaction, i.e. isn't leaked to other users of the connection
+        let (items, total_items, total_pages) = self.connection.get()?.transaction(|connection| {
+            sql_query(format!("SET LOCAL pg_trgm.word_similarity_threshold = {FUZZY_SEARCH_THRESHOLD}"))
+                .execute(connection)?;
+
+            query.paginate(page).load_and_count_pages::<Signature>(connection)
+        })?;
+
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestResponse {
+                items,
+                total_items,
+                total_pages,
+            }),
+        })
     }
 
+    /// Returns the signatures whose hash starts with `entity_str`, which has to be either a 4 byte selector
+    /// or a whole 32 byte hash (i.e. 8 or 64 hex characters), looked up by the indexed `selector` and
+    /// `topic0` columns respectively. Signatures sharing the hash are ranked by their on-chain usage unless
+    /// `sort` is given, i.e. the most likely decoding comes first.
     pub fn signature_where_hash_starts_with(
         &self,
         entity_str: &str,
         entity_kind: Option<SignatureKind>,
+        source: Option<SignatureSource>,
+        sort: Option<SignatureSort>,
+        order: Option<SortOrder>,
         page: i64,
-    ) -> Response<Signature> {
+    ) -> Result<Response<Signature>, Error> {
+        use crate::database::handler::signature::filter_by_kind_and_source;
+        use crate::database::schema::signature::dsl::*;
+
+        let entity_hash = match decode_hash(entity_str) {
+            Some(val) if val.len() == 4 || val.len() == 32 => val,
+            _ => return Ok(None),
+        };
+
+        let query = signature.filter(is_valid.eq(true)).into_boxed();
+        let query = match entity_hash.len() {
+            4 => query.filter(selector.eq(entity_hash)),
+            _ => query.filter(topic0.eq(entity_hash)),
+        };
+        let query = filter_by_kind_and_source(query, entity_kind, source);
+        let query = order_signatures(query, sort.unwrap_or(SignatureSort::Usage), order);
+
+        let (items, total_items, total_pages) =
+            query.paginate(page).load_and_count_pages::<Signature>(&mut self.connection.get()?)?;
+
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestResponse {
+                items,
+                total_items,
+                total_pages,
+            }),
+        })
+    }
+
+    /// Returns the signature with the given id alongside its kinds, the number of places it was found at per
+    /// source, the dates it was first and last seen at and the standards defining it.
+    pub fn signature_detail(&self, entity_id: i32) -> Result<Option<SignatureDetail>, Error> {
         use crate::database::schema::mapping_signature_kind;
-        // use crate::database::schema::mapping_signature_kind::dsl::*;
         use crate::database::schema::signature;
+
+        let mut connection = self.connection.get()?;
+        let entity: Signature = match signature::table.find(entity_id).first(&mut connection).optional()? {
+            Some(entity) => entity,
+            None => return Ok(None),
+        };
+
+        let kinds = mapping_signature_kind::table
+            .filter(mapping_signature_kind::signature_id.eq(entity_id))
+            .select(mapping_signature_kind::kind)
+            .order_by(mapping_signature_kind::kind.asc())
+            .get_results(&mut connection)?;
+
+        let summary: SourceSummary = sql_query(
+            "SELECT
+                (
+                    SELECT COUNT(DISTINCT repository_id) FROM mapping_signature_github WHERE signature_id = $1
+                ) AS github,
+                (
+                    SELECT COUNT(DISTINCT contract_id) FROM mapping_signature_etherscan WHERE signature_id = $1
+                ) AS etherscan,
+                (SELECT COUNT(*) FROM mapping_signature_fourbyte WHERE signature_id = $1) AS fourbyte,
+                MIN(added_at) AS first_seen_at,
+                MAX(last_seen_at) AS last_seen_at
+            FROM (
+                SELECT added_at, last_seen_at FROM mapping_signature_github WHERE signature_id = $1
+                UNION ALL
+                SELECT added_at, last_seen_at FROM mapping_signature_etherscan WHERE signature_id = $1
+                UNION ALL
+                SELECT added_at, last_seen_at FROM mapping_signature_fourbyte WHERE signature_id = $1
+            ) AS seen",
+        )
+        .bind::<Int4, _>(entity_id)
+        .get_result(&mut connection)?;
+
+        Ok(Some(SignatureDetail {
+            standards: standards_of(&entity.text),
+            signature: entity,
+            kinds,
+            sources: SignatureSourceCounts {
+                github: summary.github,
+                etherscan: summary.etherscan,
+                fourbyte: summary.fourbyte,
+            },
+            first_seen_at: summary.first_seen_at,
+            last_seen_at: summary.last_seen_at,
+        }))
+    }
+
+    /// Returns the text of the valid signatures of the given kind whose selector (4 bytes) or topic
+    /// (32 bytes) equals `entity_hash`, most used ones first, limited to [`MAX_DECODE_CANDIDATES`].
+    pub fn signature_texts_where_hash(
+        &self,
+        entity_hash: &[u8],
+        entity_kind: SignatureKind,
+    ) -> Result<Vec<String>, Error> {
+        use crate::database::handler::signature::filter_by_kind_and_source;
         use crate::database::schema::signature::dsl::*;
 
-        let (items, total_items, total_pages) = match entity_kind {
-            Some(entity_kind) => {
-                let query = signature
-                    .inner_join(mapping_signature_kind::table)
-                    .filter(
-                        signature::hash
-                            .like(format!("{entity_str}%"))
-                            .and(signature::is_valid.eq(true))
-                            .and(mapping_signature_kind::kind.eq(entity_kind)),
-                    )
-                    .order_by(signature::id.asc())
-                    .select(signature::all_columns)
-                    .paginate(page);
+        let query = signature.filter(is_valid.eq(true)).into_boxed();
+        let query = match entity_hash.len() {
+            4 => query.filter(selector.eq(entity_hash)),
+            _ => query.filter(topic0.eq(entity_hash)),
+        };
 
-                query.load_and_count_pages::<Signature>(&mut self.connection.get().unwrap()).unwrap()
-            }
+        Ok(filter_by_kind_and_source(query, Some(entity_kind), None)
+            .order_by((usage_count.desc(), id.asc()))
+            .select(text)
+            .limit(MAX_DECODE_CANDIDATES)
+            .get_results(&mut self.connection.get()?)?)
+    }
 
-            None => {
-                let query = signature
-                    .filter(signature::hash.like(format!("{entity_str}%")).and(signature::is_valid.eq(true)))
-                    .order_by(signature::id.asc())
-                    .select(signature::all_columns)
-                    .paginate(page);
+    /// Returns the Etherscan contract deployed at `entity_address` (compared case-insensitively) on the chain
+    /// `entity_chain_id` alongside all signatures found within its ABI, ordered by their kind and text, and
+    /// all hashes found within its bytecode if it's unverified.
+    pub fn contract_detail(
+        &self,
+        entity_address: &str,
+        entity_chain_id: i32,
+    ) -> Result<Option<ContractDetail>, Error> {
+        use crate::database::schema::etherscan_bytecode_hash;
+        use crate::database::schema::etherscan_contract::dsl::*;
+        use crate::database::schema::mapping_signature_etherscan;
+        use crate::database::schema::signature;
+
+        let connection = &mut self.connection.get()?;
+        let contract = match etherscan_contract
+            .filter(lower(address).eq(entity_address.to_lowercase()).and(chain_id.eq(entity_chain_id)))
+            .first::<EtherscanContract>(connection)
+            .optional()?
+        {
+            Some(contract) => contract,
+            None => return Ok(None),
+        };
+
+        let signatures = mapping_signature_etherscan::table
+            .inner_join(signature::table)
+            .filter(mapping_signature_etherscan::contract_id.eq(contract.id))
+            .order_by((mapping_signature_etherscan::kind.asc(), signature::text.asc()))
+            .select((signature::all_columns, mapping_signature_etherscan::kind))
+            .load::<(Signature, SignatureKind)>(connection)?
+            .into_iter()
+            .map(|(signature, kind)| ContractSignature { signature, kind })
+            .collect();
+
+        let bytecode_hashes = etherscan_bytecode_hash::table
+            .filter(etherscan_bytecode_hash::contract_id.eq(contract.id))
+            .order_by((etherscan_bytecode_hash::kind.asc(), etherscan_bytecode_hash::hash.asc()))
+            .select((etherscan_bytecode_hash::hash, etherscan_bytecode_hash::kind))
+            .load::<ContractBytecodeHash>(connection)?;
+
+        Ok(Some(ContractDetail {
+            contract,
+            signatures,
+            bytecode_hashes,
+        }))
+    }
+
+    /// Returns the signatures of any of the given kinds whose selector (4 bytes) or topic (32 bytes) is one
+    /// of `entity_hashes`, most used ones first. Invalid signatures are only returned if `include_invalid`.
+    pub fn signatures_where_hashes(
+        &self,
+        entity_hashes: &[Vec<u8>],
+        entity_kinds: &[SignatureKind],
+        include_invalid: bool,
+    ) -> Result<Vec<Signature>, Error> {
+        use crate::database::schema::mapping_signature_kind;
+        use crate::database::schema::signature::dsl::*;
+
+        let (selectors, topics): (Vec<_>, Vec<_>) = entity_hashes.iter().cloned().partition(|x| x.len() == 4);
+        let kinds = mapping_signature_kind::table
+            .filter(mapping_signature_kind::kind.eq_any(entity_kinds.to_vec()))
+            .select(mapping_signature_kind::signature_id);
+
+        let mut query = signature
+            .filter(selector.eq_any(selectors).or(topic0.eq_any(topics)))
+            .filter(id.eq_any(kinds))
+            .into_boxed();
+        if !include_invalid {
+            query = query.filter(is_valid.eq(true));
+        }
 
-                query.load_and_count_pages::<Signature>(&mut self.connection.get().unwrap()).unwrap()
+        Ok(query.order_by((usage_count.desc(), id.asc())).load(&mut self.connection.get()?)?)
+    }
+
+    /// Decodes revert `data` by the errors built into Solidity and all stored errors sharing its selector,
+    /// see [`crate::decoder`]. Returns `None` if no error decodes it.
+    pub fn decode_error(&self, data: &[u8]) -> Result<Option<Decoded>, Error> {
+        if data.len() < 4 {
+            return Ok(None);
+        }
+
+        let mut candidates = self.signature_texts_where_hash(&data[..4], SignatureKind::Error)?;
+        candidates.retain(|x| !BUILTIN_ERRORS.contains(&x.as_str()));
+
+        let candidates = BUILTIN_ERRORS.iter().copied().chain(candidates.iter().map(String::as_str));
+        Ok(decode_with_selector(candidates, data).filter(|x| !x.candidates.is_empty()))
+    }
+
+    /// Decodes the input, logs and `revert_data` of a `transaction` and its `receipt` (`None` if pending), as
+    /// fetched from a node by [`crate::api::rpc::RpcClient`], with the stored functions, events and errors.
+    pub fn decode_transaction(
+        &self,
+        transaction: &RpcTransaction,
+        receipt: Option<&RpcReceipt>,
+        revert_data: Option<&[u8]>,
+    ) -> Result<DecodedTransaction, Error> {
+        let input = parse_hex(&transaction.input).unwrap_or_default();
+        let input = match input.len() >= 4 {
+            true => {
+                let candidates = self.signature_texts_where_hash(&input[..4], SignatureKind::Function)?;
+                decode_with_selector(candidates.iter().map(String::as_str), &input)
             }
+
+            false => None,
+        };
+
+        // Logs of a transaction mostly share a handful of events, e.g. `Transfer`, hence look each up once
+        let mut events: HashMap<Vec<u8>, Vec<String>> = HashMap::new();
+        let mut logs = Vec::new();
+        for log in receipt.map(|x| x.logs.as_slice()).unwrap_or_default() {
+            let topics = log.topics.iter().filter_map(|x| parse_hex(x)).collect::<Vec<_>>();
+            let data = parse_hex(&log.data).unwrap_or_default();
+
+            let candidates = match topics.first() {
+                Some(topic0) if topic0.len() == 32 => {
+                    if !events.contains_key(topic0) {
+                        let candidates = self.signature_texts_where_hash(topic0, SignatureKind::Event)?;
+                        events.insert(topic0.clone(), candidates);
+                    }
+
+                    events[topic0].as_slice()
+                }
+
+                // Anonymous events can't be looked up
+                _ => &[],
+            };
+
+            logs.push(decode_log(candidates.iter().map(String::as_str), &log.address, &topics, &data));
+        }
+
+        let revert = match revert_data {
+            Some(data) => self.decode_error(data)?,
+            None => None,
         };
 
-        match items.len() {
+        Ok(DecodedTransaction {
+            hash: transaction.hash.clone(),
+            from: transaction.from.clone(),
+            to: transaction.to.clone(),
+            value: parse_wei(&transaction.value).unwrap_or_default(),
+            block_number: transaction.block_number.as_deref().and_then(parse_quantity),
+            succeeded: receipt.and_then(|x| x.status.as_deref()).map(|x| x == "0x1"),
+            input,
+            logs,
+            revert_data: revert_data.map(encode_hex),
+            revert,
+        })
+    }
+
+    /// Returns the selectors shared by multiple valid function / error signatures as of the last materialized
+    /// view refresh, ordered by their number of signatures. The signatures of each selector can be looked up
+    /// with [`RestHandler::signature_where_hash_starts_with`].
+    pub fn selector_collisions(&self, page: i64) -> Result<Response<SelectorCollision>, Error> {
+        use crate::database::schema::view_selector_collision::dsl::*;
+
+        let (items, total_items, total_pages) = view_selector_collision
+            .order_by((signature_count.desc(), selector.asc()))
+            .paginate(page)
+            .load_and_count_pages::<SelectorCollision>(&mut self.connection.get()?)?;
+
+        Ok(match items.len() {
             0 => None,
             _ => Some(RestResponse {
                 items,
                 total_items,
                 total_pages,
             }),
-        }
+        })
     }
 
+    /// Returns the non-forked GitHub repositories the signature `entity_id` was found in, ordered by `sort`.
     pub fn sources_github(
         &self,
         entity_id: i32,
         entity_kind: Option<SignatureKind>,
+        sort: SourceSort,
+        order: Option<SortOrder>,
         page: i64,
-    ) -> Response<GithubRepositoryDatabase> {
-        use crate::database::schema::github_repository;
+    ) -> Result<Response<GithubRepositoryDatabase>, Error> {
         use crate::database::schema::github_repository::dsl::*;
         use crate::database::schema::mapping_signature_github;
-        // use crate::database::schema::mapping_signature_github::dsl::*;
-
-        let (items, total_items, total_pages) = match entity_kind {
-            Some(entity_kind) => {
-                let query = github_repository
-                    .inner_join(mapping_signature_github::table)
-                    .filter(
-                        mapping_signature_github::signature_id
-                            .eq(entity_id)
-                            .and(mapping_signature_github::kind.eq(entity_kind))
-                            .and(github_repository::fork.eq(false)),
-                    )
-                    .order_by(github_repository::stargazers_count.desc())
-                    .distinct_on((github_repository::id, github_repository::stargazers_count))
-                    .select(github_repository::all_columns)
-                    .paginate(page);
-
-                query
-                    .load_and_count_pages::<GithubRepositoryDatabase>(&mut self.connection.get().unwrap())
-                    .unwrap()
-            }
 
-            None => {
-                let query = github_repository
-                    .inner_join(mapping_signature_github::table)
-                    .filter(
-                        mapping_signature_github::signature_id
-                            .eq(entity_id)
-                            .and(github_repository::fork.eq(false)),
-                    )
-                    .order_by(github_repository::stargazers_count.desc())
-                    .distinct_on((github_repository::id, github_repository::stargazers_count))
-                    .select(github_repository::all_columns)
-                    .paginate(page);
+        // Filtered by a subquery rather than joined, as repositories may contain the signature as several
+        // kinds and would otherwise be listed once per kind
+        let mut mappings = mapping_signature_github::table
+            .filter(mapping_signature_github::signature_id.eq(entity_id))
+            .select(mapping_signature_github::repository_id)
+            .into_boxed();
+        if let Some(entity_kind) = entity_kind {
+            mappings = mappings.filter(mapping_signature_github::kind.eq(entity_kind));
+        }
 
-                query
-                    .load_and_count_pages::<GithubRepositoryDatabase>(&mut self.connection.get().unwrap())
-                    .unwrap()
-            }
+        let query = github_repository.filter(id.eq_any(mappings).and(fork.eq(false))).into_boxed();
+        let query = match (sort, order.unwrap_or(sort.default_order())) {
+            (SourceSort::Stars, SortOrder::Asc) => query.order_by((stargazers_count.asc(), id.asc())),
+            (SourceSort::Stars, SortOrder::Desc) => query.order_by((stargazers_count.desc(), id.asc())),
+            (SourceSort::Added, SortOrder::Asc) => query.order_by((added_at.asc(), id.asc())),
+            (SourceSort::Added, SortOrder::Desc) => query.order_by((added_at.desc(), id.desc())),
         };
 
-        match items.len() {
+        let (items, total_items, total_pages) = query
+            .paginate(page)
+            .load_and_count_pages::<GithubRepositoryDatabase>(&mut self.connection.get()?)?;
+
+        Ok(match items.len() {
             0 => None,
             _ => Some(RestResponse {
                 items,
                 total_items,
                 total_pages,
             }),
-        }
+        })
     }
 
+    /// Returns the Etherscan contracts the signature `entity_id` was found in, ordered by their `added_at` in
+    /// `order` (most recently added first if `None`).
     pub fn sources_etherscan(
         &self,
         entity_id: i32,
         entity_kind: Option<SignatureKind>,
+        order: Option<SortOrder>,
         page: i64,
-    ) -> Response<EtherscanContract> {
-        use crate::database::schema::etherscan_contract;
+    ) -> Result<Response<EtherscanContract>, Error> {
         use crate::database::schema::etherscan_contract::dsl::*;
         use crate::database::schema::mapping_signature_etherscan;
-        // use crate::database::schema::mapping_signature_github::dsl::*;
 
-        let (items, total_items, total_pages) = match entity_kind {
-            Some(entity_kind) => {
-                let query = etherscan_contract
-                    .inner_join(mapping_signature_etherscan::table)
-                    .filter(
-                        mapping_signature_etherscan::signature_id
-                            .eq(entity_id)
-                            .and(mapping_signature_etherscan::kind.eq(entity_kind)),
-                    )
-                    .order_by(etherscan_contract::added_at.desc())
-                    .distinct_on((etherscan_contract::id, etherscan_contract::added_at))
-                    .select(etherscan_contract::all_columns)
-                    .paginate(page);
-
-                query.load_and_count_pages::<EtherscanContract>(&mut self.connection.get().unwrap()).unwrap()
-            }
-            None => {
-                let query = etherscan_contract
-                    .inner_join(mapping_signature_etherscan::table)
-                    .filter(mapping_signature_etherscan::signature_id.eq(entity_id))
-                    .order_by(etherscan_contract::added_at.desc())
-                    .distinct_on((etherscan_contract::id, etherscan_contract::added_at))
-                    .select(etherscan_contract::all_columns)
-                    .paginate(page);
+        let mut mappings = mapping_signature_etherscan::table
+            .filter(mapping_signature_etherscan::signature_id.eq(entity_id))
+            .select(mapping_signature_etherscan::contract_id)
+            .into_boxed();
+        if let Some(entity_kind) = entity_kind {
+            mappings = mappings.filter(mapping_signature_etherscan::kind.eq(entity_kind));
+        }
 
-                query.load_and_count_pages::<EtherscanContract>(&mut self.connection.get().unwrap()).unwrap()
-            }
+        let query = etherscan_contract.filter(id.eq_any(mappings)).into_boxed();
+        let query = match order.unwrap_or(SortOrder::Desc) {
+            SortOrder::Asc => query.order_by((added_at.asc(), id.asc())),
+            SortOrder::Desc => query.order_by((added_at.desc(), id.desc())),
         };
 
-        match items.len() {
+        let (items, total_items, total_pages) =
+            query.paginate(page).load_and_count_pages::<EtherscanContract>(&mut self.connection.get()?)?;
+
+        Ok(match items.len() {
             0 => None,
             _ => Some(RestResponse {
                 items,
                 total_items,
                 total_pages,
             }),
-        }
+        })
     }
 
-    pub fn statistics_signature_insert_rate(&self) -> Vec<ViewSignatureInsertRate> {
-        sql_query("SELECT date, count FROM view_signature_insert_rate")
-            .get_results(&self.connection.get().unwrap())
-            .unwrap()
-    }
+    /// Cursor paginated version of [`RestHandler::signatures_where_text_starts_with`], ordered by the
+    /// signatures id.
+    pub fn signatures_where_text_starts_with_after(
+        &self,
+        entity_str: &str,
+        entity_kind: Option<SignatureKind>,
+        cursor: Option<Cursor>,
+    ) -> Result<CursorResponse<Signature>, Error> {
+        use crate::database::schema::mapping_signature_kind;
+        use crate::database::schema::signature;
+        use crate::database::schema::signature::dsl::*;
 
-    pub fn statistics_various_signature_counts(&self) -> ViewSignatureCountStatistics {
-        sql_query("SELECT signature_count, signature_count_github, signature_count_etherscan, signature_count_fourbyte, average_daily_signature_insert_rate_last_week, average_daily_signature_insert_rate_week_before_last FROM view_signature_count_statistics")
-            .get_result(&self.connection.get().unwrap())
-            .unwrap()
-    }
+        let (items, next_cursor) = match entity_kind {
+            Some(entity_kind) => {
+                let query = signature
+                    .inner_join(mapping_signature_kind::table)
+                    .filter(
+                        signature::text
+                            .like(format!("{entity_str}%"))
+                            .and(signature::is_valid.eq(true))
+                            .and(mapping_signature_kind::kind.eq(entity_kind)),
+                    )
+                    .select(signature::all_columns)
+                    .paginate_after(CursorOrder::IdAsc, cursor);
 
-    pub fn statistics_signatures_popular_on_github(&self) -> Vec<ViewSignaturesPopularOnGithub> {
-        sql_query("SELECT text, count FROM view_signatures_popular_on_github")
-            .get_results(&self.connection.get().unwrap())
-            .unwrap()
-    }
+                query.load_with_cursor::<Signature>(&mut self.connection.get()?)?
+            }
+
+            None => {
+                let query = signature
+                    .filter(signature::text.like(format!("{entity_str}%")).and(signature::is_valid.eq(true)))
+                    .select(signature::all_columns)
+                    .paginate_after(CursorOrder::IdAsc, cursor);
+
+                query.load_with_cursor::<Signature>(&mut self.connection.get()?)?
+            }
+        };
+
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestCursorResponse { items, next_cursor }),
+        })
+    }
+
+    /// Cursor paginated version of [`RestHandler::signatures_where_text_contains`], ordered by the signatures
+    /// id.
+    pub fn signatures_where_text_contains_after(
+        &self,
+        entity_str: &str,
+        entity_kind: Option<SignatureKind>,
+        cursor: Option<Cursor>,
+    ) -> Result<CursorResponse<Signature>, Error> {
+        use crate::database::schema::mapping_signature_kind;
+        use crate::database::schema::signature;
+        use crate::database::schema::signature::dsl::*;
+
+        let pattern = format!("%{}%", escape_like(entity_str));
+        let (items, next_cursor) = match entity_kind {
+            Some(entity_kind) => {
+                let query = signature
+                    .inner_join(mapping_signature_kind::table)
+                    .filter(
+                        signature::text
+                            .ilike(pattern)
+                            .and(signature::is_valid.eq(true))
+                            .and(mapping_signature_kind::kind.eq(entity_kind)),
+                    )
+                    .select(signature::all_columns)
+                    .paginate_after(CursorOrder::IdAsc, cursor);
+
+                query.load_with_cursor::<Signature>(&mut self.connection.get()?)?
+            }
+
+            None => {
+                let query = signature
+                    .filter(signature::text.ilike(pattern).and(signature::is_valid.eq(true)))
+                    .select(signature::all_columns)
+                    .paginate_after(CursorOrder::IdAsc, cursor);
+
+                query.load_with_cursor::<Signature>(&mut self.connection.get()?)?
+            }
+        };
+
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestCursorResponse { items, next_cursor }),
+        })
+    }
+
+    /// Cursor paginated version of [`RestHandler::signature_where_hash_starts_with`], ordered by the
+    /// signatures id.
+    pub fn signature_where_hash_starts_with_after(
+        &self,
+        entity_str: &str,
+        entity_kind: Option<SignatureKind>,
+        cursor: Option<Cursor>,
+    ) -> Result<CursorResponse<Signature>, Error> {
+        use crate::database::schema::mapping_signature_kind;
+        use crate::database::schema::signature;
+        use crate::database::schema::signature::dsl::*;
+
+        let entity_hash = match decode_hash(entity_str) {
+            Some(val) if val.len() == 4 || val.len() == 32 => val,
+            _ => return Ok(None),
+        };
+
+        let (items, next_cursor) = match entity_kind {
+            Some(entity_kind) => {
+                let query = signature
+                    .inner_join(mapping_signature_kind::table)
+                    .filter(signature::is_valid.eq(true).and(mapping_signature_kind::kind.eq(entity_kind)))
+                    .select(signature::all_columns)
+                    .into_boxed();
+
+                let query = match entity_hash.len() {
+                    4 => query.filter(signature::selector.eq(entity_hash)),
+                    _ => query.filter(signature::topic0.eq(entity_hash)),
+                };
+
+                query
+                    .paginate_after(CursorOrder::IdAsc, cursor)
+                    .load_with_cursor::<Signature>(&mut self.connection.get()?)?
+            }
+
+            None => {
+                let query = signature
+                    .filter(signature::is_valid.eq(true))
+                    .select(signature::all_columns)
+                    .into_boxed();
+
+                let query = match entity_hash.len() {
+                    4 => query.filter(signature::selector.eq(entity_hash)),
+                    _ => query.filter(signature::topic0.eq(entity_hash)),
+                };
+
+                query
+                    .paginate_after(CursorOrder::IdAsc, cursor)
+                    .load_with_cursor::<Signature>(&mut self.connection.get()?)?
+            }
+        };
+
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestCursorResponse { items, next_cursor }),
+        })
+    }
+
+    /// Cursor paginated version of [`RestHandler::sources_etherscan`], ordered by the contracts `added_at`
+    /// descending.
+    pub fn sources_etherscan_after(
+        &self,
+        entity_id: i32,
+        entity_kind: Option<SignatureKind>,
+        cursor: Option<Cursor>,
+    ) -> Result<CursorResponse<EtherscanContract>, Error> {
+        use crate::database::schema::etherscan_contract;
+        use crate::database::schema::etherscan_contract::dsl::*;
+        use crate::database::schema::mapping_signature_etherscan;
+
+        // The distinct columns have to be a prefix of the inner ordering, which however is irrelevant as the
+        // rows are re-ordered by the outer cursor query anyway
+        let (items, next_cursor) = match entity_kind {
+            Some(entity_kind) => {
+                let query = etherscan_contract
+                    .inner_join(mapping_signature_etherscan::table)
+                    .filter(
+                        mapping_signature_etherscan::signature_id
+                            .eq(entity_id)
+                            .and(mapping_signature_etherscan::kind.eq(entity_kind)),
+                    )
+                    .order_by((etherscan_contract::id, etherscan_contract::added_at))
+                    .distinct_on((etherscan_contract::id, etherscan_contract::added_at))
+                    .select(etherscan_contract::all_columns)
+                    .paginate_after(CursorOrder::AddedAtDesc, cursor);
+
+                query.load_with_cursor::<EtherscanContract>(&mut self.connection.get()?)?
+            }
+            None => {
+                let query = etherscan_contract
+                    .inner_join(mapping_signature_etherscan::table)
+                    .filter(mapping_signature_etherscan::signature_id.eq(entity_id))
+                    .order_by((etherscan_contract::id, etherscan_contract::added_at))
+                    .distinct_on((etherscan_contract::id, etherscan_contract::added_at))
+                    .select(etherscan_contract::all_columns)
+                    .paginate_after(CursorOrder::AddedAtDesc, cursor);
+
+                query.load_with_cursor::<EtherscanContract>(&mut self.connection.get()?)?
+            }
+        };
+
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestCursorResponse { items, next_cursor }),
+        })
+    }
+
+    /// Returns the valid signatures matching all filters of `search`, `page_size` signatures per page.
+    pub fn signatures_search(
+        &self,
+        search: &SignatureSearch,
+        page: i64,
+        page_size: i64,
+    ) -> Result<Response<Signature>, Error> {
+        use crate::database::handler::signature::filter_by_kind_and_source;
+        use crate::database::schema::signature::dsl::*;
+
+        let query = signature.filter(is_valid.eq(true)).into_boxed();
+        let mut query = filter_by_kind_and_source(query, search.kind, search.source);
+
+        match search.query.as_deref() {
+            Some(entity_str) => match entity_str.strip_prefix("0x") {
+                Some(entity_str) => {
+                    let entity_hash = match decode_hash(entity_str) {
+                        Some(val) if val.len() == 4 || val.len() == 32 => val,
+                        _ => return Ok(None),
+                    };
+
+                    query = match entity_hash.len() {
+                        4 => query.filter(selector.eq(entity_hash)),
+                        _ => query.filter(topic0.eq(entity_hash)),
+                    };
+                }
+
+                None => query = query.filter(text.like(format!("{}%", escape_like(entity_str)))),
+            },
+
+            None => (),
+        }
+
+        let query = order_signatures(query, search.sort, search.order);
+        let (items, total_items, total_pages) = query
+            .paginate(page)
+            .per_page(page_size)
+            .load_and_count_pages::<Signature>(&mut self.connection.get()?)?;
+
+        Ok(match items.len() {
+            0 => None,
+            _ => Some(RestResponse {
+                items,
+                total_items,
+                total_pages,
+            }),
+        })
+    }
+
+    /// Returns the `limit` most recently inserted signatures, see
+    /// [`crate::database::handler::signature::SignatureHandler::get_latest`].
+    pub fn signatures_latest(
+        &self,
+        limit: i64,
+        entity_kind: Option<SignatureKind>,
+        source: Option<SignatureSource>,
+    ) -> Result<Vec<Signature>, Error> {
+        use crate::database::handler::signature::get_latest;
+
+        get_latest(&mut self.connection.get()?, limit, entity_kind, source)
+    }
+
+    pub fn statistics_signature_insert_rate(&self) -> Result<Vec<ViewSignatureInsertRate>, Error> {
+        Ok(sql_query("SELECT date, count FROM view_signature_insert_rate")
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    pub fn statistics_various_signature_counts(&self) -> Result<ViewSignatureCountStatistics, Error> {
+        Ok(sql_query("SELECT signature_count, signature_count_github, signature_count_etherscan, signature_count_fourbyte, average_daily_signature_insert_rate_last_week, average_daily_signature_insert_rate_week_before_last FROM view_signature_count_statistics")
+            .get_result(&mut self.connection.get()?)?)
+    }
+
+    pub fn statistics_signatures_popular_on_github(
+        &self,
+    ) -> Result<Vec<ViewSignaturesPopularOnGithub>, Error> {
+        Ok(sql_query("SELECT text, count FROM view_signatures_popular_on_github")
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    pub fn statistics_signature_kind_distribution(
+        &self,
+    ) -> Result<Vec<ViewSignatureKindDistribution>, Error> {
+        Ok(sql_query("SELECT kind, count FROM view_signature_kind_distribution")
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    pub fn statistics_signature_insert_rate_by_source(
+        &self,
+    ) -> Result<Vec<ViewSignatureInsertRateBySource>, Error> {
+        Ok(sql_query("SELECT date, source, count FROM view_signature_insert_rate_by_source")
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    pub fn statistics_repositories_top_unique_signatures(
+        &self,
+    ) -> Result<Vec<ViewRepositoriesTopUniqueSignatures>, Error> {
+        Ok(sql_query("SELECT repository_id, name, html_url, count FROM view_repositories_top_unique_signatures")
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    pub fn statistics_signatures_per_contract_distribution(
+        &self,
+    ) -> Result<Vec<ViewSignaturesPerContractDistribution>, Error> {
+        Ok(sql_query("SELECT bucket, count FROM view_signatures_per_contract_distribution")
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    pub fn statistics_etherscan_compiler_version_distribution(
+        &self,
+    ) -> Result<Vec<ViewEtherscanCompilerVersionDistribution>, Error> {
+        Ok(sql_query("SELECT compiler, version, count FROM view_etherscan_compiler_version_distribution")
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    pub fn statistics_etherscan_chain_distribution(
+        &self,
+    ) -> Result<Vec<ViewEtherscanChainDistribution>, Error> {
+        Ok(sql_query("SELECT chain_id, contract_count, signature_count FROM view_etherscan_chain_distribution")
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    /// Returns up to `limit` signatures (alongside their kinds) formatted as `format` whose id is greater
+    /// than `after_id` and which were added at or after `added_since`, ordered by their id. Unlike the search
+    /// endpoints invalid signatures are included as well, such that the whole table can be mirrored.
+    pub fn signatures_export(
+        &self,
+        format: ExportFormat,
+        after_id: i32,
+        added_since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<ExportLine>, Error> {
+        // Lines are formatted by the database, the same way as the snapshot exports (see `export.rs`)
+        let line = match format {
+            ExportFormat::Ndjson => "row_to_json(export)::TEXT",
+            ExportFormat::Csv => {
+                "array_to_string(ARRAY(
+                    SELECT COALESCE('\"' || replace(value, '\"', '\"\"') || '\"', '')
+                    FROM json_array_elements_text(json_build_array(
+                        id, text, hash, is_valid, added_at, array_to_string(kinds, ';')
+                    )) AS value
+                ), ',')"
+            }
+        };
+
+        Ok(sql_query(format!(
+            "SELECT id, {line} AS line FROM (
+                SELECT signature.id, signature.text, signature.hash, signature.is_valid, signature.added_at,
+                    ARRAY(
+                        SELECT kind::TEXT FROM mapping_signature_kind
+                        WHERE signature_id = signature.id ORDER BY kind
+                    ) AS kinds
+                FROM signature
+                WHERE signature.id > $1 AND ($2 IS NULL OR signature.added_at >= $2)
+                ORDER BY signature.id
+                LIMIT $3
+            ) AS export ORDER BY id"
+        ))
+        .bind::<Int4, _>(after_id)
+        .bind::<Nullable<Timestamptz>, _>(added_since)
+        .bind::<BigInt, _>(limit)
+        .load(&mut self.connection.get()?)?)
+    }
+
+    /// Returns all keys which haven't been revoked.
+    pub fn api_keys_active(&self) -> Result<Vec<ApiKey>, Error> {
+        use crate::database::schema::api_key::dsl::*;
+
+        Ok(api_key.filter(revoked_at.is_null()).order_by(id.asc()).get_results(&mut self.connection.get()?)?)
+    }
+
+    /// Adds the number of requests sent with each key since the last call, given as `(id, count)` pairs, to
+    /// the keys usage. Either all or none of the counts are added, such that failed calls can be retried.
+    pub fn api_keys_add_usage(&self, usage: &[(i32, i64)]) -> Result<(), Error> {
+        use crate::database::schema::api_key::dsl::*;
+
+        self.connection.get()?.transaction(|connection| {
+            for (entity_id, count) in usage {
+                diesel::update(api_key.find(entity_id))
+                    .set((request_count.eq(request_count + count), last_used_at.eq(Utc::now())))
+                    .execute(connection)?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Submits `signatures` for moderation on behalf of the consumer with the key `entity_api_key_id`,
+    /// returning their submissions in the same order. Signatures the consumer submitted before aren't
+    /// submitted again, instead their existing submission (and as such its moderation status) is returned.
+    pub fn signature_submissions_insert(
+        &self,
+        entity_api_key_id: i32,
+        signatures: &[SignatureWithMetadata],
+    ) -> Result<Vec<SignatureSubmission>, Error> {
+        use crate::database::schema::signature_submission;
+        use crate::database::schema::signature_submission::dsl::*;
+
+        let entities = signatures
+            .iter()
+            .map(|x| SignatureSubmissionInsert {
+                api_key_id: entity_api_key_id,
+                text: &x.text,
+                hash: &x.hash,
+                kind: x.kind,
+                is_valid: x.is_valid,
+                submitted_at: Utc::now(),
+            })
+            .collect::<Vec<_>>();
+
+        self.connection.get()?.transaction(|connection| {
+            diesel::insert_into(signature_submission::table)
+                .values(&entities)
+                .on_conflict_do_nothing()
+                .execute(connection)?;
+
+            let mut submissions = Vec::with_capacity(entities.len());
+            for entity in &entities {
+                let is_entity = text.eq(entity.text).and(kind.eq(entity.kind));
+                let submission = signature_submission.filter(api_key_id.eq(entity_api_key_id).and(is_entity));
+                submissions.push(submission.first(connection)?);
+            }
+
+            Ok(submissions)
+        })
+    }
+
+    /// Flags the mappings `entity` targets for moderation, returning `None` if there are no such mappings. If
+    /// the same target is already pending moderation for the same reason, its flag count is incremented and
+    /// the existing flag returned instead.
+    pub fn signature_flags_insert(
+        &self,
+        entity: &SignatureFlagInsert,
+    ) -> Result<Option<SignatureFlag>, Error> {
+        use crate::database::schema::signature_flag::dsl::*;
+        use crate::model::Flag_reason;
+        use crate::model::Signature_kind;
+        use crate::model::Signature_source;
+
+        self.connection.get()?.transaction(|connection| {
+            if !mappings_exist(connection, entity)? {
+                return Ok(None);
+            }
+
+            // Merged by the unique index on pending flags, such that concurrent flags can't both be inserted
+            let flagged: FlagId = sql_query(
+                "INSERT INTO signature_flag
+                    (signature_id, kind, source, source_id, reason, comment, api_key_id, flagged_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (signature_id, kind, source, source_id, reason) WHERE status = 'pending'
+                DO UPDATE SET flag_count = signature_flag.flag_count + 1
+                RETURNING id",
+            )
+            .bind::<Int4, _>(entity.signature_id)
+            .bind::<Signature_kind, _>(entity.kind)
+            .bind::<Nullable<Signature_source>, _>(entity.source)
+            .bind::<Nullable<Int4>, _>(entity.source_id)
+            .bind::<Flag_reason, _>(entity.reason)
+            .bind::<Nullable<Text>, _>(entity.comment)
+            .bind::<Nullable<Int4>, _>(entity.api_key_id)
+            .bind::<Timestamptz, _>(entity.flagged_at)
+            .get_result(connection)?;
+
+            Ok(Some(signature_flag.find(flagged.id).first(connection)?))
+        })
+    }
+
+    /// Returns the watchlist of the consumer with the key `entity_api_key_id`, oldest entries first.
+    pub fn watchlist_entries(&self, entity_api_key_id: i32) -> Result<Vec<WatchlistEntry>, Error> {
+        use crate::database::schema::watchlist_entry::dsl::*;
+
+        Ok(watchlist_entry
+            .filter(api_key_id.eq(entity_api_key_id))
+            .order_by(id.asc())
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    /// Adds `entity` to the consumers watchlist, returning `None` if it already holds `max_entries` entries.
+    /// Hashes the consumer already watches aren't added again, instead their existing entry is returned.
+    /// Hashes which are already known are resolved right away, without calling the webhook.
+    pub fn watchlist_entries_insert(
+        &self,
+        entity: &WatchlistEntryInsert,
+        max_entries: i64,
+    ) -> Result<Option<WatchlistEntry>, Error> {
+        use crate::database::schema::api_key;
+        use crate::database::schema::watchlist_entry;
+        use crate::database::schema::watchlist_entry::dsl::*;
+
+        self.connection.get()?.transaction(|connection| {
+            // Locking the consumers API key serializes its concurrent inserts, which otherwise could all pass
+            // the limit as they don't see each others uncommitted entries
+            api_key::table.find(entity.api_key_id).select(api_key::id).for_update().first::<i32>(connection)?;
+
+            let existing = watchlist_entry
+                .filter(api_key_id.eq(entity.api_key_id).and(hash.eq(entity.hash)))
+                .first(connection)
+                .optional()?;
+            if existing.is_some() {
+                return Ok(existing);
+            }
+
+            let count: i64 =
+                watchlist_entry.filter(api_key_id.eq(entity.api_key_id)).count().get_result(connection)?;
+            if count >= max_entries {
+                return Ok(None);
+            }
+
+            let inserted: WatchlistEntry =
+                diesel::insert_into(watchlist_entry::table).values(entity).get_result(connection)?;
+
+            // Same as `trigger_resolve_watchlist_entry`, preferring the signature used most often on chain
+            sql_query(
+                "WITH found AS (
+                    SELECT signature.id, signature.text, kinds.kind FROM signature
+                    JOIN mapping_signature_kind AS kinds ON kinds.signature_id = signature.id
+                    WHERE (kinds.kind = 'event' AND signature.hash = $2)
+                    OR (kinds.kind IN ('function', 'error') AND signature.selector = decode($2, 'hex'))
+                    ORDER BY signature.usage_count DESC, signature.id ASC
+                    LIMIT 1
+                )
+                UPDATE watchlist_entry
+                SET resolved_at = NOW(), signature_id = found.id, text = found.text, kind = found.kind
+                FROM found WHERE watchlist_entry.id = $1",
+            )
+            .bind::<Int4, _>(inserted.id)
+            .bind::<Text, _>(entity.hash)
+            .execute(connection)?;
+
+            Ok(Some(watchlist_entry.find(inserted.id).first(connection)?))
+        })
+    }
+
+    /// Removes the entry with the given id from the consumers watchlist, returning `None` if it has no such
+    /// entry.
+    pub fn watchlist_entries_delete(
+        &self,
+        entity_api_key_id: i32,
+        entity_id: i32,
+    ) -> Result<Option<WatchlistEntry>, Error> {
+        use crate::database::schema::watchlist_entry::dsl::*;
+
+        let entry = watchlist_entry.filter(id.eq(entity_id).and(api_key_id.eq(entity_api_key_id)));
+        Ok(diesel::delete(entry).get_result(&mut self.connection.get()?).optional()?)
+    }
+
+    /// Returns all webhooks registered by the consumer, see `handler::webhook`.
+    pub fn webhooks(&self, entity_api_key_id: i32) -> Result<Vec<Webhook>, Error> {
+        use crate::database::schema::webhook::dsl::*;
+
+        Ok(webhook
+            .filter(api_key_id.eq(entity_api_key_id))
+            .order_by(id.asc())
+            .get_results(&mut self.connection.get()?)?)
+    }
+
+    /// Registers `entity`, returning `None` if the consumer already registered `max_webhooks` webhooks. Only
+    /// signatures found after the registration are delivered, consumers mirroring the whole dataset should
+    /// start off with the `/export/signatures` endpoints.
+    pub fn webhooks_insert(
+        &self,
+        entity: &WebhookInsert,
+        max_webhooks: i64,
+    ) -> Result<Option<Webhook>, Error> {
+        use crate::database::schema::api_key;
+        use crate::database::schema::webhook;
+        use crate::database::schema::webhook::dsl::*;
+
+        self.connection.get()?.transaction(|connection| {
+            // Serializes concurrent registrations of the consumer, same as with `watchlist_entries_insert`
+            api_key::table.find(entity.api_key_id).select(api_key::id).for_update().first::<i32>(connection)?;
+
+            let count: i64 = webhook.filter(api_key_id.eq(entity.api_key_id)).count().get_result(connection)?;
+            if count >= max_webhooks {
+                return Ok(None);
+            }
+
+            Ok(Some(diesel::insert_into(webhook::table).values(entity).get_result(connection)?))
+        })
+    }
+
+    /// Removes the webhook with the given id alongside its pending deliveries, returning `None` if the
+    /// consumer has no such webhook.
+    pub fn webhooks_delete(&self, entity_api_key_id: i32, entity_id: i32) -> Result<Option<Webhook>, Error> {
+        use crate::database::schema::webhook::dsl::*;
+
+        let entity = webhook.filter(id.eq(entity_id).and(api_key_id.eq(entity_api_key_id)));
+        Ok(diesel::delete(entity).get_result(&mut self.connection.get()?).optional()?)
+    }
+
+    pub fn worker_status(&self) -> Result<Vec<WorkerStatus>, Error> {
+        use crate::database::schema::worker_status::dsl::*;
+
+        Ok(worker_status.order_by(name.asc()).get_results(&mut self.connection.get()?)?)
+    }
+
+    pub fn materialized_view_refresh(&self) -> Result<Vec<MaterializedViewRefresh>, Error> {
+        use crate::database::schema::materialized_view_refresh::dsl::*;
+
+        Ok(materialized_view_refresh.order_by(name.asc()).get_results(&mut self.connection.get()?)?)
+    }
+}
+
+/// Escapes the `LIKE` wildcards `%` and `_` (as well as the escape character itself) within `value`, such
+/// that they're matched literally.
+pub(crate) fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Orders the signature `query` by `sort` in `order`, respectively the default direction of `sort`.
+fn order_signatures(
+    query: crate::database::schema::signature::BoxedQuery<'_, Pg>,
+    sort: SignatureSort,
+    order: Option<SortOrder>,
+) -> crate::database::schema::signature::BoxedQuery<'_, Pg> {
+    use crate::database::schema::signature::dsl::*;
+
+    match (sort, order.unwrap_or(sort.default_order())) {
+        (SignatureSort::Id, SortOrder::Asc) => query.order_by(id.asc()),
+        (SignatureSort::Id, SortOrder::Desc) => query.order_by(id.desc()),
+        (SignatureSort::Usage, SortOrder::Asc) => query.order_by((usage_count.asc(), id.asc())),
+        (SignatureSort::Usage, SortOrder::Desc) => query.order_by((usage_count.desc(), id.asc())),
+        (SignatureSort::Added, SortOrder::Asc) => query.order_by((added_at.asc(), id.asc())),
+        (SignatureSort::Added, SortOrder::Desc) => query.order_by((added_at.desc(), id.desc())),
+        (SignatureSort::Text, SortOrder::Asc) => query.order_by((text.asc(), id.asc())),
+        (SignatureSort::Text, SortOrder::Desc) => query.order_by((text.desc(), id.asc())),
+    }
+}
+
+/// Returns whether any of the mappings the flag `entity` targets exist, see
+/// [`RestHandler::signature_flags_insert`].
+fn mappings_exist(connection: &mut PgConnection, entity: &SignatureFlagInsert) -> Result<bool, Error> {
+    use crate::database::schema::mapping_signature_etherscan;
+    use crate::database::schema::mapping_signature_fourbyte;
+    use crate::database::schema::mapping_signature_github;
+    use crate::database::schema::mapping_signature_kind;
+
+    let found = match entity.source {
+        None => mapping_signature_kind::table
+            .filter(mapping_signature_kind::signature_id.eq(entity.signature_id))
+            .filter(mapping_signature_kind::kind.eq(entity.kind))
+            .select(mapping_signature_kind::signature_id)
+            .first::<i32>(connection)
+            .optional()?,
+
+        Some(SignatureSource::Github) => {
+            let mut query = mapping_signature_github::table
+                .filter(mapping_signature_github::signature_id.eq(entity.signature_id))
+                .filter(mapping_signature_github::kind.eq(entity.kind))
+                .into_boxed();
+            if let Some(source_id) = entity.source_id {
+                query = query.filter(mapping_signature_github::repository_id.eq(source_id));
+            }
+
+            query.select(mapping_signature_github::signature_id).first::<i32>(connection).optional()?
+        }
+
+        Some(SignatureSource::Etherscan) => {
+            let mut query = mapping_signature_etherscan::table
+                .filter(mapping_signature_etherscan::signature_id.eq(entity.signature_id))
+                .filter(mapping_signature_etherscan::kind.eq(entity.kind))
+                .into_boxed();
+            if let Some(source_id) = entity.source_id {
+                query = query.filter(mapping_signature_etherscan::contract_id.eq(source_id));
+            }
+
+            query.select(mapping_signature_etherscan::signature_id).first::<i32>(connection).optional()?
+        }
+
+        Some(SignatureSource::Fourbyte) => mapping_signature_fourbyte::table
+            .filter(mapping_signature_fourbyte::signature_id.eq(entity.signature_id))
+            .filter(mapping_signature_fourbyte::kind.eq(entity.kind))
+            .select(mapping_signature_fourbyte::signature_id)
+            .first::<i32>(connection)
+            .optional()?,
+    };
+
+    Ok(found.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::rpc::RpcLog;
+    use crate::api::rpc::RpcReceipt;
+    use crate::api::rpc::RpcTransaction;
+    use crate::database::handler::rest::escape_like;
+    use crate::database::handler::rest::ExportFormat;
+    use crate::database::handler::rest::SignatureSearch;
+    use crate::database::handler::rest::SignatureSort;
+    use crate::database::handler::rest::SortOrder;
+    use crate::database::handler::rest::SourceSort;
+    use crate::database::handler::testing;
+    use crate::decoder::encode_hex;
+    use crate::decoder::selector_of;
+    use crate::decoder::topic_of;
+    use crate::model::FlagReason;
+    use crate::model::FlagStatus;
+    use crate::model::SignatureFlagInsert;
+    use crate::model::SignatureKind;
+    use crate::model::SignatureSource;
+    use crate::model::SignatureSourceCounts;
+    use crate::model::SignatureWithMetadata;
+    use crate::model::SubmissionStatus;
+    use crate::model::WatchlistEntryInsert;
+    use crate::model::WebhookInsert;
+    use chrono::Utc;
+    use ethabi::ethereum_types::U256;
+    use ethabi::Token;
+    use serde_json::json;
+    use sha3::Digest;
+    use sha3::Keccak256;
+
+    // Two signatures sharing the `a9059cbb` selector, the second one being used more often on chain, both of
+    // which were found in the same repository twice (once as a function, once as an error)
+    const SEED: &str = "
+        INSERT INTO signature (id, text, hash, is_valid, added_at, selector, topic0, usage_count)
+        SELECT id, text, hash, TRUE, NOW(), decode(substr(hash, 1, 8), 'hex'), decode(hash, 'hex'), usage_count
+        FROM (VALUES
+            (1, 'transfer(address,uint256)', 'a9059cbb' || repeat('0', 56), 10),
+            (2, 'many_msg_babbage(bytes1)', 'a9059cbb' || repeat('1', 56), 20)
+        ) AS seed (id, text, hash, usage_count);
+
+        INSERT INTO github_user (id, login, html_url, is_deleted, added_at)
+        VALUES (1, 'volsa', 'https://github.com/volsa', FALSE, NOW());
+
+        INSERT INTO github_repository (id, owner_id, name, html_url, stargazers_count, size, fork, created_at,
+            pushed_at, updated_at, added_at, is_deleted, found_by_crawling)
+        VALUES
+            (1, 1, 'a', 'https://github.com/volsa/a', 10, 1, FALSE, NOW(), NOW(), NOW(), NOW(), FALSE, FALSE),
+            (2, 1, 'b', 'https://github.com/volsa/b', 20, 1, FALSE, NOW(), NOW(), NOW(), NOW(), FALSE, FALSE);
+
+        INSERT INTO mapping_signature_github (signature_id, repository_id, kind, added_at, last_seen_at)
+        VALUES
+            (1, 1, 'function', NOW(), NOW()),
+            (1, 1, 'error', NOW(), NOW()),
+            (1, 2, 'function', NOW(), NOW());
+    ";
+
+    #[test]
+    fn escape_like_wildcards() {
+        assert_eq!(escape_like("_transfer"), "\\_transfer");
+        assert_eq!(escape_like("100%"), "100\\%");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signatures_search_fuzzy_tolerates_typos_and_fragments() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        let hash = format!("23b872dd{}", "0".repeat(56));
+        testing::seed(&dbc, &testing::signature_sql(3, "transferFrom(address,address,uint256)", &hash));
+        let ids = |entity_str| {
+            let response = dbc.rest().signatures_search_fuzzy(entity_str, None, None, 1).unwrap();
+            response.map_or(vec![], |x| x.items.iter().map(|x| x.id).collect::<Vec<_>>())
+        };
+
+        // The closer match comes first
+        assert_eq!(ids("tranfer"), vec![1, 3]);
+
+        // Fragments are contained case-insensitively, ranking before merely similar signatures
+        assert_eq!(ids("from"), vec![3]);
+        assert_eq!(ids("msg_babbage"), vec![2]);
+        assert!(ids("approve").is_empty());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signatures_where_text_contains_ignores_case() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        let ids = |entity_str| {
+            let response = dbc.rest().signatures_where_text_contains(entity_str, None, None, None, None, 1);
+            response.unwrap().map_or(vec![], |x| x.items.iter().map(|x| x.id).collect::<Vec<_>>())
+        };
+
+        assert_eq!(ids("Transfer"), vec![1]);
+        assert_eq!(ids("MSG_BABBAGE"), vec![2]);
+        assert!(ids("Approve").is_empty());
+
+        let response = dbc.rest().signatures_where_text_contains_after("ADDRESS", None, None).unwrap();
+        let response = response.unwrap();
+        assert_eq!(response.items.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signature_where_hash_starts_with_ranks_by_usage() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        let rest = dbc.rest();
+        let hash = |entity_str, source, page| {
+            rest.signature_where_hash_starts_with(entity_str, None, source, None, None, page)
+        };
+
+        let response = hash("a9059cbb", None, 1).unwrap().unwrap();
+        assert_eq!(response.total_items, 2);
+        assert_eq!(response.items.iter().map(|x| x.id).collect::<Vec<_>>(), vec![2, 1]);
+
+        assert!(hash("a9059cbb", None, 2).unwrap().is_none());
+        assert!(hash("00000000", None, 1).unwrap().is_none());
+
+        // Only the first signature was found on GitHub, neither was found on Etherscan
+        let response = hash("a9059cbb", Some(SignatureSource::Github), 1).unwrap().unwrap();
+        assert_eq!(response.items.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1]);
+        assert!(hash("a9059cbb", Some(SignatureSource::Etherscan), 1).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signatures_sorted_by_key_and_order() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        let rest = dbc.rest();
+        let ids = |sort, order| {
+            let response = rest.signature_where_hash_starts_with("a9059cbb", None, None, sort, order, 1);
+            response.unwrap().unwrap().items.iter().map(|x| x.id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(Some(SignatureSort::Usage), Some(SortOrder::Asc)), vec![1, 2]);
+        assert_eq!(ids(Some(SignatureSort::Id), None), vec![1, 2]);
+        assert_eq!(ids(Some(SignatureSort::Id), Some(SortOrder::Desc)), vec![2, 1]);
+
+        // `many_msg_babbage` sorts before `transfer`
+        assert_eq!(ids(Some(SignatureSort::Text), None), vec![2, 1]);
+        assert_eq!(ids(Some(SignatureSort::Text), Some(SortOrder::Desc)), vec![1, 2]);
+
+        // Ties of equally old signatures are broken by their id
+        assert_eq!(ids(Some(SignatureSort::Added), None), vec![2, 1]);
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signature_detail_summarizes_sources() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        testing::seed(&dbc, "INSERT INTO mapping_signature_kind VALUES (1, 'function'), (1, 'error');");
+
+        let detail = dbc.rest().signature_detail(1).unwrap().unwrap();
+        assert_eq!(detail.signature.text, "transfer(address,uint256)");
+        assert_eq!(detail.kinds, vec![SignatureKind::Function, SignatureKind::Error]);
+        assert_eq!(detail.sources, SignatureSourceCounts { github: 2, etherscan: 0, fourbyte: 0 });
+        assert!(detail.first_seen_at.is_some());
+        assert_eq!(detail.standards, vec!["ERC-20"]);
+
+        let detail = dbc.rest().signature_detail(2).unwrap().unwrap();
+        assert!(detail.kinds.is_empty() && detail.first_seen_at.is_none() && detail.standards.is_empty());
+
+        assert!(dbc.rest().signature_detail(3).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn decode_error_by_builtin_and_stored_errors() {
+        let dbc = testing::client_pooled();
+
+        let text = "InsufficientBalance(uint256,uint256)";
+        let hash = format!("{:x}", Keccak256::digest(text));
+        testing::seed(&dbc, &testing::signature_sql(1, text, &hash));
+        testing::seed(&dbc, "INSERT INTO mapping_signature_kind VALUES (1, 'error');");
+
+        let tokens = [Token::Uint(U256::from(1)), Token::Uint(U256::from(2))];
+        let data = [&selector_of(text)[..], &ethabi::encode(&tokens)].concat();
+        let decoded = dbc.rest().decode_error(&data).unwrap().unwrap();
+        assert_eq!(decoded.candidates.len(), 1);
+        assert_eq!(decoded.candidates[0].signature, text);
+
+        let tokens = [Token::String("a".to_string())];
+        let data = [&selector_of("Error(string)")[..], &ethabi::encode(&tokens)].concat();
+        assert_eq!(dbc.rest().decode_error(&data).unwrap().unwrap().candidates[0].signature, "Error(string)");
+
+        // Same selector, but not a valid encoding of its parameters
+        assert!(dbc.rest().decode_error(&data[..36]).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn statistics_of_extended_analytics_views() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        testing::seed(
+            &dbc,
+            "INSERT INTO mapping_signature_kind VALUES (1, 'function'), (2, 'function'), (1, 'error');
+
+            INSERT INTO etherscan_contract
+                (id, address, name, compiler, compiler_version, url, added_at, scraped_at, chain_id)
+            VALUES
+                (1, '0x01', 'A', 'Solidity', 'v0.8.17+commit.8df45f5f', '', NOW(), NOW(), 1),
+                (2, '0x02', 'B', 'Solidity', 'v0.8.17+commit.8df45f5f', '', NOW(), NOW(), 1),
+                (3, '0x03', 'C', 'Vyper', 'vyper:0.3.7', '', NOW(), NOW(), 137);
+
+            INSERT INTO mapping_signature_etherscan (signature_id, contract_id, kind, added_at, last_seen_at)
+            VALUES (1, 1, 'function', NOW(), NOW()), (1, 2, 'function', NOW(), NOW());
+
+            REFRESH MATERIALIZED VIEW view_signature_kind_distribution;
+            REFRESH MATERIALIZED VIEW view_etherscan_compiler_version_distribution;
+            REFRESH MATERIALIZED VIEW view_etherscan_chain_distribution;",
+        );
+
+        let rest = dbc.rest();
+        let kinds = serde_json::to_value(rest.statistics_signature_kind_distribution().unwrap()).unwrap();
+        assert_eq!(kinds.as_array().unwrap().len(), 6);
+        assert!(kinds.as_array().unwrap().contains(&json!({ "kind": "function", "count": 2 })));
+        assert!(kinds.as_array().unwrap().contains(&json!({ "kind": "receive", "count": 0 })));
+
+        let versions = rest.statistics_etherscan_compiler_version_distribution().unwrap();
+        assert_eq!(
+            serde_json::to_value(versions).unwrap(),
+            json!([
+                { "compiler": "Solidity", "version": "0.8.17", "count": 2 },
+                { "compiler": "Vyper", "version": "0.3.7", "count": 1 },
+            ])
+        );
+
+        let chains = serde_json::to_value(rest.statistics_etherscan_chain_distribution().unwrap()).unwrap();
+        assert_eq!(
+            chains,
+            json!([
+                { "chain_id": 1, "contract_count": 2, "signature_count": 1 },
+                { "chain_id": 137, "contract_count": 1, "signature_count": 0 },
+            ])
+        );
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn contract_detail_by_case_insensitive_address() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        testing::seed(
+            &dbc,
+            "INSERT INTO etherscan_contract (id, address, name, compiler, compiler_version, url, added_at)
+            VALUES (1, '0xdAC17F958D2ee523a2206206994597C13D831ec7', 'TetherToken', 'Solidity', '0.4.18', '', NOW());
+
+            INSERT INTO mapping_signature_etherscan (signature_id, contract_id, kind, added_at, last_seen_at)
+            VALUES
+                (2, 1, 'function', NOW(), NOW()),
+                (1, 1, 'function', NOW(), NOW()),
+                (1, 1, 'error', NOW(), NOW());
+
+            INSERT INTO etherscan_bytecode_hash (contract_id, hash, kind, added_at)
+            VALUES
+                (1, 'ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef', 'event', NOW()),
+                (1, 'a9059cbb', 'function', NOW());",
+        );
+
+        let rest = dbc.rest();
+        let detail = rest.contract_detail("0xdac17f958d2ee523a2206206994597c13d831ec7", 1).unwrap().unwrap();
+        assert_eq!(detail.contract.name, "TetherToken");
+
+        let signatures = detail.signatures.iter().map(|x| (x.signature.id, x.kind)).collect::<Vec<_>>();
+        assert_eq!(
+            signatures,
+            vec![(2, SignatureKind::Function), (1, SignatureKind::Function), (1, SignatureKind::Error)]
+        );
+
+        let hashes = detail.bytecode_hashes.iter().map(|x| (x.hash.as_str(), x.kind)).collect::<Vec<_>>();
+        assert_eq!(
+            hashes,
+            vec![
+                ("a9059cbb", SignatureKind::Function),
+                ("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef", SignatureKind::Event)
+            ]
+        );
+
+        assert!(rest.contract_detail("0xdac17f958d2ee523a2206206994597c13d831ec7", 137).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signatures_where_hashes_by_kinds_and_validity() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        testing::seed(
+            &dbc,
+            "INSERT INTO mapping_signature_kind VALUES (1, 'function'), (2, 'error');
+            UPDATE signature SET is_valid = FALSE WHERE id = 2;",
+        );
+
+        let rest = dbc.rest();
+        let hashes = [vec![0xa9, 0x05, 0x9c, 0xbb], vec![0xff; 4]];
+        let kinds = [SignatureKind::Function, SignatureKind::Error];
+
+        let signatures = rest.signatures_where_hashes(&hashes, &kinds, false).unwrap();
+        assert_eq!(signatures.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1]);
+
+        // Ranked by their usage
+        let signatures = rest.signatures_where_hashes(&hashes, &kinds, true).unwrap();
+        assert_eq!(signatures.iter().map(|x| x.id).collect::<Vec<_>>(), vec![2, 1]);
+
+        assert!(rest.signatures_where_hashes(&hashes, &[SignatureKind::Event], true).unwrap().is_empty());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn decode_transaction_input_logs_and_revert() {
+        let dbc = testing::client_pooled();
+
+        for (id, text, kind) in [
+            (1, "transfer(address,uint256)", "function"),
+            (2, "Transfer(address,address,uint256)", "event"),
+        ] {
+            let hash = format!("{:x}", Keccak256::digest(text));
+            testing::seed(&dbc, &testing::signature_sql(id, text, &hash));
+            testing::seed(&dbc, &format!("INSERT INTO mapping_signature_kind VALUES ({id}, '{kind}');"));
+        }
+
+        let recipient = ethabi::encode(&[Token::Address([0x22; 20].into())]);
+        let amount = ethabi::encode(&[Token::Uint(U256::from(1000))]);
+        let input = [&selector_of("transfer(address,uint256)")[..], &recipient, &amount].concat();
+
+        let transaction = RpcTransaction {
+            hash: "0x01".to_string(),
+            from: "0x02".to_string(),
+            to: Some("0x03".to_string()),
+            value: "0xde0b6b3a7640000".to_string(),
+            gas: "0x5208".to_string(),
+            input: encode_hex(&input),
+            block_number: Some("0x10".to_string()),
+        };
+
+        let topics = [topic_of("Transfer(address,address,uint256)").to_vec(), [0; 32].to_vec(), recipient];
+        let receipt = RpcReceipt {
+            status: Some("0x1".to_string()),
+            logs: vec![
+                RpcLog {
+                    address: "0x03".to_string(),
+                    topics: topics.iter().map(|x| encode_hex(x)).collect(),
+                    data: encode_hex(&amount),
+                },
+                RpcLog {
+                    address: "0x04".to_string(),
+                    topics: Vec::new(),
+                    data: "0x".to_string(),
+                },
+            ],
+        };
+
+        let decoded = dbc.rest().decode_transaction(&transaction, Some(&receipt), None).unwrap();
+        assert_eq!(decoded.value, "1000000000000000000");
+        assert_eq!(decoded.block_number, Some(16));
+        assert_eq!(decoded.succeeded, Some(true));
+        assert_eq!(decoded.input.unwrap().candidates[0].signature, "transfer(address,uint256)");
+        assert_eq!(decoded.logs.len(), 2);
+        assert_eq!(decoded.logs[0].candidates[0].signature, "Transfer(address,address,uint256)");
+        assert!(decoded.logs[1].candidates.is_empty());
+        assert!(decoded.revert.is_none());
+
+        let reason = ethabi::encode(&[Token::String("a".to_string())]);
+        let revert_data = [&selector_of("Error(string)")[..], &reason].concat();
+        let transaction = RpcTransaction {
+            input: "0x".to_string(),
+            ..transaction
+        };
+
+        let decoded = dbc.rest().decode_transaction(&transaction, None, Some(&revert_data)).unwrap();
+        assert!(decoded.input.is_none() && decoded.logs.is_empty() && decoded.succeeded.is_none());
+        assert_eq!(decoded.revert_data, Some(encode_hex(&revert_data)));
+        assert_eq!(decoded.revert.unwrap().candidates[0].signature, "Error(string)");
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn selector_collisions_of_functions_and_errors() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        testing::seed(
+            &dbc,
+            "INSERT INTO mapping_signature_kind VALUES (1, 'function'), (2, 'error');
+            REFRESH MATERIALIZED VIEW view_selector_collision;",
+        );
+
+        let response = dbc.rest().selector_collisions(1).unwrap().unwrap();
+        assert_eq!(response.total_items, 1);
+        assert_eq!(response.items[0].selector, "a9059cbb");
+        assert_eq!(response.items[0].signature_count, 2);
+
+        // Events and invalid signatures sharing a selector aren't ambiguous decodings of calldata
+        testing::seed(
+            &dbc,
+            "UPDATE mapping_signature_kind SET kind = 'event' WHERE signature_id = 2;
+            REFRESH MATERIALIZED VIEW view_selector_collision;",
+        );
+        assert!(dbc.rest().selector_collisions(1).unwrap().is_none());
+
+        testing::seed(
+            &dbc,
+            "UPDATE mapping_signature_kind SET kind = 'function' WHERE signature_id = 2;
+            UPDATE signature SET is_valid = FALSE WHERE id = 2;
+            REFRESH MATERIALIZED VIEW view_selector_collision;",
+        );
+        assert!(dbc.rest().selector_collisions(1).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signatures_export_after_id() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        testing::seed(&dbc, "INSERT INTO mapping_signature_kind VALUES (1, 'function'), (1, 'error');");
+
+        let lines = dbc.rest().signatures_export(ExportFormat::Csv, 0, None, 1).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].id, 1);
+        assert!(lines[0].line.starts_with(r#""1","transfer(address,uint256)","a9059cbb"#));
+        assert!(lines[0].line.ends_with(r#","function;error""#));
+
+        let lines = dbc.rest().signatures_export(ExportFormat::Ndjson, 1, None, 10).unwrap();
+        assert_eq!(lines.len(), 1);
+        let json: serde_json::Value = serde_json::from_str(&lines[0].line).unwrap();
+        assert_eq!(json["text"], "many_msg_babbage(bytes1)");
+        assert_eq!(json["kinds"], serde_json::json!([]));
+
+        assert!(dbc.rest().signatures_export(ExportFormat::Ndjson, 2, None, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signatures_search_filters_and_sorts() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        testing::seed(&dbc, "INSERT INTO mapping_signature_kind VALUES (1, 'function'), (2, 'function');");
+
+        let search = SignatureSearch {
+            query: Some("0xa9059cbb".to_string()),
+            sort: SignatureSort::Usage,
+            ..Default::default()
+        };
+        let response = dbc.rest().signatures_search(&search, 1, 1).unwrap().unwrap();
+        assert_eq!((response.total_items, response.total_pages), (2, 2));
+        assert_eq!(response.items[0].id, 2);
+
+        // Only the first signature was found on GitHub
+        let search = SignatureSearch {
+            kind: Some(SignatureKind::Function),
+            source: Some(SignatureSource::Github),
+            ..Default::default()
+        };
+        let response = dbc.rest().signatures_search(&search, 1, 10).unwrap().unwrap();
+        assert_eq!(response.items.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1]);
+
+        // `_` is matched literally rather than as a wildcard
+        let search = SignatureSearch {
+            query: Some("many_".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(dbc.rest().signatures_search(&search, 1, 10).unwrap().unwrap().items[0].id, 2);
+
+        let search = SignatureSearch {
+            query: Some("man_".to_string()),
+            ..Default::default()
+        };
+        assert!(dbc.rest().signatures_search(&search, 1, 10).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn sources_github_distinct_repositories() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+
+        // Repository `a` contains the signature both as a function and an error, but is listed only once
+        let response = dbc.rest().sources_github(1, None, SourceSort::Stars, None, 1).unwrap().unwrap();
+        assert_eq!(response.total_items, 2);
+        assert_eq!(response.items.iter().map(|x| x.id).collect::<Vec<_>>(), vec![2, 1]);
+
+        let order = Some(SortOrder::Asc);
+        let response = dbc.rest().sources_github(1, None, SourceSort::Stars, order, 1).unwrap().unwrap();
+        assert_eq!(response.items.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1, 2]);
+
+        let kind = Some(SignatureKind::Error);
+        let response = dbc.rest().sources_github(1, kind, SourceSort::Added, None, 1).unwrap().unwrap();
+        assert_eq!(response.items.iter().map(|x| x.id).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signature_submissions_insert_merges_resubmissions() {
+        let dbc = testing::client_pooled();
+        testing::seed(
+            &dbc,
+            "INSERT INTO api_key (id, key_hash, name, rate_limit_per_minute, rate_limit_burst, created_at)
+            VALUES (1, 'hash', 'dune', 600, 100, NOW());",
+        );
+
+        let signature = |text: &str, kind| SignatureWithMetadata::new(text.to_string(), kind, true);
+        let signatures = [
+            signature("approve(address,uint256)", SignatureKind::Function),
+            signature("Approval(address,address,uint256)", SignatureKind::Event),
+        ];
+
+        let submissions = dbc.rest().signature_submissions_insert(1, &signatures).unwrap();
+        assert_eq!(submissions.len(), 2);
+        assert_eq!(submissions[0].text, "approve(address,uint256)");
+        assert_eq!(submissions[1].kind, SignatureKind::Event);
+        assert!(submissions.iter().all(|x| x.status == SubmissionStatus::Pending));
+
+        // Submitted again alongside a new signature, the existing submission is returned as is
+        let signatures = [
+            signature("Approval(address,address,uint256)", SignatureKind::Event),
+            signature("transferFrom(address,address,uint256)", SignatureKind::Function),
+        ];
+
+        let resubmissions = dbc.rest().signature_submissions_insert(1, &signatures).unwrap();
+        assert_eq!(resubmissions[0].id, submissions[1].id);
+        assert_eq!(resubmissions[0].submitted_at, submissions[1].submitted_at);
+        assert_ne!(resubmissions[1].id, submissions[0].id);
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn signature_flags_insert_merges_pending_flags() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+
+        let insert = |source, source_id, reason| {
+            let flag = SignatureFlagInsert {
+                signature_id: 1,
+                kind: SignatureKind::Function,
+                source: Some(source),
+                source_id,
+                reason,
+                comment: None,
+                api_key_id: None,
+                flagged_at: Utc::now(),
+            };
+
+            dbc.rest().signature_flags_insert(&flag).unwrap()
+        };
+
+        let first = insert(SignatureSource::Github, Some(2), FlagReason::Wrong).unwrap();
+        assert_eq!(first.flag_count, 1);
+        assert_eq!(first.status, FlagStatus::Pending);
+
+        // Flagged again for the same reason, whereas flagging it as spam is moderated separately
+        let again = insert(SignatureSource::Github, Some(2), FlagReason::Wrong).unwrap();
+        assert_eq!((again.id, again.flag_count), (first.id, 2));
+        let spam = insert(SignatureSource::Github, Some(2), FlagReason::Spam).unwrap();
+        assert_ne!(spam.id, first.id);
+
+        // Neither found on Etherscan nor in the repository with ID 3
+        assert!(insert(SignatureSource::Etherscan, None, FlagReason::Wrong).is_none());
+        assert!(insert(SignatureSource::Github, Some(3), FlagReason::Wrong).is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn watchlist_entries_resolved_once_found() {
+        let dbc = testing::client_pooled();
+        testing::seed(&dbc, SEED);
+        testing::seed(
+            &dbc,
+            "INSERT INTO api_key (id, key_hash, name, rate_limit_per_minute, rate_limit_burst, created_at)
+            VALUES (1, 'hash', 'dune', 600, 100, NOW());
+
+            INSERT INTO mapping_signature_kind VALUES (1, 'function');",
+        );
+
+        let insert = |entity_hash| {
+            let entity = WatchlistEntryInsert {
+                api_key_id: 1,
+                hash: entity_hash,
+                webhook_url: Some("https://example.com/hook"),
+                webhook_secret: Some("secret"),
+                created_at: Utc::now(),
+            };
+
+            dbc.rest().watchlist_entries_insert(&entity, 2).unwrap()
+        };
+
+        // Known selectors are resolved right away, without calling the webhook
+        let known = insert("a9059cbb").unwrap();
+        assert_eq!(known.text.as_deref(), Some("transfer(address,uint256)"));
+        assert!(known.resolved_at.is_some() && known.next_notify_at.is_none());
+
+        let unknown = insert("deadbeef").unwrap();
+        assert!(unknown.resolved_at.is_none());
+        assert_eq!(insert("deadbeef").unwrap().id, unknown.id);
+        assert!(insert("cafebabe").is_none());
+
+        // Found as a function later on, hence resolved by the trigger and due to be notified
+        testing::seed(&dbc, &testing::signature_sql(3, "foo()", &format!("deadbeef{}", "2".repeat(56))));
+        testing::seed(&dbc, "INSERT INTO mapping_signature_kind VALUES (3, 'function');");
+
+        let entries = dbc.rest().watchlist_entries(1).unwrap();
+        assert_eq!(entries[1].signature_id, Some(3));
+        assert_eq!(entries[1].kind, Some(SignatureKind::Function));
+        assert!(entries[1].next_notify_at.is_some());
+
+        assert!(dbc.rest().watchlist_entries_delete(1, unknown.id).unwrap().is_some());
+        assert!(dbc.rest().watchlist_entries_delete(1, unknown.id).unwrap().is_none());
+        assert_eq!(dbc.rest().watchlist_entries(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn webhooks_limited_and_scoped_to_their_key() {
+        let dbc = testing::client_pooled();
+        testing::seed(
+            &dbc,
+            "INSERT INTO api_key (id, key_hash, name, rate_limit_per_minute, rate_limit_burst, created_at)
+            VALUES (1, 'hash', 'dune', 600, 100, NOW()), (2, 'other', 'bot', 600, 100, NOW());",
+        );
+
+        let insert = |entity_api_key_id| {
+            let entity = WebhookInsert {
+                api_key_id: entity_api_key_id,
+                url: "https://example.com/hook",
+                secret: "secret",
+                kind: Some(SignatureKind::Event),
+                prefix: None,
+                source: None,
+                created_at: Utc::now(),
+            };
+
+            dbc.rest().webhooks_insert(&entity, 1).unwrap()
+        };
+
+        let registered = insert(1).unwrap();
+        assert!(insert(1).is_none());
+        assert!(insert(2).is_some());
 
-    pub fn statistics_signature_kind_distribution(&self) -> Vec<ViewSignatureKindDistribution> {
-        sql_query("SELECT kind, count FROM view_signature_kind_distribution")
-            .get_results(&self.connection.get().unwrap())
-            .unwrap()
+        assert!(dbc.rest().webhooks_delete(2, registered.id).unwrap().is_none());
+        assert!(dbc.rest().webhooks_delete(1, registered.id).unwrap().is_some());
+        assert!(dbc.rest().webhooks(1).unwrap().is_empty());
     }
 }
@@ -1,39 +1,66 @@
 //! `signature` table handler.
 
+use crate::database::cache::PendingSignatures;
+use crate::database::handler::INSERT_BATCH_SIZE;
+use crate::database::schema::mapping_signature_etherscan;
+use crate::database::schema::mapping_signature_fourbyte;
+use crate::database::schema::mapping_signature_github;
 use crate::database::schema::mapping_signature_kind;
+use crate::database::schema::mapping_signature_submission;
 use crate::database::schema::signature;
 use crate::database::schema::signature::dsl::*;
+use crate::error::Error;
+use crate::model::KindInconsistency;
 use crate::model::MappingSignatureKind;
 use crate::model::Signature;
+use crate::model::SignatureKind;
+use crate::model::SignatureSource;
+use crate::model::SignatureUsage;
 use crate::model::SignatureWithMetadata;
+use diesel::pg::Pg;
 use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Array;
+use diesel::sql_types::Bytea;
+use diesel::sql_types::Int4;
+use diesel::sql_types::Int8;
+use diesel::sql_types::Nullable;
+use diesel::sql_types::Timestamptz;
 use diesel::PgConnection;
+use std::cell::RefCell;
+use std::collections::HashMap;
 
 pub struct SignatureHandler<'a> {
-    connection: &'a PgConnection,
+    connection: &'a RefCell<PgConnection>,
+    pending: &'a PendingSignatures,
 }
 
 impl<'a> SignatureHandler<'a> {
-    pub fn new(connection: &'a PgConnection) -> Self {
-        SignatureHandler { connection }
+    pub(crate) fn new(connection: &'a RefCell<PgConnection>, pending: &'a PendingSignatures) -> Self {
+        SignatureHandler { connection, pending }
     }
 
-    pub fn get_latest_500(&self) -> Vec<Signature> {
-        signature
-            .select(signature::table::all_columns())
-            .limit(500)
-            .order_by(id.desc())
-            .get_results(self.connection)
-            .unwrap()
+    /// Returns the `limit` most recently inserted signatures, optionally only those of the given kind and /
+    /// or found in the given source.
+    pub fn get_latest(
+        &self,
+        limit: i64,
+        entity_kind: Option<SignatureKind>,
+        source: Option<SignatureSource>,
+    ) -> Result<Vec<Signature>, Error> {
+        get_latest(&mut *self.connection.borrow_mut(), limit, entity_kind, source)
     }
 
-    pub fn insert(&self, entity: &SignatureWithMetadata) -> Signature {
-        let res = match self.get_by_hash(&entity.hash) {
+    pub fn insert(&self, entity: &SignatureWithMetadata) -> Result<Signature, Error> {
+        if let Some(cached) = self.pending.cache().get(&entity.hash, entity.kind) {
+            return Ok(cached);
+        }
+
+        let res = match self.get_by_hash(&entity.hash)? {
             Some(val) => val,
             None => diesel::insert_into(signature::table)
                 .values(&entity.to_insertable())
-                .get_result(self.connection)
-                .unwrap(),
+                .get_result(&mut *self.connection.borrow_mut())?,
         };
 
         diesel::insert_into(mapping_signature_kind::table)
@@ -42,13 +69,628 @@ impl<'a> SignatureHandler<'a> {
                 kind: entity.kind,
             })
             .on_conflict_do_nothing()
-            .execute(self.connection)
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        self.pending.insert(&res, entity.kind);
+        Ok(res)
+    }
+
+    /// Same as [`SignatureHandler::insert`] but for many signatures at once, writing them in a handful of
+    /// batched statements rather than a few statements per signature. Returns the (either inserted or already
+    /// existing) rows in the same order as `entities`.
+    pub fn insert_many(&self, entities: &[SignatureWithMetadata]) -> Result<Vec<Signature>, Error> {
+        let cached = entities.iter().map(|x| self.pending.cache().get(&x.hash, x.kind)).collect::<Vec<_>>();
+        let missing = entities
+            .iter()
+            .zip(&cached)
+            .filter(|(_, cached)| cached.is_none())
+            .map(|(entity, _)| entity)
+            .collect::<Vec<&SignatureWithMetadata>>();
+
+        let mut rows = HashMap::with_capacity(missing.len());
+        for chunk in missing.chunks(INSERT_BATCH_SIZE) {
+            diesel::insert_into(signature::table)
+                .values(chunk.iter().map(|x| x.to_insertable()).collect::<Vec<_>>())
+                .on_conflict_do_nothing()
+                .execute(&mut *self.connection.borrow_mut())?;
+
+            let hashes = chunk.iter().map(|x| x.hash.as_str()).collect::<Vec<_>>();
+            let inserted: Vec<Signature> =
+                signature.filter(hash.eq_any(hashes)).load(&mut *self.connection.borrow_mut())?;
+            rows.extend(inserted.into_iter().map(|x| (x.hash.clone(), x)));
+        }
+
+        let kinds = missing
+            .iter()
+            .map(|x| match rows.get(&x.hash) {
+                Some(row) => Ok(MappingSignatureKind {
+                    signature_id: row.id,
+                    kind: x.kind,
+                }),
+                None => Err(diesel::result::Error::NotFound),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for chunk in kinds.chunks(INSERT_BATCH_SIZE) {
+            diesel::insert_into(mapping_signature_kind::table)
+                .values(chunk)
+                .on_conflict_do_nothing()
+                .execute(&mut *self.connection.borrow_mut())?;
+        }
+
+        for entity in &missing {
+            self.pending.insert(&rows[&entity.hash], entity.kind);
+        }
+
+        Ok(entities
+            .iter()
+            .zip(cached)
+            .map(|(entity, cached)| cached.unwrap_or_else(|| rows[&entity.hash].clone()))
+            .collect())
+    }
+
+    /// Sets the on-chain usage of all function / error signatures whose selector matches one of the 4 byte
+    /// hashes and of all event signatures whose topic matches one of the 32 byte hashes of `entities`,
+    /// returning the number of updated signatures.
+    pub fn set_usage_many(&self, entities: &[SignatureUsage]) -> Result<usize, Error> {
+        let mut updated = 0;
+        for chunk in entities.chunks(INSERT_BATCH_SIZE) {
+            // Only calls and reverts are identified by selectors, an event merely sharing it isn't used
+            for (column, len, kinds) in [("selector", 4, "'function', 'error'"), ("topic0", 32, "'event'")] {
+                let chunk = chunk.iter().filter(|x| x.hash.len() == len).collect::<Vec<_>>();
+                if chunk.is_empty() {
+                    continue;
+                }
+
+                let query = format!(
+                    "UPDATE signature AS s SET usage_count = u.usage_count, last_called_at = u.last_called_at
+                    FROM unnest($1, $2, $3) AS u (hash, usage_count, last_called_at)
+                    WHERE s.{column} = u.hash AND EXISTS (
+                        SELECT 1 FROM mapping_signature_kind AS k
+                        WHERE k.signature_id = s.id AND k.kind IN ({kinds})
+                    )"
+                );
+
+                updated += sql_query(query)
+                    .bind::<Array<Bytea>, _>(chunk.iter().map(|x| x.hash.clone()).collect::<Vec<_>>())
+                    .bind::<Array<Int8>, _>(chunk.iter().map(|x| x.usage_count).collect::<Vec<_>>())
+                    .bind::<Array<Nullable<Timestamptz>>, _>(
+                        chunk.iter().map(|x| x.last_called_at).collect::<Vec<_>>(),
+                    )
+                    .execute(&mut *self.connection.borrow_mut())?;
+            }
+        }
+
+        Ok(updated)
+    }
+
+    pub fn get_by_hash(&self, entity_hash: &str) -> Result<Option<Signature>, Error> {
+        Ok(signature.filter(hash.eq(entity_hash)).first(&mut *self.connection.borrow_mut()).optional()?)
+    }
+
+    /// Returns at most `limit` signatures with an ID greater than `after`, ordered by their ID.
+    pub fn get_batch_after(&self, after: i32, limit: i64) -> Result<Vec<Signature>, Error> {
+        Ok(signature
+            .filter(id.gt(after))
+            .order_by(id.asc())
+            .limit(limit)
+            .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn set_valid(&self, entity_id: i32, entity_is_valid: bool) -> Result<(), Error> {
+        diesel::update(signature.find(entity_id))
+            .set(is_valid.eq(entity_is_valid))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Replaces the text of the given signature and with it its hash, e.g. because its canonical form
+    /// changed. The kind of `entity` is ignored, as kinds are stored in the `mapping_signature_kind` table.
+    pub fn set_text(&self, entity_id: i32, entity: &SignatureWithMetadata) -> Result<(), Error> {
+        let insertable = entity.to_insertable();
+
+        diesel::update(signature.find(entity_id))
+            .set((
+                text.eq(insertable.text),
+                hash.eq(insertable.hash),
+                is_valid.eq(insertable.is_valid),
+                selector.eq(&insertable.selector),
+                topic0.eq(&insertable.topic0),
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Merges the signature `from_id` into `into_id`, i.e. remaps all kinds and mappings of the former to the
+    /// latter (keeping the latter's mapping if both are mapped to the same source), adds up their on-chain
+    /// usage and finally deletes the former. Should be run within a transaction.
+    pub fn merge(&self, from_id: i32, into_id: i32) -> Result<(), Error> {
+        let mut connection = self.connection.borrow_mut();
+
+        sql_query(
+            "INSERT INTO mapping_signature_kind (signature_id, kind)
+            SELECT $2, kind FROM mapping_signature_kind WHERE signature_id = $1
+            ON CONFLICT DO NOTHING",
+        )
+        .bind::<Int4, _>(from_id)
+        .bind::<Int4, _>(into_id)
+        .execute(&mut *connection)?;
+
+        diesel::delete(mapping_signature_kind::table.filter(mapping_signature_kind::signature_id.eq(from_id)))
+            .execute(&mut *connection)?;
+
+        // Mappings are partitioned by `added_at`, hence duplicates are identified by their source and kind
+        for (table, keys) in [
+            ("mapping_signature_github", "repository_id, kind"),
+            ("mapping_signature_etherscan", "contract_id, kind"),
+            ("mapping_signature_fourbyte", "kind"),
+        ] {
+            let same_source = keys
+                .split(", ")
+                .map(|key| format!("target.{key} = source.{key}"))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+
+            sql_query(format!(
+                "UPDATE {table} AS target
+                SET last_seen_at = GREATEST(target.last_seen_at, source.last_seen_at)
+                FROM {table} AS source
+                WHERE target.signature_id = $2 AND source.signature_id = $1 AND {same_source}"
+            ))
+            .bind::<Int4, _>(from_id)
+            .bind::<Int4, _>(into_id)
+            .execute(&mut *connection)?;
+
+            sql_query(format!(
+                "DELETE FROM {table} AS source WHERE source.signature_id = $1
+                AND EXISTS (SELECT 1 FROM {table} AS target WHERE target.signature_id = $2 AND {same_source})"
+            ))
+            .bind::<Int4, _>(from_id)
+            .bind::<Int4, _>(into_id)
+            .execute(&mut *connection)?;
+
+            sql_query(format!("UPDATE {table} SET signature_id = $2 WHERE signature_id = $1"))
+                .bind::<Int4, _>(from_id)
+                .bind::<Int4, _>(into_id)
+                .execute(&mut *connection)?;
+        }
+
+        // Each submission maps to exactly one signature, hence there are no duplicates to resolve
+        sql_query("UPDATE mapping_signature_submission SET signature_id = $2 WHERE signature_id = $1")
+            .bind::<Int4, _>(from_id)
+            .bind::<Int4, _>(into_id)
+            .execute(&mut *connection)?;
+
+        for table in ["signature_flag", "watchlist_entry"] {
+            sql_query(format!("UPDATE {table} SET signature_id = $2 WHERE signature_id = $1"))
+                .bind::<Int4, _>(from_id)
+                .bind::<Int4, _>(into_id)
+                .execute(&mut *connection)?;
+        }
+
+        sql_query(
+            "UPDATE signature AS target
+            SET usage_count = target.usage_count + source.usage_count,
+                last_called_at = GREATEST(target.last_called_at, source.last_called_at)
+            FROM signature AS source
+            WHERE target.id = $2 AND source.id = $1",
+        )
+        .bind::<Int4, _>(from_id)
+        .bind::<Int4, _>(into_id)
+        .execute(&mut *connection)?;
+
+        diesel::delete(signature.find(from_id)).execute(&mut *connection)?;
+
+        Ok(())
+    }
+
+    /// Removes the mappings of the given signature and kind, either of all sources or of `source` only, where
+    /// `source_id` narrows GitHub and Etherscan mappings down to a single repository respectively contract.
+    /// Kinds of the signature no longer backed by any source mapping are removed as well, while the signature
+    /// itself is kept. Returns the number of removed source mappings. Should be run within a transaction.
+    pub fn remove_mappings(
+        &self,
+        entity_id: i32,
+        entity_kind: SignatureKind,
+        source: Option<SignatureSource>,
+        source_id: Option<i32>,
+    ) -> Result<usize, Error> {
+        let mut connection = self.connection.borrow_mut();
+        let mut removed = 0;
+
+        if matches!(source, None | Some(SignatureSource::Github)) {
+            let mut query = diesel::delete(
+                mapping_signature_github::table
+                    .filter(mapping_signature_github::signature_id.eq(entity_id))
+                    .filter(mapping_signature_github::kind.eq(entity_kind)),
+            )
+            .into_boxed();
+            if let Some(source_id) = source_id {
+                query = query.filter(mapping_signature_github::repository_id.eq(source_id));
+            }
+
+            removed += query.execute(&mut *connection)?;
+        }
+
+        if matches!(source, None | Some(SignatureSource::Etherscan)) {
+            let mut query = diesel::delete(
+                mapping_signature_etherscan::table
+                    .filter(mapping_signature_etherscan::signature_id.eq(entity_id))
+                    .filter(mapping_signature_etherscan::kind.eq(entity_kind)),
+            )
+            .into_boxed();
+            if let Some(source_id) = source_id {
+                query = query.filter(mapping_signature_etherscan::contract_id.eq(source_id));
+            }
+
+            removed += query.execute(&mut *connection)?;
+        }
+
+        if matches!(source, None | Some(SignatureSource::Fourbyte)) {
+            removed += diesel::delete(
+                mapping_signature_fourbyte::table
+                    .filter(mapping_signature_fourbyte::signature_id.eq(entity_id))
+                    .filter(mapping_signature_fourbyte::kind.eq(entity_kind)),
+            )
+            .execute(&mut *connection)?;
+        }
+
+        // Approved submissions aren't a source of their own, hence only removed alongside all other sources
+        if source.is_none() {
+            removed += diesel::delete(
+                mapping_signature_submission::table
+                    .filter(mapping_signature_submission::signature_id.eq(entity_id))
+                    .filter(mapping_signature_submission::kind.eq(entity_kind)),
+            )
+            .execute(&mut *connection)?;
+        }
+
+        sql_query(format!(
+            "DELETE FROM mapping_signature_kind AS target WHERE target.signature_id = $1 AND NOT ({})",
+            any_source_mapping("source.signature_id = target.signature_id AND source.kind = target.kind"),
+        ))
+        .bind::<Int4, _>(entity_id)
+        .execute(&mut *connection)?;
+
+        Ok(removed)
+    }
+
+    /// Returns the number of kinds per [`SignatureKind`] on which `mapping_signature_kind` disagrees with the
+    /// source mapping tables, e.g. a signature found as an event on GitHub but only recorded as a function.
+    /// Signatures without any source mapping are not considered.
+    pub fn get_kind_inconsistencies(&self) -> Result<Vec<KindInconsistency>, Error> {
+        Ok(sql_query(format!(
+            "SELECT kind, SUM(missing)::BIGINT AS missing, SUM(orphaned)::BIGINT AS orphaned FROM (
+                SELECT kind, 1 AS missing, 0 AS orphaned FROM ({MISSING_KINDS_QUERY}) AS missing
+                UNION ALL
+                SELECT kind, 0 AS missing, 1 AS orphaned FROM mapping_signature_kind AS target WHERE {}
+            ) AS temp GROUP BY kind ORDER BY kind",
+            orphaned_kinds_condition(),
+        ))
+        .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Repairs the inconsistencies reported by [`SignatureHandler::get_kind_inconsistencies`], inserting the
+    /// missing kinds and deleting the orphaned ones, and returns the number of inserted and deleted kinds.
+    /// Should be run within a transaction.
+    pub fn repair_kind_inconsistencies(&self) -> Result<(usize, usize), Error> {
+        let mut connection = self.connection.borrow_mut();
+
+        let inserted = sql_query(format!(
+            "INSERT INTO mapping_signature_kind (signature_id, kind) {MISSING_KINDS_QUERY}
+            ON CONFLICT DO NOTHING"
+        ))
+        .execute(&mut *connection)?;
+
+        let deleted = sql_query(format!(
+            "DELETE FROM mapping_signature_kind AS target WHERE {}",
+            orphaned_kinds_condition()
+        ))
+        .execute(&mut *connection)?;
+
+        Ok((inserted, deleted))
+    }
+}
+
+/// Returns a condition being true if any source mapping table (aliased as `source`) has a row satisfying
+/// `condition`.
+fn any_source_mapping(condition: &str) -> String {
+    [
+        "mapping_signature_github",
+        "mapping_signature_etherscan",
+        "mapping_signature_fourbyte",
+        "mapping_signature_submission",
+    ]
+    .iter()
+    .map(|table| format!("EXISTS (SELECT 1 FROM {table} AS source WHERE {condition})"))
+    .collect::<Vec<_>>()
+    .join(" OR ")
+}
+
+/// Selects the `(signature_id, kind)` pairs of all source mapping tables missing in `mapping_signature_kind`.
+const MISSING_KINDS_QUERY: &str = "SELECT DISTINCT source.signature_id, source.kind FROM (
+        SELECT signature_id, kind FROM mapping_signature_github
+        UNION SELECT signature_id, kind FROM mapping_signature_etherscan
+        UNION SELECT signature_id, kind FROM mapping_signature_fourbyte
+        UNION SELECT signature_id, kind FROM mapping_signature_submission
+    ) AS source
+    WHERE NOT EXISTS (
+        SELECT 1 FROM mapping_signature_kind AS target
+        WHERE target.signature_id = source.signature_id AND target.kind = source.kind
+    )";
+
+/// Returns a condition on `mapping_signature_kind` (aliased as `target`) matching kinds which aren't backed
+/// by any source mapping of the signature, given the signature has source mappings at all.
+fn orphaned_kinds_condition() -> String {
+    format!(
+        "({}) AND NOT ({})",
+        any_source_mapping("source.signature_id = target.signature_id"),
+        any_source_mapping("source.signature_id = target.signature_id AND source.kind = target.kind"),
+    )
+}
+
+/// See [`SignatureHandler::get_latest`], shared with [`crate::database::handler::rest::RestHandler`] which
+/// operates on a pooled connection.
+pub(crate) fn get_latest(
+    connection: &mut PgConnection,
+    limit: i64,
+    entity_kind: Option<SignatureKind>,
+    source: Option<SignatureSource>,
+) -> Result<Vec<Signature>, Error> {
+    let query = filter_by_kind_and_source(signature.into_boxed(), entity_kind, source);
+
+    Ok(query.order_by(id.desc()).limit(limit).load(connection)?)
+}
+
+/// Filters `query` by signatures of the given kind and / or found in the given source.
+pub(crate) fn filter_by_kind_and_source<'a>(
+    mut query: signature::BoxedQuery<'a, Pg>,
+    entity_kind: Option<SignatureKind>,
+    source: Option<SignatureSource>,
+) -> signature::BoxedQuery<'a, Pg> {
+    // The kind is filtered within the sources mapping table if given, as signatures may be of a different
+    // kind in another source
+    match source {
+        Some(SignatureSource::Github) => {
+            let mut mappings = mapping_signature_github::table
+                .select(mapping_signature_github::signature_id)
+                .into_boxed();
+            if let Some(entity_kind) = entity_kind {
+                mappings = mappings.filter(mapping_signature_github::kind.eq(entity_kind));
+            }
+
+            query = query.filter(id.eq_any(mappings));
+        }
+
+        Some(SignatureSource::Etherscan) => {
+            let mut mappings = mapping_signature_etherscan::table
+                .select(mapping_signature_etherscan::signature_id)
+                .into_boxed();
+            if let Some(entity_kind) = entity_kind {
+                mappings = mappings.filter(mapping_signature_etherscan::kind.eq(entity_kind));
+            }
+
+            query = query.filter(id.eq_any(mappings));
+        }
+
+        Some(SignatureSource::Fourbyte) => {
+            let mut mappings = mapping_signature_fourbyte::table
+                .select(mapping_signature_fourbyte::signature_id)
+                .into_boxed();
+            if let Some(entity_kind) = entity_kind {
+                mappings = mappings.filter(mapping_signature_fourbyte::kind.eq(entity_kind));
+            }
+
+            query = query.filter(id.eq_any(mappings));
+        }
+
+        None => {
+            if let Some(entity_kind) = entity_kind {
+                query = query.filter(
+                    id.eq_any(
+                        mapping_signature_kind::table
+                            .filter(mapping_signature_kind::kind.eq(entity_kind))
+                            .select(mapping_signature_kind::signature_id),
+                    ),
+                );
+            }
+        }
+    }
+
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::handler::testing;
+    use crate::model::MappingSignatureFourbyte;
+    use crate::model::Signature;
+    use crate::model::SignatureKind;
+    use crate::model::SignatureSource;
+    use crate::model::SignatureUsage;
+    use crate::model::SignatureWithMetadata;
+    use chrono::Utc;
+
+    fn metadata(text: &str, kind: SignatureKind) -> SignatureWithMetadata {
+        SignatureWithMetadata::new(text.to_string(), kind, true)
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn insert_many_returns_existing_rows() {
+        let dbc = testing::client();
+
+        let transfer = metadata("transfer(address,uint256)", SignatureKind::Function);
+        let existing = dbc.signature().insert(&transfer).unwrap();
+        let rows = dbc
+            .signature()
+            .insert_many(&[
+                metadata("approve(address,uint256)", SignatureKind::Function),
+                metadata("transfer(address,uint256)", SignatureKind::Function),
+                metadata("transfer(address,uint256)", SignatureKind::Error),
+            ])
+            .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].text, "approve(address,uint256)");
+        assert_eq!(rows[1].id, existing.id);
+        assert_eq!(rows[2].id, existing.id);
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn set_usage_many_by_selector() {
+        let dbc = testing::client();
+
+        dbc.signature().insert(&metadata("transfer(address,uint256)", SignatureKind::Function)).unwrap();
+
+        // Shares the `a9059cbb` selector with `transfer(address,uint256)` but is only known as an event
+        dbc.signature().insert(&metadata("many_msg_babbage(bytes1)", SignatureKind::Event)).unwrap();
+        let updated = dbc
+            .signature()
+            .set_usage_many(&[
+                SignatureUsage {
+                    hash: vec![0xa9, 0x05, 0x9c, 0xbb],
+                    usage_count: 42,
+                    last_called_at: None,
+                },
+                SignatureUsage {
+                    hash: vec![0x00, 0x00, 0x00, 0x00],
+                    usage_count: 1,
+                    last_called_at: None,
+                },
+            ])
             .unwrap();
 
-        res
+        assert_eq!(updated, 1);
     }
 
-    fn get_by_hash(&self, entity_hash: &str) -> Option<Signature> {
-        signature.filter(hash.eq(entity_hash)).first(self.connection).optional().unwrap()
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn merge_remaps_mappings() {
+        let dbc = testing::client();
+
+        let stale = dbc.signature().insert(&metadata("foo(uint)", SignatureKind::Function)).unwrap();
+        let stale_event = dbc.signature().insert(&metadata("foo(uint)", SignatureKind::Event)).unwrap();
+        let canonical = dbc.signature().insert(&metadata("foo(uint256)", SignatureKind::Function)).unwrap();
+        assert_eq!(stale.id, stale_event.id);
+
+        let mapping = |signature_id, kind| MappingSignatureFourbyte {
+            signature_id,
+            kind,
+            added_at: Utc::now(),
+            last_seen_at: Utc::now(),
+        };
+        dbc.mapping_signature_fourbyte()
+            .insert_many(&[
+                mapping(stale.id, SignatureKind::Function),
+                mapping(stale.id, SignatureKind::Event),
+                mapping(canonical.id, SignatureKind::Function),
+            ])
+            .unwrap();
+
+        dbc.signature().merge(stale.id, canonical.id).unwrap();
+
+        assert!(dbc.signature().get_by_hash(&stale.hash).unwrap().is_none());
+        let handler = dbc.mapping_signature_fourbyte();
+        assert!(handler.get(&mapping(canonical.id, SignatureKind::Function)).unwrap().is_some());
+        assert!(handler.get(&mapping(canonical.id, SignatureKind::Event)).unwrap().is_some());
+        assert!(handler.get(&mapping(stale.id, SignatureKind::Function)).unwrap().is_none());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn remove_mappings_of_kind_and_source() {
+        let dbc = testing::client();
+
+        let transfer = metadata("Transfer(address)", SignatureKind::Function);
+        let transfer = dbc.signature().insert(&transfer).unwrap();
+        dbc.signature().insert(&metadata("Transfer(address)", SignatureKind::Event)).unwrap();
+
+        let mapping = |kind| MappingSignatureFourbyte {
+            signature_id: transfer.id,
+            kind,
+            added_at: Utc::now(),
+            last_seen_at: Utc::now(),
+        };
+        dbc.mapping_signature_fourbyte()
+            .insert_many(&[mapping(SignatureKind::Function), mapping(SignatureKind::Event)])
+            .unwrap();
+
+        let removed = dbc
+            .signature()
+            .remove_mappings(transfer.id, SignatureKind::Function, Some(SignatureSource::Fourbyte), None)
+            .unwrap();
+        assert_eq!(removed, 1);
+
+        // The function kind isn't backed by any mapping anymore, whereas the event kind is untouched
+        let handler = dbc.mapping_signature_fourbyte();
+        assert!(handler.get(&mapping(SignatureKind::Function)).unwrap().is_none());
+        assert!(handler.get(&mapping(SignatureKind::Event)).unwrap().is_some());
+        assert!(dbc.signature().get_latest(10, Some(SignatureKind::Function), None).unwrap().is_empty());
+        assert_eq!(dbc.signature().get_latest(10, Some(SignatureKind::Event), None).unwrap().len(), 1);
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn repair_kind_inconsistencies() {
+        let dbc = testing::client();
+
+        // Recorded as a function, but only found as an event on 4Byte
+        let transfer = dbc.signature().insert(&metadata("Transfer(bool)", SignatureKind::Function)).unwrap();
+        // Recorded as a function without any source mapping, hence not considered
+        dbc.signature().insert(&metadata("approve(address)", SignatureKind::Function)).unwrap();
+        dbc.mapping_signature_fourbyte()
+            .insert_many(&[MappingSignatureFourbyte {
+                signature_id: transfer.id,
+                kind: SignatureKind::Event,
+                added_at: Utc::now(),
+                last_seen_at: Utc::now(),
+            }])
+            .unwrap();
+
+        let inconsistencies = dbc.signature().get_kind_inconsistencies().unwrap();
+        let inconsistencies: Vec<(SignatureKind, i64, i64)> =
+            inconsistencies.iter().map(|x| (x.kind, x.missing, x.orphaned)).collect();
+        assert_eq!(inconsistencies, vec![(SignatureKind::Function, 0, 1), (SignatureKind::Event, 1, 0)]);
+
+        assert_eq!(dbc.signature().repair_kind_inconsistencies().unwrap(), (1, 1));
+        assert!(dbc.signature().get_kind_inconsistencies().unwrap().is_empty());
+    }
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn get_latest_filters_by_kind_and_source() {
+        let dbc = testing::client();
+
+        let transfer = dbc.signature().insert(&metadata("Transfer(address)", SignatureKind::Event)).unwrap();
+        let approve = dbc.signature().insert(&metadata("approve(address)", SignatureKind::Function)).unwrap();
+        let burn = dbc.signature().insert(&metadata("burn(uint256)", SignatureKind::Function)).unwrap();
+        dbc.mapping_signature_fourbyte()
+            .insert_many(&[MappingSignatureFourbyte {
+                signature_id: approve.id,
+                kind: SignatureKind::Function,
+                added_at: Utc::now(),
+                last_seen_at: Utc::now(),
+            }])
+            .unwrap();
+
+        let ids = |x: Vec<Signature>| x.iter().map(|x| x.id).collect::<Vec<_>>();
+        assert_eq!(ids(dbc.signature().get_latest(2, None, None).unwrap()), vec![burn.id, approve.id]);
+        assert_eq!(
+            ids(dbc.signature().get_latest(10, Some(SignatureKind::Event), None).unwrap()),
+            vec![transfer.id]
+        );
+        assert_eq!(
+            ids(dbc.signature().get_latest(10, None, Some(SignatureSource::Fourbyte)).unwrap()),
+            vec![approve.id]
+        );
+        assert!(dbc
+            .signature()
+            .get_latest(10, Some(SignatureKind::Event), Some(SignatureSource::Fourbyte))
+            .unwrap()
+            .is_empty());
     }
 }
@@ -0,0 +1,67 @@
+//! `signature_flag` table handler.
+//!
+//! Flags are raised by the REST API (see `RestHandler::signature_flags_insert`) and moderated by
+//! administrators, who either invalidate the flagged signature, remove the flagged mappings (see
+//! `SignatureHandler::remove_mappings`) or dismiss the flag (see the `flag` command of the `etherface` binary).
+
+use crate::database::schema::signature_flag::dsl::*;
+use crate::error::Error;
+use crate::model::FlagStatus;
+use crate::model::SignatureFlag;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct SignatureFlagHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> SignatureFlagHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        SignatureFlagHandler { connection }
+    }
+
+    pub fn get(&self, entity_id: i32) -> Result<Option<SignatureFlag>, Error> {
+        Ok(signature_flag.find(entity_id).first(&mut *self.connection.borrow_mut()).optional()?)
+    }
+
+    /// Returns the `limit` most often flagged pending flags, oldest first among equally often flagged ones.
+    pub fn get_pending(&self, limit: i64) -> Result<Vec<SignatureFlag>, Error> {
+        Ok(signature_flag
+            .filter(status.eq(FlagStatus::Pending))
+            .order_by((flag_count.desc(), id.asc()))
+            .limit(limit)
+            .get_results(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Returns the IDs of all signatures invalidated or removed by moderation, whose validity must not be
+    /// re-evaluated by the parser.
+    pub fn get_invalidated_signature_ids(&self) -> Result<Vec<i32>, Error> {
+        Ok(signature_flag
+            .filter(status.eq_any([FlagStatus::Invalidated, FlagStatus::Removed]))
+            .select(signature_id)
+            .distinct()
+            .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Sets the status of the pending flag with the given id to `entity_status` on behalf of
+    /// `entity_moderated_by`, returning `None` if there's no such pending flag.
+    pub fn moderate(
+        &self,
+        entity_id: i32,
+        entity_status: FlagStatus,
+        entity_moderated_by: &str,
+    ) -> Result<Option<SignatureFlag>, Error> {
+        let pending = signature_flag.filter(id.eq(entity_id).and(status.eq(FlagStatus::Pending)));
+
+        Ok(diesel::update(pending)
+            .set((
+                status.eq(entity_status),
+                moderated_at.eq(Utc::now()),
+                moderated_by.eq(entity_moderated_by),
+            ))
+            .get_result(&mut *self.connection.borrow_mut())
+            .optional()?)
+    }
+}
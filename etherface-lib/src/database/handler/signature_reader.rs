@@ -0,0 +1,151 @@
+//! PostgreSQL implementation of the [`SignatureReader`] trait.
+
+use crate::database::handler::rest::escape_like;
+use crate::database::reader::SignatureReader;
+use crate::database::schema::mapping_signature_etherscan;
+use crate::database::schema::mapping_signature_fourbyte;
+use crate::database::schema::mapping_signature_github;
+use crate::database::schema::mapping_signature_kind;
+use crate::database::schema::signature;
+use crate::error::Error;
+use crate::model::decode_hash;
+use crate::model::Signature;
+use crate::model::SignatureKind;
+use diesel::dsl::exists;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct SignatureReaderHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> SignatureReaderHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        SignatureReaderHandler { connection }
+    }
+}
+
+impl<'a> SignatureReader for SignatureReaderHandler<'a> {
+    fn signatures_where_hash(
+        &self,
+        hash: &str,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<Signature>, Error> {
+        let hash = match decode_hash(hash) {
+            Some(val) if val.len() == 4 || val.len() == 32 => val,
+            _ => return Ok(Vec::new()),
+        };
+
+        let mut query = signature::table
+            .filter(signature::is_valid.eq(true))
+            .order_by(signature::id.asc())
+            .limit(limit)
+            .into_boxed();
+
+        query = match hash.len() {
+            4 => query.filter(signature::selector.eq(hash)),
+            _ => query.filter(signature::topic0.eq(hash)),
+        };
+
+        if let Some(kind) = kind {
+            query = query.filter(
+                signature::id.eq_any(
+                    mapping_signature_kind::table
+                        .filter(mapping_signature_kind::kind.eq(kind))
+                        .select(mapping_signature_kind::signature_id),
+                ),
+            );
+        }
+
+        Ok(query.load(&mut *self.connection.borrow_mut())?)
+    }
+
+    fn signatures_where_text_starts_with(
+        &self,
+        text: &str,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<Signature>, Error> {
+        let mut query = signature::table
+            .filter(signature::is_valid.eq(true))
+            .filter(signature::text.like(format!("{}%", escape_like(text))))
+            .order_by(signature::id.asc())
+            .limit(limit)
+            .into_boxed();
+
+        if let Some(kind) = kind {
+            query = query.filter(
+                signature::id.eq_any(
+                    mapping_signature_kind::table
+                        .filter(mapping_signature_kind::kind.eq(kind))
+                        .select(mapping_signature_kind::signature_id),
+                ),
+            );
+        }
+
+        Ok(query.load(&mut *self.connection.borrow_mut())?)
+    }
+
+    fn kinds(&self, signature_id: i32) -> Result<Vec<SignatureKind>, Error> {
+        Ok(mapping_signature_kind::table
+            .filter(mapping_signature_kind::signature_id.eq(signature_id))
+            .select(mapping_signature_kind::kind)
+            .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    fn github_repository_ids(
+        &self,
+        signature_id: i32,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<i32>, Error> {
+        let mut query = mapping_signature_github::table
+            .filter(mapping_signature_github::signature_id.eq(signature_id))
+            .select(mapping_signature_github::repository_id)
+            .distinct()
+            .order_by(mapping_signature_github::repository_id.asc())
+            .limit(limit)
+            .into_boxed();
+
+        if let Some(kind) = kind {
+            query = query.filter(mapping_signature_github::kind.eq(kind));
+        }
+
+        Ok(query.load(&mut *self.connection.borrow_mut())?)
+    }
+
+    fn etherscan_contract_ids(
+        &self,
+        signature_id: i32,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<i32>, Error> {
+        let mut query = mapping_signature_etherscan::table
+            .filter(mapping_signature_etherscan::signature_id.eq(signature_id))
+            .select(mapping_signature_etherscan::contract_id)
+            .distinct()
+            .order_by(mapping_signature_etherscan::contract_id.asc())
+            .limit(limit)
+            .into_boxed();
+
+        if let Some(kind) = kind {
+            query = query.filter(mapping_signature_etherscan::kind.eq(kind));
+        }
+
+        Ok(query.load(&mut *self.connection.borrow_mut())?)
+    }
+
+    fn is_on_fourbyte(&self, signature_id: i32, kind: Option<SignatureKind>) -> Result<bool, Error> {
+        let mut query = mapping_signature_fourbyte::table
+            .filter(mapping_signature_fourbyte::signature_id.eq(signature_id))
+            .into_boxed();
+
+        if let Some(kind) = kind {
+            query = query.filter(mapping_signature_fourbyte::kind.eq(kind));
+        }
+
+        Ok(diesel::select(exists(query)).get_result(&mut *self.connection.borrow_mut())?)
+    }
+}
@@ -0,0 +1,57 @@
+//! `signature_submission` table handler.
+//!
+//! Submissions are inserted by the REST API (see `RestHandler::signature_submissions_insert`) and moderated
+//! by administrators, where approving a submission inserts its signature alongside a
+//! `mapping_signature_submission` row (see the `submission` command of the `etherface` binary).
+
+use crate::database::schema::signature_submission::dsl::*;
+use crate::error::Error;
+use crate::model::SignatureSubmission;
+use crate::model::SubmissionStatus;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct SignatureSubmissionHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> SignatureSubmissionHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        SignatureSubmissionHandler { connection }
+    }
+
+    pub fn get(&self, entity_id: i32) -> Result<Option<SignatureSubmission>, Error> {
+        Ok(signature_submission.find(entity_id).first(&mut *self.connection.borrow_mut()).optional()?)
+    }
+
+    /// Returns the `limit` oldest submissions pending moderation.
+    pub fn get_pending(&self, limit: i64) -> Result<Vec<SignatureSubmission>, Error> {
+        Ok(signature_submission
+            .filter(status.eq(SubmissionStatus::Pending))
+            .order_by(id.asc())
+            .limit(limit)
+            .get_results(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Sets the status of the pending submission with the given id to `entity_status` on behalf of
+    /// `entity_moderated_by`, returning `None` if there's no such pending submission.
+    pub fn moderate(
+        &self,
+        entity_id: i32,
+        entity_status: SubmissionStatus,
+        entity_moderated_by: &str,
+    ) -> Result<Option<SignatureSubmission>, Error> {
+        let pending = signature_submission.filter(id.eq(entity_id).and(status.eq(SubmissionStatus::Pending)));
+
+        Ok(diesel::update(pending)
+            .set((
+                status.eq(entity_status),
+                moderated_at.eq(Utc::now()),
+                moderated_by.eq(entity_moderated_by),
+            ))
+            .get_result(&mut *self.connection.borrow_mut())
+            .optional()?)
+    }
+}
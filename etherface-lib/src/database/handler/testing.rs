@@ -0,0 +1,192 @@
+//! Test harness for the table handlers.
+//!
+//! Handler tests run against the PostgreSQL database given by `ETHERFACE_TEST_DATABASE_URL`. As such they're
+//! marked `#[ignore]` and run via `cargo test -- --ignored`, failing if the variable isn't set. Each test
+//! connection begins a transaction which is never committed, creating and migrating a temporary schema within
+//! it, such that tests neither see each others rows nor leave anything behind, even when run against a
+//! database which already contains an Etherface schema. For the latter the `text_and_hash_search_indexes`
+//! migration is run in an adjusted form, see [`TestMigrations`].
+//!
+//! ```ignore
+//! #[test]
+//! #[ignore = "requires a test database, see `database::handler::testing`"]
+//! fn some_handler_test() {
+//!     let dbc = testing::client();
+//!     testing::seed(&dbc, &testing::signature_sql(1, "transfer(address,uint256)", TRANSFER_HASH));
+//! }
+//! ```
+
+use crate::database::cache::PendingSignatures;
+use crate::database::cache::SignatureCache;
+use crate::database::handler::DatabaseClient;
+use crate::database::handler::DatabaseClientPooled;
+use crate::database::pagination::COUNT_CACHE;
+use crate::database::MIGRATIONS;
+use crate::error::Error;
+use diesel::connection::BoxableConnection;
+use diesel::connection::SimpleConnection;
+use diesel::migration::Migration;
+use diesel::migration::MigrationMetadata;
+use diesel::migration::MigrationName;
+use diesel::migration::MigrationSource;
+use diesel::pg::Pg;
+use diesel::r2d2::ConnectionManager;
+use diesel::r2d2::CustomizeConnection;
+use diesel::r2d2::Pool;
+use diesel::Connection;
+use diesel::PgConnection;
+use diesel_migrations::MigrationHarness;
+use std::cell::RefCell;
+use std::sync::Arc;
+use std::time::Duration;
+
+const ENV_VAR_TEST_DATABASE_URL: &str = "ETHERFACE_TEST_DATABASE_URL";
+
+/// Name of the migration replaced by [`SEARCH_INDEXES_SQL`] within [`TestMigrations`].
+const SEARCH_INDEXES_MIGRATION: &str = "2022-08-13-180338_text_and_hash_search_indexes";
+
+/// Same as the `text_and_hash_search_indexes` migration, except that the `pg_trgm` extension may already
+/// exist and that it doesn't `VACUUM`, which can't be run within a transaction.
+const SEARCH_INDEXES_SQL: &str = "
+    CREATE EXTENSION IF NOT EXISTS pg_trgm;
+    CREATE INDEX index_trgm_ops__signature_text ON signature USING gin (text gin_trgm_ops);
+    CREATE INDEX index_trgm_ops__signature_hash ON signature USING gin (hash gin_trgm_ops);
+";
+
+/// Returns a client whose queries run within a rolled back transaction on a freshly migrated schema.
+pub(crate) fn client() -> DatabaseClient {
+    let database_url = database_url();
+
+    let mut connection = PgConnection::establish(&database_url).expect("Failed to connect to test database");
+    setup(&mut connection).expect("Failed to set up test schema");
+
+    // Each client gets its own (disabled) signature cache, as rows of one test must not be served to another
+    // test, whereas the process-wide cache is exercised by the `cache` tests
+    DatabaseClient {
+        connection: RefCell::new(connection),
+        pending_signatures: PendingSignatures::new(Arc::new(SignatureCache::new(0))),
+    }
+}
+
+/// Same as [`client`] but for the REST API. The pool consists of a single connection, such that all queries
+/// share the same transaction and schema.
+pub(crate) fn client_pooled() -> DatabaseClientPooled {
+    let database_url = database_url();
+
+    let pool = Pool::builder()
+        .max_size(1)
+        .connection_customizer(Box::new(TestTransaction))
+        .build(ConnectionManager::<PgConnection>::new(database_url))
+        .expect("Failed to set up test schema");
+
+    DatabaseClientPooled { connection: pool }
+}
+
+/// Clients returned by [`client`] and [`client_pooled`], which rows can be seeded into.
+pub(crate) trait Seed {
+    fn batch_execute(&self, sql: &str) -> diesel::QueryResult<()>;
+}
+
+impl Seed for DatabaseClient {
+    fn batch_execute(&self, sql: &str) -> diesel::QueryResult<()> {
+        self.connection.borrow_mut().batch_execute(sql)
+    }
+}
+
+impl Seed for DatabaseClientPooled {
+    fn batch_execute(&self, sql: &str) -> diesel::QueryResult<()> {
+        self.connection.get().expect("Failed to get test connection").batch_execute(sql)
+    }
+}
+
+/// Executes the given SQL statements on `dbc`, e.g. to insert rows read by the handler under test.
+pub(crate) fn seed(dbc: &impl Seed, sql: &str) {
+    dbc.batch_execute(sql).expect("Failed to seed test database");
+}
+
+/// Returns SQL inserting a valid signature with the given (unescaped) `text` and `hash`, deriving its
+/// selector and topic from the hash.
+pub(crate) fn signature_sql(id: i32, text: &str, hash: &str) -> String {
+    format!(
+        "INSERT INTO signature (id, text, hash, is_valid, added_at, selector, topic0)
+        VALUES ({id}, '{text}', '{hash}', TRUE, NOW(), decode('{}', 'hex'), decode('{hash}', 'hex'));",
+        &hash[..8]
+    )
+}
+
+fn database_url() -> String {
+    dotenv::dotenv().ok();
+    let database_url = std::env::var(ENV_VAR_TEST_DATABASE_URL)
+        .ok()
+        .filter(|x| !x.is_empty())
+        .unwrap_or_else(|| panic!("{ENV_VAR_TEST_DATABASE_URL} must be set to run the handler tests"));
+
+    // Totals of one test must not be served to another test from the process-wide count cache, as they're
+    // only valid within the tests transaction
+    COUNT_CACHE.configure(Duration::ZERO);
+
+    database_url
+}
+
+/// Begins a never committed transaction on `connection`, migrating a new schema within it.
+fn setup(connection: &mut PgConnection) -> Result<(), Error> {
+    connection.begin_test_transaction()?;
+
+    // `public` is kept in the search path for extensions (i.e. `pg_trgm`) which might have been created there
+    let schema = format!("etherface_test_{}", rand::random::<u32>());
+    connection.batch_execute(&format!("CREATE SCHEMA {schema}; SET LOCAL search_path TO {schema}, public"))?;
+
+    connection.run_pending_migrations(TestMigrations).map_err(Error::DatabaseMigration)?;
+    Ok(())
+}
+
+/// The embedded migrations, with the `text_and_hash_search_indexes` migration replaced by
+/// [`SEARCH_INDEXES_SQL`]. Extensions are created database-wide rather than within a schema, as such its
+/// `CREATE EXTENSION pg_trgm` fails on databases where an Etherface schema was already migrated.
+struct TestMigrations;
+
+impl MigrationSource<Pg> for TestMigrations {
+    fn migrations(&self) -> diesel::migration::Result<Vec<Box<dyn Migration<Pg>>>> {
+        let migrations = MigrationSource::<Pg>::migrations(&MIGRATIONS)?;
+
+        Ok(migrations
+            .into_iter()
+            .map(|migration| match migration.name().to_string() == SEARCH_INDEXES_MIGRATION {
+                true => Box::new(SearchIndexesMigration(migration)) as Box<dyn Migration<Pg>>,
+                false => migration,
+            })
+            .collect())
+    }
+}
+
+struct SearchIndexesMigration(Box<dyn Migration<Pg>>);
+
+impl Migration<Pg> for SearchIndexesMigration {
+    fn run(&self, connection: &mut dyn BoxableConnection<Pg>) -> diesel::migration::Result<()> {
+        connection.batch_execute(SEARCH_INDEXES_SQL)?;
+        Ok(())
+    }
+
+    fn revert(&self, connection: &mut dyn BoxableConnection<Pg>) -> diesel::migration::Result<()> {
+        self.0.revert(connection)
+    }
+
+    fn metadata(&self) -> &dyn MigrationMetadata {
+        self.0.metadata()
+    }
+
+    fn name(&self) -> &dyn MigrationName {
+        self.0.name()
+    }
+}
+
+#[derive(Debug)]
+struct TestTransaction;
+
+impl CustomizeConnection<PgConnection, diesel::r2d2::Error> for TestTransaction {
+    fn on_acquire(&self, connection: &mut PgConnection) -> Result<(), diesel::r2d2::Error> {
+        setup(connection).map_err(|why| {
+            diesel::r2d2::Error::QueryError(diesel::result::Error::QueryBuilderError(why.to_string().into()))
+        })
+    }
+}
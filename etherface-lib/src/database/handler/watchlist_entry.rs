@@ -0,0 +1,67 @@
+//! `watchlist_entry` table handler.
+//!
+//! Entries are created by the REST API (see `RestHandler::watchlist_entries_insert`) and resolved by the
+//! `trigger_resolve_watchlist_entry` database trigger, whereas this handler is used by the watchlist notifier
+//! of the `etherface` binary calling the webhooks of resolved entries.
+
+use crate::database::schema::watchlist_entry::dsl::*;
+use crate::error::Error;
+use crate::model::WatchlistEntry;
+use chrono::DateTime;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct WatchlistEntryHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> WatchlistEntryHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        WatchlistEntryHandler { connection }
+    }
+
+    /// Returns at most `limit` resolved entries whose webhook is due to be called, oldest first.
+    pub fn get_due_notifications(&self, limit: i64) -> Result<Vec<WatchlistEntry>, Error> {
+        Ok(watchlist_entry
+            .filter(resolved_at.is_not_null())
+            .filter(notified_at.is_null())
+            .filter(webhook_url.is_not_null())
+            .filter(next_notify_at.le(Utc::now()))
+            .order_by(next_notify_at.asc())
+            .limit(limit)
+            .get_results(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn set_notified(&self, entity_id: i32) -> Result<(), Error> {
+        diesel::update(watchlist_entry.find(entity_id))
+            .set((
+                notified_at.eq(Utc::now()),
+                notify_attempts.eq(notify_attempts + 1),
+                next_notify_at.eq(None::<DateTime<Utc>>),
+                last_notify_error.eq(None::<String>),
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Records a failed webhook call, retrying it at `retry_at` or never if `None`.
+    pub fn set_notify_failed(
+        &self,
+        entity_id: i32,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<(), Error> {
+        diesel::update(watchlist_entry.find(entity_id))
+            .set((
+                notify_attempts.eq(notify_attempts + 1),
+                next_notify_at.eq(retry_at),
+                last_notify_error.eq(error),
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+//! `webhook` and `webhook_delivery` table handler.
+//!
+//! Webhooks are registered by the REST API (see `RestHandler::webhooks_insert`) and deliveries queued by the
+//! `trigger_queue_webhook_delivery` database trigger, whereas this handler is used by the webhook dispatcher
+//! of the `etherface` binary delivering them.
+
+use crate::database::schema::api_key;
+use crate::database::schema::webhook;
+use crate::database::schema::webhook::dsl::*;
+use crate::database::schema::webhook_delivery;
+use crate::error::Error;
+use crate::model::SignatureSource;
+use crate::model::Webhook;
+use crate::model::WebhookDelivery;
+use chrono::DateTime;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::Int4;
+use diesel::sql_types::Int8;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct WebhookHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> WebhookHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        WebhookHandler { connection }
+    }
+
+    /// Returns at most `limit` enabled webhooks of unrevoked keys with pending deliveries which are due to be
+    /// delivered, longest due first.
+    pub fn get_due(&self, limit: i64) -> Result<Vec<Webhook>, Error> {
+        let pending = webhook_delivery::table.select(webhook_delivery::webhook_id);
+
+        Ok(webhook
+            .inner_join(api_key::table)
+            .filter(api_key::revoked_at.is_null())
+            .filter(disabled_at.is_null())
+            .filter(next_delivery_at.le(Utc::now()))
+            .filter(id.eq_any(pending))
+            .select(webhook::all_columns)
+            .order_by(next_delivery_at.asc())
+            .limit(limit)
+            .get_results(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Returns the `limit` oldest pending deliveries of `entity`, flagging whether they match its source
+    /// filter.
+    pub fn get_pending_deliveries(
+        &self,
+        entity: &Webhook,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, Error> {
+        let matches_source = match entity.source {
+            None => "TRUE",
+            Some(SignatureSource::Github) => "EXISTS (SELECT 1 FROM mapping_signature_github AS source
+                WHERE source.signature_id = delivery.signature_id AND source.kind = delivery.kind)",
+            Some(SignatureSource::Etherscan) => "EXISTS (SELECT 1 FROM mapping_signature_etherscan AS source
+                WHERE source.signature_id = delivery.signature_id AND source.kind = delivery.kind)",
+            Some(SignatureSource::Fourbyte) => "EXISTS (SELECT 1 FROM mapping_signature_fourbyte AS source
+                WHERE source.signature_id = delivery.signature_id AND source.kind = delivery.kind)",
+        };
+
+        let query = format!(
+            "SELECT delivery.id, delivery.signature_id, signature.text, signature.hash, delivery.kind,
+                {matches_source} AS matches_source
+            FROM webhook_delivery AS delivery
+            JOIN signature ON signature.id = delivery.signature_id
+            WHERE delivery.webhook_id = $1
+            ORDER BY delivery.id ASC
+            LIMIT $2"
+        );
+
+        Ok(sql_query(query)
+            .bind::<Int4, _>(entity.id)
+            .bind::<Int8, _>(limit)
+            .load(&mut *self.connection.borrow_mut())?)
+    }
+
+    /// Removes the given deliveries, i.e. ones which have been delivered or don't match the source filter.
+    pub fn delete_deliveries(&self, ids: &[i64]) -> Result<usize, Error> {
+        let deliveries = webhook_delivery::table.filter(webhook_delivery::id.eq_any(ids));
+        Ok(diesel::delete(deliveries).execute(&mut *self.connection.borrow_mut())?)
+    }
+
+    pub fn set_delivered(&self, entity_id: i32) -> Result<(), Error> {
+        let now = Utc::now();
+        diesel::update(webhook.find(entity_id))
+            .set((
+                delivered_at.eq(now),
+                failure_count.eq(0),
+                next_delivery_at.eq(now),
+                last_error.eq(None::<String>),
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Records a failed delivery, retrying it at `retry_at` or disabling the webhook if `None`. Pending
+    /// deliveries of disabled webhooks are removed, as no further ones are queued for them.
+    pub fn set_failed(
+        &self,
+        entity_id: i32,
+        error: &str,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> Result<(), Error> {
+        self.connection.borrow_mut().transaction(|connection| {
+            diesel::update(webhook.find(entity_id))
+                .set((
+                    failure_count.eq(failure_count + 1),
+                    next_delivery_at.eq(retry_at.unwrap_or_else(Utc::now)),
+                    last_error.eq(error),
+                    disabled_at.eq(retry_at.map_or(Some(Utc::now()), |_| None)),
+                ))
+                .execute(connection)?;
+
+            if retry_at.is_none() {
+                diesel::delete(webhook_delivery::table.filter(webhook_delivery::webhook_id.eq(entity_id)))
+                    .execute(connection)?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::handler::testing;
+    use crate::model::SignatureSource;
+
+    const SEED: &str = "
+        INSERT INTO api_key (id, key_hash, name, rate_limit_per_minute, rate_limit_burst, created_at)
+        VALUES (1, 'hash', 'mirror', 600, 100, NOW());
+
+        INSERT INTO signature (id, text, hash, is_valid, added_at, selector, topic0)
+        SELECT id, text, hash, is_valid, NOW(), decode(substr(hash, 1, 8), 'hex'), decode(hash, 'hex')
+        FROM (VALUES
+            (1, 'transfer(address,uint256)', 'a9059cbb' || repeat('0', 56), TRUE),
+            (2, 'approve(address,uint256)', '095ea7b3' || repeat('0', 56), TRUE),
+            (3, 'transferFrom(address,address,uint256)', '23b872dd' || repeat('0', 56), FALSE)
+        ) AS signatures (id, text, hash, is_valid);
+
+        INSERT INTO webhook (id, api_key_id, url, secret, prefix, source, created_at) VALUES
+            (1, 1, 'https://example.com/all', 'secret', NULL, NULL, NOW()),
+            (2, 1, 'https://example.com/transfers', 'secret', 'transfer', 'fourbyte', NOW());
+
+        INSERT INTO mapping_signature_kind (signature_id, kind) VALUES (1, 'function'), (2, 'function'),
+            (3, 'function');
+        INSERT INTO mapping_signature_fourbyte (signature_id, kind, added_at, last_seen_at)
+        VALUES (2, 'function', NOW(), NOW());
+    ";
+
+    #[test]
+    #[ignore = "requires a test database, see `database::handler::testing`"]
+    fn deliveries_queued_by_filter() {
+        let dbc = testing::client();
+
+        testing::seed(&dbc, SEED);
+
+        // The trigger skips invalid signatures as well as ones not matching the prefix
+        let due = dbc.webhook().get_due(10).unwrap();
+        assert_eq!(due.len(), 2);
+
+        let all = due.iter().find(|x| x.source.is_none()).unwrap();
+        let deliveries = dbc.webhook().get_pending_deliveries(all, 10).unwrap();
+        assert_eq!(deliveries.iter().map(|x| x.signature_id).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(deliveries.iter().all(|x| x.matches_source));
+
+        // Only found within 4Byte by `approve`, which doesn't match the prefix
+        let transfers = due.iter().find(|x| x.source == Some(SignatureSource::Fourbyte)).unwrap();
+        let deliveries = dbc.webhook().get_pending_deliveries(transfers, 10).unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].signature_id, 1);
+        assert!(!deliveries[0].matches_source);
+
+        // Disabled webhooks lose their pending deliveries
+        dbc.webhook().set_failed(transfers.id, "HTTP 500", None).unwrap();
+        let due = dbc.webhook().get_due(10).unwrap();
+        assert_eq!(due.iter().map(|x| x.id).collect::<Vec<_>>(), vec![all.id]);
+    }
+}
@@ -0,0 +1,67 @@
+//! `worker_status` table handler.
+
+use crate::database::schema::worker_status;
+use crate::database::schema::worker_status::dsl::*;
+use crate::error::Error;
+use crate::model::WorkerStatus;
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::cell::RefCell;
+
+pub struct WorkerStatusHandler<'a> {
+    connection: &'a RefCell<PgConnection>,
+}
+
+impl<'a> WorkerStatusHandler<'a> {
+    pub fn new(connection: &'a RefCell<PgConnection>) -> Self {
+        WorkerStatusHandler { connection }
+    }
+
+    /// Registers a (re-)started worker, resetting its status.
+    pub fn register(&self, entity_name: &str) -> Result<(), Error> {
+        let entity = WorkerStatus {
+            name: entity_name.to_string(),
+            started_at: Utc::now(),
+            last_loop_at: Utc::now(),
+            current_item: None,
+            items_processed: 0,
+        };
+
+        diesel::insert_into(worker_status::table)
+            .values(&entity)
+            .on_conflict(name)
+            .do_update()
+            .set((
+                started_at.eq(entity.started_at),
+                last_loop_at.eq(entity.last_loop_at),
+                current_item.eq(&entity.current_item),
+                items_processed.eq(0),
+            ))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Updates the last sign of life of the worker as well as the item it's currently processing.
+    pub fn heartbeat(&self, entity_name: &str, entity_current_item: Option<&str>) -> Result<(), Error> {
+        diesel::update(worker_status.filter(name.eq(entity_name)))
+            .set((last_loop_at.eq(Utc::now()), current_item.eq(entity_current_item)))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// Adds `count` to the number of processed items of the worker.
+    pub fn add_processed(&self, entity_name: &str, count: i64) -> Result<(), Error> {
+        diesel::update(worker_status.filter(name.eq(entity_name)))
+            .set(items_processed.eq(items_processed + count))
+            .execute(&mut *self.connection.borrow_mut())?;
+
+        Ok(())
+    }
+
+    pub fn get_all(&self) -> Result<Vec<WorkerStatus>, Error> {
+        Ok(worker_status.order_by(name.asc()).get_results(&mut *self.connection.borrow_mut())?)
+    }
+}
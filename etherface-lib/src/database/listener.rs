@@ -0,0 +1,78 @@
+//! Listens for newly found signatures, i.e. signatures found as a kind for the first time.
+//!
+//! Notifications are sent by a trigger on `mapping_signature_kind` (see
+//! `migrations/2022-10-22-181940_signature_notify/up.sql`) over a dedicated connection, as `LISTEN` is bound
+//! to the session it was issued in. Used by the REST APIs `/v1/stream/signatures` endpoint.
+
+use crate::config::Config;
+use crate::database::schema::signature::dsl::*;
+use crate::error::Error;
+use crate::model::Signature;
+use crate::model::SignatureKind;
+use crate::model::StreamedSignature;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::PgConnection;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Channel the notifications are sent on.
+pub const CHANNEL: &str = "signature_found";
+
+#[derive(Deserialize)]
+struct Payload {
+    signature_id: i32,
+    kind: SignatureKind,
+}
+
+pub struct SignatureListener {
+    connection: PgConnection,
+}
+
+impl SignatureListener {
+    /// Returns a new listener connected to the database configured in `.env`.
+    pub fn new() -> Result<Self, Error> {
+        SignatureListener::with_url(&Config::new()?.database_url)
+    }
+
+    /// Returns a new listener connecting to `database_url` instead of the one configured in `.env`.
+    pub fn with_url(database_url: &str) -> Result<Self, Error> {
+        let mut connection = PgConnection::establish(database_url)?;
+        sql_query(format!("LISTEN {CHANNEL}")).execute(&mut connection)?;
+
+        Ok(SignatureListener { connection })
+    }
+
+    /// Returns the valid signatures found since the previous call in the order they were found, without
+    /// blocking if there are none.
+    pub fn poll(&mut self) -> Result<Vec<StreamedSignature>, Error> {
+        let mut payloads = Vec::new();
+        for notification in self.connection.notifications_iter() {
+            payloads.push(serde_json::from_str::<Payload>(&notification?.payload)?);
+        }
+
+        if payloads.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<i32> = payloads.iter().map(|x| x.signature_id).collect();
+        let signatures: HashMap<i32, Signature> = signature
+            .filter(id.eq_any(ids).and(is_valid.eq(true)))
+            .load::<Signature>(&mut self.connection)?
+            .into_iter()
+            .map(|x| (x.id, x))
+            .collect();
+
+        Ok(payloads
+            .into_iter()
+            .filter_map(|payload| {
+                signatures.get(&payload.signature_id).map(|entity| StreamedSignature {
+                    id: entity.id,
+                    text: entity.text.clone(),
+                    hash: entity.hash.clone(),
+                    kind: payload.kind,
+                })
+            })
+            .collect())
+    }
+}
@@ -1,6 +1,27 @@
 //! Database manager, providing handlers for all tables specified in [`schema`]
+//!
+//! All migrations within `migrations/` are embedded into the binary, such that pending migrations can be run
+//! without the diesel CLI (see `DatabaseClient::run_pending_migrations`).
 
+mod cache;
 pub mod handler;
+pub mod listener;
 #[allow(unused_imports)]
 pub mod schema;
-mod pagination;
+pub mod pagination;
+pub mod reader;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use crate::error::Error;
+use diesel::PgConnection;
+use diesel_migrations::EmbeddedMigrations;
+use diesel_migrations::MigrationHarness;
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../migrations");
+
+/// Runs all pending embedded migrations on `connection`.
+fn run_migrations(connection: &mut PgConnection) -> Result<(), Error> {
+    connection.run_pending_migrations(MIGRATIONS).map_err(Error::DatabaseMigration)?;
+    Ok(())
+}
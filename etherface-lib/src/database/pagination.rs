@@ -2,50 +2,302 @@
 //! 
 //! Wraps a `SELECT *, COUNT(*) OVER () FROM ( {query} ) t LIMIT {page_size} OFFSET {page_index}` over the
 //! `query`. Modified version taken from <https://github.com/diesel-rs/diesel/blob/master/examples/postgres/advanced-blog-cli/src/pagination.rs>.
+//!
+//! As the `OFFSET` rows still have to be computed (and counted) by the database, page number based
+//! pagination gets progressively slower for large tables and skips / repeats rows if data is inserted in
+//! between requests. Alternatively queries can therefore be paginated by a [`Cursor`] (keyset pagination),
+//! i.e. `SELECT * FROM ( {query} ) t WHERE {sort key} > {cursor} ORDER BY {sort key} LIMIT {page_size}`,
+//! which only reads the returned rows given an index on the sort key.
+//!
+//! Counting all rows of a page number based query is as expensive as the query itself for broad filters
+//! (e.g. short text prefixes), as such the total of each query is cached for a short duration (see
+//! [`COUNT_CACHE`]), sparing the `COUNT(*)` while paging through its results.
 
+use crate::config::DEFAULT_DATABASE_COUNT_CACHE_TTL;
+use crate::model::EtherscanContract;
+use crate::model::Signature;
+use chrono::DateTime;
+use chrono::Utc;
+use diesel::debug_query;
 use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::query_builder::*;
 use diesel::query_dsl::methods::LoadQuery;
 use diesel::sql_types::BigInt;
+use diesel::sql_types::Integer;
+use diesel::sql_types::Timestamptz;
+use serde::Deserialize;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
 
 const DEFAULT_PER_PAGE: i64 = 100;
 
+/// Maximum number of cached totals, after which expired ones (or if there are none, all) are evicted.
+const COUNT_CACHE_CAPACITY: usize = 10_000;
+
+lazy_static! {
+    /// Totals of recently paginated queries shared by all database clients of this process.
+    pub(crate) static ref COUNT_CACHE: CountCache =
+        CountCache::new(Duration::from_secs(DEFAULT_DATABASE_COUNT_CACHE_TTL));
+}
+
+pub(crate) struct CountCache {
+    state: Mutex<CountCacheState>,
+}
+
+struct CountCacheState {
+    ttl: Duration,
+
+    /// Totals keyed by their query's SQL including its bind parameters, alongside the time they were counted.
+    entries: HashMap<String, (i64, Instant)>,
+}
+
+impl CountCache {
+    /// Returns a new cache whose totals expire after `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        CountCache {
+            state: Mutex::new(CountCacheState {
+                ttl,
+                entries: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Updates the duration after which totals expire.
+    pub fn configure(&self, ttl: Duration) {
+        self.state.lock().unwrap().ttl = ttl;
+    }
+
+    /// Returns the unexpired total cached for `query`, if any.
+    pub fn get(&self, query: &str) -> Option<i64> {
+        let state = self.state.lock().unwrap();
+        match state.entries.get(query) {
+            Some((total, counted_at)) if counted_at.elapsed() < state.ttl => Some(*total),
+            _ => None,
+        }
+    }
+
+    /// Caches the `total` of `query`.
+    pub fn insert(&self, query: String, total: i64) {
+        let mut state = self.state.lock().unwrap();
+        if state.entries.len() >= COUNT_CACHE_CAPACITY {
+            let ttl = state.ttl;
+            state.entries.retain(|_, (_, counted_at)| counted_at.elapsed() < ttl);
+
+            if state.entries.len() >= COUNT_CACHE_CAPACITY {
+                state.entries.clear();
+            }
+        }
+
+        state.entries.insert(query, (total, Instant::now()));
+    }
+}
+
 pub trait Paginate: Sized {
     fn paginate(self, page: i64) -> Paginated<Self>;
+
+    /// Paginates the query by its `order`, returning the rows after `cursor` (or the first rows if `None`).
+    fn paginate_after(self, order: CursorOrder, cursor: Option<Cursor>) -> CursorPaginated<Self>;
 }
 
 impl<T> Paginate for T {
     fn paginate(self, page: i64) -> Paginated<Self> {
         Paginated {
             query: self,
+            page,
             per_page: DEFAULT_PER_PAGE,
             offset: (page - 1) * DEFAULT_PER_PAGE,
+            total: None,
+        }
+    }
+
+    fn paginate_after(self, order: CursorOrder, cursor: Option<Cursor>) -> CursorPaginated<Self> {
+        CursorPaginated {
+            query: self,
+            per_page: DEFAULT_PER_PAGE,
+            limit: DEFAULT_PER_PAGE + 1,
+            order,
+            cursor,
+        }
+    }
+}
+
+/// Sort key of a cursor paginated query, referencing the columns of the wrapped query's result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorOrder {
+    /// Ordered by `id` ascending.
+    IdAsc,
+
+    /// Ordered by `added_at` descending, ties being ordered by `id` descending.
+    AddedAtDesc,
+}
+
+/// Position of a row within a cursor paginated query, i.e. its sort key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub id: i32,
+    pub added_at: DateTime<Utc>,
+}
+
+/// Rows which can be cursor paginated.
+pub trait Cursored {
+    fn cursor(&self) -> Cursor;
+}
+
+impl Cursored for Signature {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            id: self.id,
+            added_at: self.added_at,
+        }
+    }
+}
+
+impl Cursored for EtherscanContract {
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            id: self.id,
+            added_at: self.added_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CursorPaginated<T> {
+    query: T,
+    per_page: i64,
+
+    /// `per_page + 1`, as one row more than requested is loaded to know whether there's a next page at all.
+    limit: i64,
+
+    order: CursorOrder,
+    cursor: Option<Cursor>,
+}
+
+impl<T> CursorPaginated<T> {
+    /// Returns the rows of the page alongside the cursor of the next page, which is `None` for the last page.
+    pub fn load_with_cursor<'a, U>(self, conn: &mut PgConnection) -> QueryResult<(Vec<U>, Option<Cursor>)>
+    where
+        Self: LoadQuery<'a, PgConnection, U>,
+        U: Cursored,
+    {
+        let per_page = self.per_page as usize;
+        let mut records = self.load::<U>(conn)?;
+
+        let next_cursor = match records.len() > per_page {
+            true => {
+                records.truncate(per_page);
+                records.last().map(Cursored::cursor)
+            }
+            false => None,
+        };
+
+        Ok((records, next_cursor))
+    }
+}
+
+// The SQL depends on whether a cursor is given, as such the prepared statement can't be cached by type
+impl<T> QueryId for CursorPaginated<T> {
+    type QueryId = ();
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<T: Query> Query for CursorPaginated<T> {
+    type SqlType = T::SqlType;
+}
+
+impl<T> RunQueryDsl<PgConnection> for CursorPaginated<T> {}
+
+impl<T> QueryFragment<Pg> for CursorPaginated<T>
+where
+    T: QueryFragment<Pg>,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.push_sql("SELECT * FROM (");
+        self.query.walk_ast(out.reborrow())?;
+        out.push_sql(") t");
+
+        match (self.order, &self.cursor) {
+            (CursorOrder::IdAsc, Some(cursor)) => {
+                out.push_sql(" WHERE t.id > ");
+                out.push_bind_param::<Integer, _>(&cursor.id)?;
+            }
+
+            (CursorOrder::AddedAtDesc, Some(cursor)) => {
+                out.push_sql(" WHERE (t.added_at, t.id) < (");
+                out.push_bind_param::<Timestamptz, _>(&cursor.added_at)?;
+                out.push_sql(", ");
+                out.push_bind_param::<Integer, _>(&cursor.id)?;
+                out.push_sql(")");
+            }
+
+            (_, None) => (),
         }
+
+        match self.order {
+            CursorOrder::IdAsc => out.push_sql(" ORDER BY t.id ASC LIMIT "),
+            CursorOrder::AddedAtDesc => out.push_sql(" ORDER BY t.added_at DESC, t.id DESC LIMIT "),
+        }
+
+        out.push_bind_param::<BigInt, _>(&self.limit)?;
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone, Copy, QueryId)]
+#[derive(Debug, Clone, Copy)]
 pub struct Paginated<T> {
     query: T,
+    page: i64,
     per_page: i64,
     offset: i64,
+
+    /// Cached total of `query`, which if present is selected instead of counting all rows.
+    total: Option<i64>,
 }
 
 impl<T> Paginated<T> {
-    pub fn load_and_count_pages<U>(self, conn: &mut PgConnection) -> QueryResult<(Vec<U>, i64, i64)>
+    /// Sets the number of rows per page, defaulting to 100.
+    pub fn per_page(self, per_page: i64) -> Self {
+        Paginated {
+            per_page,
+            offset: (self.page - 1) * per_page,
+            ..self
+        }
+    }
+
+    pub fn load_and_count_pages<'a, U>(mut self, conn: &mut PgConnection) -> QueryResult<(Vec<U>, i64, i64)>
     where
-        Self: LoadQuery<PgConnection, (U, i64)>,
+        Self: LoadQuery<'a, PgConnection, (U, i64)>,
+        T: QueryFragment<Pg>,
     {
-        let per_page = self.per_page;
+        let key = debug_query::<Pg, _>(&self.query).to_string();
+        self.total = COUNT_CACHE.get(&key);
+
+        let (per_page, cached) = (self.per_page, self.total.is_some());
         let results = self.load::<(U, i64)>(conn)?;
         let total = results.get(0).map(|x| x.1).unwrap_or(0);
-        let records = results.into_iter().map(|x| x.0).collect();
+        let records = results.into_iter().map(|x| x.0).collect::<Vec<U>>();
         let total_pages = (total as f64 / per_page as f64).ceil() as i64;
+
+        // Pages past the last one don't return any rows and hence no total either
+        if !cached && !records.is_empty() {
+            COUNT_CACHE.insert(key, total);
+        }
+
         Ok((records, total, total_pages))
     }
 }
 
+// The SQL depends on whether the total is cached, as such the prepared statement can't be cached by type
+impl<T> QueryId for Paginated<T> {
+    type QueryId = ();
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
 impl<T: Query> Query for Paginated<T> {
     type SqlType = (T::SqlType, BigInt);
 }
@@ -56,8 +308,17 @@ impl<T> QueryFragment<Pg> for Paginated<T>
 where
     T: QueryFragment<Pg>,
 {
-    fn walk_ast(&self, mut out: AstPass<'_, Pg>) -> QueryResult<()> {
-        out.push_sql("SELECT *, COUNT(*) OVER () FROM (");
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        match &self.total {
+            Some(total) => {
+                out.push_sql("SELECT *, ");
+                out.push_bind_param::<BigInt, _>(total)?;
+                out.push_sql(" FROM (");
+            }
+
+            None => out.push_sql("SELECT *, COUNT(*) OVER () FROM ("),
+        }
+
         self.query.walk_ast(out.reborrow())?;
         out.push_sql(") t LIMIT ");
         out.push_bind_param::<BigInt, _>(&self.per_page)?;
@@ -66,3 +327,67 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::database::pagination::CountCache;
+    use crate::database::pagination::Cursor;
+    use crate::database::pagination::CursorOrder;
+    use crate::database::pagination::Paginate;
+    use crate::database::schema::signature;
+    use chrono::Utc;
+    use diesel::debug_query;
+    use diesel::pg::Pg;
+    use diesel::prelude::*;
+    use std::time::Duration;
+
+    #[test]
+    fn count_cache_expires() {
+        let cache = CountCache::new(Duration::from_secs(60));
+        cache.insert("a".to_string(), 42);
+        assert_eq!(cache.get("a"), Some(42));
+        assert_eq!(cache.get("b"), None);
+
+        cache.configure(Duration::ZERO);
+        assert_eq!(cache.get("a"), None);
+    }
+
+    #[test]
+    fn paginated_query_with_cached_total() {
+        let mut query = signature::table.select(signature::id).paginate(2);
+        let sql = debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.starts_with("SELECT *, COUNT(*) OVER () FROM ("));
+
+        query.total = Some(1234);
+        let sql = debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.starts_with("SELECT *, $1 FROM ("));
+        assert!(sql.ends_with("binds: [1234, 100, 100]"));
+
+        let sql = debug_query::<Pg, _>(&query.per_page(20)).to_string();
+        assert!(sql.ends_with("binds: [1234, 20, 20]"));
+    }
+
+    #[test]
+    fn cursor_paginated_query() {
+        let query = signature::table.select(signature::id).paginate_after(CursorOrder::IdAsc, None);
+        let sql = debug_query::<Pg, _>(&query).to_string();
+        assert!(!sql.contains("WHERE"));
+        assert!(sql.contains(") t ORDER BY t.id ASC LIMIT $1"));
+        assert!(sql.ends_with("binds: [101]"));
+
+        let cursor = Cursor {
+            id: 5,
+            added_at: Utc::now(),
+        };
+
+        let query = signature::table.select(signature::id).paginate_after(CursorOrder::IdAsc, Some(cursor));
+        let sql = debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains(") t WHERE t.id > $1 ORDER BY t.id ASC LIMIT $2"));
+
+        let query =
+            signature::table.select(signature::id).paginate_after(CursorOrder::AddedAtDesc, Some(cursor));
+        let sql = debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains(") t WHERE (t.added_at, t.id) < ($1, $2)"));
+        assert!(sql.contains(" ORDER BY t.added_at DESC, t.id DESC LIMIT $3"));
+    }
+}
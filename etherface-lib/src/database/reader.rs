@@ -0,0 +1,51 @@
+//! Backend independent read access to signatures and their mappings.
+//!
+//! Whereas the table handlers (see [`super::handler`]) are tied to PostgreSQL, the [`SignatureReader`] trait
+//! only covers looking up signatures and where they were found, such that it can be implemented by
+//! lightweight backends as well (see `database::sqlite`, enabled by the `sqlite` feature).
+
+use crate::error::Error;
+use crate::model::Signature;
+use crate::model::SignatureKind;
+
+pub trait SignatureReader {
+    /// Returns up to `limit` valid signatures whose selector (4 bytes) or topic (32 bytes) equals the hex
+    /// encoded `hash`, ordered by their id. Hashes of any other length yield no signatures.
+    fn signatures_where_hash(
+        &self,
+        hash: &str,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<Signature>, Error>;
+
+    /// Returns up to `limit` valid signatures whose text starts with `text` (case sensitive), ordered by
+    /// their id.
+    fn signatures_where_text_starts_with(
+        &self,
+        text: &str,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<Signature>, Error>;
+
+    /// Returns all kinds the signature was found as, e.g. both as a function and an error.
+    fn kinds(&self, signature_id: i32) -> Result<Vec<SignatureKind>, Error>;
+
+    /// Returns the ids of up to `limit` GitHub repositories the signature was found in.
+    fn github_repository_ids(
+        &self,
+        signature_id: i32,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<i32>, Error>;
+
+    /// Returns the ids of up to `limit` Etherscan contracts the signature was found in.
+    fn etherscan_contract_ids(
+        &self,
+        signature_id: i32,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<i32>, Error>;
+
+    /// Returns whether the signature is listed on 4Byte.
+    fn is_on_fourbyte(&self, signature_id: i32, kind: Option<SignatureKind>) -> Result<bool, Error>;
+}
@@ -1,3 +1,46 @@
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    api_key (id) {
+        id -> Int4,
+        key_hash -> Text,
+        name -> Text,
+        rate_limit_per_minute -> Int4,
+        rate_limit_burst -> Int4,
+        request_count -> Int8,
+        created_at -> Timestamptz,
+        last_used_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    audit_log (id) {
+        id -> Int4,
+        actor -> Text,
+        action -> Audit_action,
+        target -> Nullable<Text>,
+        details -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    etherscan_bytecode_hash (contract_id, hash) {
+        contract_id -> Int4,
+        hash -> Text,
+        kind -> Signature_kind,
+        added_at -> Timestamptz,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::model::*;
@@ -11,6 +54,34 @@ table! {
         url -> Text,
         scraped_at -> Nullable<Timestamptz>,
         added_at -> Timestamptz,
+        retry_count -> Int4,
+        next_retry_at -> Nullable<Timestamptz>,
+        last_error -> Nullable<Text>,
+        is_unverified -> Bool,
+        creator_address -> Nullable<Text>,
+        creation_tx_hash -> Nullable<Text>,
+        creation_block -> Nullable<Int8>,
+        is_destroyed -> Bool,
+        code_checked_at -> Nullable<Timestamptz>,
+        parser_version -> Int4,
+        chain_id -> Int4,
+        optimization_used -> Nullable<Bool>,
+        optimization_runs -> Nullable<Int4>,
+        evm_version -> Nullable<Text>,
+        license -> Nullable<Text>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    etherscan_payload (id) {
+        id -> Int4,
+        hash -> Text,
+        kind -> Payload_kind,
+        content -> Bytea,
+        added_at -> Timestamptz,
     }
 }
 
@@ -48,6 +119,10 @@ table! {
         solidity_ratio -> Nullable<Float4>,
         is_deleted -> Bool,
         found_by_crawling -> Bool,
+        parser_version -> Int4,
+        description -> Nullable<Text>,
+        topics -> Array<Text>,
+        license -> Nullable<Text>,
     }
 }
 
@@ -69,11 +144,33 @@ table! {
     use diesel::sql_types::*;
     use crate::model::*;
 
-    mapping_signature_etherscan (signature_id, contract_id, kind) {
+    mapping_payload_etherscan (payload_id, contract_id) {
+        payload_id -> Int4,
+        contract_id -> Int4,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    mapping_stargazer (user_id, repository_id) {
+        user_id -> Int4,
+        repository_id -> Int4,
+        added_at -> Timestamptz,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    mapping_signature_etherscan (signature_id, contract_id, kind, added_at) {
         signature_id -> Int4,
         contract_id -> Int4,
         kind -> Signature_kind,
         added_at -> Timestamptz,
+        last_seen_at -> Timestamptz,
     }
 }
 
@@ -81,10 +178,11 @@ table! {
     use diesel::sql_types::*;
     use crate::model::*;
 
-    mapping_signature_fourbyte (signature_id, kind) {
+    mapping_signature_fourbyte (signature_id, kind, added_at) {
         signature_id -> Int4,
         kind -> Signature_kind,
         added_at -> Timestamptz,
+        last_seen_at -> Timestamptz,
     }
 }
 
@@ -92,11 +190,12 @@ table! {
     use diesel::sql_types::*;
     use crate::model::*;
 
-    mapping_signature_github (signature_id, repository_id, kind) {
+    mapping_signature_github (signature_id, repository_id, kind, added_at) {
         signature_id -> Int4,
         repository_id -> Int4,
         kind -> Signature_kind,
         added_at -> Timestamptz,
+        last_seen_at -> Timestamptz,
     }
 }
 
@@ -110,6 +209,29 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    mapping_signature_submission (signature_id, submission_id, kind) {
+        signature_id -> Int4,
+        submission_id -> Int4,
+        kind -> Signature_kind,
+        added_at -> Timestamptz,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    materialized_view_refresh (name) {
+        name -> Text,
+        refreshed_at -> Timestamptz,
+        duration_ms -> Int8,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::model::*;
@@ -120,25 +242,177 @@ table! {
         hash -> Text,
         is_valid -> Bool,
         added_at -> Timestamptz,
+        selector -> Bytea,
+        topic0 -> Bytea,
+        usage_count -> Int8,
+        last_called_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    signature_flag (id) {
+        id -> Int4,
+        signature_id -> Int4,
+        kind -> Signature_kind,
+        source -> Nullable<Signature_source>,
+        source_id -> Nullable<Int4>,
+        reason -> Flag_reason,
+        comment -> Nullable<Text>,
+        api_key_id -> Nullable<Int4>,
+        flag_count -> Int4,
+        status -> Flag_status,
+        flagged_at -> Timestamptz,
+        moderated_at -> Nullable<Timestamptz>,
+        moderated_by -> Nullable<Text>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    signature_submission (id) {
+        id -> Int4,
+        api_key_id -> Int4,
+        text -> Text,
+        hash -> Text,
+        kind -> Signature_kind,
+        is_valid -> Bool,
+        status -> Submission_status,
+        submitted_at -> Timestamptz,
+        moderated_at -> Nullable<Timestamptz>,
+        moderated_by -> Nullable<Text>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    view_selector_collision (selector) {
+        selector -> Text,
+        signature_count -> Int4,
+        last_added_at -> Timestamptz,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    watchlist_entry (id) {
+        id -> Int4,
+        api_key_id -> Int4,
+        hash -> Text,
+        webhook_url -> Nullable<Text>,
+        created_at -> Timestamptz,
+        resolved_at -> Nullable<Timestamptz>,
+        signature_id -> Nullable<Int4>,
+        text -> Nullable<Text>,
+        kind -> Nullable<Signature_kind>,
+        notified_at -> Nullable<Timestamptz>,
+        notify_attempts -> Int4,
+        next_notify_at -> Nullable<Timestamptz>,
+        last_notify_error -> Nullable<Text>,
+        webhook_secret -> Nullable<Text>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    webhook (id) {
+        id -> Int4,
+        api_key_id -> Int4,
+        url -> Text,
+        secret -> Text,
+        kind -> Nullable<Signature_kind>,
+        prefix -> Nullable<Text>,
+        source -> Nullable<Signature_source>,
+        created_at -> Timestamptz,
+        delivered_at -> Nullable<Timestamptz>,
+        failure_count -> Int4,
+        next_delivery_at -> Timestamptz,
+        last_error -> Nullable<Text>,
+        disabled_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    webhook_delivery (id) {
+        id -> Int8,
+        webhook_id -> Int4,
+        signature_id -> Int4,
+        kind -> Signature_kind,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::model::*;
+
+    worker_status (name) {
+        name -> Text,
+        started_at -> Timestamptz,
+        last_loop_at -> Timestamptz,
+        current_item -> Nullable<Text>,
+        items_processed -> Int8,
     }
 }
 
+joinable!(etherscan_bytecode_hash -> etherscan_contract (contract_id));
 joinable!(github_repository -> github_user (owner_id));
+joinable!(mapping_payload_etherscan -> etherscan_contract (contract_id));
+joinable!(mapping_payload_etherscan -> etherscan_payload (payload_id));
 joinable!(mapping_signature_etherscan -> etherscan_contract (contract_id));
 joinable!(mapping_signature_etherscan -> signature (signature_id));
 joinable!(mapping_signature_fourbyte -> signature (signature_id));
 joinable!(mapping_signature_github -> github_repository (repository_id));
 joinable!(mapping_signature_github -> signature (signature_id));
 joinable!(mapping_signature_kind -> signature (signature_id));
+joinable!(mapping_signature_submission -> signature (signature_id));
+joinable!(mapping_signature_submission -> signature_submission (submission_id));
+joinable!(mapping_stargazer -> github_repository (repository_id));
+joinable!(mapping_stargazer -> github_user (user_id));
+joinable!(signature_flag -> api_key (api_key_id));
+joinable!(signature_flag -> signature (signature_id));
+joinable!(watchlist_entry -> api_key (api_key_id));
+joinable!(watchlist_entry -> signature (signature_id));
+joinable!(webhook -> api_key (api_key_id));
+joinable!(webhook_delivery -> signature (signature_id));
+joinable!(webhook_delivery -> webhook (webhook_id));
 
 allow_tables_to_appear_in_same_query!(
+    api_key,
+    audit_log,
+    etherscan_bytecode_hash,
     etherscan_contract,
+    etherscan_payload,
     github_crawler_metadata,
     github_repository,
     github_user,
+    mapping_payload_etherscan,
     mapping_signature_etherscan,
     mapping_signature_fourbyte,
     mapping_signature_github,
     mapping_signature_kind,
+    mapping_signature_submission,
+    mapping_stargazer,
+    materialized_view_refresh,
     signature,
+    signature_flag,
+    signature_submission,
+    view_selector_collision,
+    watchlist_entry,
+    webhook,
+    webhook_delivery,
+    worker_status,
 );
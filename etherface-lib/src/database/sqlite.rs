@@ -0,0 +1,457 @@
+//! SQLite implementation of the [`SignatureReader`] trait, enabled by the `sqlite` feature.
+//!
+//! Intended for local / offline use (e.g. `etherface lookup --sqlite <path>` or small private deployments)
+//! where running a PostgreSQL server is overkill. Only signatures and their mappings are stored, loaded via
+//! the `insert_*` methods (e.g. from a dataset snapshot via `etherface sqlite`, see
+//! `etherface/src/maintenance/sqlite.rs`). The schema is created on connecting rather than by the PostgreSQL
+//! migrations, storing timestamps as RFC 3339 text and kinds as their lowercase names.
+
+use crate::database::reader::SignatureReader;
+use crate::error::Error;
+use crate::model::decode_hash;
+use crate::model::MappingSignatureEtherscan;
+use crate::model::MappingSignatureFourbyte;
+use crate::model::MappingSignatureGithub;
+use crate::model::MappingSignatureKind;
+use crate::model::Signature;
+use crate::model::SignatureKind;
+use chrono::DateTime;
+use chrono::Utc;
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use diesel::sql_query;
+use diesel::sql_types::BigInt;
+use diesel::sql_types::Binary;
+use diesel::sql_types::Bool;
+use diesel::sql_types::Integer;
+use diesel::sql_types::Nullable;
+use diesel::sql_types::Text;
+use diesel::SqliteConnection;
+use std::cell::RefCell;
+use std::str::FromStr;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS signature (
+        id INTEGER PRIMARY KEY,
+        text TEXT NOT NULL,
+        hash TEXT NOT NULL UNIQUE,
+        is_valid BOOLEAN NOT NULL,
+        added_at TEXT NOT NULL,
+        selector BLOB NOT NULL,
+        topic0 BLOB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS index_signature_selector ON signature (selector);
+    CREATE INDEX IF NOT EXISTS index_signature_topic0 ON signature (topic0);
+
+    CREATE TABLE IF NOT EXISTS mapping_signature_kind (
+        signature_id INTEGER NOT NULL REFERENCES signature (id),
+        kind TEXT NOT NULL,
+        PRIMARY KEY (signature_id, kind)
+    );
+
+    CREATE TABLE IF NOT EXISTS mapping_signature_github (
+        signature_id INTEGER NOT NULL REFERENCES signature (id),
+        repository_id INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        added_at TEXT NOT NULL,
+        last_seen_at TEXT NOT NULL,
+        PRIMARY KEY (signature_id, repository_id, kind)
+    );
+
+    CREATE TABLE IF NOT EXISTS mapping_signature_etherscan (
+        signature_id INTEGER NOT NULL REFERENCES signature (id),
+        contract_id INTEGER NOT NULL,
+        kind TEXT NOT NULL,
+        added_at TEXT NOT NULL,
+        last_seen_at TEXT NOT NULL,
+        PRIMARY KEY (signature_id, contract_id, kind)
+    );
+
+    CREATE TABLE IF NOT EXISTS mapping_signature_fourbyte (
+        signature_id INTEGER NOT NULL REFERENCES signature (id),
+        kind TEXT NOT NULL,
+        added_at TEXT NOT NULL,
+        last_seen_at TEXT NOT NULL,
+        PRIMARY KEY (signature_id, kind)
+    );
+";
+
+/// Database client backed by a single SQLite file.
+pub struct SqliteClient {
+    connection: RefCell<SqliteConnection>,
+}
+
+#[derive(QueryableByName)]
+struct SignatureRow {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+
+    #[diesel(sql_type = Text)]
+    text: String,
+
+    #[diesel(sql_type = Text)]
+    hash: String,
+
+    #[diesel(sql_type = Bool)]
+    is_valid: bool,
+
+    #[diesel(sql_type = Text)]
+    added_at: String,
+
+    #[diesel(sql_type = Binary)]
+    selector: Vec<u8>,
+
+    #[diesel(sql_type = Binary)]
+    topic0: Vec<u8>,
+}
+
+#[derive(QueryableByName)]
+struct KindRow {
+    #[diesel(sql_type = Text)]
+    kind: String,
+}
+
+#[derive(QueryableByName)]
+struct IdRow {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+}
+
+#[derive(QueryableByName)]
+struct ExistsRow {
+    #[diesel(sql_type = Bool)]
+    exists: bool,
+}
+
+impl SqliteClient {
+    /// Opens (or creates) the SQLite database at `path`, creating all tables if they don't exist yet.
+    /// `:memory:` opens a temporary in-memory database.
+    pub fn new(path: &str) -> Result<Self, Error> {
+        let mut connection = SqliteConnection::establish(path)?;
+        connection.batch_execute(SCHEMA)?;
+
+        Ok(SqliteClient {
+            connection: RefCell::new(connection),
+        })
+    }
+
+    /// Inserts `entities` keeping their ids, skipping already present signatures.
+    pub fn insert_signatures(&self, entities: &[Signature]) -> Result<usize, Error> {
+        self.connection.borrow_mut().transaction(|connection| {
+            let mut inserted = 0;
+            for entity in entities {
+                inserted += sql_query(
+                    "INSERT OR IGNORE INTO signature (id, text, hash, is_valid, added_at, selector, topic0)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind::<Integer, _>(entity.id)
+                .bind::<Text, _>(&entity.text)
+                .bind::<Text, _>(&entity.hash)
+                .bind::<Bool, _>(entity.is_valid)
+                .bind::<Text, _>(entity.added_at.to_rfc3339())
+                .bind::<Binary, _>(&entity.selector)
+                .bind::<Binary, _>(&entity.topic0)
+                .execute(connection)?;
+            }
+
+            Ok(inserted)
+        })
+    }
+
+    /// Inserts `entities`, skipping already present mappings.
+    pub fn insert_kinds(&self, entities: &[MappingSignatureKind]) -> Result<usize, Error> {
+        self.connection.borrow_mut().transaction(|connection| {
+            let mut inserted = 0;
+            for entity in entities {
+                inserted += sql_query(
+                    "INSERT OR IGNORE INTO mapping_signature_kind (signature_id, kind) VALUES (?, ?)",
+                )
+                .bind::<Integer, _>(entity.signature_id)
+                .bind::<Text, _>(kind_name(entity.kind))
+                .execute(connection)?;
+            }
+
+            Ok(inserted)
+        })
+    }
+
+    /// Inserts `entities`, skipping already present mappings.
+    pub fn insert_github_mappings(&self, entities: &[MappingSignatureGithub]) -> Result<usize, Error> {
+        self.connection.borrow_mut().transaction(|connection| {
+            let mut inserted = 0;
+            for entity in entities {
+                inserted += sql_query(
+                    "INSERT OR IGNORE INTO mapping_signature_github
+                    (signature_id, repository_id, kind, added_at, last_seen_at) VALUES (?, ?, ?, ?, ?)",
+                )
+                .bind::<Integer, _>(entity.signature_id)
+                .bind::<Integer, _>(entity.repository_id)
+                .bind::<Text, _>(kind_name(entity.kind))
+                .bind::<Text, _>(entity.added_at.to_rfc3339())
+                .bind::<Text, _>(entity.last_seen_at.to_rfc3339())
+                .execute(connection)?;
+            }
+
+            Ok(inserted)
+        })
+    }
+
+    /// Inserts `entities`, skipping already present mappings.
+    pub fn insert_etherscan_mappings(&self, entities: &[MappingSignatureEtherscan]) -> Result<usize, Error> {
+        self.connection.borrow_mut().transaction(|connection| {
+            let mut inserted = 0;
+            for entity in entities {
+                inserted += sql_query(
+                    "INSERT OR IGNORE INTO mapping_signature_etherscan
+                    (signature_id, contract_id, kind, added_at, last_seen_at) VALUES (?, ?, ?, ?, ?)",
+                )
+                .bind::<Integer, _>(entity.signature_id)
+                .bind::<Integer, _>(entity.contract_id)
+                .bind::<Text, _>(kind_name(entity.kind))
+                .bind::<Text, _>(entity.added_at.to_rfc3339())
+                .bind::<Text, _>(entity.last_seen_at.to_rfc3339())
+                .execute(connection)?;
+            }
+
+            Ok(inserted)
+        })
+    }
+
+    /// Inserts `entities`, skipping already present mappings.
+    pub fn insert_fourbyte_mappings(&self, entities: &[MappingSignatureFourbyte]) -> Result<usize, Error> {
+        self.connection.borrow_mut().transaction(|connection| {
+            let mut inserted = 0;
+            for entity in entities {
+                inserted += sql_query(
+                    "INSERT OR IGNORE INTO mapping_signature_fourbyte
+                    (signature_id, kind, added_at, last_seen_at) VALUES (?, ?, ?, ?)",
+                )
+                .bind::<Integer, _>(entity.signature_id)
+                .bind::<Text, _>(kind_name(entity.kind))
+                .bind::<Text, _>(entity.added_at.to_rfc3339())
+                .bind::<Text, _>(entity.last_seen_at.to_rfc3339())
+                .execute(connection)?;
+            }
+
+            Ok(inserted)
+        })
+    }
+}
+
+impl SignatureReader for SqliteClient {
+    fn signatures_where_hash(
+        &self,
+        hash: &str,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<Signature>, Error> {
+        let hash = match decode_hash(hash) {
+            Some(val) if val.len() == 4 || val.len() == 32 => val,
+            _ => return Ok(Vec::new()),
+        };
+
+        let column = match hash.len() {
+            4 => "selector",
+            _ => "topic0",
+        };
+
+        let query = format!(
+            "SELECT * FROM signature WHERE is_valid AND {column} = ?1
+            AND (?2 IS NULL OR id IN (SELECT signature_id FROM mapping_signature_kind WHERE kind = ?2))
+            ORDER BY id LIMIT ?3"
+        );
+
+        let rows = sql_query(query)
+            .bind::<Binary, _>(hash)
+            .bind::<Nullable<Text>, _>(kind.map(kind_name))
+            .bind::<BigInt, _>(limit)
+            .load::<SignatureRow>(&mut *self.connection.borrow_mut())?;
+
+        rows.into_iter().map(SignatureRow::into_signature).collect()
+    }
+
+    fn signatures_where_text_starts_with(
+        &self,
+        text: &str,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<Signature>, Error> {
+        // `substr` rather than `LIKE`, which is case insensitive in SQLite
+        let rows = sql_query(
+            "SELECT * FROM signature WHERE is_valid AND substr(text, 1, length(?1)) = ?1
+            AND (?2 IS NULL OR id IN (SELECT signature_id FROM mapping_signature_kind WHERE kind = ?2))
+            ORDER BY id LIMIT ?3",
+        )
+        .bind::<Text, _>(text)
+        .bind::<Nullable<Text>, _>(kind.map(kind_name))
+        .bind::<BigInt, _>(limit)
+        .load::<SignatureRow>(&mut *self.connection.borrow_mut())?;
+
+        rows.into_iter().map(SignatureRow::into_signature).collect()
+    }
+
+    fn kinds(&self, signature_id: i32) -> Result<Vec<SignatureKind>, Error> {
+        let rows = sql_query("SELECT kind FROM mapping_signature_kind WHERE signature_id = ?")
+            .bind::<Integer, _>(signature_id)
+            .load::<KindRow>(&mut *self.connection.borrow_mut())?;
+
+        rows.iter().map(|row| parse_kind(&row.kind)).collect()
+    }
+
+    fn github_repository_ids(
+        &self,
+        signature_id: i32,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<i32>, Error> {
+        let rows = sql_query(
+            "SELECT DISTINCT repository_id AS id FROM mapping_signature_github
+            WHERE signature_id = ?1 AND (?2 IS NULL OR kind = ?2) ORDER BY id LIMIT ?3",
+        )
+        .bind::<Integer, _>(signature_id)
+        .bind::<Nullable<Text>, _>(kind.map(kind_name))
+        .bind::<BigInt, _>(limit)
+        .load::<IdRow>(&mut *self.connection.borrow_mut())?;
+
+        Ok(rows.into_iter().map(|row| row.id).collect())
+    }
+
+    fn etherscan_contract_ids(
+        &self,
+        signature_id: i32,
+        kind: Option<SignatureKind>,
+        limit: i64,
+    ) -> Result<Vec<i32>, Error> {
+        let rows = sql_query(
+            "SELECT DISTINCT contract_id AS id FROM mapping_signature_etherscan
+            WHERE signature_id = ?1 AND (?2 IS NULL OR kind = ?2) ORDER BY id LIMIT ?3",
+        )
+        .bind::<Integer, _>(signature_id)
+        .bind::<Nullable<Text>, _>(kind.map(kind_name))
+        .bind::<BigInt, _>(limit)
+        .load::<IdRow>(&mut *self.connection.borrow_mut())?;
+
+        Ok(rows.into_iter().map(|row| row.id).collect())
+    }
+
+    fn is_on_fourbyte(&self, signature_id: i32, kind: Option<SignatureKind>) -> Result<bool, Error> {
+        let row = sql_query(
+            "SELECT EXISTS (
+                SELECT 1 FROM mapping_signature_fourbyte WHERE signature_id = ?1 AND (?2 IS NULL OR kind = ?2)
+            ) AS \"exists\"",
+        )
+        .bind::<Integer, _>(signature_id)
+        .bind::<Nullable<Text>, _>(kind.map(kind_name))
+        .get_result::<ExistsRow>(&mut *self.connection.borrow_mut())?;
+
+        Ok(row.exists)
+    }
+}
+
+impl SignatureRow {
+    fn into_signature(self) -> Result<Signature, Error> {
+        let added_at = DateTime::parse_from_rfc3339(&self.added_at)
+            .map_err(|why| diesel::result::Error::DeserializationError(Box::new(why)))?;
+
+        Ok(Signature {
+            id: self.id,
+            text: self.text,
+            hash: self.hash,
+            is_valid: self.is_valid,
+            added_at: added_at.with_timezone(&Utc),
+            selector: self.selector,
+            topic0: self.topic0,
+
+            // On-chain usage isn't imported into SQLite databases
+            usage_count: 0,
+            last_called_at: None,
+        })
+    }
+}
+
+/// Returns the lowercase name of `kind`, i.e. the same representation as the PostgreSQL `signature_kind`.
+fn kind_name(kind: SignatureKind) -> &'static str {
+    match kind {
+        SignatureKind::Function => "function",
+        SignatureKind::Event => "event",
+        SignatureKind::Error => "error",
+        SignatureKind::Constructor => "constructor",
+        SignatureKind::Fallback => "fallback",
+        SignatureKind::Receive => "receive",
+    }
+}
+
+fn parse_kind(name: &str) -> Result<SignatureKind, Error> {
+    SignatureKind::from_str(name).map_err(|_| {
+        Error::Database(diesel::result::Error::DeserializationError(
+            format!("Invalid signature kind '{name}'").into(),
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::reader::SignatureReader;
+    use crate::database::sqlite::SqliteClient;
+    use crate::model::MappingSignatureFourbyte;
+    use crate::model::MappingSignatureKind;
+    use crate::model::Signature;
+    use crate::model::SignatureKind;
+    use crate::model::SignatureWithMetadata;
+    use chrono::TimeZone;
+    use chrono::Utc;
+
+    fn signature(id: i32, text: &str) -> Signature {
+        let metadata = SignatureWithMetadata::new(text.to_string(), SignatureKind::Function, true);
+        let insertable = metadata.to_insertable();
+
+        Signature {
+            id,
+            text: insertable.text.to_string(),
+            hash: insertable.hash.to_string(),
+            is_valid: true,
+            added_at: Utc.ymd(2022, 10, 1).and_hms(0, 0, 0),
+            selector: insertable.selector,
+            topic0: insertable.topic0,
+            usage_count: 0,
+            last_called_at: None,
+        }
+    }
+
+    #[test]
+    fn sqlite_lookup() {
+        let client = SqliteClient::new(":memory:").unwrap();
+        let transfer = signature(1, "transfer(address,uint256)");
+        let transfer_from = signature(2, "transferFrom(address,address,uint256)");
+
+        assert_eq!(client.insert_signatures(&[transfer.clone(), transfer_from]).unwrap(), 2);
+        assert_eq!(client.insert_signatures(&[transfer.clone()]).unwrap(), 0);
+        client
+            .insert_kinds(&[MappingSignatureKind {
+                signature_id: 1,
+                kind: SignatureKind::Function,
+            }])
+            .unwrap();
+        client
+            .insert_fourbyte_mappings(&[MappingSignatureFourbyte {
+                signature_id: 1,
+                kind: SignatureKind::Function,
+                added_at: transfer.added_at,
+                last_seen_at: transfer.added_at,
+            }])
+            .unwrap();
+
+        let found = client.signatures_where_hash("a9059cbb", Some(SignatureKind::Function), 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "transfer(address,uint256)");
+        assert_eq!(found[0].added_at, transfer.added_at);
+        assert!(client.signatures_where_hash("a9059cbb", Some(SignatureKind::Event), 10).unwrap().is_empty());
+
+        assert_eq!(client.signatures_where_text_starts_with("transfer", None, 10).unwrap().len(), 2);
+        assert!(client.signatures_where_text_starts_with("Transfer", None, 10).unwrap().is_empty());
+
+        assert_eq!(client.kinds(1).unwrap(), vec![SignatureKind::Function]);
+        assert!(client.is_on_fourbyte(1, None).unwrap());
+        assert!(!client.is_on_fourbyte(2, None).unwrap());
+    }
+}
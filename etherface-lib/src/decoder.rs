@@ -0,0 +1,356 @@
+//! ABI decoding of revert data, calldata and logs by the signatures of the database.
+//!
+//! Selectors are ambiguous (see the `view_selector_collision` view), as such the data is decoded with every
+//! signature sharing its selector and only the signatures it's a valid encoding of are returned. As decoding
+//! is lenient regarding e.g. trailing data, the decoded arguments are re-encoded and compared against the
+//! data, rejecting signatures which merely happen to decode.
+//!
+//! Signatures don't state which event parameters are indexed, as such logs are decoded by every assignment
+//! of their topics to the parameters, the first one yielding a valid encoding being returned. Indexed
+//! parameters of dynamic types are hashed by the EVM and hence returned as their (undecodable) topic.
+
+use ethabi::ethereum_types::U256;
+use ethabi::param_type::Reader;
+use ethabi::ParamType;
+use ethabi::Token;
+use serde::Serialize;
+use serde_json::Value;
+use sha3::Digest;
+use sha3::Keccak256;
+
+/// Errors built into Solidity, i.e. `require(.., "reason")` / `revert("reason")` and failed assertions,
+/// arithmetic overflows etc., which aren't declared by any contract and hence aren't necessarily stored.
+pub const BUILTIN_ERRORS: &[&str] = &["Error(string)", "Panic(uint256)"];
+
+/// Data decoded by all signatures sharing its selector which it's a valid encoding of.
+#[derive(Serialize, Debug)]
+pub struct Decoded {
+    /// Hex encoded selector including its `0x` prefix.
+    pub selector: String,
+    pub candidates: Vec<DecodedCall>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DecodedCall {
+    pub signature: String,
+    pub arguments: Vec<DecodedArgument>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DecodedArgument {
+    /// Solidity type of the argument, e.g. `uint256`.
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Decoded value, numbers being represented as decimal strings as they may exceed JSONs precision and
+    /// addresses and bytes as `0x` prefixed hex strings.
+    pub value: Value,
+}
+
+/// Log decoded by all events sharing its topic which it's a valid encoding of.
+#[derive(Serialize, Debug)]
+pub struct DecodedLog {
+    /// Address of the contract which emitted the log.
+    pub address: String,
+
+    /// Hex encoded topics including their `0x` prefix, the first one being the events topic.
+    pub topics: Vec<String>,
+
+    pub candidates: Vec<DecodedCall>,
+}
+
+/// Transaction alongside its input, logs and revert data decoded by the signatures of the database.
+#[derive(Serialize, Debug)]
+pub struct DecodedTransaction {
+    pub hash: String,
+    pub from: String,
+
+    /// `None` for contract creations.
+    pub to: Option<String>,
+
+    /// Transferred wei as a decimal string.
+    pub value: String,
+
+    /// `None` for pending transactions.
+    pub block_number: Option<u64>,
+
+    /// Whether the transaction succeeded, `None` for pending and pre-Byzantium transactions.
+    pub succeeded: Option<bool>,
+
+    /// `None` for plain ether transfers, i.e. transactions without a selector.
+    pub input: Option<Decoded>,
+    pub logs: Vec<DecodedLog>,
+
+    /// Hex encoded data the transaction reverted with including its `0x` prefix, alongside the errors
+    /// decoding it; `None` if it didn't revert or reverted without data.
+    pub revert_data: Option<String>,
+    pub revert: Option<Decoded>,
+}
+
+/// Maximum number of event parameters for which all assignments of topics are tried.
+const MAX_EVENT_PARAMETERS: usize = 16;
+
+/// Returns the 4 byte selector of the signature `text`.
+pub fn selector_of(text: &str) -> [u8; 4] {
+    let hash = Keccak256::digest(text);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Returns the 32 byte topic of the event signature `text`.
+pub fn topic_of(text: &str) -> [u8; 32] {
+    Keccak256::digest(text).into()
+}
+
+/// Returns `bytes` hex encoded with a `0x` prefix.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    format!("0x{}", bytes.iter().map(|x| format!("{x:02x}")).collect::<String>())
+}
+
+/// Returns the parameter types of the signature `text`, e.g. `[address, uint256]` for
+/// `transfer(address,uint256)`, or `None` if they can't be parsed.
+pub fn parameter_types(text: &str) -> Option<Vec<ParamType>> {
+    let parameters = &text[text.find('(')?..];
+    if parameters == "()" {
+        return Some(Vec::new());
+    }
+
+    match Reader::read(parameters).ok()? {
+        ParamType::Tuple(types) => Some(types),
+        _ => None,
+    }
+}
+
+/// Decodes `data`, i.e. the ABI encoded parameters without a selector, by the signature `text`, returning
+/// `None` if `data` isn't a valid encoding of its parameters.
+pub fn decode_parameters(text: &str, data: &[u8]) -> Option<DecodedCall> {
+    let types = parameter_types(text)?;
+    let tokens = decode_strict(&types, data)?;
+
+    let arguments = types
+        .iter()
+        .zip(tokens)
+        .map(|(kind, token)| DecodedArgument {
+            kind: kind.to_string(),
+            value: token_to_json(token),
+        })
+        .collect();
+
+    Some(DecodedCall {
+        signature: text.to_string(),
+        arguments,
+    })
+}
+
+/// Decodes `data` by `types`, returning `None` unless `data` is exactly their encoding.
+fn decode_strict(types: &[ParamType], data: &[u8]) -> Option<Vec<Token>> {
+    let tokens = ethabi::decode(types, data).ok()?;
+    match ethabi::encode(&tokens) == data {
+        true => Some(tokens),
+        false => None,
+    }
+}
+
+/// Decodes a log, i.e. its `topics` (the first one being the events topic) and `data`, by each of the
+/// `candidates` whose topic matches. Anonymous events, i.e. logs without topics, can't be decoded.
+pub fn decode_log<'a>(
+    candidates: impl IntoIterator<Item = &'a str>,
+    address: &str,
+    topics: &[Vec<u8>],
+    data: &[u8],
+) -> DecodedLog {
+    let candidates = match topics.split_first() {
+        Some((topic0, indexed)) => candidates
+            .into_iter()
+            .filter(|text| &topic_of(text)[..] == topic0.as_slice())
+            .filter_map(|text| decode_event(text, indexed, data))
+            .collect(),
+
+        None => Vec::new(),
+    };
+
+    DecodedLog {
+        address: address.to_string(),
+        topics: topics.iter().map(|x| encode_hex(x)).collect(),
+        candidates,
+    }
+}
+
+/// Decodes a log by the event `text`, trying every assignment of the `indexed` topics to its parameters.
+fn decode_event(text: &str, indexed: &[Vec<u8>], data: &[u8]) -> Option<DecodedCall> {
+    let types = parameter_types(text)?;
+    if indexed.len() > types.len() || types.len() > MAX_EVENT_PARAMETERS {
+        return None;
+    }
+
+    // Each set bit marks an indexed parameter, assignments being tried in ascending order such that the
+    // leading parameters are assumed to be indexed first, as is the common case.
+    let mut masks = (0u32..1 << types.len())
+        .filter(|mask| mask.count_ones() as usize == indexed.len())
+        .collect::<Vec<_>>();
+    masks.sort_by_key(|mask| std::cmp::Reverse(mask.reverse_bits()));
+
+    masks.into_iter().find_map(|mask| {
+        let is_indexed = |idx: usize| mask & (1 << idx) != 0;
+
+        let unindexed = (0..types.len()).filter(|idx| !is_indexed(*idx)).map(|idx| types[idx].clone());
+        let mut unindexed = decode_strict(&unindexed.collect::<Vec<_>>(), data)?.into_iter();
+        let mut indexed = indexed.iter();
+
+        let mut arguments = Vec::with_capacity(types.len());
+        for (idx, kind) in types.iter().enumerate() {
+            let value = match is_indexed(idx) {
+                true => decode_topic(kind, indexed.next()?)?,
+                false => token_to_json(unindexed.next()?),
+            };
+
+            arguments.push(DecodedArgument {
+                kind: kind.to_string(),
+                value,
+            });
+        }
+
+        Some(DecodedCall {
+            signature: text.to_string(),
+            arguments,
+        })
+    })
+}
+
+/// Decodes an indexed parameter of type `kind` from its `topic`, returning the topic itself for dynamic
+/// types as only their hash is logged.
+fn decode_topic(kind: &ParamType, topic: &[u8]) -> Option<Value> {
+    match kind {
+        ParamType::Address
+        | ParamType::Uint(_)
+        | ParamType::Int(_)
+        | ParamType::Bool
+        | ParamType::FixedBytes(_) => {
+            let mut tokens = decode_strict(&[kind.clone()], topic)?;
+            Some(token_to_json(tokens.remove(0)))
+        }
+
+        _ if topic.len() == 32 => Some(Value::String(encode_hex(topic))),
+        _ => None,
+    }
+}
+
+/// Decodes `data`, i.e. a selector followed by the ABI encoded parameters, by each of the `candidates`
+/// whose selector matches, returning `None` if `data` is shorter than a selector.
+pub fn decode_with_selector<'a>(
+    candidates: impl IntoIterator<Item = &'a str>,
+    data: &[u8],
+) -> Option<Decoded> {
+    if data.len() < 4 {
+        return None;
+    }
+
+    let (selector, parameters) = data.split_at(4);
+    let candidates = candidates
+        .into_iter()
+        .filter(|text| selector_of(text) == selector)
+        .filter_map(|text| decode_parameters(text, parameters))
+        .collect();
+
+    Some(Decoded {
+        selector: encode_hex(selector),
+        candidates,
+    })
+}
+
+fn token_to_json(token: Token) -> Value {
+    match token {
+        Token::Address(address) => Value::String(format!("{address:#x}")),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => Value::String(encode_hex(&bytes)),
+        Token::Uint(value) => Value::String(value.to_string()),
+
+        // Two's complement
+        Token::Int(value) if value.bit(255) => {
+            Value::String(format!("-{}", (!value).overflowing_add(U256::one()).0))
+        }
+        Token::Int(value) => Value::String(value.to_string()),
+
+        Token::Bool(value) => Value::Bool(value),
+        Token::String(value) => Value::String(value),
+        Token::FixedArray(tokens) | Token::Array(tokens) | Token::Tuple(tokens) => {
+            Value::Array(tokens.into_iter().map(token_to_json).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decoder::decode_log;
+    use crate::decoder::decode_parameters;
+    use crate::decoder::decode_with_selector;
+    use crate::decoder::encode_hex;
+    use crate::decoder::selector_of;
+    use crate::decoder::topic_of;
+    use crate::decoder::BUILTIN_ERRORS;
+    use ethabi::ethereum_types::Address;
+    use ethabi::ethereum_types::U256;
+    use ethabi::Token;
+    use serde_json::json;
+
+    fn encode_call(text: &str, tokens: &[Token]) -> Vec<u8> {
+        [&selector_of(text)[..], &ethabi::encode(tokens)].concat()
+    }
+
+    #[test]
+    fn decode_builtin_errors() {
+        assert_eq!(encode_hex(&selector_of("Error(string)")), "0x08c379a0");
+        assert_eq!(encode_hex(&selector_of("Panic(uint256)")), "0x4e487b71");
+
+        let data = encode_call("Error(string)", &[Token::String("Not enough balance".to_string())]);
+        let decoded = decode_with_selector(BUILTIN_ERRORS.iter().copied(), &data).unwrap();
+        assert_eq!(decoded.selector, "0x08c379a0");
+        assert_eq!(decoded.candidates.len(), 1);
+        assert_eq!(decoded.candidates[0].arguments[0].value, json!("Not enough balance"));
+
+        let data = encode_call("Panic(uint256)", &[Token::Uint(U256::from(0x11))]);
+        let decoded = decode_with_selector(BUILTIN_ERRORS.iter().copied(), &data).unwrap();
+        assert_eq!(decoded.candidates[0].arguments[0].value, json!("17"));
+
+        assert!(decode_with_selector(BUILTIN_ERRORS.iter().copied(), &[0x08, 0xc3]).is_none());
+    }
+
+    #[test]
+    fn decode_parameters_rejects_mismatches() {
+        let data = ethabi::encode(&[Token::Int(U256::MAX), Token::Tuple(vec![Token::Bool(true)])]);
+        let decoded = decode_parameters("Foo(int8,(bool))", &data).unwrap();
+        assert_eq!(decoded.arguments[0].kind, "int8");
+        assert_eq!(decoded.arguments[0].value, json!("-1"));
+        assert_eq!(decoded.arguments[1].value, json!([true]));
+
+        // Trailing data
+        assert!(decode_parameters("Foo(int8)", &data).is_none());
+        assert!(decode_parameters("Foo()", &[]).unwrap().arguments.is_empty());
+    }
+
+    #[test]
+    fn decode_log_by_indexed_topics() {
+        let from = ethabi::encode(&[Token::Address(Address::repeat_byte(0x11))]);
+        let to = ethabi::encode(&[Token::Address(Address::repeat_byte(0x22))]);
+        let amount = ethabi::encode(&[Token::Uint(U256::from(1000))]);
+        let transfer = "Transfer(address,address,uint256)";
+
+        // ERC-20 transfers index both addresses, ERC-721 transfers the token id as well
+        let topics = vec![topic_of(transfer).to_vec(), from.clone(), to.clone()];
+        let decoded = decode_log([transfer, "Approval(address,address,uint256)"], "0xabcd", &topics, &amount);
+        assert_eq!(decoded.topics.len(), 3);
+        assert_eq!(decoded.candidates.len(), 1);
+        assert_eq!(decoded.candidates[0].arguments[0].value, json!(format!("0x{}", "11".repeat(20))));
+        assert_eq!(decoded.candidates[0].arguments[2].value, json!("1000"));
+
+        let topics = vec![topic_of(transfer).to_vec(), from, to, amount];
+        let decoded = decode_log([transfer], "0xabcd", &topics, &[]);
+        assert_eq!(decoded.candidates[0].arguments[2].value, json!("1000"));
+
+        // Dynamic types are logged as their hash
+        let topic = [0xff; 32].to_vec();
+        let topics = vec![topic_of("Named(string)").to_vec(), topic.clone()];
+        let decoded = decode_log(["Named(string)"], "0xabcd", &topics, &[]);
+        assert_eq!(decoded.candidates[0].arguments[0].value, json!(encode_hex(&topic)));
+
+        assert!(decode_log([transfer], "0xabcd", &[], &[]).candidates.is_empty());
+    }
+}
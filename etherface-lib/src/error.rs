@@ -14,6 +14,12 @@ pub enum Error {
     #[error("Failed to request data, token invalid")]
     GithubTokenInvalid,
 
+    #[error("GitHub API version unsupported, check ETHERFACE_GITHUB_API_VERSION; {0}")]
+    GithubApiVersionUnsupported(String),
+
+    #[error("GraphQL query returned no data; {0}")]
+    GithubGraphql(String),
+
     #[error("Failed to deserialize JSON input; {0}")]
     DeserializeError(#[from] serde_json::Error),
 
@@ -27,12 +33,35 @@ pub enum Error {
     #[error("Failed to retrieve source for '{0}'; Contract source code not verified")]
     EtherscanContractSourceCodeNotVerified(String),
 
+    #[error("Etherscan has no data for '{0}'")]
+    EtherscanNoData(String),
+
+    #[error("Etherscan rejected request '{0}'; {1}")]
+    EtherscanRequestRejected(String, String),
+
+    // JSON-RPC Errors
+    #[error("JSON-RPC method '{0}' failed with code {1}; {2}")]
+    Rpc(String, i64, String),
+
+    // Webhook Errors
+    #[error("Refusing to call webhook '{0}', its host must only resolve to public addresses")]
+    WebhookHostForbidden(String),
+
     // HTTP Errors
     #[error("Failed to initialize HTTP client; {0}")]
     HttpClient(#[from] reqwest::Error),
 
-    #[error("Failed to send HTTP request; {0}")]
-    HttpRequest(#[source] reqwest::Error),
+    #[error("Failed to send HTTP request to '{0}'; {1}")]
+    HttpRequest(String, #[source] reqwest::Error),
+
+    #[error("Request to '{0}' failed with status {1}; {2}")]
+    HttpStatus(String, u16, String),
+
+    #[error("Request to '{0}' failed without being retried; {1}")]
+    HttpResponseNotRetried(String, String),
+
+    #[error("Invalid HTTP header value; {0}")]
+    HttpHeader(#[from] reqwest::header::InvalidHeaderValue),
 
     // Config Errors
     #[error("Failed to read .env file; {0}")]
@@ -44,13 +73,38 @@ pub enum Error {
     #[error("Environment variable '{0}' is empty")]
     ConfigReadEmptyEnvironmentVariable(&'static str),
 
+    #[error("Environment variable '{0}' has invalid value '{1}'")]
+    ConfigReadInvalidEnvironmentVariable(&'static str, String),
+
+    // Logging Errors
+    #[error("Failed to open log file; {0}")]
+    LoggerFile(#[source] tracing_appender::rolling::InitError),
+
+    #[error("Failed to initialize logger; {0}")]
+    LoggerInit(#[from] tracing_subscriber::util::TryInitError),
+
     #[error("Failed to connect to database; {0}")]
     DatabaseConnect(#[from] diesel::result::ConnectionError),
 
+    #[error("Failed to get connection from database pool; {0}")]
+    DatabasePool(#[from] diesel::r2d2::PoolError),
+
+    #[error("Failed to run database migrations; {0}")]
+    DatabaseMigration(#[source] Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("Failed to execute database query; {0}")]
+    Database(#[from] diesel::result::Error),
+
+    #[error("Failed to (de)compress Etherscan payload; {0}")]
+    PayloadCompression(#[source] std::io::Error),
+
     // Parser / Deserializer
     #[error("Failed to deserialize content, invalid ABI?")]
     ParseAbi(#[source] serde_json::Error),
 
+    #[error("Failed to decode bytecode, invalid hex string?")]
+    ParseBytecode,
+
     #[error("Aborting crawling process, one or more background events disconnected from channel")]
     CrawlerChannelDisconnected,
 }
@@ -3,9 +3,17 @@
 pub mod api;
 pub mod config;
 pub mod database;
+pub mod decoder;
 pub mod error;
+pub mod logging;
+pub mod metrics;
 pub mod model;
 pub mod parser;
+pub mod report;
+pub mod standard;
 
 #[macro_use]
 extern crate diesel;
+
+#[macro_use]
+extern crate diesel_migrations;
@@ -0,0 +1,69 @@
+//! Logging setup shared by the `etherface` and `etherface-rest` binaries.
+//!
+//! Uses `tracing` with either human readable or JSON output (see `Config::log_format`), where the log levels
+//! per module can be configured with an [`EnvFilter`] directive (see `Config::log_filter`), e.g.
+//! `etherface=debug,etherface_lib::api=trace`. Records emitted with the `log` crate are forwarded to
+//! `tracing`, hence existing `log::debug!` etc. calls don't need to be changed.
+//!
+//! Log files are rotated (see `Config::log_rotation`), keeping only the latest `Config::log_retention` files
+//! such that long-running deployments don't fill the disk.
+
+use crate::config::Config;
+use crate::config::LogFormat;
+use crate::config::LogRotation;
+use crate::config::ENV_VAR_LOG_FILTER;
+use crate::error::Error;
+use std::path::Path;
+use tracing_appender::rolling::RollingFileAppender;
+use tracing_appender::rolling::Rotation;
+use tracing_subscriber::fmt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer;
+use tracing_subscriber::Registry;
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Initializes the global logger, writing to stdout and, if given, additionally to the rotated `log_file`.
+/// `default_filter` is used if no filter has been configured.
+pub fn init(default_filter: &str, log_file: Option<&str>) -> Result<(), Error> {
+    let config = Config::new()?;
+
+    let filter_directive = config.log_filter.as_deref().unwrap_or(default_filter);
+    let filter = EnvFilter::try_new(filter_directive).map_err(|_| {
+        Error::ConfigReadInvalidEnvironmentVariable(ENV_VAR_LOG_FILTER, filter_directive.to_string())
+    })?;
+
+    let mut layers: Vec<BoxedLayer> = vec![layer(config.log_format, std::io::stdout, true)];
+    if let Some(log_file) = log_file {
+        let path = Path::new(log_file);
+        let rotation = match config.log_rotation {
+            LogRotation::Hourly => Rotation::HOURLY,
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Never => Rotation::NEVER,
+        };
+
+        let appender = RollingFileAppender::builder()
+            .rotation(rotation)
+            .filename_prefix(path.file_name().and_then(|x| x.to_str()).unwrap_or(log_file))
+            .max_log_files(config.log_retention)
+            .build(path.parent().unwrap_or_else(|| Path::new(".")))
+            .map_err(Error::LoggerFile)?;
+
+        layers.push(layer(config.log_format, appender, false));
+    }
+
+    tracing_subscriber::registry().with(layers).with(filter).try_init()?;
+    Ok(())
+}
+
+fn layer<W>(format: LogFormat, writer: W, ansi: bool) -> BoxedLayer
+where
+    W: for<'a> fmt::MakeWriter<'a> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => fmt::layer().with_writer(writer).with_ansi(ansi).boxed(),
+        LogFormat::Json => fmt::layer().json().with_writer(writer).boxed(),
+    }
+}
@@ -0,0 +1,141 @@
+//! Prometheus metrics shared by all modules of this crate and its dependents.
+//!
+//! Metrics are registered in the default registry, such that an exporter (see the `etherface` binary) only
+//! has to encode [`prometheus::gather`] to expose them.
+
+use lazy_static::lazy_static;
+use prometheus::register_histogram_vec;
+use prometheus::register_int_counter_vec;
+use prometheus::register_int_gauge;
+use prometheus::register_int_gauge_vec;
+use prometheus::HistogramVec;
+use prometheus::IntCounterVec;
+use prometheus::IntGauge;
+use prometheus::IntGaugeVec;
+
+lazy_static! {
+    /// Number of API requests sent, labeled by the requested host.
+    pub static ref API_REQUESTS: IntCounterVec =
+        register_int_counter_vec!("etherface_api_requests_total", "Number of API requests sent", &["host"])
+            .unwrap();
+
+    /// Duration in seconds until the response headers of an API request were received, labeled by the
+    /// requested host.
+    pub static ref API_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "etherface_api_request_duration_seconds",
+        "Duration until the response of an API request was received",
+        &["host"],
+        vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]
+    )
+    .unwrap();
+
+    /// Number of API responses, labeled by the requested host and status code (`error` if no response was
+    /// received, e.g. because of a timeout).
+    pub static ref API_RESPONSES: IntCounterVec = register_int_counter_vec!(
+        "etherface_api_responses_total",
+        "Number of API responses",
+        &["host", "status"]
+    )
+    .unwrap();
+
+    /// Number of retried API requests, labeled by the requested host and reason (`response` for unexpected
+    /// responses, `ratelimit`, `token` for GitHub token pool changes or `error` if no response was received).
+    pub static ref API_RETRIES: IntCounterVec = register_int_counter_vec!(
+        "etherface_api_retries_total",
+        "Number of retried API requests",
+        &["host", "reason"]
+    )
+    .unwrap();
+
+    /// Number of remaining GitHub API calls summed over all valid tokens, updated whenever the token manager
+    /// looks for a new active token.
+    pub static ref GITHUB_TOKEN_BUDGET: IntGauge = register_int_gauge!(
+        "etherface_github_token_budget",
+        "Remaining GitHub API calls of all tokens"
+    )
+    .unwrap();
+
+    /// Number of remaining GitHub API calls per token, labeled by the (masked) token and ratelimit resource
+    /// (`core` or `search`).
+    pub static ref GITHUB_TOKEN_REMAINING: IntGaugeVec = register_int_gauge_vec!(
+        "etherface_github_token_remaining",
+        "Remaining GitHub API calls per token",
+        &["token", "resource"]
+    )
+    .unwrap();
+
+    /// Number of GitHub API calls used within the current hourly budget window, labeled by consumer.
+    pub static ref GITHUB_BUDGET_USED: IntGaugeVec = register_int_gauge_vec!(
+        "etherface_github_budget_used",
+        "GitHub API calls used within the current budget window",
+        &["consumer"]
+    )
+    .unwrap();
+
+    /// Number of times a consumer had to wait for the shared GitHub API budget, labeled by consumer.
+    pub static ref GITHUB_BUDGET_CONTENTION: IntCounterVec = register_int_counter_vec!(
+        "etherface_github_budget_contention_total",
+        "Number of times a consumer had to wait for the shared GitHub budget",
+        &["consumer"]
+    )
+    .unwrap();
+
+    /// Number of inserted signature mappings, labeled by the source they were found at.
+    pub static ref SIGNATURES_INSERTED: IntCounterVec = register_int_counter_vec!(
+        "etherface_signatures_inserted_total",
+        "Number of inserted signature mappings",
+        &["source"]
+    )
+    .unwrap();
+
+    /// Number of source files read by the GitHub scraper, labeled by result (`decoded`, `decoded_lossily` if
+    /// invalid sequences were replaced, or the skipped `binary` and `unreadable` files).
+    pub static ref SCRAPED_FILES: IntCounterVec = register_int_counter_vec!(
+        "etherface_scraped_files_total",
+        "Number of source files read by the GitHub scraper",
+        &["result"]
+    )
+    .unwrap();
+
+    /// Number of REST API requests, labeled by route (e.g. `/v1/signatures/hash/{kind}/{input}/{page}`,
+    /// `unmatched` for unknown paths) and status code.
+    pub static ref REST_REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "etherface_rest_requests_total",
+        "Number of REST API requests",
+        &["route", "status"]
+    )
+    .unwrap();
+
+    /// Duration in seconds until a REST API request was answered, labeled by route.
+    pub static ref REST_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "etherface_rest_request_duration_seconds",
+        "Duration until a REST API request was answered",
+        &["route"],
+        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]
+    )
+    .unwrap();
+
+    /// Number of REST API response cache lookups, labeled by result (`hit` or `miss`).
+    pub static ref REST_CACHE_LOOKUPS: IntCounterVec = register_int_counter_vec!(
+        "etherface_rest_cache_lookups_total",
+        "Number of REST API response cache lookups",
+        &["result"]
+    )
+    .unwrap();
+
+    /// Number of connections of the REST APIs database pool, labeled by state (`in_use`, `idle` or `max`).
+    pub static ref REST_DATABASE_POOL_CONNECTIONS: IntGaugeVec = register_int_gauge_vec!(
+        "etherface_rest_database_pool_connections",
+        "Number of connections of the REST APIs database pool",
+        &["state"]
+    )
+    .unwrap();
+
+    /// Number of items waiting to be processed, labeled by queue.
+    pub static ref QUEUE_DEPTH: IntGaugeVec = register_int_gauge_vec!(
+        "etherface_queue_depth",
+        "Number of items waiting to be processed",
+        &["queue"]
+    )
+    .unwrap();
+}
@@ -3,6 +3,7 @@
 #![allow(clippy::extra_unused_lifetimes)] // Clippy complains about the Insertable proc-macro
 
 use crate::database::schema::*;
+use crate::parser::PARSER_VERSION;
 use chrono::DateTime;
 use chrono::Utc;
 use diesel::Insertable;
@@ -15,7 +16,7 @@ use sha3::Keccak256;
 use std::str::FromStr;
 
 #[derive(Queryable, Insertable)]
-#[table_name = "github_crawler_metadata"]
+#[diesel(table_name = github_crawler_metadata)]
 pub struct GithubCrawlerMetadata {
     pub id: i32,
     pub last_user_check: DateTime<Utc>,
@@ -46,8 +47,8 @@ impl GithubUser {
     }
 }
 
-#[derive(Queryable, Insertable)]
-#[table_name = "github_user"]
+#[derive(Queryable, Insertable, QueryableByName)]
+#[diesel(table_name = github_user)]
 pub struct GithubUserDatabase {
     pub id: i32,
     pub login: String,
@@ -73,11 +74,107 @@ pub struct GithubRepository {
     pub pushed_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 
+    pub description: Option<String>,
+
+    /// Topics the repository is tagged with, e.g. `defi` or `solidity`.
+    #[serde(default)]
+    pub topics: Vec<String>,
+    pub license: Option<GithubLicense>,
+
     pub owner: GithubUser,
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GithubLicense {
+    /// [SPDX](https://spdx.org/licenses/) identifier of the license, e.g. `MIT`, or `NOASSERTION` if GitHub
+    /// couldn't determine it.
+    pub spdx_id: Option<String>,
+}
+
+/// Public event of a GitHub user, see <https://docs.github.com/en/rest/activity/events>.
+#[derive(Deserialize, Debug)]
+pub struct GithubUserEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl GithubUserEvent {
+    /// Returns whether the event indicates repository activity, i.e. a push or a created or published
+    /// repository, branch or tag.
+    pub fn is_repository_activity(&self) -> bool {
+        matches!(self.kind.as_str(), "PushEvent" | "CreateEvent" | "PublicEvent")
+    }
+}
+
+/// Recursive file tree of a repository, see [`crate::api::github::handler::repositories::RepoHandler::tree`].
+#[derive(Deserialize, Debug)]
+pub struct GithubTree {
+    pub sha: String,
+    pub tree: Vec<GithubTreeEntry>,
+
+    /// Set if the tree exceeded GitHub's limit of 100,000 entries, in which case `tree` is incomplete.
+    pub truncated: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GithubTreeEntry {
+    pub path: String,
+
+    /// Either `blob` (file), `tree` (directory) or `commit` (submodule).
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// Size in bytes, not present for directories and submodules.
+    pub size: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GithubCommit {
+    pub sha: String,
+    pub html_url: String,
+    pub commit: GithubCommitDetails,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GithubCommitDetails {
+    pub message: String,
+    pub committer: Option<GithubCommitSignature>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GithubCommitSignature {
+    pub name: String,
+    pub date: DateTime<Utc>,
+}
+
+/// Comparison between two commits, see [`crate::api::github::handler::repositories::RepoHandler::compare`].
+#[derive(Deserialize, Debug)]
+pub struct GithubComparison {
+    /// Either `ahead`, `behind`, `diverged` or `identical`.
+    pub status: String,
+    pub ahead_by: i32,
+    pub behind_by: i32,
+    pub total_commits: i32,
+    pub commits: Vec<GithubCommit>,
+
+    #[serde(default)]
+    pub files: Vec<GithubChangedFile>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GithubChangedFile {
+    pub filename: String,
+
+    /// Either `added`, `removed`, `modified`, `renamed`, `copied`, `changed` or `unchanged`.
+    pub status: String,
+
+    /// Previous path of a renamed file.
+    pub previous_filename: Option<String>,
+}
+
 #[derive(Queryable, Insertable, Deserialize, Serialize, QueryableByName)]
-#[table_name = "github_repository"]
+#[diesel(table_name = github_repository)]
 pub struct GithubRepositoryDatabase {
     pub id: i32,
     pub owner_id: i32,
@@ -98,9 +195,24 @@ pub struct GithubRepositoryDatabase {
     pub solidity_ratio: Option<f32>,
     pub is_deleted: bool,
     pub found_by_crawling: bool,
+
+    #[serde(skip_serializing)]
+    pub parser_version: i32,
+
+    pub description: Option<String>,
+    pub topics: Vec<String>,
+
+    /// SPDX identifier of the repositories license, see [`GithubLicense`].
+    pub license: Option<String>,
 }
 
 impl GithubRepository {
+    /// Returns the SPDX identifier of the repositories license, if any.
+    pub fn license_spdx_id(&self) -> Option<String> {
+        self.license.as_ref().and_then(|x| x.spdx_id.clone())
+    }
+
+
     pub fn to_insertable(&self, solidity_ratio: Option<f32>, by_crawling: bool) -> GithubRepositoryDatabase {
         // XXX: This isn't ideal because there are multiple copy semantics but it doesn't make sense
         // to create a RepositoryDatabaseInsert<'a> struct because it's 1:1 the same as RepositoryDatabase
@@ -120,6 +232,11 @@ impl GithubRepository {
 
             solidity_ratio,
             found_by_crawling: by_crawling,
+            parser_version: PARSER_VERSION,
+
+            description: self.description.clone(),
+            topics: self.topics.clone(),
+            license: self.license_spdx_id(),
 
             // Both fields are initially None and will be updated once the crawler / scraper visited them
             visited_at: None,
@@ -139,10 +256,81 @@ pub struct EtherscanContract {
     pub url: String,
     pub scraped_at: Option<DateTime<Utc>>,
     pub added_at: DateTime<Utc>,
+
+    // Retry bookkeeping, only relevant for the scraper hence not part of any API response
+    #[serde(skip_serializing)]
+    pub retry_count: i32,
+    #[serde(skip_serializing)]
+    pub next_retry_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub last_error: Option<String>,
+    #[serde(skip_serializing)]
+    pub is_unverified: bool,
+
+    // Contract creation metadata, see [`EtherscanContractCreation`]
+    pub creator_address: Option<String>,
+    pub creation_tx_hash: Option<String>,
+    pub creation_block: Option<i64>,
+
+    /// Whether or not the contract has no code anymore, i.e. self-destructed.
+    pub is_destroyed: bool,
+    pub code_checked_at: Option<DateTime<Utc>>,
+
+    #[serde(skip_serializing)]
+    pub parser_version: i32,
+
+    /// [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain id of the network the contract is deployed on,
+    /// e.g. 1 for Ethereum mainnet. Contracts are unique by their chain id and address.
+    pub chain_id: i32,
+
+    // Compiler settings, see [`EtherscanCompilerSettings`]; `None` if they haven't been fetched (yet)
+    pub optimization_used: Option<bool>,
+    pub optimization_runs: Option<i32>,
+    pub evm_version: Option<String>,
+    pub license: Option<String>,
+}
+
+/// Contract creation metadata returned by Etherscan's `getcontractcreation` endpoint.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EtherscanContractCreation {
+    /// Address of the account which deployed the contract.
+    pub creator_address: String,
+
+    /// Hash of the transaction which deployed the contract.
+    pub tx_hash: String,
+
+    /// Block number the contract was deployed in; not returned by every Etherscan-family explorer.
+    pub block: Option<i64>,
+}
+
+/// Compiler settings of a verified contract returned by Etherscan's `getsourcecode` endpoint.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EtherscanCompilerSettings {
+    /// Whether or not the optimizer was enabled.
+    pub optimization_used: bool,
+
+    /// Number of optimizer runs, i.e. how often the deployed code is expected to be executed.
+    pub optimization_runs: i32,
+
+    /// Target EVM version, e.g. `london`, or `Default` if the compilers default was used.
+    pub evm_version: String,
+
+    /// License of the source code as declared on Etherscan, e.g. `MIT` or `None`.
+    pub license: String,
+}
+
+/// Verified source code of a contract returned by Etherscan's `getsourcecode` endpoint.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EtherscanSourceCode {
+    /// Raw source code as returned by Etherscan, either plain Solidity or a JSON object of multiple files.
+    pub content: String,
+
+    /// Compiler settings the source code was verified with.
+    pub settings: EtherscanCompilerSettings,
 }
 
 #[derive(Debug, Insertable)]
-#[table_name = "etherscan_contract"]
+#[diesel(table_name = etherscan_contract)]
 pub struct EtherscanContractInsert<'a> {
     pub address: &'a str,
     pub name: &'a str,
@@ -150,6 +338,7 @@ pub struct EtherscanContractInsert<'a> {
     pub compiler_version: &'a str,
     pub url: &'a str,
     pub added_at: &'a DateTime<Utc>,
+    pub chain_id: i32,
 }
 
 impl EtherscanContract {
@@ -161,26 +350,511 @@ impl EtherscanContract {
             compiler_version: &self.compiler_version,
             url: &self.url,
             added_at: &self.added_at,
+            chain_id: self.chain_id,
+        }
+    }
+}
+
+#[derive(Queryable)]
+pub struct EtherscanPayload {
+    pub id: i32,
+
+    /// Keccak256 hash of the uncompressed content.
+    pub hash: String,
+    pub kind: PayloadKind,
+
+    /// Zlib compressed content.
+    pub content: Vec<u8>,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = etherscan_payload)]
+pub struct EtherscanPayloadInsert<'a> {
+    pub hash: &'a str,
+    pub kind: PayloadKind,
+    pub content: &'a [u8],
+    pub added_at: DateTime<Utc>,
+}
+
+/// Function selector or event topic extracted from the bytecode of a contract without verified source code,
+/// see [`SignatureHash`].
+#[derive(Queryable, Insertable, Serialize, Debug, PartialEq, Eq)]
+#[diesel(table_name = etherscan_bytecode_hash)]
+pub struct EtherscanBytecodeHash {
+    pub contract_id: i32,
+
+    /// Hex encoded selector (functions) or topic (events), see [`SignatureHash::hash`].
+    pub hash: String,
+    pub kind: SignatureKind,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = mapping_payload_etherscan)]
+pub struct MappingPayloadEtherscan {
+    pub payload_id: i32,
+    pub contract_id: i32,
+}
+
+#[derive(Queryable, Insertable, Serialize, Debug)]
+#[diesel(table_name = worker_status)]
+pub struct WorkerStatus {
+    /// Name of the fetcher / scraper, e.g. `github-fetcher`.
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+
+    /// Date of the last loop iteration, i.e. the last sign of life.
+    pub last_loop_at: DateTime<Utc>,
+
+    /// Item currently being processed, e.g. a repository URL or contract address.
+    pub current_item: Option<String>,
+
+    /// Number of items processed since the worker was started.
+    pub items_processed: i64,
+}
+
+/// API key of a registered REST API consumer, see `handler::api_key`.
+#[derive(Queryable, Serialize, Debug, Clone)]
+pub struct ApiKey {
+    pub id: i32,
+
+    /// SHA3-256 hash of the key, the key itself isn't stored.
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+
+    /// Name of the consumer the key was issued to.
+    pub name: String,
+
+    /// Number of requests per minute the consumer is allowed to send.
+    pub rate_limit_per_minute: i32,
+
+    /// Number of requests the consumer is allowed to send at once.
+    pub rate_limit_burst: i32,
+
+    /// Number of requests sent with the key, flushed periodically by the REST API.
+    pub request_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+
+    /// Date the key was revoked, `None` if it's still valid.
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = api_key)]
+pub struct ApiKeyInsert<'a> {
+    pub key_hash: &'a str,
+    pub name: &'a str,
+    pub rate_limit_per_minute: i32,
+    pub rate_limit_burst: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Signature submitted by a registered REST API consumer, see `handler::signature_submission`.
+#[derive(Queryable, Serialize, Debug, Clone)]
+pub struct SignatureSubmission {
+    pub id: i32,
+
+    /// Key of the consumer who submitted the signature.
+    #[serde(skip_serializing)]
+    pub api_key_id: i32,
+
+    /// Canonical form of the submitted text, e.g. `transfer(address,uint256)`.
+    pub text: String,
+    pub hash: String,
+    pub kind: SignatureKind,
+    pub is_valid: bool,
+    pub status: SubmissionStatus,
+    pub submitted_at: DateTime<Utc>,
+    pub moderated_at: Option<DateTime<Utc>>,
+
+    /// Who approved or rejected the submission.
+    #[serde(skip_serializing)]
+    pub moderated_by: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = signature_submission)]
+pub struct SignatureSubmissionInsert<'a> {
+    pub api_key_id: i32,
+    pub text: &'a str,
+    pub hash: &'a str,
+    pub kind: SignatureKind,
+    pub is_valid: bool,
+    pub submitted_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = mapping_signature_submission)]
+pub struct MappingSignatureSubmission {
+    pub signature_id: i32,
+    pub submission_id: i32,
+    pub kind: SignatureKind,
+
+    /// Date the submission was approved.
+    pub added_at: DateTime<Utc>,
+}
+
+/// Complaint about a signature or one of its source mappings, see `handler::signature_flag`.
+#[derive(Queryable, Serialize, Debug, Clone)]
+pub struct SignatureFlag {
+    pub id: i32,
+    pub signature_id: i32,
+    pub kind: SignatureKind,
+
+    /// Source whose mappings are flagged, all sources if `None`.
+    pub source: Option<SignatureSource>,
+
+    /// Repository respectively contract whose mapping is flagged, set for GitHub and Etherscan flags only.
+    pub source_id: Option<i32>,
+
+    pub reason: FlagReason,
+
+    /// Comment of the first flag, repeated flags are merged into it.
+    #[serde(skip_serializing)]
+    pub comment: Option<String>,
+
+    /// Key of the consumer who flagged first, if any.
+    #[serde(skip_serializing)]
+    pub api_key_id: Option<i32>,
+
+    /// Number of times the target was flagged for the same reason while pending.
+    pub flag_count: i32,
+
+    pub status: FlagStatus,
+    pub flagged_at: DateTime<Utc>,
+    pub moderated_at: Option<DateTime<Utc>>,
+
+    /// Who resolved or dismissed the flag.
+    #[serde(skip_serializing)]
+    pub moderated_by: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = signature_flag)]
+pub struct SignatureFlagInsert<'a> {
+    pub signature_id: i32,
+    pub kind: SignatureKind,
+    pub source: Option<SignatureSource>,
+    pub source_id: Option<i32>,
+    pub reason: FlagReason,
+    pub comment: Option<&'a str>,
+    pub api_key_id: Option<i32>,
+    pub flagged_at: DateTime<Utc>,
+}
+
+/// Selector or event topic watched by a registered REST API consumer, see `handler::watchlist_entry`.
+#[derive(Queryable, Serialize, Debug, Clone)]
+pub struct WatchlistEntry {
+    pub id: i32,
+
+    /// Key of the consumer watching the hash.
+    #[serde(skip_serializing)]
+    pub api_key_id: i32,
+
+    /// Function / error selector (8 characters) or event topic (64 characters), e.g. `a9059cbb`.
+    pub hash: String,
+
+    /// URL the resolved entry is POSTed to, if any.
+    pub webhook_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+
+    /// Date the first signature matching the hash was found.
+    pub resolved_at: Option<DateTime<Utc>>,
+
+    /// Signature the entry was resolved by, `None` if unresolved.
+    pub signature_id: Option<i32>,
+    pub text: Option<String>,
+    pub kind: Option<SignatureKind>,
+
+    /// Date the webhook was called successfully.
+    pub notified_at: Option<DateTime<Utc>>,
+    pub notify_attempts: i32,
+
+    /// Date of the next webhook attempt.
+    #[serde(skip_serializing)]
+    pub next_notify_at: Option<DateTime<Utc>>,
+
+    /// Why the last webhook attempt failed, e.g. a non-2xx status code.
+    pub last_notify_error: Option<String>,
+
+    /// Key webhook calls are signed with, set if `webhook_url` is.
+    #[serde(skip_serializing)]
+    pub webhook_secret: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = watchlist_entry)]
+pub struct WatchlistEntryInsert<'a> {
+    pub api_key_id: i32,
+    pub hash: &'a str,
+    pub webhook_url: Option<&'a str>,
+    pub webhook_secret: Option<&'a str>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Webhook newly found signatures are delivered to, registered by a REST API consumer, see
+/// `handler::webhook`.
+#[derive(Queryable, Serialize, Debug, Clone)]
+pub struct Webhook {
+    pub id: i32,
+
+    /// Key of the consumer who registered the webhook.
+    #[serde(skip_serializing)]
+    pub api_key_id: i32,
+    pub url: String,
+
+    /// Key deliveries are signed with, only returned on registration.
+    #[serde(skip_serializing)]
+    pub secret: String,
+
+    /// Only signatures of this kind are delivered, all if `None`.
+    pub kind: Option<SignatureKind>,
+
+    /// Only signatures whose text starts with this prefix are delivered, e.g. `transfer`.
+    pub prefix: Option<String>,
+
+    /// Only signatures found within this source are delivered, all if `None`.
+    pub source: Option<SignatureSource>,
+    pub created_at: DateTime<Utc>,
+
+    /// Date of the last successful delivery.
+    pub delivered_at: Option<DateTime<Utc>>,
+
+    /// Number of failed deliveries since the last successful one.
+    pub failure_count: i32,
+
+    /// Date of the next delivery attempt.
+    #[serde(skip_serializing)]
+    pub next_delivery_at: DateTime<Utc>,
+
+    /// Why the last delivery failed, e.g. a non-2xx status code.
+    pub last_error: Option<String>,
+
+    /// Date the webhook was disabled after failing too often, `None` if enabled.
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = webhook)]
+pub struct WebhookInsert<'a> {
+    pub api_key_id: i32,
+    pub url: &'a str,
+    pub secret: &'a str,
+    pub kind: Option<SignatureKind>,
+    pub prefix: Option<&'a str>,
+    pub source: Option<SignatureSource>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Signature pending to be delivered to a webhook, see
+/// [`crate::database::handler::webhook::WebhookHandler::get_pending_deliveries`].
+#[derive(Debug, QueryableByName)]
+pub struct WebhookDelivery {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub id: i64,
+
+    #[diesel(sql_type = diesel::sql_types::Int4)]
+    pub signature_id: i32,
+
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub text: String,
+
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    pub hash: String,
+
+    #[diesel(sql_type = Signature_kind)]
+    pub kind: SignatureKind,
+
+    /// Whether the signature was found within the webhooks source, always `true` if it has no source filter.
+    #[diesel(sql_type = diesel::sql_types::Bool)]
+    pub matches_source: bool,
+}
+
+impl WebhookDelivery {
+    pub fn to_streamed(&self) -> StreamedSignature {
+        StreamedSignature {
+            id: self.signature_id,
+            text: self.text.clone(),
+            hash: self.hash.clone(),
+            kind: self.kind,
         }
     }
 }
 
+/// Administrative mutation, see [`AuditAction`].
 #[derive(Queryable, Serialize, Debug)]
+pub struct AuditLog {
+    pub id: i32,
+
+    /// Who performed the mutation, e.g. the admin token name or the local user running a CLI command.
+    pub actor: String,
+    pub action: AuditAction,
+
+    /// Entity the mutation was applied to, e.g. a signature hash or repository id.
+    pub target: Option<String>,
+
+    /// Free-form details, e.g. a moderation reason.
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct AuditLogInsert<'a> {
+    pub actor: &'a str,
+    pub action: AuditAction,
+    pub target: Option<&'a str>,
+    pub details: Option<&'a str>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Insertable, Serialize, Debug)]
+#[diesel(table_name = materialized_view_refresh)]
+pub struct MaterializedViewRefresh {
+    /// Name of the materialized view, e.g. `view_signature_insert_rate`.
+    pub name: String,
+
+    /// Date of the last refresh, i.e. the date the views data is as recent as.
+    pub refreshed_at: DateTime<Utc>,
+
+    /// Duration of the last refresh in milliseconds.
+    pub duration_ms: i64,
+}
+
+/// A 4-byte selector shared by multiple valid function / error signatures, see the `view_selector_collision`
+/// materialized view.
+#[derive(Queryable, Serialize, Debug)]
+pub struct SelectorCollision {
+    /// Hex encoded selector, e.g. `a9059cbb`.
+    pub selector: String,
+
+    /// Number of signatures sharing the selector, always greater than one.
+    pub signature_count: i32,
+
+    /// Date the most recent of the signatures was added.
+    pub last_added_at: DateTime<Utc>,
+}
+
+#[derive(Queryable, Serialize, Debug, Clone)]
 pub struct Signature {
     pub id: i32,
     pub text: String,
     pub hash: String,
     pub is_valid: bool,
     pub added_at: DateTime<Utc>,
+
+    /// First 4 bytes of the [`Signature::hash`], i.e. the function / error selector.
+    #[serde(skip_serializing)]
+    pub selector: Vec<u8>,
+
+    /// Decoded [`Signature::hash`], i.e. the event topic.
+    #[serde(skip_serializing)]
+    pub topic0: Vec<u8>,
+
+    /// Number of on-chain calls (or emitted logs for events) of the signature, see [`SignatureUsage`].
+    pub usage_count: i64,
+
+    /// Date of the most recent on-chain call, `None` if unknown.
+    pub last_called_at: Option<DateTime<Utc>>,
+}
+
+/// A newly found signature, see [`crate::database::listener::SignatureListener`].
+#[derive(Serialize, Debug, Clone)]
+pub struct StreamedSignature {
+    pub id: i32,
+    pub text: String,
+    pub hash: String,
+
+    /// Kind the signature was found as, i.e. the same signature is streamed once per kind.
+    pub kind: SignatureKind,
+}
+
+/// Signature alongside everything known about it, see
+/// [`crate::database::handler::rest::RestHandler::signature_detail`].
+#[derive(Serialize, Debug)]
+pub struct SignatureDetail {
+    #[serde(flatten)]
+    pub signature: Signature,
+
+    /// All kinds the signature was found as.
+    pub kinds: Vec<SignatureKind>,
+    pub sources: SignatureSourceCounts,
+
+    /// Date the signature was first found in any source, `None` if it isn't backed by any source (anymore).
+    pub first_seen_at: Option<DateTime<Utc>>,
+
+    /// Date the signature was last seen in any source.
+    pub last_seen_at: Option<DateTime<Utc>>,
+
+    /// Interface standards defining the signature, e.g. `ERC-20`; see [`crate::standard`].
+    pub standards: Vec<&'static str>,
+}
+
+/// Etherscan contract alongside all signatures found within its ABI, see
+/// [`crate::database::handler::rest::RestHandler::contract_detail`].
+#[derive(Serialize, Debug)]
+pub struct ContractDetail {
+    #[serde(flatten)]
+    pub contract: EtherscanContract,
+    pub signatures: Vec<ContractSignature>,
+
+    /// Selectors and topics found within the bytecode of unverified contracts, empty for verified ones.
+    pub bytecode_hashes: Vec<ContractBytecodeHash>,
+}
+
+/// Selector or topic found within the bytecode of an unverified contract, see [`EtherscanBytecodeHash`].
+#[derive(Serialize, Queryable, Debug, PartialEq, Eq)]
+pub struct ContractBytecodeHash {
+    pub hash: String,
+    pub kind: SignatureKind,
+}
+
+/// Signature found within a contracts ABI as the given kind; signatures found as multiple kinds (e.g. an
+/// error and a function sharing their text) are listed once per kind.
+#[derive(Serialize, Debug)]
+pub struct ContractSignature {
+    #[serde(flatten)]
+    pub signature: Signature,
+    pub kind: SignatureKind,
+}
+
+/// Number of places a signature was found at, by source.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct SignatureSourceCounts {
+    /// Number of GitHub repositories.
+    pub github: i64,
+
+    /// Number of Etherscan contracts.
+    pub etherscan: i64,
+
+    /// Number of 4Byte entries, i.e. one per kind the signature was submitted as.
+    pub fourbyte: i64,
 }
 
 #[derive(Insertable)]
-#[table_name = "signature"]
+#[diesel(table_name = signature)]
 pub struct SignatureInsert<'a> {
     pub text: &'a str,
     pub hash: &'a str,
     pub is_valid: bool,
     pub added_at: DateTime<Utc>,
+    pub selector: Vec<u8>,
+    pub topic0: Vec<u8>,
+}
+
+/// On-chain usage of a selector (or event topic), imported from an external counts file.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SignatureUsage {
+    /// Decoded selector (4 bytes) or event topic (32 bytes).
+    pub hash: Vec<u8>,
+
+    /// Number of on-chain calls or emitted logs.
+    pub usage_count: i64,
+
+    /// Date of the most recent call or log, if known.
+    pub last_called_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Hash)]
@@ -198,39 +872,105 @@ pub struct SignatureWithMetadata {
     pub is_valid: bool,
 }
 
+/// Signature hash whose text representation is unknown, e.g. because it was extracted from bytecode.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct SignatureHash {
+    /// Hex encoded hash without a `0x` prefix; only the 4-byte selector (8 characters) for functions and the
+    /// full Keccak256 hash (64 characters) for events. As such it can be joined with known signatures by
+    /// checking whether their [`Signature::hash`] starts with it.
+    pub hash: String,
+
+    /// The signatures kind, either [`SignatureKind::Function`] or [`SignatureKind::Event`].
+    pub kind: SignatureKind,
+}
+
 #[derive(Queryable, Insertable)]
-#[table_name = "mapping_signature_github"]
+#[diesel(table_name = mapping_signature_github)]
 pub struct MappingSignatureGithub {
     pub signature_id: i32,
     pub repository_id: i32,
     pub kind: SignatureKind,
+
+    /// Date the signature was first found in the source.
     pub added_at: DateTime<Utc>,
+
+    /// Date the signature was last found in the source, updated whenever it's re-encountered.
+    pub last_seen_at: DateTime<Utc>,
 }
 
 #[derive(Queryable, Insertable)]
-#[table_name = "mapping_signature_etherscan"]
+#[diesel(table_name = mapping_signature_etherscan)]
 pub struct MappingSignatureEtherscan {
     pub signature_id: i32,
     pub contract_id: i32,
     pub kind: SignatureKind,
+
+    /// Date the signature was first found in the source.
     pub added_at: DateTime<Utc>,
+
+    /// Date the signature was last found in the source, updated whenever it's re-encountered.
+    pub last_seen_at: DateTime<Utc>,
 }
 
 #[derive(Queryable, Insertable)]
-#[table_name = "mapping_signature_fourbyte"]
+#[diesel(table_name = mapping_signature_fourbyte)]
 pub struct MappingSignatureFourbyte {
     pub signature_id: i32,
     pub kind: SignatureKind,
+
+    /// Date the signature was first found in the source.
     pub added_at: DateTime<Utc>,
+
+    /// Date the signature was last found in the source, updated whenever it's re-encountered.
+    pub last_seen_at: DateTime<Utc>,
 }
 
 #[derive(Queryable, Insertable)]
-#[table_name = "mapping_signature_kind"]
+#[diesel(table_name = mapping_signature_kind)]
 pub struct MappingSignatureKind {
     pub signature_id: i32,
     pub kind: SignatureKind,
 }
 
+/// A GitHub user having starred a GitHub repository.
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = mapping_stargazer)]
+pub struct MappingStargazer {
+    pub user_id: i32,
+    pub repository_id: i32,
+
+    /// Date the star was first found while crawling.
+    pub added_at: DateTime<Utc>,
+}
+
+/// A repository sharing stargazers with another repository, see
+/// [`crate::database::handler::mapping_stargazer::MappingStargazerHandler::get_co_starred_repositories`].
+#[derive(Debug, QueryableByName)]
+pub struct CoStarredRepository {
+    #[diesel(sql_type = diesel::sql_types::Int4)]
+    pub repository_id: i32,
+
+    /// Number of users having starred both repositories.
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub shared_stargazers: i64,
+}
+
+/// Number of kinds of one [`SignatureKind`] on which `mapping_signature_kind` and the source mapping tables
+/// disagree, see [`crate::database::handler::signature::SignatureHandler::get_kind_inconsistencies`].
+#[derive(Debug, QueryableByName)]
+pub struct KindInconsistency {
+    #[diesel(sql_type = Signature_kind)]
+    pub kind: SignatureKind,
+
+    /// Kinds recorded in a source mapping table but missing in `mapping_signature_kind`.
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub missing: i64,
+
+    /// Kinds recorded in `mapping_signature_kind` but in none of the signatures source mappings.
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    pub orphaned: i64,
+}
+
 impl SignatureWithMetadata {
     pub fn new(text: String, kind: SignatureKind, is_valid: bool) -> Self {
         let hash = format!("{:x}", Keccak256::digest(&text));
@@ -244,15 +984,28 @@ impl SignatureWithMetadata {
     }
 
     pub fn to_insertable(&self) -> SignatureInsert {
+        let topic0 = decode_hash(&self.hash).unwrap_or_default();
+
         SignatureInsert {
             text: &self.text,
             hash: &self.hash,
             is_valid: self.is_valid,
             added_at: Utc::now(),
+            selector: topic0.iter().take(4).copied().collect(),
+            topic0,
         }
     }
 }
 
+/// Decodes a hex encoded hash (without a `0x` prefix), returning `None` if it isn't valid hex.
+pub fn decode_hash(hash: &str) -> Option<Vec<u8>> {
+    if hash.len() % 2 != 0 || !hash.bytes().all(|x| x.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..hash.len()).step_by(2).map(|idx| u8::from_str_radix(&hash[idx..idx + 2], 16).ok()).collect()
+}
+
 #[derive(Serialize, Deserialize, DbEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 #[DieselType = "Signature_kind"]
@@ -265,6 +1018,78 @@ pub enum SignatureKind {
     Receive,
 }
 
+/// Source a signature was found in, i.e. one of the `mapping_signature_*` tables.
+#[derive(Serialize, Deserialize, DbEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[DieselType = "Signature_source"]
+pub enum SignatureSource {
+    Github,
+    Etherscan,
+    Fourbyte,
+}
+
+/// Kind of administrative mutation recorded in the `audit_log` table.
+#[derive(Serialize, Deserialize, DbEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[DieselType = "Audit_action"]
+pub enum AuditAction {
+    DenylistAdd,
+    DenylistRemove,
+    Requeue,
+    Moderation,
+    TokenChange,
+    ApiKeyIssue,
+    ApiKeyRevoke,
+}
+
+/// Moderation state of a [`SignatureSubmission`].
+#[derive(Serialize, Deserialize, DbEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[DieselType = "Submission_status"]
+pub enum SubmissionStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+/// Why a [`SignatureFlag`] was raised.
+#[derive(Serialize, Deserialize, DbEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[DieselType = "Flag_reason"]
+pub enum FlagReason {
+    /// The signature doesn't belong to the source or isn't of the given kind.
+    Wrong,
+
+    /// The signature was made up, e.g. to collide with a popular selector.
+    Spam,
+}
+
+/// Moderation state of a [`SignatureFlag`].
+#[derive(Serialize, Deserialize, DbEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[DieselType = "Flag_status"]
+pub enum FlagStatus {
+    Pending,
+
+    /// The flagged signature was marked as invalid, keeping its mappings.
+    Invalidated,
+
+    /// The flagged mappings were removed and the signature marked as invalid.
+    Removed,
+
+    /// The flag was found to be unfounded.
+    Dismissed,
+}
+
+/// Kind of raw payload fetched from Etherscan.
+#[derive(Serialize, Deserialize, DbEnum, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[DieselType = "Payload_kind"]
+pub enum PayloadKind {
+    Abi,
+    Source,
+}
+
 impl FromStr for SignatureKind {
     type Err = ();
 
@@ -289,6 +1114,7 @@ pub mod views {
     use chrono::NaiveDate;
     use diesel::sql_types::BigInt;
     use diesel::sql_types::Date;
+    use diesel::sql_types::Int4;
     use diesel::sql_types::Text;
     use diesel::sql_types::Nullable;
     use diesel::Queryable;
@@ -297,49 +1123,155 @@ pub mod views {
 
     #[derive(Queryable, QueryableByName, Serialize)]
     pub struct ViewSignatureInsertRate {
-        #[sql_type = "Date"]
+        #[diesel(sql_type = Date)]
         date: NaiveDate,
 
-        #[sql_type = "BigInt"]
+        #[diesel(sql_type = BigInt)]
         count: i64,
     }
 
     #[derive(Queryable, QueryableByName, Serialize)]
     pub struct ViewSignaturesPopularOnGithub {
-        #[sql_type = "Text"]
+        #[diesel(sql_type = Text)]
         text: String,
 
-        #[sql_type = "BigInt"]
+        #[diesel(sql_type = BigInt)]
         count: i64,
     }
 
     #[derive(Queryable, QueryableByName, Serialize)]
     pub struct ViewSignatureCountStatistics {
-        #[sql_type = "BigInt"]
+        #[diesel(sql_type = BigInt)]
         signature_count: i64,
 
-        #[sql_type = "BigInt"]
+        #[diesel(sql_type = BigInt)]
         signature_count_github: i64,
 
-        #[sql_type = "BigInt"]
+        #[diesel(sql_type = BigInt)]
         signature_count_etherscan: i64,
 
-        #[sql_type = "BigInt"]
+        #[diesel(sql_type = BigInt)]
         signature_count_fourbyte: i64,
 
-        #[sql_type = "BigInt"]
+        #[diesel(sql_type = BigInt)]
         average_daily_signature_insert_rate_last_week: i64,
 
-        #[sql_type = "Nullable<BigInt>"]
+        #[diesel(sql_type = Nullable<BigInt>)]
         average_daily_signature_insert_rate_week_before_last: Option<i64>, // This can be NULL in the first week
     }
 
     #[derive(Queryable, QueryableByName, Serialize)]
     pub struct ViewSignatureKindDistribution {
-        #[sql_type = "Text"]
+        #[diesel(sql_type = Text)]
         kind: String,
 
-        #[sql_type = "BigInt"]
+        #[diesel(sql_type = BigInt)]
+        count: i64,
+    }
+
+    #[derive(Queryable, QueryableByName, Serialize)]
+    pub struct ViewSignatureInsertRateBySource {
+        #[diesel(sql_type = Date)]
+        date: NaiveDate,
+
+        #[diesel(sql_type = Text)]
+        source: String, // Either `github`, `etherscan` or `fourbyte`
+
+        #[diesel(sql_type = BigInt)]
+        count: i64,
+    }
+
+    #[derive(Queryable, QueryableByName, Serialize)]
+    pub struct ViewRepositoriesTopUniqueSignatures {
+        #[diesel(sql_type = Int4)]
+        repository_id: i32,
+
+        #[diesel(sql_type = Text)]
+        name: String,
+
+        #[diesel(sql_type = Text)]
+        html_url: String,
+
+        #[diesel(sql_type = BigInt)]
+        count: i64, // Number of valid signatures found in no other repository
+    }
+
+    #[derive(Queryable, QueryableByName, Serialize)]
+    pub struct ViewSignaturesPerContractDistribution {
+        #[diesel(sql_type = Int4)]
+        bucket: i32, // Lower bound of the bucket, e.g. 10 for contracts with 10 to 19 signatures
+
+        #[diesel(sql_type = BigInt)]
         count: i64,
     }
+
+    #[derive(Queryable, QueryableByName, Serialize)]
+    pub struct ViewEtherscanCompilerVersionDistribution {
+        #[diesel(sql_type = Text)]
+        compiler: String,
+
+        #[diesel(sql_type = Text)]
+        version: String, // Without its commit hash, e.g. `0.8.17`
+
+        #[diesel(sql_type = BigInt)]
+        count: i64,
+    }
+
+    #[derive(Queryable, QueryableByName, Serialize)]
+    pub struct ViewEtherscanChainDistribution {
+        #[diesel(sql_type = Int4)]
+        chain_id: i32,
+
+        #[diesel(sql_type = BigInt)]
+        contract_count: i64,
+
+        #[diesel(sql_type = BigInt)]
+        signature_count: i64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::decode_hash;
+    use crate::model::GithubRepository;
+    use crate::model::SignatureKind;
+    use crate::model::SignatureWithMetadata;
+
+    #[test]
+    fn decode_hash_of_signature() {
+        let signature =
+            SignatureWithMetadata::new("transfer(address,uint256)".to_string(), SignatureKind::Function, true);
+        let insertable = signature.to_insertable();
+
+        assert_eq!(insertable.selector, vec![0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(insertable.topic0.len(), 32);
+        assert_eq!(decode_hash("a9059cbb"), Some(vec![0xa9, 0x05, 0x9c, 0xbb]));
+    }
+
+    #[test]
+    fn decode_hash_invalid() {
+        assert_eq!(decode_hash("a9059cb"), None);
+        assert_eq!(decode_hash("+9059cbb"), None);
+        assert_eq!(decode_hash("zz059cbb"), None);
+    }
+
+    #[test]
+    fn github_repository_metadata() {
+        let repo: GithubRepository = serde_json::from_str(
+            r#"{
+                "id": 1, "name": "a", "html_url": "https://github.com/volsa/a", "language": "Solidity",
+                "stargazers_count": 0, "size": 0, "fork": false, "created_at": "2022-01-01T00:00:00Z",
+                "pushed_at": "2022-01-01T00:00:00Z", "updated_at": "2022-01-01T00:00:00Z",
+                "description": "DeFi protocol", "topics": ["defi", "solidity"],
+                "license": { "key": "mit", "name": "MIT License", "spdx_id": "MIT" },
+                "owner": { "id": 1, "login": "volsa", "html_url": "https://github.com/volsa" }
+            }"#,
+        )
+        .unwrap();
+
+        let insertable = repo.to_insertable(None, false);
+        assert_eq!(insertable.description.as_deref(), Some("DeFi protocol"));
+        assert_eq!(insertable.topics, vec!["defi", "solidity"]);
+        assert_eq!(insertable.license.as_deref(), Some("MIT"));
+    }
 }
@@ -19,14 +19,31 @@
 //! 
 //! For ABI (= JSON) files the parser simply uses serde to deserialize the content and assemble all extracted
 //! data to form the canonical signature.
+//!
+//! For (unverified) bytecode neither names nor parameters are available, instead the parser disassembles the
+//! bytecode extracting function selectors from the dispatcher as well as event topics, returning them as
+//! [`SignatureHash`]es which can then be joined with known signatures.
+
+mod bytecode;
 
 use crate::error::Error;
+use crate::model::SignatureHash;
 use crate::model::SignatureKind;
 use crate::model::SignatureWithMetadata;
 use lazy_static::lazy_static;
 use regex::Regex;
 use regex::RegexBuilder;
 use serde::Deserialize;
+use std::borrow::Cow;
+
+/// Version of the parser, which has to be incremented whenever the parser is changed in a way that existing
+/// sources would yield different (e.g. additional) signatures. Sources scraped with an older version can then
+/// be re-parsed, see the `reparse` maintenance job, whereas already stored signatures can be re-evaluated via
+/// the `revalidate` maintenance job.
+pub const PARSER_VERSION: i32 = 3;
+
+/// Number of leading bytes inspected to tell UTF-16 without a byte order mark apart from binary content.
+const DECODE_SNIFF_LENGTH: usize = 1024;
 
 #[derive(Deserialize)]
 struct Abi {
@@ -41,6 +58,24 @@ struct Abi {
 struct AbiParameter {
     #[serde(rename = "type")]
     type_: String,
+
+    /// Members of a `tuple` (i.e. struct) type.
+    components: Option<Vec<AbiParameter>>,
+}
+
+impl AbiParameter {
+    /// Returns the canonical type of the parameter, resolving `tuple` types such as `tuple[]` into their
+    /// members, e.g. `(address,uint256)[]`.
+    fn canonical_type(&self) -> String {
+        match (self.type_.strip_prefix("tuple"), &self.components) {
+            (Some(array), Some(components)) => format!(
+                "({}){array}",
+                components.iter().map(AbiParameter::canonical_type).collect::<Vec<String>>().join(",")
+            ),
+
+            _ => self.type_.clone(),
+        }
+    }
 }
 
 lazy_static! {
@@ -90,7 +125,7 @@ lazy_static! {
 
     static ref REGEX_PARAMETER_TYPES: Regex = Regex::new(
         r"(?x)
-            (   
+            ^(
                 (
                     address|
                     bool|
@@ -98,12 +133,14 @@ lazy_static! {
                     bytes(\d{0,3})?|
                     int(\d{0,3})?|
                     uint(\d{0,3})?|
-                    fixed|
-                    ufixed
+                    fixed(\d+x\d+)?|
+                    ufixed(\d+x\d+)?
                 )
-            (\[\d*\])*)                 # (optional) Array declaration (0 - * times)
+            (\[\d*\])*)$                # (optional) Array declaration (0 - * times)
         ").unwrap();
 
+    static ref REGEX_ARRAY_DECLARATION: Regex = Regex::new(r"^(\[\d*\])*$").unwrap();
+
     // The `REGEX_SIGNATURE` pattern only recognizes signatures defined within a line, as such multi-line
     // signatures won't be detected by default. To bypass this we have to remove all newlines[0] as well a
     // code-comments[1] before actually starting to extract signatures from an arbitrary Solidity file.
@@ -133,6 +170,61 @@ lazy_static! {
         ").multi_line(true).build().unwrap();
 }
 
+/// Decodes the raw content of a source file, returning its text alongside whether it was decoded lossily
+/// (i.e. invalid sequences were replaced by `U+FFFD`) or `None` if it's binary. Besides UTF-8, UTF-16 is
+/// detected both with and without byte order mark. Byte order marks are stripped, as neither the Solidity
+/// patterns nor serde expect them.
+pub fn decode(content: &[u8]) -> Option<(String, bool)> {
+    if let Some(content) = content.strip_prefix(b"\xEF\xBB\xBF") {
+        return Some(decode_utf8(content));
+    }
+
+    if let Some(content) = content.strip_prefix(b"\xFF\xFE") {
+        return Some(decode_utf16(content, u16::from_le_bytes));
+    }
+
+    if let Some(content) = content.strip_prefix(b"\xFE\xFF") {
+        return Some(decode_utf16(content, u16::from_be_bytes));
+    }
+
+    let sniffed = &content[..content.len().min(DECODE_SNIFF_LENGTH)];
+    if !sniffed.contains(&0) {
+        return Some(decode_utf8(content));
+    }
+
+    // ASCII encoded as UTF-16 has every other byte zeroed, anything else containing zeros is binary
+    let zeroed = |offset| sniffed.iter().skip(offset).step_by(2).all(|x| *x == 0);
+    match (zeroed(0), zeroed(1)) {
+        (false, true) => Some(decode_utf16(content, u16::from_le_bytes)),
+        (true, false) => Some(decode_utf16(content, u16::from_be_bytes)),
+        _ => None,
+    }
+}
+
+fn decode_utf8(content: &[u8]) -> (String, bool) {
+    match String::from_utf8_lossy(content) {
+        Cow::Borrowed(text) => (text.to_string(), false),
+        Cow::Owned(text) => (text, true),
+    }
+}
+
+fn decode_utf16(content: &[u8], from_bytes: fn([u8; 2]) -> u16) -> (String, bool) {
+    // A trailing odd byte can't be decoded
+    let chunks = content.chunks_exact(2);
+    let mut lossy = !chunks.remainder().is_empty();
+    let units = chunks.map(|x| from_bytes([x[0], x[1]]));
+    let text = char::decode_utf16(units)
+        .map(|x| {
+            x.unwrap_or_else(|_| {
+                lossy = true;
+                char::REPLACEMENT_CHARACTER
+            })
+        })
+        .collect();
+
+    (text, lossy)
+}
+
 /// Returns a list of [`SignatureWithMetadata`] extracted from a JSON ABI file.
 pub fn from_abi(content: &str) -> Result<Vec<SignatureWithMetadata>, Error> {
     let mut signatures = Vec::new();
@@ -158,8 +250,8 @@ pub fn from_abi(content: &str) -> Result<Vec<SignatureWithMetadata>, Error> {
                 // We sometimes (very rarely) have to deal with ABI entries with no parameter list hence we
                 // return an empty vector if the unwrap fails
                 .unwrap_or_else(|| Vec::with_capacity(0))
-                .into_iter()
-                .map(|x| x.type_)
+                .iter()
+                .map(AbiParameter::canonical_type)
                 .collect::<Vec<String>>()
                 .join(",")
         );
@@ -181,7 +273,10 @@ pub fn from_sol(content: &str) -> Vec<SignatureWithMetadata> {
         let kind: SignatureKind = capture.name("kind").unwrap().as_str().parse().unwrap();
 
         let (text, is_valid) = match get_split_parameter_list(capture.name("params").unwrap().as_str()) {
-            Some(list) => (format!("{name}({})", list.join(",")), parameter_types_are_valid(&list)),
+            Some(list) => {
+                let list = list.iter().map(|x| normalize_type(x)).collect::<Vec<String>>();
+                (format!("{name}({})", list.join(",")), parameter_types_are_valid(&list))
+            }
             None => (format!("{name}()"), true),
         };
 
@@ -194,11 +289,60 @@ pub fn from_sol(content: &str) -> Vec<SignatureWithMetadata> {
     signatures
 }
 
+/// Returns a list of [`SignatureHash`] extracted from hex encoded EVM bytecode, i.e. all function selectors
+/// found in the dispatcher as well as all (likely) event topics.
+pub fn from_bytecode(content: &str) -> Result<Vec<SignatureHash>, Error> {
+    let bytecode = bytecode::decode_hex(content).ok_or(Error::ParseBytecode)?;
+    let instructions = bytecode::disassemble(&bytecode);
+
+    let mut hashes = Vec::new();
+    for selector in bytecode::selectors(&instructions) {
+        hashes.push(SignatureHash {
+            hash: selector.iter().map(|x| format!("{x:02x}")).collect(),
+            kind: SignatureKind::Function,
+        });
+    }
+
+    for topic in bytecode::topics(&instructions) {
+        hashes.push(SignatureHash {
+            hash: topic.iter().map(|x| format!("{x:02x}")).collect(),
+            kind: SignatureKind::Event,
+        });
+    }
+
+    // Selectors may be compared multiple times within one dispatcher and topics emitted in multiple places
+    let mut seen = std::collections::HashSet::new();
+    hashes.retain(|x| seen.insert(x.hash.clone()));
+
+    Ok(hashes)
+}
+
+/// Returns the canonical form of an already extracted signature such as `transfer(address to, uint amount)`,
+/// i.e. `transfer(address,uint256)`, alongside whether or not its parameter types are valid. Returns `None`
+/// if the text isn't a signature at all. Used to re-evaluate signatures stored by older parser versions, see
+/// the `revalidate` maintenance job.
+pub fn canonicalize(text: &str) -> Option<(String, bool)> {
+    let (name, params) = text.trim().split_once('(')?;
+    let params = params.trim_end().strip_suffix(')')?;
+
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|x| x.is_ascii_alphanumeric() || x == '_' || x == '$') {
+        return None;
+    }
+
+    let params = match params.trim().is_empty() {
+        true => Vec::new(),
+        false => split_top_level(params).into_iter().map(normalize_type).collect::<Vec<String>>(),
+    };
+
+    Some((format!("{name}({})", params.join(",")), parameter_types_are_valid(&params)))
+}
+
 /// Checks whether or not the given parameter type is valid, i.e. not an user defined type (see 
 /// <https://blog.soliditylang.org/2021/09/27/user-defined-value-types/>).
 fn parameter_types_are_valid(params: &Vec<String>) -> bool {
     for param in params {
-        if !REGEX_PARAMETER_TYPES.is_match(param) {
+        if !parameter_type_is_valid(param) {
             if param.is_empty() {
                 continue;
             }
@@ -210,6 +354,86 @@ fn parameter_types_are_valid(params: &Vec<String>) -> bool {
     true
 }
 
+/// Same as [`parameter_types_are_valid`] but for a single (possibly tuple) parameter type.
+fn parameter_type_is_valid(param: &str) -> bool {
+    match split_tuple(param) {
+        Some((members, array)) => {
+            REGEX_ARRAY_DECLARATION.is_match(array)
+                && split_top_level(members).into_iter().all(|x| !x.is_empty() && parameter_type_is_valid(x))
+        }
+
+        None => REGEX_PARAMETER_TYPES.is_match(param),
+    }
+}
+
+/// Returns the canonical form of a parameter type, i.e. without a parameter name, data location or
+/// `payable` keyword and with aliases such as `uint` replaced by their canonical type (`uint256`), see
+/// <https://docs.soliditylang.org/en/latest/abi-spec.html#types>.
+fn normalize_type(param: &str) -> String {
+    let param = param.trim();
+
+    if let Some((members, array)) = split_tuple(param) {
+        let members = split_top_level(members).into_iter().map(normalize_type).collect::<Vec<String>>();
+        let array = match array.starts_with('[') {
+            true => array.split_whitespace().next().unwrap_or_default(),
+            false => "",
+        };
+
+        return format!("({}){array}", members.join(","));
+    }
+
+    let param = param.split_whitespace().next().unwrap_or_default();
+    let (base, array) = param.split_at(param.find('[').unwrap_or(param.len()));
+
+    let base = match base {
+        "uint" => "uint256",
+        "int" => "int256",
+        "byte" => "bytes1",
+        "fixed" => "fixed128x18",
+        "ufixed" => "ufixed128x18",
+        _ => base,
+    };
+
+    format!("{base}{array}")
+}
+
+/// Splits a tuple type such as `(address,uint256)[]` into its members (`address,uint256`) and trailing array
+/// declaration (`[]`), returning `None` if the type isn't a tuple.
+fn split_tuple(param: &str) -> Option<(&str, &str)> {
+    let param = param.strip_prefix('(')?;
+
+    let mut depth = 0;
+    for (idx, x) in param.char_indices() {
+        match x {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some((&param[..idx], &param[idx + 1..])),
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Splits a parameter list at all commas which aren't part of a tuple type.
+fn split_top_level(list: &str) -> Vec<&str> {
+    let (mut params, mut depth, mut start) = (Vec::new(), 0, 0);
+    for (idx, x) in list.char_indices() {
+        match x {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                params.push(&list[start..idx]);
+                start = idx + 1;
+            }
+            _ => (),
+        }
+    }
+
+    params.push(&list[start..]);
+    params
+}
+
 /// Converts and returns a parameter list such as `uint foo, uint bar` to a vector of `[uint, uint]`.
 fn get_split_parameter_list(raw_parameter_list: &str) -> Option<Vec<String>> {
     if raw_parameter_list.trim().is_empty() {
@@ -306,6 +530,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_detects_encodings() {
+        let utf16 = |text: &str, to_bytes: fn(u16) -> [u8; 2]| -> Vec<u8> {
+            text.encode_utf16().flat_map(to_bytes).collect()
+        };
+
+        assert_eq!(parser::decode(b"contract A {}"), Some(("contract A {}".into(), false)));
+        assert_eq!(parser::decode(b"\xEF\xBB\xBF[]"), Some(("[]".into(), false)));
+        assert_eq!(parser::decode(b"// \xE9t\xE9\n"), Some(("// \u{FFFD}t\u{FFFD}\n".into(), true)));
+
+        // UTF-16 both with and without byte order mark
+        let content = [b"\xFF\xFE".to_vec(), utf16("contract A {}", u16::to_le_bytes)].concat();
+        assert_eq!(parser::decode(&content), Some(("contract A {}".into(), false)));
+        let content = utf16("contract A {}", u16::to_be_bytes);
+        assert_eq!(parser::decode(&content), Some(("contract A {}".into(), false)));
+
+        assert_eq!(parser::decode(b"\x7FELF\x02\x01\x00\x00\x03\x00"), None);
+    }
+
     #[test]
     fn from_abi_all_files_without_panicing() {
         for file in std::fs::read_dir("../res/abi/").unwrap() {
@@ -474,6 +717,34 @@ mod tests {
         );
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn from_bytecode_dispatcher_and_topics() {
+        let bytecode = concat!(
+            "0x6080604052",                                                             // PUSH1 0x80 PUSH1 0x40 MSTORE
+            "80", "63a9059cbb", "14", "610045", "57",                                   // DUP1 PUSH4 transfer(address,uint256) EQ PUSH2 JUMPI
+            "80", "6370a08231", "14", "610050", "57",                                   // DUP1 PUSH4 balanceOf(address) EQ PUSH2 JUMPI
+            "63ffffffff", "16",                                                         // PUSH4 0xffffffff AND (no dispatcher entry)
+            "7fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",       // PUSH32 Transfer(address,address,uint256)
+            "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",       // PUSH32 bitmask (no topic)
+            "a3",                                                                       // LOG3
+        );
+
+        let hashes = parser::from_bytecode(bytecode).unwrap();
+        assert_eq!(hashes.len(), 3);
+
+        assert_eq!(hashes[0].hash, "a9059cbb");
+        assert_eq!(hashes[0].kind, SignatureKind::Function);
+
+        assert_eq!(hashes[1].hash, "70a08231");
+        assert_eq!(hashes[1].kind, SignatureKind::Function);
+
+        assert_eq!(hashes[2].hash, "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+        assert_eq!(hashes[2].kind, SignatureKind::Event);
+
+        assert!(parser::from_bytecode("0x6080zz").is_err());
+    }
+
     #[test]
     fn from_sol_custom_signatures() {
         let code = r#"
@@ -554,4 +825,45 @@ mod tests {
         assert_eq!(signatures[8].text, "doesntWorkButNowDoesBecauseItsFixedYay(address,uint256)");
         assert_eq!(signatures[8].kind, SignatureKind::Function);
     }
+
+    #[test]
+    fn from_sol_normalizes_type_aliases() {
+        let code = "function foo(uint a, int[] b, byte c, address payable d) external;";
+        let signatures = parser::from_sol(code);
+        assert_eq!(signatures[0].text, "foo(uint256,int256[],bytes1,address)");
+        assert!(signatures[0].is_valid);
+
+        // Previously considered valid because the type merely contained `int`
+        let signatures = parser::from_sol("function foo(Point memory a) external;");
+        assert_eq!(signatures[0].text, "foo(Point)");
+        assert!(!signatures[0].is_valid);
+    }
+
+    #[test]
+    fn from_abi_resolves_tuples() {
+        let abi = r#"[{
+            "type": "function",
+            "name": "execute",
+            "inputs": [
+                { "type": "tuple[]", "components": [{ "type": "address" }, { "type": "bytes" }] },
+                { "type": "uint256" }
+            ]
+        }]"#;
+
+        let signatures = parser::from_abi(abi).unwrap();
+        assert_eq!(signatures[0].text, "execute((address,bytes)[],uint256)");
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn canonicalize() {
+        assert_eq!(parser::canonicalize("foo()"), Some(("foo()".into(), true)));
+        assert_eq!(parser::canonicalize("foo(uint,address)"), Some(("foo(uint256,address)".into(), true)));
+        assert_eq!(parser::canonicalize("foo(uint a, bytes memory b)"), Some(("foo(uint256,bytes)".into(), true)));
+        assert_eq!(parser::canonicalize("foo((uint,fixed)[2],bool)"), Some(("foo((uint256,fixed128x18)[2],bool)".into(), true)));
+        assert_eq!(parser::canonicalize("foo(Point)"), Some(("foo(Point)".into(), false)));
+        assert_eq!(parser::canonicalize("foo((Point,uint256))"), Some(("foo((Point,uint256))".into(), false)));
+        assert_eq!(parser::canonicalize("foo(uint256"), None);
+        assert_eq!(parser::canonicalize("(uint256)"), None);
+    }
 }
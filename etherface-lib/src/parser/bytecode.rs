@@ -0,0 +1,140 @@
+//! Minimal EVM bytecode disassembler.
+//!
+//! Only understands as much of the instruction set as needed to extract signature hashes, i.e. it splits
+//! bytecode into instructions, correctly skipping the immediate values of `PUSH1` to `PUSH32` such that data
+//! isn't mistaken for opcodes.
+
+const OPCODE_EQ: u8 = 0x14;
+const OPCODE_JUMPI: u8 = 0x57;
+const OPCODE_PUSH1: u8 = 0x60;
+const OPCODE_PUSH4: u8 = 0x63;
+const OPCODE_PUSH32: u8 = 0x7f;
+const OPCODE_DUP1: u8 = 0x80;
+const OPCODE_SWAP16: u8 = 0x9f;
+const OPCODE_LOG1: u8 = 0xa1;
+const OPCODE_LOG4: u8 = 0xa4;
+
+/// Number of bytes within a `PUSH32` value that may be `0x00` or `0xff` before the value is considered to be
+/// a constant (e.g. a bitmask) rather than a Keccak256 hash.
+const MAX_TRIVIAL_BYTES: usize = 4;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Instruction<'a> {
+    pub opcode: u8,
+
+    /// Immediate value of `PUSH` instructions, empty for all other instructions.
+    pub immediate: &'a [u8],
+}
+
+/// Returns all instructions of the given bytecode; a truncated `PUSH` at the end of the bytecode yields a
+/// shorter immediate value instead of an error.
+pub(crate) fn disassemble(bytecode: &[u8]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+
+    let mut pc = 0;
+    while pc < bytecode.len() {
+        let opcode = bytecode[pc];
+        let immediate_len = match opcode {
+            OPCODE_PUSH1..=OPCODE_PUSH32 => (opcode - OPCODE_PUSH1 + 1) as usize,
+            _ => 0,
+        };
+
+        let immediate_end = (pc + 1 + immediate_len).min(bytecode.len());
+        instructions.push(Instruction {
+            opcode,
+            immediate: &bytecode[pc + 1..immediate_end],
+        });
+
+        pc = immediate_end;
+    }
+
+    instructions
+}
+
+/// Returns all function selectors found in the dispatcher of the given instructions.
+///
+/// Solidity (and Vyper) compile the function dispatcher into a sequence of comparisons against the first four
+/// bytes of the calldata, each looking like `PUSH4 <selector> (DUPn | SWAPn)* EQ PUSHn <destination> JUMPI`.
+pub(crate) fn selectors(instructions: &[Instruction]) -> Vec<[u8; 4]> {
+    let mut selectors = Vec::new();
+
+    for (idx, instruction) in instructions.iter().enumerate() {
+        if instruction.opcode != OPCODE_PUSH4 || instruction.immediate.len() != 4 {
+            continue;
+        }
+
+        let mut rest = instructions[idx + 1..]
+            .iter()
+            .skip_while(|x| (OPCODE_DUP1..=OPCODE_SWAP16).contains(&x.opcode))
+            .map(|x| x.opcode);
+
+        let is_dispatcher_entry = rest.next() == Some(OPCODE_EQ)
+            && matches!(rest.next(), Some(OPCODE_PUSH1..=OPCODE_PUSH32))
+            && rest.next() == Some(OPCODE_JUMPI);
+
+        if is_dispatcher_entry {
+            selectors.push(instruction.immediate.try_into().unwrap());
+        }
+    }
+
+    selectors
+}
+
+/// Returns all `PUSH32` values which are likely event topics, i.e. values that look like a Keccak256 hash
+/// within bytecode that emits events at all.
+pub(crate) fn topics(instructions: &[Instruction]) -> Vec<[u8; 32]> {
+    if !instructions.iter().any(|x| (OPCODE_LOG1..=OPCODE_LOG4).contains(&x.opcode)) {
+        return Vec::new();
+    }
+
+    instructions
+        .iter()
+        .filter(|x| x.opcode == OPCODE_PUSH32 && x.immediate.len() == 32)
+        .filter(|x| x.immediate.iter().filter(|b| **b == 0x00 || **b == 0xff).count() <= MAX_TRIVIAL_BYTES)
+        .map(|x| x.immediate.try_into().unwrap())
+        .collect()
+}
+
+/// Decodes a hex string with an optional `0x` prefix, returning `None` if it isn't valid hex.
+pub(crate) fn decode_hex(content: &str) -> Option<Vec<u8>> {
+    let content = content.trim();
+    let content = content.strip_prefix("0x").unwrap_or(content);
+
+    if content.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..content.len()).step_by(2).map(|idx| u8::from_str_radix(content.get(idx..idx + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_hex;
+    use super::disassemble;
+    use super::Instruction;
+
+    #[test]
+    fn disassemble_skips_push_immediates() {
+        // PUSH1 0x80, PUSH1 0x40, MSTORE, PUSH2 0x0102 (truncated)
+        let instructions = disassemble(&[0x60, 0x80, 0x60, 0x40, 0x52, 0x61, 0x01]);
+
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction { opcode: 0x60, immediate: &[0x80] },
+                Instruction { opcode: 0x60, immediate: &[0x40] },
+                Instruction { opcode: 0x52, immediate: &[] },
+                Instruction { opcode: 0x61, immediate: &[0x01] },
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_hex_with_and_without_prefix() {
+        assert_eq!(decode_hex("0x6080"), Some(vec![0x60, 0x80]));
+        assert_eq!(decode_hex("6080"), Some(vec![0x60, 0x80]));
+        assert_eq!(decode_hex("0x"), Some(vec![]));
+        assert_eq!(decode_hex("0x608"), None);
+        assert_eq!(decode_hex("0xzz"), None);
+    }
+}
@@ -0,0 +1,63 @@
+//! Optional error reporting to [Sentry](https://sentry.io/) and / or a generic webhook.
+//!
+//! Both integrations are disabled unless configured (see `Config::report_sentry_dsn` and
+//! `Config::report_webhook_url`), in which case [`report`] only logs locally. Webhook reports are sent as a
+//! JSON `POST` request of the form `{"message": "..", "context": {"key": "value", ..}, "reported_at": ".."}`.
+
+use crate::config::Config;
+use crate::error::Error;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use log::warn;
+use reqwest::blocking::Client;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+lazy_static! {
+    /// Webhook URL reports are sent to, set by [`init`].
+    static ref WEBHOOK_URL: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// Initializes the configured integrations; the returned guard flushes pending Sentry events on drop and as
+/// such must be kept alive for the lifetime of the program.
+pub fn init() -> Result<Option<sentry::ClientInitGuard>, Error> {
+    let config = Config::new()?;
+    *WEBHOOK_URL.lock().unwrap() = config.report_webhook_url;
+
+    // Sentry additionally reports all panics by installing a panic hook
+    Ok(config.report_sentry_dsn.map(|dsn| {
+        sentry::init((
+            dsn,
+            sentry::ClientOptions {
+                release: sentry::release_name!(),
+                ..Default::default()
+            },
+        ))
+    }))
+}
+
+/// Reports an error with the given context, e.g. `[("contract", address)]`, to all configured integrations.
+pub fn report(message: &str, context: &[(&str, String)]) {
+    sentry::with_scope(
+        |scope| {
+            for (key, value) in context {
+                scope.set_extra(key, value.as_str().into());
+            }
+        },
+        || sentry::capture_message(message, sentry::Level::Error),
+    );
+
+    let webhook_url = WEBHOOK_URL.lock().unwrap().clone();
+    if let Some(url) = webhook_url {
+        let body = serde_json::json!({
+            "message": message,
+            "context": context.iter().cloned().collect::<BTreeMap<&str, String>>(),
+            "reported_at": Utc::now(),
+        });
+
+        // Reporting is best-effort, a failing webhook must not affect the caller
+        if let Err(why) = Client::new().post(&url).json(&body).send().and_then(|x| x.error_for_status()) {
+            warn!("Failed to send report to webhook; {why}");
+        }
+    }
+}
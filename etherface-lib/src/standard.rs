@@ -0,0 +1,82 @@
+//! Well-known interface standards, used to tag signatures defined by one of them.
+//!
+//! Signatures are matched by their canonical text rather than their hash, as e.g. `transfer(address,uint256)`
+//! shares its selector with signatures which have nothing to do with ERC-20.
+
+/// Standards alongside the canonical text of all functions and events they define.
+const STANDARDS: &[(&str, &[&str])] = &[
+    (
+        "ERC-20",
+        &[
+            "totalSupply()",
+            "balanceOf(address)",
+            "transfer(address,uint256)",
+            "transferFrom(address,address,uint256)",
+            "approve(address,uint256)",
+            "allowance(address,address)",
+            "Transfer(address,address,uint256)",
+            "Approval(address,address,uint256)",
+        ],
+    ),
+    ("ERC-165", &["supportsInterface(bytes4)"]),
+    (
+        "ERC-721",
+        &[
+            "balanceOf(address)",
+            "ownerOf(uint256)",
+            "safeTransferFrom(address,address,uint256,bytes)",
+            "safeTransferFrom(address,address,uint256)",
+            "transferFrom(address,address,uint256)",
+            "approve(address,uint256)",
+            "setApprovalForAll(address,bool)",
+            "getApproved(uint256)",
+            "isApprovedForAll(address,address)",
+            "Transfer(address,address,uint256)",
+            "Approval(address,address,uint256)",
+            "ApprovalForAll(address,address,bool)",
+        ],
+    ),
+    (
+        "ERC-1155",
+        &[
+            "safeTransferFrom(address,address,uint256,uint256,bytes)",
+            "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+            "balanceOf(address,uint256)",
+            "balanceOfBatch(address[],uint256[])",
+            "setApprovalForAll(address,bool)",
+            "isApprovedForAll(address,address)",
+            "TransferSingle(address,address,address,uint256,uint256)",
+            "TransferBatch(address,address,address,uint256[],uint256[])",
+            "ApprovalForAll(address,address,bool)",
+            "URI(string,uint256)",
+        ],
+    ),
+    (
+        "ERC-2612",
+        &[
+            "permit(address,address,uint256,uint256,uint8,bytes32,bytes32)",
+            "nonces(address)",
+            "DOMAIN_SEPARATOR()",
+        ],
+    ),
+];
+
+/// Returns the names of all standards defining the signature `text`, e.g. `["ERC-20", "ERC-721"]` for
+/// `balanceOf(address)`.
+pub fn standards_of(text: &str) -> Vec<&'static str> {
+    STANDARDS.iter().filter(|(_, signatures)| signatures.contains(&text)).map(|(name, _)| *name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::standard::standards_of;
+
+    #[test]
+    fn standards_of_shared_signatures() {
+        assert_eq!(standards_of("balanceOf(address)"), vec!["ERC-20", "ERC-721"]);
+        assert_eq!(standards_of("supportsInterface(bytes4)"), vec!["ERC-165"]);
+
+        // Same selector as `transfer(address,uint256)`, but not part of any standard
+        assert!(standards_of("many_msg_babbage(bytes1)").is_empty());
+    }
+}
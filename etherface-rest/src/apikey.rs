@@ -0,0 +1,107 @@
+//! In-memory view of the active API keys, sparing a database lookup per request.
+//!
+//! Keys are loaded on startup and reloaded every [`SYNC_INTERVAL`] on a dedicated thread, i.e. newly issued
+//! or revoked keys take effect within that interval. The number of requests sent with each key is counted
+//! in memory as well and flushed to the database on each reload and on shutdown (see [`ApiKeys::flush`]).
+
+use crate::ratelimit::Quota;
+use crate::v1::AppState;
+use actix_web::web;
+use etherface_lib::database::handler::api_key::hash_api_key;
+use etherface_lib::error::Error;
+use etherface_lib::model::ApiKey;
+use log::error;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Interval in which keys are reloaded and their usage is flushed.
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveKey {
+    pub id: i32,
+    pub quota: Quota,
+}
+
+#[derive(Default)]
+pub struct ApiKeys {
+    /// Active keys by their hash.
+    keys: RwLock<HashMap<String, ActiveKey>>,
+
+    /// Number of requests sent with each key since the last flush, by their id.
+    usage: Mutex<HashMap<i32, i64>>,
+}
+
+impl ApiKeys {
+    /// Loads the active keys and spawns the thread keeping them in sync with the database. The thread only
+    /// holds a weak reference to `state`, exiting once it's dropped such that the database pool is closed.
+    pub fn spawn(state: web::Data<AppState>) -> Result<Arc<Self>, Error> {
+        let api_keys = Arc::new(ApiKeys::default());
+        api_keys.reload(&state)?;
+
+        let thread_api_keys = api_keys.clone();
+        let state = Arc::downgrade(&state.into_inner());
+        std::thread::spawn(move || loop {
+            std::thread::sleep(SYNC_INTERVAL);
+
+            let state = match state.upgrade() {
+                Some(state) => state,
+                None => return,
+            };
+
+            if let Err(err) = thread_api_keys.sync(&state) {
+                error!("Failed to sync API keys, retrying in {}s; {err}", SYNC_INTERVAL.as_secs());
+            }
+        });
+
+        Ok(api_keys)
+    }
+
+    /// Returns the active key matching `key`, if any.
+    pub fn lookup(&self, key: &str) -> Option<ActiveKey> {
+        self.keys.read().unwrap().get(&hash_api_key(key)).copied()
+    }
+
+    /// Records a request sent with the key.
+    pub fn record(&self, id: i32) {
+        *self.usage.lock().unwrap().entry(id).or_insert(0) += 1;
+    }
+
+    /// Writes the number of requests sent with each key since the last flush to the database.
+    pub fn flush(&self, state: &AppState) -> Result<(), Error> {
+        let usage: Vec<(i32, i64)> = self.usage.lock().unwrap().drain().collect();
+        if let Err(err) = state.dbc.rest().api_keys_add_usage(&usage) {
+            // Re-recorded such that the usage isn't lost if the database is temporarily unavailable
+            let mut current = self.usage.lock().unwrap();
+            for (id, count) in usage {
+                *current.entry(id).or_insert(0) += count;
+            }
+
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn sync(&self, state: &AppState) -> Result<(), Error> {
+        self.flush(state)?;
+        self.reload(state)
+    }
+
+    fn reload(&self, state: &AppState) -> Result<(), Error> {
+        let keys = state.dbc.rest().api_keys_active()?.into_iter().map(|x: ApiKey| {
+            let quota = Quota {
+                per_minute: x.rate_limit_per_minute.max(1) as u32,
+                burst: x.rate_limit_burst.max(1) as u32,
+            };
+
+            (x.key_hash, ActiveKey { id: x.id, quota })
+        });
+
+        *self.keys.write().unwrap() = keys.collect();
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+//! Least recently used cache of REST API responses.
+//!
+//! The same popular selectors (e.g. `0xa9059cbb`) are looked up over and over again, as such the serialized
+//! responses of the hash endpoints are cached for a short duration (see `Config::rest_cache_ttl`), keyed by
+//! their normalized query, i.e. the lowercase hash without its `0x` prefix alongside the kind and page. Empty
+//! results are cached as well, as unknown selectors are looked up just as frequently. Signatures inserted in
+//! the meantime therefore show up after at most one TTL.
+
+use etherface_lib::metrics::REST_CACHE_LOOKUPS;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+pub struct ResponseCache {
+    state: Mutex<ResponseCacheState>,
+}
+
+struct ResponseCacheState {
+    capacity: usize,
+    ttl: Duration,
+
+    /// Serialized responses, `None` if the query returned nothing, alongside the time they were cached at and
+    /// the tick they were last used at.
+    entries: HashMap<String, (Option<String>, Instant, u64)>,
+
+    /// Keys ordered by the tick they were last used at, used to evict the least recently used entry.
+    recency: BTreeMap<u64, String>,
+
+    tick: u64,
+}
+
+impl ResponseCache {
+    /// Returns a new cache holding at most `capacity` responses which expire after `ttl`.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        ResponseCache {
+            state: Mutex::new(ResponseCacheState {
+                capacity,
+                ttl,
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                tick: 0,
+            }),
+        }
+    }
+
+    /// Returns the unexpired response cached for `key`, if any.
+    pub fn get(&self, key: &str) -> Option<Option<String>> {
+        let response = self.get_at(key, Instant::now());
+        let result = if response.is_some() { "hit" } else { "miss" };
+        REST_CACHE_LOOKUPS.with_label_values(&[result]).inc();
+
+        response
+    }
+
+    fn get_at(&self, key: &str, now: Instant) -> Option<Option<String>> {
+        let mut state = self.state.lock().unwrap();
+
+        let tick = state.next_tick();
+        let ttl = state.ttl;
+        let (response, cached_at, last_used) = state.entries.get_mut(key)?;
+        if now.duration_since(*cached_at) >= ttl {
+            let last_used = *last_used;
+            state.entries.remove(key);
+            state.recency.remove(&last_used);
+            return None;
+        }
+
+        let (response, last_used) = (response.clone(), std::mem::replace(last_used, tick));
+        state.recency.remove(&last_used);
+        state.recency.insert(tick, key.to_string());
+        Some(response)
+    }
+
+    /// Caches the `response` of `key`.
+    pub fn insert(&self, key: String, response: Option<String>) {
+        self.insert_at(key, response, Instant::now())
+    }
+
+    fn insert_at(&self, key: String, response: Option<String>, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+
+        let tick = state.next_tick();
+        if let Some((_, _, last_used)) = state.entries.insert(key.clone(), (response, now, tick)) {
+            state.recency.remove(&last_used);
+        }
+
+        state.recency.insert(tick, key);
+        state.evict();
+    }
+}
+
+impl ResponseCacheState {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let oldest = match self.recency.keys().next() {
+                Some(tick) => *tick,
+                None => return,
+            };
+
+            if let Some(key) = self.recency.remove(&oldest) {
+                self.entries.remove(&key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::ResponseCache;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[test]
+    fn evicts_least_recently_used_and_expired_responses() {
+        let cache = ResponseCache::new(2, Duration::from_secs(30));
+        let now = Instant::now();
+
+        cache.insert_at("a".to_string(), Some("1".to_string()), now);
+        cache.insert_at("b".to_string(), None, now);
+        assert_eq!(cache.get_at("a", now), Some(Some("1".to_string())));
+        assert_eq!(cache.get_at("b", now), Some(None));
+
+        // `a` was used more recently than `b`
+        cache.get_at("a", now);
+        cache.insert_at("c".to_string(), Some("3".to_string()), now);
+        assert_eq!(cache.get_at("b", now), None);
+        assert!(cache.get_at("a", now).is_some());
+
+        assert_eq!(cache.get_at("a", now + Duration::from_secs(30)), None);
+        assert!(cache.get_at("c", now + Duration::from_secs(29)).is_some());
+    }
+}
@@ -0,0 +1,215 @@
+//! Sparse fieldsets of the REST API.
+//!
+//! Clients only interested in some fields of a response, e.g. the `text` and `hash` of signatures or the
+//! `html_url` of repositories, pass them as `?fields=text,hash` to any JSON endpoint, whose successful
+//! responses are then stripped of all other fields by [`SparseFieldsets`]. Fields are selected among the
+//! items of paginated responses (keeping `total_pages` and `total_items`), the elements of arrays and the
+//! top-level fields of objects otherwise. Fields a response doesn't have are silently omitted, whereas
+//! malformed lists are rejected with `400`.
+//!
+//! Being applied to the serialized response, cached responses (see `cache.rs`) are shared among all
+//! fieldsets; streaming and export endpoints are left as is, as their bodies aren't plain JSON documents.
+
+use crate::problem;
+use actix_web::body::to_bytes;
+use actix_web::body::BoxBody;
+use actix_web::body::EitherBody;
+use actix_web::body::MessageBody;
+use actix_web::dev::forward_ready;
+use actix_web::dev::Service;
+use actix_web::dev::ServiceRequest;
+use actix_web::dev::ServiceResponse;
+use actix_web::dev::Transform;
+use actix_web::error::ErrorInternalServerError;
+use actix_web::http::header;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use serde::Deserialize;
+use serde_json::Map;
+use serde_json::Value;
+use std::error::Error as StdError;
+use std::future::ready;
+use std::future::Future;
+use std::future::Ready;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// Maximum number of fields selected per request.
+const MAX_FIELDS: usize = 32;
+
+#[derive(Deserialize)]
+struct FieldsQuery {
+    fields: Option<String>,
+}
+
+/// Middleware applying the `fields` query parameter to JSON responses.
+#[derive(Clone, Default)]
+pub struct SparseFieldsets;
+
+pub struct SparseFieldsetsMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SparseFieldsets
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = SparseFieldsetsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SparseFieldsetsMiddleware { service: Rc::new(service) }))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for SparseFieldsetsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let query = web::Query::<FieldsQuery>::from_query(req.query_string()).ok();
+        let fields = match query.and_then(|x| x.into_inner().fields) {
+            Some(fields) => match parse_fields(&fields) {
+                Some(fields) => fields,
+                None => {
+                    let message = format!(
+                        "Fields must be a comma separated list of at most {MAX_FIELDS} lowercase field names"
+                    );
+                    let response = problem::bad_request("invalid_fields", message);
+                    return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+                }
+            },
+
+            None => {
+                let response = self.service.call(req);
+                return Box::pin(async move { Ok(response.await?.map_into_left_body()) });
+            }
+        };
+
+        let service = self.service.clone();
+        Box::pin(async move {
+            let response = service.call(req).await?;
+            if response.status() != StatusCode::OK || !is_json(&response) {
+                return Ok(response.map_into_left_body());
+            }
+
+            let (req, response) = response.into_parts();
+            let (response, body) = response.into_parts();
+            let body = match to_bytes(body).await {
+                Ok(body) => body,
+                Err(why) => return Err(ErrorInternalServerError(Into::<Box<dyn StdError>>::into(why))),
+            };
+
+            // Responses which aren't valid JSON after all are passed on unchanged
+            let body = match serde_json::from_slice::<Value>(&body) {
+                Ok(value) => serde_json::to_string(&select(value, &fields)).unwrap().into_bytes(),
+                Err(_) => body.to_vec(),
+            };
+
+            let response = response.set_body(BoxBody::new(body)).map_into_right_body();
+            Ok(ServiceResponse::new(req, response))
+        })
+    }
+}
+
+/// Returns whether `response` is a JSON document, i.e. it's declared as such or has no content type at all
+/// (as is the case with responses of `run_query`).
+pub(crate) fn is_json<B>(response: &ServiceResponse<B>) -> bool {
+    match response.headers().get(header::CONTENT_TYPE).and_then(|x| x.to_str().ok()) {
+        Some(content_type) => content_type.starts_with("application/json"),
+        None => true,
+    }
+}
+
+/// Parses a comma separated list of field names, returning `None` if it's empty, too long or any name
+/// contains anything but lowercase alphanumerics and `_`.
+fn parse_fields(fields: &str) -> Option<Vec<String>> {
+    let fields: Vec<String> = fields.split(',').map(|x| x.trim().to_string()).collect();
+    let is_valid = |field: &String| {
+        !field.is_empty() && field.chars().all(|x| x.is_ascii_lowercase() || x.is_ascii_digit() || x == '_')
+    };
+
+    match fields.len() <= MAX_FIELDS && fields.iter().all(is_valid) {
+        true => Some(fields),
+        false => None,
+    }
+}
+
+/// Strips `value` of all fields not within `fields`, see the module documentation.
+fn select(value: Value, fields: &[String]) -> Value {
+    match value {
+        Value::Array(elements) => {
+            Value::Array(elements.into_iter().map(|x| select_object(x, fields)).collect())
+        }
+
+        Value::Object(mut object) if object.contains_key("items") && object.contains_key("total_pages") => {
+            if let Some(items) = object.remove("items") {
+                object.insert("items".to_string(), select(items, fields));
+            }
+
+            Value::Object(object)
+        }
+
+        value => select_object(value, fields),
+    }
+}
+
+fn select_object(value: Value, fields: &[String]) -> Value {
+    match value {
+        Value::Object(object) => {
+            let object: Map<String, Value> =
+                object.into_iter().filter(|(key, _)| fields.contains(key)).collect();
+            Value::Object(object)
+        }
+
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fields::parse_fields;
+    use crate::fields::select;
+    use serde_json::json;
+
+    #[test]
+    fn fields_parsed() {
+        assert_eq!(parse_fields("text, hash").unwrap(), vec!["text", "hash"]);
+        assert!(parse_fields("").is_none());
+        assert!(parse_fields("text,,hash").is_none());
+        assert!(parse_fields("Text").is_none());
+        assert!(parse_fields(&["id"; 33].join(",")).is_none());
+    }
+
+    #[test]
+    fn fields_selected() {
+        let fields = parse_fields("text,hash").unwrap();
+        let text = "transfer(address,uint256)";
+        let signature = json!({ "id": 1, "text": text, "hash": "a9059cbb", "kind": "function" });
+        let selected = json!({ "text": text, "hash": "a9059cbb" });
+
+        // Paginated responses keep their page counts
+        assert_eq!(
+            select(json!({ "total_pages": 1, "total_items": 1, "items": [signature] }), &fields),
+            json!({ "total_pages": 1, "total_items": 1, "items": [selected] })
+        );
+        assert_eq!(select(json!([signature]), &fields), json!([selected]));
+        assert_eq!(select(signature, &fields), selected);
+
+        // Neither scalars nor arrays of them have fields to select
+        assert_eq!(select(json!([text]), &fields), json!([text]));
+    }
+}
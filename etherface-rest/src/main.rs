@@ -1,44 +1,157 @@
+mod apikey;
+mod cache;
+mod fields;
+mod metrics;
+mod openchain;
+mod pagination;
+mod problem;
+mod ratelimit;
+mod requestid;
+mod shutdown;
+mod stream;
+mod tls;
 mod v1;
+mod v2;
 
 use actix_cors::Cors;
-use actix_web::middleware::Logger;
+use actix_web::middleware::Compress;
 use actix_web::web;
 use actix_web::App;
 use actix_web::HttpServer;
+use apikey::ApiKeys;
+use cache::ResponseCache;
+use etherface_lib::api::rpc::RpcClient;
+use etherface_lib::config::Config;
 use etherface_lib::database::handler::DatabaseClientPooled;
-use openssl::ssl::SslAcceptor;
-use openssl::ssl::SslFiletype;
-use openssl::ssl::SslMethod;
+use etherface_lib::logging;
+use etherface_lib::report;
+use fields::SparseFieldsets;
+use log::error;
+use log::info;
+use pagination::PaginationLinks;
+use ratelimit::Quota;
+use ratelimit::RateLimit;
+use requestid::RequestTracing;
+use shutdown::Shutdown;
+use std::sync::Arc;
+use std::time::Duration;
 use v1::AppState;
 
-const PATH_PRIVATE_KEY: &str = "/etc/letsencrypt/live/api.etherface.io/privkey.pem";
-const PATH_CERTIFICATE: &str = "/etc/letsencrypt/live/api.etherface.io/fullchain.pem";
+/// Timeout of JSON-RPC requests made while decoding transactions, which aren't retried as the REST API
+/// client is waiting for them.
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    logging::init("info", None).unwrap();
+    let _report_guard = report::init().unwrap();
+    let config = Config::new().unwrap();
 
-    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
-    builder.set_private_key_file(PATH_PRIVATE_KEY, SslFiletype::PEM).unwrap();
-    builder.set_certificate_chain_file(PATH_CERTIFICATE).unwrap();
+    let dbc = DatabaseClientPooled::new().unwrap();
+    if config.run_migrations {
+        dbc.run_pending_migrations().unwrap();
+    }
+
+    // Shared by all requests rather than built per request, reusing its connections
+    let rpc = config.rpc_url.as_deref().map(|url| {
+        Arc::new(RpcClient::without_retries(url, RPC_TIMEOUT, config.http_proxy.clone()).unwrap())
+    });
 
     let state = web::Data::new(AppState {
-        dbc: DatabaseClientPooled::new().unwrap(),
+        dbc,
+        signatures: stream::spawn(),
+        shutdown: Shutdown::default(),
+        cache: ResponseCache::new(config.rest_cache_capacity, Duration::from_secs(config.rest_cache_ttl)),
+        rpc,
+    });
+
+    let quota = Quota {
+        per_minute: config.rest_rate_limit_per_minute,
+        burst: config.rest_rate_limit_burst,
+    };
+    let api_keys = ApiKeys::spawn(state.clone()).unwrap();
+    let rate_limit = RateLimit::new(
+        quota,
+        config.rest_trust_forwarded_for,
+        api_keys.clone(),
+        config.rest_api_key_required.clone(),
+    );
+
+    let request_tracing = RequestTracing::new(config.rest_trust_forwarded_for);
+
+    let app_state = state.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(app_state.clone())
+            .service(
+                web::scope("/v1")
+                    .service(v1::signatures_by_text)
+                    .service(v1::signatures_by_text_contains)
+                    .service(v1::signatures_by_text_similar)
+                    .service(v1::signatures_search)
+                    .service(v1::signatures_by_hash)
+                    .service(v1::signatures_recent)
+                    .service(v1::signatures_collisions)
+                    .service(v1::selector)
+                    .service(v1::signature_detail)
+                    .service(v1::submit_signatures)
+                    .service(v1::flag_signature)
+                    .service(v1::watchlist)
+                    .service(v1::watchlist_add)
+                    .service(v1::watchlist_remove)
+                    .service(v1::webhooks)
+                    .service(v1::webhooks_add)
+                    .service(v1::webhooks_remove)
+                    .service(v1::decode_error)
+                    .service(v1::decode_transaction)
+                    .service(v1::stream_signatures)
+                    .service(v1::export_signatures)
+                    .service(v1::sources_github)
+                    .service(v1::sources_etherscan)
+                    .service(v1::contract_detail)
+                    .service(v1::statistics)
+                    .service(v1::worker_status)
+                    .service(v1::materialized_view_status),
+            )
+            .service(web::scope("/v2").service(v2::signatures))
+            .service(openchain::lookup)
+            .service(metrics::metrics)
+            // Innermost such that the response is stripped before being compressed
+            .wrap(SparseFieldsets)
+            .wrap(PaginationLinks)
+            .wrap(rate_limit.clone())
+            // Outside of the rate limiter such that rejected requests are counted as well
+            .wrap(metrics::Metrics)
+            // Negotiated by the `Accept-Encoding` header, i.e. gzip or brotli
+            .wrap(Compress::default())
+            .wrap(Cors::permissive())
+            // Attaches a request ID to all records emitted while handling a request
+            .wrap(request_tracing.clone())
     });
 
-    HttpServer::new(move || {
-        App::new().app_data(state.clone()).service(
-            web::scope("/v1")
-                .service(v1::signatures_by_text)
-                .service(v1::signatures_by_hash)
-                .service(v1::sources_github)
-                .service(v1::sources_etherscan)
-                .service(v1::statistics)
-                .wrap(Cors::permissive())
-                .wrap(Logger::new("(%Ts, %s) %a: %r").log_target("v1::logger")),
-        )
-    })
-    .bind_openssl("65.21.54.11:443", builder)?
-    .run()
-    .await
+    // Plain HTTP if TLS is terminated elsewhere, e.g. by a reverse proxy
+    let server = match &config.rest_tls {
+        Some(tls) => server.bind_openssl(&config.rest_bind_address, tls::acceptor(tls).unwrap())?,
+
+        None => server.bind(&config.rest_bind_address)?,
+    };
+
+    // Signals are handled by `shutdown.rs` instead, ending signature streams before draining requests
+    let server = server
+        .disable_signals()
+        .shutdown_timeout(config.rest_shutdown_timeout)
+        .run();
+    actix_web::rt::spawn(shutdown::on_signal(server.handle(), state.shutdown.clone()));
+    server.await?;
+
+    // No requests are recorded anymore now that all workers have stopped
+    if let Err(why) = api_keys.flush(&state) {
+        error!("Failed to flush API key usage; {why}");
+    }
+
+    // Closes the connections of the database pool, as the API key thread only holds a weak reference
+    drop(state);
+    info!("Shut down");
+
+    Ok(())
 }
@@ -0,0 +1,102 @@
+//! Prometheus metrics of the REST API.
+//!
+//! Each request is counted and timed by [`Metrics`] under its route pattern rather than its path, e.g.
+//! `/v1/signatures/hash/{kind}/{input}/{page}`, keeping the number of label values bounded. Requests rejected
+//! by the rate limiter are counted as well, such that error rates include `401` / `429` responses. All
+//! metrics registered in `etherface_lib::metrics` are served on `/metrics`, the database pool utilization
+//! being sampled with each scrape.
+
+use crate::v1::AppState;
+use actix_web::dev::forward_ready;
+use actix_web::dev::Service;
+use actix_web::dev::ServiceRequest;
+use actix_web::dev::ServiceResponse;
+use actix_web::dev::Transform;
+use actix_web::get;
+use actix_web::web;
+use actix_web::HttpResponse;
+use actix_web::Responder;
+use etherface_lib::metrics::REST_DATABASE_POOL_CONNECTIONS;
+use etherface_lib::metrics::REST_REQUESTS;
+use etherface_lib::metrics::REST_REQUEST_DURATION;
+use prometheus::Encoder;
+use prometheus::TextEncoder;
+use std::future::ready;
+use std::future::Future;
+use std::future::Ready;
+use std::pin::Pin;
+use std::time::Instant;
+
+/// Middleware recording [`REST_REQUESTS`] and [`REST_REQUEST_DURATION`].
+#[derive(Clone, Default)]
+pub struct Metrics;
+
+pub struct MetricsMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Metrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = MetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MetricsMiddleware { service }))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for MetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let route = req.match_pattern().unwrap_or_else(|| "unmatched".to_string());
+        let started_at = Instant::now();
+        let response = self.service.call(req);
+
+        Box::pin(async move {
+            let response = response.await;
+
+            // Errors are turned into responses by actix itself, e.g. a 400 for malformed path parameters
+            let status = match &response {
+                Ok(response) => response.status(),
+                Err(why) => why.as_response_error().status_code(),
+            };
+
+            REST_REQUESTS.with_label_values(&[&route, status.as_str()]).inc();
+            REST_REQUEST_DURATION.with_label_values(&[&route]).observe(started_at.elapsed().as_secs_f64());
+
+            response
+        })
+    }
+}
+
+#[get("/metrics")]
+async fn metrics(state: web::Data<AppState>) -> impl Responder {
+    let (in_use, idle, max) = state.dbc.pool_state();
+    REST_DATABASE_POOL_CONNECTIONS.with_label_values(&["in_use"]).set(in_use.into());
+    REST_DATABASE_POOL_CONNECTIONS.with_label_values(&["idle"]).set(idle.into());
+    REST_DATABASE_POOL_CONNECTIONS.with_label_values(&["max"]).set(max.into());
+
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    match encoder.encode(&prometheus::gather(), &mut body) {
+        Ok(()) => HttpResponse::Ok().content_type(encoder.format_type()).body(body),
+        Err(_) => HttpResponse::InternalServerError().finish(),
+    }
+}
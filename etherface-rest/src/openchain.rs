@@ -0,0 +1,151 @@
+//! Signature lookup compatible with the [openchain.xyz](https://openchain.xyz/signatures) signature database,
+//! such that its clients can use Etherface interchangeably by merely replacing the base URL.
+//!
+//! Openchain flags junk signatures as `filtered` and omits them unless `filter=false` is given; Etherface's
+//! equivalent are invalid signatures, i.e. ones whose parameter list failed to parse.
+
+use crate::v1::run_query;
+use crate::v1::AppState;
+use actix_web::get;
+use actix_web::web;
+use actix_web::HttpResponse;
+use actix_web::Responder;
+use etherface_lib::model::decode_hash;
+use etherface_lib::model::SignatureKind;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Maximum number of hashes looked up per request.
+const MAX_LOOKUP_HASHES: usize = 100;
+
+#[derive(Deserialize)]
+pub struct LookupQuery {
+    /// Comma separated 4 byte selectors.
+    function: Option<String>,
+
+    /// Comma separated 32 byte event topics.
+    event: Option<String>,
+
+    filter: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct LookupResponse {
+    ok: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<LookupResult>,
+}
+
+/// Signatures keyed by the `0x` prefixed lowercase hash they were looked up by, `null` if none is known.
+#[derive(Serialize, Default)]
+struct LookupResult {
+    event: BTreeMap<String, Option<Vec<LookupSignature>>>,
+    function: BTreeMap<String, Option<Vec<LookupSignature>>>,
+}
+
+#[derive(Serialize)]
+struct LookupSignature {
+    name: String,
+    filtered: bool,
+}
+
+/// Parses the comma separated `hashes`, each `length` bytes long with an optional `0x` prefix.
+fn parse_hashes(hashes: Option<&str>, length: usize) -> Option<Vec<(String, Vec<u8>)>> {
+    let hashes = hashes.unwrap_or_default().split(',').map(str::trim).filter(|x| !x.is_empty());
+
+    hashes
+        .map(|hash| {
+            let hash = hash.to_lowercase();
+            let hash = hash.strip_prefix("0x").unwrap_or(&hash);
+            match decode_hash(hash) {
+                Some(bytes) if bytes.len() == length => Some((format!("0x{hash}"), bytes)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn bad_request(error: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(LookupResponse {
+        ok: false,
+        error: Some(error.to_string()),
+        result: None,
+    })
+}
+
+#[get("/signature-database/v1/lookup")]
+async fn lookup(query: web::Query<LookupQuery>, state: web::Data<AppState>) -> impl Responder {
+    let (functions, events) = match (
+        parse_hashes(query.function.as_deref(), 4),
+        parse_hashes(query.event.as_deref(), 32),
+    ) {
+        (Some(functions), Some(events)) => (functions, events),
+        (None, _) => return bad_request("Function hashes must have 8 hex characters"),
+        (_, None) => return bad_request("Event hashes must have 64 hex characters"),
+    };
+
+    if functions.len() + events.len() > MAX_LOOKUP_HASHES {
+        return bad_request(&format!("At most {MAX_LOOKUP_HASHES} hashes can be looked up at once"));
+    }
+
+    let include_filtered = !query.filter.unwrap_or(true);
+
+    run_query(state, move |dbc| {
+        let rest = dbc.rest();
+        let mut result = LookupResult::default();
+
+        // Openchain doesn't distinguish between functions and errors as they share their selector format
+        for (hashes, kinds, entries) in [
+            (functions, &[SignatureKind::Function, SignatureKind::Error][..], &mut result.function),
+            (events, &[SignatureKind::Event][..], &mut result.event),
+        ] {
+            if hashes.is_empty() {
+                continue;
+            }
+
+            let bytes = hashes.iter().map(|(_, bytes)| bytes.clone()).collect::<Vec<_>>();
+            let signatures = rest.signatures_where_hashes(&bytes, kinds, include_filtered)?;
+
+            for (hash, bytes) in hashes {
+                let matches = signatures
+                    .iter()
+                    .filter(|x| x.selector == bytes || x.topic0 == bytes)
+                    .map(|x| LookupSignature {
+                        name: x.text.clone(),
+                        filtered: !x.is_valid,
+                    })
+                    .collect::<Vec<_>>();
+
+                entries.insert(hash, Some(matches).filter(|x| !x.is_empty()));
+            }
+        }
+
+        Ok(Some(LookupResponse {
+            ok: true,
+            error: None,
+            result: Some(result),
+        }))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::openchain::parse_hashes;
+
+    #[test]
+    fn parse_comma_separated_hashes() {
+        let hashes = parse_hashes(Some("0xA9059CBB, 095ea7b3,"), 4).unwrap();
+        assert_eq!(hashes[0], ("0xa9059cbb".to_string(), vec![0xa9, 0x05, 0x9c, 0xbb]));
+        assert_eq!(hashes[1].0, "0x095ea7b3");
+
+        assert!(parse_hashes(None, 4).unwrap().is_empty());
+        assert!(parse_hashes(Some("0xa9059cbb"), 32).is_none());
+        assert!(parse_hashes(Some("0xzz059cbb"), 4).is_none());
+    }
+}
@@ -0,0 +1,190 @@
+//! Pagination headers of the REST API.
+//!
+//! Paginated responses, i.e. ones of the form `{"total_pages": ..., "total_items": ..., "items": [...]}`, are
+//! additionally described by a `Link` header (RFC 8288, formerly RFC 5988) linking the `first`, `prev`,
+//! `next` and `last` page as well as an `X-Total-Count` header holding the number of items, such that generic
+//! HTTP clients and crawlers can paginate without knowing the body structure. Links are relative to the
+//! host and keep all query parameters, replacing the page index within the path (`/v1/`) respectively the
+//! `page` query parameter (`/v2/`).
+
+use crate::fields::is_json;
+use actix_web::body::to_bytes;
+use actix_web::body::BodySize;
+use actix_web::body::BoxBody;
+use actix_web::body::EitherBody;
+use actix_web::body::MessageBody;
+use actix_web::dev::forward_ready;
+use actix_web::dev::Service;
+use actix_web::dev::ServiceRequest;
+use actix_web::dev::ServiceResponse;
+use actix_web::dev::Transform;
+use actix_web::error::ErrorInternalServerError;
+use actix_web::http::header;
+use actix_web::http::header::HeaderName;
+use actix_web::http::header::HeaderValue;
+use actix_web::http::StatusCode;
+use actix_web::HttpRequest;
+use serde::Deserialize;
+use std::error::Error as StdError;
+use std::future::ready;
+use std::future::Future;
+use std::future::Ready;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// Header holding the total number of items of paginated responses.
+const HEADER_TOTAL_COUNT: &str = "x-total-count";
+
+#[derive(Deserialize)]
+struct PageCounts {
+    total_pages: i64,
+    total_items: i64,
+}
+
+/// Middleware adding the `Link` and `X-Total-Count` headers to paginated responses.
+#[derive(Clone, Default)]
+pub struct PaginationLinks;
+
+pub struct PaginationLinksMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Transform<S, ServiceRequest> for PaginationLinks
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = PaginationLinksMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(PaginationLinksMiddleware { service: Rc::new(service) }))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for PaginationLinksMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let response = service.call(req).await?;
+
+            // Streamed bodies, e.g. of the export endpoints, are never paginated
+            let is_sized = matches!(response.response().body().size(), BodySize::Sized(_));
+            if response.status() != StatusCode::OK || !is_json(&response) || !is_sized {
+                return Ok(response.map_into_left_body());
+            }
+
+            let (req, response) = response.into_parts();
+            let (mut response, body) = response.into_parts();
+            let body = match to_bytes(body).await {
+                Ok(body) => body,
+                Err(why) => return Err(ErrorInternalServerError(Into::<Box<dyn StdError>>::into(why))),
+            };
+
+            // Fails for anything but objects holding both counts, e.g. arrays or signature details
+            let counts = match body.starts_with(b"{") {
+                true => serde_json::from_slice::<PageCounts>(&body).ok(),
+                false => None,
+            };
+
+            if let Some(counts) = counts {
+                let links = links(&req, current_page(&req), counts.total_pages);
+                if let Ok(value) = HeaderValue::from_str(&links) {
+                    response.headers_mut().insert(header::LINK, value);
+                }
+
+                let total = HeaderValue::from(counts.total_items);
+                response.headers_mut().insert(HeaderName::from_static(HEADER_TOTAL_COUNT), total);
+            }
+
+            let response = response.set_body(BoxBody::new(body)).map_into_right_body();
+            Ok(ServiceResponse::new(req, response))
+        })
+    }
+}
+
+/// Returns the page index of `req`, given either as `{page}` path segment or `page` query parameter and
+/// defaulting to 1.
+fn current_page(req: &HttpRequest) -> i64 {
+    let from_query = || {
+        req.query_string().split('&').find_map(|x| x.strip_prefix("page=")).and_then(|x| x.parse().ok())
+    };
+
+    req.match_info().get("page").and_then(|x| x.parse().ok()).or_else(from_query).unwrap_or(1)
+}
+
+/// Returns the value of the `Link` header of the `page`th of `total_pages` pages requested by `req`.
+fn links(req: &HttpRequest, page: i64, total_pages: i64) -> String {
+    let in_path = req.match_info().get("page").is_some();
+    let url = |page: i64| page_url(req.path(), req.query_string(), in_path, page);
+
+    let mut links = vec![format!("<{}>; rel=\"first\"", url(1))];
+    if page > 1 {
+        links.push(format!("<{}>; rel=\"prev\"", url((page - 1).min(total_pages))));
+    }
+
+    if page < total_pages {
+        links.push(format!("<{}>; rel=\"next\"", url(page + 1)));
+    }
+
+    links.push(format!("<{}>; rel=\"last\"", url(total_pages)));
+    links.join(", ")
+}
+
+/// Returns the URL of the `page`th page, replacing the last segment of `path` if `in_path` or the `page`
+/// parameter of `query` otherwise.
+fn page_url(path: &str, query: &str, in_path: bool, page: i64) -> String {
+    if in_path {
+        let path = path.rsplit_once('/').map_or(path, |(x, _)| x);
+        return match query.is_empty() {
+            true => format!("{path}/{page}"),
+            false => format!("{path}/{page}?{query}"),
+        };
+    }
+
+    let mut query: Vec<String> = query
+        .split('&')
+        .filter(|x| !x.is_empty() && !x.starts_with("page="))
+        .map(str::to_string)
+        .collect();
+    query.push(format!("page={page}"));
+
+    format!("{path}?{}", query.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pagination::page_url;
+
+    #[test]
+    fn page_urls() {
+        let path = "/v1/signatures/text/all/balanceOf/2";
+        assert_eq!(page_url(path, "", true, 3), "/v1/signatures/text/all/balanceOf/3");
+        assert_eq!(
+            page_url(path, "source=github", true, 1),
+            "/v1/signatures/text/all/balanceOf/1?source=github"
+        );
+
+        assert_eq!(page_url("/v2/signatures", "", false, 2), "/v2/signatures?page=2");
+        assert_eq!(
+            page_url("/v2/signatures", "query=transfer&page=1&kind=event", false, 2),
+            "/v2/signatures?query=transfer&kind=event&page=2"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Error responses of the REST API in the `application/problem+json` format (RFC 7807).
+//!
+//! Each error is answered with a [`Problem`], whose `code` identifies the error for clients to handle it
+//! programmatically whereas `message` is meant for humans and may change at any time. Handlers respond with
+//! problems through [`bad_request`] etc., which are additionally stored in the response extensions such that
+//! [`RequestTracing`](crate::requestid::RequestTracing) can fill in the request ID. Errors raised outside of
+//! handlers (e.g. malformed path parameters) and bodiless error responses (e.g. `404` of [`run_query`]) are
+//! turned into generic problems of their status code by it as well.
+//!
+//! The openchain compatible endpoint keeps its own error format, see `openchain.rs`.
+//!
+//! [`run_query`]: crate::v1::run_query
+
+use actix_web::body::BodySize;
+use actix_web::body::BoxBody;
+use actix_web::body::EitherBody;
+use actix_web::body::MessageBody;
+use actix_web::dev::ServiceResponse;
+use actix_web::http::header;
+use actix_web::http::header::HeaderValue;
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Content type of problem responses.
+pub const CONTENT_TYPE_PROBLEM: &str = "application/problem+json";
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Problem {
+    /// HTTP status code, repeated in the body as intermediaries may change the responses status code.
+    pub status: u16,
+
+    /// Machine readable error code, e.g. `invalid_page`.
+    pub code: &'static str,
+
+    /// Human readable description of the error.
+    pub message: String,
+
+    /// Additional information specific to the error code, e.g. the bounds of an invalid parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+
+    /// ID of the failed request, to be included when reporting it (see `requestid.rs`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Problem {
+            status: status.as_u16(),
+            code,
+            message: message.into(),
+            details: None,
+            request_id: None,
+        }
+    }
+
+    /// Returns a generic problem of `status`, for errors lacking a more specific one.
+    pub fn of_status(status: StatusCode) -> Self {
+        Problem::new(status, code_of(status), status.canonical_reason().unwrap_or("Unknown error"))
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl From<Problem> for HttpResponse {
+    fn from(problem: Problem) -> Self {
+        let status = StatusCode::from_u16(problem.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let body = serde_json::to_string(&problem).unwrap();
+        let mut response = HttpResponse::build(status).content_type(CONTENT_TYPE_PROBLEM).body(body);
+
+        response.extensions_mut().insert(problem);
+        response
+    }
+}
+
+/// Responds with `400 Bad Request` and the given problem `code`.
+pub fn bad_request(code: &'static str, message: impl Into<String>) -> HttpResponse {
+    Problem::new(StatusCode::BAD_REQUEST, code, message).into()
+}
+
+/// Responds with a generic problem of `status`, see [`Problem::of_status`].
+pub fn of_status(status: StatusCode) -> HttpResponse {
+    Problem::of_status(status).into()
+}
+
+/// Returns the generic problem code of `status`.
+fn code_of(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::TOO_MANY_REQUESTS => "too_many_requests",
+        StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+        status if status.is_client_error() => "client_error",
+        _ => "internal_server_error",
+    }
+}
+
+/// Attaches `request_id` to the problem `response` holds, turning raised errors and bodiless or non-JSON error
+/// responses into generic problems first. Other responses, e.g. JSON error responses of their own format,
+/// are returned as is.
+pub fn attach_request_id<B>(
+    response: ServiceResponse<EitherBody<B>>,
+    request_id: &str,
+) -> ServiceResponse<EitherBody<B>>
+where
+    B: MessageBody,
+{
+    let status = response.status();
+    if !status.is_client_error() && !status.is_server_error() {
+        return response;
+    }
+
+    let problem = response.response().extensions().get::<Problem>().cloned();
+    let problem = match (problem, response.response().error()) {
+        (Some(problem), _) => problem,
+
+        // Messages of errors raised by actix itself describe what's wrong with the request, e.g. which query
+        // parameter failed to parse, but internal errors might leak details
+        (None, Some(why)) if status.is_client_error() => {
+            Problem::new(status, code_of(status), why.to_string())
+        }
+
+        // Error responses of their own format, such as the openchain compatible ones
+        (None, None) if is_json(response.headers()) && !is_empty(response.response().body().size()) => {
+            return response
+        }
+
+        (None, _) if status.is_server_error() || is_empty(response.response().body().size()) => {
+            Problem::of_status(status)
+        }
+
+        (None, _) => return response,
+    };
+
+    let problem = Problem {
+        request_id: Some(request_id.to_string()),
+        ..problem
+    };
+
+    let body = serde_json::to_string(&problem).unwrap();
+    let mut response = response.map_body(|_, _| EitherBody::right(BoxBody::new(body)));
+
+    // The replaced body may have been compressed already, the problem itself isn't
+    response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static(CONTENT_TYPE_PROBLEM));
+    response.headers_mut().remove(header::CONTENT_ENCODING);
+    response
+}
+
+fn is_empty(size: BodySize) -> bool {
+    matches!(size, BodySize::None | BodySize::Sized(0))
+}
+
+/// Returns whether the content type is JSON, i.e. `application/json` or a `+json` suffixed one.
+fn is_json(headers: &header::HeaderMap) -> bool {
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|x| x.to_str().ok()).unwrap_or_default();
+    let media_type = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+
+    media_type == "application/json" || media_type.ends_with("+json")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::problem::attach_request_id;
+    use crate::problem::Problem;
+    use crate::problem::CONTENT_TYPE_PROBLEM;
+    use actix_web::body::BoxBody;
+    use actix_web::http::header;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use actix_web::HttpResponse;
+    use serde_json::json;
+
+    #[test]
+    fn problem_serialization() {
+        let problem = Problem::new(StatusCode::BAD_REQUEST, "invalid_page_size", "Page size must be <= 500")
+            .with_details(json!({ "min": 1, "max": 500 }));
+
+        assert_eq!(
+            serde_json::to_value(&problem).unwrap(),
+            json!({
+                "status": 400,
+                "code": "invalid_page_size",
+                "message": "Page size must be <= 500",
+                "details": { "min": 1, "max": 500 },
+            })
+        );
+
+        let problem = Problem::of_status(StatusCode::NOT_FOUND);
+        assert_eq!(problem.code, "not_found");
+        assert_eq!(problem.message, "Not Found");
+    }
+
+    #[test]
+    fn attach_request_id_keeps_json_error_responses() {
+        let content_type = |response: HttpResponse| {
+            let response = TestRequest::default().to_srv_response(response).map_into_left_body::<BoxBody>();
+            let response = attach_request_id(response, "a");
+            response.headers().get(header::CONTENT_TYPE).unwrap().to_str().unwrap().to_string()
+        };
+
+        // E.g. the openchain compatible `{"ok": false}` responses
+        let response = HttpResponse::InternalServerError().json(json!({ "ok": false }));
+        assert_eq!(content_type(response), "application/json");
+
+        assert_eq!(content_type(HttpResponse::InternalServerError().finish()), CONTENT_TYPE_PROBLEM);
+        assert_eq!(content_type(HttpResponse::InternalServerError().body("oops")), CONTENT_TYPE_PROBLEM);
+    }
+}
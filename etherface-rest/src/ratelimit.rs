@@ -0,0 +1,350 @@
+//! Per-client rate limiting of the REST API.
+//!
+//! Each client is granted a token bucket holding up to [`Quota::burst`] requests, refilled at
+//! [`Quota::per_minute`] requests per minute. Requests exceeding it are answered with `429 Too Many Requests`
+//! and a `Retry-After` header holding the number of seconds until the client may retry, before reaching any
+//! handler (and as such the database).
+//!
+//! Clients sending an API key in the `X-Api-Key` header share one bucket per key with the keys own quota
+//! (see [`ApiKeys`]), whereas all other clients are identified by their IP address and granted the configured
+//! quota (see `Config::rest_rate_limit_per_minute`). Their address is taken from the `X-Forwarded-For` /
+//! `Forwarded` headers only if configured to do so (see `Config::rest_trust_forwarded_for`) as these can be
+//! set by anyone not behind a reverse proxy. Even then only the rightmost entry, i.e. the address appended
+//! by the reverse proxy itself, is used, as clients can prepend arbitrary entries. Route groups, i.e. the
+//! first path segment after the API version (e.g. `/v1/`), may require an API key altogether (see
+//! `Config::rest_api_key_required`).
+
+use crate::apikey::ApiKeys;
+use crate::problem::Problem;
+use actix_web::body::EitherBody;
+use actix_web::dev::forward_ready;
+use actix_web::dev::Service;
+use actix_web::dev::ServiceRequest;
+use actix_web::dev::ServiceResponse;
+use actix_web::dev::Transform;
+use actix_web::http::header;
+use actix_web::http::header::HeaderValue;
+use actix_web::http::StatusCode;
+use actix_web::HttpMessage;
+use actix_web::HttpResponse;
+use serde_json::json;
+use std::collections::HashMap;
+use std::future::ready;
+use std::future::Future;
+use std::future::Ready;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Interval in which the buckets of clients which haven't sent requests for a while are dropped.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Header API keys are sent in.
+const HEADER_API_KEY: &str = "X-Api-Key";
+
+/// Id of the API key a request was sent with, available to handlers as request extension (see
+/// [`actix_web::web::ReqData`]) such that they can identify registered consumers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiKeyId(pub i32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    /// Number of requests per minute, i.e. the rate the bucket is refilled at.
+    pub per_minute: u32,
+
+    /// Number of requests which can be sent at once, i.e. the buckets capacity.
+    pub burst: u32,
+}
+
+impl Quota {
+    fn refill_rate(&self) -> f64 {
+        self.per_minute as f64 / 60.0
+    }
+}
+
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: Mutex<Buckets>,
+}
+
+struct Buckets {
+    entries: HashMap<String, Bucket>,
+    cleaned_up_at: Instant,
+}
+
+impl Default for Buckets {
+    fn default() -> Self {
+        Buckets {
+            entries: HashMap::new(),
+            cleaned_up_at: Instant::now(),
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    quota: Quota,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    /// Returns the number of tokens at `now`, i.e. including the tokens refilled since the last update.
+    fn tokens_at(&self, now: Instant) -> f64 {
+        let refilled = now.duration_since(self.updated_at).as_secs_f64() * self.quota.refill_rate();
+        (self.tokens + refilled).min(self.quota.burst as f64)
+    }
+}
+
+impl RateLimiter {
+    /// Takes a token from the bucket of `key` with the given quota, returning the duration until a token is
+    /// available again if the bucket is empty.
+    pub fn check(&self, key: &str, quota: Quota) -> Result<(), Duration> {
+        self.check_at(key, quota, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, quota: Quota, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        // Buckets which would be full by now are indistinguishable from new ones
+        if now.duration_since(buckets.cleaned_up_at) >= CLEANUP_INTERVAL {
+            buckets.entries.retain(|_, x| x.tokens_at(now) < x.quota.burst as f64);
+            buckets.cleaned_up_at = now;
+        }
+
+        let bucket = buckets.entries.entry(key.to_string()).or_insert(Bucket {
+            tokens: quota.burst as f64,
+            quota,
+            updated_at: now,
+        });
+
+        // The quota of a key may have changed since the bucket was created
+        bucket.quota = quota;
+        bucket.tokens = bucket.tokens_at(now);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / quota.refill_rate()))
+    }
+}
+
+/// Middleware rejecting requests of clients exceeding their quota or lacking a required API key.
+#[derive(Clone)]
+pub struct RateLimit {
+    limiter: Arc<RateLimiter>,
+    api_keys: Arc<ApiKeys>,
+
+    /// Quota of clients without an API key.
+    quota: Quota,
+    trust_forwarded_for: bool,
+
+    /// Route groups requiring an API key, e.g. `export`.
+    api_key_required: Arc<Vec<String>>,
+}
+
+impl RateLimit {
+    pub fn new(
+        quota: Quota,
+        trust_forwarded_for: bool,
+        api_keys: Arc<ApiKeys>,
+        api_key_required: Vec<String>,
+    ) -> Self {
+        RateLimit {
+            limiter: Arc::new(RateLimiter::default()),
+            api_keys,
+            quota,
+            trust_forwarded_for,
+            api_key_required: Arc::new(api_key_required),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service,
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+    config: RateLimit,
+}
+
+impl<S> RateLimitMiddleware<S> {
+    /// Returns the bucket, quota and API key id of the requests client, or the reason it's rejected.
+    fn client(&self, req: &ServiceRequest) -> Result<(String, Quota, Option<i32>), Problem> {
+        let config = &self.config;
+
+        if let Some(key) = req.headers().get(HEADER_API_KEY) {
+            let key = key.to_str().ok().and_then(|key| config.api_keys.lookup(key));
+            return match key {
+                Some(key) => Ok((format!("key:{}", key.id), key.quota, Some(key.id))),
+                None => {
+                    let message = "Invalid or revoked API key";
+                    Err(Problem::new(StatusCode::UNAUTHORIZED, "invalid_api_key", message))
+                }
+            };
+        }
+
+        // Paths are of the form `/{version}/{group}/..`
+        let group = req.path().split('/').nth(2).unwrap_or_default();
+        if config.api_key_required.iter().any(|x| x == group) {
+            return Err(Problem::new(StatusCode::UNAUTHORIZED, "api_key_required", "API key required"));
+        }
+
+        let address = client_address(req, config.trust_forwarded_for);
+        Ok((address.unwrap_or_else(|| "unknown".to_string()), config.quota, None))
+    }
+}
+
+/// Returns the IP address of the requests client, taken from the rightmost `X-Forwarded-For` / `Forwarded`
+/// entry if `trust_forwarded_for` is set and either header is present.
+pub(crate) fn client_address(req: &ServiceRequest, trust_forwarded_for: bool) -> Option<String> {
+    let forwarded_for = match trust_forwarded_for {
+        true => rightmost_forwarded_for(req.headers()),
+        false => None,
+    };
+
+    forwarded_for.or_else(|| req.peer_addr().map(|x| x.ip().to_string()))
+}
+
+/// Returns the last address of the `X-Forwarded-For` header, or if absent the last `for` parameter of the
+/// `Forwarded` header without its port, e.g. `2001:db8::1` for `for="[2001:db8::1]:4711"`.
+fn rightmost_forwarded_for(headers: &header::HeaderMap) -> Option<String> {
+    // Proxies may append further header lines instead of extending the last one
+    let last = |name| {
+        let values = headers.get_all(name).filter_map(|x| x.to_str().ok()).collect::<Vec<_>>();
+        values.join(",").rsplit(',').map(str::trim).find(|x| !x.is_empty()).map(str::to_string)
+    };
+
+    if let Some(address) = last(header::HeaderName::from_static("x-forwarded-for")) {
+        return Some(address);
+    }
+
+    let element = last(header::FORWARDED)?;
+    let node = element.split(';').find_map(|pair| {
+        let (name, value) = pair.trim().split_once('=')?;
+        name.eq_ignore_ascii_case("for").then(|| value.trim_matches('"'))
+    })?;
+
+    let address = match node.strip_prefix('[') {
+        Some(node) => node.split(']').next().unwrap_or_default(),
+        None if node.matches(':').count() == 1 => node.split(':').next().unwrap_or_default(),
+        None => node,
+    };
+
+    Some(address.to_string())
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let response = match self.client(&req) {
+            Ok((bucket, quota, api_key)) => match self.config.limiter.check(&bucket, quota) {
+                Ok(()) => {
+                    if let Some(id) = api_key {
+                        self.config.api_keys.record(id);
+                        req.extensions_mut().insert(ApiKeyId(id));
+                    }
+
+                    let response = self.service.call(req);
+                    return Box::pin(async move { Ok(response.await?.map_into_left_body()) });
+                }
+
+                // Rounded up, as retrying after the rounded down duration would be rejected again
+                Err(retry_after) => {
+                    let retry_after = retry_after.as_secs_f64().ceil() as u64;
+                    let message = "Rate limit exceeded";
+                    let problem = Problem::new(StatusCode::TOO_MANY_REQUESTS, "rate_limited", message)
+                        .with_details(json!({ "retry_after": retry_after }));
+
+                    let mut response = HttpResponse::from(problem);
+                    response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+                    response
+                }
+            },
+
+            Err(problem) => problem.into(),
+        };
+
+        Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ratelimit::client_address;
+    use crate::ratelimit::Quota;
+    use crate::ratelimit::RateLimiter;
+    use actix_web::test::TestRequest;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let limiter = RateLimiter::default();
+        let quota = Quota {
+            per_minute: 60,
+            burst: 2,
+        };
+        let now = Instant::now();
+
+        assert!(limiter.check_at("a", quota, now).is_ok());
+        assert!(limiter.check_at("a", quota, now).is_ok());
+        assert_eq!(limiter.check_at("a", quota, now), Err(Duration::from_secs(1)));
+
+        // Clients have separate buckets
+        assert!(limiter.check_at("b", quota, now).is_ok());
+
+        assert!(limiter.check_at("a", quota, now + Duration::from_secs(1)).is_ok());
+        assert!(limiter.check_at("a", quota, now + Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn client_address_from_rightmost_forwarded_entry() {
+        let peer = "10.0.0.1:443".parse().unwrap();
+        let address = |name, value, trust_forwarded_for| {
+            let req = TestRequest::default().peer_addr(peer).insert_header((name, value)).to_srv_request();
+            client_address(&req, trust_forwarded_for)
+        };
+
+        // The leftmost entries are sent by the client and as such spoofable
+        let spoofed = "1.1.1.1, 203.0.113.7";
+        assert_eq!(address("X-Forwarded-For", spoofed, true), Some("203.0.113.7".to_string()));
+        assert_eq!(address("X-Forwarded-For", spoofed, false), Some("10.0.0.1".to_string()));
+
+        let forwarded = "for=1.1.1.1, for=\"[2001:db8::1]:4711\";proto=https";
+        assert_eq!(address("Forwarded", forwarded, true), Some("2001:db8::1".to_string()));
+        assert_eq!(address("Forwarded", "for=203.0.113.7:80", true), Some("203.0.113.7".to_string()));
+        assert_eq!(address("X-Request-Id", "a", true), Some("10.0.0.1".to_string()));
+    }
+}
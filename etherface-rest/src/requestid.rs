@@ -0,0 +1,171 @@
+//! Request IDs and structured access logs of the REST API.
+//!
+//! Each request is assigned an ID, taken from its `X-Request-Id` header if it holds a sane one (e.g. set by
+//! a reverse proxy) or generated otherwise, which is returned in the `X-Request-Id` response header. All
+//! records emitted while handling a request are attached to a `request` span carrying its ID, and once
+//! answered an access log record with its method, path, route, status, duration and client is emitted, such
+//! that a request reported by a user can be traced in the server logs. Error responses additionally state the
+//! ID in their body (see `problem.rs`) as users rarely look at headers.
+
+use crate::problem::attach_request_id;
+use crate::ratelimit::client_address;
+use actix_web::body::EitherBody;
+use actix_web::body::MessageBody;
+use actix_web::dev::forward_ready;
+use actix_web::dev::Service;
+use actix_web::dev::ServiceRequest;
+use actix_web::dev::ServiceResponse;
+use actix_web::dev::Transform;
+use actix_web::http::header;
+use actix_web::http::header::HeaderName;
+use actix_web::http::header::HeaderValue;
+use std::future::ready;
+use std::future::Future;
+use std::future::Ready;
+use std::pin::Pin;
+use std::time::Instant;
+use tracing::info;
+use tracing::info_span;
+use tracing::Instrument;
+
+/// Header request IDs are accepted from and returned in.
+const HEADER_REQUEST_ID: &str = "x-request-id";
+
+/// Maximum length of request IDs accepted from clients.
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+/// Middleware assigning request IDs and emitting access logs.
+#[derive(Clone)]
+pub struct RequestTracing {
+    /// Whether the clients address is taken from the `Forwarded` / `X-Forwarded-For` headers.
+    trust_forwarded_for: bool,
+}
+
+impl RequestTracing {
+    pub fn new(trust_forwarded_for: bool) -> Self {
+        RequestTracing { trust_forwarded_for }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTracing
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = RequestTracingMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTracingMiddleware {
+            service,
+            trust_forwarded_for: self.trust_forwarded_for,
+        }))
+    }
+}
+
+pub struct RequestTracingMiddleware<S> {
+    service: S,
+    trust_forwarded_for: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTracingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let id = request_id(req.headers().get(HEADER_REQUEST_ID).and_then(|x| x.to_str().ok()));
+        let span = info_span!("request", request_id = %id);
+
+        let method = req.method().to_string();
+        let path = req.path().to_string();
+        let client = client_address(&req, self.trust_forwarded_for).unwrap_or_else(|| "unknown".to_string());
+        let user_agent = req.headers().get(header::USER_AGENT).and_then(|x| x.to_str().ok());
+        let user_agent = user_agent.unwrap_or_default().to_string();
+
+        let http_request = req.request().clone();
+        let started_at = Instant::now();
+        let response = span.in_scope(|| self.service.call(req));
+
+        Box::pin(async move {
+            // Errors are only turned into responses by actix after passing all middlewares, hence converted
+            // here already such that they carry the request ID and show up in the access log as well
+            let response = match response.instrument(span.clone()).await {
+                Ok(response) => response.map_into_left_body(),
+                Err(why) => ServiceResponse::from_err(why, http_request).map_into_right_body(),
+            };
+
+            let status = response.status();
+            let mut response = attach_request_id(response, &id);
+
+            // Request IDs only consist of visible ASCII characters, hence always valid header values
+            if let Ok(value) = HeaderValue::from_str(&id) {
+                response.headers_mut().insert(HeaderName::from_static(HEADER_REQUEST_ID), value);
+            }
+
+            let route = response.request().match_pattern().unwrap_or_else(|| "unmatched".to_string());
+            span.in_scope(|| {
+                info!(
+                    method = %method,
+                    path = %path,
+                    route = %route,
+                    status = status.as_u16(),
+                    duration_ms = started_at.elapsed().as_millis() as u64,
+                    client = %client,
+                    user_agent = %user_agent,
+                    "Answered request"
+                )
+            });
+
+            Ok(response)
+        })
+    }
+}
+
+/// Returns the request ID sent by the client if it's non-empty, at most [`MAX_REQUEST_ID_LENGTH`] characters
+/// long and only consists of alphanumerics, `-` and `_`, a newly generated one otherwise. Anything else is
+/// rejected as it would end up verbatim in log records.
+fn request_id(sent: Option<&str>) -> String {
+    let is_valid = |id: &str| {
+        !id.is_empty()
+            && id.len() <= MAX_REQUEST_ID_LENGTH
+            && id.chars().all(|x| x.is_ascii_alphanumeric() || x == '-' || x == '_')
+    };
+
+    match sent {
+        Some(id) if is_valid(id) => id.to_string(),
+        _ => format!("{:032x}", rand::random::<u128>()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::requestid::request_id;
+
+    #[test]
+    fn request_id_sent_or_generated() {
+        let sent = "f81d4fae-7dec-11d0-a765-00a0c91e6bf6";
+        assert_eq!(request_id(Some(sent)), sent);
+
+        let generated = request_id(None);
+        assert_eq!(generated.len(), 32);
+        assert_ne!(generated, request_id(None));
+
+        // Neither empty, overly long or containing anything that could forge log records
+        assert_eq!(request_id(Some("")).len(), 32);
+        assert_eq!(request_id(Some(&"a".repeat(65))).len(), 32);
+        assert_eq!(request_id(Some("abc\ndef")).len(), 32);
+        assert_eq!(request_id(Some("abc def")).len(), 32);
+    }
+}
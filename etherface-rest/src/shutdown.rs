@@ -0,0 +1,79 @@
+//! Graceful shutdown of the REST API.
+//!
+//! On `SIGTERM` or `SIGINT` the server stops accepting connections and waits up to
+//! `Config::rest_shutdown_timeout` seconds for in-flight requests to be answered before dropping them, such
+//! that deploys don't surface errors to clients mid-request. Signature streams never complete by themselves
+//! and would hold up the shutdown until the timeout, hence they are ended as soon as the shutdown begins (see
+//! [`Shutdown::wait`]); clients simply reconnect to the next instance. A second signal drops all in-flight
+//! requests immediately.
+
+use actix_web::dev::ServerHandle;
+use log::error;
+use log::info;
+use log::warn;
+use std::sync::Arc;
+use tokio::signal::unix::signal;
+use tokio::signal::unix::Signal;
+use tokio::signal::unix::SignalKind;
+use tokio::sync::watch;
+
+/// Notifies long-lived requests of the shutdown.
+#[derive(Clone)]
+pub struct Shutdown {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Shutdown {
+            sender: Arc::new(watch::channel(false).0),
+        }
+    }
+}
+
+impl Shutdown {
+    /// Resolves once the shutdown began.
+    pub async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        while !*receiver.borrow_and_update() {
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    fn begin(&self) {
+        self.sender.send_replace(true);
+    }
+}
+
+/// Waits for `SIGTERM` / `SIGINT`, then stops the `server` gracefully.
+pub async fn on_signal(server: ServerHandle, shutdown: Shutdown) {
+    let signals = (signal(SignalKind::terminate()), signal(SignalKind::interrupt()));
+    let (mut terminate, mut interrupt) = match signals {
+        (Ok(terminate), Ok(interrupt)) => (terminate, interrupt),
+        (Err(why), _) | (_, Err(why)) => {
+            error!("Failed to listen for shutdown signals, stop the server with SIGKILL instead; {why}");
+            return;
+        }
+    };
+
+    received(&mut terminate, &mut interrupt).await;
+    info!("Shutting down, waiting for in-flight requests to be answered");
+    shutdown.begin();
+
+    tokio::select! {
+        _ = server.stop(true) => (),
+        _ = received(&mut terminate, &mut interrupt) => {
+            warn!("Received second shutdown signal, dropping in-flight requests");
+            server.stop(false).await;
+        }
+    }
+}
+
+async fn received(terminate: &mut Signal, interrupt: &mut Signal) {
+    tokio::select! {
+        _ = terminate.recv() => (),
+        _ = interrupt.recv() => (),
+    }
+}
@@ -0,0 +1,50 @@
+//! Broadcasts newly found signatures to the clients of the `/v1/stream/signatures` endpoint.
+//!
+//! A single [`SignatureListener`] is polled on a dedicated thread, forwarding each signature to all
+//! subscribed clients. Clients lagging behind by more than [`CHANNEL_CAPACITY`] signatures skip the missed
+//! ones rather than slowing down the others.
+
+use etherface_lib::database::listener::SignatureListener;
+use etherface_lib::error::Error;
+use etherface_lib::model::StreamedSignature;
+use log::error;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Number of signatures buffered per client.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Interval in which the listener is polled for new signatures.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Interval after which the listener reconnects if its connection failed.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Spawns the listener thread and returns the sender clients subscribe to.
+pub fn spawn() -> broadcast::Sender<StreamedSignature> {
+    let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+    let listener_sender = sender.clone();
+    std::thread::spawn(move || loop {
+        if let Err(err) = listen(&listener_sender) {
+            error!("Signature listener failed, reconnecting in {}s; {err}", RECONNECT_INTERVAL.as_secs());
+        }
+
+        std::thread::sleep(RECONNECT_INTERVAL);
+    });
+
+    sender
+}
+
+fn listen(sender: &broadcast::Sender<StreamedSignature>) -> Result<(), Error> {
+    let mut listener = SignatureListener::new()?;
+
+    loop {
+        for entity in listener.poll()? {
+            // Only fails if no client is subscribed, in which case there's no one to send the signature to
+            let _ = sender.send(entity);
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,110 @@
+//! TLS with hot reloaded certificates.
+//!
+//! Certificates are renewed externally (e.g. by certbot), as such the certificate files are polled for
+//! changes every [`RELOAD_INTERVAL`] and swapped in for all subsequent handshakes without restarting the
+//! server. Each poll additionally checks the certificates expiry date, reporting certificates expiring within
+//! [`EXPIRY_WARNING_DAYS`] days (see `etherface_lib::report`) such that failed renewals don't go unnoticed
+//! until the API is unreachable.
+
+use etherface_lib::config::TlsPaths;
+use etherface_lib::report;
+use log::error;
+use log::info;
+use openssl::asn1::Asn1Time;
+use openssl::error::ErrorStack;
+use openssl::ssl::SniError;
+use openssl::ssl::SslAcceptor;
+use openssl::ssl::SslAcceptorBuilder;
+use openssl::ssl::SslContext;
+use openssl::ssl::SslFiletype;
+use openssl::ssl::SslMethod;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+/// Interval in which the certificate files are checked for changes.
+const RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Number of days before its expiry from which on a certificate is reported.
+const EXPIRY_WARNING_DAYS: u32 = 14;
+
+/// Interval in which an expiring certificate is reported again.
+const EXPIRY_REPORT_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Returns an acceptor serving the certificate of `tls`, reloading it whenever its files change.
+pub fn acceptor(tls: &TlsPaths) -> Result<SslAcceptorBuilder, ErrorStack> {
+    let context = Arc::new(RwLock::new(load(tls)?.build().into_context()));
+
+    // Handshakes start with the initially loaded certificate, which is replaced by the current one as soon as
+    // the client hello was received
+    let mut builder = load(tls)?;
+    let callback_context = context.clone();
+    builder.set_servername_callback(move |ssl, _| {
+        ssl.set_ssl_context(&callback_context.read().unwrap()).map_err(|_| SniError::ALERT_FATAL)
+    });
+
+    let tls = tls.clone();
+    std::thread::spawn(move || watch(&tls, &context));
+
+    Ok(builder)
+}
+
+/// Returns an acceptor builder with the certificate chain and private key of `tls`.
+fn load(tls: &TlsPaths) -> Result<SslAcceptorBuilder, ErrorStack> {
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
+    builder.set_private_key_file(&tls.private_key, SslFiletype::PEM)?;
+    builder.set_certificate_chain_file(&tls.certificate)?;
+    builder.check_private_key()?;
+
+    Ok(builder)
+}
+
+/// Returns the modification dates of the certificate files, `None` if either can't be read.
+fn modified(tls: &TlsPaths) -> Option<(SystemTime, SystemTime)> {
+    let certificate = std::fs::metadata(&tls.certificate).and_then(|x| x.modified()).ok()?;
+    let private_key = std::fs::metadata(&tls.private_key).and_then(|x| x.modified()).ok()?;
+
+    Some((certificate, private_key))
+}
+
+/// Returns whether the certificate of `context` expires within [`EXPIRY_WARNING_DAYS`] days.
+fn is_expiring(context: &SslContext) -> Result<bool, ErrorStack> {
+    let threshold = Asn1Time::days_from_now(EXPIRY_WARNING_DAYS)?;
+    Ok(context.certificate().map_or(false, |certificate| certificate.not_after() < threshold))
+}
+
+fn watch(tls: &TlsPaths, context: &RwLock<SslContext>) {
+    let mut last_modified = modified(tls);
+    let mut last_expiry_report: Option<Instant> = None;
+
+    loop {
+        std::thread::sleep(RELOAD_INTERVAL);
+
+        let current_modified = modified(tls);
+        if current_modified.is_some() && current_modified != last_modified {
+            // Renewals may replace the files one after another, in which case the next poll succeeds
+            match load(tls) {
+                Ok(builder) => {
+                    *context.write().unwrap() = builder.build().into_context();
+                    last_modified = current_modified;
+                    last_expiry_report = None;
+                    info!("Reloaded TLS certificate {}", tls.certificate);
+                }
+
+                Err(why) => error!("Failed to reload TLS certificate {}; {why}", tls.certificate),
+            }
+        }
+
+        let is_expiring = is_expiring(&context.read().unwrap()).unwrap_or(false);
+        if is_expiring && last_expiry_report.map_or(true, |x| x.elapsed() >= EXPIRY_REPORT_INTERVAL) {
+            report::report(
+                &format!("TLS certificate expires within {EXPIRY_WARNING_DAYS} days, renewal likely failed"),
+                &[("certificate", tls.certificate.clone())],
+            );
+
+            last_expiry_report = Some(Instant::now());
+        }
+    }
+}
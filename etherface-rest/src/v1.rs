@@ -1,15 +1,62 @@
+use crate::cache::ResponseCache;
+use crate::problem;
+use crate::problem::Problem;
+use crate::ratelimit::ApiKeyId;
+use crate::shutdown::Shutdown;
+use actix_web::delete;
 use actix_web::get;
+use actix_web::http::header::ContentEncoding;
+use actix_web::http::header::HeaderValue;
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::http::StatusCode;
+use actix_web::post;
 use actix_web::web;
+use actix_web::web::Bytes;
 use actix_web::HttpResponse;
 use actix_web::Responder;
+use chrono::DateTime;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use chrono::Utc;
+use etherface_lib::api::rpc::RpcClient;
+use etherface_lib::api::webhook;
+use etherface_lib::database::handler::rest::ExportFormat;
+use etherface_lib::database::handler::rest::SignatureSort;
+use etherface_lib::database::handler::rest::SortOrder;
+use etherface_lib::database::handler::rest::SourceSort;
+use etherface_lib::database::handler::rest::EXPORT_CSV_HEADER;
 use etherface_lib::database::handler::DatabaseClientPooled;
+use etherface_lib::error::Error;
+use etherface_lib::model::decode_hash;
+use etherface_lib::model::views::ViewEtherscanChainDistribution;
+use etherface_lib::model::views::ViewEtherscanCompilerVersionDistribution;
+use etherface_lib::model::views::ViewRepositoriesTopUniqueSignatures;
 use etherface_lib::model::views::ViewSignatureCountStatistics;
 use etherface_lib::model::views::ViewSignatureInsertRate;
+use etherface_lib::model::views::ViewSignatureInsertRateBySource;
 use etherface_lib::model::views::ViewSignatureKindDistribution;
+use etherface_lib::model::views::ViewSignaturesPerContractDistribution;
 use etherface_lib::model::views::ViewSignaturesPopularOnGithub;
+use etherface_lib::model::FlagReason;
+use etherface_lib::model::SignatureFlagInsert;
 use etherface_lib::model::SignatureKind;
+use etherface_lib::model::SignatureSource;
+use etherface_lib::model::SignatureWithMetadata;
+use etherface_lib::model::StreamedSignature;
+use etherface_lib::model::WatchlistEntry;
+use etherface_lib::model::WatchlistEntryInsert;
+use etherface_lib::model::Webhook;
+use etherface_lib::model::WebhookInsert;
+use etherface_lib::parser;
+use log::error;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::json;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
 
 #[derive(Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -20,6 +67,45 @@ pub enum Kind {
     Error,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    All,
+    Github,
+    Etherscan,
+    Fourbyte,
+}
+
+/// Value of the `sort` query parameter, not every key applying to every endpoint.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    AddedAt,
+    Stars,
+    Popularity,
+    Text,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(Deserialize)]
+pub struct SourceQuery {
+    source: Option<Source>,
+    sort: Option<SortKey>,
+    order: Option<Order>,
+}
+
+#[derive(Deserialize)]
+pub struct SortQuery {
+    sort: Option<SortKey>,
+    order: Option<Order>,
+}
+
 #[derive(Deserialize)]
 pub struct ContentPath {
     input: String,
@@ -27,6 +113,11 @@ pub struct ContentPath {
     page: i64,
 }
 
+#[derive(Deserialize)]
+pub struct ChainQuery {
+    chain_id: Option<i32>,
+}
+
 #[derive(Deserialize)]
 pub struct SourcePath {
     signature_id: i32,
@@ -34,17 +125,224 @@ pub struct SourcePath {
     page: i64,
 }
 
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    q: Option<String>,
+    kind: Option<Kind>,
+    source: Option<SignatureSource>,
+    page: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct RecentQuery {
+    limit: Option<i64>,
+    kind: Option<Kind>,
+    source: Option<SignatureSource>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Ndjson,
+    Csv,
+}
+
+#[derive(Deserialize)]
+pub struct ExportPath {
+    format: Format,
+}
+
+/// Cursor of the `/export/signatures` endpoints. Unknown parameters (e.g. a bare `since`) are rejected rather
+/// than ignored, as ignoring them would restart the export from the first signature.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportQuery {
+    /// Id of the last exported signature, i.e. the `X-Next-Since-Id` header of the previous response.
+    since_id: Option<i32>,
+
+    /// Date (`YYYY-MM-DD` or RFC 3339) the exported signatures were added at or after.
+    since_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DecodeBody {
+    /// Hex encoded data, optionally prefixed by `0x`.
+    data: String,
+}
+
+#[derive(Deserialize)]
+pub struct SubmissionBody {
+    signatures: Vec<SubmittedSignature>,
+}
+
+#[derive(Deserialize)]
+pub struct SubmittedSignature {
+    /// Signature text, e.g. `transfer(address to, uint256 amount)`; canonicalized before being stored.
+    text: String,
+    kind: SignatureKind,
+}
+
+#[derive(Deserialize)]
+pub struct FlagBody {
+    kind: SignatureKind,
+
+    /// Source whose mappings are flagged, all sources if not given.
+    source: Option<SignatureSource>,
+
+    /// Repository respectively contract whose mapping is flagged, only for GitHub and Etherscan.
+    source_id: Option<i32>,
+    reason: FlagReason,
+    comment: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WatchlistBody {
+    /// Function / error selector or event topic, hex encoded and optionally prefixed by `0x`.
+    hash: String,
+
+    /// URL the entry is POSTed to once resolved, see `etherface/src/notifier.rs`.
+    webhook_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookBody {
+    /// URL newly found signatures are POSTed to, see `etherface/src/dispatcher.rs`.
+    url: String,
+
+    /// Only signatures of this kind are delivered, all if not given.
+    kind: Option<SignatureKind>,
+
+    /// Only signatures whose text starts with this prefix are delivered, e.g. `transfer`.
+    prefix: Option<String>,
+
+    /// Only signatures found within this source are delivered, all if not given.
+    source: Option<SignatureSource>,
+}
+
+#[derive(Deserialize)]
+pub struct StreamQuery {
+    kind: Option<Kind>,
+    prefix: Option<String>,
+}
+
 pub struct AppState {
     pub dbc: DatabaseClientPooled,
+
+    /// Newly found signatures, see `stream.rs`.
+    pub signatures: broadcast::Sender<StreamedSignature>,
+
+    /// Ends signature streams on shutdown, see `shutdown.rs`.
+    pub shutdown: Shutdown,
+
+    /// Responses of the hash endpoints, see `cache.rs`.
+    pub cache: ResponseCache,
+
+    /// JSON-RPC client transactions are fetched with, see [`decode_transaction`].
+    pub rpc: Option<Arc<RpcClient>>,
 }
 
+/// Number of signatures returned by the `/signatures/recent` endpoint if no limit is given.
+const DEFAULT_RECENT_LIMIT: i64 = 50;
+
+/// Maximum number of signatures returned by the `/signatures/recent` endpoint.
+const MAX_RECENT_LIMIT: i64 = 500;
+
+/// Maximum length of `/signatures/search` queries, bounding the number of trigrams compared per signature.
+const MAX_SEARCH_QUERY_LENGTH: usize = 256;
+
+/// Maximum number of signatures submitted per request to the `POST /signatures` endpoint.
+const MAX_SUBMISSIONS: usize = 100;
+
+/// Maximum length of comments of the `POST /signatures/{id}/flags` endpoint.
+const MAX_FLAG_COMMENT_LENGTH: usize = 1000;
+
+/// Maximum number of entries of a consumers watchlist.
+const MAX_WATCHLIST_ENTRIES: i64 = 1000;
+
+/// Maximum number of webhooks registered per consumer.
+const MAX_WEBHOOKS: i64 = 10;
+
+/// Maximum length of the prefix filter of webhooks.
+const MAX_WEBHOOK_PREFIX_LENGTH: usize = 256;
+
+/// Message of the problem returned for webhook URLs rejected by [`is_valid_webhook_url`].
+const INVALID_WEBHOOK_URL_MESSAGE: &str = "Webhook URL must be an absolute https URL of a public host";
+
+/// Maximum number of signatures returned by one request of the `/export/signatures` endpoints.
+const EXPORT_LIMIT: i64 = 50_000;
+
+/// `Cache-Control` of `/selector` responses; known selectors rarely change their decoding whereas unknown
+/// ones may be found any time.
+const SELECTOR_CACHE_CONTROL_KNOWN: &str = "public, max-age=86400, stale-while-revalidate=604800";
+const SELECTOR_CACHE_CONTROL_UNKNOWN: &str = "public, max-age=300";
+
 #[inline]
-fn is_valid_page_index(index: i64) -> bool {
+pub(crate) fn is_valid_page_index(index: i64) -> bool {
     index >= 1
 }
 
+/// Runs the blocking database `query` on actix's blocking thread pool rather than the async worker threads,
+/// responding with its serialized result, `404` if it returned nothing or `500` if it failed.
+pub(crate) async fn run_query<F, T>(state: web::Data<AppState>, query: F) -> HttpResponse
+where
+    F: FnOnce(&DatabaseClientPooled) -> Result<Option<T>, Error> + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    match web::block(move || query(&state.dbc)).await {
+        Ok(Ok(Some(content))) => HttpResponse::Ok().body(serde_json::to_string(&content).unwrap()),
+        Ok(Ok(None)) => problem::of_status(StatusCode::NOT_FOUND),
+        Ok(Err(why)) => query_failed(why),
+        Err(why) => query_failed(why),
+    }
+}
+
+/// Logs why a database query failed, responding with `500`. Logged within the requests span, i.e. along its
+/// request ID (see [`crate::requestid`]).
+fn query_failed(why: impl std::fmt::Display) -> HttpResponse {
+    error!("Query failed; {why}");
+    problem::of_status(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Same as [`run_query`] but serving the response from the [`ResponseCache`] under `key` if present, caching
+/// it otherwise. Failed queries aren't cached.
+async fn run_cached_query<F, T>(state: web::Data<AppState>, key: String, query: F) -> HttpResponse
+where
+    F: FnOnce(&DatabaseClientPooled) -> Result<Option<T>, Error> + Send + 'static,
+    T: Serialize + Send + 'static,
+{
+    let response = match state.cache.get(&key) {
+        Some(response) => response,
+        None => {
+            let query_state = state.clone();
+            let response = match web::block(move || query(&query_state.dbc)).await {
+                Ok(Ok(content)) => content.map(|x| serde_json::to_string(&x).unwrap()),
+                Ok(Err(why)) => return query_failed(why),
+                Err(why) => return query_failed(why),
+            };
+
+            state.cache.insert(key, response.clone());
+            response
+        }
+    };
+
+    match response {
+        Some(content) => HttpResponse::Ok().body(content),
+        None => problem::of_status(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Parses the `since_date` parameter of the `/export/signatures` endpoints, either a day (`YYYY-MM-DD`,
+/// starting at midnight UTC) or an RFC 3339 timestamp.
+fn parse_since_date(since: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?));
+    }
+
+    DateTime::parse_from_rfc3339(since).ok().map(|date| date.with_timezone(&Utc))
+}
+
 #[inline]
-fn query_kind_to_signaturekind(kind: &Kind) -> Option<SignatureKind> {
+pub(crate) fn query_kind_to_signaturekind(kind: &Kind) -> Option<SignatureKind> {
     match kind {
         Kind::All => None,
         Kind::Function => Some(SignatureKind::Function),
@@ -53,28 +351,155 @@ fn query_kind_to_signaturekind(kind: &Kind) -> Option<SignatureKind> {
     }
 }
 
+#[inline]
+fn query_source_to_signaturesource(source: &Option<Source>) -> Option<SignatureSource> {
+    match source {
+        None | Some(Source::All) => None,
+        Some(Source::Github) => Some(SignatureSource::Github),
+        Some(Source::Etherscan) => Some(SignatureSource::Etherscan),
+        Some(Source::Fourbyte) => Some(SignatureSource::Fourbyte),
+    }
+}
+
+#[inline]
+pub(crate) fn query_order_to_sortorder(order: &Option<Order>) -> Option<SortOrder> {
+    match order {
+        None => None,
+        Some(Order::Asc) => Some(SortOrder::Asc),
+        Some(Order::Desc) => Some(SortOrder::Desc),
+    }
+}
+
+/// Returns the sort key of signature endpoints, responding with `400` if signatures can't be sorted by it.
+fn query_sort_to_signaturesort(sort: &Option<SortKey>) -> Result<Option<SignatureSort>, HttpResponse> {
+    match sort {
+        None => Ok(None),
+        Some(SortKey::AddedAt) => Ok(Some(SignatureSort::Added)),
+        Some(SortKey::Popularity) => Ok(Some(SignatureSort::Usage)),
+        Some(SortKey::Text) => Ok(Some(SignatureSort::Text)),
+        Some(SortKey::Stars) => {
+            let message = "Signatures can be sorted by either added_at, popularity or text";
+            Err(problem::bad_request("invalid_sort", message))
+        }
+    }
+}
+
 #[get("/signatures/text/{kind}/{input}/{page}")]
-async fn signatures_by_text(path: web::Path<ContentPath>, state: web::Data<AppState>) -> impl Responder {
+async fn signatures_by_text(
+    path: web::Path<ContentPath>,
+    query: web::Query<SourceQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
     if !is_valid_page_index(path.page) {
-        return HttpResponse::BadRequest().body("Page index must be >= 1");
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
     }
 
     let input_trimmed = path.input.trim();
     if input_trimmed.len() < 3 {
-        return HttpResponse::BadRequest().body("Query must have at least 3 characters");
+        return problem::bad_request("query_too_short", "Query must have at least 3 characters");
+    }
+
+    let (input, kind, page) = (input_trimmed.to_string(), query_kind_to_signaturekind(&path.kind), path.page);
+    let source = query_source_to_signaturesource(&query.source);
+    let sort = match query_sort_to_signaturesort(&query.sort) {
+        Ok(sort) => sort,
+        Err(response) => return response,
+    };
+    let order = query_order_to_sortorder(&query.order);
+    run_query(state, move |dbc| {
+        dbc.rest().signatures_where_text_starts_with(&input, kind, source, sort, order, page)
+    })
+    .await
+}
+
+#[get("/signatures/text/contains/{kind}/{input}/{page}")]
+async fn signatures_by_text_contains(
+    path: web::Path<ContentPath>,
+    query: web::Query<SourceQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    if !is_valid_page_index(path.page) {
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
+    }
+
+    let input_trimmed = path.input.trim();
+    if input_trimmed.len() < 3 {
+        return problem::bad_request("query_too_short", "Query must have at least 3 characters");
+    }
+
+    let (input, kind, page) = (input_trimmed.to_string(), query_kind_to_signaturekind(&path.kind), path.page);
+    let source = query_source_to_signaturesource(&query.source);
+    let sort = match query_sort_to_signaturesort(&query.sort) {
+        Ok(sort) => sort,
+        Err(response) => return response,
+    };
+    let order = query_order_to_sortorder(&query.order);
+    run_query(state, move |dbc| {
+        dbc.rest().signatures_where_text_contains(&input, kind, source, sort, order, page)
+    })
+    .await
+}
+
+#[get("/signatures/text/similar/{kind}/{input}/{page}")]
+async fn signatures_by_text_similar(
+    path: web::Path<ContentPath>,
+    query: web::Query<SourceQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    if !is_valid_page_index(path.page) {
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
+    }
+
+    let input_trimmed = path.input.trim();
+    if input_trimmed.len() < 3 {
+        return problem::bad_request("query_too_short", "Query must have at least 3 characters");
+    }
+
+    let (input, kind, page) = (input_trimmed.to_string(), query_kind_to_signaturekind(&path.kind), path.page);
+    let source = query_source_to_signaturesource(&query.source);
+    let sort = match query_sort_to_signaturesort(&query.sort) {
+        Ok(sort) => sort,
+        Err(response) => return response,
+    };
+    let order = query_order_to_sortorder(&query.order);
+    run_query(state, move |dbc| {
+        dbc.rest().signatures_where_text_similar_to(&input, kind, source, sort, order, page)
+    })
+    .await
+}
+
+/// Typo-tolerant search, see `RestHandler::signatures_search_fuzzy`.
+#[get("/signatures/search")]
+async fn signatures_search(query: web::Query<SearchQuery>, state: web::Data<AppState>) -> impl Responder {
+    let page = query.page.unwrap_or(1);
+    if !is_valid_page_index(page) {
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
+    }
+
+    let input = query.q.as_deref().map(str::trim).unwrap_or_default();
+    if input.len() < 3 {
+        return problem::bad_request("query_too_short", "Query must have at least 3 characters");
     }
 
-    let kind = query_kind_to_signaturekind(&path.kind);
-    match state.dbc.rest().signatures_where_text_starts_with(&input_trimmed, kind, path.page) {
-        Some(signatures) => HttpResponse::Ok().body(serde_json::to_string(&signatures).unwrap()),
-        None => HttpResponse::NotFound().finish(),
+    if input.len() > MAX_SEARCH_QUERY_LENGTH {
+        let message = format!("Query must have at most {MAX_SEARCH_QUERY_LENGTH} characters");
+        return problem::bad_request("query_too_long", message);
     }
+
+    let input = input.to_string();
+    let kind = query.kind.as_ref().and_then(query_kind_to_signaturekind);
+    let source = query.source;
+    run_query(state, move |dbc| dbc.rest().signatures_search_fuzzy(&input, kind, source, page)).await
 }
 
 #[get("/signatures/hash/{kind}/{input}/{page}")]
-async fn signatures_by_hash(path: web::Path<ContentPath>, state: web::Data<AppState>) -> impl Responder {
+async fn signatures_by_hash(
+    path: web::Path<ContentPath>,
+    query: web::Query<SourceQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
     if !is_valid_page_index(path.page) {
-        return HttpResponse::BadRequest().body("Page index must be >= 1");
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
     }
 
     let mut input_trimmed = path.input.trim();
@@ -83,40 +508,583 @@ async fn signatures_by_hash(path: web::Path<ContentPath>, state: web::Data<AppSt
     }
 
     if input_trimmed.len() != 8 && input_trimmed.len() != 64 {
-        return HttpResponse::BadRequest().body("Query must have 8 or 64 characters");
+        return problem::bad_request("invalid_hash", "Query must have 8 or 64 characters");
+    }
+
+    // Hashes are case-insensitive, hence normalized such that e.g. `0xA9059CBB` and `a9059cbb` share an entry
+    let input = input_trimmed.to_lowercase();
+    let (kind, page) = (query_kind_to_signaturekind(&path.kind), path.page);
+    let source = query_source_to_signaturesource(&query.source);
+    let sort = match query_sort_to_signaturesort(&query.sort) {
+        Ok(sort) => sort,
+        Err(response) => return response,
+    };
+    let order = query_order_to_sortorder(&query.order);
+    let key = format!("hash/{kind:?}/{source:?}/{sort:?}/{order:?}/{input}/{page}");
+    run_cached_query(state, key, move |dbc| {
+        dbc.rest().signature_where_hash_starts_with(&input, kind, source, sort, order, page)
+    })
+    .await
+}
+
+/// Returns the texts of the functions and errors matching a selector as a bare JSON array, most used first,
+/// for wallets and explorers decoding calldata on the fly which neither need pagination nor metadata.
+#[get("/selector/{selector}")]
+async fn selector(path: web::Path<String>, state: web::Data<AppState>) -> impl Responder {
+    let input = path.trim().to_lowercase();
+    let input = input.strip_prefix("0x").unwrap_or(&input);
+    let selector = match decode_hash(input) {
+        Some(selector) if selector.len() == 4 => selector,
+        _ => return problem::bad_request("invalid_selector", "Selector must have 8 hex characters"),
+    };
+
+    let key = format!("selector/{input}");
+    let mut response = run_cached_query(state, key, move |dbc| {
+        let kinds = [SignatureKind::Function, SignatureKind::Error];
+        let signatures = dbc.rest().signatures_where_hashes(&[selector], &kinds, false)?;
+        let texts = signatures.into_iter().map(|x| x.text).collect::<Vec<_>>();
+
+        Ok(Some(texts).filter(|x| !x.is_empty()))
+    })
+    .await;
+
+    // Unknown selectors are answered with an empty array rather than a 404, sparing clients special handling
+    match response.status() {
+        StatusCode::OK => {
+            let cache_control = HeaderValue::from_static(SELECTOR_CACHE_CONTROL_KNOWN);
+            response.headers_mut().insert(CACHE_CONTROL, cache_control);
+            response
+        }
+
+        StatusCode::NOT_FOUND => {
+            HttpResponse::Ok().insert_header((CACHE_CONTROL, SELECTOR_CACHE_CONTROL_UNKNOWN)).body("[]")
+        }
+
+        _ => response,
+    }
+}
+
+#[get("/signatures/recent")]
+async fn signatures_recent(query: web::Query<RecentQuery>, state: web::Data<AppState>) -> impl Responder {
+    let limit = query.limit.unwrap_or(DEFAULT_RECENT_LIMIT);
+    if !(1..=MAX_RECENT_LIMIT).contains(&limit) {
+        let message = format!("Limit must be between 1 and {MAX_RECENT_LIMIT}");
+        let problem = Problem::new(StatusCode::BAD_REQUEST, "invalid_limit", message);
+        return problem.with_details(json!({ "min": 1, "max": MAX_RECENT_LIMIT })).into();
+    }
+
+    let kind = query.kind.as_ref().and_then(query_kind_to_signaturekind);
+    let source = query.source;
+    run_query(state, move |dbc| Ok(Some(dbc.rest().signatures_latest(limit, kind, source)?))).await
+}
+
+#[get("/signatures/collisions/{page}")]
+async fn signatures_collisions(page: web::Path<i64>, state: web::Data<AppState>) -> impl Responder {
+    let page = page.into_inner();
+    if !is_valid_page_index(page) {
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
+    }
+
+    run_query(state, move |dbc| dbc.rest().selector_collisions(page)).await
+}
+
+// Restricted to digits as `/signatures/recent` would match otherwise
+#[get("/signatures/{id:\\d+}")]
+async fn signature_detail(id: web::Path<i32>, state: web::Data<AppState>) -> impl Responder {
+    let id = id.into_inner();
+    run_query(state, move |dbc| dbc.rest().signature_detail(id)).await
+}
+
+/// Submits signatures for moderation on behalf of the registered consumer identified by its API key, see
+/// `etherface_lib::database::handler::signature_submission`.
+#[post("/signatures")]
+async fn submit_signatures(
+    body: web::Json<SubmissionBody>,
+    api_key: Option<web::ReqData<ApiKeyId>>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let api_key_id = match require_api_key(api_key, "Submitting signatures") {
+        Ok(api_key_id) => api_key_id,
+        Err(response) => return response,
+    };
+
+    if body.signatures.is_empty() || body.signatures.len() > MAX_SUBMISSIONS {
+        let message = format!("Between 1 and {MAX_SUBMISSIONS} signatures can be submitted at once");
+        let problem = Problem::new(StatusCode::BAD_REQUEST, "invalid_submission_count", message);
+        return problem.with_details(json!({ "min": 1, "max": MAX_SUBMISSIONS })).into();
+    }
+
+    let mut signatures: Vec<SignatureWithMetadata> = Vec::with_capacity(body.signatures.len());
+    for (index, submitted) in body.signatures.iter().enumerate() {
+        if !matches!(submitted.kind, SignatureKind::Function | SignatureKind::Event | SignatureKind::Error) {
+            let message = "Only functions, events and errors can be submitted";
+            let problem = Problem::new(StatusCode::BAD_REQUEST, "invalid_kind", message);
+            return problem.with_details(json!({ "index": index })).into();
+        }
+
+        // Texts with invalid parameter types are accepted but flagged, same as scraped ones
+        let (text, is_valid) = match parser::canonicalize(&submitted.text) {
+            Some(canonical) => canonical,
+            None => {
+                let message = format!("'{}' is not a signature", submitted.text.trim());
+                let problem = Problem::new(StatusCode::BAD_REQUEST, "invalid_signature", message);
+                return problem.with_details(json!({ "index": index })).into();
+            }
+        };
+
+        if !signatures.iter().any(|x| x.text == text && x.kind == submitted.kind) {
+            signatures.push(SignatureWithMetadata::new(text, submitted.kind, is_valid));
+        }
+    }
+
+    run_query(state, move |dbc| {
+        let submissions = dbc.rest().signature_submissions_insert(api_key_id, &signatures)?;
+        Ok(Some(submissions))
+    })
+    .await
+}
+
+/// Flags a signatures mappings as wrong or spam for moderation, see
+/// `etherface_lib::database::handler::signature_flag`. Flags may be raised anonymously; the API key is merely
+/// recorded if given.
+#[post("/signatures/{id:\\d+}/flags")]
+async fn flag_signature(
+    id: web::Path<i32>,
+    body: web::Json<FlagBody>,
+    api_key: Option<web::ReqData<ApiKeyId>>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let FlagBody { kind, source, source_id, reason, comment } = body.into_inner();
+
+    let has_source_id = matches!(source, Some(SignatureSource::Github | SignatureSource::Etherscan));
+    if source_id.is_some() && !has_source_id {
+        let message = "Source IDs are only given for GitHub and Etherscan";
+        return problem::bad_request("invalid_source_id", message);
+    }
+
+    let comment = comment.map(|x| x.trim().to_string()).filter(|x| !x.is_empty());
+    if comment.as_ref().map_or(false, |x| x.chars().count() > MAX_FLAG_COMMENT_LENGTH) {
+        let message = format!("Comment must be <= {MAX_FLAG_COMMENT_LENGTH} characters");
+        let problem = Problem::new(StatusCode::BAD_REQUEST, "invalid_comment", message);
+        return problem.with_details(json!({ "max": MAX_FLAG_COMMENT_LENGTH })).into();
+    }
+
+    let signature_id = id.into_inner();
+    let api_key_id = api_key.map(|x| x.0);
+    run_query(state, move |dbc| {
+        dbc.rest().signature_flags_insert(&SignatureFlagInsert {
+            signature_id,
+            kind,
+            source,
+            source_id,
+            reason,
+            comment: comment.as_deref(),
+            api_key_id,
+            flagged_at: Utc::now(),
+        })
+    })
+    .await
+}
+
+/// Returns the watchlist of the registered consumer identified by its API key, see
+/// `etherface_lib::database::handler::watchlist_entry`.
+#[get("/watchlist")]
+async fn watchlist(api_key: Option<web::ReqData<ApiKeyId>>, state: web::Data<AppState>) -> impl Responder {
+    let api_key_id = match require_api_key(api_key, "Watchlists") {
+        Ok(api_key_id) => api_key_id,
+        Err(response) => return response,
+    };
+
+    run_query(state, move |dbc| Ok(Some(dbc.rest().watchlist_entries(api_key_id)?))).await
+}
+
+#[post("/watchlist")]
+async fn watchlist_add(
+    body: web::Json<WatchlistBody>,
+    api_key: Option<web::ReqData<ApiKeyId>>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let api_key_id = match require_api_key(api_key, "Watchlists") {
+        Ok(api_key_id) => api_key_id,
+        Err(response) => return response,
+    };
+
+    let WatchlistBody { hash, webhook_url } = body.into_inner();
+    let hash = hash.trim();
+    let hash = hash.strip_prefix("0x").unwrap_or(hash).to_lowercase();
+    if !matches!(hash.len(), 8 | 64) || decode_hash(&hash).is_none() {
+        let message = "Hash must be a hex encoded 4 byte selector or 32 byte topic";
+        return problem::bad_request("invalid_hash", message);
+    }
+
+    let webhook_url = webhook_url.map(|x| x.trim().to_string()).filter(|x| !x.is_empty());
+    if let Some(url) = &webhook_url {
+        if !is_valid_webhook_url(url.clone()).await {
+            return problem::bad_request("invalid_webhook_url", INVALID_WEBHOOK_URL_MESSAGE);
+        }
+    }
+
+    #[derive(Serialize)]
+    struct AddedWatchlistEntry {
+        #[serde(flatten)]
+        entry: WatchlistEntry,
+
+        /// Key webhook calls are signed with, only returned when adding the entry.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        webhook_secret: Option<String>,
+    }
+
+    let inserted = web::block(move || {
+        let secret = webhook_url.as_ref().map(|_| webhook::generate_secret());
+        let entity = WatchlistEntryInsert {
+            api_key_id,
+            hash: &hash,
+            webhook_url: webhook_url.as_deref(),
+            webhook_secret: secret.as_deref(),
+            created_at: Utc::now(),
+        };
+
+        state.dbc.rest().watchlist_entries_insert(&entity, MAX_WATCHLIST_ENTRIES)
+    });
+
+    // Not answered via `run_query` as a full watchlist isn't a `404`
+    match inserted.await {
+        Ok(Ok(Some(entry))) => {
+            let added = AddedWatchlistEntry { webhook_secret: entry.webhook_secret.clone(), entry };
+            HttpResponse::Ok().body(serde_json::to_string(&added).unwrap())
+        }
+        Ok(Ok(None)) => {
+            let message = format!("Watchlists hold at most {MAX_WATCHLIST_ENTRIES} entries");
+            let problem = Problem::new(StatusCode::CONFLICT, "watchlist_full", message);
+            problem.with_details(json!({ "max": MAX_WATCHLIST_ENTRIES })).into()
+        }
+        Ok(Err(why)) => query_failed(why),
+        Err(why) => query_failed(why),
+    }
+}
+
+#[delete("/watchlist/{id}")]
+async fn watchlist_remove(
+    id: web::Path<i32>,
+    api_key: Option<web::ReqData<ApiKeyId>>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let api_key_id = match require_api_key(api_key, "Watchlists") {
+        Ok(api_key_id) => api_key_id,
+        Err(response) => return response,
+    };
+
+    let id = id.into_inner();
+    run_query(state, move |dbc| dbc.rest().watchlist_entries_delete(api_key_id, id)).await
+}
+
+/// Returns the webhooks registered by the consumer identified by its API key, see
+/// `etherface_lib::database::handler::webhook`.
+#[get("/webhooks")]
+async fn webhooks(api_key: Option<web::ReqData<ApiKeyId>>, state: web::Data<AppState>) -> impl Responder {
+    let api_key_id = match require_api_key(api_key, "Webhooks") {
+        Ok(api_key_id) => api_key_id,
+        Err(response) => return response,
+    };
+
+    run_query(state, move |dbc| Ok(Some(dbc.rest().webhooks(api_key_id)?))).await
+}
+
+#[post("/webhooks")]
+async fn webhooks_add(
+    body: web::Json<WebhookBody>,
+    api_key: Option<web::ReqData<ApiKeyId>>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let api_key_id = match require_api_key(api_key, "Webhooks") {
+        Ok(api_key_id) => api_key_id,
+        Err(response) => return response,
+    };
+
+    let WebhookBody { url, kind, prefix, source } = body.into_inner();
+    let url = url.trim().to_string();
+    if !is_valid_webhook_url(url.clone()).await {
+        return problem::bad_request("invalid_webhook_url", INVALID_WEBHOOK_URL_MESSAGE);
+    }
+
+    let prefix = prefix.filter(|x| !x.is_empty());
+    if prefix.as_ref().map_or(false, |x| x.len() > MAX_WEBHOOK_PREFIX_LENGTH) {
+        let message = format!("Prefix must not exceed {MAX_WEBHOOK_PREFIX_LENGTH} characters");
+        return problem::bad_request("invalid_prefix", message);
+    }
+
+    #[derive(Serialize)]
+    struct RegisteredWebhook {
+        #[serde(flatten)]
+        webhook: Webhook,
+
+        /// Key deliveries are signed with, which isn't returned afterwards.
+        secret: String,
+    }
+
+    let inserted = web::block(move || {
+        let secret = webhook::generate_secret();
+        let entity = WebhookInsert {
+            api_key_id,
+            url: &url,
+            secret: &secret,
+            kind,
+            prefix: prefix.as_deref(),
+            source,
+            created_at: Utc::now(),
+        };
+
+        state.dbc.rest().webhooks_insert(&entity, MAX_WEBHOOKS)
+    });
+
+    // Not answered via `run_query` as hitting the limit isn't a `404`
+    match inserted.await {
+        Ok(Ok(Some(entity))) => {
+            let registered = RegisteredWebhook { secret: entity.secret.clone(), webhook: entity };
+            HttpResponse::Ok().body(serde_json::to_string(&registered).unwrap())
+        }
+        Ok(Ok(None)) => {
+            let message = format!("At most {MAX_WEBHOOKS} webhooks can be registered");
+            let problem = Problem::new(StatusCode::CONFLICT, "webhook_limit_reached", message);
+            problem.with_details(json!({ "max": MAX_WEBHOOKS })).into()
+        }
+        Ok(Err(why)) => query_failed(why),
+        Err(why) => query_failed(why),
     }
+}
+
+#[delete("/webhooks/{id}")]
+async fn webhooks_remove(
+    id: web::Path<i32>,
+    api_key: Option<web::ReqData<ApiKeyId>>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let api_key_id = match require_api_key(api_key, "Webhooks") {
+        Ok(api_key_id) => api_key_id,
+        Err(response) => return response,
+    };
 
-    let kind = query_kind_to_signaturekind(&path.kind);
-    match state.dbc.rest().signature_where_hash_starts_with(&input_trimmed, kind, path.page) {
-        Some(signatures) => HttpResponse::Ok().body(serde_json::to_string(&signatures).unwrap()),
-        None => HttpResponse::NotFound().finish(),
+    let id = id.into_inner();
+    run_query(state, move |dbc| dbc.rest().webhooks_delete(api_key_id, id)).await
+}
+
+/// Returns the ID of the API key the request was sent with, responding with `401` if there's none. `feature`
+/// names what requires the key within the problems message, e.g. `Watchlists`.
+fn require_api_key(api_key: Option<web::ReqData<ApiKeyId>>, feature: &str) -> Result<i32, HttpResponse> {
+    match api_key {
+        Some(api_key) => Ok(api_key.0),
+        None => {
+            let message = format!("{feature} require an API key");
+            Err(Problem::new(StatusCode::UNAUTHORIZED, "api_key_required", message).into())
+        }
     }
 }
 
+/// Returns whether `url` may be registered as a webhook, see `etherface_lib::api::webhook::is_valid_url`. The
+/// check resolves the URLs host, as such it's run on the blocking thread pool.
+async fn is_valid_webhook_url(url: String) -> bool {
+    web::block(move || webhook::is_valid_url(&url)).await.unwrap_or(false)
+}
+
+#[post("/decode/error")]
+async fn decode_error(body: web::Json<DecodeBody>, state: web::Data<AppState>) -> impl Responder {
+    let data = body.data.trim();
+    let data = match decode_hash(data.strip_prefix("0x").unwrap_or(data)) {
+        Some(data) if data.len() >= 4 => data,
+        _ => {
+            return problem::bad_request("invalid_data", "Data must be hex encoded and at least 4 bytes long")
+        }
+    };
+
+    run_query(state, move |dbc| dbc.rest().decode_error(&data)).await
+}
+
+/// Fetches the transaction with the given hash and its receipt from the configured JSON-RPC node, decoding
+/// its input, logs and, if it reverted, the data it reverted with.
+#[get("/decode/transaction/{hash}")]
+async fn decode_transaction(path: web::Path<String>, state: web::Data<AppState>) -> impl Responder {
+    let hash = path.trim().to_lowercase();
+    let hash = match hash.strip_prefix("0x").unwrap_or(&hash) {
+        hash if hash.len() == 64 && decode_hash(hash).is_some() => format!("0x{hash}"),
+        _ => {
+            let message = "Transaction hash must have 64 hex characters";
+            return problem::bad_request("invalid_transaction_hash", message);
+        }
+    };
+
+    let rpc = match state.rpc.clone() {
+        Some(rpc) => rpc,
+        None => {
+            let problem = Problem::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "decoding_not_configured",
+                "Transaction decoding is not configured",
+            );
+
+            return problem.into();
+        }
+    };
+
+    run_query(state, move |dbc| {
+        let transaction = match rpc.transaction(&hash)? {
+            Some(transaction) => transaction,
+            None => return Ok(None),
+        };
+
+        let receipt = rpc.receipt(&hash)?;
+        let revert_data = match &receipt {
+            Some(receipt) if receipt.is_reverted() => rpc.revert_data(&transaction)?,
+            _ => None,
+        };
+
+        Ok(Some(dbc.rest().decode_transaction(&transaction, receipt.as_ref(), revert_data.as_deref())?))
+    })
+    .await
+}
+
+/// Exports up to [`EXPORT_LIMIT`] signatures ordered by their id, responding with the id of the last exported
+/// signature in the `X-Next-Since-Id` header, i.e. the `since_id` cursor of the next request.
+///
+/// The cursor only advances over newly inserted signatures; changes to already exported ones (kinds added
+/// later, or signatures re-validated, merged or deleted by maintenance jobs) aren't exported again, as such
+/// mirrors should periodically be rebuilt from a snapshot or a full export.
+#[get("/export/signatures.{format}")]
+async fn export_signatures(
+    path: web::Path<ExportPath>,
+    query: web::Query<ExportQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let after_id = query.since_id.unwrap_or_default();
+    let added_since = match query.since_date.as_deref().map(parse_since_date) {
+        Some(Some(date)) => Some(date),
+        Some(None) => {
+            let message = "Since date must be a date (YYYY-MM-DD) or an RFC 3339 timestamp";
+            return problem::bad_request("invalid_since_date", message);
+        }
+        None => None,
+    };
+
+    let (format, content_type) = match path.format {
+        Format::Ndjson => (ExportFormat::Ndjson, "application/x-ndjson"),
+        Format::Csv => (ExportFormat::Csv, "text/csv"),
+    };
+
+    let query = move || state.dbc.rest().signatures_export(format, after_id, added_since, EXPORT_LIMIT);
+    let lines = match web::block(query).await {
+        Ok(Ok(lines)) => lines,
+        Ok(Err(why)) => return query_failed(why),
+        Err(why) => return query_failed(why),
+    };
+
+    let mut body = String::new();
+    if format == ExportFormat::Csv {
+        body.push_str(EXPORT_CSV_HEADER);
+        body.push('\n');
+    }
+
+    for line in &lines {
+        body.push_str(&line.line);
+        body.push('\n');
+    }
+
+    let mut response = HttpResponse::Ok();
+    response.content_type(content_type);
+    if let Some(last) = lines.last() {
+        response.insert_header(("X-Next-Since-Id", last.id.to_string()));
+    }
+
+    response.body(body)
+}
+
+/// Streams newly found signatures as server-sent events, one JSON encoded signature per event.
+#[get("/stream/signatures")]
+async fn stream_signatures(query: web::Query<StreamQuery>, state: web::Data<AppState>) -> impl Responder {
+    let kind = query.kind.as_ref().and_then(query_kind_to_signaturekind);
+    let prefix = query.prefix.clone().unwrap_or_default();
+
+    // Lagging clients receive an error for the skipped signatures, which are simply left out
+    let events = BroadcastStream::new(state.signatures.subscribe()).filter_map(move |entity| match entity {
+        Ok(entity) if kind.map_or(true, |kind| entity.kind == kind) && entity.text.starts_with(&prefix) => {
+            let event = format!("data: {}\n\n", serde_json::to_string(&entity).unwrap());
+            Some(Ok::<_, Infallible>(Bytes::from(event)))
+        }
+
+        _ => None,
+    });
+
+    // Ended on shutdown rather than holding it up, as streams never complete by themselves
+    let shutdown = state.shutdown.clone();
+    let events = futures_util::StreamExt::take_until(events, async move { shutdown.wait().await });
+
+    // Not compressed, as the compression middleware would buffer events until enough data has accumulated
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .insert_header(ContentEncoding::Identity)
+        .streaming(events)
+}
+
 #[get("/sources/github/{kind}/{signature_id}/{page}")]
-async fn sources_github(path: web::Path<SourcePath>, state: web::Data<AppState>) -> impl Responder {
+async fn sources_github(
+    path: web::Path<SourcePath>,
+    query: web::Query<SortQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
     if !is_valid_page_index(path.page) {
-        return HttpResponse::BadRequest().body("Page index must be >= 1");
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
     }
 
-    let kind = query_kind_to_signaturekind(&path.kind);
-    match state.dbc.rest().sources_github(path.signature_id, kind, path.page) {
-        Some(signatures) => HttpResponse::Ok().body(serde_json::to_string(&signatures).unwrap()),
-        None => HttpResponse::NotFound().finish(),
-    }
+    let sort = match query.sort {
+        None | Some(SortKey::Stars) => SourceSort::Stars,
+        Some(SortKey::AddedAt) => SourceSort::Added,
+        Some(_) => {
+            let message = "Repositories can be sorted by either stars or added_at";
+            return problem::bad_request("invalid_sort", message);
+        }
+    };
+
+    let (signature_id, kind, page) = (path.signature_id, query_kind_to_signaturekind(&path.kind), path.page);
+    let order = query_order_to_sortorder(&query.order);
+    run_query(state, move |dbc| dbc.rest().sources_github(signature_id, kind, sort, order, page)).await
 }
 
 #[get("/sources/etherscan/{kind}/{signature_id}/{page}")]
-async fn sources_etherscan(path: web::Path<SourcePath>, state: web::Data<AppState>) -> impl Responder {
+async fn sources_etherscan(
+    path: web::Path<SourcePath>,
+    query: web::Query<SortQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
     if !is_valid_page_index(path.page) {
-        return HttpResponse::BadRequest().body("Page index must be >= 1");
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
+    }
+
+    if !matches!(query.sort, None | Some(SortKey::AddedAt)) {
+        return problem::bad_request("invalid_sort", "Contracts can only be sorted by added_at");
     }
 
-    let kind = query_kind_to_signaturekind(&path.kind);
-    match state.dbc.rest().sources_etherscan(path.signature_id, kind, path.page) {
-        Some(signatures) => HttpResponse::Ok().body(serde_json::to_string(&signatures).unwrap()),
-        None => HttpResponse::NotFound().finish(),
+    let (signature_id, kind, page) = (path.signature_id, query_kind_to_signaturekind(&path.kind), path.page);
+    let order = query_order_to_sortorder(&query.order);
+    run_query(state, move |dbc| dbc.rest().sources_etherscan(signature_id, kind, order, page)).await
+}
+
+/// Returns the Etherscan contract at the given address alongside all signatures of its ABI, i.e. its
+/// interface, or the selectors and topics found within its bytecode if it's unverified, on Ethereum mainnet
+/// unless another chain id is given.
+#[get("/contracts/{address}")]
+async fn contract_detail(
+    path: web::Path<String>,
+    query: web::Query<ChainQuery>,
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let address = path.trim();
+    let is_address = address.strip_prefix("0x").and_then(decode_hash).map(|x| x.len()) == Some(20);
+    if !is_address {
+        let message = "Address must be 0x prefixed and have 40 hex characters";
+        return problem::bad_request("invalid_address", message);
     }
+
+    let (address, chain_id) = (address.to_string(), query.chain_id.unwrap_or(1));
+    run_query(state, move |dbc| dbc.rest().contract_detail(&address, chain_id)).await
 }
 
 #[get("/statistics")]
@@ -127,18 +1095,43 @@ async fn statistics(state: web::Data<AppState>) -> impl Responder {
         statistics_signature_insert_rate: Vec<ViewSignatureInsertRate>,
         statistics_signature_kind_distribution: Vec<ViewSignatureKindDistribution>,
         statistics_signatures_popular_on_github: Vec<ViewSignaturesPopularOnGithub>,
+        statistics_signature_insert_rate_by_source: Vec<ViewSignatureInsertRateBySource>,
+        statistics_repositories_top_unique_signatures: Vec<ViewRepositoriesTopUniqueSignatures>,
+        statistics_signatures_per_contract_distribution: Vec<ViewSignaturesPerContractDistribution>,
+        statistics_etherscan_compiler_version_distribution: Vec<ViewEtherscanCompilerVersionDistribution>,
+        statistics_etherscan_chain_distribution: Vec<ViewEtherscanChainDistribution>,
     }
 
-    HttpResponse::Ok().body(
-        serde_json::to_string(&Statistics {
-            statistics_various_signature_counts: state.dbc.rest().statistics_various_signature_counts(),
-            statistics_signature_insert_rate: state.dbc.rest().statistics_signature_insert_rate(),
-            statistics_signature_kind_distribution: state.dbc.rest().statistics_signature_kind_distribution(),
-            statistics_signatures_popular_on_github: state
-                .dbc
+    run_query(state, |dbc| {
+        Ok(Some(Statistics {
+            statistics_various_signature_counts: dbc.rest().statistics_various_signature_counts()?,
+            statistics_signature_insert_rate: dbc.rest().statistics_signature_insert_rate()?,
+            statistics_signature_kind_distribution: dbc.rest().statistics_signature_kind_distribution()?,
+            statistics_signatures_popular_on_github: dbc.rest().statistics_signatures_popular_on_github()?,
+            statistics_signature_insert_rate_by_source: dbc
                 .rest()
-                .statistics_signatures_popular_on_github(),
-        })
-        .unwrap(),
-    )
-}
\ No newline at end of file
+                .statistics_signature_insert_rate_by_source()?,
+            statistics_repositories_top_unique_signatures: dbc
+                .rest()
+                .statistics_repositories_top_unique_signatures()?,
+            statistics_signatures_per_contract_distribution: dbc
+                .rest()
+                .statistics_signatures_per_contract_distribution()?,
+            statistics_etherscan_compiler_version_distribution: dbc
+                .rest()
+                .statistics_etherscan_compiler_version_distribution()?,
+            statistics_etherscan_chain_distribution: dbc.rest().statistics_etherscan_chain_distribution()?,
+        }))
+    })
+    .await
+}
+
+#[get("/status/workers")]
+async fn worker_status(state: web::Data<AppState>) -> impl Responder {
+    run_query(state, |dbc| Ok(Some(dbc.rest().worker_status()?))).await
+}
+
+#[get("/status/views")]
+async fn materialized_view_status(state: web::Data<AppState>) -> impl Responder {
+    run_query(state, |dbc| Ok(Some(dbc.rest().materialized_view_refresh()?))).await
+}
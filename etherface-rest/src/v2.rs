@@ -0,0 +1,113 @@
+//! `/v2/` REST API, taking filters as query parameters rather than path segments such that new filters can
+//! be added without breaking existing clients. Responses share the structure of the `/v1/` endpoints.
+
+use crate::problem;
+use crate::problem::Problem;
+use crate::v1::is_valid_page_index;
+use crate::v1::query_kind_to_signaturekind;
+use crate::v1::query_order_to_sortorder;
+use crate::v1::run_query;
+use crate::v1::AppState;
+use crate::v1::Kind;
+use crate::v1::Order;
+use actix_web::get;
+use actix_web::http::StatusCode;
+use actix_web::web;
+use actix_web::Responder;
+use etherface_lib::database::handler::rest::SignatureSearch;
+use etherface_lib::database::handler::rest::SignatureSort;
+use etherface_lib::model::SignatureSource;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Number of signatures per page if no page size is given.
+const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// Maximum number of signatures per page.
+const MAX_PAGE_SIZE: i64 = 500;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    Id,
+    #[serde(alias = "popularity")]
+    Usage,
+    #[serde(alias = "added_at")]
+    Added,
+    Text,
+}
+
+/// Parameters of the `/v2/signatures` endpoint, all of which are optional. Unknown parameters are rejected
+/// rather than ignored, such that misspelled filters don't silently return unfiltered results.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignaturesQuery {
+    query: Option<String>,
+    kind: Option<Kind>,
+    source: Option<SignatureSource>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+    sort: Option<Sort>,
+    order: Option<Order>,
+
+    /// Sparse fieldset, applied to the response by `fields.rs` rather than this endpoint.
+    #[allow(dead_code)]
+    fields: Option<String>,
+}
+
+/// Returns whether `hash` is either a selector or a whole hash, i.e. 8 or 64 hex characters long.
+#[inline]
+fn is_hash(hash: &str) -> bool {
+    (hash.len() == 8 || hash.len() == 64) && hash.bytes().all(|x| x.is_ascii_hexdigit())
+}
+
+#[get("/signatures")]
+async fn signatures(query: web::Query<SignaturesQuery>, state: web::Data<AppState>) -> impl Responder {
+    let page = query.page.unwrap_or(1);
+    if !is_valid_page_index(page) {
+        return problem::bad_request("invalid_page", "Page index must be >= 1");
+    }
+
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&page_size) {
+        let message = format!("Page size must be between 1 and {MAX_PAGE_SIZE}");
+        let problem = Problem::new(StatusCode::BAD_REQUEST, "invalid_page_size", message);
+        return problem.with_details(json!({ "min": 1, "max": MAX_PAGE_SIZE })).into();
+    }
+
+    let input = query.query.as_deref().map(str::trim).filter(|x| !x.is_empty());
+    let input = match input {
+        Some(input) => match input.strip_prefix("0x") {
+            Some(hash) if is_hash(hash) => Some(format!("0x{}", hash.to_lowercase())),
+            Some(_) => {
+                return problem::bad_request("invalid_hash", "Hash query must have 8 or 64 hex characters")
+            }
+            None if input.len() < 3 => {
+                return problem::bad_request("query_too_short", "Text query must have at least 3 characters")
+            }
+            None => Some(input.to_string()),
+        },
+
+        None => None,
+    };
+
+    // Signatures sharing a hash are most likely decoded by the most used one
+    let sort = match (&query.sort, &input) {
+        (Some(Sort::Id), _) => SignatureSort::Id,
+        (Some(Sort::Usage), _) => SignatureSort::Usage,
+        (Some(Sort::Added), _) => SignatureSort::Added,
+        (Some(Sort::Text), _) => SignatureSort::Text,
+        (None, Some(input)) if input.starts_with("0x") => SignatureSort::Usage,
+        (None, _) => SignatureSort::Id,
+    };
+
+    let search = SignatureSearch {
+        query: input,
+        kind: query.kind.as_ref().and_then(query_kind_to_signaturekind),
+        source: query.source,
+        sort,
+        order: query_order_to_sortorder(&query.order),
+    };
+
+    run_query(state, move |dbc| dbc.rest().signatures_search(&search, page, page_size)).await
+}
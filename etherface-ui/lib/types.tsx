@@ -15,6 +15,11 @@ export interface Statistics {
     statistics_signature_insert_rate: StatisticsSignatureInsertRate[]
     statistics_signature_kind_distribution: StatisticsSignatureKindDistribution[]
     statistics_signatures_popular_on_github: StatisticsSignaturesPopularOnGithub[]
+    statistics_signature_insert_rate_by_source: StatisticsSignatureInsertRateBySource[]
+    statistics_repositories_top_unique_signatures: StatisticsRepositoriesTopUniqueSignatures[]
+    statistics_signatures_per_contract_distribution: StatisticsSignaturesPerContractDistribution[]
+    statistics_etherscan_compiler_version_distribution: StatisticsEtherscanCompilerVersionDistribution[]
+    statistics_etherscan_chain_distribution: StatisticsEtherscanChainDistribution[]
 }
 
 export interface StatisticsVariousSignatureCounts {
@@ -41,6 +46,36 @@ export interface StatisticsSignaturesPopularOnGithub {
     count: number
 }
 
+export interface StatisticsSignatureInsertRateBySource {
+    date: string
+    source: 'github' | 'etherscan' | 'fourbyte'
+    count: number
+}
+
+export interface StatisticsRepositoriesTopUniqueSignatures {
+    repository_id: number
+    name: string
+    html_url: string
+    count: number
+}
+
+export interface StatisticsSignaturesPerContractDistribution {
+    bucket: number
+    count: number
+}
+
+export interface StatisticsEtherscanCompilerVersionDistribution {
+    compiler: string
+    version: string
+    count: number
+}
+
+export interface StatisticsEtherscanChainDistribution {
+    chain_id: number
+    contract_count: number
+    signature_count: number
+}
+
 export enum SignatureKind {
     All = 'all',
     Function = 'function',
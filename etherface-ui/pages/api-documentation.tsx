@@ -29,9 +29,16 @@ const ApiDocumentation = () => {
                             <div>
                                 <ul className='list-disc list-inside'>
                                     <li className='list-item'>All listed API endpoints are paginated, returning 100 items per page starting at page 1</li>
+                                    <li className='list-item'>The <code>{`/v1/signatures/{text,hash}`}</code> endpoints optionally only return signatures found in the given source by appending <code>?source=</code> followed by either <code>github</code>, <code>etherscan</code>, <code>fourbyte</code> or <code>all</code>, e.g. to only trust signatures backed by actual source code</li>
+                                    <li className='list-item'>The <code>{`/v1/signatures/{text,hash}`}</code> endpoints optionally sort signatures by appending <code>?sort=</code> followed by either <code>added_at</code> (newest first), <code>popularity</code> (most used on-chain first) or <code>text</code> (alphabetically), whereas the <code>/v1/sources</code> endpoints sort by <code>stars</code> (GitHub only, most starred first) or <code>added_at</code>; <code>&amp;order=asc</code> or <code>&amp;order=desc</code> reverses the default direction of a key, keys not applicable to an endpoint being answered with <code>400</code> (<code>invalid_sort</code>)</li>
                                     <li className='list-item'>Successful responses have the following JSON structure: <code className='text-sm'>{`{"total_pages": ..., "total_items": ..., "items": [ {...}, ...] }`}</code></li>
-                                    <li className='list-item'>Unsuccessful responses either return the <code>400</code> or <code>404</code> HTTP status code</li>
-                                    <li className='list-item'>Additionally the <code>429</code> status code might be added to the list depending on whether or not ratelimiting is neccessary (hopefully not, fingers crossed)</li>
+                                    <li className='list-item'>Paginated responses additionally carry a <code>Link</code> header linking the <code>first</code>, <code>prev</code>, <code>next</code> and <code>last</code> page (e.g. <code className='text-sm'>{`</v1/signatures/text/all/balanceOf/2>; rel="next"`}</code>) as well as an <code>X-Total-Count</code> header holding the total number of items</li>
+                                    <li className='list-item'>All JSON endpoints optionally only return some fields of each item by appending <code>?fields=</code> followed by a comma separated list of field names, e.g. <code>?fields=text,hash</code> for signatures or <code>?fields=html_url</code> for GitHub sources; unknown fields are omitted whereas malformed lists are answered with <code>400</code> (<code>invalid_fields</code>)</li>
+                                    <li className='list-item'>Unsuccessful responses either return the <code>400</code> or <code>404</code> HTTP status code with an <code>application/problem+json</code> body of the following structure, where <code>code</code> is stable and meant for programmatic handling (e.g. <code>invalid_page</code>, <code>not_found</code>) whereas <code>details</code> is optional: <code className='text-sm'>{`{"status": 400, "code": "...", "message": "...", "details": {...}, "request_id": "..."}`}</code></li>
+                                    <li className='list-item'>Additionally the <code>429</code> status code is returned if you exceed the rate limit, alongside a <code>Retry-After</code> header holding the number of seconds until you may retry</li>
+                                    <li className='list-item'>Responses are compressed with gzip or brotli if requested by the <code>Accept-Encoding</code> header</li>
+                                    <li className='list-item'>Registered consumers may send their API key in the <code>X-Api-Key</code> header for higher limits; some endpoints, such as bulk exports, may require one and return <code>401</code> otherwise</li>
+                                    <li className='list-item'>Every response carries an <code>X-Request-Id</code> header, either echoing the one you sent or a generated one; please include it when reporting a failing request</li>
                                     <li className='list-item'>Lastly, if you enjoy this project make sure to also star it on <LinkItem text='GitHub' url='https://github.com/volsa/etherface' /> {`<3`}</li>
                                 </ul>
                             </div>
@@ -54,16 +61,276 @@ const ApiDocumentation = () => {
                     />
 
                     <Paragraph
-                        title={<code>{`/v1/signatures/hash/{kind}/{query}/{page}`}</code>}
+                        title={<code>{`/v1/signatures/text/contains/{kind}/{query}/{page}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns a paginated list of signatures where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code>, <code>error</code> or <code>all</code></li>
+                                    <li className='list-item'><code>query</code> is contained anywhere within the signatures text representation (at least 3 characters long)</li>
+                                    <li className='list-item'><code>page</code> is the page index, starting at 1</li>
+                                </ul>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/signatures/text/contains/all/Swap/1' url='https://api.etherface.io/v1/signatures/text/contains/all/Swap/1' /> returns all signatures containing <code>Swap</code> (case sensitive!)</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/signatures/text/similar/{kind}/{query}/{page}`}</code>}
                         content={
                             <div>
                                 <p>Returns a paginated list of signatures where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code>, <code>error</code> or <code>all</code></li>
+                                    <li className='list-item'><code>query</code> is similar to the signatures text representation, e.g. with typos (at least 3 characters long)</li>
+                                    <li className='list-item'><code>page</code> is the page index, starting at 1</li>
+                                </ul>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/signatures/text/similar/all/balancOf/1' url='https://api.etherface.io/v1/signatures/text/similar/all/balancOf/1' /> returns all signatures similar to <code>balancOf</code>, most similar first</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/signatures/search?q={query}&kind={kind}&source={source}&page={page}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns a paginated list of signatures either containing the query (case insensitive) or a word similar to it, such that typos and parts of camel-case names still find the intended signature. Signatures containing the query come first, followed by the remaining ones ranked by their similarity, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>q</code> is the query, between 3 and 256 characters long</li>
+                                    <li className='list-item'><code>kind</code> is optional and either <code>function</code>, <code>event</code>, <code>error</code> or <code>all</code></li>
+                                    <li className='list-item'><code>source</code> is optional and either <code>github</code>, <code>etherscan</code> or <code>fourbyte</code></li>
+                                    <li className='list-item'><code>page</code> is the page index, starting at 1 (the default)</li>
+                                </ul>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/signatures/search?q=tranfer' url='https://api.etherface.io/v1/signatures/search?q=tranfer' /> returns, amongst others, <code>transfer(address,uint256)</code> and <code>transferFrom(address,address,uint256)</code></p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/signatures/hash/{kind}/{query}/{page}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns a paginated list of signatures ordered by their on-chain usage (most used first) where</p>
                                 <ul className='list-disc list-inside'>
                                     <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code>, <code>error</code> or <code>all</code></li>
                                     <li className='list-item'><code>query</code> is the signature hash (either 8 or 64 characters long, excluding the <code>0x</code> head which is also optional)</li>
                                     <li className='list-item'><code>page</code> is the page index, starting at 1</li>
                                 </ul>
-                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/signatures/hash/all/70a08231/1' url='https://api.etherface.io/v1/signatures/hash/all/70a08231/1' /> returns all signatures starting with the hash <code>70a08231</code> (case sensitive!)</p>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/signatures/hash/all/70a08231/1' url='https://api.etherface.io/v1/signatures/hash/all/70a08231/1' /> returns all signatures starting with the hash <code>70a08231</code> (case insensitive)</p>
+                                <p>Responses are cached for a short duration, as such newly found signatures may take up to 30 seconds to show up.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/signatures/recent?limit={limit}&kind={kind}&source={source}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns the most recently added signatures, newest first, where all parameters are optional and</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>limit</code> is the number of signatures, between 1 and 500 (defaults to 50)</li>
+                                    <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code>, <code>error</code> or <code>all</code></li>
+                                    <li className='list-item'><code>source</code> is either <code>github</code>, <code>etherscan</code> or <code>fourbyte</code></li>
+                                </ul>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/signatures/recent?kind=event&source=github' url='https://api.etherface.io/v1/signatures/recent?kind=event&source=github' /> returns the 50 most recently added events found on GitHub</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/signatures/collisions/{page}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns all 4-byte selectors shared by more than one valid function or error signature, ordered by their number of signatures and refreshed periodically (see <code>/v1/status/views</code>), where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>page</code> is the page index, starting at 1</li>
+                                </ul>
+                                <p>The signatures of a selector can then be looked up with the <code>/v1/signatures/hash</code> endpoint.</p>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/signatures/collisions/1' url='https://api.etherface.io/v1/signatures/collisions/1' /> returns the most ambiguous selectors</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/selector/{selector}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns the texts of all functions and errors of a selector as a plain JSON array, most used on-chain first, intended for wallets and explorers decoding calldata on the fly, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>selector</code> is the 4-byte selector, i.e. 8 hex characters with an optional <code>0x</code> prefix</li>
+                                </ul>
+                                <p>Unknown selectors return an empty array. Responses may be cached by clients for a day (5 minutes for unknown selectors), see the <code>Cache-Control</code> header.</p>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/selector/0xa9059cbb' url='https://api.etherface.io/v1/selector/0xa9059cbb' /> returns <code className='text-sm'>{`["transfer(address,uint256)", ...]`}</code></p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/signatures/{id}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns a single signature alongside all kinds it was found as, the number of GitHub repositories, Etherscan contracts and 4Byte entries it was found in, the dates it was first and last seen at and the standards (e.g. <code>ERC-20</code>) defining it, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>id</code> is the signatures internal ID, obtained by the <code>{`/v1/signatures/{text,hash}`}</code> endpoints</li>
+                                </ul>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/signatures/7' url='https://api.etherface.io/v1/signatures/7' /> returns the details of the <code>balanceOf(address)</code> signature</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`POST /v1/signatures`}</code>}
+                        content={
+                            <div>
+                                <p>Submits up to 100 signatures missing from Etherface, taking a JSON body of the form <code className='text-sm'>{`{"signatures": [ {"text": ..., "kind": ...}, ...] }`}</code> and requiring an API key in the <code>X-Api-Key</code> header, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>text</code> is the signature, e.g. <code>transfer(address to, uint amount)</code>, which is stored in its canonical form <code>transfer(address,uint256)</code></li>
+                                    <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code> or <code>error</code></li>
+                                </ul>
+                                <p>Submissions are reviewed before their signatures show up in any other endpoint. Returns the submissions as <code className='text-sm'>{`[ {"id": ..., "text": ..., "hash": ..., "kind": ..., "is_valid": ..., "status": ..., "submitted_at": ..., "moderated_at": ...}, ...]`}</code>, where <code>status</code> is either <code>pending</code>, <code>approved</code> or <code>rejected</code>; submitting a signature again returns its current status.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`POST /v1/signatures/{id}/flags`}</code>}
+                        content={
+                            <div>
+                                <p>Flags the signature with the given ID as wrong or spam for moderation, taking a JSON body of the form <code className='text-sm'>{`{"kind": ..., "source": ..., "source_id": ..., "reason": ..., "comment": ...}`}</code>, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code>, <code>error</code>, <code>constructor</code>, <code>fallback</code> or <code>receive</code></li>
+                                    <li className='list-item'><code>source</code> optionally restricts the flag to the mappings of either <code>github</code>, <code>etherscan</code> or <code>fourbyte</code>, all sources being flagged otherwise</li>
+                                    <li className='list-item'><code>source_id</code> optionally restricts GitHub and Etherscan flags to the repository respectively contract with the given ID, as returned by the <code>/v1/sources</code> endpoints</li>
+                                    <li className='list-item'><code>reason</code> is either <code>wrong</code>, e.g. if the signature doesn't occur in the source, or <code>spam</code></li>
+                                    <li className='list-item'><code>comment</code> optionally describes the issue in at most 1000 characters</li>
+                                </ul>
+                                <p>No API key is required. Returns the flag as <code className='text-sm'>{`{"id": ..., "signature_id": ..., "kind": ..., "source": ..., "source_id": ..., "reason": ..., "flag_count": ..., "status": ..., "flagged_at": ..., "moderated_at": ...}`}</code>, where <code>status</code> is either <code>pending</code>, <code>invalidated</code>, <code>removed</code> or <code>dismissed</code>; flagging a pending target again for the same reason increments <code>flag_count</code> instead. Returns <code>404</code> if the signature isn't mapped to the given kind and source.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`POST /v1/watchlist`}</code>}
+                        content={
+                            <div>
+                                <p>Watches a selector or event topic which is currently unknown, taking a JSON body of the form <code className='text-sm'>{`{"hash": ..., "webhook_url": ...}`}</code> and requiring an API key in the <code>X-Api-Key</code> header, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>hash</code> is either a function / error selector (e.g. <code>0xa9059cbb</code>) or an event topic, optionally prefixed by <code>0x</code></li>
+                                    <li className='list-item'><code>webhook_url</code> optionally is an <code>https</code> URL the entry is POSTed to as <code className='text-sm'>{`{"event": "watchlist_entry_resolved", "entry": ...}`}</code> once resolved, signed just like webhook deliveries (see below); failed calls (i.e. non-2xx responses) are retried with an exponential backoff up to 10 times</li>
+                                </ul>
+                                <p>Entries are resolved as soon as a matching function or error (for selectors) respectively event (for topics) is found in any source. Returns the entry as <code className='text-sm'>{`{"id": ..., "hash": ..., "webhook_url": ..., "created_at": ..., "resolved_at": ..., "signature_id": ..., "text": ..., "kind": ..., "notified_at": ..., "notify_attempts": ..., "last_notify_error": ..., "webhook_secret": ...}`}</code>, where hashes which are already known are resolved right away without calling the webhook and <code>webhook_secret</code>, the key webhook calls are signed with, is only returned here; watching a hash again returns its existing entry. Watchlists hold at most 1000 entries.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`GET /v1/watchlist`}</code>}
+                        content={
+                            <div>
+                                <p>Returns all entries of the watchlist of the given API key in the format described above, oldest first, e.g. to poll for resolved entries instead of registering a webhook.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`DELETE /v1/watchlist/{id}`}</code>}
+                        content={
+                            <div>
+                                <p>Removes the entry with the given ID from the watchlist of the given API key, returning the removed entry.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`POST /v1/webhooks`}</code>}
+                        content={
+                            <div>
+                                <p>Registers a webhook newly found signatures are delivered to, e.g. to mirror them into another database without polling the export endpoints, taking a JSON body of the form <code className='text-sm'>{`{"url": ..., "kind": ..., "prefix": ..., "source": ...}`}</code> and requiring an API key in the <code>X-Api-Key</code> header, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>url</code> is an <code>https</code> URL signatures are POSTed to in batches of up to 100 as <code className='text-sm'>{`{"event": "signatures_found", "webhook_id": ..., "signatures": [{"id": ..., "text": ..., "hash": ..., "kind": ...}, ...]}`}</code></li>
+                                    <li className='list-item'><code>kind</code> optionally is either <code>function</code>, <code>event</code> or <code>error</code>, delivering only signatures of that kind</li>
+                                    <li className='list-item'><code>prefix</code> optionally delivers only signatures whose text starts with it, e.g. <code>transfer</code></li>
+                                    <li className='list-item'><code>source</code> optionally is either <code>github</code>, <code>etherscan</code> or <code>fourbyte</code>, delivering only signatures found within that source</li>
+                                </ul>
+                                <p>Returns the webhook as <code className='text-sm'>{`{"id": ..., "url": ..., "kind": ..., "prefix": ..., "source": ..., "created_at": ..., "delivered_at": ..., "failure_count": ..., "last_error": ..., "disabled_at": ..., "secret": ...}`}</code>, where <code>secret</code> is only returned once. Every call is signed within the <code>X-Etherface-Signature</code> header as <code>{`t=<timestamp>,v1=<signature>`}</code>, <code>signature</code> being the hex encoded HMAC-SHA256 of <code>{`<timestamp>.<body>`}</code> keyed with the secret. Signatures are delivered once per kind they're found as, in the order they were found. Failed calls (i.e. non-2xx responses) are retried with an exponential backoff, disabling the webhook after 15 failures in a row. Consumers register at most 10 webhooks.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`GET /v1/webhooks`}</code>}
+                        content={
+                            <div>
+                                <p>Returns all webhooks registered by the given API key in the format described above (without their secret), e.g. to check whether one got disabled.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`DELETE /v1/webhooks/{id}`}</code>}
+                        content={
+                            <div>
+                                <p>Removes the webhook with the given ID registered by the given API key alongside its pending deliveries, returning the removed webhook. Disabled webhooks can be registered anew this way.</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`POST /v1/decode/error`}</code>}
+                        content={
+                            <div>
+                                <p>Decodes the return data of a reverted transaction, taking a JSON body of the form <code className='text-sm'>{`{"data": "0x..."}`}</code>, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>data</code> is the hex encoded revert data, starting with the 4-byte error selector</li>
+                                </ul>
+                                <p>The data is decoded by the built-in <code>Error(string)</code> and <code>Panic(uint256)</code> errors as well as all stored errors sharing its selector, returning every error the data is a valid encoding of as <code className='text-sm'>{`{"selector": "0x...", "candidates": [ {"signature": ..., "arguments": [ {"type": ..., "value": ...}, ...] }, ...] }`}</code>. Numbers are returned as decimal strings.</p>
+                                <p><b>Example:</b> <code className='text-sm'>{`curl -X POST https://api.etherface.io/v1/decode/error -H 'Content-Type: application/json' -d '{"data": "0x4e487b710000000000000000000000000000000000000000000000000000000000000011"}'`}</code> decodes an arithmetic overflow, i.e. <code>Panic(17)</code></p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/decode/transaction/{hash}`}</code>}
+                        content={
+                            <div>
+                                <p>Explains a transaction in one call, fetching it and its receipt from a node and decoding its input, all of its logs and, if it reverted, its revert reason with the stored signatures, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>hash</code> is the transaction hash, i.e. 64 hex characters with an optional <code>0x</code> prefix</li>
+                                </ul>
+                                <p>Returns <code className='text-sm'>{`{"hash": ..., "from": ..., "to": ..., "value": ..., "block_number": ..., "succeeded": ..., "input": {...}, "logs": [ {"address": ..., "topics": [...], "candidates": [...] }, ...], "revert_data": ..., "revert": {...} }`}</code>, where <code>input</code> and <code>revert</code> share the structure of <code>/v1/decode/error</code> responses and <code>value</code> is the transferred wei as a decimal string. As signatures don&apos;t state which event parameters are indexed, logs are decoded with the first matching assignment of their topics; indexed strings, bytes and arrays are only logged as their hash and hence returned as the raw topic. The revert reason is obtained by replaying the transaction on top of its parent block and may be missing if preceding transactions of the same block affected its execution. Unknown transactions return a 404.</p>
+                                <p><b>Example:</b> <code className='text-sm'>{`curl https://api.etherface.io/v1/decode/transaction/0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060`}</code></p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/export/signatures.{format}?since_id={id}&since_date={date}`}</code>}
+                        content={
+                            <div>
+                                <p>Exports up to 50000 signatures (including invalid ones) alongside their kinds ordered by their id, allowing to mirror the whole dataset, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>format</code> is either <code>ndjson</code> (one JSON object per line) or <code>csv</code></li>
+                                    <li className='list-item'><code>since_id</code> is optional and the id of the last exported signature</li>
+                                    <li className='list-item'><code>since_date</code> is optional and a date (<code>YYYY-MM-DD</code> or RFC 3339) the signatures were added at or after</li>
+                                </ul>
+                                <p>The <code>X-Next-Since-Id</code> response header holds the id of the last exported signature, i.e. the <code>since_id</code> value of the next request. Exporting is complete once the response is empty.</p>
+                                <p>The export only advances over newly added signatures. Changes to already exported signatures, e.g. kinds they were found as later on, re-validations, merges or deletions, aren&apos;t exported again, as such mirrors should periodically be rebuilt from a full export.</p>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/export/signatures.ndjson?since_date=2022-10-01' url='https://api.etherface.io/v1/export/signatures.ndjson?since_date=2022-10-01' /> returns the first 50000 signatures added since October 1st 2022</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v1/stream/signatures?kind={kind}&prefix={prefix}`}</code>}
+                        content={
+                            <div>
+                                <p>Streams newly found signatures in near real time as <LinkItem text='server-sent events' url='https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events' />, one JSON encoded signature per event, where all parameters are optional and</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code>, <code>error</code> or <code>all</code></li>
+                                    <li className='list-item'><code>prefix</code> only streams signatures starting with the given text (case sensitive)</li>
+                                </ul>
+                                <p>Signatures found as multiple kinds are streamed once per kind. Clients falling too far behind skip the missed signatures.</p>
+                                <p><b>Example:</b> <code>curl -N &apos;https://api.etherface.io/v1/stream/signatures?kind=event&apos;</code> streams all newly found events</p>
                             </div>
                         }
                     />
@@ -72,7 +339,7 @@ const ApiDocumentation = () => {
                         title={<code>{`/v1/sources/github/{kind}/{id}/{page}`}</code>}
                         content={
                             <div>
-                                <p>Returns a paginated list of GitHub repositories ordered by their stargazers where </p>
+                                <p>Returns a paginated list of GitHub repositories ordered by their stargazers (unless sorted by <code>?sort=added_at</code>) where </p>
                                 <ul className='list-disc list-inside'>
                                     <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code>, <code>error</code> or <code>all</code></li>
                                     <li className='list-item'><code>id</code> is the signatures internal ID, obtained by the <code>{`/v1/signatures/{text,hash}`}</code> endpoints</li>
@@ -97,6 +364,56 @@ const ApiDocumentation = () => {
                             </div>
                         }
                     />
+
+                    <Paragraph
+                        title={<code>{`/v1/contracts/{address}?chain_id={chain_id}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns a scraped Etherscan contract alongside all signatures of its ABI, i.e. the interface of the address, where</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>address</code> is the <code>0x</code> prefixed contract address (case insensitive)</li>
+                                    <li className='list-item'><code>chain_id</code> is optional and the chain id the contract is deployed on, defaulting to 1 (Ethereum mainnet)</li>
+                                </ul>
+                                <p>Signatures are ordered by their kind and text, each carrying the <code>kind</code> it was found as within the ABI. Contracts which weren&apos;t scraped return a 404.</p>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v1/contracts/0xdac17f958d2ee523a2206206994597c13d831ec7' url='https://api.etherface.io/v1/contracts/0xdac17f958d2ee523a2206206994597c13d831ec7' /> returns the interface of the USDT token</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/v2/signatures?query={query}&kind={kind}&source={source}&sort={sort}&order={order}&page={page}&page_size={page_size}`}</code>}
+                        content={
+                            <div>
+                                <p>Returns a paginated list of signatures matching all given filters, where all parameters are optional (unknown ones are rejected) and</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>query</code> is either a text prefix of at least 3 characters or, if starting with <code>0x</code>, a signature hash of 8 or 64 characters</li>
+                                    <li className='list-item'><code>kind</code> is either <code>function</code>, <code>event</code>, <code>error</code> or <code>all</code></li>
+                                    <li className='list-item'><code>source</code> is either <code>github</code>, <code>etherscan</code> or <code>fourbyte</code></li>
+                                    <li className='list-item'><code>sort</code> is either <code>id</code> (oldest first), <code>usage</code> or <code>popularity</code> (most used on-chain first), <code>added</code> or <code>added_at</code> (newest first) or <code>text</code> (alphabetically), defaulting to <code>usage</code> for hashes and <code>id</code> otherwise</li>
+                                    <li className='list-item'><code>order</code> is either <code>asc</code> or <code>desc</code>, defaulting to the direction given above</li>
+                                    <li className='list-item'><code>page</code> is the page index, starting at 1 (the default)</li>
+                                    <li className='list-item'><code>page_size</code> is the number of signatures per page, between 1 and 500 (defaults to 100)</li>
+                                </ul>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/v2/signatures?query=balanceOf&kind=function&source=etherscan' url='https://api.etherface.io/v2/signatures?query=balanceOf&kind=function&source=etherscan' /> returns all functions starting with <code>balanceOf</code> found on Etherscan</p>
+                            </div>
+                        }
+                    />
+
+                    <Paragraph
+                        title={<code>{`/signature-database/v1/lookup?function={selectors}&event={topics}&filter={filter}`}</code>}
+                        content={
+                            <div>
+                                <p>Looks up signatures in the response format of the <LinkItem text='openchain.xyz' url='https://openchain.xyz/signatures' /> signature database, such that its clients can switch to Etherface by replacing the base URL, where all parameters are optional and</p>
+                                <ul className='list-disc list-inside'>
+                                    <li className='list-item'><code>function</code> is a comma separated list of function (or error) selectors, i.e. 8 hex characters each</li>
+                                    <li className='list-item'><code>event</code> is a comma separated list of event topics, i.e. 64 hex characters each</li>
+                                    <li className='list-item'><code>filter</code> omits signatures flagged as <code>filtered</code>, i.e. invalid ones, unless set to <code>false</code></li>
+                                </ul>
+                                <p>Returns <code className='text-sm'>{`{"ok": true, "result": {"function": {"0x...": [ {"name": ..., "filtered": ...}, ...] }, "event": {...} } }`}</code>, the signatures of unknown hashes being <code>null</code>. At most 100 hashes can be looked up at once.</p>
+                                <p><b>Example:</b> <LinkItem text='api.etherface.io/signature-database/v1/lookup?function=0xa9059cbb' url='https://api.etherface.io/signature-database/v1/lookup?function=0xa9059cbb' /></p>
+                            </div>
+                        }
+                    />
                 </div>
         </Layout>
     )
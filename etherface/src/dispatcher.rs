@@ -0,0 +1,126 @@
+//! Delivery of newly found signatures to consumer webhooks.
+//!
+//! Deliveries are queued by a database trigger as soon as any fetcher finds a signature matching a webhooks
+//! kind and prefix filter (see the `webhook` migration), whereas this worker POSTs them in batches of up to
+//! [`BATCH_SIZE`] signatures every `Config::webhook_delivery_interval` seconds, signed with the webhooks
+//! secret (see `etherface_lib::api::webhook::sign`). Failed calls are retried with an exponential backoff,
+//! keeping the order of deliveries; webhooks failing [`MAX_CONSECUTIVE_FAILURES`] times in a row are
+//! disabled.
+
+use anyhow::Error;
+use chrono::Duration;
+use chrono::Utc;
+use etherface_lib::api::webhook::WebhookClient;
+use etherface_lib::config::Config;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::StreamedSignature;
+use etherface_lib::model::Webhook;
+use log::debug;
+use log::warn;
+use serde_json::json;
+
+/// Name of the dispatcher within the `worker_status` table.
+const WORKER_NAME: &str = "webhook-dispatcher";
+
+/// Number of webhooks delivered to per iteration.
+const WEBHOOKS_PER_ITERATION: i64 = 100;
+
+/// Maximum number of signatures delivered per webhook call.
+const BATCH_SIZE: i64 = 100;
+
+/// Number of failed webhook calls in a row after which a webhook is disabled.
+const MAX_CONSECUTIVE_FAILURES: i32 = 15;
+
+/// Delay before retrying the first failed webhook call, doubled with each further one.
+const RETRY_BACKOFF_SECONDS: i64 = 30;
+
+/// Upper bound of the delay before retrying a failed webhook call.
+const RETRY_MAX_BACKOFF_SECONDS: i64 = 6 * 60 * 60;
+
+pub fn start(one_shot: bool) -> Result<(), Error> {
+    let config = Config::new()?;
+    let dbc = DatabaseClient::new()?;
+    let client = WebhookClient::new()?;
+    dbc.worker_status().register(WORKER_NAME)?;
+
+    loop {
+        dbc.worker_status().heartbeat(WORKER_NAME, None)?;
+
+        let webhooks = dbc.webhook().get_due(WEBHOOKS_PER_ITERATION)?;
+        let mut delivered = 0;
+        for webhook in &webhooks {
+            delivered += deliver(&dbc, &client, webhook)?;
+        }
+
+        dbc.worker_status().add_processed(WORKER_NAME, delivered as i64)?;
+        if delivered > 0 {
+            debug!("Delivered {delivered} signatures to {} webhooks", webhooks.len());
+        }
+
+        // Continue right away if there might be more due webhooks
+        if webhooks.len() as i64 == WEBHOOKS_PER_ITERATION {
+            continue;
+        }
+
+        if one_shot {
+            return Ok(());
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(config.webhook_delivery_interval));
+    }
+}
+
+/// Delivers the oldest pending signatures to `webhook`, returning the number of delivered signatures.
+fn deliver(dbc: &DatabaseClient, client: &WebhookClient, webhook: &Webhook) -> Result<usize, Error> {
+    let deliveries = dbc.webhook().get_pending_deliveries(webhook, BATCH_SIZE)?;
+    let ids: Vec<i64> = deliveries.iter().map(|x| x.id).collect();
+
+    // Signatures not found within the webhooks source are dropped without calling it
+    let signatures: Vec<StreamedSignature> =
+        deliveries.iter().filter(|x| x.matches_source).map(|x| x.to_streamed()).collect();
+    if signatures.is_empty() {
+        dbc.webhook().delete_deliveries(&ids)?;
+        return Ok(0);
+    }
+
+    let body = json!({ "event": "signatures_found", "webhook_id": webhook.id, "signatures": signatures });
+    match client.post_signed(&webhook.url, &body, &webhook.secret) {
+        Ok(()) => {
+            dbc.webhook().delete_deliveries(&ids)?;
+            dbc.webhook().set_delivered(webhook.id)?;
+            Ok(signatures.len())
+        }
+
+        Err(why) => {
+            let failures = webhook.failure_count + 1;
+            let retry_at = match failures < MAX_CONSECUTIVE_FAILURES {
+                true => Some(Utc::now() + retry_backoff(failures)),
+                false => None,
+            };
+
+            warn!("Failed to call webhook {} ({failures} failures in a row); {why}", webhook.id);
+            dbc.webhook().set_failed(webhook.id, &why.to_string(), retry_at)?;
+            Ok(0)
+        }
+    }
+}
+
+/// Returns the delay before retrying a webhook call after `failures` failed calls in a row, i.e.
+/// `RETRY_BACKOFF_SECONDS * 2^(failures - 1)` capped at [`RETRY_MAX_BACKOFF_SECONDS`].
+fn retry_backoff(failures: i32) -> Duration {
+    let factor = 2_i64.saturating_pow(failures.saturating_sub(1).max(0) as u32);
+    Duration::seconds(RETRY_BACKOFF_SECONDS.saturating_mul(factor).min(RETRY_MAX_BACKOFF_SECONDS))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dispatcher::retry_backoff;
+    use chrono::Duration;
+
+    #[test]
+    fn retry_backoff_doubles_up_to_six_hours() {
+        assert_eq!(retry_backoff(1), Duration::seconds(30));
+        assert_eq!(retry_backoff(3), Duration::minutes(2));
+        assert_eq!(retry_backoff(20), Duration::hours(6));
+    }
+}
@@ -0,0 +1,186 @@
+//! Snapshots of the public dataset.
+//!
+//! Every `Config::snapshot_interval` seconds (daily by default) all exported tables as well as a flat
+//! `selector,text,kind` file (see `EXPORT_SOURCES`) are written as gzip compressed CSV files into
+//! `{Config::snapshot_directory}/{YYYY-MM-DD}/`, such that the whole dataset can be consumed without paging
+//! through the REST API. Only the latest `Config::snapshot_retention` snapshots are kept. If a bucket is
+//! configured each snapshot is additionally uploaded to S3(-compatible) storage using the same
+//! `{YYYY-MM-DD}/{name}.csv.gz` keys.
+
+use anyhow::Error;
+use chrono::NaiveDate;
+use chrono::Utc;
+use etherface_lib::config::Config;
+use etherface_lib::database::handler::export::ExportSource;
+use etherface_lib::database::handler::export::EXPORT_SOURCES;
+use etherface_lib::database::handler::DatabaseClient;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::info;
+use s3::bucket::Bucket;
+use s3::creds::Credentials;
+use s3::region::Region;
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Name of the exporter within the `worker_status` table.
+const WORKER_NAME: &str = "snapshot-exporter";
+
+/// Width of the key ranges exported at once, see [`etherface_lib::database::handler::export`].
+const CHUNK_SIZE: i64 = 10_000;
+
+/// Date format of the snapshot directories.
+const SNAPSHOT_NAME_FORMAT: &str = "%Y-%m-%d";
+
+pub fn start(one_shot: bool) -> Result<(), Error> {
+    let config = Config::new()?;
+    let directory = match &config.snapshot_directory {
+        Some(directory) => PathBuf::from(directory),
+        None => anyhow::bail!("No snapshot directory configured, see `.env-EXAMPLE`"),
+    };
+
+    let dbc = DatabaseClient::new()?;
+    dbc.worker_status().register(WORKER_NAME)?;
+
+    loop {
+        let name = Utc::now().format(SNAPSHOT_NAME_FORMAT).to_string();
+        let snapshot = directory.join(&name);
+
+        // Written into a separate directory first such that incomplete snapshots are never visible
+        let partial = directory.join(format!("{name}.partial"));
+        if partial.exists() {
+            fs::remove_dir_all(&partial)?;
+        }
+        fs::create_dir_all(&partial)?;
+
+        for source in &EXPORT_SOURCES {
+            dbc.worker_status().heartbeat(WORKER_NAME, Some(source.name))?;
+            let rows = export(&dbc, source, &partial.join(file_name(source)))?;
+            dbc.worker_status().add_processed(WORKER_NAME, rows as i64)?;
+        }
+
+        if snapshot.exists() {
+            fs::remove_dir_all(&snapshot)?;
+        }
+        fs::rename(&partial, &snapshot)?;
+        info!("Created snapshot {}", snapshot.display());
+
+        if let Some(bucket) = &config.snapshot_s3_bucket {
+            upload(&config, bucket, &name, &snapshot)?;
+            info!("Uploaded snapshot {name} to bucket {bucket}");
+        }
+
+        for expired in expired_snapshots(&directory, config.snapshot_retention)? {
+            fs::remove_dir_all(&expired)?;
+        }
+
+        if one_shot {
+            return Ok(());
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(config.snapshot_interval));
+    }
+}
+
+pub(crate) fn file_name(source: &ExportSource) -> String {
+    format!("{}.csv.gz", source.name)
+}
+
+/// Writes all rows of `source` into the gzip compressed CSV file at `path`, returning the number of rows.
+fn export(dbc: &DatabaseClient, source: &ExportSource, path: &Path) -> Result<usize, Error> {
+    let mut writer = GzEncoder::new(BufWriter::new(File::create(path)?), Compression::default());
+    writeln!(writer, "{}", source.header)?;
+
+    let mut rows = 0;
+    if let Some((min, max)) = dbc.export().key_range(source)? {
+        let mut start = min;
+        while start <= max {
+            for line in dbc.export().lines(source, start, start + CHUNK_SIZE)? {
+                writeln!(writer, "{line}")?;
+                rows += 1;
+            }
+
+            start += CHUNK_SIZE;
+        }
+    }
+
+    writer.finish()?.flush()?;
+    Ok(rows)
+}
+
+/// Uploads all files of the `snapshot` directory into `bucket`, prefixed by the snapshots `name`.
+fn upload(config: &Config, bucket: &str, name: &str, snapshot: &Path) -> Result<(), Error> {
+    let credentials = Credentials::new(
+        config.snapshot_s3_access_key.as_deref(),
+        config.snapshot_s3_secret_key.as_deref(),
+        None,
+        None,
+        None,
+    )?;
+
+    // Most S3-compatible storages (e.g. MinIO) only support path-style requests
+    let bucket = match &config.snapshot_s3_endpoint {
+        Some(endpoint) => {
+            let region = Region::Custom {
+                region: config.snapshot_s3_region.clone(),
+                endpoint: endpoint.clone(),
+            };
+
+            Bucket::new(bucket, region, credentials)?.with_path_style()
+        }
+
+        None => Bucket::new(bucket, config.snapshot_s3_region.parse()?, credentials)?,
+    };
+
+    for source in &EXPORT_SOURCES {
+        let mut file = File::open(snapshot.join(file_name(source)))?;
+        let status = bucket.put_object_stream(&mut file, format!("{name}/{}", file_name(source)))?;
+
+        if status != 200 {
+            anyhow::bail!("Failed to upload {}, status code {status}", file_name(source));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the snapshots within `directory` exceeding the `retention` most recent ones.
+fn expired_snapshots(directory: &Path, retention: usize) -> Result<Vec<PathBuf>, Error> {
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|x| x.to_str()).unwrap_or_default();
+
+        if path.is_dir() && NaiveDate::parse_from_str(name, SNAPSHOT_NAME_FORMAT).is_ok() {
+            snapshots.push(path);
+        }
+    }
+
+    // The date format sorts chronologically
+    snapshots.sort();
+    snapshots.reverse();
+    Ok(snapshots.into_iter().skip(retention).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expired_snapshots;
+    use std::fs;
+
+    #[test]
+    fn expired_snapshots_keeps_most_recent() {
+        let directory = std::env::temp_dir().join(format!("etherface-snapshots-{}", std::process::id()));
+        for name in ["2022-10-01", "2022-10-03", "2022-10-02", "2022-10-03.partial", "unrelated"] {
+            fs::create_dir_all(directory.join(name)).unwrap();
+        }
+
+        let expired = expired_snapshots(&directory, 2).unwrap();
+        assert_eq!(expired, vec![directory.join("2022-10-01")]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -1,27 +1,79 @@
 //! Fetcher for <https://etherscan.io/>
-//! 
-//! Polls the <https://etherscan.io/contractsVerified> site every [`FETCHER_POLLING_SLEEP_TIME`], extracting
-//! all contract metadata inserting them into the database (if not already present). 
+//!
+//! Polls the <https://etherscan.io/contractsVerified> site every `Config::fetcher_polling_interval`, extracting
+//! all contract metadata inserting them into the database (if not already present).
+//!
+//! Additionally with each iteration up to [`NUM_CODE_CHECKS_PER_ITERATION`] stored contracts whose code
+//! hasn't been checked within the last [`CODE_CHECK_INTERVAL_DAYS`] days are checked for being
+//! self-destructed, i.e. having no code anymore.
 use crate::fetcher::Fetcher;
-use crate::fetcher::FETCHER_POLLING_SLEEP_TIME;
 use anyhow::Error;
 use etherface_lib::api::etherscan::EtherscanClient;
+use etherface_lib::config::Config;
 use etherface_lib::database::handler::DatabaseClient;
+use log::debug;
+use log::warn;
+
+/// Number of days after which the code of a contract is checked again.
+const CODE_CHECK_INTERVAL_DAYS: i64 = 30;
+
+/// Number of contracts whose code is checked per polling iteration.
+const NUM_CODE_CHECKS_PER_ITERATION: i64 = 500;
+
+/// Name of this fetcher within the `worker_status` table.
+const WORKER_NAME: &str = "etherscan-fetcher";
 
 #[derive(Debug)]
 pub struct EtherscanFetcher;
 
 impl Fetcher for EtherscanFetcher {
-    fn start(&self) -> Result<(), Error> {
+    fn start(&self, one_shot: bool) -> Result<(), Error> {
+        let config = Config::new()?;
         let esc = EtherscanClient::new()?;
         let dbc = DatabaseClient::new()?;
 
+        dbc.worker_status().register(WORKER_NAME)?;
+
         loop {
-            for contract in esc.get_verified_contracts()? {
-                dbc.etherscan_contract().insert(&contract);
+            dbc.worker_status().heartbeat(WORKER_NAME, None)?;
+
+            let contracts = esc.get_verified_contracts()?;
+            for contract in &contracts {
+                dbc.etherscan_contract().insert(contract)?;
             }
+            dbc.worker_status().add_processed(WORKER_NAME, contracts.len() as i64)?;
 
-            std::thread::sleep(std::time::Duration::from_secs(FETCHER_POLLING_SLEEP_TIME));
+            check_contract_code(&esc, &dbc)?;
+
+            if one_shot {
+                return Ok(());
+            }
+
+            std::thread::sleep(std::time::Duration::from_secs(config.fetcher_polling_interval));
         }
     }
 }
+
+/// Flags contracts whose code is empty, i.e. which self-destructed since they were verified.
+fn check_contract_code(esc: &EtherscanClient, dbc: &DatabaseClient) -> Result<(), Error> {
+    let contracts =
+        dbc.etherscan_contract().get_code_check_due(CODE_CHECK_INTERVAL_DAYS, NUM_CODE_CHECKS_PER_ITERATION)?;
+
+    for contract in contracts {
+        match esc.get_code(&contract.address) {
+            Ok(code) => {
+                let destroyed = code == "0x";
+                if destroyed {
+                    debug!("Contract {} has no code, flagging it as destroyed", contract.address);
+                }
+
+                dbc.etherscan_contract().set_code_checked(&contract, destroyed)?;
+            }
+
+            // Checked again in the next iteration
+            Err(why) => warn!("Failed to check code of {}; {why}", contract.address),
+        }
+    }
+
+    Ok(())
+}
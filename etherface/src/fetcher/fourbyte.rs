@@ -1,36 +1,41 @@
 //! Fetcher for <https://www.4byte.directory/>
 //!
 //! Polls the <https://www.4byte.directory/api/v1/signatures/> and <https://www.4byte.directory/api/v1/event-signatures/>
-//! API endpoints every [`FETCHER_POLLING_SLEEP_TIME`] seconds inserting new signatures into the database. 
+//! API endpoints every `Config::fetcher_polling_interval` seconds inserting new signatures into the database. 
 //! Instead of retrieving all pages from these paginated API endpoints however, the fetcher only retrieves the latest 
 //! pages that contain signatures not present in our database. That is fetch one page, check if the page contains any signature
 //! already present in our database and if not continue with the next page until the condition no longer is valid in which case
 //! sleep before repeating the process starting from page one again.
 
 use crate::fetcher::Fetcher;
-use crate::fetcher::FETCHER_POLLING_SLEEP_TIME;
 use anyhow::Error;
 use chrono::Utc;
 use etherface_lib::api::fourbyte::FourbyteClient;
+use etherface_lib::config::Config;
 use etherface_lib::database::handler::DatabaseClient;
 use etherface_lib::model::MappingSignatureFourbyte;
 use etherface_lib::model::SignatureWithMetadata;
 use log::info;
 
+/// Name of this fetcher within the `worker_status` table.
+const WORKER_NAME: &str = "fourbyte-fetcher";
+
 #[derive(Debug)]
 pub struct FourbyteFetcher;
 
 impl Fetcher for FourbyteFetcher {
-    fn start(&self) -> Result<(), Error> {
+    fn start(&self, one_shot: bool) -> Result<(), Error> {
+        let config = Config::new()?;
         let dbc = DatabaseClient::new()?;
+        dbc.worker_status().register(WORKER_NAME)?;
 
         // Check if this the first run and if so retrieve and insert all event / function signatures from 4Byte
         // into our database
-        if dbc.mapping_signature_fourbyte().get_events_count() == 0 {
+        if dbc.mapping_signature_fourbyte().get_events_count()? == 0 {
             initial_data_retrieval(&dbc, false)?;
         }
 
-        if dbc.mapping_signature_fourbyte().get_functions_count() == 0 {
+        if dbc.mapping_signature_fourbyte().get_functions_count()? == 0 {
             initial_data_retrieval(&dbc, true)?;
         }
 
@@ -42,27 +47,38 @@ impl Fetcher for FourbyteFetcher {
         // - https://www.4byte.directory/api/v1/event-signatures/
         loop {
             // Create new client with each iteration because of internal (index) modifications
-            let mut fbc = FourbyteClient::new();
+            let mut fbc = FourbyteClient::new()?;
+            dbc.worker_status().heartbeat(WORKER_NAME, None)?;
 
             while let Some(signatures) = fbc.page_event_signature()? {
-                if insert_signature(&signatures, &dbc) == 0 {
+                let insert_count = insert_signature(&signatures, &dbc)?;
+                dbc.worker_status().add_processed(WORKER_NAME, insert_count as i64)?;
+
+                if insert_count == 0 {
                     break;
                 }
             }
 
             while let Some(signatures) = fbc.page_function_signature()? {
-                if insert_signature(&signatures, &dbc) == 0 {
+                let insert_count = insert_signature(&signatures, &dbc)?;
+                dbc.worker_status().add_processed(WORKER_NAME, insert_count as i64)?;
+
+                if insert_count == 0 {
                     break;
                 }
             }
 
-            std::thread::sleep(std::time::Duration::from_secs(FETCHER_POLLING_SLEEP_TIME));
+            if one_shot {
+                return Ok(());
+            }
+
+            std::thread::sleep(std::time::Duration::from_secs(config.fetcher_polling_interval));
         }
     }
 }
 
 fn initial_data_retrieval(dbc: &DatabaseClient, function_endpoint: bool) -> Result<(), Error> {
-    let mut fbc = FourbyteClient::new();
+    let mut fbc = FourbyteClient::new()?;
 
     info!("Retrieving all 4Byte signatures...");
     let mut signatures = Vec::new();
@@ -80,42 +96,47 @@ fn initial_data_retrieval(dbc: &DatabaseClient, function_endpoint: bool) -> Resu
     }
 
     info!("Inserting retrieved 4Byte signatures...");
-    for signature in signatures {
-        let inserted_signature = dbc.signature().insert(&signature);
-        let mapping = MappingSignatureFourbyte {
+    let mappings = dbc
+        .signature()
+        .insert_many(&signatures)?
+        .iter()
+        .zip(&signatures)
+        .map(|(inserted_signature, signature)| MappingSignatureFourbyte {
             signature_id: inserted_signature.id,
             kind: signature.kind,
             added_at: Utc::now(),
-        };
+            last_seen_at: Utc::now(),
+        })
+        .collect::<Vec<_>>();
 
-        dbc.mapping_signature_fourbyte().insert(&mapping);
-    }
+    dbc.mapping_signature_fourbyte().insert_many(&mappings)?;
 
     Ok(())
 }
 
-fn insert_signature(signatures: &Vec<SignatureWithMetadata>, dbc: &DatabaseClient) -> usize {
+fn insert_signature(signatures: &Vec<SignatureWithMetadata>, dbc: &DatabaseClient) -> Result<usize, Error> {
     let mut insert_count = 0;
 
     for signature in signatures {
-        let inserted_signature = dbc.signature().insert(signature);
+        let inserted_signature = dbc.signature().insert(signature)?;
         let mapping = MappingSignatureFourbyte {
             signature_id: inserted_signature.id,
             kind: signature.kind,
             added_at: Utc::now(),
+            last_seen_at: Utc::now(),
         };
 
-        match dbc.mapping_signature_fourbyte().get(&mapping) {
+        match dbc.mapping_signature_fourbyte().get(&mapping)? {
             // Signature already exists in our database; we're in sync with 4Byte
-            Some(_) => return insert_count,
+            Some(_) => return Ok(insert_count),
 
             // Signature does not exist in our database; insert new signature
             None => {
-                dbc.mapping_signature_fourbyte().insert(&mapping);
+                dbc.mapping_signature_fourbyte().insert(&mapping)?;
                 insert_count += 1;
             }
         }
     }
 
-    insert_count
+    Ok(insert_count)
 }
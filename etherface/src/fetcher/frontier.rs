@@ -0,0 +1,136 @@
+//! Crawl frontier strategies of the GitHub fetcher.
+//!
+//! A [`FrontierStrategy`] decides which unvisited Solidity repository owners (GitHub users) and repositories
+//! are visited next within [`super::github::GithubCrawler`]s crawling iterations. The strategy is selected
+//! via [`Config::crawler_frontier`], allowing the crawler to be tuned without code changes.
+
+use etherface_lib::config::Config;
+use etherface_lib::config::CrawlerFrontier;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::error::Error;
+use etherface_lib::model::GithubRepositoryDatabase;
+use etherface_lib::model::GithubUserDatabase;
+
+/// Strategy selecting the next resources to visit from the crawl frontier.
+pub trait FrontierStrategy: std::fmt::Debug {
+    /// Returns at most `limit` unvisited Solidity repository owners to visit next.
+    fn next_owners(&self, dbc: &DatabaseClient, limit: usize) -> Result<Vec<GithubUserDatabase>, Error>;
+
+    /// Returns at most `limit` unvisited Solidity repositories to visit next.
+    fn next_repositories(
+        &self,
+        dbc: &DatabaseClient,
+        limit: usize,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error>;
+}
+
+/// Visits the most recently added resources first.
+#[derive(Debug)]
+pub struct Recency;
+
+/// Visits the resources which yielded the most signatures first, assuming their neighbourhood (owners and
+/// stargazers) is more likely to yield further signatures.
+#[derive(Debug)]
+pub struct Yield;
+
+/// Randomly samples resources weighted by their stargazer count, favouring popular resources while still
+/// reaching less popular ones.
+#[derive(Debug)]
+pub struct StarWeighted;
+
+/// Uniformly samples resources at random.
+#[derive(Debug)]
+pub struct Random;
+
+/// Visits the resources starred by the most Solidity stargazers first, i.e. users which starred several
+/// Solidity repositories, assuming resources popular among them are more likely to contain Solidity code.
+#[derive(Debug)]
+pub struct SolidityStargazers;
+
+impl FrontierStrategy for Recency {
+    fn next_owners(&self, dbc: &DatabaseClient, limit: usize) -> Result<Vec<GithubUserDatabase>, Error> {
+        let mut owners = dbc.github_user().get_unvisited_solidity_repository_owners_orderd_by_added_at()?;
+        owners.truncate(limit);
+        Ok(owners)
+    }
+
+    fn next_repositories(
+        &self,
+        dbc: &DatabaseClient,
+        limit: usize,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        let mut repos = dbc.github_repository().get_unvisited_ordered_by_added_at()?;
+        repos.truncate(limit);
+        Ok(repos)
+    }
+}
+
+impl FrontierStrategy for Yield {
+    fn next_owners(&self, dbc: &DatabaseClient, limit: usize) -> Result<Vec<GithubUserDatabase>, Error> {
+        dbc.github_user().get_unvisited_solidity_repository_owners_ordered_by_signature_count(limit as i64)
+    }
+
+    fn next_repositories(
+        &self,
+        dbc: &DatabaseClient,
+        limit: usize,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        let mut repos = dbc.github_repository().get_unvisited_ordered_by_signature_count()?;
+        repos.truncate(limit);
+        Ok(repos)
+    }
+}
+
+impl FrontierStrategy for StarWeighted {
+    fn next_owners(&self, dbc: &DatabaseClient, limit: usize) -> Result<Vec<GithubUserDatabase>, Error> {
+        dbc.github_user().get_unvisited_solidity_repository_owners_weighted_by_stargazers(limit as i64)
+    }
+
+    fn next_repositories(
+        &self,
+        dbc: &DatabaseClient,
+        limit: usize,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        dbc.github_repository().get_unvisited_weighted_by_stargazers(limit as i64)
+    }
+}
+
+impl FrontierStrategy for Random {
+    fn next_owners(&self, dbc: &DatabaseClient, limit: usize) -> Result<Vec<GithubUserDatabase>, Error> {
+        dbc.github_user().get_unvisited_solidity_repository_owners_random(limit as i64)
+    }
+
+    fn next_repositories(
+        &self,
+        dbc: &DatabaseClient,
+        limit: usize,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        dbc.github_repository().get_unvisited_random(limit as i64)
+    }
+}
+
+impl FrontierStrategy for SolidityStargazers {
+    fn next_owners(&self, dbc: &DatabaseClient, limit: usize) -> Result<Vec<GithubUserDatabase>, Error> {
+        let limit = limit as i64;
+        dbc.github_user().get_unvisited_solidity_repository_owners_ordered_by_solidity_stargazers(limit)
+    }
+
+    fn next_repositories(
+        &self,
+        dbc: &DatabaseClient,
+        limit: usize,
+    ) -> Result<Vec<GithubRepositoryDatabase>, Error> {
+        dbc.github_repository().get_unvisited_ordered_by_solidity_stargazers(limit as i64)
+    }
+}
+
+/// Returns the frontier strategy configured in `config`.
+pub fn from_config(config: &Config) -> Box<dyn FrontierStrategy> {
+    match config.crawler_frontier {
+        CrawlerFrontier::Recency => Box::new(Recency),
+        CrawlerFrontier::Yield => Box::new(Yield),
+        CrawlerFrontier::StarWeighted => Box::new(StarWeighted),
+        CrawlerFrontier::Random => Box::new(Random),
+        CrawlerFrontier::SolidityStargazers => Box::new(SolidityStargazers),
+    }
+}
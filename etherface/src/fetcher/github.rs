@@ -15,11 +15,13 @@ use chrono::Date;
 use chrono::DateTime;
 use chrono::TimeZone;
 use chrono::Utc;
+use etherface_lib::api::github::handler::graphql::GRAPHQL_BATCH_SIZE;
 use etherface_lib::api::github::GithubClient;
+use etherface_lib::config::Config;
 use etherface_lib::database::handler::DatabaseClient;
 use etherface_lib::error::Error;
 use etherface_lib::model::GithubRepository;
-use etherface_lib::model::GithubUser;
+use etherface_lib::model::MappingStargazer;
 use log::debug;
 use log::info;
 use log::trace;
@@ -27,14 +29,19 @@ use std::sync::mpsc;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::Sender;
 
+use super::frontier;
+use super::frontier::FrontierStrategy;
 use super::Fetcher;
 
 #[derive(Debug)]
 pub struct GithubFetcher;
 
 impl Fetcher for GithubFetcher {
-    fn start(&self) -> Result<(), anyhow::Error> {
-        Ok(GithubCrawler::new()?.start()?)
+    fn start(&self, one_shot: bool) -> Result<(), anyhow::Error> {
+        match one_shot {
+            true => Ok(GithubCrawler::new()?.start_once()?),
+            false => Ok(GithubCrawler::new()?.start()?),
+        }
     }
 }
 
@@ -58,96 +65,181 @@ struct ChannelMessage {
     pub new_event_date: DateTime<Utc>,
 }
 
+/// Name of this fetcher within the `worker_status` table.
+const WORKER_NAME: &str = "github-fetcher";
+
 pub struct GithubCrawler {
     dbc: DatabaseClient,
     ghc: GithubClient,
-}
 
-/// The number of users and/or repositories we want to visit per crawling iteration.
-/// Choosing a higher number means longer crawling iterations which _may_ set events into a queue until the
-/// iteration is done; for example if an iteration takes ~1 hour for N resource visits, then no event can be
-/// executed within that timeframe but will instead be queued in a FIFO manner.
-const NUM_RESOURCE_VISITS_PER_CRAWLING_ITERATION: usize = 50;
+    /// The number of users and/or repositories we want to visit per crawling iteration.
+    /// Choosing a higher number means longer crawling iterations which _may_ set events into a queue until
+    /// the iteration is done; for example if an iteration takes ~1 hour for N resource visits, then no event
+    /// can be executed within that timeframe but will instead be queued in a FIFO manner.
+    num_resource_visits_per_crawling_iteration: usize,
+
+    /// Strategy deciding which unvisited users and repositories are visited next.
+    frontier: Box<dyn FrontierStrategy>,
+
+    /// Frequencies of the [`Event::SearchRepositories`], [`Event::CheckRepositories`] and
+    /// [`Event::CheckUsers`] events.
+    search_repositories_frequency: chrono::Duration,
+    check_repositories_frequency: chrono::Duration,
+    check_users_frequency: chrono::Duration,
+}
 
 impl GithubCrawler {
     pub fn new() -> Result<Self, Error> {
+        let config = Config::new()?;
+        let days = chrono::Duration::days;
+
         Ok(GithubCrawler {
             dbc: DatabaseClient::new()?,
             ghc: GithubClient::new()?,
+            num_resource_visits_per_crawling_iteration: config.crawler_resource_visits_per_iteration,
+            frontier: frontier::from_config(&config),
+            search_repositories_frequency: days(config.crawler_search_repositories_frequency),
+            check_repositories_frequency: days(config.crawler_check_repositories_frequency),
+            check_users_frequency: days(config.crawler_check_users_frequency),
         })
     }
 
     pub fn start(&self) -> Result<(), Error> {
-        // Check if this is the first ever run and if so fetch all Solidity repositories created between 2015
-        // and today's date.
-        if self.dbc.github_repository().get_total_count() == 0 {
-            for repo in self.search_solidity_repositories_starting_from(Utc.ymd(2015, 1, 1), true)? {
-                self.insert_repository_if_not_exists(&repo, false)?;
-            }
-        }
+        self.dbc.worker_status().register(WORKER_NAME)?;
+        self.initial_data_retrieval()?;
 
         let (tx, rx): (Sender<ChannelMessage>, Receiver<ChannelMessage>) = mpsc::channel();
-        start_background_event(tx.clone(), Event::SearchRepositories, chrono::Duration::days(1))?;
-        start_background_event(tx.clone(), Event::CheckRepositories, chrono::Duration::days(21))?;
-        start_background_event(tx, Event::CheckUsers, chrono::Duration::days(21))?;
+        start_background_event(tx.clone(), Event::SearchRepositories, self.search_repositories_frequency)?;
+        start_background_event(tx.clone(), Event::CheckRepositories, self.check_repositories_frequency)?;
+        start_background_event(tx, Event::CheckUsers, self.check_users_frequency)?;
 
         // Sleep a few seconds to give the background event schedulers some time to fetch data from the
         // database and issue events if possible
         std::thread::sleep(std::time::Duration::from_secs(5));
 
         loop {
+            self.dbc.worker_status().heartbeat(WORKER_NAME, None)?;
+
             match rx.try_recv() {
-                Ok(msg) => match msg.event {
-                    Event::SearchRepositories => {
-                        debug!("Starting SearchRepositories event");
-                        let prev_event_date = self.dbc.github_crawler_metadata().get().last_repository_search.date();
-
-                        debug!("Prev event date: {prev_event_date}");
-                        self.insert_recently_created_solidity_repositories(prev_event_date)?;
-                        self.upsert_recently_updated_solidity_repositories(prev_event_date)?;
-
-                        // Only set if previous function calls were successful
-                        debug!("Prev event date: {}", msg.new_event_date);
-                        self.dbc.github_crawler_metadata().update_last_repository_search_date(msg.new_event_date);
-                        debug!("{}", self.dbc.github_crawler_metadata().get().last_repository_search.date());
+                Ok(msg) => self.handle_event(msg.event, msg.new_event_date)?,
+
+                Err(why) => match why {
+                    mpsc::TryRecvError::Empty => {
+                        if !self.start_one_crawling_iteration()? {
+                            info!("Crawl frontier exhausted, waiting for the next event");
+                            let msg = self.wait_for_event(&rx)?;
+                            self.handle_event(msg.event, msg.new_event_date)?;
+                        }
                     }
 
-                    Event::CheckRepositories => {
-                        debug!("Starting CheckRepositories event");
-                        self.find_repository_updates(180)?;
+                    mpsc::TryRecvError::Disconnected => return Err(Error::CrawlerChannelDisconnected),
+                },
+            }
+        }
+    }
 
-                        // Only set if previous function calls were successful
-                        self.dbc.github_crawler_metadata().update_last_repository_check_date(msg.new_event_date);
-                    }
+    /// Blocks until the next event is received, updating the workers heartbeat in the meantime.
+    fn wait_for_event(&self, rx: &Receiver<ChannelMessage>) -> Result<ChannelMessage, Error> {
+        loop {
+            self.dbc.worker_status().heartbeat(WORKER_NAME, None)?;
 
-                    Event::CheckUsers => {
-                        debug!("Starting CheckUser event");
-                        self.find_user_updates(180)?;
+            match rx.recv_timeout(std::time::Duration::from_secs(60)) {
+                Ok(msg) => return Ok(msg),
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return Err(Error::CrawlerChannelDisconnected),
+            }
+        }
+    }
 
-                        // Only set if previous commands were successful
-                        self.dbc.github_crawler_metadata().update_last_user_check_date(msg.new_event_date);
-                    }
-                },
+    /// Same as [`GithubCrawler::start`] but instead of looping forever only executes all events which are due
+    /// followed by one crawling iteration, e.g. for cron-style operation.
+    pub fn start_once(&self) -> Result<(), Error> {
+        self.dbc.worker_status().register(WORKER_NAME)?;
+        self.initial_data_retrieval()?;
+
+        let events = [
+            (Event::SearchRepositories, self.search_repositories_frequency),
+            (Event::CheckRepositories, self.check_repositories_frequency),
+            (Event::CheckUsers, self.check_users_frequency),
+        ];
+
+        for (event, freq) in events {
+            if Utc::now() - last_event_date(&self.dbc, event) >= freq {
+                self.handle_event(event, Utc::now())?;
+            }
+        }
 
-                Err(why) => match why {
-                    mpsc::TryRecvError::Empty => self.start_one_crawling_iteration()?,
-                    mpsc::TryRecvError::Disconnected => return Err(Error::CrawlerChannelDisconnected),
-                },
+        self.dbc.worker_status().heartbeat(WORKER_NAME, None)?;
+        if !self.start_one_crawling_iteration()? {
+            info!("Crawl frontier exhausted, nothing to visit");
+        }
+
+        Ok(())
+    }
+
+    /// Checks if this is the first ever run and if so fetches all Solidity repositories created between 2015
+    /// and today's date.
+    fn initial_data_retrieval(&self) -> Result<(), Error> {
+        if self.dbc.github_repository().get_total_count()? == 0 {
+            for repo in self.search_solidity_repositories_starting_from(Utc.ymd(2015, 1, 1), true)? {
+                self.insert_repository_if_not_exists(&repo, false)?;
             }
         }
+
+        Ok(())
+    }
+
+    fn handle_event(&self, event: Event, new_event_date: DateTime<Utc>) -> Result<(), Error> {
+        match event {
+            Event::SearchRepositories => {
+                debug!("Starting SearchRepositories event");
+                let prev_event_date = self.dbc.github_crawler_metadata().get()?.last_repository_search.date();
+
+                debug!("Prev event date: {prev_event_date}");
+                self.insert_recently_created_solidity_repositories(prev_event_date)?;
+                self.upsert_recently_updated_solidity_repositories(prev_event_date)?;
+
+                // Only set if previous function calls were successful
+                debug!("Prev event date: {}", new_event_date);
+                self.dbc.github_crawler_metadata().update_last_repository_search_date(new_event_date)?;
+                debug!("{}", self.dbc.github_crawler_metadata().get()?.last_repository_search.date());
+            }
+
+            Event::CheckRepositories => {
+                debug!("Starting CheckRepositories event");
+                self.find_repository_updates(180)?;
+
+                // Only set if previous function calls were successful
+                self.dbc.github_crawler_metadata().update_last_repository_check_date(new_event_date)?;
+            }
+
+            Event::CheckUsers => {
+                debug!("Starting CheckUser event");
+                self.find_user_updates(180)?;
+
+                // Only set if previous commands were successful
+                self.dbc.github_crawler_metadata().update_last_user_check_date(new_event_date)?;
+            }
+        }
+
+        Ok(())
     }
 
     /// Starts one crawling iteration which can be summarised as:
     /// Check if there are any unvisited Solidity repository owners (GitHub users)
-    ///     Yes => Take the first [`NUM_RESOURCE_VISITS_PER_CRAWLING_ITERATION`] owners from the database and
-    ///            retrieve their owned + starred repositories; set them as visited
-    ///     No  => Take the first [`NUM_RESOURCE_VISITS_PER_CRAWLING_ITERATION`] unvisited repositories from
-    ///            the database and for each one of them fetch their stargazers; for each fetched stargazer
-    ///            retrieve their owner + starred repositories; set them and the repository as visited
-    fn start_one_crawling_iteration(&self) -> Result<(), Error> {
-        let unvisited_solidity_repository_owners =
-            self.dbc.github_user().get_unvisited_solidity_repository_owners_orderd_by_added_at();
-        debug!("Starting one crawling iteration");
+    ///     Yes => Take `num_resource_visits_per_crawling_iteration` owners chosen by the frontier strategy
+    ///            and retrieve their owned + starred repositories; set them as visited
+    ///     No  => Take `num_resource_visits_per_crawling_iteration` unvisited repositories chosen by the
+    ///            frontier strategy and for each one of them fetch their stargazers; for each fetched
+    ///            stargazer retrieve their owner + starred repositories; set them and the repository as
+    ///            visited
+    ///
+    /// Returns `false` if the crawl frontier is exhausted, i.e. all Solidity repositories and their owners
+    /// have been visited, in which case only new repositories found by events can continue the crawling.
+    fn start_one_crawling_iteration(&self) -> Result<bool, Error> {
+        let limit = self.num_resource_visits_per_crawling_iteration;
+        let unvisited_solidity_repository_owners = self.frontier.next_owners(&self.dbc, limit)?;
+        debug!("Starting one crawling iteration ({:?} frontier)", self.frontier);
 
         match unvisited_solidity_repository_owners.is_empty() {
             false => {
@@ -155,64 +247,78 @@ impl GithubCrawler {
                     "Visiting unvisited solidity repository owners (len: {})",
                     unvisited_solidity_repository_owners.len()
                 );
-                for owner in unvisited_solidity_repository_owners
-                    .iter()
-                    .take(NUM_RESOURCE_VISITS_PER_CRAWLING_ITERATION)
-                {
+                for owner in &unvisited_solidity_repository_owners {
+                    self.dbc.worker_status().heartbeat(WORKER_NAME, Some(&owner.html_url))?;
                     self.get_and_insert_user_owned_repos(owner.id, true)?;
                     self.get_and_insert_user_starred_repos(owner.id, true)?;
 
-                    self.dbc.github_user().set_visited(owner.id);
+                    self.dbc.github_user().set_visited(owner.id)?;
+                    self.dbc.worker_status().add_processed(WORKER_NAME, 1)?;
                 }
             }
 
             true => {
-                let unvisited_repos = self.dbc.github_repository().get_unvisited_ordered_by_added_at();
+                let unvisited_repos = self.frontier.next_repositories(&self.dbc, limit)?;
                 debug!("Visiting unvisited solidity repositories (len: {})", unvisited_repos.len());
 
                 if unvisited_repos.is_empty() {
-                    panic!(
-                        "If you read this message, the crawler is not able to find further repositories;
-                        The reason for this is because all Solidity repositories in our database have been
-                        visited. There are a couple of ways to bypass this issue:
-                        - Issue an event hoping to find new repositories
-                        - Sleep until the next event
-                        - Widen the crawlers bubble to not only focus on Solidity repositories / owners, but
-                          we don't want this as we would get a lot of garbage input
-                        - ...
-                        We could also use the time to check if our local data needs to be updated to mirror
-                        GitHub's state."
-                    );
+                    return Ok(false);
                 }
 
-                for repo in unvisited_repos.iter().take(NUM_RESOURCE_VISITS_PER_CRAWLING_ITERATION) {
-                    let stargazers = self.get_stargazers_or_set_repository_deleted(repo.id)?;
+                for repo in &unvisited_repos {
+                    self.dbc.worker_status().heartbeat(WORKER_NAME, Some(&repo.html_url))?;
                     trace!("Visiting {}", repo.html_url);
 
-                    for stargazer in stargazers {
-                        if self.dbc.github_user().insert_if_not_exists(&stargazer).visited_at.is_some() {
-                            // We don't want to accidentally re-visit stargazers
-                            continue;
+                    // Stargazers are processed page-wise, as popular repositories may have hundreds of
+                    // thousands of them
+                    for page in self.ghc.repos(repo.id).stargazers_pages() {
+                        let stargazers = match page {
+                            Ok(stargazers) => stargazers,
+
+                            Err(Error::GithubResourceUnavailable(_)) => {
+                                self.dbc.github_repository().set_deleted(repo.id)?;
+                                break;
+                            }
+
+                            Err(why) => return Err(why),
+                        };
+
+                        let mut mappings = Vec::with_capacity(stargazers.len());
+                        for stargazer in stargazers {
+                            let user = self.dbc.github_user().insert_if_not_exists(&stargazer)?;
+                            mappings.push(MappingStargazer {
+                                user_id: user.id,
+                                repository_id: repo.id,
+                                added_at: Utc::now(),
+                            });
+
+                            if user.visited_at.is_some() {
+                                // We don't want to accidentally re-visit stargazers
+                                continue;
+                            }
+
+                            self.get_and_insert_user_owned_repos(stargazer.id, true)?;
+                            self.get_and_insert_user_starred_repos(stargazer.id, true)?;
+                            self.dbc.github_user().set_visited(stargazer.id)?;
                         }
 
-                        self.get_and_insert_user_owned_repos(stargazer.id, true)?;
-                        self.get_and_insert_user_starred_repos(stargazer.id, true)?;
-                        self.dbc.github_user().set_visited(stargazer.id);
+                        self.dbc.mapping_stargazer().insert_many(&mappings)?;
                     }
 
-                    self.dbc.github_repository().set_visited(repo.id);
+                    self.dbc.github_repository().set_visited(repo.id)?;
+                    self.dbc.worker_status().add_processed(WORKER_NAME, 1)?;
                 }
             }
         }
 
-        Ok(())
+        Ok(true)
     }
 }
 
 /// Helper Functions
 impl GithubCrawler {
     fn get_and_insert_user_owned_repos(&self, user_id: i32, crawled: bool) -> Result<(), Error> {
-        if let Ok(repos) = self.ghc.user(user_id).repos() {
+        for repos in self.ghc.user(user_id).repos_pages().map_while(Result::ok) {
             for repo in repos {
                 self.insert_repository_if_not_exists(&repo, crawled)?;
             }
@@ -222,28 +328,37 @@ impl GithubCrawler {
     }
 
     fn get_and_insert_user_starred_repos(&self, user_id: i32, crawled: bool) -> Result<(), Error> {
-        if let Ok(repos) = self.ghc.user(user_id).starred() {
+        for repos in self.ghc.user(user_id).starred_pages().map_while(Result::ok) {
+            let mut mappings = Vec::with_capacity(repos.len());
             for repo in repos {
                 self.insert_repository_if_not_exists(&repo, crawled)?;
+                mappings.push(MappingStargazer {
+                    user_id,
+                    repository_id: repo.id,
+                    added_at: Utc::now(),
+                });
             }
+
+            // Recorded after the repositories have been inserted, as the mappings reference them
+            self.dbc.mapping_stargazer().insert_many(&mappings)?;
         }
 
         Ok(())
     }
 
     fn insert_repository_if_not_exists(&self, entity: &GithubRepository, crawled: bool) -> Result<(), Error> {
-        if let Some(repo) = self.dbc.github_repository().get_by_id(entity.id) {
+        if let Some(repo) = self.dbc.github_repository().get_by_id(entity.id)? {
             if repo.is_deleted {
                 // Update the deleted status; this can happen if a repository was set to be private rather
                 // than deleted and we re-found it within our crawling process
-                self.dbc.github_repository().set_undeleted(repo.id);
+                self.dbc.github_repository().set_undeleted(repo.id)?;
             }
 
             return Ok(());
         }
 
-        self.dbc.github_user().insert_if_not_exists(&entity.owner);
-        self.dbc.github_repository().insert(entity, 0.0, crawled);
+        self.dbc.github_user().insert_if_not_exists(&entity.owner)?;
+        self.dbc.github_repository().insert(entity, 0.0, crawled)?;
 
         // Repositories created prior to 2018 are most likely not that interesting because according to our
         // data harvested from GitHub Solidity development started in 2018 and really kicked in in Q3 of 2020
@@ -257,7 +372,7 @@ impl GithubCrawler {
 
         // Fetch the Solidity ratio of the given repository
         if let Some(ratio) = self.get_solidity_ratio_or_set_repository_deleted(entity.id)? {
-            self.dbc.github_repository().set_ratio(entity.id, ratio);
+            self.dbc.github_repository().set_ratio(entity.id, ratio)?;
 
             // Check if the repository is a fork and if so get a) their parent and b) all other forks
             // Normally we're not too keen in forks, but if someone forked a repository with Solidity code
@@ -269,8 +384,8 @@ impl GithubCrawler {
 
                 // To save some API calls we'll simply assume the ratio to be the same as the parents'
                 for fork in self.ghc.repos(parent.id).forks()? {
-                    self.dbc.github_user().insert_if_not_exists(&fork.owner);
-                    self.dbc.github_repository().insert(&fork, ratio, true);
+                    self.dbc.github_user().insert_if_not_exists(&fork.owner)?;
+                    self.dbc.github_repository().insert(&fork, ratio, true)?;
                 }
             }
         }
@@ -278,14 +393,36 @@ impl GithubCrawler {
         Ok(())
     }
 
+    /// Backfills Solidity repositories created (or pushed to if `query_by_created` is false) between the
+    /// given dates, both inclusive, without touching the crawler metadata. Useful to repair gaps caused by
+    /// downtime.
+    pub fn backfill(&self, from: Date<Utc>, to: Date<Utc>, query_by_created: bool) -> Result<(), Error> {
+        let by = if query_by_created { "created" } else { "pushed" };
+        info!("Backfilling repositories {by} between {from} and {to}");
+        let repos = self.search_solidity_repositories_between(from, to, query_by_created)?;
+
+        match query_by_created {
+            true => self.insert_solidity_repositories(repos),
+            false => self.upsert_solidity_repositories(repos),
+        }
+    }
+
     fn search_solidity_repositories_starting_from(
+        &self,
+        from: Date<Utc>,
+        query_by_created: bool,
+    ) -> Result<Vec<GithubRepository>, Error> {
+        self.search_solidity_repositories_between(from, Utc::now().date(), query_by_created)
+    }
+
+    fn search_solidity_repositories_between(
         &self,
         mut from: Date<Utc>,
+        to: Date<Utc>,
         query_by_created: bool,
     ) -> Result<Vec<GithubRepository>, Error> {
         let mut repositories = Vec::new();
 
-        let to = Utc::now().date();
         while from <= to {
             match query_by_created {
                 true => repositories.append(&mut self.ghc.search().solidity_repos_created_at(from)?),
@@ -299,7 +436,14 @@ impl GithubCrawler {
     }
 
     fn insert_recently_created_solidity_repositories(&self, date: Date<Utc>) -> Result<(), Error> {
-        let repos = self.search_solidity_repositories_starting_from(date, true)?;
+        self.insert_solidity_repositories(self.search_solidity_repositories_starting_from(date, true)?)
+    }
+
+    fn upsert_recently_updated_solidity_repositories(&self, date: Date<Utc>) -> Result<(), Error> {
+        self.upsert_solidity_repositories(self.search_solidity_repositories_starting_from(date, false)?)
+    }
+
+    fn insert_solidity_repositories(&self, repos: Vec<GithubRepository>) -> Result<(), Error> {
         debug!("Inserting {} repositories", repos.len());
 
         for repo in repos {
@@ -309,12 +453,11 @@ impl GithubCrawler {
         Ok(())
     }
 
-    fn upsert_recently_updated_solidity_repositories(&self, date: Date<Utc>) -> Result<(), Error> {
-        let repos = self.search_solidity_repositories_starting_from(date, false)?;
+    fn upsert_solidity_repositories(&self, repos: Vec<GithubRepository>) -> Result<(), Error> {
         debug!("Upserting {} repos", repos.len());
 
-        for repo in self.search_solidity_repositories_starting_from(date, false)? {
-            if self.dbc.github_repository().get_by_id(repo.id).is_none() {
+        for repo in repos {
+            if self.dbc.github_repository().get_by_id(repo.id)?.is_none() {
                 self.insert_repository_if_not_exists(&repo, false)?;
                 continue; // Nothing to do, we inserted the latest version into the database
             }
@@ -322,8 +465,8 @@ impl GithubCrawler {
             // Repository already present in database, update it and re-trigger the scraping process
             if let Some(ratio) = self.get_solidity_ratio_or_set_repository_deleted(repo.id)? {
                 trace!("Updating {}", repo.html_url);
-                self.dbc.github_repository().update(&repo, ratio);
-                self.dbc.github_repository().set_scraped_to_null(repo.id);
+                self.dbc.github_repository().update(&repo, ratio)?;
+                self.dbc.github_repository().set_scraped_to_null(repo.id)?;
             }
         }
 
@@ -332,31 +475,27 @@ impl GithubCrawler {
 
     fn find_repository_updates(&self, days: i64) -> Result<(), Error> {
         let sol_repos_active_in_last_n_days =
-            self.dbc.github_repository().get_solidity_repos_active_in_last_n_days(days);
+            self.dbc.github_repository().get_solidity_repos_active_in_last_n_days(days)?;
         info!("Checking {} repositories for updates", sol_repos_active_in_last_n_days.len());
 
-        for repo_db in sol_repos_active_in_last_n_days {
-            match self.ghc.repos(repo_db.id).modified_since(repo_db.updated_at) {
-                Ok(repo_gh) => {
-                    if let Some(repo_gh) = repo_gh {
-                        if repo_gh.pushed_at != repo_db.pushed_at {
-                            if let Some(ratio) =
-                                self.get_solidity_ratio_or_set_repository_deleted(repo_gh.id)?
-                            {
-                                self.dbc.github_repository().update(&repo_gh, ratio);
-                                self.dbc.github_repository().set_scraped_to_null(repo_gh.id);
-                            }
+        // Metadata (including languages) of up to 100 repositories is retrieved with a single GraphQL query
+        for repos_db in sol_repos_active_in_last_n_days.chunks(GRAPHQL_BATCH_SIZE) {
+            let ids = repos_db.iter().map(|repo| repo.id).collect::<Vec<i32>>();
+            let metadata = self.ghc.graphql().repositories(&ids)?;
+
+            for (repo_db, metadata) in repos_db.iter().zip(metadata) {
+                match metadata {
+                    Some(metadata) => {
+                        if metadata.repository.pushed_at != repo_db.pushed_at {
+                            let ratio = metadata.solidity_ratio;
+                            self.dbc.github_repository().update(&metadata.repository, ratio)?;
+                            self.dbc.github_repository().set_scraped_to_null(repo_db.id)?;
                         }
                     }
-                }
-
-                Err(why) => match why {
-                    Error::GithubResourceUnavailable(_) => {
-                        self.dbc.github_repository().set_deleted(repo_db.id);
-                    }
 
-                    _ => return Err(why),
-                },
+                    // Repository can't be resolved, i.e. it was either deleted or made private
+                    None => self.dbc.github_repository().set_deleted(repo_db.id)?,
+                }
             }
         }
 
@@ -365,29 +504,26 @@ impl GithubCrawler {
 
     fn find_user_updates(&self, days: i64) -> Result<(), Error> {
         let sol_repository_owners_active_in_last_n_days =
-            self.dbc.github_user().get_solidity_repository_owners_active_in_last_n_days(days);
+            self.dbc.github_user().get_solidity_repository_owners_active_in_last_n_days(days)?;
         info!(
             "Checking {} Solidity repository owners for updates",
             sol_repository_owners_active_in_last_n_days.len()
         );
 
+        // Rather than listing the repositories of every user, their public events are checked for pushes or
+        // created repositories since the last check, only listing the repositories of users with activity
+        let last_user_check = self.dbc.github_crawler_metadata().get()?.last_user_check;
         for user_db in sol_repository_owners_active_in_last_n_days {
-            match self.ghc.user(user_db.id).get() {
-                Ok(user_gh) => {
-                    if user_gh.public_repos.unwrap() as i64 != self.dbc.github_user().repo_count(user_gh.id) {
-                        for repo in self.ghc.user(user_gh.id).repos()? {
-                            self.insert_repository_if_not_exists(&repo, true)?;
-                        }
+            match self.ghc.user(user_db.id).has_repository_activity_since(last_user_check) {
+                Ok(true) => {
+                    for repo in self.ghc.user(user_db.id).repos()? {
+                        self.insert_repository_if_not_exists(&repo, true)?;
                     }
                 }
 
-                Err(why) => match why {
-                    Error::GithubResourceUnavailable(_) => {
-                        self.dbc.github_user().set_deleted(user_db.id);
-                    }
-
-                    _ => return Err(why),
-                },
+                Ok(false) => trace!("No activity of {} since {last_user_check}", user_db.html_url),
+                Err(Error::GithubResourceUnavailable(_)) => self.dbc.github_user().set_deleted(user_db.id)?,
+                Err(why) => return Err(why),
             }
         }
 
@@ -401,7 +537,7 @@ impl GithubCrawler {
 
             Err(why) => match why {
                 Error::GithubResourceUnavailable(_) => {
-                    self.dbc.github_repository().set_deleted(repo_id);
+                    self.dbc.github_repository().set_deleted(repo_id)?;
 
                     Ok(None)
                 }
@@ -410,23 +546,6 @@ impl GithubCrawler {
             },
         }
     }
-
-    #[inline]
-    fn get_stargazers_or_set_repository_deleted(&self, repo_id: i32) -> Result<Vec<GithubUser>, Error> {
-        match self.ghc.repos(repo_id).stargazers() {
-            Ok(stargazers) => Ok(stargazers),
-
-            Err(why) => match why {
-                Error::GithubResourceUnavailable(_) => {
-                    self.dbc.github_repository().set_deleted(repo_id);
-
-                    Ok(Vec::with_capacity(0))
-                }
-
-                _ => Err(why),
-            },
-        }
-    }
 }
 
 fn start_background_event(
@@ -434,12 +553,7 @@ fn start_background_event(
     event: Event,
     freq: chrono::Duration,
 ) -> Result<(), Error> {
-    let dbc = DatabaseClient::new()?;
-    let last_event_date = match event {
-        Event::SearchRepositories => dbc.github_crawler_metadata().get().last_repository_search,
-        Event::CheckRepositories => dbc.github_crawler_metadata().get().last_repository_check,
-        Event::CheckUsers => dbc.github_crawler_metadata().get().last_user_check,
-    };
+    let last_event_date = last_event_date(&DatabaseClient::new()?, event)?;
 
     std::thread::spawn(move || {
         let delta = Utc::now() - last_event_date;
@@ -466,3 +580,12 @@ fn start_background_event(
     });
     Ok(())
 }
+
+/// Returns the date the given event was last executed successfully.
+fn last_event_date(dbc: &DatabaseClient, event: Event) -> Result<DateTime<Utc>, Error> {
+    Ok(match event {
+        Event::SearchRepositories => dbc.github_crawler_metadata().get()?.last_repository_search,
+        Event::CheckRepositories => dbc.github_crawler_metadata().get()?.last_repository_check,
+        Event::CheckUsers => dbc.github_crawler_metadata().get()?.last_user_check,
+    })
+}
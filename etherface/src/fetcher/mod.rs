@@ -2,16 +2,13 @@
 
 pub mod etherscan;
 pub mod fourbyte;
+pub mod frontier;
 pub mod github;
 
 use anyhow::Error;
 
-/// Sleep duration between fetching iterations; used only for fetchers where polling is present, i.e.
-/// [`etherscan`] and [`fourbyte`].
-const FETCHER_POLLING_SLEEP_TIME: u64 = 5 * 60;
-
 /// Trait providing the entry point for starting a fetcher.
 pub trait Fetcher: std::fmt::Debug {
-    /// Starts the fetching process.
-    fn start(&self) -> Result<(), Error>;
+    /// Starts the fetching process, returning after one iteration if `one_shot` is set.
+    fn start(&self, one_shot: bool) -> Result<(), Error>;
 }
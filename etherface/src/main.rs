@@ -19,94 +19,450 @@
 //! files where such signatures are present by either crawling or polling websites whereas the `scraper` module
 //! is responsible for downloading these files, scraping all function, event and error signatures inserting
 //! them into the database. These scraped signatures are then publicly available at <https://etherface.io/>.
+//! The remaining components (`refresher`, `exporter`, `notifier` and `dispatcher`) maintain the REST APIs
+//! statistics, snapshots and webhooks.
+//!
+//! By default all components are started within one process, whereas `etherface run --only <..>` starts only
+//! the given ones. All other subcommands are one-off jobs documented within the `maintenance` module, see
+//! `etherface --help`.
 
+mod dispatcher;
+mod exporter;
 mod fetcher;
+mod maintenance;
+mod metrics;
+mod notifier;
+mod refresher;
 mod scraper;
+mod supervisor;
 
 extern crate log;
-extern crate simplelog;
 
 use crate::fetcher::etherscan::EtherscanFetcher;
 use crate::fetcher::fourbyte::FourbyteFetcher;
 use crate::fetcher::Fetcher;
+use crate::maintenance::lookup::LookupArgs;
 use crate::scraper::etherscan::EtherscanScraper;
 use crate::scraper::github::GithubScraper;
 use crate::scraper::Scraper;
 use anyhow::Error;
+use chrono::NaiveDate;
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use etherface_lib::config::Config;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::logging;
+use etherface_lib::report;
 use fetcher::github::GithubFetcher;
-use log::debug;
-use simplelog::CombinedLogger;
-use simplelog::*;
+use log::error;
+use log::info;
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::sync::mpsc::Sender;
 
-fn main() -> Result<(), Error> {
-    CombinedLogger::init(vec![
-        TermLogger::new(
-            // LevelFilter::max(),
-            LevelFilter::Debug,
-            ConfigBuilder::new()
-                .add_filter_allow_str("etherface")
-                .set_time_format_str("[%d.%m.%Y; %T]")
-                .build(),
-            TerminalMode::Mixed,
-            ColorChoice::Auto,
-        ),
-        WriteLogger::new(
-            LevelFilter::Debug,
-            ConfigBuilder::new()
-                .add_filter_allow_str("etherface")
-                .set_time_format_str("[%d.%m.%Y; %T]")
-                .build(),
-            std::fs::OpenOptions::new().append(true).create(true).open("etherface.log")?,
-        ),
-    ])
-    .unwrap();
+/// Log filter used if none is configured, see `.env-EXAMPLE`.
+const DEFAULT_LOG_FILTER: &str = "etherface=debug,etherface_lib=debug";
 
-    let (tx, rx) = mpsc::channel();
-    start_data_retrieval_threads(&tx);
-    start_data_scraper_threads(&tx);
+#[derive(Debug, Parser)]
+#[clap(version, about)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
 
-    // This block until we receive a message, which in turn we only receive if there was an error
-    match rx.recv() {
-        Ok(msg) => anyhow::bail!(msg),
-        Err(why) => anyhow::bail!(why),
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Starts fetchers and scrapers (the default if no subcommand is given)
+    Run {
+        /// Components to start, all if none are given
+        #[clap(long, value_enum, multiple_values = true)]
+        only: Vec<Component>,
+
+        /// Runs exactly one iteration of each component and exits, e.g. for cron-style operation
+        #[clap(long)]
+        once: bool,
+    },
+
+    /// Re-parses all sources scraped with an older parser version and exits
+    Reparse,
+
+    /// Backfills data of a source for the given date range and exits
+    Backfill {
+        #[clap(subcommand)]
+        source: BackfillSource,
+    },
+
+    /// Imports a snapshot created by the snapshot exporter into a fresh database and exits
+    Import {
+        /// Directory of the snapshot, i.e. `{snapshot_directory}/{YYYY-MM-DD}`
+        #[clap(long)]
+        from: PathBuf,
+    },
+
+    /// Imports on-chain usage counts of signatures from a `hash,count,last_called_at` CSV file and exits
+    Usage {
+        /// Path of the CSV file, optionally gzip compressed (`.gz` extension)
+        #[clap(long)]
+        from: PathBuf,
+    },
+
+    /// Looks up signatures by their selector / topic or the start of their text, prints them and exits
+    Lookup(LookupArgs),
+
+    /// Populates a SQLite database for `lookup --sqlite` from a snapshot created by the snapshot exporter and
+    /// exits
+    #[cfg(feature = "sqlite")]
+    Sqlite {
+        /// Directory of the snapshot, i.e. `{snapshot_directory}/{YYYY-MM-DD}`
+        #[clap(long)]
+        from: PathBuf,
+
+        /// Path of the SQLite database, created if it doesn't exist yet
+        #[clap(long)]
+        to: PathBuf,
+    },
+
+    /// Re-validates all signatures and re-computes their hashes with the current parser and exits
+    Revalidate {
+        /// Only logs the changes instead of applying them
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Checks and repairs the kinds of all signatures against their source mappings and exits
+    Consistency {
+        /// Only reports the inconsistencies instead of repairing them
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Issues, revokes or lists the API keys of REST API consumers and exits
+    ApiKey {
+        #[clap(subcommand)]
+        action: ApiKeyAction,
+    },
+
+    /// Lists, approves or rejects signatures submitted by REST API consumers and exits
+    Submission {
+        #[clap(subcommand)]
+        action: SubmissionAction,
+    },
+
+    /// Lists, resolves or dismisses signatures flagged as wrong or spam by REST API users and exits
+    Flag {
+        #[clap(subcommand)]
+        action: FlagAction,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ApiKeyAction {
+    /// Issues a new key and prints it, e.g. `api-key issue --name "Some Explorer" --per-minute 600`
+    Issue {
+        /// Name of the consumer the key is issued to
+        #[clap(long)]
+        name: String,
+
+        /// Number of requests per minute the consumer may send
+        #[clap(long, default_value_t = 600)]
+        per_minute: u32,
+
+        /// Number of requests the consumer may send at once
+        #[clap(long, default_value_t = 100)]
+        burst: u32,
+    },
+
+    /// Revokes the key with the given id
+    Revoke {
+        #[clap(long)]
+        id: i32,
+    },
+
+    /// Lists all keys including revoked ones alongside their usage
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+enum SubmissionAction {
+    /// Lists the oldest submissions pending moderation
+    List {
+        /// Maximum number of submissions to list
+        #[clap(long, default_value_t = 100)]
+        limit: i64,
+    },
+
+    /// Approves the submission with the given id, inserting its signature
+    Approve {
+        #[clap(long)]
+        id: i32,
+    },
+
+    /// Rejects the submission with the given id
+    Reject {
+        #[clap(long)]
+        id: i32,
+
+        /// Reason recorded within the audit log
+        #[clap(long)]
+        reason: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum FlagAction {
+    /// Lists the most often flagged pending flags
+    List {
+        /// Maximum number of flags to list
+        #[clap(long, default_value_t = 100)]
+        limit: i64,
+    },
+
+    /// Marks the signature of the flag with the given id as invalid, keeping its mappings
+    Invalidate {
+        #[clap(long)]
+        id: i32,
+    },
+
+    /// Removes the mappings flagged by the flag with the given id and marks the signature as invalid
+    Remove {
+        #[clap(long)]
+        id: i32,
+    },
+
+    /// Dismisses the flag with the given id
+    Dismiss {
+        #[clap(long)]
+        id: i32,
+
+        /// Reason recorded within the audit log
+        #[clap(long)]
+        reason: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum BackfillSource {
+    /// Searches GitHub for Solidity repositories, e.g. `backfill github --from 2019-01-01 --to 2019-06-30`
+    Github {
+        /// First day to search for, formatted as YYYY-MM-DD
+        #[clap(long)]
+        from: NaiveDate,
+
+        /// Last day to search for (inclusive), formatted as YYYY-MM-DD
+        #[clap(long)]
+        to: NaiveDate,
+
+        /// Whether to search by creation or last push date
+        #[clap(long, value_enum, default_value = "created")]
+        by: BackfillBy,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BackfillBy {
+    Created,
+    Pushed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Component {
+    GithubFetcher,
+    EtherscanFetcher,
+    FourbyteFetcher,
+    GithubScraper,
+    EtherscanScraper,
+    MaterializedViewRefresher,
+    SnapshotExporter,
+    WatchlistNotifier,
+    WebhookDispatcher,
+}
+
+impl Component {
+    fn is_enabled(&self, config: &Config) -> bool {
+        match self {
+            Component::GithubFetcher | Component::GithubScraper => config.source_github_enabled,
+            Component::EtherscanFetcher | Component::EtherscanScraper => config.source_etherscan_enabled,
+            Component::FourbyteFetcher => config.source_fourbyte_enabled,
+            Component::MaterializedViewRefresher => true,
+            Component::SnapshotExporter => config.snapshot_directory.is_some(),
+            Component::WatchlistNotifier => true,
+            Component::WebhookDispatcher => true,
+        }
+    }
+
+    fn worker(&self) -> Worker {
+        match self {
+            Component::GithubFetcher => Worker::Fetcher(Box::new(GithubFetcher)),
+            Component::EtherscanFetcher => Worker::Fetcher(Box::new(EtherscanFetcher)),
+            Component::FourbyteFetcher => Worker::Fetcher(Box::new(FourbyteFetcher)),
+            Component::GithubScraper => Worker::Scraper(Box::new(GithubScraper)),
+            Component::EtherscanScraper => Worker::Scraper(Box::new(EtherscanScraper)),
+            Component::MaterializedViewRefresher => Worker::MaterializedViewRefresher,
+            Component::SnapshotExporter => Worker::SnapshotExporter,
+            Component::WatchlistNotifier => Worker::WatchlistNotifier,
+            Component::WebhookDispatcher => Worker::WebhookDispatcher,
+        }
+    }
+}
+
+enum Worker {
+    Fetcher(Box<dyn Fetcher + Sync + Send>),
+    Scraper(Box<dyn Scraper + Sync + Send>),
+    MaterializedViewRefresher,
+    SnapshotExporter,
+    WatchlistNotifier,
+    WebhookDispatcher,
+}
+
+impl Worker {
+    fn name(&self) -> String {
+        match self {
+            Worker::Fetcher(fetcher) => format!("fetcher {:?}", fetcher),
+            Worker::Scraper(scraper) => format!("scraper {:?}", scraper),
+            Worker::MaterializedViewRefresher => "materialized view refresher".to_string(),
+            Worker::SnapshotExporter => "snapshot exporter".to_string(),
+            Worker::WatchlistNotifier => "watchlist notifier".to_string(),
+            Worker::WebhookDispatcher => "webhook dispatcher".to_string(),
+        }
+    }
+
+    fn start(&self, one_shot: bool) -> Result<(), Error> {
+        match self {
+            Worker::Fetcher(fetcher) => fetcher.start(one_shot),
+            Worker::Scraper(scraper) => scraper.start(one_shot),
+            Worker::MaterializedViewRefresher => refresher::start(one_shot),
+            Worker::SnapshotExporter => exporter::start(one_shot),
+            Worker::WatchlistNotifier => notifier::start(one_shot),
+            Worker::WebhookDispatcher => dispatcher::start(one_shot),
+        }
     }
 }
 
-fn start_data_scraper_threads(tx: &Sender<Error>) {
-    let scrapers: Vec<Box<dyn Scraper + Sync + Send>> =
-        vec![Box::new(GithubScraper), Box::new(EtherscanScraper)];
+fn main() -> Result<(), Error> {
+    let cli = Cli::parse();
 
-    for scraper in scrapers {
-        let tx_abort_channel = tx.clone();
+    logging::init(DEFAULT_LOG_FILTER, Some("etherface.log"))?;
+    let _report_guard = report::init()?;
 
-        std::thread::spawn(move || {
-            debug!("Starting scraper {:#?}", scraper);
+    if Config::new()?.run_migrations {
+        info!("Running pending database migrations");
+        DatabaseClient::new()?.run_pending_migrations()?;
+    }
 
-            if let Err(why) = scraper.start() {
-                tx_abort_channel.send(why).unwrap();
+    let (components, once) = match cli.command {
+        Some(Command::Reparse) => return maintenance::reparse::start(),
+        Some(Command::Backfill { source: BackfillSource::Github { from, to, by } }) => {
+            return maintenance::backfill::github(from, to, by == BackfillBy::Created)
+        }
+        Some(Command::Import { from }) => return maintenance::import::start(&from),
+        Some(Command::Usage { from }) => return maintenance::usage::start(&from),
+        Some(Command::Lookup(args)) => return maintenance::lookup::start(&args),
+        #[cfg(feature = "sqlite")]
+        Some(Command::Sqlite { from, to }) => return maintenance::sqlite::start(&from, &to),
+        Some(Command::Revalidate { dry_run }) => return maintenance::revalidate::start(dry_run),
+        Some(Command::Consistency { dry_run }) => return maintenance::consistency::start(dry_run),
+        Some(Command::ApiKey { action }) => {
+            return match action {
+                ApiKeyAction::Issue { name, per_minute, burst } => {
+                    maintenance::api_key::issue(&name, per_minute, burst)
+                }
+                ApiKeyAction::Revoke { id } => maintenance::api_key::revoke(id),
+                ApiKeyAction::List => maintenance::api_key::list(),
+            }
+        }
+        Some(Command::Submission { action }) => {
+            return match action {
+                SubmissionAction::List { limit } => maintenance::submission::list(limit),
+                SubmissionAction::Approve { id } => maintenance::submission::approve(id),
+                SubmissionAction::Reject { id, reason } => {
+                    maintenance::submission::reject(id, reason.as_deref())
+                }
+            }
+        }
+        Some(Command::Flag { action }) => {
+            return match action {
+                FlagAction::List { limit } => maintenance::flag::list(limit),
+                FlagAction::Invalidate { id } => maintenance::flag::invalidate(id),
+                FlagAction::Remove { id } => maintenance::flag::remove(id),
+                FlagAction::Dismiss { id, reason } => maintenance::flag::dismiss(id, reason.as_deref()),
             }
-        });
+        }
+        Some(Command::Run { only, once }) if !only.is_empty() => (only, once),
+        Some(Command::Run { once, .. }) => (Component::value_variants().to_vec(), once),
+        None => (Component::value_variants().to_vec(), false),
+    };
+
+    // Skip components whose data source is disabled or which aren't configured, see `.env-EXAMPLE`
+    let config = Config::new()?;
+    let (components, disabled): (Vec<_>, Vec<_>) =
+        components.into_iter().partition(|component| component.is_enabled(&config));
+    if !disabled.is_empty() {
+        info!("Skipping disabled components: {:?}", disabled);
+    }
+
+    if components.is_empty() {
+        anyhow::bail!("No components to start, all of their data sources are disabled");
+    }
+
+    if once {
+        return run_worker_threads_once(&components);
+    }
+
+    let (tx, rx) = mpsc::channel();
+    start_worker_threads(&components, &tx);
+
+    if let Some(address) = config.metrics_address {
+        supervisor::supervise("metrics exporter".to_string(), tx.clone(), move || metrics::start(&address));
+    }
+
+    // This block until we receive a message, which in turn we only receive if a worker failed too often
+    match rx.recv() {
+        Ok(msg) => anyhow::bail!(msg),
+        Err(why) => anyhow::bail!(why),
     }
 }
 
-fn start_data_retrieval_threads(tx: &Sender<Error>) {
-    let fetchers: Vec<Box<dyn Fetcher + Sync + Send>> = vec![
-        Box::new(FourbyteFetcher),
-        Box::new(EtherscanFetcher),
-        Box::new(GithubFetcher),
-    ];
+fn start_worker_threads(components: &[Component], tx: &Sender<Error>) {
+    for component in components {
+        let worker = component.worker();
+        supervisor::supervise(worker.name(), tx.clone(), move || worker.start(false));
+    }
+}
 
-    for fetcher in fetchers {
-        let tx_abort_channel = tx.clone();
+/// Runs one iteration of all given components concurrently, returning an error if any of them failed.
+fn run_worker_threads_once(components: &[Component]) -> Result<(), Error> {
+    let failures = std::thread::scope(|scope| {
+        let handles: Vec<_> = components
+            .iter()
+            .map(|component| {
+                let handle = scope.spawn(move || component.worker().start(true));
 
-        std::thread::spawn(move || {
-            debug!("Starting fetcher {:#?}", fetcher);
+                (component, handle)
+            })
+            .collect();
 
-            if let Err(why) = fetcher.start() {
-                tx_abort_channel.send(why).unwrap();
+        let mut failures = 0;
+        for (component, handle) in handles {
+            match handle.join() {
+                Ok(Ok(())) => info!("{component:?} finished"),
+                Ok(Err(why)) => {
+                    error!("{component:?} failed; {why}");
+                    failures += 1;
+                }
+                Err(_) => {
+                    error!("{component:?} panicked");
+                    failures += 1;
+                }
             }
-        });
+        }
+
+        failures
+    });
+
+    if failures > 0 {
+        anyhow::bail!("{failures} of {} components failed", components.len());
     }
+
+    Ok(())
 }
@@ -0,0 +1,78 @@
+//! Issues, revokes and lists the API keys of registered REST API consumers, granting them their own quota
+//! instead of the per-IP one (see `etherface-rest/src/ratelimit.rs`). Issuing and revoking keys is recorded
+//! within the audit log, actored by the local user running the command.
+
+use crate::maintenance::actor;
+use anyhow::Error;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::AuditAction;
+use log::info;
+
+/// Issues a new key to the consumer `name`, printing the key which can't be recovered afterwards.
+pub fn issue(name: &str, per_minute: u32, burst: u32) -> Result<(), Error> {
+    if per_minute == 0 || burst == 0 {
+        anyhow::bail!("Both the number of requests per minute and the burst must be greater than 0");
+    }
+
+    let dbc = DatabaseClient::new()?;
+
+    let (entity, key) = dbc.transaction(|| -> Result<_, Error> {
+        let (entity, key) = dbc.api_key().issue(name, per_minute.try_into()?, burst.try_into()?)?;
+
+        let target = entity.id.to_string();
+        let details = format!("{name} ({per_minute}/min, burst {burst})");
+        dbc.audit_log().insert(&actor(), AuditAction::ApiKeyIssue, Some(&target), Some(&details))?;
+
+        Ok((entity, key))
+    })?;
+
+    info!("Issued API key {} to {}", entity.id, entity.name);
+    println!("{key}");
+
+    Ok(())
+}
+
+/// Revokes the key with the given id, taking effect within a minute in all running REST API instances.
+pub fn revoke(id: i32) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    let revoked = dbc.transaction(|| -> Result<_, Error> {
+        let revoked = dbc.api_key().revoke(id)?;
+        if revoked {
+            dbc.audit_log().insert(&actor(), AuditAction::ApiKeyRevoke, Some(&id.to_string()), None)?;
+        }
+
+        Ok(revoked)
+    })?;
+
+    match revoked {
+        true => info!("Revoked API key {id}"),
+        false => anyhow::bail!("No active API key with id {id}"),
+    }
+
+    Ok(())
+}
+
+/// Prints all keys including revoked ones alongside their usage.
+pub fn list() -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    for entity in dbc.api_key().get_all()? {
+        let status = match entity.revoked_at {
+            Some(revoked_at) => format!("revoked {revoked_at}"),
+            None => "active".to_string(),
+        };
+
+        println!(
+            "{}\t{}\t{}/min, burst {}\t{} requests, last used {}\t{status}",
+            entity.id,
+            entity.name,
+            entity.rate_limit_per_minute,
+            entity.rate_limit_burst,
+            entity.request_count,
+            entity.last_used_at.map(|x| x.to_string()).unwrap_or_else(|| "never".to_string()),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,17 @@
+//! Backfills data of a given source for an arbitrary date range, e.g. to repair gaps caused by downtime.
+
+use crate::fetcher::github::GithubCrawler;
+use anyhow::Error;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use chrono::Utc;
+
+/// Backfills all Solidity repositories created (or pushed to if `by_created` is false) between `from` and
+/// `to`, both inclusive.
+pub fn github(from: NaiveDate, to: NaiveDate, by_created: bool) -> Result<(), Error> {
+    if from > to {
+        anyhow::bail!("Start date {from} is after end date {to}");
+    }
+
+    Ok(GithubCrawler::new()?.backfill(Utc.from_utc_date(&from), Utc.from_utc_date(&to), by_created)?)
+}
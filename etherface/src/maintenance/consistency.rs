@@ -0,0 +1,40 @@
+//! Checks that the kinds recorded within `mapping_signature_kind` agree with the source mapping tables, which
+//! can drift apart e.g. after a scraper fix changed the kind of previously scraped signatures or a merge of
+//! [`super::revalidate`] combined the kinds of two signatures.
+//!
+//! Kinds found in a source mapping table but missing in `mapping_signature_kind` are inserted, whereas kinds
+//! not backed by any source mapping of the signature are deleted. All repairs run within one transaction.
+
+use anyhow::Error;
+use etherface_lib::database::handler::DatabaseClient;
+use log::info;
+
+/// Checks and repairs all kinds, only reporting the inconsistencies instead of repairing them if `dry_run`
+/// is set.
+pub fn start(dry_run: bool) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    let inconsistencies = dbc.signature().get_kind_inconsistencies()?;
+    if inconsistencies.is_empty() {
+        info!("No kind inconsistencies found");
+        return Ok(());
+    }
+
+    for entity in &inconsistencies {
+        info!("{:?}: {} missing, {} orphaned", entity.kind, entity.missing, entity.orphaned);
+    }
+
+    let (num_missing, num_orphaned) = inconsistencies
+        .iter()
+        .fold((0, 0), |(missing, orphaned), x| (missing + x.missing, orphaned + x.orphaned));
+
+    if dry_run {
+        info!("[dry run] Found {num_missing} missing and {num_orphaned} orphaned kinds");
+        return Ok(());
+    }
+
+    let (num_inserted, num_deleted) = dbc.transaction(|| dbc.signature().repair_kind_inconsistencies())?;
+    info!("Inserted {num_inserted} missing and deleted {num_deleted} orphaned kinds");
+
+    Ok(())
+}
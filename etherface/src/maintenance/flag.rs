@@ -0,0 +1,117 @@
+//! Lists and moderates flags raised by REST API users against signatures or their source mappings (see
+//! `POST /v1/signatures/{id}/flags`). Flags are resolved by either invalidating the signature, keeping its
+//! mappings, or by removing the flagged mappings and invalidating the signature, whereas unfounded flags are
+//! dismissed. Signatures invalidated this way stay invalid when re-validated (see `maintenance::revalidate`).
+//! All actions are recorded within the audit log, actored by the local user running the command.
+
+use crate::maintenance::actor;
+use anyhow::Error;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::AuditAction;
+use etherface_lib::model::FlagStatus;
+use etherface_lib::model::SignatureFlag;
+use log::info;
+
+/// Prints the `limit` most often flagged pending flags.
+pub fn list(limit: i64) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    for entity in dbc.signature_flag().get_pending(limit)? {
+        println!(
+            "{}\tsignature {} ({:?}) on {}\t{:?}, flagged {} times since {}\t{}",
+            entity.id,
+            entity.signature_id,
+            entity.kind,
+            mappings_of(&entity),
+            entity.reason,
+            entity.flag_count,
+            entity.flagged_at,
+            entity.comment.as_deref().unwrap_or_default(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Resolves the pending flag with the given id by marking its signature as invalid.
+pub fn invalidate(id: i32) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    dbc.transaction(|| -> Result<_, Error> {
+        let flag = moderate(&dbc, id, FlagStatus::Invalidated)?;
+        dbc.signature().set_valid(flag.signature_id, false)?;
+
+        let details = format!("invalidated signature {}", flag.signature_id);
+        let target = format!("flag:{id}");
+        dbc.audit_log().insert(&actor(), AuditAction::Moderation, Some(&target), Some(&details))?;
+
+        Ok(())
+    })?;
+
+    info!("Invalidated the signature of flag {id}");
+    Ok(())
+}
+
+/// Resolves the pending flag with the given id by removing the flagged mappings and marking its signature
+/// as invalid.
+pub fn remove(id: i32) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    let removed = dbc.transaction(|| -> Result<_, Error> {
+        let flag = moderate(&dbc, id, FlagStatus::Removed)?;
+        let removed =
+            dbc.signature().remove_mappings(flag.signature_id, flag.kind, flag.source, flag.source_id)?;
+        dbc.signature().set_valid(flag.signature_id, false)?;
+
+        let details = format!(
+            "removed {removed} mappings of signature {} ({:?}) on {}",
+            flag.signature_id,
+            flag.kind,
+            mappings_of(&flag)
+        );
+        let target = format!("flag:{id}");
+        dbc.audit_log().insert(&actor(), AuditAction::Moderation, Some(&target), Some(&details))?;
+
+        Ok(removed)
+    })?;
+
+    info!("Removed {removed} mappings flagged by flag {id}");
+    Ok(())
+}
+
+/// Dismisses the pending flag with the given id, optionally recording the reason in the audit log.
+pub fn dismiss(id: i32, reason: Option<&str>) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    dbc.transaction(|| -> Result<_, Error> {
+        let flag = moderate(&dbc, id, FlagStatus::Dismissed)?;
+
+        let details = match reason {
+            Some(reason) => format!("dismissed flag of signature {}: {reason}", flag.signature_id),
+            None => format!("dismissed flag of signature {}", flag.signature_id),
+        };
+        let target = format!("flag:{id}");
+        dbc.audit_log().insert(&actor(), AuditAction::Moderation, Some(&target), Some(&details))?;
+
+        Ok(())
+    })?;
+
+    info!("Dismissed flag {id}");
+    Ok(())
+}
+
+fn moderate(dbc: &DatabaseClient, id: i32, status: FlagStatus) -> Result<SignatureFlag, Error> {
+    match dbc.signature_flag().moderate(id, status, &actor())? {
+        Some(flag) => Ok(flag),
+        None => anyhow::bail!("No pending flag with id {id}"),
+    }
+}
+
+/// Returns a description of the mappings `flag` targets, e.g. `Github 42`.
+fn mappings_of(flag: &SignatureFlag) -> String {
+    match (flag.source, flag.source_id) {
+        (Some(source), Some(source_id)) => format!("{source:?} {source_id}"),
+        (Some(source), None) => format!("{source:?}"),
+        (None, _) => "all sources".to_string(),
+    }
+}
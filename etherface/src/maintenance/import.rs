@@ -0,0 +1,99 @@
+//! Imports a dataset snapshot (see `exporter`) into a fresh database, e.g. to bootstrap a new instance from
+//! a published snapshot rather than crawling all sources from scratch.
+//!
+//! Each file is imported within its own transaction, in the order of `EXPORT_SOURCES` such that referenced
+//! rows are always imported first. Columns which aren't part of the export (e.g. scraping dates) are set
+//! such that imported repositories and contracts aren't scraped again, whereas the crawler continues from
+//! the imported repositories.
+
+use crate::exporter::file_name;
+use anyhow::Error;
+use etherface_lib::database::handler::export::ExportSource;
+use etherface_lib::database::handler::export::EXPORT_SOURCES;
+use etherface_lib::database::handler::DatabaseClient;
+use flate2::read::GzDecoder;
+use log::info;
+use serde_json::Map;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Number of rows inserted with one statement.
+const BATCH_SIZE: usize = 1000;
+
+pub fn start(directory: &Path) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    if !dbc.export().is_empty()? {
+        anyhow::bail!("Snapshots can only be imported into a fresh database");
+    }
+
+    for source in EXPORT_SOURCES.iter().filter(|source| source.table.is_some()) {
+        let path = directory.join(file_name(source));
+        if !path.exists() {
+            anyhow::bail!("Snapshot file {} is missing", path.display());
+        }
+
+        let num_imported = dbc.transaction(|| import(&dbc, source, &path))?;
+        info!("Imported {num_imported} rows from {}", path.display());
+    }
+
+    dbc.export().finish_import()?;
+    info!("Imported snapshot {}", directory.display());
+
+    Ok(())
+}
+
+/// Inserts all rows of the gzip compressed CSV file at `path` in batches, returning the number of rows.
+fn import(dbc: &DatabaseClient, source: &ExportSource, path: &Path) -> Result<usize, Error> {
+    let mut reader = csv::Reader::from_reader(GzDecoder::new(BufReader::new(File::open(path)?)));
+    let header = reader.headers()?.clone();
+
+    let mut num_imported = 0;
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    for record in reader.records() {
+        batch.push(to_json(&header, &record?));
+
+        if batch.len() == BATCH_SIZE {
+            num_imported += dbc.export().import(source, &batch)?;
+            batch.clear();
+        }
+    }
+
+    if !batch.is_empty() {
+        num_imported += dbc.export().import(source, &batch)?;
+    }
+
+    Ok(num_imported)
+}
+
+/// Converts a CSV record into a JSON object keyed by the header, with empty values (i.e. `NULL`) as `null`.
+fn to_json(header: &csv::StringRecord, record: &csv::StringRecord) -> Value {
+    let object = header
+        .iter()
+        .zip(record.iter())
+        .map(|(column, value)| match value {
+            "" => (column.to_string(), Value::Null),
+            _ => (column.to_string(), Value::String(value.to_string())),
+        })
+        .collect::<Map<String, Value>>();
+
+    Value::Object(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_json_empty_values_are_null() {
+        let header = csv::StringRecord::from(vec!["id", "language", "added_at"]);
+        let record = csv::StringRecord::from(vec!["1", "", "2022-10-01 00:00:00+00"]);
+
+        assert_eq!(
+            to_json(&header, &record),
+            serde_json::json!({"id": "1", "language": null, "added_at": "2022-10-01 00:00:00+00"})
+        );
+    }
+}
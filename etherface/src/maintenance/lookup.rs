@@ -0,0 +1,120 @@
+//! Looks up signatures by their selector / topic or the start of their text and prints them alongside where
+//! they were found, e.g. `etherface lookup 0xa9059cbb` or `etherface lookup "transfer(" --kind function`.
+//!
+//! Lookups only need read access to signatures and their mappings, as such they run against any
+//! [`SignatureReader`]; the PostgreSQL database by default or, with the `sqlite` feature enabled, a local
+//! SQLite database given by `--sqlite <path>` (see `etherface_lib::database::sqlite`), populated from a
+//! snapshot via `etherface sqlite --from <snapshot> --to <path>`.
+
+use anyhow::Error;
+use clap::Args;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::database::reader::SignatureReader;
+use etherface_lib::model::decode_hash;
+use etherface_lib::model::SignatureKind;
+use std::str::FromStr;
+
+#[cfg(feature = "sqlite")]
+use etherface_lib::database::sqlite::SqliteClient;
+#[cfg(feature = "sqlite")]
+use std::path::PathBuf;
+
+/// Maximum number of repositories and contracts listed per signature.
+const MAX_SOURCES: i64 = 5;
+
+#[derive(Debug, Args)]
+pub struct LookupArgs {
+    /// Hex encoded selector (4 bytes) or topic (32 bytes), optionally `0x` prefixed, or the start of a
+    /// signature text (case sensitive)
+    query: String,
+
+    /// Only looks up signatures found as the given kind, e.g. `function`
+    #[clap(long, value_parser = parse_kind)]
+    kind: Option<SignatureKind>,
+
+    /// Maximum number of signatures to print
+    #[clap(long, default_value_t = 20)]
+    limit: i64,
+
+    /// Looks up signatures within the SQLite database at the given path instead of the PostgreSQL database
+    #[cfg(feature = "sqlite")]
+    #[clap(long)]
+    sqlite: Option<PathBuf>,
+}
+
+pub fn start(args: &LookupArgs) -> Result<(), Error> {
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &args.sqlite {
+        let path = path.to_str().ok_or_else(|| anyhow::anyhow!("Path {} isn't valid UTF-8", path.display()))?;
+        return lookup(&SqliteClient::new(path)?, args);
+    }
+
+    let dbc = DatabaseClient::new()?;
+    lookup(&dbc.signature_reader(), args)
+}
+
+/// Prints all signatures matching the query, one per line, followed by their kinds and sources.
+fn lookup(reader: &dyn SignatureReader, args: &LookupArgs) -> Result<(), Error> {
+    let query = args.query.trim();
+    let signatures = match as_hash(query) {
+        Some(hash) => reader.signatures_where_hash(hash, args.kind, args.limit)?,
+        None => reader.signatures_where_text_starts_with(query, args.kind, args.limit)?,
+    };
+
+    if signatures.is_empty() {
+        anyhow::bail!("No signatures found for {query}");
+    }
+
+    for signature in signatures {
+        let kinds = reader.kinds(signature.id)?;
+        let repositories = reader.github_repository_ids(signature.id, args.kind, MAX_SOURCES)?;
+        let contracts = reader.etherscan_contract_ids(signature.id, args.kind, MAX_SOURCES)?;
+        let is_on_fourbyte = reader.is_on_fourbyte(signature.id, args.kind)?;
+
+        println!(
+            "{}\t{}\t{}\t{:?}\tGitHub repositories {:?}, Etherscan contracts {:?}{}",
+            signature.id,
+            signature.text,
+            signature.hash,
+            kinds,
+            repositories,
+            contracts,
+            if is_on_fourbyte { ", 4Byte" } else { "" },
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the query without its `0x` prefix if it's a selector or topic rather than the start of a signature
+/// text.
+fn as_hash(query: &str) -> Option<&str> {
+    let hash = query.strip_prefix("0x").unwrap_or(query);
+    match decode_hash(hash).map(|x| x.len()) {
+        Some(4 | 32) => Some(hash),
+        _ => None,
+    }
+}
+
+fn parse_kind(value: &str) -> Result<SignatureKind, String> {
+    SignatureKind::from_str(value).map_err(|_| format!("Unknown signature kind `{value}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn as_hash_selectors_and_topics() {
+        assert_eq!(as_hash("0xa9059cbb"), Some("a9059cbb"));
+        assert_eq!(as_hash("a9059cbb"), Some("a9059cbb"));
+        assert_eq!(
+            as_hash("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"),
+            Some("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
+        );
+
+        assert_eq!(as_hash("0xa9059c"), None);
+        assert_eq!(as_hash("transfer("), None);
+        assert_eq!(as_hash("deadbeef00"), None);
+    }
+}
@@ -0,0 +1,39 @@
+//! One-off maintenance jobs, started via a command line argument instead of running alongside the fetchers
+//! and scrapers. Each job exits once done, see `etherface --help` for all of their arguments.
+//!
+//! - `etherface reparse` re-parses all sources scraped with an older parser version (see [`reparse`])
+//! - `etherface backfill github --from <..> --to <..>` backfills GitHub repositories of an arbitrary date
+//!   range (see [`backfill`])
+//! - `etherface import --from <..>` bootstraps a fresh database from a snapshot (see [`import`])
+//! - `etherface usage --from <..>` imports on-chain usage counts of signatures, ranking signatures sharing a
+//!   selector (see [`usage`])
+//! - `etherface lookup <query>` looks up signatures by their selector / topic or the start of their text
+//!   (see [`lookup`]), optionally within a SQLite database populated by `etherface sqlite --from <..> --to
+//!   <..>` (see `sqlite`, requires the `sqlite` feature)
+//! - `etherface revalidate` re-evaluates all stored signatures with the current parser, fixing their
+//!   validity and hashes (see [`revalidate`])
+//! - `etherface consistency` repairs signature kinds drifted apart from their source mappings (see
+//!   [`consistency`])
+//! - `etherface api-key issue|revoke|list` manages the API keys of REST API consumers (see [`api_key`])
+//! - `etherface submission list|approve|reject` moderates the signatures they submitted (see
+//!   [`submission`])
+//! - `etherface flag list|invalidate|remove|dismiss` moderates the signatures flagged as wrong or spam by
+//!   REST API users (see [`flag`])
+
+pub mod api_key;
+pub mod backfill;
+pub mod consistency;
+pub mod flag;
+pub mod import;
+pub mod lookup;
+pub mod reparse;
+pub mod revalidate;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod submission;
+pub mod usage;
+
+/// Returns the local user running the command, recorded as the actor within the audit log.
+fn actor() -> String {
+    std::env::var("USER").unwrap_or_else(|_| "cli".to_string())
+}
@@ -0,0 +1,58 @@
+//! Re-parses sources which were scraped with an older parser version (see [`parser::PARSER_VERSION`]).
+//!
+//! Etherscan contracts are re-parsed from their stored raw ABI payloads, contracts without a (readable)
+//! payload are reset in order to be re-fetched by the Etherscan scraper. GitHub repositories are not stored
+//! locally, hence they're reset in order to be re-cloned by the GitHub scraper.
+
+use crate::scraper::etherscan::insert_signatures;
+use anyhow::Error;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::PayloadKind;
+use etherface_lib::parser;
+use log::info;
+use log::warn;
+
+pub fn start() -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    let contracts = dbc.etherscan_contract().get_scraped_with_parser_version_below(parser::PARSER_VERSION)?;
+    info!("Re-parsing {} Etherscan contracts...", contracts.len());
+
+    let (mut num_reparsed, mut num_reset) = (0, 0);
+    for contract in contracts {
+        // Corrupt payloads are treated like missing ones, i.e. the contract is re-fetched
+        let payloads = match dbc.etherscan_payload().get_by_contract(contract.id, PayloadKind::Abi) {
+            Ok(payloads) => payloads,
+            Err(why) => {
+                warn!("Failed to read stored ABI of {}; {why}", contract.address);
+                Vec::new()
+            }
+        };
+
+        if payloads.is_empty() {
+            dbc.etherscan_contract().set_scraped_to_null(&contract)?;
+            num_reset += 1;
+            continue;
+        }
+
+        dbc.transaction(|| -> Result<(), Error> {
+            for payload in &payloads {
+                if let Ok(signatures) = parser::from_abi(payload) {
+                    insert_signatures(&dbc, &contract, &signatures)?;
+                }
+            }
+
+            dbc.etherscan_contract().set_parser_version(&contract, parser::PARSER_VERSION)?;
+
+            Ok(())
+        })?;
+        num_reparsed += 1;
+    }
+    info!("Re-parsed {num_reparsed} Etherscan contracts, reset {num_reset} without a stored ABI");
+
+    let num_repositories =
+        dbc.github_repository().set_scraped_to_null_where_parser_version_below(parser::PARSER_VERSION)?;
+    info!("Reset {num_repositories} GitHub repositories to be scraped again");
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+//! Re-evaluates all stored signatures with the current parser, e.g. after a parser fix changed how parameter
+//! types are canonicalized or validated (see [`parser::canonicalize`]).
+//!
+//! Signatures whose validity changed are updated in place. Signatures whose canonical form changed are
+//! rewritten with their re-computed hash, or, if the canonical form is already stored, merged into the
+//! existing signature such that all of their mappings are kept. Each merge runs within its own transaction.
+//! Signatures invalidated by moderation (see `maintenance::flag`) are kept invalid regardless of the parser.
+
+use anyhow::Error;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::SignatureKind;
+use etherface_lib::model::SignatureWithMetadata;
+use etherface_lib::parser;
+use log::debug;
+use log::info;
+use std::collections::HashSet;
+
+/// Number of signatures read at once.
+const BATCH_SIZE: i64 = 10_000;
+
+/// Re-evaluates all signatures, only logging the changes instead of applying them if `dry_run` is set.
+pub fn start(dry_run: bool) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+    let invalidated = dbc.signature_flag().get_invalidated_signature_ids()?;
+    let invalidated = invalidated.into_iter().collect::<HashSet<_>>();
+
+    let (mut after, mut num_checked, mut num_revalidated, mut num_rehashed, mut num_merged) = (0, 0, 0, 0, 0);
+    loop {
+        let signatures = dbc.signature().get_batch_after(after, BATCH_SIZE)?;
+        match signatures.last() {
+            Some(last) => after = last.id,
+            None => break,
+        }
+
+        for entity in signatures {
+            num_checked += 1;
+
+            // Texts which aren't signatures at all are kept but marked as invalid
+            let (text, is_valid) = parser::canonicalize(&entity.text).unwrap_or((entity.text.clone(), false));
+            let is_valid = is_valid && !invalidated.contains(&entity.id);
+
+            if text == entity.text {
+                if is_valid != entity.is_valid {
+                    debug!("Setting validity of {} to {is_valid}", entity.text);
+                    if !dry_run {
+                        dbc.signature().set_valid(entity.id, is_valid)?;
+                    }
+
+                    num_revalidated += 1;
+                }
+
+                continue;
+            }
+
+            // The kind is irrelevant as the signatures kinds are kept within `mapping_signature_kind`
+            let canonical = SignatureWithMetadata::new(text, SignatureKind::Function, is_valid);
+            match dbc.signature().get_by_hash(&canonical.hash)? {
+                Some(existing) => {
+                    debug!("Merging {} into {}", entity.text, existing.text);
+                    if !dry_run {
+                        dbc.transaction(|| dbc.signature().merge(entity.id, existing.id))?;
+                    }
+
+                    num_merged += 1;
+                }
+
+                None => {
+                    debug!("Rewriting {} as {}", entity.text, canonical.text);
+                    if !dry_run {
+                        dbc.signature().set_text(entity.id, &canonical)?;
+                    }
+
+                    num_rehashed += 1;
+                }
+            }
+        }
+
+        info!("Checked {num_checked} signatures so far");
+    }
+
+    info!(
+        "{}Checked {num_checked} signatures: {num_revalidated} re-validated, {num_rehashed} re-hashed, \
+        {num_merged} merged into existing signatures",
+        if dry_run { "[dry run] " } else { "" }
+    );
+
+    Ok(())
+}
@@ -0,0 +1,237 @@
+//! Populates a SQLite database (see `etherface_lib::database::sqlite`) from a dataset snapshot created by the
+//! snapshot exporter, such that signatures can be looked up offline via `etherface lookup --sqlite <path>`.
+//!
+//! Only signatures and their mappings are imported; already present rows are skipped, hence a database can
+//! be populated from a newer snapshot again.
+
+use crate::exporter::file_name;
+use anyhow::Error;
+use chrono::DateTime;
+use chrono::Utc;
+use etherface_lib::database::handler::export::EXPORT_SOURCES;
+use etherface_lib::database::sqlite::SqliteClient;
+use etherface_lib::model::decode_hash;
+use etherface_lib::model::MappingSignatureEtherscan;
+use etherface_lib::model::MappingSignatureFourbyte;
+use etherface_lib::model::MappingSignatureGithub;
+use etherface_lib::model::MappingSignatureKind;
+use etherface_lib::model::Signature;
+use etherface_lib::model::SignatureKind;
+use flate2::read::GzDecoder;
+use log::info;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// Number of rows inserted within one transaction.
+const BATCH_SIZE: usize = 1000;
+
+#[derive(Deserialize)]
+struct SignatureRecord {
+    id: i32,
+    text: String,
+    hash: String,
+    is_valid: bool,
+    added_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct KindRecord {
+    signature_id: i32,
+    kind: SignatureKind,
+}
+
+#[derive(Deserialize)]
+struct GithubRecord {
+    signature_id: i32,
+    repository_id: i32,
+    kind: SignatureKind,
+    added_at: DateTime<Utc>,
+    last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct EtherscanRecord {
+    signature_id: i32,
+    contract_id: i32,
+    kind: SignatureKind,
+    added_at: DateTime<Utc>,
+    last_seen_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct FourbyteRecord {
+    signature_id: i32,
+    kind: SignatureKind,
+    added_at: DateTime<Utc>,
+    last_seen_at: DateTime<Utc>,
+}
+
+pub fn start(directory: &Path, path: &Path) -> Result<(), Error> {
+    let path = path.to_str().ok_or_else(|| anyhow::anyhow!("Path {} isn't valid UTF-8", path.display()))?;
+    populate(&SqliteClient::new(path)?, directory)?;
+    info!("Populated {path} from snapshot {}", directory.display());
+
+    Ok(())
+}
+
+/// Inserts the signatures and their mappings of the snapshot within `directory`, signatures first such that
+/// mappings always reference present signatures.
+fn populate(client: &SqliteClient, directory: &Path) -> Result<(), Error> {
+    insert(directory, "signature", to_signature, |x| client.insert_signatures(x))?;
+    insert(directory, "mapping_signature_kind", to_kind, |x| client.insert_kinds(x))?;
+    insert(directory, "mapping_signature_github", to_github, |x| client.insert_github_mappings(x))?;
+    insert(directory, "mapping_signature_etherscan", to_etherscan, |x| client.insert_etherscan_mappings(x))?;
+    insert(directory, "mapping_signature_fourbyte", to_fourbyte, |x| client.insert_fourbyte_mappings(x))?;
+
+    Ok(())
+}
+
+/// Reads all records of the gzip compressed CSV file exported as `name` and inserts them in batches.
+fn insert<R, T, C, I>(directory: &Path, name: &str, convert: C, insert_batch: I) -> Result<(), Error>
+where
+    R: DeserializeOwned,
+    C: Fn(R) -> Result<T, Error>,
+    I: Fn(&[T]) -> Result<usize, etherface_lib::error::Error>,
+{
+    let source = EXPORT_SOURCES.iter().find(|source| source.name == name).expect("exported source");
+    let path = directory.join(file_name(source));
+    if !path.exists() {
+        anyhow::bail!("Snapshot file {} is missing", path.display());
+    }
+
+    let mut reader = csv::Reader::from_reader(GzDecoder::new(BufReader::new(File::open(&path)?)));
+    let (mut num_read, mut num_inserted) = (0, 0);
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    for record in reader.deserialize() {
+        batch.push(convert(record?)?);
+        num_read += 1;
+
+        if batch.len() == BATCH_SIZE {
+            num_inserted += insert_batch(&batch)?;
+            batch.clear();
+        }
+    }
+
+    if !batch.is_empty() {
+        num_inserted += insert_batch(&batch)?;
+    }
+
+    info!("Inserted {num_inserted} of {num_read} rows from {}", path.display());
+    Ok(())
+}
+
+/// Converts a signature record, deriving its selector and topic from the hash as the export doesn't contain
+/// them.
+fn to_signature(record: SignatureRecord) -> Result<Signature, Error> {
+    let topic0 = match decode_hash(&record.hash) {
+        Some(topic0) if topic0.len() == 32 => topic0,
+        _ => anyhow::bail!("Signature {} has an invalid hash '{}'", record.id, record.hash),
+    };
+
+    Ok(Signature {
+        id: record.id,
+        text: record.text,
+        hash: record.hash,
+        is_valid: record.is_valid,
+        added_at: record.added_at,
+        selector: topic0[..4].to_vec(),
+        topic0,
+        usage_count: 0,
+        last_called_at: None,
+    })
+}
+
+fn to_kind(record: KindRecord) -> Result<MappingSignatureKind, Error> {
+    Ok(MappingSignatureKind {
+        signature_id: record.signature_id,
+        kind: record.kind,
+    })
+}
+
+fn to_github(record: GithubRecord) -> Result<MappingSignatureGithub, Error> {
+    Ok(MappingSignatureGithub {
+        signature_id: record.signature_id,
+        repository_id: record.repository_id,
+        kind: record.kind,
+        added_at: record.added_at,
+        last_seen_at: record.last_seen_at,
+    })
+}
+
+fn to_etherscan(record: EtherscanRecord) -> Result<MappingSignatureEtherscan, Error> {
+    Ok(MappingSignatureEtherscan {
+        signature_id: record.signature_id,
+        contract_id: record.contract_id,
+        kind: record.kind,
+        added_at: record.added_at,
+        last_seen_at: record.last_seen_at,
+    })
+}
+
+fn to_fourbyte(record: FourbyteRecord) -> Result<MappingSignatureFourbyte, Error> {
+    Ok(MappingSignatureFourbyte {
+        signature_id: record.signature_id,
+        kind: record.kind,
+        added_at: record.added_at,
+        last_seen_at: record.last_seen_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use etherface_lib::database::reader::SignatureReader;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::fs;
+    use std::io::Write;
+
+    fn write(directory: &Path, name: &str, content: &str) {
+        let mut encoder = GzEncoder::new(File::create(directory.join(name)).unwrap(), Compression::default());
+        encoder.write_all(content.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    #[test]
+    fn populate_from_snapshot() {
+        let directory = std::env::temp_dir().join(format!("etherface-sqlite-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        let hash = "a9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b";
+        let added_at = "2022-10-01T00:00:00.123456+00:00";
+        write(
+            &directory,
+            "signature.csv.gz",
+            &format!(
+                "id,text,hash,is_valid,added_at\n1,\"transfer(address,uint256)\",{hash},true,{added_at}"
+            ),
+        );
+        write(&directory, "mapping_signature_kind.csv.gz", "signature_id,kind\n1,function\n");
+        write(
+            &directory,
+            "mapping_signature_github.csv.gz",
+            &format!(
+                "signature_id,repository_id,kind,added_at,last_seen_at\n1,7,function,{added_at},{added_at}"
+            ),
+        );
+        write(
+            &directory,
+            "mapping_signature_etherscan.csv.gz",
+            "signature_id,contract_id,kind,added_at,last_seen_at\n",
+        );
+        write(&directory, "mapping_signature_fourbyte.csv.gz", "signature_id,kind,added_at,last_seen_at\n");
+
+        let client = SqliteClient::new(":memory:").unwrap();
+        populate(&client, &directory).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let found = client.signatures_where_hash("a9059cbb", Some(SignatureKind::Function), 10).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].text, "transfer(address,uint256)");
+        assert_eq!(client.github_repository_ids(1, None, 10).unwrap(), vec![7]);
+        assert!(!client.is_on_fourbyte(1, None).unwrap());
+    }
+}
@@ -0,0 +1,89 @@
+//! Lists and moderates signatures submitted by REST API consumers (see `POST /v1/signatures`). Approving a
+//! submission inserts its signature, referencing the submission as its source, whereas rejected submissions
+//! are merely kept such that re-submitting them returns their status. Both are recorded within the audit log,
+//! actored by the local user running the command.
+
+use crate::maintenance::actor;
+use anyhow::Error;
+use chrono::Utc;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::AuditAction;
+use etherface_lib::model::MappingSignatureSubmission;
+use etherface_lib::model::SignatureWithMetadata;
+use etherface_lib::model::SubmissionStatus;
+use log::info;
+
+/// Prints the `limit` oldest submissions pending moderation.
+pub fn list(limit: i64) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    for entity in dbc.signature_submission().get_pending(limit)? {
+        println!(
+            "{}\t{:?}\t{}\t{}\tsubmitted {} by API key {}",
+            entity.id,
+            entity.kind,
+            entity.text,
+            if entity.is_valid { "valid" } else { "invalid" },
+            entity.submitted_at,
+            entity.api_key_id,
+        );
+    }
+
+    Ok(())
+}
+
+/// Approves the pending submission with the given id, inserting its signature.
+pub fn approve(id: i32) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    let signature = dbc.transaction(|| -> Result<_, Error> {
+        let moderated = dbc.signature_submission().moderate(id, SubmissionStatus::Approved, &actor())?;
+        let submission = match moderated {
+            Some(submission) => submission,
+            None => anyhow::bail!("No pending submission with id {id}"),
+        };
+
+        let metadata = SignatureWithMetadata::new(submission.text, submission.kind, submission.is_valid);
+        let signature = dbc.signature().insert(&metadata)?;
+        dbc.mapping_signature_submission().insert(&MappingSignatureSubmission {
+            signature_id: signature.id,
+            submission_id: submission.id,
+            kind: submission.kind,
+            added_at: Utc::now(),
+        })?;
+
+        let target = format!("submission:{id}");
+        let details = format!("approved {:?} {}", submission.kind, signature.text);
+        dbc.audit_log().insert(&actor(), AuditAction::Moderation, Some(&target), Some(&details))?;
+
+        Ok(signature)
+    })?;
+
+    info!("Approved submission {id} as signature {} ({})", signature.id, signature.text);
+    Ok(())
+}
+
+/// Rejects the pending submission with the given id, optionally recording the reason in the audit log.
+pub fn reject(id: i32, reason: Option<&str>) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    dbc.transaction(|| -> Result<_, Error> {
+        let moderated = dbc.signature_submission().moderate(id, SubmissionStatus::Rejected, &actor())?;
+        let submission = match moderated {
+            Some(submission) => submission,
+            None => anyhow::bail!("No pending submission with id {id}"),
+        };
+
+        let target = format!("submission:{id}");
+        let details = match reason {
+            Some(reason) => format!("rejected {:?} {}: {reason}", submission.kind, submission.text),
+            None => format!("rejected {:?} {}", submission.kind, submission.text),
+        };
+        dbc.audit_log().insert(&actor(), AuditAction::Moderation, Some(&target), Some(&details))?;
+
+        Ok(())
+    })?;
+
+    info!("Rejected submission {id}");
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! Imports the on-chain usage of signatures from an external counts file, e.g. produced by scanning the
+//! transactions and logs of an archive node.
+//!
+//! The file is a (optionally gzip compressed) CSV file with a `hash,count,last_called_at` header, where
+//! `hash` is either a 4 byte selector or a 32 byte event topic (hex encoded, `0x` prefix optional) and
+//! `last_called_at` an optional RFC 3339 date. Counts are absolute, i.e. they replace previously imported
+//! counts rather than being added to them.
+
+use anyhow::Error;
+use chrono::DateTime;
+use chrono::Utc;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::decode_hash;
+use etherface_lib::model::SignatureUsage;
+use flate2::read::GzDecoder;
+use log::info;
+use log::warn;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
+
+/// Number of counts updated at once.
+const BATCH_SIZE: usize = 10_000;
+
+#[derive(Debug, Deserialize)]
+struct UsageRecord {
+    hash: String,
+    count: i64,
+    last_called_at: Option<DateTime<Utc>>,
+}
+
+pub fn start(path: &Path) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+
+    let file = BufReader::new(File::open(path)?);
+    let reader: Box<dyn Read> = match path.extension() {
+        Some(extension) if extension == "gz" => Box::new(GzDecoder::new(file)),
+        _ => Box::new(file),
+    };
+
+    let (mut num_records, mut num_updated) = (0, 0);
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    for record in csv::Reader::from_reader(reader).deserialize::<UsageRecord>() {
+        let record = record?;
+        num_records += 1;
+
+        if let Some(usage) = to_usage(record) {
+            batch.push(usage);
+        }
+
+        if batch.len() == BATCH_SIZE {
+            num_updated += dbc.signature().set_usage_many(&batch)?;
+            batch.clear();
+        }
+    }
+
+    if !batch.is_empty() {
+        num_updated += dbc.signature().set_usage_many(&batch)?;
+    }
+
+    info!("Imported {num_records} usage counts, updating {num_updated} signatures");
+    Ok(())
+}
+
+/// Converts a record into a [`SignatureUsage`], returning `None` if its hash is invalid.
+fn to_usage(record: UsageRecord) -> Option<SignatureUsage> {
+    let hash = record.hash.trim_start_matches("0x");
+
+    match decode_hash(hash) {
+        Some(hash) if hash.len() == 4 || hash.len() == 32 => Some(SignatureUsage {
+            hash,
+            usage_count: record.count,
+            last_called_at: record.last_called_at,
+        }),
+
+        _ => {
+            warn!("Skipping usage count of invalid hash '{}'", record.hash);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_usage_decodes_hash() {
+        let record = UsageRecord {
+            hash: "0xa9059cbb".to_string(),
+            count: 42,
+            last_called_at: None,
+        };
+
+        assert_eq!(
+            to_usage(record),
+            Some(SignatureUsage {
+                hash: vec![0xa9, 0x05, 0x9c, 0xbb],
+                usage_count: 42,
+                last_called_at: None,
+            })
+        );
+    }
+
+    #[test]
+    fn to_usage_invalid_hash() {
+        let record = UsageRecord {
+            hash: "a9059c".to_string(),
+            count: 42,
+            last_called_at: None,
+        };
+
+        assert_eq!(to_usage(record), None);
+    }
+}
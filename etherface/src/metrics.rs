@@ -0,0 +1,92 @@
+//! Prometheus metrics exporter.
+//!
+//! Serves all metrics registered in `etherface_lib::metrics` on `http://<Config::metrics_address>/metrics`.
+//! Queue depths are queried from the database with each request, whereas all other metrics are updated by
+//! the fetchers, scrapers and API clients themselves.
+//!
+//! Requests are served one at a time, as such each connection is bounded by [`STREAM_TIMEOUT`] such that a
+//! stalled client can't block the exporter. If the database is unavailable the last known queue depths are
+//! served and the connection is re-established with the next request.
+
+use anyhow::Error;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::metrics::QUEUE_DEPTH;
+use log::debug;
+use log::info;
+use log::warn;
+use prometheus::Encoder;
+use prometheus::TextEncoder;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// Maximum duration of reading a request or writing a response.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Starts the exporter, blocking forever.
+pub fn start(address: &str) -> Result<(), Error> {
+    let mut dbc = None;
+    let listener = TcpListener::bind(address)?;
+    info!("Serving metrics on http://{address}/metrics");
+
+    for stream in listener.incoming() {
+        // A misbehaving client shouldn't take down the exporter
+        if let Err(why) = stream.map_err(Error::from).and_then(|stream| handle(&mut dbc, stream)) {
+            debug!("Failed to serve metrics; {why}");
+        }
+    }
+
+    Ok(())
+}
+
+fn handle(dbc: &mut Option<DatabaseClient>, mut stream: TcpStream) -> Result<(), Error> {
+    stream.set_read_timeout(Some(STREAM_TIMEOUT))?;
+    stream.set_write_timeout(Some(STREAM_TIMEOUT))?;
+
+    // Only the request line is of interest, e.g. `GET /metrics HTTP/1.1`
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+
+    if request_line.split_whitespace().nth(1) != Some("/metrics") {
+        stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+        return Ok(());
+    }
+
+    if let Err(why) = update_queue_depths(dbc) {
+        warn!("Failed to query queue depths; {why}");
+    }
+
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    encoder.encode(&prometheus::gather(), &mut body)?;
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        encoder.format_type(),
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+
+    Ok(())
+}
+
+/// Queries the queue depths, connecting to the database first if not already connected.
+fn update_queue_depths(dbc: &mut Option<DatabaseClient>) -> Result<(), Error> {
+    // Taken such that a failing client is dropped and re-established with the next request
+    let client = match dbc.take() {
+        Some(client) => client,
+        None => DatabaseClient::new()?,
+    };
+
+    let unscraped_repositories = client.github_repository().get_unscraped_count()?;
+    let unscraped_contracts = client.etherscan_contract().get_unscraped_count()?;
+    QUEUE_DEPTH.with_label_values(&["github_repository_unscraped"]).set(unscraped_repositories);
+    QUEUE_DEPTH.with_label_values(&["etherscan_contract_unscraped"]).set(unscraped_contracts);
+
+    *dbc = Some(client);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Delivery of watchlist webhooks.
+//!
+//! Watchlist entries are resolved by a database trigger as soon as any fetcher finds a matching signature
+//! (see the `watchlist` migration), whereas this worker POSTs resolved entries to their webhook every
+//! `Config::watchlist_notify_interval` seconds. Failed calls, e.g. non-2xx responses, are retried with an
+//! exponential backoff up to [`MAX_NOTIFY_ATTEMPTS`] times; consumers can always fall back to polling their
+//! watchlist via `GET /v1/watchlist`. Calls to hosts no longer resolving to public addresses fail the same way
+//! (see `etherface_lib::api::webhook`). Calls are signed with the entries `webhook_secret`, just like webhook
+//! deliveries.
+
+use anyhow::Error;
+use chrono::Duration;
+use chrono::Utc;
+use etherface_lib::api::webhook::WebhookClient;
+use etherface_lib::config::Config;
+use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::WatchlistEntry;
+use log::debug;
+use log::warn;
+use serde_json::json;
+
+/// Name of the notifier within the `worker_status` table.
+const WORKER_NAME: &str = "watchlist-notifier";
+
+/// Number of entries notified per iteration.
+const BATCH_SIZE: i64 = 100;
+
+/// Number of failed webhook calls after which an entry is given up on.
+const MAX_NOTIFY_ATTEMPTS: i32 = 10;
+
+/// Delay before retrying the first failed webhook call, doubled with each further one.
+const RETRY_BACKOFF_MINUTES: i64 = 1;
+
+/// Upper bound of the delay before retrying a failed webhook call.
+const RETRY_MAX_BACKOFF_MINUTES: i64 = 24 * 60;
+
+pub fn start(one_shot: bool) -> Result<(), Error> {
+    let config = Config::new()?;
+    let dbc = DatabaseClient::new()?;
+    let client = WebhookClient::new()?;
+    dbc.worker_status().register(WORKER_NAME)?;
+
+    loop {
+        dbc.worker_status().heartbeat(WORKER_NAME, None)?;
+
+        let entries = dbc.watchlist_entry().get_due_notifications(BATCH_SIZE)?;
+        for entry in &entries {
+            notify(&dbc, &client, entry)?;
+        }
+
+        dbc.worker_status().add_processed(WORKER_NAME, entries.len() as i64)?;
+        if !entries.is_empty() {
+            debug!("Notified {} resolved watchlist entries", entries.len());
+        }
+
+        // Continue right away if there might be more due entries
+        if entries.len() as i64 == BATCH_SIZE {
+            continue;
+        }
+
+        if one_shot {
+            return Ok(());
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(config.watchlist_notify_interval));
+    }
+}
+
+fn notify(dbc: &DatabaseClient, client: &WebhookClient, entry: &WatchlistEntry) -> Result<(), Error> {
+    // Entries with a webhook always hold a secret, see the `watchlist_entry_webhook_secret` migration
+    let (url, secret) = match (&entry.webhook_url, &entry.webhook_secret) {
+        (Some(url), Some(secret)) => (url, secret),
+        _ => return Ok(()),
+    };
+
+    let body = json!({ "event": "watchlist_entry_resolved", "entry": entry });
+    match client.post_signed(url, &body, secret) {
+        Ok(()) => dbc.watchlist_entry().set_notified(entry.id)?,
+
+        Err(why) => {
+            let attempts = entry.notify_attempts + 1;
+            let retry_at = match attempts < MAX_NOTIFY_ATTEMPTS {
+                true => Some(Utc::now() + retry_backoff(attempts)),
+                false => None,
+            };
+
+            warn!("Failed to call webhook of watchlist entry {} ({attempts} attempts); {why}", entry.id);
+            dbc.watchlist_entry().set_notify_failed(entry.id, &why.to_string(), retry_at)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the delay before retrying a webhook call which failed `attempts` times, i.e.
+/// `RETRY_BACKOFF_MINUTES * 2^(attempts - 1)` capped at [`RETRY_MAX_BACKOFF_MINUTES`].
+fn retry_backoff(attempts: i32) -> Duration {
+    let factor = 2_i64.saturating_pow(attempts.saturating_sub(1).max(0) as u32);
+    Duration::minutes(RETRY_BACKOFF_MINUTES.saturating_mul(factor).min(RETRY_MAX_BACKOFF_MINUTES))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::notifier::retry_backoff;
+    use chrono::Duration;
+
+    #[test]
+    fn retry_backoff_doubles_up_to_a_day() {
+        assert_eq!(retry_backoff(1), Duration::minutes(1));
+        assert_eq!(retry_backoff(2), Duration::minutes(2));
+        assert_eq!(retry_backoff(5), Duration::minutes(16));
+        assert_eq!(retry_backoff(20), Duration::days(1));
+    }
+}
@@ -0,0 +1,44 @@
+//! Periodic refresh of the materialized views read by the REST APIs statistics endpoint.
+//!
+//! Refreshes all views every `Config::materialized_view_refresh_interval` seconds, recording the date and
+//! duration of each refresh within the `materialized_view_refresh` table such that stale statistics can be
+//! identified via the `/v1/status/views` endpoint. Additionally the monthly partitions of the
+//! `mapping_signature_*` tables for the current and next month are created ahead of time.
+
+use anyhow::Error;
+use chrono::Datelike;
+use chrono::Duration;
+use chrono::Utc;
+use etherface_lib::config::Config;
+use etherface_lib::database::handler::materialized_view_refresh::MATERIALIZED_VIEWS;
+use etherface_lib::database::handler::DatabaseClient;
+use log::debug;
+
+/// Name of the refresher within the `worker_status` table.
+const WORKER_NAME: &str = "materialized-view-refresher";
+
+pub fn start(one_shot: bool) -> Result<(), Error> {
+    let config = Config::new()?;
+    let dbc = DatabaseClient::new()?;
+    dbc.worker_status().register(WORKER_NAME)?;
+
+    loop {
+        dbc.worker_status().heartbeat(WORKER_NAME, None)?;
+
+        // The first day of a month plus 31 days is always within the next month
+        let this_month = Utc::now().naive_utc().date().with_day(1).unwrap();
+        let next_month = (this_month + Duration::days(31)).with_day(1).unwrap();
+        dbc.partition().create_monthly(this_month)?;
+        dbc.partition().create_monthly(next_month)?;
+
+        dbc.materialized_view_refresh().refresh_all()?;
+        dbc.worker_status().add_processed(WORKER_NAME, MATERIALIZED_VIEWS.len() as i64)?;
+        debug!("Refreshed {} materialized views", MATERIALIZED_VIEWS.len());
+
+        if one_shot {
+            return Ok(());
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(config.materialized_view_refresh_interval));
+    }
+}
@@ -3,50 +3,249 @@
 //! Fetches all unscraped Etherscan contract addresses from the database, downloads their ABI content using
 //! the <https://api.etherscan.io/api?module=contract&action=getabi> endpoint extracting signatures. These
 //! extracted signatures are then inserted into the database with a reference to the contract address, marking
-//! the contract as scraped. The whole process is then repeated every [`SCRAPER_SLEEP_DURATION`] seconds.
+//! the contract as scraped. The whole process is then repeated every `Config::scraper_sleep_duration` seconds.
+//!
+//! Because most of the time is spent waiting on Etherscan, contracts are distributed among
+//! [`NUM_WORKERS`] worker threads. All workers share the same API key and as such the same ratelimiter
+//! (see `EtherscanClient`), draining the contract backlog close to the 5 requests / second Etherscan allows.
+//!
+//! Contracts whose ABI can't be fetched because of a transient error are retried with an exponential backoff
+//! (see [`retry_delay`]) up to [`MAX_RETRIES`] times, whereas contracts with unverified source code are
+//! flagged as such and never scraped again. For the latter only the function selectors and event topics found
+//! in their bytecode are kept (see `parser::from_bytecode`), as their signature texts are unknown.
+//!
+//! Additionally the creation metadata (creator, transaction and block) of each scraped contract is recorded
+//! using the <https://api.etherscan.io/api?module=contract&action=getcontractcreation> endpoint, as well as
+//! its source code and compiler settings (optimizer, EVM version and license) using the
+//! <https://api.etherscan.io/api?module=contract&action=getsourcecode> endpoint. Both the raw ABI and source
+//! code are kept as payloads, such that contracts can be re-parsed without re-fetching them.
 
 use crate::scraper::Scraper;
 use anyhow::Error;
 use chrono::Utc;
 use etherface_lib::api::etherscan::EtherscanClient;
+use etherface_lib::config::Config;
 use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::model::EtherscanContract;
 use etherface_lib::model::MappingSignatureEtherscan;
+use etherface_lib::model::PayloadKind;
+use etherface_lib::model::SignatureHash;
+use etherface_lib::model::SignatureWithMetadata;
 use etherface_lib::parser;
+use etherface_lib::report;
+use log::debug;
+use log::warn;
+use std::sync::Mutex;
+
+/// Number of worker threads concurrently fetching ABIs from Etherscan.
+const NUM_WORKERS: usize = 4;
+
+/// Number of failed ABI fetches after which a contract is no longer scraped.
+const MAX_RETRIES: i32 = 8;
+
+/// Delay before the first retry of a failed contract, doubled with each further retry.
+const RETRY_BASE_DELAY_MINUTES: i64 = 10;
+
+/// Upper bound for the delay between two retries.
+const RETRY_MAX_DELAY_MINUTES: i64 = 24 * 60;
 
-use super::SCRAPER_SLEEP_DURATION;
+/// Name of this scraper within the `worker_status` table, shared by all worker threads.
+const WORKER_NAME: &str = "etherscan-scraper";
 
 #[derive(Debug)]
 pub struct EtherscanScraper;
 impl Scraper for EtherscanScraper {
-    fn start(&self) -> Result<(), Error> {
+    fn start(&self, one_shot: bool) -> Result<(), Error> {
+        let config = Config::new()?;
         let dbc = DatabaseClient::new()?;
-        let esc = EtherscanClient::new()?;
+        dbc.worker_status().register(WORKER_NAME)?;
 
         loop {
-            // Scrape signatures from unvisited contracts
-            for contract in dbc.etherscan_contract().get_unvisited() {
-                if let Ok(abi_content) = esc.get_abi(&contract.address) {
-                    if let Ok(signatures) = parser::from_abi(&abi_content) {
-                        // Insert all scraped signatures
-                        for signature in signatures {
-                            let inserted_signature = dbc.signature().insert(&signature);
-
-                            let mapping = MappingSignatureEtherscan {
-                                signature_id: inserted_signature.id,
-                                contract_id: contract.id,
-                                kind: signature.kind,
-                                added_at: Utc::now(),
-                            };
-
-                            dbc.mapping_signature_etherscan().insert(&mapping);
+            dbc.worker_status().heartbeat(WORKER_NAME, None)?;
+            let contracts = dbc.etherscan_contract().get_unvisited(MAX_RETRIES)?;
+
+            if !contracts.is_empty() {
+                debug!("Scraping {} Etherscan contracts...", contracts.len());
+
+                // Workers pop contracts from a shared queue until it's empty
+                let queue = Mutex::new(contracts.into_iter());
+                std::thread::scope(|scope| {
+                    let workers: Vec<_> = (0..NUM_WORKERS).map(|_| scope.spawn(|| worker(&queue))).collect();
+
+                    workers.into_iter().map(|worker| worker.join().unwrap()).collect::<Result<(), Error>>()
+                })?;
+            }
+
+            if one_shot {
+                return Ok(());
+            }
+
+            std::thread::sleep(std::time::Duration::from_secs(config.scraper_sleep_duration));
+        }
+    }
+}
+
+/// Scrapes contracts from the queue until it's empty.
+fn worker(queue: &Mutex<std::vec::IntoIter<EtherscanContract>>) -> Result<(), Error> {
+    let dbc = DatabaseClient::new()?;
+    let esc = EtherscanClient::new()?;
+
+    loop {
+        let contract = match queue.lock().unwrap().next() {
+            Some(contract) => contract,
+            None => return Ok(()),
+        };
+
+        dbc.worker_status().heartbeat(WORKER_NAME, Some(&contract.address))?;
+
+        let abi_content = match esc.get_abi(&contract.address) {
+            Ok(abi_content) => abi_content,
+
+            Err(why) => match why {
+                etherface_lib::error::Error::EtherscanContractSourceCodeNotVerified(_) => {
+                    let code = match esc.get_code(&contract.address) {
+                        Ok(code) => code,
+                        Err(why @ etherface_lib::error::Error::EtherscanInvalidToken(_)) => {
+                            return Err(why.into())
+                        }
+
+                        // The contract stays in the retry path until its bytecode was fetched
+                        Err(why) => {
+                            set_failed(&dbc, &contract, "bytecode", &why)?;
+                            continue;
                         }
-                    }
+                    };
+                    let hashes = bytecode_hashes(&contract, &code);
 
-                    dbc.etherscan_contract().set_visited(&contract);
+                    dbc.transaction(|| -> Result<(), Error> {
+                        dbc.etherscan_bytecode_hash().insert_many(contract.id, &hashes)?;
+                        dbc.etherscan_contract().set_unverified(&contract)?;
+                        Ok(())
+                    })?;
+
+                    continue;
                 }
+
+                // Every further request would fail too, no need to continue
+                etherface_lib::error::Error::EtherscanInvalidToken(_) => return Err(why.into()),
+
+                _ => {
+                    set_failed(&dbc, &contract, "ABI", &why)?;
+                    continue;
+                }
+            },
+        };
+
+        // The creation metadata is nice to have but not essential, hence don't retry the contract if it fails
+        let creation = match esc.get_contract_creation(&contract.address) {
+            Ok(creation) => creation,
+            Err(why) => {
+                warn!("Failed to fetch creation metadata of {}; {why}", contract.address);
+                None
+            }
+        };
+
+        // Same as with the creation metadata
+        let source = match esc.get_source_code(&contract.address) {
+            Ok(source) => source,
+            Err(why) => {
+                warn!("Failed to fetch source code of {}; {why}", contract.address);
+                None
             }
+        };
+
+        // All writes of a contract are done in one transaction, such that a failure can't leave the contract
+        // marked as scraped with only some of its signatures
+        dbc.transaction(|| -> Result<(), Error> {
+            // Keep the raw ABI and source code around such that they can be re-parsed without having to
+            // re-fetch them
+            dbc.etherscan_payload().insert(contract.id, PayloadKind::Abi, &abi_content)?;
+            if let Some(source) = &source {
+                dbc.etherscan_payload().insert(contract.id, PayloadKind::Source, &source.content)?;
+            }
+
+            if let Ok(signatures) = parser::from_abi(&abi_content) {
+                insert_signatures(&dbc, &contract, &signatures)?;
+            }
+
+            if let Some(creation) = &creation {
+                dbc.etherscan_contract().set_creation(&contract, creation)?;
+            }
+
+            if let Some(source) = &source {
+                dbc.etherscan_contract().set_compiler_settings(&contract, &source.settings)?;
+            }
+
+            dbc.etherscan_contract().set_visited(&contract)?;
+            dbc.etherscan_contract().set_parser_version(&contract, parser::PARSER_VERSION)?;
+            dbc.worker_status().add_processed(WORKER_NAME, 1)?;
 
-            std::thread::sleep(std::time::Duration::from_secs(SCRAPER_SLEEP_DURATION));
+            Ok(())
+        })?;
+    }
+}
+
+/// Inserts the given signatures, mapping them to the given contract.
+pub fn insert_signatures(
+    dbc: &DatabaseClient,
+    contract: &EtherscanContract,
+    signatures: &[SignatureWithMetadata],
+) -> Result<(), Error> {
+    let mappings = dbc
+        .signature()
+        .insert_many(signatures)?
+        .iter()
+        .zip(signatures)
+        .map(|(inserted_signature, signature)| MappingSignatureEtherscan {
+            signature_id: inserted_signature.id,
+            contract_id: contract.id,
+            kind: signature.kind,
+            added_at: Utc::now(),
+            last_seen_at: Utc::now(),
+        })
+        .collect::<Vec<_>>();
+
+    dbc.mapping_signature_etherscan().insert_many(&mappings)?;
+    Ok(())
+}
+
+/// Records a failed attempt of fetching `what` of the contract, scheduling its next attempt and reporting it
+/// if it won't be retried anymore.
+fn set_failed(
+    dbc: &DatabaseClient,
+    contract: &EtherscanContract,
+    what: &str,
+    why: &etherface_lib::error::Error,
+) -> Result<(), Error> {
+    warn!("Failed to fetch {what} of {} (retry {}); {why}", contract.address, contract.retry_count);
+    let retry_at = Utc::now() + retry_delay(contract.retry_count);
+    dbc.etherscan_contract().set_failed(contract, &why.to_string(), retry_at)?;
+
+    if contract.retry_count + 1 >= MAX_RETRIES {
+        report::report(
+            &format!("Giving up on fetching {what} of {}; {why}", contract.address),
+            &[("contract_id", contract.id.to_string()), ("contract", contract.address.clone())],
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the function selectors and event topics extracted from the bytecode of an unverified contract.
+/// Malformed bytecode won't change with a retry, hence it's only logged.
+fn bytecode_hashes(contract: &EtherscanContract, code: &str) -> Vec<SignatureHash> {
+    match parser::from_bytecode(code) {
+        Ok(hashes) => hashes,
+        Err(why) => {
+            warn!("Failed to extract hashes from bytecode of {}; {why}", contract.address);
+            Vec::new()
         }
     }
 }
+
+/// Returns the delay before retrying a contract which already failed `retry_count` times, i.e.
+/// `RETRY_BASE_DELAY_MINUTES * 2^retry_count` capped at [`RETRY_MAX_DELAY_MINUTES`].
+fn retry_delay(retry_count: i32) -> chrono::Duration {
+    let factor = 2_i64.saturating_pow(retry_count.clamp(0, 32) as u32);
+    chrono::Duration::minutes(RETRY_BASE_DELAY_MINUTES.saturating_mul(factor).min(RETRY_MAX_DELAY_MINUTES))
+}
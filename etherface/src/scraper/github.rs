@@ -2,18 +2,24 @@
 //!
 //! Fetches all unscraped GitHub repositories from the database, clones them onto the local filesystem finding
 //! all files ending in `.{sol,json,abi}` scraping their signatures from them before deleting the repository.
+//! Files are parsed regardless of their encoding (see `parser::decode`), such that only binary or unreadable
+//! files are skipped.
 //! These extracted signatures are then inserted into the database with a reference to the given GitHub
 //! repository, marking the repository as scraped. The whole process is then repeated every
-//! [`SCRAPER_SLEEP_DURATION`] seconds.
+//! `Config::scraper_sleep_duration` seconds.
 
-use crate::scraper::SCRAPER_SLEEP_DURATION;
 use crate::scraper::Scraper;
 use anyhow::Error;
 use chrono::Utc;
 use etherface_lib::api::github::GithubClient;
+use etherface_lib::api::github::GithubConsumer;
+use etherface_lib::config::Config;
 use etherface_lib::database::handler::DatabaseClient;
+use etherface_lib::metrics::SCRAPED_FILES;
+use etherface_lib::model::GithubRepositoryDatabase;
 use etherface_lib::model::MappingSignatureGithub;
 use etherface_lib::parser;
+use etherface_lib::report;
 use log::debug;
 use log::error;
 use log::trace;
@@ -40,23 +46,49 @@ enum FileKind {
 /// Path where repositories are cloned to.
 const PATH_CLONE_DIR: &str = "/tmp/etherface";
 
+/// Name of this scraper within the `worker_status` table.
+const WORKER_NAME: &str = "github-scraper";
+
 impl Scraper for GithubScraper {
-    fn start(&self) -> Result<(), Error> {
-        let ghc = GithubClient::new()?;
+    fn start(&self, one_shot: bool) -> Result<(), Error> {
+        let config = Config::new()?;
+        let ghc = GithubClient::with_consumer(GithubConsumer::Scraper)?;
         let dbc = DatabaseClient::new()?;
 
         std::fs::create_dir_all(PATH_CLONE_DIR)?;
+        dbc.worker_status().register(WORKER_NAME)?;
 
         loop {
-            let repos = dbc.github_repository().get_unscraped_with_forks();
+            dbc.worker_status().heartbeat(WORKER_NAME, None)?;
+            let repos = dbc.github_repository().get_unscraped_with_forks()?;
 
             if repos.is_empty() {
-                sleep(std::time::Duration::from_secs(SCRAPER_SLEEP_DURATION));
+                if one_shot {
+                    return Ok(());
+                }
+
+                sleep(std::time::Duration::from_secs(config.scraper_sleep_duration));
                 continue;
             }
 
-            debug!("Scraping {} repositories...", dbc.github_repository().get_unscraped_with_forks().len());
+            debug!("Scraping {} repositories...", dbc.github_repository().get_unscraped_with_forks()?.len());
             for repo in repos {
+                dbc.worker_status().heartbeat(WORKER_NAME, Some(&repo.html_url))?;
+
+                // Listing the file tree costs one API call but saves cloning repositories without any files
+                // we could scrape signatures from
+                if let Ok(tree) = ghc.repos(repo.id).tree() {
+                    let has_files = tree.tree.iter().any(|x| FileKind::from_path(&x.path).is_some());
+
+                    if !tree.truncated && !has_files {
+                        trace!("Skipping {}, no Solidity or ABI files found", repo.html_url);
+                        dbc.github_repository().set_scraped(repo.id)?;
+                        dbc.github_repository().set_parser_version(repo.id, parser::PARSER_VERSION)?;
+                        dbc.worker_status().add_processed(WORKER_NAME, 1)?;
+                        continue;
+                    }
+                }
+
                 // Repository names within GitHub can start with a dash, which any CLI application such as `git`
                 // interprets as an argument. Hence we pre-emptively replace ALL dashes with an underscore because
                 // something like `git clone https://github.com/foo/-bar -bar` would result in an error rather
@@ -83,6 +115,7 @@ impl Scraper for GithubScraper {
                     Ok(status) => status,
                     Err(why) => {
                         error!("Failed to clone {}; {why}", repo.html_url);
+                        report::report(&format!("Failed to clone; {why}"), &repo_context(&repo));
                         continue;
                     }
                 };
@@ -91,30 +124,43 @@ impl Scraper for GithubScraper {
                     match ghc.repos(repo.id).get() {
                         Ok(_) => {
                             error!("Repository available but failed to clone: {}", repo.html_url);
+                            report::report("Repository available but failed to clone", &repo_context(&repo));
                             // Set it as scraped and re-try in the next scraping cycle
-                            dbc.github_repository().set_scraped(repo.id);
+                            dbc.github_repository().set_scraped(repo.id)?;
                             continue;
                         }
 
                         Err(why) => match why {
                             etherface_lib::error::Error::GithubResourceUnavailable(_) => {
                                 debug!("Setting {} as deleted", repo.html_url);
-                                dbc.github_repository().set_deleted(repo.id);
+                                dbc.github_repository().set_deleted(repo.id)?;
                                 continue;
                             }
 
                             _ => {
                                 // Never happend so far, as such we just log it for now
                                 error!("Failed to clone; {why}");
+                                report::report(&format!("Failed to clone; {why}"), &repo_context(&repo));
                                 continue;
                             }
                         },
                     }
                 }
 
+                // Signatures, mappings and the scraped state are written in one transaction, such that a
+                // failure can't leave the repository marked as scraped with only some of its signatures
                 trace!("Scraping {}", clone_name);
-                for file in get_sol_files(&clone_name) {
-                    if let Ok(content) = std::fs::read_to_string(&file.path) {
+                dbc.transaction(|| -> Result<(), Error> {
+                    let mut skipped = 0;
+                    for file in get_sol_files(&clone_name) {
+                        let content = match read_file(&file.path) {
+                            Some(content) => content,
+                            None => {
+                                skipped += 1;
+                                continue;
+                            }
+                        };
+
                         let signatures = match file.kind {
                             FileKind::Solidity => parser::from_sol(&content),
                             FileKind::Json => match parser::from_abi(&content) {
@@ -123,25 +169,89 @@ impl Scraper for GithubScraper {
                             },
                         };
 
-                        for signature in signatures {
-                            let signature_db = dbc.signature().insert(&signature);
-
-                            let mapping_entity = MappingSignatureGithub {
+                        let mappings = dbc
+                            .signature()
+                            .insert_many(&signatures)?
+                            .iter()
+                            .zip(&signatures)
+                            .map(|(signature_db, signature)| MappingSignatureGithub {
                                 signature_id: signature_db.id,
                                 repository_id: repo.id,
                                 kind: signature.kind,
                                 added_at: Utc::now(),
-                            };
+                                last_seen_at: Utc::now(),
+                            })
+                            .collect::<Vec<_>>();
 
-                            dbc.mapping_signature_github().insert(&mapping_entity);
-                        }
+                        dbc.mapping_signature_github().insert_many(&mappings)?;
                     }
-                }
 
-                dbc.github_repository().set_scraped(repo.id);
+                    if skipped > 0 {
+                        debug!("Skipped {skipped} binary or unreadable files of {}", repo.html_url);
+                    }
+
+                    dbc.github_repository().set_scraped(repo.id)?;
+                    dbc.github_repository().set_parser_version(repo.id, parser::PARSER_VERSION)?;
+                    dbc.worker_status().add_processed(WORKER_NAME, 1)?;
+
+                    Ok(())
+                })?;
                 std::fs::remove_dir_all(clone_name)?;
             }
 
+            if one_shot {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Returns the context attached to error reports concerning the given repository.
+fn repo_context(repo: &GithubRepositoryDatabase) -> [(&'static str, String); 2] {
+    [("repository_id", repo.id.to_string()), ("repository", repo.html_url.clone())]
+}
+
+impl FileKind {
+    /// Returns the kind of file at `path` based on its extension, `None` if it can't contain signatures.
+    fn from_path(path: &str) -> Option<FileKind> {
+        if path.ends_with(".sol") {
+            return Some(FileKind::Solidity);
+        }
+
+        if path.ends_with(".json") || path.ends_with(".abi") {
+            return Some(FileKind::Json);
+        }
+
+        None
+    }
+}
+
+/// Returns the decoded content of the file at `path` (see [`parser::decode`]), `None` if it's binary or
+/// couldn't be read. Either way the result is accounted for in [`SCRAPED_FILES`].
+fn read_file(path: &str) -> Option<String> {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(why) => {
+            trace!("Failed to read {path}; {why}");
+            SCRAPED_FILES.with_label_values(&["unreadable"]).inc();
+            return None;
+        }
+    };
+
+    match parser::decode(&content) {
+        Some((content, false)) => {
+            SCRAPED_FILES.with_label_values(&["decoded"]).inc();
+            Some(content)
+        }
+
+        Some((content, true)) => {
+            SCRAPED_FILES.with_label_values(&["decoded_lossily"]).inc();
+            Some(content)
+        }
+
+        None => {
+            SCRAPED_FILES.with_label_values(&["binary"]).inc();
+            None
         }
     }
 }
@@ -153,17 +263,10 @@ fn get_sol_files(dir_name: &str) -> Vec<File> {
 
     for entry in WalkDir::new(dir_name).into_iter().filter_map(|x| x.ok()) {
         if let Some(path) = entry.path().to_str() {
-            if path.ends_with(".sol") {
-                files.push(File {
-                    path: path.to_string(),
-                    kind: FileKind::Solidity,
-                });
-            }
-
-            if path.ends_with(".json") || path.ends_with(".abi") {
+            if let Some(kind) = FileKind::from_path(path) {
                 files.push(File {
                     path: path.to_string(),
-                    kind: FileKind::Json,
+                    kind,
                 });
             }
         }
@@ -5,11 +5,8 @@ pub mod github;
 
 use anyhow::Error;
 
-/// Sleep duration between scraping iterations 
-const SCRAPER_SLEEP_DURATION: u64 = 5 * 60;
-
 /// Trait providing the entry point for starting a scraper.
 pub trait Scraper: std::fmt::Debug {
-    /// Starts the scraping process.
-    fn start(&self) -> Result<(), Error>;
+    /// Starts the scraping process, returning after one iteration if `one_shot` is set.
+    fn start(&self, one_shot: bool) -> Result<(), Error>;
 }
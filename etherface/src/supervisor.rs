@@ -0,0 +1,107 @@
+//! Supervisor running fetchers and scrapers in their own threads, restarting them if they fail.
+//!
+//! A failing worker (i.e. one returning an error or panicking) is restarted after an exponential backoff
+//! (see [`restart_delay`]) such that a single failing data source doesn't stop all the others. Only if a
+//! worker failed more than [`MAX_RESTARTS`] times in a row is the error sent to the abort channel, which in
+//! turn stops the whole process. A worker running for at least [`HEALTHY_RUNTIME`] before failing is
+//! considered to have recovered, resetting its restart counter.
+
+use anyhow::Error;
+use etherface_lib::report;
+use log::debug;
+use log::error;
+use log::warn;
+use std::panic::AssertUnwindSafe;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Number of consecutive failures after which a worker is no longer restarted.
+const MAX_RESTARTS: u32 = 10;
+
+/// Delay before the first restart of a worker, doubled with each further consecutive restart.
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(30);
+
+/// Upper bound for the delay between two restarts.
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Runtime after which a failing worker is considered to have recovered since its last failure.
+const HEALTHY_RUNTIME: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Runs `worker` in a new thread, restarting it on failure and sending its error over `tx_abort_channel`
+/// once it exceeded [`MAX_RESTARTS`] consecutive restarts.
+pub fn supervise<F>(name: String, tx_abort_channel: Sender<Error>, worker: F)
+where
+    F: Fn() -> Result<(), Error> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut restarts = 0;
+
+        loop {
+            debug!("Starting {name}");
+            let started_at = Instant::now();
+
+            let why = match std::panic::catch_unwind(AssertUnwindSafe(&worker)) {
+                Ok(Ok(())) => {
+                    debug!("{name} finished");
+                    return;
+                }
+
+                Ok(Err(why)) => why,
+                Err(panic) => anyhow::anyhow!("{name} panicked; {}", panic_message(&*panic)),
+            };
+
+            if started_at.elapsed() >= HEALTHY_RUNTIME {
+                restarts = 0;
+            }
+
+            report::report(
+                &format!("{name} failed; {why}"),
+                &[("worker", name.clone()), ("restarts", restarts.to_string())],
+            );
+
+            if restarts >= MAX_RESTARTS {
+                error!("{name} failed {restarts} times in a row, giving up; {why}");
+                report::report(&format!("{name} exhausted all restarts"), &[("worker", name.clone())]);
+                tx_abort_channel.send(why).unwrap();
+                return;
+            }
+
+            let delay = restart_delay(restarts);
+            warn!("{name} failed (restart {restarts}), restarting in {}s; {why}", delay.as_secs());
+
+            restarts += 1;
+            std::thread::sleep(delay);
+        }
+    });
+}
+
+/// Returns the delay before restarting a worker which already has been restarted `restarts` times, i.e.
+/// `RESTART_BASE_DELAY * 2^restarts` capped at [`RESTART_MAX_DELAY`].
+fn restart_delay(restarts: u32) -> Duration {
+    RESTART_BASE_DELAY.saturating_mul(2_u32.saturating_pow(restarts)).min(RESTART_MAX_DELAY)
+}
+
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    match panic.downcast_ref::<&str>() {
+        Some(msg) => msg,
+        None => panic.downcast_ref::<String>().map(String::as_str).unwrap_or("unknown reason"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::restart_delay;
+    use super::RESTART_BASE_DELAY;
+    use super::RESTART_MAX_DELAY;
+    use std::time::Duration;
+
+    #[test]
+    fn restart_delay_doubles_and_caps() {
+        assert_eq!(restart_delay(0), RESTART_BASE_DELAY);
+        assert_eq!(restart_delay(1), Duration::from_secs(60));
+        assert_eq!(restart_delay(3), Duration::from_secs(240));
+        assert_eq!(restart_delay(10), RESTART_MAX_DELAY);
+        assert_eq!(restart_delay(u32::MAX), RESTART_MAX_DELAY);
+    }
+}
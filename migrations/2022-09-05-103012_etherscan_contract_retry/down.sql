@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE etherscan_contract
+    DROP COLUMN retry_count,
+    DROP COLUMN next_retry_at,
+    DROP COLUMN last_error,
+    DROP COLUMN is_unverified;
@@ -0,0 +1,5 @@
+ALTER TABLE etherscan_contract
+    ADD COLUMN retry_count      INT                         NOT NULL DEFAULT 0,     -- number of failed ABI fetches
+    ADD COLUMN next_retry_at    TIMESTAMP WITH TIME ZONE,                           -- date after which a failed contract is scraped again
+    ADD COLUMN last_error       TEXT,                                               -- error message of the last failed ABI fetch
+    ADD COLUMN is_unverified    BOOLEAN                     NOT NULL DEFAULT FALSE; -- flag indicating if the contract source code is not verified (terminal)
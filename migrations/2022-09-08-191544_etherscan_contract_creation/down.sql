@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE etherscan_contract
+    DROP COLUMN creator_address,
+    DROP COLUMN creation_tx_hash,
+    DROP COLUMN creation_block;
@@ -0,0 +1,4 @@
+ALTER TABLE etherscan_contract
+    ADD COLUMN creator_address      TEXT,   -- address of the account which deployed the contract
+    ADD COLUMN creation_tx_hash     TEXT,   -- hash of the transaction which deployed the contract
+    ADD COLUMN creation_block       BIGINT; -- block number the contract was deployed in
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE etherscan_contract
+    DROP COLUMN is_destroyed,
+    DROP COLUMN code_checked_at;
@@ -0,0 +1,3 @@
+ALTER TABLE etherscan_contract
+    ADD COLUMN is_destroyed     BOOLEAN                     NOT NULL DEFAULT FALSE, -- flag indicating if the contract has no code anymore (self-destructed)
+    ADD COLUMN code_checked_at  TIMESTAMP WITH TIME ZONE;                           -- date we last checked the contract code
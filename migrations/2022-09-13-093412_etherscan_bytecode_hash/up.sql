@@ -0,0 +1,11 @@
+-- Function selectors and event topics extracted from the bytecode of contracts without verified source code
+-- (see `parser::from_bytecode`). Their texts are unknown, but they can be joined with known signatures whose
+-- hash starts with them.
+CREATE TABLE etherscan_bytecode_hash (
+    contract_id     INT                         NOT NULL REFERENCES etherscan_contract (id),
+    hash            TEXT                        NOT NULL,
+    kind            SIGNATURE_KIND              NOT NULL,
+    added_at        TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (contract_id, hash)
+);
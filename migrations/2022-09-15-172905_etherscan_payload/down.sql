@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE mapping_payload_etherscan;
+DROP TABLE etherscan_payload;
+
+DROP TYPE payload_kind;
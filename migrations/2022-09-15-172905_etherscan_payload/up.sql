@@ -0,0 +1,22 @@
+CREATE TYPE payload_kind AS ENUM ('abi', 'source');
+
+-- Raw payloads (ABI JSON, source code) fetched from Etherscan, such that they can be re-parsed without having
+-- to re-fetch them. Payloads are zlib compressed and deduplicated by their (uncompressed) Keccak256 hash, as
+-- many contracts share the exact same ABI.
+CREATE TABLE etherscan_payload (
+    id          SERIAL                      NOT NULL,
+    hash        TEXT                        NOT NULL,
+    kind        PAYLOAD_KIND                NOT NULL,
+    content     BYTEA                       NOT NULL,
+    added_at    TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    UNIQUE (hash),
+    PRIMARY KEY (id)
+);
+
+CREATE TABLE mapping_payload_etherscan (
+    payload_id      INT     NOT NULL REFERENCES etherscan_payload   (id),
+    contract_id     INT     NOT NULL REFERENCES etherscan_contract  (id),
+
+    PRIMARY KEY (payload_id, contract_id)
+);
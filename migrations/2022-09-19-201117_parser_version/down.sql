@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE etherscan_contract DROP COLUMN parser_version;
+ALTER TABLE github_repository DROP COLUMN parser_version;
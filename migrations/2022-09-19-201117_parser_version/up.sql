@@ -0,0 +1,4 @@
+-- Version of the parser (see `parser::PARSER_VERSION`) a source was last scraped with, such that sources can be
+-- re-parsed whenever the parser is upgraded. All sources scraped so far were scraped with the initial version.
+ALTER TABLE etherscan_contract ADD COLUMN parser_version INT NOT NULL DEFAULT 1;
+ALTER TABLE github_repository ADD COLUMN parser_version INT NOT NULL DEFAULT 1;
@@ -0,0 +1,9 @@
+-- Heartbeat of each fetcher / scraper, updated with every loop iteration such that stuck workers can be
+-- identified by an old `last_loop_at` date.
+CREATE TABLE worker_status (
+    name                TEXT        PRIMARY KEY,
+    started_at          TIMESTAMPTZ NOT NULL,
+    last_loop_at        TIMESTAMPTZ NOT NULL,
+    current_item        TEXT,
+    items_processed     BIGINT      NOT NULL DEFAULT 0
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX index__signature_topic0;
+DROP INDEX index__signature_selector;
+ALTER TABLE signature DROP COLUMN topic0;
+ALTER TABLE signature DROP COLUMN selector;
@@ -0,0 +1,13 @@
+-- Dedicated columns for hash lookups, such that the REST API can use an indexed equality comparison rather than
+-- a `hash LIKE '12345678%'` scan. `selector` holds the first 4 bytes of the hash (i.e. the function / error
+-- selector), `topic0` the whole 32 bytes (i.e. the event topic).
+ALTER TABLE signature ADD COLUMN selector BYTEA;
+ALTER TABLE signature ADD COLUMN topic0 BYTEA;
+
+UPDATE signature SET topic0 = decode(hash, 'hex'), selector = substring(decode(hash, 'hex') FROM 1 FOR 4);
+
+ALTER TABLE signature ALTER COLUMN selector SET NOT NULL;
+ALTER TABLE signature ALTER COLUMN topic0 SET NOT NULL;
+
+CREATE INDEX index__signature_selector ON signature (selector);
+CREATE UNIQUE INDEX index__signature_topic0 ON signature (topic0);
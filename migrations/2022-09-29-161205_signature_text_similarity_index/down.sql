@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX index_trgm_gist_ops__signature_text;
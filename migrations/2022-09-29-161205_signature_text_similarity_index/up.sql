@@ -0,0 +1,2 @@
+-- GiST index supporting the ordering by trigram distance (`<->`) of fuzzy text searches
+CREATE INDEX index_trgm_gist_ops__signature_text ON signature USING gist (text gist_trgm_ops);
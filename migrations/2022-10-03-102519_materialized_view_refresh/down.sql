@@ -0,0 +1,21 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE materialized_view_refresh;
+
+CREATE OR REPLACE FUNCTION function_refresh_materialized_views() RETURNS TRIGGER AS $trigger_refresh_materialized_views$
+BEGIN
+	REFRESH MATERIALIZED VIEW view_signature_insert_rate;
+	REFRESH MATERIALIZED VIEW view_signatures_popular_on_github;
+	REFRESH MATERIALIZED VIEW view_signature_kind_distribution;
+	REFRESH MATERIALIZED VIEW view_signature_count_statistics;
+	RETURN NULL;
+END $trigger_refresh_materialized_views$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER trigger_refresh_materialized_views
+	AFTER UPDATE OF last_repository_search ON github_crawler_metadata 
+	FOR EACH STATEMENT 
+	EXECUTE FUNCTION function_refresh_materialized_views();
+
+DROP INDEX index__view_signature_count_statistics;
+DROP INDEX index__view_signature_kind_distribution;
+DROP INDEX index__view_signatures_popular_on_github;
+DROP INDEX index__view_signature_insert_rate;
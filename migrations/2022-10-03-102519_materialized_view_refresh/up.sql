@@ -0,0 +1,18 @@
+-- `REFRESH MATERIALIZED VIEW CONCURRENTLY` requires a unique index on each view, which in turn allows the
+-- views to be read while being refreshed. `view_signature_count_statistics` consists of a single row only,
+-- as such any of its columns is unique.
+CREATE UNIQUE INDEX index__view_signature_insert_rate ON view_signature_insert_rate (date);
+CREATE UNIQUE INDEX index__view_signatures_popular_on_github ON view_signatures_popular_on_github (text);
+CREATE UNIQUE INDEX index__view_signature_kind_distribution ON view_signature_kind_distribution (kind);
+CREATE UNIQUE INDEX index__view_signature_count_statistics ON view_signature_count_statistics (signature_count);
+
+-- The views are refreshed periodically by the `etherface` binary instead, see `refresher.rs`
+DROP TRIGGER trigger_refresh_materialized_views ON github_crawler_metadata;
+DROP FUNCTION function_refresh_materialized_views;
+
+-- Date and duration of the last refresh of each materialized view.
+CREATE TABLE materialized_view_refresh (
+    name                TEXT        PRIMARY KEY,
+    refreshed_at        TIMESTAMPTZ NOT NULL,
+    duration_ms         BIGINT      NOT NULL
+);
@@ -0,0 +1,62 @@
+-- This file should undo anything in `up.sql`
+DROP MATERIALIZED VIEW view_signatures_popular_on_github;
+DROP MATERIALIZED VIEW view_signature_count_statistics;
+
+ALTER TABLE mapping_signature_github RENAME TO mapping_signature_github_partitioned;
+ALTER TABLE mapping_signature_etherscan RENAME TO mapping_signature_etherscan_partitioned;
+ALTER TABLE mapping_signature_fourbyte RENAME TO mapping_signature_fourbyte_partitioned;
+
+CREATE TABLE mapping_signature_github (
+    signature_id    INT                         NOT NULL REFERENCES signature           (id),
+    repository_id   INT                         NOT NULL REFERENCES github_repository   (id),
+    kind            SIGNATURE_KIND              NOT NULL, 
+    added_at        TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (signature_id, repository_id, kind)
+);
+
+CREATE TABLE mapping_signature_etherscan (
+    signature_id    INT                         NOT NULL REFERENCES signature            (id),
+    contract_id     INT                         NOT NULL REFERENCES etherscan_contract   (id),
+    kind            SIGNATURE_KIND              NOT NULL,
+    added_at        TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (signature_id, contract_id, kind)
+);
+
+CREATE TABLE mapping_signature_fourbyte (
+    signature_id    INT                         NOT NULL REFERENCES signature            (id),
+    kind            SIGNATURE_KIND              NOT NULL,
+    added_at        TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (signature_id, kind)
+);
+
+INSERT INTO mapping_signature_github SELECT * FROM mapping_signature_github_partitioned ON CONFLICT DO NOTHING;
+INSERT INTO mapping_signature_etherscan SELECT * FROM mapping_signature_etherscan_partitioned ON CONFLICT DO NOTHING;
+INSERT INTO mapping_signature_fourbyte SELECT * FROM mapping_signature_fourbyte_partitioned ON CONFLICT DO NOTHING;
+
+DROP TABLE mapping_signature_github_partitioned;
+DROP TABLE mapping_signature_etherscan_partitioned;
+DROP TABLE mapping_signature_fourbyte_partitioned;
+DROP FUNCTION create_monthly_partition;
+
+CREATE MATERIALIZED VIEW view_signatures_popular_on_github AS 
+	SELECT signature."text", COUNT(*) FROM signature JOIN mapping_signature_github ON signature.id = mapping_signature_github.signature_id WHERE signature.is_valid IS TRUE GROUP BY 1 ORDER BY 2 DESC LIMIT 100;
+
+CREATE MATERIALIZED VIEW view_signature_count_statistics AS 
+	SELECT 	(SELECT COUNT(*) as signature_count FROM signature WHERE is_valid IS TRUE) 
+				AS signature_count, 
+			(SELECT COUNT(DISTINCT signature_id) AS signature_count_github FROM mapping_signature_github JOIN signature ON mapping_signature_github.signature_id = signature.id WHERE is_valid IS TRUE) 
+				AS signature_count_github,
+			(SELECT COUNT(DISTINCT signature_id) AS signature_count_etherscan FROM mapping_signature_etherscan)
+				AS signature_count_etherscan,
+			(SELECT COUNT(DISTINCT signature_id) AS signature_count_fourbyte FROM mapping_signature_fourbyte) 
+				AS signature_count_fourbyte,
+			(SELECT AVG(added_at_count)::BIGINT FROM (SELECT date_trunc('day', added_at), COUNT(*) AS added_at_count FROM signature WHERE added_at > CURRENT_DATE - 7 GROUP BY 1) AS temp)
+				AS average_daily_signature_insert_rate_last_week,
+			(SELECT AVG(added_at_count)::BIGINT FROM (SELECT date_trunc('day', added_at), COUNT(*) AS added_at_count FROM signature WHERE added_at < CURRENT_DATE - 7 AND added_at > CURRENT_DATE - 14 GROUP BY 1) AS temp)
+				AS average_daily_signature_insert_rate_week_before_last;
+
+CREATE UNIQUE INDEX index__view_signatures_popular_on_github ON view_signatures_popular_on_github (text);
+CREATE UNIQUE INDEX index__view_signature_count_statistics ON view_signature_count_statistics (signature_count);
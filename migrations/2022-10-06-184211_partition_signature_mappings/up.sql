@@ -0,0 +1,102 @@
+-- Partitions the `mapping_signature_*` tables by month of their `added_at` column, keeping insert performance
+-- and vacuum times of the (ever growing) tables manageable. As the partition key has to be part of the
+-- primary key, uniqueness of the previous primary key is ensured by the table handlers instead.
+
+-- Both views depend on the mapping tables, as such they're recreated once the tables are partitioned
+DROP MATERIALIZED VIEW view_signatures_popular_on_github;
+DROP MATERIALIZED VIEW view_signature_count_statistics;
+
+-- Creates the partition of `parent` for the month of `month` if it doesn't exist yet. Rows of that month
+-- which were inserted into the default partition in the meantime are moved into the new partition, as
+-- otherwise it couldn't be attached.
+CREATE OR REPLACE FUNCTION create_monthly_partition(parent TEXT, month DATE) RETURNS VOID AS $$
+DECLARE
+	range_start DATE := date_trunc('month', month);
+	range_end DATE := date_trunc('month', month) + INTERVAL '1 month';
+	partition_name TEXT := format('%s_%s', parent, to_char(month, 'YYYY_MM'));
+BEGIN
+	IF to_regclass(partition_name) IS NOT NULL THEN
+		RETURN;
+	END IF;
+
+	EXECUTE format('CREATE TABLE %I (LIKE %I INCLUDING DEFAULTS INCLUDING CONSTRAINTS)', partition_name, parent);
+	EXECUTE format(
+		'WITH moved AS (DELETE FROM %I WHERE added_at >= %L AND added_at < %L RETURNING *) INSERT INTO %I SELECT * FROM moved',
+		parent || '_default', range_start, range_end, partition_name
+	);
+	EXECUTE format('ALTER TABLE %I ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)', parent, partition_name, range_start, range_end);
+END $$ LANGUAGE plpgsql;
+
+ALTER TABLE mapping_signature_github RENAME TO mapping_signature_github_unpartitioned;
+ALTER TABLE mapping_signature_etherscan RENAME TO mapping_signature_etherscan_unpartitioned;
+ALTER TABLE mapping_signature_fourbyte RENAME TO mapping_signature_fourbyte_unpartitioned;
+
+CREATE TABLE mapping_signature_github (
+    signature_id    INT                         NOT NULL REFERENCES signature           (id),
+    repository_id   INT                         NOT NULL REFERENCES github_repository   (id),
+    kind            SIGNATURE_KIND              NOT NULL, 
+    added_at        TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (signature_id, repository_id, kind, added_at)
+) PARTITION BY RANGE (added_at);
+
+CREATE TABLE mapping_signature_etherscan (
+    signature_id    INT                         NOT NULL REFERENCES signature            (id),
+    contract_id     INT                         NOT NULL REFERENCES etherscan_contract   (id),
+    kind            SIGNATURE_KIND              NOT NULL,
+    added_at        TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (signature_id, contract_id, kind, added_at)
+) PARTITION BY RANGE (added_at);
+
+CREATE TABLE mapping_signature_fourbyte (
+    signature_id    INT                         NOT NULL REFERENCES signature            (id),
+    kind            SIGNATURE_KIND              NOT NULL,
+    added_at        TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (signature_id, kind, added_at)
+) PARTITION BY RANGE (added_at);
+
+-- Catches rows whose monthly partition wasn't created (yet), see `create_monthly_partition`
+CREATE TABLE mapping_signature_github_default PARTITION OF mapping_signature_github DEFAULT;
+CREATE TABLE mapping_signature_etherscan_default PARTITION OF mapping_signature_etherscan DEFAULT;
+CREATE TABLE mapping_signature_fourbyte_default PARTITION OF mapping_signature_fourbyte DEFAULT;
+
+-- Creates the partitions of all months from the oldest row up to the current month and moves the rows
+DO $$
+DECLARE
+	parent TEXT;
+	month DATE;
+BEGIN
+	FOREACH parent IN ARRAY ARRAY['mapping_signature_github', 'mapping_signature_etherscan', 'mapping_signature_fourbyte'] LOOP
+		FOR month IN EXECUTE format(
+			'SELECT generate_series(date_trunc(''month'', COALESCE((SELECT MIN(added_at) FROM %I), NOW())), date_trunc(''month'', NOW()), INTERVAL ''1 month'')::DATE',
+			parent || '_unpartitioned'
+		) LOOP
+			PERFORM create_monthly_partition(parent, month);
+		END LOOP;
+
+		EXECUTE format('INSERT INTO %I SELECT * FROM %I', parent, parent || '_unpartitioned');
+		EXECUTE format('DROP TABLE %I', parent || '_unpartitioned');
+	END LOOP;
+END $$;
+
+CREATE MATERIALIZED VIEW view_signatures_popular_on_github AS 
+	SELECT signature."text", COUNT(*) FROM signature JOIN mapping_signature_github ON signature.id = mapping_signature_github.signature_id WHERE signature.is_valid IS TRUE GROUP BY 1 ORDER BY 2 DESC LIMIT 100;
+
+CREATE MATERIALIZED VIEW view_signature_count_statistics AS 
+	SELECT 	(SELECT COUNT(*) as signature_count FROM signature WHERE is_valid IS TRUE) 
+				AS signature_count, 
+			(SELECT COUNT(DISTINCT signature_id) AS signature_count_github FROM mapping_signature_github JOIN signature ON mapping_signature_github.signature_id = signature.id WHERE is_valid IS TRUE) 
+				AS signature_count_github,
+			(SELECT COUNT(DISTINCT signature_id) AS signature_count_etherscan FROM mapping_signature_etherscan)
+				AS signature_count_etherscan,
+			(SELECT COUNT(DISTINCT signature_id) AS signature_count_fourbyte FROM mapping_signature_fourbyte) 
+				AS signature_count_fourbyte,
+			(SELECT AVG(added_at_count)::BIGINT FROM (SELECT date_trunc('day', added_at), COUNT(*) AS added_at_count FROM signature WHERE added_at > CURRENT_DATE - 7 GROUP BY 1) AS temp)
+				AS average_daily_signature_insert_rate_last_week,
+			(SELECT AVG(added_at_count)::BIGINT FROM (SELECT date_trunc('day', added_at), COUNT(*) AS added_at_count FROM signature WHERE added_at < CURRENT_DATE - 7 AND added_at > CURRENT_DATE - 14 GROUP BY 1) AS temp)
+				AS average_daily_signature_insert_rate_week_before_last;
+
+CREATE UNIQUE INDEX index__view_signatures_popular_on_github ON view_signatures_popular_on_github (text);
+CREATE UNIQUE INDEX index__view_signature_count_statistics ON view_signature_count_statistics (signature_count);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE mapping_signature_github DROP COLUMN last_seen_at;
+ALTER TABLE mapping_signature_etherscan DROP COLUMN last_seen_at;
+ALTER TABLE mapping_signature_fourbyte DROP COLUMN last_seen_at;
@@ -0,0 +1,14 @@
+-- `added_at` of a mapping is the date a signature was first found in a source, `last_seen_at` the date it
+-- was last found there (e.g. when re-scraping a repository), such that signatures still present in a source
+-- can be distinguished from ones which only existed in the past.
+ALTER TABLE mapping_signature_github ADD COLUMN last_seen_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE mapping_signature_etherscan ADD COLUMN last_seen_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE mapping_signature_fourbyte ADD COLUMN last_seen_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE mapping_signature_github SET last_seen_at = added_at;
+UPDATE mapping_signature_etherscan SET last_seen_at = added_at;
+UPDATE mapping_signature_fourbyte SET last_seen_at = added_at;
+
+ALTER TABLE mapping_signature_github ALTER COLUMN last_seen_at SET NOT NULL;
+ALTER TABLE mapping_signature_etherscan ALTER COLUMN last_seen_at SET NOT NULL;
+ALTER TABLE mapping_signature_fourbyte ALTER COLUMN last_seen_at SET NOT NULL;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE signature DROP COLUMN last_called_at;
+ALTER TABLE signature DROP COLUMN usage_count;
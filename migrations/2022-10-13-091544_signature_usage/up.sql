@@ -0,0 +1,4 @@
+-- Number of on-chain calls (functions, errors) or emitted logs (events) of a signature, imported from an
+-- external counts file (see `etherface usage`) and used to rank signatures sharing the same selector.
+ALTER TABLE signature ADD COLUMN usage_count BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE signature ADD COLUMN last_called_at TIMESTAMP WITH TIME ZONE;
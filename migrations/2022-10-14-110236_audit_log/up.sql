@@ -0,0 +1,13 @@
+CREATE TYPE audit_action AS ENUM ('denylist_add', 'denylist_remove', 'requeue', 'moderation', 'token_change');
+
+-- Administrative mutations, written by the admin endpoints and CLI commands alongside the mutation itself.
+CREATE TABLE audit_log (
+    id          SERIAL                      PRIMARY KEY,
+    actor       TEXT                        NOT NULL,
+    action      AUDIT_ACTION                NOT NULL,
+    target      TEXT,
+    details     TEXT,
+    created_at  TIMESTAMP WITH TIME ZONE    NOT NULL
+);
+
+CREATE INDEX index__audit_log_created_at ON audit_log (created_at);
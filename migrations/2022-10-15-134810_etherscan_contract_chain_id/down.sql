@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE etherscan_contract DROP CONSTRAINT etherscan_contract_chain_id_address_key;
+ALTER TABLE etherscan_contract ADD CONSTRAINT etherscan_contract_address_key UNIQUE (address);
+
+ALTER TABLE etherscan_contract DROP COLUMN chain_id;
@@ -0,0 +1,6 @@
+-- Contracts are identified by their address per chain, such that Etherscan-family explorers of other chains
+-- can be scraped as well. All contracts scraped so far are deployed on Ethereum mainnet.
+ALTER TABLE etherscan_contract ADD COLUMN chain_id INT NOT NULL DEFAULT 1;
+
+ALTER TABLE etherscan_contract DROP CONSTRAINT etherscan_contract_address_key;
+ALTER TABLE etherscan_contract ADD CONSTRAINT etherscan_contract_chain_id_address_key UNIQUE (chain_id, address);
@@ -0,0 +1,11 @@
+-- Stargazer graph of GitHub users and the repositories they starred, recorded while crawling. Used to find
+-- users interested in Solidity and repositories commonly starred together.
+CREATE TABLE mapping_stargazer (
+    user_id         INT                         NOT NULL REFERENCES github_user(id),
+    repository_id   INT                         NOT NULL REFERENCES github_repository(id),
+    added_at        TIMESTAMP WITH TIME ZONE    NOT NULL,
+
+    PRIMARY KEY (user_id, repository_id)
+);
+
+CREATE INDEX index__mapping_stargazer_repository_id ON mapping_stargazer (repository_id);
@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX index__github_repository_license;
+DROP INDEX index__github_repository_topics;
+
+ALTER TABLE github_repository DROP COLUMN license;
+ALTER TABLE github_repository DROP COLUMN topics;
+ALTER TABLE github_repository DROP COLUMN description;
@@ -0,0 +1,7 @@
+-- Repository metadata as returned by GitHub, allowing to filter sources by their license or topics
+ALTER TABLE github_repository ADD COLUMN description TEXT;
+ALTER TABLE github_repository ADD COLUMN topics TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE github_repository ADD COLUMN license TEXT;
+
+CREATE INDEX index__github_repository_topics ON github_repository USING GIN (topics);
+CREATE INDEX index__github_repository_license ON github_repository (license);
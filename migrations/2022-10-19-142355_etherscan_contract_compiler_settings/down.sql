@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE etherscan_contract DROP COLUMN license;
+ALTER TABLE etherscan_contract DROP COLUMN evm_version;
+ALTER TABLE etherscan_contract DROP COLUMN optimization_runs;
+ALTER TABLE etherscan_contract DROP COLUMN optimization_used;
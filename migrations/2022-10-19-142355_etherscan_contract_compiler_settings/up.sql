@@ -0,0 +1,5 @@
+-- Compiler settings as returned by Etherscan's `getsourcecode` endpoint, NULL if not fetched (yet)
+ALTER TABLE etherscan_contract ADD COLUMN optimization_used BOOLEAN;
+ALTER TABLE etherscan_contract ADD COLUMN optimization_runs INT;
+ALTER TABLE etherscan_contract ADD COLUMN evm_version TEXT;
+ALTER TABLE etherscan_contract ADD COLUMN license TEXT;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP MATERIALIZED VIEW view_signature_insert_rate_by_source;
+DROP MATERIALIZED VIEW view_repositories_top_unique_signatures;
+DROP MATERIALIZED VIEW view_signatures_per_contract_distribution;
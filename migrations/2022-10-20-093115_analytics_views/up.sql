@@ -0,0 +1,27 @@
+-- Number of signatures first found within each source per day, i.e. the daily insert rate of
+-- `view_signature_insert_rate` broken down by source.
+CREATE MATERIALIZED VIEW view_signature_insert_rate_by_source AS 
+	SELECT date, source, SUM(count)::BIGINT AS count FROM (
+		SELECT DATE(date_trunc('day', added_at)) AS date, 'github' AS source, COUNT(DISTINCT signature_id) AS count FROM mapping_signature_github WHERE added_at > (CURRENT_DATE - INTERVAL '14 days') GROUP BY 1
+		UNION ALL
+		SELECT DATE(date_trunc('day', added_at)) AS date, 'etherscan' AS source, COUNT(DISTINCT signature_id) AS count FROM mapping_signature_etherscan WHERE added_at > (CURRENT_DATE - INTERVAL '14 days') GROUP BY 1
+		UNION ALL
+		SELECT DATE(date_trunc('day', added_at)) AS date, 'fourbyte' AS source, COUNT(DISTINCT signature_id) AS count FROM mapping_signature_fourbyte WHERE added_at > (CURRENT_DATE - INTERVAL '14 days') GROUP BY 1
+	) AS temp GROUP BY 1, 2 ORDER BY 1 ASC, 2 ASC;
+
+-- Repositories contributing the most valid signatures not found in any other repository.
+CREATE MATERIALIZED VIEW view_repositories_top_unique_signatures AS 
+	SELECT github_repository.id AS repository_id, github_repository.name, github_repository.html_url, COUNT(*) AS count FROM (
+		SELECT mapping_signature_github.signature_id, MIN(mapping_signature_github.repository_id) AS repository_id FROM mapping_signature_github JOIN signature ON mapping_signature_github.signature_id = signature.id WHERE signature.is_valid IS TRUE GROUP BY 1 HAVING COUNT(DISTINCT mapping_signature_github.repository_id) = 1
+	) AS temp JOIN github_repository ON temp.repository_id = github_repository.id GROUP BY 1, 2, 3 ORDER BY 4 DESC LIMIT 100;
+
+-- Number of Etherscan contracts by their number of distinct signatures, grouped into buckets of 10 where
+-- e.g. bucket 10 holds contracts with 10 to 19 signatures; bucket 200 holds all contracts with 200 or more.
+CREATE MATERIALIZED VIEW view_signatures_per_contract_distribution AS 
+	SELECT LEAST(signature_count / 10 * 10, 200)::INT AS bucket, COUNT(*) AS count FROM (
+		SELECT contract_id, COUNT(DISTINCT signature_id) AS signature_count FROM mapping_signature_etherscan GROUP BY 1
+	) AS temp GROUP BY 1 ORDER BY 1 ASC;
+
+CREATE UNIQUE INDEX index__view_signature_insert_rate_by_source ON view_signature_insert_rate_by_source (date, source);
+CREATE UNIQUE INDEX index__view_repositories_top_unique_signatures ON view_repositories_top_unique_signatures (repository_id);
+CREATE UNIQUE INDEX index__view_signatures_per_contract_distribution ON view_signatures_per_contract_distribution (bucket);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER trigger_update_selector_collision ON signature;
+DROP FUNCTION function_update_selector_collision;
+DROP FUNCTION function_refresh_selector_collision;
+DROP TABLE selector_collision;
@@ -0,0 +1,50 @@
+-- 4-byte selectors shared by multiple signatures (e.g. `transfer(address,uint256)` and
+-- `many_msg_babbage(bytes1)`), such that consumers can detect ambiguous decodings. Kept up to date by a
+-- trigger on the `signature` table rather than a periodically refreshed view, as collisions are rare and
+-- each change only affects the selectors of the inserted, updated or deleted row.
+CREATE TABLE selector_collision (
+    selector            TEXT        PRIMARY KEY,
+    signature_count     INT         NOT NULL,
+    updated_at          TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX index__selector_collision_signature_count ON selector_collision (signature_count);
+
+INSERT INTO selector_collision (selector, signature_count, updated_at)
+	SELECT encode(selector, 'hex'), COUNT(*), NOW() FROM signature GROUP BY selector HAVING COUNT(*) > 1;
+
+-- Re-counts the signatures of the given selector, (re-)recording it as a collision if shared by more than one
+-- signature and removing it otherwise.
+CREATE OR REPLACE FUNCTION function_refresh_selector_collision(entity_selector BYTEA) RETURNS VOID AS $$
+DECLARE
+	entity_count INT;
+BEGIN
+	SELECT COUNT(*) INTO entity_count FROM signature WHERE selector = entity_selector;
+
+	IF entity_count > 1 THEN
+		INSERT INTO selector_collision (selector, signature_count, updated_at)
+			VALUES (encode(entity_selector, 'hex'), entity_count, NOW())
+			ON CONFLICT (selector) DO UPDATE SET signature_count = EXCLUDED.signature_count, updated_at = EXCLUDED.updated_at
+			WHERE selector_collision.signature_count <> EXCLUDED.signature_count;
+	ELSE
+		DELETE FROM selector_collision WHERE selector = encode(entity_selector, 'hex');
+	END IF;
+END $$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION function_update_selector_collision() RETURNS TRIGGER AS $trigger_update_selector_collision$
+BEGIN
+	IF TG_OP IN ('UPDATE', 'DELETE') THEN
+		PERFORM function_refresh_selector_collision(OLD.selector);
+	END IF;
+
+	IF TG_OP IN ('INSERT', 'UPDATE') THEN
+		PERFORM function_refresh_selector_collision(NEW.selector);
+	END IF;
+
+	RETURN NULL;
+END $trigger_update_selector_collision$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER trigger_update_selector_collision
+	AFTER INSERT OR DELETE OR UPDATE OF selector ON signature
+	FOR EACH ROW
+	EXECUTE FUNCTION function_update_selector_collision();
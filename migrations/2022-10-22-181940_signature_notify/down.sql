@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER trigger_notify_signature_found ON mapping_signature_kind;
+DROP FUNCTION function_notify_signature_found;
@@ -0,0 +1,14 @@
+-- Notifies listeners of the `signature_found` channel whenever a signature is found as a kind for the first
+-- time, i.e. a row is inserted into `mapping_signature_kind`, see `etherface-lib/src/database/listener.rs`.
+-- Only the ids are sent as payloads are limited to 8000 bytes, whereas signature texts aren't. Notifications
+-- are delivered once the inserting transaction commits.
+CREATE OR REPLACE FUNCTION function_notify_signature_found() RETURNS TRIGGER AS $trigger_notify_signature_found$
+BEGIN
+	PERFORM pg_notify('signature_found', json_build_object('signature_id', NEW.signature_id, 'kind', NEW.kind)::TEXT);
+	RETURN NULL;
+END $trigger_notify_signature_found$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER trigger_notify_signature_found
+	AFTER INSERT ON mapping_signature_kind
+	FOR EACH ROW
+	EXECUTE FUNCTION function_notify_signature_found();
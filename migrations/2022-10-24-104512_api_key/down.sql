@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE api_key;
+
+-- Enum values can't be dropped, as such `audit_action` is re-created without them
+DELETE FROM audit_log WHERE action IN ('api_key_issue', 'api_key_revoke');
+ALTER TYPE audit_action RENAME TO audit_action_old;
+CREATE TYPE audit_action AS ENUM ('denylist_add', 'denylist_remove', 'requeue', 'moderation', 'token_change');
+ALTER TABLE audit_log ALTER COLUMN action TYPE audit_action USING action::TEXT::audit_action;
+DROP TYPE audit_action_old;
@@ -0,0 +1,16 @@
+-- API keys of registered REST API consumers, granting them their own (usually higher) rate limit. Only the
+-- SHA3-256 hash of each key is stored, the key itself is shown once when issued.
+CREATE TABLE api_key (
+    id                      SERIAL      PRIMARY KEY,
+    key_hash                TEXT        NOT NULL UNIQUE,
+    name                    TEXT        NOT NULL,
+    rate_limit_per_minute   INT         NOT NULL,
+    rate_limit_burst        INT         NOT NULL,
+    request_count           BIGINT      NOT NULL DEFAULT 0,
+    created_at              TIMESTAMPTZ NOT NULL,
+    last_used_at            TIMESTAMPTZ,
+    revoked_at              TIMESTAMPTZ
+);
+
+ALTER TYPE audit_action ADD VALUE 'api_key_issue';
+ALTER TYPE audit_action ADD VALUE 'api_key_revoke';
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX index_lower__etherscan_contract_address;
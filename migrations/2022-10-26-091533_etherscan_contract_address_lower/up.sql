@@ -0,0 +1,3 @@
+-- Etherscan lists contracts by their checksummed (i.e. mixed case) address whereas clients usually pass them
+-- in lowercase, hence addresses are looked up case-insensitively by the REST API.
+CREATE INDEX index_lower__etherscan_contract_address ON etherscan_contract (LOWER(address));
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP MATERIALIZED VIEW view_etherscan_chain_distribution;
+DROP MATERIALIZED VIEW view_etherscan_compiler_version_distribution;
+
+DROP MATERIALIZED VIEW view_signature_kind_distribution;
+CREATE MATERIALIZED VIEW view_signature_kind_distribution AS 
+	SELECT kind, COUNT(*) FROM mapping_signature_kind GROUP BY 1;
+CREATE UNIQUE INDEX index__view_signature_kind_distribution ON view_signature_kind_distribution (kind);
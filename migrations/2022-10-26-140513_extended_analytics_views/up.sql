@@ -0,0 +1,18 @@
+-- Number of signatures per kind, including all kinds of `SignatureKind` without any signature (e.g. `receive`,
+-- which isn't even part of the `signature_kind` type yet) such that consumers get a stable set of rows.
+DROP MATERIALIZED VIEW view_signature_kind_distribution;
+CREATE MATERIALIZED VIEW view_signature_kind_distribution AS 
+	SELECT kinds.kind, COUNT(mapping_signature_kind.signature_id) AS count FROM (VALUES ('function'), ('event'), ('error'), ('constructor'), ('fallback'), ('receive')) AS kinds (kind) LEFT JOIN mapping_signature_kind ON mapping_signature_kind.kind::TEXT = kinds.kind GROUP BY 1;
+
+-- Number of scraped Etherscan contracts by compiler and version, the latter stripped of its commit hash
+-- (e.g. `v0.8.17+commit.8df45f5f` becomes `0.8.17`).
+CREATE MATERIALIZED VIEW view_etherscan_compiler_version_distribution AS 
+	SELECT compiler, COALESCE(substring(compiler_version FROM '\d+\.\d+\.\d+'), compiler_version) AS version, COUNT(*) AS count FROM etherscan_contract WHERE scraped_at IS NOT NULL GROUP BY 1, 2 ORDER BY 3 DESC;
+
+-- Number of Etherscan contracts and their distinct signatures per chain.
+CREATE MATERIALIZED VIEW view_etherscan_chain_distribution AS 
+	SELECT etherscan_contract.chain_id, COUNT(DISTINCT etherscan_contract.id) AS contract_count, COUNT(DISTINCT mapping_signature_etherscan.signature_id) AS signature_count FROM etherscan_contract LEFT JOIN mapping_signature_etherscan ON etherscan_contract.id = mapping_signature_etherscan.contract_id GROUP BY 1 ORDER BY 1 ASC;
+
+CREATE UNIQUE INDEX index__view_signature_kind_distribution ON view_signature_kind_distribution (kind);
+CREATE UNIQUE INDEX index__view_etherscan_compiler_version_distribution ON view_etherscan_compiler_version_distribution (compiler, version);
+CREATE UNIQUE INDEX index__view_etherscan_chain_distribution ON view_etherscan_chain_distribution (chain_id);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE mapping_signature_submission;
+DROP TABLE signature_submission;
+DROP TYPE submission_status;
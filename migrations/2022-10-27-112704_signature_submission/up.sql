@@ -0,0 +1,32 @@
+CREATE TYPE submission_status AS ENUM ('pending', 'approved', 'rejected');
+
+-- Signatures submitted by registered REST API consumers (see `api_key`), only inserted into the `signature`
+-- table once approved by an administrator. Texts are stored in their canonical form, hence re-submissions of
+-- the same signature by the same consumer are merged.
+CREATE TABLE signature_submission (
+    id              SERIAL              PRIMARY KEY,
+    api_key_id      INT                 NOT NULL REFERENCES api_key (id),
+    text            TEXT                NOT NULL,
+    hash            TEXT                NOT NULL,
+    kind            SIGNATURE_KIND      NOT NULL,
+    is_valid        BOOLEAN             NOT NULL,
+    status          SUBMISSION_STATUS   NOT NULL DEFAULT 'pending',
+    submitted_at    TIMESTAMPTZ         NOT NULL,
+    moderated_at    TIMESTAMPTZ,
+    moderated_by    TEXT,
+
+    UNIQUE (api_key_id, text, kind)
+);
+
+CREATE INDEX index__signature_submission_status ON signature_submission (status, id);
+
+-- Same as the other `mapping_signature_*` tables, but for approved submissions. Not partitioned as the number
+-- of submissions is bound by moderation.
+CREATE TABLE mapping_signature_submission (
+    signature_id    INT                 NOT NULL REFERENCES signature               (id),
+    submission_id   INT                 NOT NULL REFERENCES signature_submission    (id),
+    kind            SIGNATURE_KIND      NOT NULL,
+    added_at        TIMESTAMPTZ         NOT NULL,
+
+    PRIMARY KEY (signature_id, submission_id, kind)
+);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE signature_flag;
+DROP TYPE flag_status;
+DROP TYPE flag_reason;
+DROP TYPE signature_source;
@@ -0,0 +1,27 @@
+CREATE TYPE signature_source AS ENUM ('github', 'etherscan', 'fourbyte');
+CREATE TYPE flag_reason AS ENUM ('wrong', 'spam');
+CREATE TYPE flag_status AS ENUM ('pending', 'invalidated', 'removed', 'dismissed');
+
+-- Complaints about signatures or their source mappings, e.g. a signature which doesn't actually occur in a
+-- repository or a spammed signature colliding with a popular selector, moderated by administrators. Flags
+-- target all mappings of the signature with the given kind if `source` is NULL, otherwise the ones of the
+-- given source only, where `source_id` references the repository respectively contract. Repeated flags of a
+-- pending flags target and reason are merged by incrementing `flag_count`.
+CREATE TABLE signature_flag (
+    id              SERIAL              PRIMARY KEY,
+    signature_id    INT                 NOT NULL REFERENCES signature (id),
+    kind            SIGNATURE_KIND      NOT NULL,
+    source          SIGNATURE_SOURCE,
+    source_id       INT,
+    reason          FLAG_REASON         NOT NULL,
+    comment         TEXT,
+    api_key_id      INT                 REFERENCES api_key (id),
+    flag_count      INT                 NOT NULL DEFAULT 1,
+    status          FLAG_STATUS         NOT NULL DEFAULT 'pending',
+    flagged_at      TIMESTAMPTZ         NOT NULL,
+    moderated_at    TIMESTAMPTZ,
+    moderated_by    TEXT
+);
+
+CREATE INDEX index__signature_flag_status ON signature_flag (status, id);
+CREATE INDEX index__signature_flag_signature_id ON signature_flag (signature_id);
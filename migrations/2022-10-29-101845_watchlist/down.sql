@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER trigger_resolve_watchlist_entry ON mapping_signature_kind;
+DROP FUNCTION function_resolve_watchlist_entry;
+DROP TABLE watchlist_entry;
@@ -0,0 +1,45 @@
+-- Selectors and event topics watched by registered REST API consumers (see `api_key`), typically ones unknown
+-- at the time. Hashes are stored lowercase without `0x` prefix, being either 8 (function and error selectors)
+-- or 64 (event topics) characters long. Entries are resolved by the trigger below, after which the consumers
+-- webhook (if any) is called by the watchlist notifier of the `etherface` binary.
+CREATE TABLE watchlist_entry (
+    id                  SERIAL          PRIMARY KEY,
+    api_key_id          INT             NOT NULL REFERENCES api_key (id),
+    hash                TEXT            NOT NULL,
+    webhook_url         TEXT,
+    created_at          TIMESTAMPTZ     NOT NULL,
+    resolved_at         TIMESTAMPTZ,
+    signature_id        INT             REFERENCES signature (id) ON DELETE SET NULL,
+    text                TEXT,
+    kind                SIGNATURE_KIND,
+    notified_at         TIMESTAMPTZ,
+    notify_attempts     INT             NOT NULL DEFAULT 0,
+    next_notify_at      TIMESTAMPTZ,
+    last_notify_error   TEXT,
+
+    UNIQUE (api_key_id, hash)
+);
+
+CREATE INDEX index__watchlist_entry_unresolved ON watchlist_entry (hash) WHERE resolved_at IS NULL;
+CREATE INDEX index__watchlist_entry_unnotified ON watchlist_entry (next_notify_at)
+    WHERE resolved_at IS NOT NULL AND notified_at IS NULL AND webhook_url IS NOT NULL;
+
+-- Resolves all unresolved entries matching a signature whenever it's found as a kind for the first time, i.e.
+-- a row is inserted into `mapping_signature_kind` (see `signature_notify`), such that entries are resolved no
+-- matter which fetcher found the signature. Selectors are resolved by functions and errors, topics by events.
+CREATE OR REPLACE FUNCTION function_resolve_watchlist_entry() RETURNS TRIGGER AS $trigger_resolve_watchlist_entry$
+BEGIN
+	UPDATE watchlist_entry
+	SET resolved_at = NOW(), signature_id = signature.id, text = signature.text, kind = NEW.kind, next_notify_at = NOW()
+	FROM signature
+	WHERE signature.id = NEW.signature_id AND watchlist_entry.resolved_at IS NULL AND (
+		(NEW.kind = 'event' AND watchlist_entry.hash = signature.hash)
+		OR (NEW.kind IN ('function', 'error') AND watchlist_entry.hash = left(signature.hash, 8))
+	);
+	RETURN NULL;
+END $trigger_resolve_watchlist_entry$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER trigger_resolve_watchlist_entry
+	AFTER INSERT ON mapping_signature_kind
+	FOR EACH ROW
+	EXECUTE FUNCTION function_resolve_watchlist_entry();
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER trigger_queue_webhook_delivery ON mapping_signature_kind;
+DROP FUNCTION function_queue_webhook_delivery;
+DROP TABLE webhook_delivery;
+DROP TABLE webhook;
@@ -0,0 +1,55 @@
+-- Webhooks registered by REST API consumers (see `api_key`) to mirror newly found signatures, optionally
+-- filtered by kind, text prefix and source. Every delivery is signed with the webhooks `secret` (see
+-- `etherface_lib::api::webhook::sign`), which is returned once on registration. Webhooks failing too often in
+-- a row are disabled by the webhook dispatcher of the `etherface` binary.
+CREATE TABLE webhook (
+    id                  SERIAL              PRIMARY KEY,
+    api_key_id          INT                 NOT NULL REFERENCES api_key (id),
+    url                 TEXT                NOT NULL,
+    secret              TEXT                NOT NULL,
+    kind                SIGNATURE_KIND,
+    prefix              TEXT,
+    source              SIGNATURE_SOURCE,
+    created_at          TIMESTAMPTZ         NOT NULL,
+    delivered_at        TIMESTAMPTZ,
+    failure_count       INT                 NOT NULL DEFAULT 0,
+    next_delivery_at    TIMESTAMPTZ         NOT NULL DEFAULT NOW(),
+    last_error          TEXT,
+    disabled_at         TIMESTAMPTZ
+);
+
+CREATE INDEX index__webhook_api_key_id ON webhook (api_key_id);
+
+-- Signatures pending to be delivered to a webhook, removed once delivered.
+CREATE TABLE webhook_delivery (
+    id                  BIGSERIAL           PRIMARY KEY,
+    webhook_id          INT                 NOT NULL REFERENCES webhook (id) ON DELETE CASCADE,
+    signature_id        INT                 NOT NULL REFERENCES signature (id) ON DELETE CASCADE,
+    kind                SIGNATURE_KIND      NOT NULL,
+    created_at          TIMESTAMPTZ         NOT NULL
+);
+
+CREATE INDEX index__webhook_delivery_webhook_id ON webhook_delivery (webhook_id, id);
+CREATE INDEX index__webhook_delivery_signature_id ON webhook_delivery (signature_id);
+
+-- Queues a delivery to every enabled webhook whose kind and prefix filter match a signature whenever it's
+-- found as a kind for the first time (see `signature_notify`), no matter which fetcher found it. Invalid
+-- signatures are skipped just like within the signature stream. The source filter is applied by the
+-- dispatcher, as the source mappings may be inserted after the kind within the fetchers transaction.
+CREATE OR REPLACE FUNCTION function_queue_webhook_delivery() RETURNS TRIGGER AS $trigger_queue_webhook_delivery$
+BEGIN
+	INSERT INTO webhook_delivery (webhook_id, signature_id, kind, created_at)
+	SELECT webhook.id, signature.id, NEW.kind, NOW()
+	FROM webhook
+	JOIN api_key ON api_key.id = webhook.api_key_id
+	JOIN signature ON signature.id = NEW.signature_id
+	WHERE webhook.disabled_at IS NULL AND api_key.revoked_at IS NULL AND signature.is_valid
+		AND (webhook.kind IS NULL OR webhook.kind = NEW.kind)
+		AND (webhook.prefix IS NULL OR starts_with(signature.text, webhook.prefix));
+	RETURN NULL;
+END $trigger_queue_webhook_delivery$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE TRIGGER trigger_queue_webhook_delivery
+	AFTER INSERT ON mapping_signature_kind
+	FOR EACH ROW
+	EXECUTE FUNCTION function_queue_webhook_delivery();
@@ -0,0 +1,21 @@
+-- Replaces the trigger maintained `selector_collision` table, which counted events and invalid signatures as
+-- well and whose per-row count-then-upsert raced between concurrent inserts of the same selector. Collisions
+-- are instead computed from the valid function and error signatures whenever the materialized views are
+-- refreshed.
+DROP TRIGGER trigger_update_selector_collision ON signature;
+DROP FUNCTION function_update_selector_collision;
+DROP FUNCTION function_refresh_selector_collision;
+DROP TABLE selector_collision;
+
+-- 4-byte selectors shared by multiple valid function or error signatures, such that consumers can detect
+-- ambiguous decodings.
+CREATE MATERIALIZED VIEW view_selector_collision AS
+	SELECT encode(signature.selector, 'hex') AS selector, COUNT(*)::INT AS signature_count, MAX(signature.added_at) AS last_added_at
+	FROM signature
+	WHERE signature.is_valid IS TRUE AND EXISTS (
+		SELECT 1 FROM mapping_signature_kind WHERE mapping_signature_kind.signature_id = signature.id AND mapping_signature_kind.kind IN ('function', 'error')
+	)
+	GROUP BY signature.selector HAVING COUNT(*) > 1;
+
+CREATE UNIQUE INDEX index__view_selector_collision ON view_selector_collision (selector);
+CREATE INDEX index__view_selector_collision_signature_count ON view_selector_collision (signature_count);
@@ -0,0 +1,29 @@
+-- Flags of the same target and reason raised concurrently could both be inserted as pending, hence such
+-- duplicates are merged into the oldest one before ensuring each target and reason is pending at most once.
+UPDATE signature_flag AS f
+SET flag_count = merged.flag_count
+FROM (
+    SELECT MIN(id) AS id, SUM(flag_count) AS flag_count
+    FROM signature_flag
+    WHERE status = 'pending'
+    GROUP BY signature_id, kind, source, source_id, reason
+    HAVING COUNT(*) > 1
+) AS merged
+WHERE f.id = merged.id;
+
+DELETE FROM signature_flag AS f
+USING signature_flag AS g
+WHERE f.status = 'pending'
+    AND g.status = 'pending'
+    AND f.signature_id = g.signature_id
+    AND f.kind = g.kind
+    AND f.source IS NOT DISTINCT FROM g.source
+    AND f.source_id IS NOT DISTINCT FROM g.source_id
+    AND f.reason = g.reason
+    AND f.id > g.id;
+
+-- `source` and `source_id` are NULL for flags targeting all sources, which are duplicates all the same
+CREATE UNIQUE INDEX index__signature_flag_pending
+    ON signature_flag (signature_id, kind, source, source_id, reason)
+    NULLS NOT DISTINCT
+    WHERE status = 'pending';
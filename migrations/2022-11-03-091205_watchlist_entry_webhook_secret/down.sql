@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE watchlist_entry DROP COLUMN webhook_secret;
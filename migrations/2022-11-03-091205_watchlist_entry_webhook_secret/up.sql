@@ -0,0 +1,14 @@
+-- Calls to watchlist webhooks are signed just like webhook deliveries (see `webhook`), with a secret returned
+-- whenever the entry is added. Existing entries get a random secret, returned by adding them again.
+ALTER TABLE watchlist_entry ADD COLUMN webhook_secret TEXT;
+
+-- Same format as `etherface_lib::api::webhook::generate_secret`, i.e. 32 random hex encoded bytes
+UPDATE watchlist_entry
+SET webhook_secret = 'whsec_' || encode(
+    sha256((gen_random_uuid()::TEXT || gen_random_uuid()::TEXT)::BYTEA),
+    'hex'
+)
+WHERE webhook_url IS NOT NULL;
+
+ALTER TABLE watchlist_entry ADD CONSTRAINT watchlist_entry_webhook_secret_check
+    CHECK (webhook_url IS NULL OR webhook_secret IS NOT NULL);